@@ -10,10 +10,20 @@ pub mod rad_clone;
 pub mod rad_comment;
 #[path = "commands/delegate.rs"]
 pub mod rad_delegate;
+#[path = "commands/discuss.rs"]
+pub mod rad_discuss;
 #[path = "commands/edit.rs"]
 pub mod rad_edit;
+#[path = "commands/export.rs"]
+pub mod rad_export;
+#[path = "commands/fork.rs"]
+pub mod rad_fork;
 #[path = "commands/help.rs"]
 pub mod rad_help;
+#[path = "commands/import.rs"]
+pub mod rad_import;
+#[path = "commands/inbox.rs"]
+pub mod rad_inbox;
 #[path = "commands/init.rs"]
 pub mod rad_init;
 #[path = "commands/inspect.rs"]
@@ -24,18 +34,30 @@ pub mod rad_issue;
 pub mod rad_ls;
 #[path = "commands/merge.rs"]
 pub mod rad_merge;
+#[path = "commands/milestone.rs"]
+pub mod rad_milestone;
+#[path = "commands/mirror.rs"]
+pub mod rad_mirror;
 #[path = "commands/patch.rs"]
 pub mod rad_patch;
 #[path = "commands/path.rs"]
 pub mod rad_path;
 #[path = "commands/push.rs"]
 pub mod rad_push;
+#[path = "commands/release.rs"]
+pub mod rad_release;
 #[path = "commands/review.rs"]
 pub mod rad_review;
 #[path = "commands/rm.rs"]
 pub mod rad_rm;
 #[path = "commands/self.rs"]
 pub mod rad_self;
+#[path = "commands/stats.rs"]
+pub mod rad_stats;
+#[path = "commands/storage.rs"]
+pub mod rad_storage;
+#[path = "commands/sync.rs"]
+pub mod rad_sync;
 #[path = "commands/track.rs"]
 pub mod rad_track;
 #[path = "commands/unassign.rs"]
@@ -3,6 +3,8 @@ use std::ffi::OsString;
 
 use anyhow::anyhow;
 
+use radicle::crypto::mnemonic::Mnemonic;
+use radicle::crypto::shamir::Share;
 use radicle::crypto::ssh;
 use radicle::{profile, Profile};
 
@@ -18,21 +20,50 @@ pub const HELP: Help = Help {
 Usage
 
     rad auth [<options>...]
+    rad auth backup [<options>...]
+    rad auth recover [<options>...]
+    rad auth split [--threshold <n>] [--shares <n>] [<options>...]
+    rad auth combine [<options>...]
 
     A passphrase may be given via the environment variable `RAD_PASSPHRASE` or
     via the standard input stream if `--stdin` is used. Using one of these
     methods disables the passphrase prompt.
 
+    The `backup` command prints your secret key as a 24-word recovery phrase.
+    Write it down and keep it somewhere safe: anyone with the phrase has full
+    control over your radicle identity. The `recover` command re-creates your
+    profile from such a phrase, on a device that has no profile yet.
+
+    The `split` command splits your secret key into shares, any `threshold`
+    of which are enough to recover it. Give each share to a different person
+    or keep them in different places. The `combine` command re-creates your
+    profile from enough shares, on a device that has no profile yet.
+
 Options
 
+    --threshold <n>         Number of shares needed to recover the key (default: 2)
+    --shares <n>            Number of shares to create (default: 3)
     --stdin                 Read passphrase from stdin (default: false)
     --help                  Print help
 "#,
 };
 
+#[derive(Debug, Default, PartialEq, Eq)]
+pub enum Operation {
+    #[default]
+    Authenticate,
+    Backup,
+    Recover,
+    Split,
+    Combine,
+}
+
 #[derive(Debug)]
 pub struct Options {
+    pub op: Operation,
     pub stdin: bool,
+    pub threshold: u8,
+    pub shares: u8,
 }
 
 impl Args for Options {
@@ -40,6 +71,9 @@ impl Args for Options {
         use lexopt::prelude::*;
 
         let mut stdin = false;
+        let mut threshold = 2;
+        let mut shares = 3;
+        let mut op: Option<Operation> = None;
         let mut parser = lexopt::Parser::from_args(args);
 
         while let Some(arg) = parser.next()? {
@@ -47,21 +81,63 @@ impl Args for Options {
                 Long("stdin") => {
                     stdin = true;
                 }
+                Long("threshold") => {
+                    threshold = parser.value()?.parse()?;
+                }
+                Long("shares") => {
+                    shares = parser.value()?.parse()?;
+                }
                 Long("help") => {
                     return Err(Error::Help.into());
                 }
+                Value(val) if op.is_none() => match val.to_string_lossy().as_ref() {
+                    "backup" => op = Some(Operation::Backup),
+                    "recover" => op = Some(Operation::Recover),
+                    "split" => op = Some(Operation::Split),
+                    "combine" => op = Some(Operation::Combine),
+
+                    unknown => anyhow::bail!("unknown operation '{}'", unknown),
+                },
                 _ => return Err(anyhow::anyhow!(arg.unexpected())),
             }
         }
 
-        Ok((Options { stdin }, vec![]))
+        Ok((
+            Options {
+                op: op.unwrap_or_default(),
+                stdin,
+                threshold,
+                shares,
+            },
+            vec![],
+        ))
     }
 }
 
 pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
-    match ctx.profile() {
-        Ok(profile) => authenticate(&profile, options),
-        Err(_) => init(options),
+    match options.op {
+        Operation::Authenticate => match ctx.profile() {
+            Ok(profile) => authenticate(&profile, options),
+            Err(_) => init(options),
+        },
+        Operation::Backup => backup(&ctx.profile()?, options),
+        Operation::Recover => match ctx.profile() {
+            Ok(profile) => anyhow::bail!(
+                "a profile already exists at {:?} for {}",
+                profile.home(),
+                profile.id()
+            ),
+            Err(_) => recover(options),
+        },
+        Operation::Split => split(&ctx.profile()?, options),
+        Operation::Combine => match ctx.profile() {
+            Ok(profile) => anyhow::bail!(
+                "a profile already exists at {:?} for {}",
+                profile.home(),
+                profile.id()
+            ),
+            Err(_) => combine(options),
+        },
     }
 }
 
@@ -133,3 +209,101 @@ pub fn authenticate(profile: &Profile, options: Options) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+pub fn backup(profile: &Profile, options: Options) -> anyhow::Result<()> {
+    let passphrase = term::read_passphrase(options.stdin, false)?;
+    let mnemonic = profile.mnemonic(passphrase)?;
+
+    term::warning("Anyone with this phrase has full control over your radicle identity.");
+    term::blank();
+
+    let words = mnemonic.words().collect::<Vec<_>>();
+    for (i, line) in words.chunks(6).enumerate() {
+        let line = line
+            .iter()
+            .enumerate()
+            .map(|(j, w)| format!("{:>2}. {:<10}", i * 6 + j + 1, w))
+            .collect::<String>();
+        term::indented(line.trim_end());
+    }
+    term::blank();
+    term::tip!(
+        "To restore your profile from this phrase, run {}.",
+        term::format::secondary("`rad auth recover`")
+    );
+
+    Ok(())
+}
+
+pub fn recover(options: Options) -> anyhow::Result<()> {
+    term::headline("Recovering your 🌱 profile and identity");
+
+    let home = profile::home()?;
+    let phrase = term::secret_input_with_prompt("Recovery phrase");
+    let mnemonic = phrase
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .parse::<Mnemonic>()?;
+    let passphrase = term::read_passphrase(options.stdin, true)?;
+    let profile = Profile::recover(home, &mnemonic, passphrase)?;
+
+    term::success!(
+        "Profile {} recovered.",
+        term::format::highlight(profile.id().to_string())
+    );
+
+    Ok(())
+}
+
+pub fn split(profile: &Profile, options: Options) -> anyhow::Result<()> {
+    let passphrase = term::read_passphrase(options.stdin, false)?;
+    let shares = profile.split(passphrase, options.threshold, options.shares)?;
+
+    term::warning(&format!(
+        "Anyone with {} of these shares has full control over your radicle identity.",
+        options.threshold
+    ));
+    term::blank();
+
+    for share in shares {
+        term::indented(format!("{:>3}. {}", share.index(), share));
+    }
+    term::blank();
+    term::tip!(
+        "To restore your profile from these shares, run {}.",
+        term::format::secondary("`rad auth combine`")
+    );
+
+    Ok(())
+}
+
+pub fn combine(options: Options) -> anyhow::Result<()> {
+    term::headline("Recovering your 🌱 profile and identity");
+
+    let home = profile::home()?;
+    let mut shares: Vec<Share> = Vec::new();
+    loop {
+        let prompt = match shares.first() {
+            Some(first) => format!("Share {} of {}", shares.len() + 1, first.threshold()),
+            None => String::from("Share 1"),
+        };
+        let share = term::secret_input_with_prompt(&prompt)
+            .trim()
+            .parse::<Share>()?;
+        shares.push(share);
+
+        if shares.len() >= shares[0].threshold() as usize {
+            break;
+        }
+    }
+    let passphrase = term::read_passphrase(options.stdin, true)?;
+    let profile = Profile::combine(home, &shares, passphrase)?;
+
+    term::success!(
+        "Profile {} recovered.",
+        term::format::highlight(profile.id().to_string())
+    );
+
+    Ok(())
+}
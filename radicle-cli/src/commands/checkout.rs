@@ -7,6 +7,7 @@ use anyhow::Context as _;
 use radicle::prelude::*;
 use radicle::storage::WriteStorage;
 
+use crate::commands::rad_init;
 use crate::project;
 use crate::terminal as term;
 use crate::terminal::args::{Args, Error, Help};
@@ -22,6 +23,8 @@ Usage
 
 Options
 
+    --hooks         Install git hooks that keep the working copy in sync with the network
+    --worktree      Share the object database of the storage repository instead of copying it
     --no-confirm    Don't ask for confirmation during checkout
     --help          Print help
 "#,
@@ -29,6 +32,8 @@ Options
 
 pub struct Options {
     pub id: Id,
+    pub hooks: bool,
+    pub worktree: bool,
 }
 
 impl Args for Options {
@@ -38,9 +43,17 @@ impl Args for Options {
 
         let mut parser = lexopt::Parser::from_args(args);
         let mut id = None;
+        let mut hooks = false;
+        let mut worktree = false;
 
         while let Some(arg) = parser.next()? {
             match arg {
+                Long("hooks") => {
+                    hooks = true;
+                }
+                Long("worktree") => {
+                    worktree = true;
+                }
                 Long("no-confirm") => {
                     // Ignored for now.
                 }
@@ -58,6 +71,8 @@ impl Args for Options {
         Ok((
             Options {
                 id: id.ok_or_else(|| anyhow!("a project id to checkout must be provided"))?,
+                hooks,
+                worktree,
             },
             vec![],
         ))
@@ -97,7 +112,12 @@ pub fn execute(options: Options, profile: &Profile) -> anyhow::Result<PathBuf> {
     ));
 
     let spinner = term::spinner("Performing checkout...");
-    let repo = match radicle::rad::checkout(options.id, profile.id(), path.clone(), &storage) {
+    let checkout = if options.worktree {
+        radicle::rad::checkout_worktree(options.id, profile.id(), path.clone(), &storage)
+    } else {
+        radicle::rad::checkout(options.id, profile.id(), path.clone(), &storage)
+    };
+    let repo = match checkout {
         Ok(repo) => repo,
         Err(err) => {
             spinner.failed();
@@ -108,6 +128,10 @@ pub fn execute(options: Options, profile: &Profile) -> anyhow::Result<PathBuf> {
     };
     spinner.finish();
 
+    if options.hooks {
+        rad_init::setup_hooks(&repo)?;
+    }
+
     let remotes = doc
         .delegates
         .into_iter()
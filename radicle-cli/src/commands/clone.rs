@@ -12,6 +12,7 @@ use radicle::rad;
 use radicle::storage::WriteStorage;
 
 use crate::commands::rad_checkout::setup_remotes;
+use crate::commands::rad_init;
 use crate::project;
 use crate::terminal as term;
 use crate::terminal::args::{Args, Error, Help};
@@ -28,6 +29,7 @@ Usage
 
 Options
 
+    --hooks         Install git hooks that keep the working copy in sync with the network
     --no-confirm    Don't ask for confirmation during clone
     --help          Print help
 
@@ -38,6 +40,7 @@ Options
 pub struct Options {
     id: Id,
     interactive: Interactive,
+    hooks: bool,
 }
 
 impl Args for Options {
@@ -47,9 +50,13 @@ impl Args for Options {
         let mut parser = lexopt::Parser::from_args(args);
         let mut id: Option<Id> = None;
         let mut interactive = Interactive::Yes;
+        let mut hooks = false;
 
         while let Some(arg) = parser.next()? {
             match arg {
+                Long("hooks") => {
+                    hooks = true;
+                }
                 Long("no-confirm") => {
                     interactive = Interactive::No;
                 }
@@ -70,15 +77,27 @@ impl Args for Options {
             anyhow!("to clone, a radicle id must be provided; see `rad clone --help`")
         })?;
 
-        Ok((Options { id, interactive }, vec![]))
+        Ok((
+            Options {
+                id,
+                interactive,
+                hooks,
+            },
+            vec![],
+        ))
     }
 }
 
 pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
-    clone(options.id, options.interactive, ctx)
+    clone(options.id, options.interactive, options.hooks, ctx)
 }
 
-pub fn clone(id: Id, _interactive: Interactive, ctx: impl term::Context) -> anyhow::Result<()> {
+pub fn clone(
+    id: Id,
+    _interactive: Interactive,
+    hooks: bool,
+    ctx: impl term::Context,
+) -> anyhow::Result<()> {
     let profile = ctx.profile()?;
     let mut node = radicle::node::connect(profile.socket())?;
     let signer = term::signer(&profile)?;
@@ -99,6 +118,9 @@ pub fn clone(id: Id, _interactive: Interactive, ctx: impl term::Context) -> anyh
 
     let path = Path::new(proj.name());
     let repo = rad::checkout(id, profile.id(), path, &profile.storage)?;
+    if hooks {
+        rad_init::setup_hooks(&repo)?;
+    }
     let delegates = doc
         .delegates
         .iter()
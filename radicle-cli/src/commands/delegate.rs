@@ -1,4 +1,5 @@
 use std::ffi::OsString;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use anyhow::{anyhow, Context as _};
@@ -15,6 +16,8 @@ mod add;
 mod list;
 #[path = "delegate/remove.rs"]
 mod remove;
+#[path = "delegate/rotate.rs"]
+mod rotate;
 
 pub const HELP: Help = Help {
     name: "delegate",
@@ -25,12 +28,19 @@ Usage
 
     rad delegate (add|remove) <public key> [--to <id>]
     rad delegate list [<id>]
+    rad delegate rotate <home> [--to <id>]
 
     The `add` and `remove` commands are limited to managing delegates
     where the `threshold` for the quorum is exactly `1`. Otherwise,
     the verification of the document will not be able to gather enough
     signatures to pass the quorum.
 
+    The `rotate` command replaces your delegate key with the key of the
+    profile found at `<home>`, eg. one created with `RAD_HOME=<home> rad auth`.
+    The new key signs the updated document too, proving it is held. Your
+    refs are then copied over to the new key, and signed with it. The same
+    threshold limitation applies.
+
 Options
 
     --help              Print help
@@ -41,6 +51,7 @@ Options
 pub enum OperationName {
     Add,
     Remove,
+    Rotate,
     #[default]
     List,
 }
@@ -50,6 +61,7 @@ pub enum Operation {
     Add { id: Option<Id>, key: PublicKey },
     Remove { id: Option<Id>, key: PublicKey },
     List { id: Option<Id> },
+    Rotate { id: Option<Id>, home: PathBuf },
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -65,6 +77,7 @@ impl Args for Options {
         let mut id: Option<Id> = None;
         let mut op: Option<OperationName> = None;
         let mut key: Option<PublicKey> = None;
+        let mut home: Option<PathBuf> = None;
 
         while let Some(arg) = parser.next()? {
             match arg {
@@ -78,6 +91,7 @@ impl Args for Options {
                     "a" | "add" => op = Some(OperationName::Add),
                     "r" | "remove" => op = Some(OperationName::Remove),
                     "l" | "list" => op = Some(OperationName::List),
+                    "rotate" => op = Some(OperationName::Rotate),
 
                     unknown => anyhow::bail!("unknown operation '{}'", unknown),
                 },
@@ -92,6 +106,9 @@ impl Args for Options {
                                 return Err(anyhow!("invalid Public Key '{}'", val));
                             }
                         }
+                        Some(OperationName::Rotate) => {
+                            home = Some(PathBuf::from(val.into_owned()));
+                        }
                         Some(OperationName::List) => {
                             if let Ok(val) = Id::from_str(&val) {
                                 id = Some(val);
@@ -116,6 +133,10 @@ impl Args for Options {
                 id,
                 key: key.ok_or_else(|| anyhow!("a delegate key must be provided"))?,
             },
+            OperationName::Rotate => Operation::Rotate {
+                id,
+                home: home.ok_or_else(|| anyhow!("the home of the new key must be provided"))?,
+            },
         };
 
         Ok((Options { op }, vec![]))
@@ -130,6 +151,7 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
         Operation::Add { id, key } => add::run(&profile, storage, get_id(id)?, key)?,
         Operation::Remove { id, key } => remove::run(&profile, storage, get_id(id)?, &key)?,
         Operation::List { id } => list::run(&profile, storage, get_id(id)?)?,
+        Operation::Rotate { id, home } => rotate::run(&profile, get_id(id)?, &home)?,
     }
 
     Ok(())
@@ -1,7 +1,7 @@
 use anyhow::Context as _;
 use radicle::{
     prelude::Id,
-    storage::{WriteRepository as _, WriteStorage},
+    storage::{git::Repository, WriteStorage},
     Profile,
 };
 use radicle_crypto::PublicKey;
@@ -10,7 +10,7 @@ use crate::terminal as term;
 
 pub fn run<S>(profile: &Profile, storage: &S, id: Id, key: &PublicKey) -> anyhow::Result<()>
 where
-    S: WriteStorage,
+    S: WriteStorage<Repository = Repository>,
 {
     let signer = term::signer(profile)?;
     let me = signer.public_key();
@@ -0,0 +1,24 @@
+use std::path::Path;
+
+use radicle::prelude::{Did, Id};
+use radicle::profile::Home;
+use radicle::Profile;
+
+use crate::terminal as term;
+
+pub fn run(profile: &Profile, id: Id, home: &Path) -> anyhow::Result<()> {
+    let new = Profile::open(Home::new(home))?;
+    let signer = term::signer(profile)?;
+    let new_signer = term::signer(&new)?;
+
+    radicle::rad::rotate(id, &signer, &new_signer, &profile.storage)?;
+
+    term::info!(
+        "Rotated delegate '{}' to '{}'",
+        Did::from(*signer.public_key()),
+        Did::from(*new_signer.public_key())
+    );
+    term::success!("Update successful!");
+
+    Ok(())
+}
@@ -0,0 +1,199 @@
+use std::ffi::OsString;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context as _};
+
+use crate::terminal as term;
+use crate::terminal::args::{Args, Error, Help};
+use crate::terminal::patch::Comment;
+
+use radicle::cob::discussion::{Discussion, DiscussionId, Discussions};
+use radicle::storage::WriteStorage;
+
+pub const HELP: Help = Help {
+    name: "discuss",
+    description: "Manage discussions",
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad discuss
+    rad discuss list
+    rad discuss open --title <title> [-m <text>]
+    rad discuss comment <id> [-m <text>]
+    rad discuss show <id>
+    rad discuss delete <id>
+
+    Discussions are titled threads that aren't attached to code, for project
+    conversations that are neither issues nor patches.
+
+Options
+
+    -m, --message               Discussion or comment message
+        --help                  Print help
+"#,
+};
+
+#[derive(Default, Debug, PartialEq, Eq)]
+pub enum OperationName {
+    Comment,
+    Delete,
+    #[default]
+    List,
+    Open,
+    Show,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Operation {
+    Comment { id: DiscussionId, message: Comment },
+    Delete { id: DiscussionId },
+    List,
+    Open { title: String, message: Comment },
+    Show { id: DiscussionId },
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub op: Operation,
+}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+        let mut op: Option<OperationName> = None;
+        let mut id: Option<DiscussionId> = None;
+        let mut title: Option<String> = None;
+        let mut message = Comment::default();
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                Long("title") if op == Some(OperationName::Open) => {
+                    title = Some(parser.value()?.to_string_lossy().into());
+                }
+                Long("message") | Short('m')
+                    if op == Some(OperationName::Open) || op == Some(OperationName::Comment) =>
+                {
+                    let txt: String = parser.value()?.to_string_lossy().into();
+                    message.append(&txt);
+                }
+                Value(val) if op.is_none() => match val.to_string_lossy().as_ref() {
+                    "c" | "comment" => op = Some(OperationName::Comment),
+                    "d" | "delete" => op = Some(OperationName::Delete),
+                    "l" | "list" => op = Some(OperationName::List),
+                    "o" | "open" => op = Some(OperationName::Open),
+                    "s" | "show" => op = Some(OperationName::Show),
+
+                    unknown => anyhow::bail!("unknown operation '{}'", unknown),
+                },
+                Value(val) if op.is_some() && id.is_none() => {
+                    let val = val
+                        .to_str()
+                        .ok_or_else(|| anyhow!("discussion id specified is not UTF-8"))?;
+
+                    id = Some(
+                        DiscussionId::from_str(val)
+                            .map_err(|_| anyhow!("invalid discussion id '{}'", val))?,
+                    );
+                }
+                _ => {
+                    return Err(anyhow!(arg.unexpected()));
+                }
+            }
+        }
+
+        let require_id = || id.ok_or_else(|| anyhow!("a discussion id must be provided"));
+        let op = match op.unwrap_or_default() {
+            OperationName::Comment => Operation::Comment {
+                id: require_id()?,
+                message,
+            },
+            OperationName::Delete => Operation::Delete { id: require_id()? },
+            OperationName::List => Operation::List,
+            OperationName::Open => Operation::Open {
+                title: title.ok_or_else(|| anyhow!("a discussion title must be provided"))?,
+                message,
+            },
+            OperationName::Show => Operation::Show { id: require_id()? },
+        };
+
+        Ok((Options { op }, vec![]))
+    }
+}
+
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let profile = ctx.profile()?;
+    let signer = term::signer(&profile)?;
+    let storage = &profile.storage;
+    let (_, id) = radicle::rad::cwd()?;
+    let repo = storage.repository(id)?;
+    let mut discussions = Discussions::open(*signer.public_key(), &repo)?;
+
+    match options.op {
+        Operation::Comment { id, message } => {
+            let mut discussion = discussions.get_mut(&id)?;
+            let message = message.get("Enter a comment...");
+            if message.is_empty() {
+                return Ok(());
+            }
+            let (root, _) = discussion.root().expect("root comment always exists");
+            let root = *root;
+
+            discussion.comment(message, root, &signer)?;
+        }
+        Operation::Delete { id } => {
+            discussions.remove(&id)?;
+        }
+        Operation::List => {
+            let mut t = term::Table::new(term::table::TableOptions::default());
+            for result in discussions.all()? {
+                let (id, discussion, _) = result?;
+
+                t.push([
+                    id.to_string(),
+                    format!("{:?}", discussion.title()),
+                    format!("{} comment(s)", discussion.comments().count()),
+                ]);
+            }
+            t.render();
+        }
+        Operation::Open { title, message } => {
+            let message = message.get("Enter a description...");
+            let discussion = discussions.create(title, message, &signer)?;
+
+            term::success!(
+                "Discussion {} opened ({})",
+                term::format::highlight(discussion.title()),
+                term::format::dim(discussion.id())
+            );
+        }
+        Operation::Show { id } => {
+            let discussion = discussions
+                .get(&id)?
+                .context("No discussion with the given ID exists")?;
+            show_discussion(&discussion)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn show_discussion(discussion: &Discussion) -> anyhow::Result<()> {
+    term::info!("title: {}", discussion.title());
+
+    for (_, comment) in discussion.comments() {
+        term::blank();
+        term::info!(
+            "{} {}",
+            term::format::node(&comment.author()),
+            term::format::dim(term::format::timestamp(&comment.timestamp()))
+        );
+        term::info!("{}", comment.body());
+    }
+    Ok(())
+}
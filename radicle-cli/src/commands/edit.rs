@@ -4,7 +4,7 @@ use std::str::FromStr;
 use anyhow::{anyhow, Context as _};
 
 use radicle::identity::Id;
-use radicle::storage::{ReadStorage, WriteRepository, WriteStorage};
+use radicle::storage::{ReadStorage, WriteStorage};
 
 use crate::terminal as term;
 use crate::terminal::args::{Args, Error, Help};
@@ -80,7 +80,18 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
     let payload = serde_json::to_string_pretty(&project.payload)?;
     match term::Editor::new().edit(&payload)? {
         Some(updated_payload) => {
-            project.payload = serde_json::from_str(&updated_payload)?;
+            let mut updated = project.clone();
+            updated.payload = serde_json::from_str(&updated_payload)?;
+
+            let diff = project.diff(&updated);
+            if diff.is_empty() {
+                term::info!("Nothing to update");
+                return Ok(());
+            }
+            term::identity::diff(&diff);
+            term::blank();
+
+            project = updated;
             project.sign(&signer).and_then(|(_, sig)| {
                 project.update(
                     signer.public_key(),
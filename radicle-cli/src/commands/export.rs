@@ -0,0 +1,91 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context as _};
+
+use radicle::identity::Id;
+
+use crate::terminal as term;
+use crate::terminal::args::{Args, Error, Help};
+
+pub const HELP: Help = Help {
+    name: "export",
+    description: "Export a project to a bundle file",
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad export <file> [--repo <id>] [<option>...]
+
+    Exports a project, including its identity, signed refs and
+    collaborative objects, to a single bundle file. The bundle
+    can be imported into another storage with `rad import`.
+
+Options
+
+    --repo <id>   The project to export (default: cwd)
+    --help        Print help
+"#,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Options {
+    pub id: Option<Id>,
+    pub file: PathBuf,
+}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+        let mut id: Option<Id> = None;
+        let mut file: Option<PathBuf> = None;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                Long("repo") => {
+                    id = Some(parser.value()?.parse::<Id>()?);
+                }
+                Value(val) if file.is_none() => {
+                    file = Some(PathBuf::from(val));
+                }
+                _ => return Err(anyhow!(arg.unexpected())),
+            }
+        }
+
+        Ok((
+            Options {
+                id,
+                file: file.ok_or_else(|| anyhow!("a bundle file must be provided"))?,
+            },
+            vec![],
+        ))
+    }
+}
+
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let profile = ctx.profile()?;
+    let id = options
+        .id
+        .or_else(|| radicle::rad::cwd().ok().map(|(_, id)| id))
+        .context("Couldn't get ID from either command line or cwd")?;
+
+    let spinner = term::spinner(format!(
+        "Exporting {} to {}..",
+        term::format::highlight(id),
+        options.file.display()
+    ));
+    match profile.storage.export(id, &options.file) {
+        Ok(()) => spinner.finish(),
+        Err(err) => {
+            spinner.failed();
+            return Err(err.into());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use std::ffi::OsString;
+
+use anyhow::{anyhow, Context as _};
+
+use radicle::identity::Id;
+use radicle::rad;
+
+use crate::terminal as term;
+use crate::terminal::args::{Args, Error, Help};
+
+pub const HELP: Help = Help {
+    name: "fork",
+    description: "Create a fork of a project",
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad fork [<id>] [<option>...]
+
+    Creates your own tree of a project in local storage, from the canonical
+    head of the project. The project must already be in local storage,
+    eg. by tracking it.
+
+Options
+
+    --help              Print help
+"#,
+};
+
+#[derive(Debug)]
+pub struct Options {
+    pub id: Option<Id>,
+}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+        let mut id: Option<Id> = None;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Value(val) if id.is_none() => {
+                    let val = val.to_string_lossy();
+
+                    if let Ok(val) = Id::from_human(&val) {
+                        id = Some(val);
+                    } else {
+                        return Err(anyhow!("invalid ID '{}'", val));
+                    }
+                }
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                _ => {
+                    return Err(anyhow!(arg.unexpected()));
+                }
+            }
+        }
+
+        Ok((Options { id }, vec![]))
+    }
+}
+
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let id = options
+        .id
+        .or_else(|| rad::cwd().ok().map(|(_, id)| id))
+        .context("current directory is not a git repository; please supply an `<id>`")?;
+    let profile = ctx.profile()?;
+    let signer = term::signer(&profile)?;
+
+    rad::fork(id, &signer, &profile.storage)?;
+
+    term::success!(
+        "Forked project {} under {}",
+        term::format::highlight(id),
+        term::format::node(profile.id())
+    );
+
+    Ok(())
+}
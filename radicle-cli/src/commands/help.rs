@@ -16,19 +16,30 @@ const COMMANDS: &[Help] = &[
     rad_auth::HELP,
     rad_checkout::HELP,
     rad_clone::HELP,
+    rad_discuss::HELP,
     rad_edit::HELP,
+    rad_export::HELP,
+    rad_fork::HELP,
     rad_help::HELP,
+    rad_import::HELP,
+    rad_inbox::HELP,
     rad_init::HELP,
     rad_inspect::HELP,
     rad_issue::HELP,
     rad_ls::HELP,
     rad_merge::HELP,
+    rad_milestone::HELP,
+    rad_mirror::HELP,
     rad_patch::HELP,
     rad_path::HELP,
     rad_push::HELP,
+    rad_release::HELP,
     rad_review::HELP,
     rad_rm::HELP,
     rad_self::HELP,
+    rad_stats::HELP,
+    rad_storage::HELP,
+    rad_sync::HELP,
     rad_track::HELP,
     rad_untrack::HELP,
 ];
@@ -0,0 +1,76 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+
+use crate::terminal as term;
+use crate::terminal::args::{Args, Error, Help};
+
+pub const HELP: Help = Help {
+    name: "import",
+    description: "Import a project from a bundle file",
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad import <file> [<option>...]
+
+    Imports a project from a bundle file created with `rad export`.
+    The project's signed refs are verified before it is added to storage.
+
+Options
+
+    --help   Print help
+"#,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Options {
+    pub file: PathBuf,
+}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+        let mut file: Option<PathBuf> = None;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                Value(val) if file.is_none() => {
+                    file = Some(PathBuf::from(val));
+                }
+                _ => return Err(anyhow!(arg.unexpected())),
+            }
+        }
+
+        Ok((
+            Options {
+                file: file.ok_or_else(|| anyhow!("a bundle file must be provided"))?,
+            },
+            vec![],
+        ))
+    }
+}
+
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let profile = ctx.profile()?;
+    let spinner = term::spinner(format!("Importing {}..", options.file.display()));
+
+    match profile.storage.import(&options.file) {
+        Ok(id) => {
+            spinner.finish();
+            term::success!("Imported project {}", term::format::highlight(id));
+        }
+        Err(err) => {
+            spinner.failed();
+            return Err(err.into());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use std::ffi::OsString;
+
+use anyhow::anyhow;
+
+use crate::terminal as term;
+use crate::terminal::args::{Args, Error, Help};
+
+use radicle::cob::inbox::{Inbox, NotificationId};
+use radicle::storage::{ReadStorage, WriteStorage};
+
+pub const HELP: Help = Help {
+    name: "inbox",
+    description: "Manage notifications",
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad inbox
+    rad inbox list
+    rad inbox clear [<id>]
+
+    Notifications are collected from the issues and patches of the projects in
+    storage: comments mentioning you by key or alias, patch revisions proposed in
+    projects you're a delegate of, and verdicts on your patches.
+
+    Clearing a notification removes it from the inbox for good. Without an id,
+    all notifications are cleared.
+
+Options
+
+    --help      Print help
+"#,
+};
+
+#[derive(Default, Debug, PartialEq, Eq)]
+pub enum OperationName {
+    Clear,
+    #[default]
+    List,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Operation {
+    Clear { id: Option<NotificationId> },
+    List,
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub op: Operation,
+}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+        let mut op: Option<OperationName> = None;
+        let mut id: Option<NotificationId> = None;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                Value(val) if op.is_none() => match val.to_string_lossy().as_ref() {
+                    "l" | "list" => op = Some(OperationName::List),
+                    "c" | "clear" => op = Some(OperationName::Clear),
+
+                    unknown => anyhow::bail!("unknown operation '{}'", unknown),
+                },
+                Value(val) if op == Some(OperationName::Clear) && id.is_none() => {
+                    let val = val.to_string_lossy();
+                    let val = val
+                        .parse()
+                        .map_err(|_| anyhow!("invalid notification id '{}'", val))?;
+
+                    id = Some(val);
+                }
+                _ => {
+                    return Err(anyhow!(arg.unexpected()));
+                }
+            }
+        }
+
+        let op = match op.unwrap_or_default() {
+            OperationName::Clear => Operation::Clear { id },
+            OperationName::List => Operation::List,
+        };
+
+        Ok((Options { op }, vec![]))
+    }
+}
+
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let profile = ctx.profile()?;
+    let inbox = Inbox::open(profile.paths().inbox())?;
+
+    match options.op {
+        Operation::Clear { id: Some(id) } => {
+            if !inbox.clear(id)? {
+                anyhow::bail!("notification {id} not found");
+            }
+        }
+        Operation::Clear { id: None } => {
+            let count = inbox.clear_all()?;
+            term::success!("Cleared {count} notification(s)");
+        }
+        Operation::List => {
+            let storage = &profile.storage;
+            let alias = profile.config.alias.as_deref();
+
+            for id in storage.inventory()? {
+                let repo = storage.repository(id)?;
+
+                if let Err(err) = inbox.sync(&repo, profile.id(), alias) {
+                    log::warn!("Failed to sync inbox with {id}: {err}");
+                }
+            }
+
+            let mut t = term::Table::new(term::table::TableOptions::default());
+            for n in inbox.all()? {
+                t.push([
+                    n.id.to_string(),
+                    n.kind.to_string(),
+                    n.typename.to_string(),
+                    term::format::cob(&n.object),
+                    term::format::node(&n.author),
+                    term::format::timestamp(&n.timestamp),
+                ]);
+            }
+            t.render();
+        }
+    }
+
+    Ok(())
+}
@@ -7,6 +7,7 @@ use std::path::PathBuf;
 use anyhow::{anyhow, bail, Context as _};
 
 use radicle::crypto::ssh;
+use radicle::git::hooks;
 use radicle::git::RefString;
 use radicle::node::NodeId;
 
@@ -33,6 +34,7 @@ Options
     --default-branch     The default branch of the project
     --set-upstream, -u   Setup the upstream of the default branch
     --setup-signing      Setup the radicle key as a signing key for this repository
+    --hooks              Install git hooks that keep the working copy in sync with the network
     --no-confirm         Don't ask for confirmation during setup
     --help               Print help
 "#,
@@ -47,6 +49,7 @@ pub struct Options {
     pub interactive: Interactive,
     pub setup_signing: bool,
     pub set_upstream: bool,
+    pub hooks: bool,
 }
 
 impl Args for Options {
@@ -62,6 +65,7 @@ impl Args for Options {
         let mut interactive = Interactive::Yes;
         let mut set_upstream = false;
         let mut setup_signing = false;
+        let mut hooks = false;
 
         while let Some(arg) = parser.next()? {
             match arg {
@@ -103,6 +107,9 @@ impl Args for Options {
                 Long("setup-signing") => {
                     setup_signing = true;
                 }
+                Long("hooks") => {
+                    hooks = true;
+                }
                 Long("no-confirm") => {
                     interactive = Interactive::No;
                 }
@@ -125,6 +132,7 @@ impl Args for Options {
                 interactive,
                 set_upstream,
                 setup_signing,
+                hooks,
             },
             vec![],
         ))
@@ -229,6 +237,10 @@ pub fn init(options: Options, profile: &profile::Profile) -> anyhow::Result<()>
                 self::setup_signing(profile.id(), &repo, interactive)?;
             }
 
+            if options.hooks {
+                self::setup_hooks(&repo)?;
+            }
+
             term::blank();
             term::info!(
                 "Your project id is {}. You can show it any time by running:",
@@ -254,6 +266,17 @@ pub fn init(options: Options, profile: &profile::Profile) -> anyhow::Result<()>
     Ok(())
 }
 
+/// Install radicle git hooks in repository.
+pub fn setup_hooks(repo: &git::Repository) -> anyhow::Result<()> {
+    for path in hooks::install(repo, &hooks::Hook::ALL, false)? {
+        term::success!(
+            "Installed hook {}",
+            term::format::tertiary(path.file_name().unwrap_or_default().to_string_lossy())
+        );
+    }
+    Ok(())
+}
+
 /// Setup radicle key as commit signing key in repository.
 pub fn setup_signing(
     node_id: &NodeId,
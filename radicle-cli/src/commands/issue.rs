@@ -1,5 +1,7 @@
 #![allow(clippy::or_fun_call)]
+use std::collections::BTreeSet;
 use std::ffi::OsString;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use anyhow::{anyhow, Context as _};
@@ -8,10 +10,14 @@ use crate::terminal as term;
 use crate::terminal::args::{Args, Error, Help};
 
 use radicle::cob;
-use radicle::cob::common::{Reaction, Tag};
+use radicle::cob::common::Reaction;
 use radicle::cob::issue;
+use radicle::cob::issue::template::{self, Template};
 use radicle::cob::issue::{CloseReason, IssueId, Issues, State};
-use radicle::storage::WriteStorage;
+use radicle::cob::patch::{self, PatchId, Patches};
+use radicle::cob::store::Filter;
+use radicle::storage::git::Repository;
+use radicle::storage::{ReadRepository, WriteStorage};
 
 pub const HELP: Help = Help {
     name: "issue",
@@ -21,12 +27,24 @@ pub const HELP: Help = Help {
 Usage
 
     rad issue
+    rad issue attachment add <id> <file>... [-m <text>]
+    rad issue attachment get <id> <name-or-oid> [-o <path>]
+    rad issue attachment list <id>
     rad issue delete <id>
-    rad issue list [--assigned <key>]
-    rad issue open [--title <title>] [--description <text>]
+    rad issue list [--assigned <key>] [--author <key>] [--open | --closed]
+    rad issue open [--title <title>] [--description <text>] [--template <name>]
     rad issue react <id> [--emoji <char>]
     rad issue show <id>
-    rad issue state <id> [--closed | --open | --solved]
+    rad issue state <id> [--closed | --open | --solved | --wontfix | --duplicate <id>]
+
+    Files are attached to a new comment on the issue, and can be downloaded by name,
+    or by blob id if several attachments have the same name.
+
+    Issue templates are markdown files stored in the project under
+    `.radicle/templates/issue`, with an optional front-matter setting the title,
+    labels and assignees of the issue. When opening an issue in the editor, you
+    are offered to pick one of the project's templates, unless one is given
+    with `--template`.
 
 Options
 
@@ -34,14 +52,9 @@ Options
 "#,
 };
 
-#[derive(serde::Deserialize, serde::Serialize, Debug)]
-pub struct Metadata {
-    title: String,
-    labels: Vec<Tag>,
-}
-
 #[derive(Default, Debug, PartialEq, Eq)]
 pub enum OperationName {
+    Attachment,
     Open,
     Delete,
     #[default]
@@ -59,11 +72,30 @@ pub enum Assigned {
     Peer(cob::ActorId),
 }
 
+/// Issue attachment operation.
+#[derive(Debug, PartialEq, Eq)]
+pub enum AttachmentOperation {
+    Add {
+        files: Vec<PathBuf>,
+        message: Option<String>,
+    },
+    Get {
+        name: String,
+        output: Option<PathBuf>,
+    },
+    List,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum Operation {
+    Attachment {
+        id: IssueId,
+        op: AttachmentOperation,
+    },
     Open {
         title: Option<String>,
         description: Option<String>,
+        template: Option<String>,
     },
     Show {
         id: IssueId,
@@ -81,6 +113,8 @@ pub enum Operation {
     },
     List {
         assigned: Option<Assigned>,
+        author: Option<Assigned>,
+        state: Option<State>,
     },
 }
 
@@ -97,10 +131,16 @@ impl Args for Options {
         let mut op: Option<OperationName> = None;
         let mut id: Option<IssueId> = None;
         let mut assigned: Option<Assigned> = None;
+        let mut author: Option<Assigned> = None;
         let mut title: Option<String> = None;
         let mut reaction: Option<Reaction> = None;
         let mut description: Option<String> = None;
+        let mut template: Option<String> = None;
         let mut state: Option<State> = None;
+        let mut attachment_op: Option<String> = None;
+        let mut attachment_args: Vec<OsString> = Vec::new();
+        let mut message: Option<String> = None;
+        let mut output: Option<PathBuf> = None;
 
         while let Some(arg) = parser.next()? {
             match arg {
@@ -118,11 +158,45 @@ impl Args for Options {
                 Long("open") if op == Some(OperationName::State) => {
                     state = Some(State::Open);
                 }
+                Long("open") if op == Some(OperationName::List) => {
+                    state = Some(State::Open);
+                }
+                Long("closed") if op == Some(OperationName::List) => {
+                    state = Some(State::Closed {
+                        reason: CloseReason::Other,
+                    });
+                }
+                Long("author") if op == Some(OperationName::List) => {
+                    if let Ok(val) = parser.value() {
+                        let val = val.to_string_lossy();
+                        let Ok(peer) = cob::ActorId::from_str(&val) else {
+                            return Err(anyhow!("invalid peer ID '{}'", val));
+                        };
+                        author = Some(Assigned::Peer(peer));
+                    } else {
+                        author = Some(Assigned::Me);
+                    }
+                }
                 Long("solved") if op == Some(OperationName::State) => {
                     state = Some(State::Closed {
                         reason: CloseReason::Solved,
                     });
                 }
+                Long("wontfix") if op == Some(OperationName::State) => {
+                    state = Some(State::Closed {
+                        reason: CloseReason::WontFix,
+                    });
+                }
+                Long("duplicate") if op == Some(OperationName::State) => {
+                    let val = parser.value()?;
+                    let val = val.to_string_lossy();
+                    let of = IssueId::from_str(&val)
+                        .map_err(|_| anyhow!("invalid issue id '{}'", val))?;
+
+                    state = Some(State::Closed {
+                        reason: CloseReason::Duplicate { of },
+                    });
+                }
                 Long("emoji") if op == Some(OperationName::React) => {
                     if let Some(emoji) = parser.value()?.to_str() {
                         reaction =
@@ -132,6 +206,9 @@ impl Args for Options {
                 Long("description") if op == Some(OperationName::Open) => {
                     description = Some(parser.value()?.to_string_lossy().into());
                 }
+                Long("template") if op == Some(OperationName::Open) => {
+                    template = Some(parser.value()?.to_string_lossy().into());
+                }
                 Long("assigned") | Short('a') if assigned.is_none() => {
                     if let Ok(val) = parser.value() {
                         let val = val.to_string_lossy();
@@ -143,7 +220,14 @@ impl Args for Options {
                         assigned = Some(Assigned::Me);
                     }
                 }
+                Long("message") | Short('m') if op == Some(OperationName::Attachment) => {
+                    message = Some(parser.value()?.to_string_lossy().into());
+                }
+                Long("output") | Short('o') if op == Some(OperationName::Attachment) => {
+                    output = Some(parser.value()?.into());
+                }
                 Value(val) if op.is_none() => match val.to_string_lossy().as_ref() {
+                    "a" | "attachment" => op = Some(OperationName::Attachment),
                     "c" | "show" => op = Some(OperationName::Show),
                     "d" | "delete" => op = Some(OperationName::Delete),
                     "l" | "list" => op = Some(OperationName::List),
@@ -153,6 +237,12 @@ impl Args for Options {
 
                     unknown => anyhow::bail!("unknown operation '{}'", unknown),
                 },
+                Value(val) if op == Some(OperationName::Attachment) && attachment_op.is_none() => {
+                    attachment_op = Some(val.to_string_lossy().into());
+                }
+                Value(val) if op == Some(OperationName::Attachment) && id.is_some() => {
+                    attachment_args.push(val);
+                }
                 Value(val) if op.is_some() => {
                     let val = val
                         .to_str()
@@ -170,7 +260,39 @@ impl Args for Options {
         }
 
         let op = match op.unwrap_or_default() {
-            OperationName::Open => Operation::Open { title, description },
+            OperationName::Attachment => {
+                let op = match attachment_op.as_deref() {
+                    Some("add") => {
+                        if attachment_args.is_empty() {
+                            anyhow::bail!("at least one file to attach must be provided");
+                        }
+                        AttachmentOperation::Add {
+                            files: attachment_args.into_iter().map(PathBuf::from).collect(),
+                            message,
+                        }
+                    }
+                    Some("get") => {
+                        let [name] = attachment_args.as_slice() else {
+                            anyhow::bail!("an attachment name or blob id must be provided");
+                        };
+                        AttachmentOperation::Get {
+                            name: name.to_string_lossy().into(),
+                            output,
+                        }
+                    }
+                    Some("list") | None => AttachmentOperation::List,
+                    Some(unknown) => anyhow::bail!("unknown attachment operation '{}'", unknown),
+                };
+                Operation::Attachment {
+                    id: id.ok_or_else(|| anyhow!("an issue id must be provided"))?,
+                    op,
+                }
+            }
+            OperationName::Open => Operation::Open {
+                title,
+                description,
+                template,
+            },
             OperationName::Show => Operation::Show {
                 id: id.ok_or_else(|| anyhow!("an issue id must be provided"))?,
             },
@@ -185,7 +307,11 @@ impl Args for Options {
             OperationName::Delete => Operation::Delete {
                 id: id.ok_or_else(|| anyhow!("an issue id to remove must be provided"))?,
             },
-            OperationName::List => Operation::List { assigned },
+            OperationName::List => Operation::List {
+                assigned,
+                author,
+                state,
+            },
         };
 
         Ok((Options { op }, vec![]))
@@ -201,17 +327,112 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
     let mut issues = Issues::open(*signer.public_key(), &repo)?;
 
     match options.op {
+        Operation::Attachment { id, op } => match op {
+            AttachmentOperation::Add { files, message } => {
+                let mut attachments = Vec::new();
+                for path in files {
+                    let name = path
+                        .file_name()
+                        .ok_or_else(|| anyhow!("invalid file path '{}'", path.display()))?
+                        .to_string_lossy()
+                        .into_owned();
+                    let content = std::fs::read(&path)
+                        .with_context(|| format!("failed to read '{}'", path.display()))?;
+
+                    cob::attachment::validate(&name, &content)?;
+                    attachments.push((name, content));
+                }
+                let message = message.unwrap_or_else(|| {
+                    let names: Vec<&str> = attachments.iter().map(|(n, _)| n.as_str()).collect();
+                    format!("Attached {}", names.join(", "))
+                });
+                let mut issue = issues.get_mut(&id)?;
+                let (root, _) = issue.root().context("issue has no description")?;
+                let root = *root;
+
+                issue.transaction("Attach", &signer, |tx| {
+                    let comment = tx.comment(message, root);
+                    tx.attach_to(comment, attachments);
+                })?;
+            }
+            AttachmentOperation::Get { name, output } => {
+                let issue = issues
+                    .get(&id)?
+                    .context("No issue with the given ID exists")?;
+                let matches = issue
+                    .comments()
+                    .flat_map(|(id, _)| issue.attachments(id))
+                    .filter(|a| a.name == name || a.oid.to_string() == name)
+                    .collect::<BTreeSet<_>>();
+                let attachment = match matches.len() {
+                    0 => anyhow::bail!("no attachment named '{}' was found", name),
+                    1 => matches.first().expect("there is one attachment"),
+                    _ => anyhow::bail!(
+                        "several attachments are named '{}', specify a blob id instead",
+                        name
+                    ),
+                };
+                let output = output.unwrap_or_else(|| PathBuf::from(&attachment.name));
+                if output.exists() {
+                    anyhow::bail!("'{}' already exists", output.display());
+                }
+                std::fs::write(&output, issues.attachment(&attachment.oid)?)?;
+
+                term::success!("Saved {} to {}", attachment.name, output.display());
+            }
+            AttachmentOperation::List => {
+                let issue = issues
+                    .get(&id)?
+                    .context("No issue with the given ID exists")?;
+
+                let mut t = term::Table::new(term::table::TableOptions::default());
+                for (comment_id, comment) in issue.comments() {
+                    for attachment in issue.attachments(comment_id) {
+                        t.push([
+                            attachment.name.clone(),
+                            attachment.oid.to_string(),
+                            term::format::node(&comment.author()),
+                        ]);
+                    }
+                }
+                t.render();
+            }
+        },
         Operation::Open {
             title: Some(title),
             description: Some(description),
+            template: name,
         } => {
-            issues.create(title, description, &[], &signer)?;
+            let template = match name {
+                Some(name) => Some(find_template(&repo, &name)?),
+                None => None,
+            };
+            let (labels, assignees) = template
+                .map(|t| (t.labels, t.assignees))
+                .unwrap_or_default();
+            let mut issue = issues.create(title, description, &labels, &signer)?;
+
+            if !assignees.is_empty() {
+                issue.assign(assignees, &signer)?;
+            }
         }
         Operation::Show { id } => {
             let issue = issues
                 .get(&id)?
                 .context("No issue with the given ID exists")?;
-            show_issue(&issue)?;
+            let patches = Patches::open(*signer.public_key(), &repo)?;
+            let mut linked = patches
+                .referencing(&issue::TYPENAME, &id)?
+                .filter_map(|r| r.ok())
+                .map(|(id, _, _)| id)
+                .collect::<BTreeSet<_>>();
+            linked.extend(
+                issue
+                    .references()
+                    .filter(|r| r.typename == *patch::TYPENAME)
+                    .map(|r| r.object_id),
+            );
+            show_issue(&issue, &linked)?;
         }
         Operation::State { id, state } => {
             let mut issue = issues.get_mut(&id)?;
@@ -223,59 +444,70 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
                 issue.react(comment_id, reaction, &signer)?;
             }
         }
-        Operation::Open { title, description } => {
-            let meta = Metadata {
-                title: title.unwrap_or("Enter a title".to_owned()),
-                labels: vec![],
+        Operation::Open {
+            title,
+            description,
+            template: name,
+        } => {
+            let template = match name {
+                Some(name) => Some(find_template(&repo, &name)?),
+                None => select_template(&repo)?,
             };
-            let yaml = serde_yaml::to_string(&meta)?;
-            let doc = format!(
-                "{}---\n\n{}",
-                yaml,
-                description.unwrap_or("Enter a description...".to_owned())
-            );
-
-            if let Some(text) = term::Editor::new().edit(&doc)? {
-                let mut meta = String::new();
-                let mut frontmatter = false;
-                let mut lines = text.lines();
-
-                while let Some(line) = lines.by_ref().next() {
-                    if line.trim() == "---" {
-                        if frontmatter {
-                            break;
-                        } else {
-                            frontmatter = true;
-                            continue;
-                        }
-                    }
-                    if frontmatter {
-                        meta.push_str(line);
-                        meta.push('\n');
-                    }
-                }
-
-                let description: String = lines.collect::<Vec<&str>>().join("\n");
-                let meta: Metadata =
-                    serde_yaml::from_str(&meta).context("failed to parse yaml front-matter")?;
+            let mut template = template.unwrap_or_else(|| Template {
+                name: String::new(),
+                title: "Enter a title".to_owned(),
+                labels: vec![],
+                assignees: vec![],
+                description: "Enter a description...".to_owned(),
+            });
+            if let Some(title) = title {
+                template.title = title;
+            }
+            if let Some(description) = description {
+                template.description = description;
+            }
 
-                issues.create(
+            if let Some(text) = term::Editor::new().edit(&template.to_text())? {
+                let meta = Template::parse(&template.name, &text)
+                    .context("failed to parse yaml front-matter")?;
+                let mut issue = issues.create(
                     &meta.title,
-                    description.trim(),
+                    &meta.description,
                     meta.labels.as_slice(),
                     &signer,
                 )?;
+
+                if !meta.assignees.is_empty() {
+                    issue.assign(meta.assignees, &signer)?;
+                }
             }
         }
-        Operation::List { assigned } => {
+        Operation::List {
+            assigned,
+            author,
+            state,
+        } => {
             let assignee = match assigned {
                 Some(Assigned::Me) => Some(*profile.id()),
                 Some(Assigned::Peer(id)) => Some(id),
                 None => None,
             };
+            let mut filter = Filter::default();
+            match author {
+                Some(Assigned::Me) => filter = filter.author(*profile.id()),
+                Some(Assigned::Peer(id)) => filter = filter.author(id),
+                None => {}
+            }
+            match state {
+                Some(State::Open) => filter = filter.state(|s| *s == State::Open),
+                Some(State::Closed { .. }) => {
+                    filter = filter.state(|s| matches!(s, State::Closed { .. }))
+                }
+                None => {}
+            }
 
             let mut t = term::Table::new(term::table::TableOptions::default());
-            for result in issues.all()? {
+            for result in issues.filter(filter)? {
                 let (id, issue, _) = result?;
                 let assigned: Vec<_> = issue.assigned().collect();
 
@@ -304,9 +536,47 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn show_issue(issue: &issue::Issue) -> anyhow::Result<()> {
+/// Get the project issue template with the given name.
+fn find_template(repo: &Repository, name: &str) -> anyhow::Result<Template> {
+    let (_, head) = repo.head()?;
+
+    template::templates(repo.raw(), head)?
+        .into_iter()
+        .find(|t| t.name == name)
+        .ok_or_else(|| anyhow!("issue template '{name}' not found"))
+}
+
+/// Prompt the user for one of the project issue templates, if there are any.
+fn select_template(repo: &Repository) -> anyhow::Result<Option<Template>> {
+    let (_, head) = repo.head()?;
+    let templates = template::templates(repo.raw(), head)?;
+
+    if templates.is_empty() {
+        return Ok(None);
+    }
+    let blank = String::from("(blank)");
+    let mut options = vec![blank.clone()];
+    options.extend(templates.iter().map(|t| t.name.clone()));
+
+    let selected = term::select_with_prompt("Issue template", &options, &blank)
+        .filter(|s| **s != blank)
+        .and_then(|name| templates.iter().find(|t| t.name == *name).cloned());
+
+    Ok(selected)
+}
+
+fn show_issue(issue: &issue::Issue, patches: &BTreeSet<PatchId>) -> anyhow::Result<()> {
     term::info!("title: {}", issue.title());
-    term::info!("state: {}", issue.state());
+    match issue.state() {
+        State::Closed { reason } => term::info!("state: closed ({reason})"),
+        State::Open => term::info!("state: open"),
+    }
+    if let Some(by) = issue.closed_by() {
+        term::info!("closed by: {by}");
+    }
+    if let Some(by) = issue.reopened_by() {
+        term::info!("reopened by: {by}");
+    }
 
     let tags: Vec<String> = issue.tags().cloned().map(|t| t.into()).collect();
     term::info!("tags: {}", tags.join(", "));
@@ -314,6 +584,9 @@ fn show_issue(issue: &issue::Issue) -> anyhow::Result<()> {
     let assignees: Vec<String> = issue.assigned().map(|a| a.to_string()).collect();
     term::info!("assignees: {}", assignees.join(", "));
 
+    let patches: Vec<String> = patches.iter().map(|p| p.to_string()).collect();
+    term::info!("patches: {}", patches.join(", "));
+
     term::info!("{}", issue.description().unwrap_or(""));
     Ok(())
 }
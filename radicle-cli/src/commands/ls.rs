@@ -3,6 +3,7 @@ use std::ffi::OsString;
 use crate::terminal as term;
 use crate::terminal::args::{Args, Error, Help};
 
+use radicle::storage::index::Index;
 use radicle::storage::{ReadRepository, WriteStorage};
 
 pub const HELP: Help = Help {
@@ -45,8 +46,12 @@ pub fn run(_options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
     let profile = ctx.profile()?;
     let storage = &profile.storage;
     let mut table = term::Table::default();
+    let projects = match Index::open_existing(profile.paths().index())? {
+        Some(index) => index.projects()?,
+        None => storage.projects()?,
+    };
 
-    storage.projects()?.into_iter().for_each(|id| {
+    projects.into_iter().for_each(|id| {
         let Ok(repo) = storage.repository(id) else { return };
         let Ok((_, head)) = repo.head() else { return };
         let Ok(proj) = repo.project_of(profile.id()) else { return };
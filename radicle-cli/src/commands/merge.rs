@@ -7,6 +7,7 @@ use anyhow::{anyhow, Context};
 
 use crate::terminal as term;
 use crate::terminal::args::{Args, Error, Help};
+use radicle::cob::issue::{Issues, State};
 use radicle::cob::patch::RevisionIx;
 use radicle::cob::patch::{Patch, PatchId, Patches};
 use radicle::git;
@@ -24,6 +25,9 @@ Usage
 
     To specify a patch to merge, use the fully qualified patch id.
 
+    Issues referenced by `Fixes:` or `Closes:` trailers in the commits of the
+    merged revision are closed.
+
 Options
 
     -i, --interactive         Ask for confirmations
@@ -268,8 +272,21 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
     // Update patch COB
     //
     // TODO: Don't allow merging the same revision twice?
+    let closes = revision.closes(&repo)?;
     patch.merge(*revision_id, head_oid.into(), &signer)?;
 
+    let issues = Issues::open(*profile.id(), &repository)?;
+    for id in closes {
+        if let Some(issue) = issues.get(&id)? {
+            if let State::Closed { .. } = issue.state() {
+                term::success!(
+                    "Issue {} closed",
+                    term::format::tertiary(term::format::cob(&id))
+                );
+            }
+        }
+    }
+
     term::success!(
         "Patch state updated, use {} to publish",
         term::format::secondary("`rad push`")
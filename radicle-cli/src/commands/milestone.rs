@@ -0,0 +1,292 @@
+use std::ffi::OsString;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context as _};
+use chrono::prelude::*;
+
+use crate::terminal as term;
+use crate::terminal::args::{Args, Error, Help};
+
+use radicle::cob::milestone::{Milestone, MilestoneId, Milestones};
+use radicle::cob::{ObjectId, Timestamp};
+use radicle::storage::WriteStorage;
+
+pub const HELP: Help = Help {
+    name: "milestone",
+    description: "Manage milestones",
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad milestone
+    rad milestone list
+    rad milestone create --title <title> [--description <text>] [--due <date>]
+    rad milestone edit <id> [--title <title>] [--description <text>] [--due <date> | --no-due]
+    rad milestone add <id> <issue-or-patch-id>...
+    rad milestone remove <id> <issue-or-patch-id>...
+    rad milestone show <id>
+    rad milestone delete <id>
+
+    Due dates are given as `YYYY-MM-DD`.
+
+Options
+
+    --help      Print help
+"#,
+};
+
+#[derive(Default, Debug, PartialEq, Eq)]
+pub enum OperationName {
+    Add,
+    Create,
+    Delete,
+    Edit,
+    #[default]
+    List,
+    Remove,
+    Show,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Operation {
+    Add {
+        id: MilestoneId,
+        items: Vec<ObjectId>,
+    },
+    Create {
+        title: String,
+        description: String,
+        due: Option<Timestamp>,
+    },
+    Delete {
+        id: MilestoneId,
+    },
+    Edit {
+        id: MilestoneId,
+        title: Option<String>,
+        description: Option<String>,
+        due: Option<Option<Timestamp>>,
+    },
+    List,
+    Remove {
+        id: MilestoneId,
+        items: Vec<ObjectId>,
+    },
+    Show {
+        id: MilestoneId,
+    },
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub op: Operation,
+}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+        let mut op: Option<OperationName> = None;
+        let mut id: Option<MilestoneId> = None;
+        let mut items: Vec<ObjectId> = Vec::new();
+        let mut title: Option<String> = None;
+        let mut description: Option<String> = None;
+        let mut due: Option<Option<Timestamp>> = None;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                Long("title")
+                    if op == Some(OperationName::Create) || op == Some(OperationName::Edit) =>
+                {
+                    title = Some(parser.value()?.to_string_lossy().into());
+                }
+                Long("description")
+                    if op == Some(OperationName::Create) || op == Some(OperationName::Edit) =>
+                {
+                    description = Some(parser.value()?.to_string_lossy().into());
+                }
+                Long("due")
+                    if op == Some(OperationName::Create) || op == Some(OperationName::Edit) =>
+                {
+                    let val = parser.value()?;
+                    due = Some(Some(parse_date(&val.to_string_lossy())?));
+                }
+                Long("no-due") if op == Some(OperationName::Edit) => {
+                    due = Some(None);
+                }
+                Value(val) if op.is_none() => match val.to_string_lossy().as_ref() {
+                    "a" | "add" => op = Some(OperationName::Add),
+                    "c" | "create" => op = Some(OperationName::Create),
+                    "d" | "delete" => op = Some(OperationName::Delete),
+                    "e" | "edit" => op = Some(OperationName::Edit),
+                    "l" | "list" => op = Some(OperationName::List),
+                    "r" | "remove" => op = Some(OperationName::Remove),
+                    "s" | "show" => op = Some(OperationName::Show),
+
+                    unknown => anyhow::bail!("unknown operation '{}'", unknown),
+                },
+                Value(val) if op.is_some() => {
+                    let val = val
+                        .to_str()
+                        .ok_or_else(|| anyhow!("object id specified is not UTF-8"))?;
+                    let oid =
+                        ObjectId::from_str(val).map_err(|_| anyhow!("invalid id '{}'", val))?;
+
+                    if id.is_none() {
+                        id = Some(oid);
+                    } else if op == Some(OperationName::Add) || op == Some(OperationName::Remove) {
+                        items.push(oid);
+                    } else {
+                        anyhow::bail!("unexpected argument '{}'", val);
+                    }
+                }
+                _ => {
+                    return Err(anyhow!(arg.unexpected()));
+                }
+            }
+        }
+
+        let require_id = || id.ok_or_else(|| anyhow!("a milestone id must be provided"));
+        let op = match op.unwrap_or_default() {
+            OperationName::Add => Operation::Add {
+                id: require_id()?,
+                items,
+            },
+            OperationName::Create => Operation::Create {
+                title: title.ok_or_else(|| anyhow!("a milestone title must be provided"))?,
+                description: description.unwrap_or_default(),
+                due: due.flatten(),
+            },
+            OperationName::Delete => Operation::Delete { id: require_id()? },
+            OperationName::Edit => Operation::Edit {
+                id: require_id()?,
+                title,
+                description,
+                due,
+            },
+            OperationName::List => Operation::List,
+            OperationName::Remove => Operation::Remove {
+                id: require_id()?,
+                items,
+            },
+            OperationName::Show => Operation::Show { id: require_id()? },
+        };
+
+        Ok((Options { op }, vec![]))
+    }
+}
+
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let profile = ctx.profile()?;
+    let signer = term::signer(&profile)?;
+    let storage = &profile.storage;
+    let (_, id) = radicle::rad::cwd()?;
+    let repo = storage.repository(id)?;
+    let mut milestones = Milestones::open(*signer.public_key(), &repo)?;
+
+    match options.op {
+        Operation::Add { id, items } => {
+            let mut milestone = milestones.get_mut(&id)?;
+            milestone.add(items, &signer)?;
+        }
+        Operation::Create {
+            title,
+            description,
+            due,
+        } => {
+            let milestone = milestones.create(title, description, due, &signer)?;
+
+            term::success!(
+                "Milestone {} created ({})",
+                term::format::highlight(milestone.title()),
+                term::format::dim(milestone.id())
+            );
+        }
+        Operation::Delete { id } => {
+            milestones.remove(&id)?;
+        }
+        Operation::Edit {
+            id,
+            title,
+            description,
+            due,
+        } => {
+            let mut milestone = milestones.get_mut(&id)?;
+
+            if title.is_some() || description.is_some() {
+                let title = title.unwrap_or_else(|| milestone.title().to_owned());
+                let description = description.unwrap_or_else(|| milestone.description().to_owned());
+
+                milestone.edit(title, description, &signer)?;
+            }
+            if let Some(due) = due {
+                milestone.set_due(due, &signer)?;
+            }
+        }
+        Operation::List => {
+            let mut t = term::Table::new(term::table::TableOptions::default());
+            for result in milestones.all()? {
+                let (id, milestone, _) = result?;
+
+                t.push([
+                    id.to_string(),
+                    format!("{:?}", milestone.title()),
+                    milestone.due().map(format_date).unwrap_or_default(),
+                    format!("{} item(s)", milestone.items().count()),
+                ]);
+            }
+            t.render();
+        }
+        Operation::Remove { id, items } => {
+            let mut milestone = milestones.get_mut(&id)?;
+            milestone.remove(items, &signer)?;
+        }
+        Operation::Show { id } => {
+            let milestone = milestones
+                .get(&id)?
+                .context("No milestone with the given ID exists")?;
+            show_milestone(&milestone)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse a `YYYY-MM-DD` date, as midnight UTC.
+fn parse_date(val: &str) -> anyhow::Result<Timestamp> {
+    let date = NaiveDate::parse_from_str(val, "%Y-%m-%d")
+        .map_err(|_| anyhow!("invalid date '{}', expected YYYY-MM-DD", val))?;
+    let seconds = date
+        .and_hms_opt(0, 0, 0)
+        .map(|t| t.timestamp())
+        .ok_or_else(|| anyhow!("invalid date '{}'", val))?;
+    let seconds = u64::try_from(seconds).map_err(|_| anyhow!("date '{}' is before 1970", val))?;
+
+    Ok(Timestamp::from(seconds))
+}
+
+fn format_date(timestamp: Timestamp) -> String {
+    DateTime::<Utc>::from(
+        std::time::UNIX_EPOCH + std::time::Duration::from_secs(timestamp.as_secs()),
+    )
+    .format("%Y-%m-%d")
+    .to_string()
+}
+
+fn show_milestone(milestone: &Milestone) -> anyhow::Result<()> {
+    term::info!("title: {}", milestone.title());
+    term::info!(
+        "due: {}",
+        milestone.due().map(format_date).unwrap_or_default()
+    );
+
+    let items: Vec<String> = milestone.items().map(|i| i.to_string()).collect();
+    term::info!("items: {}", items.join(", "));
+
+    term::info!("{}", milestone.description());
+    Ok(())
+}
@@ -0,0 +1,218 @@
+use std::ffi::OsString;
+
+use anyhow::{anyhow, Context as _};
+
+use radicle::identity::Id;
+use radicle::storage::git::mirror::Mirror;
+use radicle::storage::WriteStorage;
+
+use crate::terminal as term;
+use crate::terminal::args::{Args, Error, Help};
+
+pub const HELP: Help = Help {
+    name: "mirror",
+    description: "Manage project mirrors",
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad mirror add <name> <url> [--username <username>] [--password-stdin] [<option>...]
+    rad mirror remove <name> [<option>...]
+    rad mirror list [<option>...]
+    rad mirror push [<name>] [<option>...]
+
+    Mirrors are external git remotes, eg. on GitHub or GitLab, that the
+    project's canonical default branch and delegate tags are pushed to
+    whenever the project's references are updated by the node.
+
+    If a username is given when adding a mirror, a password or access
+    token is prompted for, and stored with the mirror configuration.
+
+Options
+
+    --repo <id>         The project to operate on (default: cwd)
+    --username <name>   Username used to authenticate with the mirror
+    --password-stdin    Read the mirror password from stdin
+    --help              Print help
+"#,
+};
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub enum OperationName {
+    Add,
+    Remove,
+    #[default]
+    List,
+    Push,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Operation {
+    Add {
+        name: String,
+        url: String,
+        username: Option<String>,
+        stdin: bool,
+    },
+    Remove {
+        name: String,
+    },
+    List,
+    Push {
+        name: Option<String>,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Options {
+    pub id: Option<Id>,
+    pub op: Operation,
+}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+        let mut id: Option<Id> = None;
+        let mut op: Option<OperationName> = None;
+        let mut username: Option<String> = None;
+        let mut stdin = false;
+        let mut values: Vec<String> = Vec::new();
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                Long("repo") => {
+                    id = Some(parser.value()?.parse::<Id>()?);
+                }
+                Long("username") => {
+                    username = Some(parser.value()?.to_string_lossy().to_string());
+                }
+                Long("password-stdin") => {
+                    stdin = true;
+                }
+                Value(val) if op.is_none() => match val.to_string_lossy().as_ref() {
+                    "a" | "add" => op = Some(OperationName::Add),
+                    "r" | "remove" => op = Some(OperationName::Remove),
+                    "l" | "list" => op = Some(OperationName::List),
+                    "p" | "push" => op = Some(OperationName::Push),
+
+                    unknown => anyhow::bail!("unknown operation '{}'", unknown),
+                },
+                Value(val) => {
+                    values.push(val.to_string_lossy().to_string());
+                }
+                _ => return Err(anyhow!(arg.unexpected())),
+            }
+        }
+        let mut values = values.into_iter();
+
+        let op = match op.unwrap_or_default() {
+            OperationName::Add => Operation::Add {
+                name: values
+                    .next()
+                    .ok_or_else(|| anyhow!("a mirror name must be provided"))?,
+                url: values
+                    .next()
+                    .ok_or_else(|| anyhow!("a mirror URL must be provided"))?,
+                username,
+                stdin,
+            },
+            OperationName::Remove => Operation::Remove {
+                name: values
+                    .next()
+                    .ok_or_else(|| anyhow!("a mirror name must be provided"))?,
+            },
+            OperationName::List => Operation::List,
+            OperationName::Push => Operation::Push {
+                name: values.next(),
+            },
+        };
+        if let Some(val) = values.next() {
+            anyhow::bail!("unexpected argument '{}'", val);
+        }
+
+        Ok((Options { id, op }, vec![]))
+    }
+}
+
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let profile = ctx.profile()?;
+    let id = options
+        .id
+        .or_else(|| radicle::rad::cwd().ok().map(|(_, id)| id))
+        .context("Couldn't get ID from either command line or cwd")?;
+    let repo = profile.storage.repository(id)?;
+
+    match options.op {
+        Operation::Add {
+            name,
+            url,
+            username,
+            stdin,
+        } => {
+            let mut mirror = Mirror::new(&name, &url);
+            if let Some(username) = username {
+                let password = if stdin {
+                    term::secret_stdin()?
+                } else {
+                    term::secret_input_with_prompt("Password")
+                };
+                mirror = mirror.with_credentials(username, password.as_str());
+            }
+            repo.add_mirror(&mirror)?;
+
+            term::success!(
+                "Added mirror {} ({})",
+                term::format::highlight(&name),
+                term::format::dim(&url)
+            );
+        }
+        Operation::Remove { name } => {
+            repo.remove_mirror(&name)?;
+
+            term::success!("Removed mirror {}", term::format::highlight(&name));
+        }
+        Operation::List => {
+            let mut table = term::Table::default();
+
+            for mirror in repo.mirrors()? {
+                table.push([
+                    term::format::highlight(&mirror.name),
+                    mirror.url,
+                    term::format::dim(mirror.credentials.map(|c| c.username).unwrap_or_default()),
+                ]);
+            }
+            table.render();
+        }
+        Operation::Push { name } => {
+            let mirrors = match name {
+                Some(name) => vec![repo.mirror(&name)?],
+                None => repo.mirrors()?,
+            };
+            for mirror in mirrors {
+                let spinner = term::spinner(format!(
+                    "Pushing to {}..",
+                    term::format::highlight(&mirror.name)
+                ));
+                match repo.push_mirror(&mirror) {
+                    Ok(refs) => {
+                        spinner.finish();
+                        for r in refs {
+                            term::indented(term::format::dim(r));
+                        }
+                    }
+                    Err(err) => {
+                        spinner.failed();
+                        term::error(err);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -11,7 +11,7 @@ use std::ffi::OsString;
 
 use anyhow::anyhow;
 
-use radicle::cob::patch::PatchId;
+use radicle::cob::patch::{PatchId, Patches};
 use radicle::prelude::*;
 
 use crate::terminal as term;
@@ -26,8 +26,10 @@ pub const HELP: Help = Help {
 Usage
 
     rad patch
+    rad patch show <id> [--interdiff]
     rad patch open [<option>...]
     rad patch update <id> [<option>...]
+    rad patch delete <id>
 
 Create/Update options
 
@@ -37,6 +39,11 @@ Create/Update options
     -m, --message [<string>]   Provide a comment message to the patch or revision (default: prompt)
         --no-message           Leave the patch or revision comment message blank
 
+Show options
+
+        --interdiff            Show the changes since the revision last reviewed by you,
+                               or since the previous revision
+
 Options
 
         --help                 Print help
@@ -65,6 +72,7 @@ pub enum OperationName {
     Open,
     Show,
     Update,
+    Delete,
     #[default]
     List,
 }
@@ -76,11 +84,15 @@ pub enum Operation {
     },
     Show {
         patch_id: PatchId,
+        interdiff: bool,
     },
     Update {
         patch_id: OptPatch,
         message: Comment,
     },
+    Delete {
+        patch_id: PatchId,
+    },
     List,
 }
 
@@ -105,6 +117,7 @@ impl Args for Options {
         let mut patch_id = OptPatch::default();
         let mut message = Comment::default();
         let mut push = true;
+        let mut interdiff = false;
 
         while let Some(arg) = parser.next()? {
             match arg {
@@ -139,6 +152,9 @@ impl Args for Options {
                 Long("no-push") => {
                     push = false;
                 }
+                Long("interdiff") if op == Some(OperationName::Show) => {
+                    interdiff = true;
+                }
 
                 // Common.
                 Long("verbose") | Short('v') => {
@@ -153,6 +169,7 @@ impl Args for Options {
                     "o" | "open" => op = Some(OperationName::Open),
                     "s" | "show" => op = Some(OperationName::Show),
                     "u" | "update" => op = Some(OperationName::Update),
+                    "d" | "delete" => op = Some(OperationName::Delete),
 
                     unknown => anyhow::bail!("unknown operation '{}'", unknown),
                 },
@@ -162,6 +179,9 @@ impl Args for Options {
                 Value(val) if op == Some(OperationName::Update) && patch_id == OptPatch::Any => {
                     patch_id = OptPatch::Patch(term::cob::parse_patch_id(val)?);
                 }
+                Value(val) if op == Some(OperationName::Delete) && patch_id == OptPatch::Any => {
+                    patch_id = OptPatch::Patch(term::cob::parse_patch_id(val)?);
+                }
                 _ => return Err(anyhow::anyhow!(arg.unexpected())),
             }
         }
@@ -172,8 +192,13 @@ impl Args for Options {
             OperationName::Show => Operation::Show {
                 patch_id: Option::from(patch_id)
                     .ok_or_else(|| anyhow!("a patch id must be provided"))?,
+                interdiff,
             },
             OperationName::Update => Operation::Update { patch_id, message },
+            OperationName::Delete => Operation::Delete {
+                patch_id: Option::from(patch_id)
+                    .ok_or_else(|| anyhow!("a patch id to remove must be provided"))?,
+            },
         };
 
         Ok((
@@ -210,8 +235,11 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
         Operation::List => {
             list::run(&storage, &profile, Some(workdir), options)?;
         }
-        Operation::Show { ref patch_id } => {
-            show::run(&storage, &profile, &workdir, patch_id)?;
+        Operation::Show {
+            ref patch_id,
+            interdiff,
+        } => {
+            show::run(&storage, &profile, &workdir, patch_id, interdiff)?;
         }
         Operation::Update {
             ref patch_id,
@@ -226,6 +254,10 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
                 options,
             )?;
         }
+        Operation::Delete { ref patch_id } => {
+            let patches = Patches::open(profile.public_key, &storage)?;
+            patches.remove(patch_id)?;
+        }
     }
     Ok(())
 }
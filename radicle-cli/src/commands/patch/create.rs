@@ -156,7 +156,7 @@ pub fn run(
                 workdir,
             )?;
 
-            if let Some((id, patch, clock)) = result.pop() {
+            if let Some((id, patch, _)) = result.pop() {
                 if result.is_empty() {
                     spinner.message(format!(
                         "Found existing patch {} {}",
@@ -166,7 +166,7 @@ pub fn run(
                     spinner.finish();
                     term::blank();
 
-                    Some((id, PatchMut::new(id, patch, clock, &mut patches)))
+                    Some((id, patches.get_mut(&id)?))
                 } else {
                     spinner.failed();
                     term::blank();
@@ -1,8 +1,11 @@
+use std::collections::BTreeSet;
+
 use super::common::*;
 use super::*;
 
 use crate::terminal as term;
-use radicle::cob::patch;
+use radicle::cob::store::FromHistory as _;
+use radicle::cob::{issue, patch};
 use radicle::git;
 use radicle::prelude::*;
 use radicle::storage::git::Repository;
@@ -21,11 +24,89 @@ fn show_patch_diff(
     Ok(())
 }
 
+/// Show the changes made to the patch since the latest revision reviewed by us, or since the
+/// previous revision if we haven't reviewed any.
+fn show_interdiff(
+    patches: &patch::Patches,
+    patch_id: &PatchId,
+    patch: &patch::Patch,
+    whoami: &PublicKey,
+) -> anyhow::Result<()> {
+    let revisions = patch.revisions().rev().collect::<Vec<_>>();
+    let Some(((to, _), previous)) = revisions.split_first() else {
+        return Ok(());
+    };
+    let reviewed = previous
+        .iter()
+        .find(|(_, r)| r.reviews.get(whoami).is_some());
+    let Some((from, _)) = reviewed.or(previous.first()) else {
+        term::info!("No previous revision to compare to");
+        return Ok(());
+    };
+    let diff = patches.interdiff(patch_id, from, to)?;
+    let mut output = Vec::new();
+
+    diff.print(git::raw::DiffFormat::Patch, |_, _, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            output.push(line.origin() as u8);
+        }
+        output.extend_from_slice(line.content());
+        true
+    })?;
+    term::blob(String::from_utf8_lossy(&output));
+
+    Ok(())
+}
+
+/// Show the code comments on the latest patch revision, along with the lines they
+/// refer to.
+fn show_code_comments(patch: &patch::Patch, storage: &Repository) -> anyhow::Result<()> {
+    let Some((_, revision)) = patch.latest() else {
+        return Ok(());
+    };
+
+    for (id, comment) in revision.code_comments() {
+        let location = &comment.location;
+
+        term::print(format!(
+            "{} {}",
+            term::format::tertiary(format!(
+                "{}:{}-{}",
+                location.path.display(),
+                location.lines.start,
+                location.lines.end
+            )),
+            term::format::dim(term::format::oid(location.commit))
+        ));
+        // Lines are numbered from `1`. The blob may be missing if the commit wasn't fetched.
+        if let Ok(blob) = storage.raw().find_blob(*location.blob) {
+            let content = String::from_utf8_lossy(blob.content());
+            let lines = content
+                .lines()
+                .enumerate()
+                .map(|(i, line)| (i + 1, line))
+                .filter(|(n, _)| location.lines.contains(n));
+
+            for (n, line) in lines {
+                term::print(term::format::dim(format!("{n:>5} | {line}")));
+            }
+        }
+        term::print(format!(
+            "{} {}",
+            term::format::node(id.actor()),
+            comment.comment
+        ));
+        term::blank();
+    }
+    Ok(())
+}
+
 pub fn run(
     storage: &Repository,
     profile: &Profile,
     workdir: &git::raw::Repository,
     patch_id: &PatchId,
+    interdiff: bool,
 ) -> anyhow::Result<()> {
     let patches = patch::Patches::open(profile.public_key, storage)?;
     let Some(patch) = patches.get(patch_id)? else {
@@ -39,8 +120,44 @@ pub fn run(
     term::patch::print_title_desc(patch.title(), patch.description().unwrap_or(""));
     term::blank();
 
-    show_patch_diff(&patch, storage, workdir)?;
+    let issues = issue::Issues::open(profile.public_key, storage)?;
+    let mut linked = issues
+        .referencing(&patch::TYPENAME, patch_id)?
+        .filter_map(|r| r.ok())
+        .map(|(id, _, _)| id)
+        .collect::<BTreeSet<_>>();
+    linked.extend(
+        patch
+            .references()
+            .into_iter()
+            .filter(|r| r.typename == *issue::TYPENAME)
+            .map(|r| r.object_id),
+    );
+    if !linked.is_empty() {
+        let linked: Vec<String> = linked.iter().map(|id| id.to_string()).collect();
+        term::info!("issues: {}", linked.join(", "));
+        term::blank();
+    }
+
+    if let Some((_, revision)) = patch.latest() {
+        let doc = storage.identity_of(profile.id())?;
+
+        term::info!(
+            "approvals: {}/{}",
+            revision.approvals(&doc).count(),
+            doc.threshold
+        );
+        term::blank();
+    }
+
+    if interdiff {
+        show_interdiff(&patches, patch_id, &patch, profile.id())?;
+    } else {
+        show_patch_diff(&patch, storage, workdir)?;
+    }
     term::blank();
 
+    show_code_comments(&patch, storage)?;
+
     Ok(())
 }
@@ -0,0 +1,275 @@
+use std::ffi::OsString;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context as _};
+
+use crate::terminal as term;
+use crate::terminal::args::{Args, Error, Help};
+
+use radicle::cob::release::{Artifact, Manifest, Release, ReleaseId, Releases};
+use radicle::identity::Doc;
+use radicle::storage::WriteStorage;
+
+pub const HELP: Help = Help {
+    name: "release",
+    description: "Publish and sign releases",
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad release
+    rad release list
+    rad release publish <rev> --version <version> [--tag <name>] [--notes <text>] [--artifact <name>=<hash>]...
+    rad release show <id>
+    rad release sign <id>
+
+    Releases are signed by the delegate publishing them, and can be co-signed by
+    other delegates. A release is attested once it is signed by at least as many
+    delegates as the project's threshold.
+
+    If <rev> names a tag, it is recorded as the released tag, unless another
+    tag name is given with `--tag`.
+
+    Artifact hashes should be prefixed with the hash algorithm, eg. `sha256:<hex>`.
+
+Options
+
+    --help      Print help
+"#,
+};
+
+#[derive(Default, Debug, PartialEq, Eq)]
+pub enum OperationName {
+    #[default]
+    List,
+    Publish,
+    Show,
+    Sign,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Operation {
+    List,
+    Publish {
+        rev: String,
+        tag: Option<String>,
+        version: String,
+        notes: String,
+        artifacts: Vec<Artifact>,
+    },
+    Show {
+        id: ReleaseId,
+    },
+    Sign {
+        id: ReleaseId,
+    },
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub op: Operation,
+}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+        let mut op: Option<OperationName> = None;
+        let mut id: Option<ReleaseId> = None;
+        let mut rev: Option<String> = None;
+        let mut tag: Option<String> = None;
+        let mut version: Option<String> = None;
+        let mut notes = String::new();
+        let mut artifacts = Vec::new();
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                Long("version") if op == Some(OperationName::Publish) => {
+                    version = Some(parser.value()?.to_string_lossy().into());
+                }
+                Long("tag") if op == Some(OperationName::Publish) => {
+                    tag = Some(parser.value()?.to_string_lossy().into());
+                }
+                Long("notes") if op == Some(OperationName::Publish) => {
+                    notes = parser.value()?.to_string_lossy().into();
+                }
+                Long("artifact") if op == Some(OperationName::Publish) => {
+                    let val = parser.value()?;
+                    let val = val.to_string_lossy();
+                    let (name, hash) = val.split_once('=').ok_or_else(|| {
+                        anyhow!("invalid artifact '{}', expected <name>=<hash>", val)
+                    })?;
+
+                    artifacts.push(Artifact {
+                        name: name.to_owned(),
+                        hash: hash.to_owned(),
+                    });
+                }
+                Value(val) if op.is_none() => match val.to_string_lossy().as_ref() {
+                    "l" | "list" => op = Some(OperationName::List),
+                    "p" | "publish" => op = Some(OperationName::Publish),
+                    "s" | "show" => op = Some(OperationName::Show),
+                    "sign" => op = Some(OperationName::Sign),
+
+                    unknown => anyhow::bail!("unknown operation '{}'", unknown),
+                },
+                Value(val) if op == Some(OperationName::Publish) && rev.is_none() => {
+                    rev = Some(val.to_string_lossy().into());
+                }
+                Value(val) if op.is_some() && id.is_none() => {
+                    let val = val
+                        .to_str()
+                        .ok_or_else(|| anyhow!("release id specified is not UTF-8"))?;
+
+                    id = Some(
+                        ReleaseId::from_str(val)
+                            .map_err(|_| anyhow!("invalid release id '{}'", val))?,
+                    );
+                }
+                _ => {
+                    return Err(anyhow!(arg.unexpected()));
+                }
+            }
+        }
+
+        let op = match op.unwrap_or_default() {
+            OperationName::List => Operation::List,
+            OperationName::Publish => Operation::Publish {
+                rev: rev.ok_or_else(|| anyhow!("a revision to release must be provided"))?,
+                tag,
+                version: version.ok_or_else(|| anyhow!("a release version must be provided"))?,
+                notes,
+                artifacts,
+            },
+            OperationName::Show => Operation::Show {
+                id: id.ok_or_else(|| anyhow!("a release id must be provided"))?,
+            },
+            OperationName::Sign => Operation::Sign {
+                id: id.ok_or_else(|| anyhow!("a release id must be provided"))?,
+            },
+        };
+
+        Ok((Options { op }, vec![]))
+    }
+}
+
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let profile = ctx.profile()?;
+    let signer = term::signer(&profile)?;
+    let storage = &profile.storage;
+    let (working, id) = radicle::rad::cwd()?;
+    let repo = storage.repository(id)?;
+    let doc = repo.identity_of(profile.id())?;
+    let mut releases = Releases::open(*signer.public_key(), &repo)?;
+
+    match options.op {
+        Operation::List => {
+            let mut t = term::Table::new(term::table::TableOptions::default());
+            for result in releases.all()? {
+                let (id, release, _) = result?;
+
+                t.push([
+                    id.to_string(),
+                    release.version().to_owned(),
+                    release.oid().map(|o| o.to_string()).unwrap_or_default(),
+                    attestation(&release, &doc),
+                ]);
+            }
+            t.render();
+        }
+        Operation::Publish {
+            rev,
+            tag,
+            version,
+            notes,
+            artifacts,
+        } => {
+            if !doc.is_delegate(signer.public_key()) {
+                anyhow::bail!("only project delegates can publish releases");
+            }
+            let oid = working
+                .revparse_single(&rev)
+                .context(format!("revision '{}' could not be found", rev))?
+                .id();
+            let tag = tag.or_else(|| {
+                working
+                    .find_reference(&format!("refs/tags/{rev}"))
+                    .is_ok()
+                    .then_some(rev)
+            });
+            let release = releases.publish(
+                Manifest {
+                    oid: oid.into(),
+                    tag,
+                    version,
+                    notes,
+                    artifacts,
+                },
+                &signer,
+            )?;
+
+            term::success!(
+                "Release {} published ({})",
+                term::format::highlight(release.version()),
+                term::format::dim(release.id())
+            );
+        }
+        Operation::Show { id } => {
+            let release = releases
+                .get(&id)?
+                .context("No release with the given ID exists")?;
+            show_release(&release, &doc)?;
+        }
+        Operation::Sign { id } => {
+            if !doc.is_delegate(signer.public_key()) {
+                anyhow::bail!("only project delegates can sign releases");
+            }
+            let mut release = releases.get_mut(&id)?;
+            release.sign(&signer)?;
+
+            term::success!(
+                "Release {} signed, {}",
+                term::format::highlight(release.version()),
+                attestation(&release, &doc)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn attestation<V>(release: &Release, doc: &Doc<V>) -> String {
+    let signers = release.signers(doc).count();
+
+    if release.is_attested(doc) {
+        format!("attested ({}/{})", signers, doc.threshold)
+    } else {
+        format!("unattested ({}/{})", signers, doc.threshold)
+    }
+}
+
+fn show_release<V>(release: &Release, doc: &Doc<V>) -> anyhow::Result<()> {
+    term::info!("version: {}", release.version());
+    term::info!(
+        "oid: {}",
+        release.oid().map(|o| o.to_string()).unwrap_or_default()
+    );
+    if let Some(tag) = release.tag() {
+        term::info!("tag: {}", tag);
+    }
+    term::info!("status: {}", attestation(release, doc));
+
+    let signers: Vec<String> = release.signers(doc).map(|s| s.to_string()).collect();
+    term::info!("signers: {}", signers.join(", "));
+
+    for artifact in release.artifacts() {
+        term::info!("artifact: {} {}", artifact.name, artifact.hash);
+    }
+    term::info!("{}", release.notes());
+
+    Ok(())
+}
@@ -1,11 +1,10 @@
 use std::ffi::OsString;
-use std::fs;
 use std::str::FromStr;
 
 use anyhow::anyhow;
 
 use radicle::identity::Id;
-use radicle::storage::ReadStorage;
+use radicle::storage::{ReadStorage, WriteStorage};
 
 use crate::commands::rad_untrack;
 use crate::terminal as term;
@@ -81,8 +80,6 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
     let id = options.id;
 
     if let Ok(Some(_)) = storage.get(signer.public_key(), id.to_owned()) {
-        let namespace = profile.paths().storage().join(id.to_human());
-
         if !options.confirm
             || term::confirm(format!(
                 "Are you sure you would like to delete {}?",
@@ -90,7 +87,7 @@ pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
             ))
         {
             rad_untrack::untrack(id.to_owned(), &profile)?;
-            fs::remove_dir_all(namespace)?;
+            storage.remove(id)?;
             term::success!("Successfully removed project {}", &id);
         }
     } else {
@@ -0,0 +1,86 @@
+use std::ffi::OsString;
+
+use crate::terminal as term;
+use crate::terminal::args::{Args, Error, Help};
+
+use radicle::storage::{Size, WriteStorage};
+
+pub const HELP: Help = Help {
+    name: "stats",
+    description: "Show storage statistics",
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad stats [<option>...]
+
+    Shows the on-disk size of each project in storage, and the total.
+    The size of collaborative objects (issues, patches, etc.) is
+    uncompressed, and part of the object size.
+
+Options
+
+    --help    Print help
+"#,
+};
+
+pub struct Options {}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+
+        if let Some(arg) = parser.next()? {
+            match arg {
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                _ => return Err(anyhow::anyhow!(arg.unexpected())),
+            }
+        }
+
+        Ok((Options {}, vec![]))
+    }
+}
+
+pub fn run(_options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let profile = ctx.profile()?;
+    let storage = &profile.storage;
+    let mut table = term::Table::default();
+    let mut total = Size::default();
+
+    table.push([
+        term::format::bold("Project"),
+        term::format::bold("Objects"),
+        term::format::bold("Refs"),
+        term::format::bold("COBs"),
+        term::format::bold("Total"),
+    ]);
+    for (id, size) in storage.sizes()? {
+        let name = storage
+            .repository(id)
+            .ok()
+            .and_then(|repo| repo.project_of(profile.id()).ok())
+            .map(|proj| proj.name().to_owned())
+            .unwrap_or_else(|| id.to_string());
+
+        table.push(row(term::format::tertiary(name), &size));
+        total += size;
+    }
+    table.push(row(term::format::bold("Total"), &total));
+    table.render();
+
+    Ok(())
+}
+
+fn row(name: String, size: &Size) -> [String; 5] {
+    [
+        name,
+        term::format::bytes(size.objects),
+        term::format::bytes(size.refs),
+        term::format::dim(term::format::bytes(size.cobs)),
+        term::format::bytes(size.total()),
+    ]
+}
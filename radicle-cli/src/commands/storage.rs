@@ -0,0 +1,135 @@
+use std::ffi::OsString;
+
+use anyhow::anyhow;
+
+use radicle::storage::git::migrate;
+use radicle::Storage;
+
+use crate::terminal as term;
+use crate::terminal::args::{Args, Error, Help};
+
+pub const HELP: Help = Help {
+    name: "storage",
+    description: "Manage storage",
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad storage version [<option>...]
+    rad storage migrate [--dry-run] [<option>...]
+
+    Storage is migrated automatically when it is first accessed by a newer
+    version of radicle. The `migrate` command can be used to check which
+    migrations are pending, and to run them explicitly.
+
+Options
+
+    --dry-run   Only list pending migrations, without running them
+    --help      Print help
+"#,
+};
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub enum Operation {
+    #[default]
+    Version,
+    Migrate {
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Options {
+    pub op: Operation,
+}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+        let mut op: Option<Operation> = None;
+        let mut dry_run = false;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                Long("dry-run") => {
+                    dry_run = true;
+                }
+                Value(val) if op.is_none() => match val.to_string_lossy().as_ref() {
+                    "v" | "version" => op = Some(Operation::Version),
+                    "m" | "migrate" => op = Some(Operation::Migrate { dry_run: false }),
+
+                    unknown => anyhow::bail!("unknown operation '{}'", unknown),
+                },
+                _ => return Err(anyhow!(arg.unexpected())),
+            }
+        }
+        let op = match op.unwrap_or_default() {
+            Operation::Migrate { .. } => Operation::Migrate { dry_run },
+            Operation::Version if dry_run => {
+                anyhow::bail!("`--dry-run` is only valid with the `migrate` operation")
+            }
+            op => op,
+        };
+
+        Ok((Options { op }, vec![]))
+    }
+}
+
+pub fn run(options: Options, _ctx: impl term::Context) -> anyhow::Result<()> {
+    // Nb. Storage is opened directly instead of via the profile, since loading the profile
+    // migrates storage.
+    let home = radicle::profile::home()?;
+    let storage = Storage::open(home.storage())?;
+
+    match options.op {
+        Operation::Version => {
+            term::info!(
+                "{} (latest {})",
+                term::format::highlight(storage.version()?),
+                migrate::current()
+            );
+        }
+        Operation::Migrate { dry_run } => {
+            let pending = storage.pending_migrations()?;
+
+            if pending.is_empty() {
+                term::success!("Storage is up to date");
+                return Ok(());
+            }
+            if dry_run {
+                for migration in pending {
+                    term::info!(
+                        "{} {}",
+                        term::format::highlight(migration.version),
+                        migration.description
+                    );
+                }
+                return Ok(());
+            }
+            let spinner = term::spinner("Migrating storage..");
+            match storage.migrate() {
+                Ok(migrations) => {
+                    spinner.finish();
+                    for migration in migrations {
+                        term::indented(format!(
+                            "{} {}",
+                            term::format::highlight(migration.version),
+                            term::format::dim(migration.description)
+                        ));
+                    }
+                }
+                Err(err) => {
+                    spinner.failed();
+                    return Err(err.into());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
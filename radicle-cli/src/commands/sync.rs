@@ -0,0 +1,105 @@
+use std::ffi::OsString;
+
+use anyhow::{anyhow, Context as _};
+
+use radicle::identity::Id;
+use radicle::node::Handle;
+use radicle::storage::{RefUpdate, WriteStorage};
+
+use crate::terminal as term;
+use crate::terminal::args::{Args, Error, Help};
+
+pub const HELP: Help = Help {
+    name: "sync",
+    description: "Sync a project with the network",
+    version: env!("CARGO_PKG_VERSION"),
+    usage: r#"
+Usage
+
+    rad sync [<id>] [<option>...]
+
+    Fetches the project from the network, and announces the local refs
+    of the project to connected peers. Requires the node to be running.
+
+    When run from a working copy, the remote tracking branches of open
+    patches, under `rad/patches/<id>`, are updated as well.
+
+Options
+
+    --help              Print help
+"#,
+};
+
+#[derive(Debug)]
+pub struct Options {
+    pub id: Option<Id>,
+}
+
+impl Args for Options {
+    fn from_args(args: Vec<OsString>) -> anyhow::Result<(Self, Vec<OsString>)> {
+        use lexopt::prelude::*;
+
+        let mut parser = lexopt::Parser::from_args(args);
+        let mut id: Option<Id> = None;
+
+        while let Some(arg) = parser.next()? {
+            match arg {
+                Value(val) if id.is_none() => {
+                    let val = val.to_string_lossy();
+
+                    if let Ok(val) = Id::from_human(&val) {
+                        id = Some(val);
+                    } else {
+                        return Err(anyhow!("invalid ID '{}'", val));
+                    }
+                }
+                Long("help") => {
+                    return Err(Error::Help.into());
+                }
+                _ => {
+                    return Err(anyhow!(arg.unexpected()));
+                }
+            }
+        }
+
+        Ok((Options { id }, vec![]))
+    }
+}
+
+pub fn run(options: Options, ctx: impl term::Context) -> anyhow::Result<()> {
+    let working = radicle::rad::cwd().ok();
+    let id = options
+        .id
+        .or_else(|| working.as_ref().map(|(_, id)| *id))
+        .context("current directory is not a git repository; please supply an `<id>`")?;
+    let profile = ctx.profile()?;
+    let mut node = radicle::node::connect(profile.socket())?;
+
+    let spinner = term::spinner(format!("Syncing {}..", term::format::highlight(id)));
+    match node.fetch(id).and_then(|_| node.announce_refs(id)) {
+        Ok(()) => spinner.finish(),
+        Err(err) => {
+            spinner.failed();
+            return Err(err.into());
+        }
+    }
+
+    if let Some((working, _)) = working.filter(|(_, wid)| *wid == id) {
+        let stored = profile.storage.repository(id)?;
+        let updates = radicle::rad::update_patch_branches(&working, &stored, *profile.id())?;
+
+        for update in updates {
+            match update {
+                RefUpdate::Created { name, .. } | RefUpdate::Updated { name, .. } => {
+                    term::success!("Updated {}", term::format::highlight(name));
+                }
+                RefUpdate::Deleted { name, .. } => {
+                    term::success!("Deleted {}", term::format::dim(name));
+                }
+                RefUpdate::Skipped { .. } => {}
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -19,6 +19,8 @@ enum Command {
 }
 
 fn main() {
+    term::configure();
+
     match parse_args().map_err(Some).and_then(run) {
         Ok(_) => process::exit(0),
         Err(err) => {
@@ -150,6 +152,14 @@ fn run_other(exe: &str, args: &[OsString]) -> Result<(), Option<anyhow::Error>>
                 args.to_vec(),
             );
         }
+        "discuss" => {
+            term::run_command_args::<rad_discuss::Options, _>(
+                rad_discuss::HELP,
+                "Discussion",
+                rad_discuss::run,
+                args.to_vec(),
+            );
+        }
         "edit" => {
             term::run_command_args::<rad_edit::Options, _>(
                 rad_edit::HELP,
@@ -158,6 +168,22 @@ fn run_other(exe: &str, args: &[OsString]) -> Result<(), Option<anyhow::Error>>
                 args.to_vec(),
             );
         }
+        "export" => {
+            term::run_command_args::<rad_export::Options, _>(
+                rad_export::HELP,
+                "Export",
+                rad_export::run,
+                args.to_vec(),
+            );
+        }
+        "fork" => {
+            term::run_command_args::<rad_fork::Options, _>(
+                rad_fork::HELP,
+                "Fork",
+                rad_fork::run,
+                args.to_vec(),
+            );
+        }
         "help" => {
             term::run_command_args::<rad_help::Options, _>(
                 rad_help::HELP,
@@ -166,6 +192,22 @@ fn run_other(exe: &str, args: &[OsString]) -> Result<(), Option<anyhow::Error>>
                 args.to_vec(),
             );
         }
+        "import" => {
+            term::run_command_args::<rad_import::Options, _>(
+                rad_import::HELP,
+                "Import",
+                rad_import::run,
+                args.to_vec(),
+            );
+        }
+        "inbox" => {
+            term::run_command_args::<rad_inbox::Options, _>(
+                rad_inbox::HELP,
+                "Inbox",
+                rad_inbox::run,
+                args.to_vec(),
+            );
+        }
         "init" => {
             term::run_command_args::<rad_init::Options, _>(
                 rad_init::HELP,
@@ -206,6 +248,14 @@ fn run_other(exe: &str, args: &[OsString]) -> Result<(), Option<anyhow::Error>>
                 args.to_vec(),
             );
         }
+        "mirror" => {
+            term::run_command_args::<rad_mirror::Options, _>(
+                rad_mirror::HELP,
+                "Mirror",
+                rad_mirror::run,
+                args.to_vec(),
+            );
+        }
         "patch" => {
             term::run_command_args::<rad_patch::Options, _>(
                 rad_patch::HELP,
@@ -214,6 +264,14 @@ fn run_other(exe: &str, args: &[OsString]) -> Result<(), Option<anyhow::Error>>
                 args.to_vec(),
             );
         }
+        "milestone" => {
+            term::run_command_args::<rad_milestone::Options, _>(
+                rad_milestone::HELP,
+                "Milestone",
+                rad_milestone::run,
+                args.to_vec(),
+            );
+        }
         "path" => {
             term::run_command_args::<rad_path::Options, _>(
                 rad_path::HELP,
@@ -230,6 +288,14 @@ fn run_other(exe: &str, args: &[OsString]) -> Result<(), Option<anyhow::Error>>
                 args.to_vec(),
             );
         }
+        "release" => {
+            term::run_command_args::<rad_release::Options, _>(
+                rad_release::HELP,
+                "Release",
+                rad_release::run,
+                args.to_vec(),
+            );
+        }
         "review" => {
             term::run_command_args::<rad_review::Options, _>(
                 rad_review::HELP,
@@ -254,6 +320,30 @@ fn run_other(exe: &str, args: &[OsString]) -> Result<(), Option<anyhow::Error>>
                 args.to_vec(),
             );
         }
+        "stats" => {
+            term::run_command_args::<rad_stats::Options, _>(
+                rad_stats::HELP,
+                "Stats",
+                rad_stats::run,
+                args.to_vec(),
+            );
+        }
+        "storage" => {
+            term::run_command_args::<rad_storage::Options, _>(
+                rad_storage::HELP,
+                "Storage",
+                rad_storage::run,
+                args.to_vec(),
+            );
+        }
+        "sync" => {
+            term::run_command_args::<rad_sync::Options, _>(
+                rad_sync::HELP,
+                "Sync",
+                rad_sync::run,
+                args.to_vec(),
+            );
+        }
         "track" => {
             term::run_command_args::<rad_track::Options, _>(
                 rad_track::HELP,
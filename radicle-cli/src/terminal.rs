@@ -2,6 +2,7 @@ pub mod args;
 pub mod cob;
 pub mod command;
 pub mod format;
+pub mod identity;
 pub mod io;
 pub mod patch;
 pub mod spinner;
@@ -12,7 +13,7 @@ use std::ffi::OsString;
 use std::process;
 
 use dialoguer::console::style;
-use radicle::profile::Profile;
+use radicle::profile::{config, Profile};
 
 pub use args::{Args, Error, Help};
 pub use console::measure_text_width as text_width;
@@ -120,6 +121,35 @@ where
     }
 }
 
+/// Apply the terminal preferences of the profile configuration, ie. colors and editor.
+pub fn configure() {
+    let Ok(home) = radicle::profile::home() else {
+        return;
+    };
+    let config = match radicle::profile::Config::load(&home.config()) {
+        Ok(config) => config,
+        Err(err) => {
+            io::warning(&format!("Failed to load configuration: {err}"));
+            return;
+        }
+    };
+    match config.color() {
+        config::Color::Auto => {}
+        config::Color::Always => {
+            console::set_colors_enabled(true);
+            console::set_colors_enabled_stderr(true);
+        }
+        config::Color::Never => {
+            console::set_colors_enabled(false);
+            console::set_colors_enabled_stderr(false);
+        }
+    }
+    // The editor is picked up by [`Editor`] via the environment.
+    if let Some(editor) = config.editor() {
+        std::env::set_var("VISUAL", editor);
+    }
+}
+
 /// Get the default profile. Fails if there is no profile.
 pub fn profile() -> Result<Profile, anyhow::Error> {
     let error = args::Error::WithHint {
@@ -36,6 +36,26 @@ pub fn timestamp(time: &Timestamp) -> String {
     fmt.convert(duration)
 }
 
+/// Format a size in bytes, eg. `1.5 MiB`.
+pub fn bytes(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if size < 1024 {
+        return format!("{size} B");
+    }
+    let mut size = size as f64 / 1024.;
+    let mut unit = UNITS[0];
+
+    for u in &UNITS[1..] {
+        if size < 1024. {
+            break;
+        }
+        size /= 1024.;
+        unit = u;
+    }
+    format!("{size:.1} {unit}")
+}
+
 /// Identity formatter that takes a profile and displays it as
 /// `<node-id> (<username>)` depending on the configuration.
 pub struct Identity<'a> {
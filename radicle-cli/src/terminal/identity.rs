@@ -0,0 +1,30 @@
+use radicle::identity::doc::{DocDiff, PayloadChange};
+
+use crate::terminal as term;
+
+/// Print the changes between two identity documents, one change per line.
+pub fn diff(diff: &DocDiff) {
+    for did in &diff.delegates_added {
+        term::print(term::format::positive(format!("+ delegate {did}")));
+    }
+    for did in &diff.delegates_removed {
+        term::print(term::format::negative(format!("- delegate {did}")));
+    }
+    if let Some((old, new)) = diff.threshold {
+        term::print(term::format::yellow(format!("~ threshold {old} -> {new}")));
+    }
+    for (id, change) in &diff.payload {
+        match change {
+            PayloadChange::Added { new } => {
+                term::print(term::format::positive(format!("+ {id} {}", **new)));
+            }
+            PayloadChange::Removed { old } => {
+                term::print(term::format::negative(format!("- {id} {}", **old)));
+            }
+            PayloadChange::Modified { old, new } => {
+                term::print(term::format::negative(format!("- {id} {}", **old)));
+                term::print(term::format::positive(format!("+ {id} {}", **new)));
+            }
+        }
+    }
+}
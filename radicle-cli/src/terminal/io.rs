@@ -10,11 +10,10 @@ use radicle::crypto::Signer;
 use radicle::profile;
 use radicle::profile::Profile;
 
-use radicle_crypto::ssh::keystore::MemorySigner;
+use radicle_crypto::ssh::{self, SshSigner};
 
 use super::command;
 use super::format;
-use super::spinner::spinner;
 use super::Error;
 
 pub const TAB: &str = "    ";
@@ -177,15 +176,23 @@ pub fn abort<D: fmt::Display>(prompt: D) -> bool {
 
 /// Get the signer. First we try getting it from ssh-agent, otherwise we prompt the user.
 pub fn signer(profile: &Profile) -> anyhow::Result<Box<dyn Signer>> {
-    if let Ok(signer) = profile.signer() {
-        return Ok(signer);
+    // Signers configured through the environment take precedence.
+    if profile::env::read_passphrase().is_some()
+        || profile::env::var(profile::env::RAD_DEVICE).is_ok()
+        || profile::env::var(profile::env::RAD_GPG_KEYGRIP).is_ok()
+    {
+        return Ok(profile.signer()?);
     }
 
-    let passphrase = secret_input();
-    let spinner = spinner("Unsealing key...");
-    let signer = MemorySigner::load(&profile.keystore, passphrase)?;
+    let signer = SshSigner::load(&profile.keystore, || Some(secret_input()))?;
+    let signer = signer.with_confirmation(|key| {
+        eprintln!(
+            "{} Requesting signature from ssh-agent for {}, confirm it if prompted..",
+            style("⤷").cyan().dim(),
+            style(ssh::fmt::fingerprint(key)).yellow()
+        );
+    });
 
-    spinner.finish();
     Ok(signer.boxed())
 }
 
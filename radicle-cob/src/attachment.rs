@@ -0,0 +1,90 @@
+// Copyright © 2022 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! Binary attachments of changes.
+//!
+//! Besides its contents, a change may carry named attachments, eg. images
+//! or log files. Attachments are stored as blobs under the `attachments`
+//! tree of the change, next to its `manifest` and contents.
+//!
+//! Attachments are loaded lazily: loading a change only reads the names and
+//! blob identifiers of its [`Attachments`], and their contents are read on
+//! demand with [`crate::change::Storage::attachment`].
+
+use std::collections::BTreeMap;
+
+use git_ext::Oid;
+use serde::{Deserialize, Serialize};
+
+/// The maximum size of a single attachment, in bytes.
+pub const MAX_SIZE: usize = 8 * 1024 * 1024;
+
+pub mod error {
+    use thiserror::Error;
+
+    #[derive(Debug, Error)]
+    pub enum Attachment {
+        #[error("invalid attachment name '{0}'")]
+        InvalidName(String),
+        #[error("attachment '{name}' is {size} bytes, which exceeds the maximum of {max} bytes")]
+        TooLarge {
+            name: String,
+            size: usize,
+            max: usize,
+        },
+    }
+}
+
+/// The attachments of a change, as a mapping from their names to the
+/// identifiers of the blobs holding their contents.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Attachments(BTreeMap<String, Oid>);
+
+impl Attachments {
+    /// Get the blob identifier of an attachment.
+    pub fn get(&self, name: &str) -> Option<&Oid> {
+        self.0.get(name)
+    }
+
+    /// Iterate over the attachments, in name order.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Oid)> {
+        self.0.iter()
+    }
+
+    /// The number of attachments.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether there are no attachments.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FromIterator<(String, Oid)> for Attachments {
+    fn from_iter<T: IntoIterator<Item = (String, Oid)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// Check that an attachment can be stored.
+///
+/// Attachment names must be non-empty, and can't contain path separators,
+/// since they are used as tree entry names.
+pub fn validate(name: &str, content: &[u8]) -> Result<(), error::Attachment> {
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\0']) {
+        return Err(error::Attachment::InvalidName(name.to_owned()));
+    }
+    if content.len() > MAX_SIZE {
+        return Err(error::Attachment::TooLarge {
+            name: name.to_owned(),
+            size: content.len(),
+            max: MAX_SIZE,
+        });
+    }
+    Ok(())
+}
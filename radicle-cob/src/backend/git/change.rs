@@ -13,70 +13,21 @@ use nonempty::NonEmpty;
 
 use crate::history::entry::Timestamp;
 use crate::{
+    attachment::{self, Attachments},
     change::{self, store, Change},
     history::entry,
     signatures::{Signature, Signatures},
-    trailers,
+    trailers, Notarization,
 };
 
 const MANIFEST_BLOB_NAME: &str = "manifest";
+const ATTACHMENTS_TREE_NAME: &str = "attachments";
 
-pub mod error {
-    use std::str::Utf8Error;
-    use std::string::FromUtf8Error;
-
-    use git_ext::Oid;
-    use git_trailers::Error as TrailerError;
-    use thiserror::Error;
-
-    use crate::signatures::error::Signatures;
-
-    #[derive(Debug, Error)]
-    pub enum Create {
-        #[error(transparent)]
-        FromUtf8(#[from] FromUtf8Error),
-        #[error(transparent)]
-        Git(#[from] git2::Error),
-        #[error(transparent)]
-        Signer(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
-        #[error(transparent)]
-        Utf8(#[from] Utf8Error),
-    }
+/// Notes reference under which the attestations of changes are stored, as JSON,
+/// see [`change::Attestations`].
+pub const ATTESTATIONS_REF: &str = "refs/notes/cobs";
 
-    #[derive(Debug, Error)]
-    pub enum Load {
-        #[error(transparent)]
-        Read(#[from] git_commit::error::Read),
-        #[error(transparent)]
-        Signatures(#[from] Signatures),
-        #[error(transparent)]
-        Git(#[from] git2::Error),
-        #[error("a 'manifest' file was expected be found in '{0}'")]
-        NoManifest(Oid),
-        #[error("the 'manifest' found at '{0}' was not a blob")]
-        ManifestIsNotBlob(Oid),
-        #[error("the 'manifest' found at '{id}' was invalid: {err}")]
-        InvalidManifest {
-            id: Oid,
-            #[source]
-            err: serde_json::Error,
-        },
-        #[error("a 'change' file was expected be found in '{0}'")]
-        NoChange(Oid),
-        #[error("the 'change' found at '{0}' was not a blob")]
-        ChangeNotBlob(Oid),
-        #[error("the 'change' found at '{0}' was not signed")]
-        ChangeNotSigned(Oid),
-        #[error("the 'change' found at '{0}' has more than one signature")]
-        TooManySignatures(Oid),
-        #[error(transparent)]
-        ResourceTrailer(#[from] super::trailers::error::InvalidResourceTrailer),
-        #[error("non utf-8 characters in commit message")]
-        Utf8(#[from] FromUtf8Error),
-        #[error(transparent)]
-        Trailer(#[from] TrailerError),
-    }
-}
+pub use crate::change::error;
 
 impl change::Storage for git2::Repository {
     type StoreError = error::Create;
@@ -98,16 +49,40 @@ impl change::Storage for git2::Repository {
         let change::Template {
             typename,
             history_type,
+            version,
+            encoding,
             tips,
             message,
             contents,
+            encryption,
+            attachments,
+            snapshot,
         } = spec;
+        let contents = match &encryption {
+            Some(encryption) => encryption.encrypt(&contents)?,
+            None => contents,
+        };
+        let attachments = attachments
+            .into_iter()
+            .map(|(name, content)| -> Result<_, error::Create> {
+                attachment::validate(&name, &content)?;
+
+                match &encryption {
+                    Some(encryption) => Ok((name, encryption.encrypt_bytes(&content)?)),
+                    None => Ok((name, content)),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
         let manifest = store::Manifest {
             typename,
             history_type,
+            version,
+            encoding,
+            encryption,
+            snapshot,
         };
 
-        let revision = write_manifest(self, &manifest, &contents)?;
+        let (revision, attachments) = write_manifest(self, &manifest, &contents, attachments)?;
         let tree = self.find_tree(revision)?;
 
         let signature = {
@@ -121,10 +96,13 @@ impl change::Storage for git2::Repository {
             id,
             revision: revision.into(),
             signature,
+            cosignatures: Signatures::default(),
             resource,
             manifest,
             contents,
+            attachments,
             timestamp,
+            notarizations: Vec::new(),
         })
     }
 
@@ -132,29 +110,65 @@ impl change::Storage for git2::Repository {
         let commit = Commit::read(self, id.into())?;
         let timestamp = git2::Time::from(commit.committer().time).seconds() as u64;
         let resource = parse_resource_trailer(commit.trailers())?;
-        let mut signatures = Signatures::try_from(&commit)?
-            .into_iter()
-            .collect::<Vec<_>>();
-        let Some(signature) = signatures.pop() else {
+        let Some(signature) = Signature::all(&commit)?.into_iter().next() else {
             return Err(error::Load::ChangeNotSigned(id));
         };
-        if !signatures.is_empty() {
-            return Err(error::Load::TooManySignatures(id));
-        }
 
         let tree = self.find_tree(commit.tree())?;
         let manifest = load_manifest(self, &tree)?;
         let contents = load_contents(self, &tree)?;
+        let attachments = load_attachments(self, &tree)?;
 
-        Ok(Change {
+        let mut change = Change {
             id,
             revision: tree.id().into(),
-            signature: signature.into(),
+            signature,
+            cosignatures: Signatures::default(),
             resource,
             manifest,
             contents,
+            attachments,
             timestamp,
-        })
+            notarizations: Vec::new(),
+        };
+        change.attest(attestations(self, ATTESTATIONS_REF, id));
+
+        Ok(change)
+    }
+
+    fn attachment(&self, id: Self::ObjectId) -> Result<Vec<u8>, Self::LoadError> {
+        let blob = self.find_blob(id.into())?;
+
+        Ok(blob.content().to_owned())
+    }
+
+    fn cosign<Signer>(
+        &self,
+        id: Self::ObjectId,
+        signer: &Signer,
+    ) -> Result<Change, Self::StoreError>
+    where
+        Signer: crypto::Signer,
+    {
+        let mut change = self.load(id)?;
+        cosign(self, ATTESTATIONS_REF, &mut change, signer)?;
+
+        Ok(change)
+    }
+
+    fn notarize<Signer>(
+        &self,
+        id: Self::ObjectId,
+        signer: &Signer,
+        timestamp: Timestamp,
+    ) -> Result<Change, Self::StoreError>
+    where
+        Signer: crypto::Signer,
+    {
+        let mut change = self.load(id)?;
+        notarize(self, ATTESTATIONS_REF, &mut change, signer, timestamp)?;
+
+        Ok(change)
     }
 }
 
@@ -215,6 +229,25 @@ fn load_contents(
     NonEmpty::collect(ops.into_values()).ok_or_else(|| error::Load::NoChange(tree.id().into()))
 }
 
+fn load_attachments(
+    repo: &git2::Repository,
+    tree: &git2::Tree,
+) -> Result<Attachments, error::Load> {
+    let Some(entry) = tree.get_name(ATTACHMENTS_TREE_NAME) else {
+        return Ok(Attachments::default());
+    };
+    let object = entry.to_object(repo)?;
+    let attachments = object
+        .as_tree()
+        .ok_or_else(|| error::Load::AttachmentsNotTree(tree.id().into()))?;
+
+    Ok(attachments
+        .iter()
+        .filter(|entry| entry.kind() == Some(git2::ObjectType::Blob))
+        .filter_map(|entry| Some((entry.name()?.to_owned(), entry.id().into())))
+        .collect())
+}
+
 fn write_commit<O>(
     repo: &git2::Repository,
     resource: O,
@@ -270,11 +303,101 @@ where
     Ok((Oid::from(oid), timestamp as u64))
 }
 
+/// Load the attestations of a change stored under the given notes reference.
+///
+/// Notes that can't be read are ignored, since attestations don't affect the
+/// change itself.
+pub fn attestations(repo: &git2::Repository, notes_ref: &str, id: Oid) -> change::Attestations {
+    let note = match repo.find_note(Some(notes_ref), id.into()) {
+        Ok(note) => note,
+        Err(err) if err.code() == git2::ErrorCode::NotFound => return Default::default(),
+        Err(err) => {
+            log::warn!("unable to read attestations of change '{id}' in '{notes_ref}': {err}");
+            return Default::default();
+        }
+    };
+    serde_json::from_slice(note.message_bytes()).unwrap_or_else(|err| {
+        log::warn!("invalid attestations of change '{id}' in '{notes_ref}': {err}");
+        Default::default()
+    })
+}
+
+/// Co-sign a change, storing the co-signature under the given notes reference,
+/// along with the other attestations stored there. See [`change::Storage::cosign`].
+pub fn cosign<G>(
+    repo: &git2::Repository,
+    notes_ref: &str,
+    change: &mut Change,
+    signer: &G,
+) -> Result<(), error::Create>
+where
+    G: crypto::Signer,
+{
+    let key = signer.public_key();
+
+    if change.signers().any(|k| k == key) {
+        return Ok(());
+    }
+    let signature = signer.sign(change.revision.as_bytes());
+    let mut attestations = attestations(repo, notes_ref, change.id);
+
+    attestations.cosignatures.insert(*key, signature);
+    write_attestations(repo, notes_ref, change.id, &attestations)?;
+    change.cosignatures.insert(*key, signature);
+
+    Ok(())
+}
+
+/// Notarize a change at the given time, storing the notarization under the given
+/// notes reference, along with the other attestations stored there. See
+/// [`change::Storage::notarize`].
+pub fn notarize<G>(
+    repo: &git2::Repository,
+    notes_ref: &str,
+    change: &mut Change,
+    signer: &G,
+    timestamp: Timestamp,
+) -> Result<(), error::Create>
+where
+    G: crypto::Signer,
+{
+    let key = signer.public_key();
+
+    if change.notarizations.iter().any(|n| &n.key == key) {
+        return Ok(());
+    }
+    let notarization = Notarization::new(signer, change.revision.as_bytes(), timestamp);
+    let mut attestations = attestations(repo, notes_ref, change.id);
+
+    attestations.notarizations.push(notarization.clone());
+    write_attestations(repo, notes_ref, change.id, &attestations)?;
+    change.notarizations.push(notarization);
+
+    Ok(())
+}
+
+/// Replace the attestations of a change stored under the given notes reference.
+fn write_attestations(
+    repo: &git2::Repository,
+    notes_ref: &str,
+    id: Oid,
+    attestations: &change::Attestations,
+) -> Result<(), error::Create> {
+    let author = repo.signature()?;
+    // SAFETY: Attestations are always serializable.
+    let json = serde_json::to_string(attestations).unwrap();
+
+    repo.note(&author, &author, Some(notes_ref), id.into(), &json, true)?;
+
+    Ok(())
+}
+
 fn write_manifest(
     repo: &git2::Repository,
     manifest: &store::Manifest,
     contents: &entry::Contents,
-) -> Result<git2::Oid, git2::Error> {
+    attachments: Vec<(String, Vec<u8>)>,
+) -> Result<(git2::Oid, Attachments), git2::Error> {
     let mut tb = repo.treebuilder(None)?;
     // SAFETY: we're serializing to an in memory buffer so the only source of
     // errors here is a programming error, which we can't recover from
@@ -291,5 +414,22 @@ fn write_manifest(
         tb.insert(&ix.to_string(), change_blob, git2::FileMode::Blob.into())?;
     }
 
-    tb.write()
+    // Only write an attachments tree if there are attachments, so that changes
+    // without attachments keep the same layout.
+    let mut written = Vec::with_capacity(attachments.len());
+    if !attachments.is_empty() {
+        let mut atb = repo.treebuilder(None)?;
+        for (name, content) in attachments {
+            let blob = repo.blob(&content)?;
+            atb.insert(&name, blob, git2::FileMode::Blob.into())?;
+            written.push((name, blob.into()));
+        }
+        tb.insert(
+            ATTACHMENTS_TREE_NAME,
+            atb.write()?,
+            git2::FileMode::Tree.into(),
+        )?;
+    }
+
+    Ok((tb.write()?, written.into_iter().collect()))
 }
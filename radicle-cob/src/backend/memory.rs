@@ -0,0 +1,359 @@
+// Copyright © 2022 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! An in-memory storage backend.
+//!
+//! [`Memory`] implements both [`change::Storage`] and [`object::Storage`]
+//! without a `git` repository, so that collaborative objects can be used in
+//! unit tests, or as a starting point for other storage backends.
+//!
+//! Changes are content-addressed the same way as `git` objects, but their
+//! identifiers don't match the commits that the `git` backend would write
+//! for the same changes.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crypto::revocation::{self, Revocations, SignedRevocation};
+use crypto::PublicKey;
+use git_ext::Oid;
+use git_ref_format::RefString;
+
+use crate::change::error;
+use crate::history::entry::Timestamp;
+use crate::{
+    attachment::{self, Attachments},
+    change::{self, store, Change},
+    object::{self, Commit, Objects, Reference},
+    signatures::{Signature, Signatures},
+    Notarization, ObjectId, Store, TypeName,
+};
+
+/// A stored change, along with the changes it depends on.
+#[derive(Clone, Debug)]
+struct Node {
+    change: Change,
+    parents: Vec<Oid>,
+}
+
+/// Storage keeping changes, attachments and references in memory.
+///
+/// References are keyed by the [`PublicKey`] of the peer that updated them.
+#[derive(Debug, Default)]
+pub struct Memory {
+    changes: Mutex<HashMap<Oid, Node>>,
+    blobs: Mutex<HashMap<Oid, Vec<u8>>>,
+    refs: Mutex<BTreeMap<(TypeName, ObjectId, PublicKey), Oid>>,
+    revocations: Mutex<Revocations>,
+}
+
+impl Memory {
+    /// Create a new, empty storage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of changes stored.
+    pub fn len(&self) -> usize {
+        self.changes.lock().unwrap().len()
+    }
+
+    /// Whether no changes are stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Revoke a key. The references of the key are left as they are, and only the
+    /// changes reachable from them are accepted from then on. Returns `true` if the
+    /// revocations were updated.
+    pub fn revoke(&self, revocation: SignedRevocation) -> Result<bool, revocation::Error> {
+        self.revocations.lock().unwrap().insert(revocation)
+    }
+
+    fn commit(&self, id: Oid) -> Commit {
+        let parents = self
+            .changes
+            .lock()
+            .unwrap()
+            .get(&id)
+            .map(|node| node.parents.clone())
+            .unwrap_or_default();
+
+        Commit {
+            id,
+            parents: parents.into_iter().map(|p| self.commit(p)).collect(),
+        }
+    }
+
+    fn reference(
+        &self,
+        typename: &TypeName,
+        object_id: &ObjectId,
+        identifier: &PublicKey,
+        target: Oid,
+    ) -> Result<Reference, git_ref_format::Error> {
+        let name =
+            RefString::try_from(format!("refs/rad/{identifier}/cobs/{typename}/{object_id}"))?;
+
+        Ok(Reference {
+            name,
+            target: self.commit(target),
+        })
+    }
+}
+
+impl Store for Memory {
+    fn revocations(&self) -> Revocations {
+        self.revocations.lock().unwrap().clone()
+    }
+
+    fn owner(&self, reference: &Reference) -> Option<PublicKey> {
+        reference.name.as_str().split('/').nth(2)?.parse().ok()
+    }
+}
+
+impl change::Storage for Memory {
+    type StoreError = error::Create;
+    type LoadError = error::Load;
+
+    type ObjectId = Oid;
+    type Resource = Oid;
+    type Signatures = Signature;
+
+    fn store<Signer>(
+        &self,
+        resource: Self::Resource,
+        signer: &Signer,
+        spec: store::Template<Self::ObjectId>,
+    ) -> Result<Change, Self::StoreError>
+    where
+        Signer: crypto::Signer,
+    {
+        let change::Template {
+            typename,
+            history_type,
+            version,
+            encoding,
+            tips,
+            message: _,
+            contents,
+            encryption,
+            attachments,
+            snapshot,
+        } = spec;
+        let contents = match &encryption {
+            Some(encryption) => encryption.encrypt(&contents)?,
+            None => contents,
+        };
+        let mut blobs = Vec::with_capacity(attachments.len());
+        for (name, content) in attachments {
+            attachment::validate(&name, &content)?;
+
+            let content = match &encryption {
+                Some(encryption) => encryption.encrypt_bytes(&content)?,
+                None => content,
+            };
+            let id = Oid::from(git2::Oid::hash_object(git2::ObjectType::Blob, &content)?);
+            blobs.push((name, id, content));
+        }
+        let attachments = blobs
+            .iter()
+            .map(|(name, id, _)| (name.clone(), *id))
+            .collect::<Attachments>();
+        let manifest = store::Manifest {
+            typename,
+            history_type,
+            version,
+            encoding,
+            encryption,
+            snapshot,
+        };
+
+        // SAFETY: we're serializing to an in memory buffer so the only source of
+        // errors here is a programming error, which we can't recover from
+        let tree = serde_json::to_vec(&(&manifest, &contents, &attachments)).unwrap();
+        let revision = Oid::from(git2::Oid::hash_object(git2::ObjectType::Tree, &tree)?);
+        let signature = Signature::from((*signer.public_key(), signer.sign(revision.as_bytes())));
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        // The identifier is derived from the change itself, below.
+        let mut change = Change {
+            id: revision,
+            revision,
+            signature,
+            cosignatures: Signatures::default(),
+            resource,
+            manifest,
+            contents,
+            attachments,
+            timestamp,
+            notarizations: Vec::new(),
+        };
+        change.id = change_id(&change, &tips)?;
+
+        self.blobs
+            .lock()
+            .unwrap()
+            .extend(blobs.into_iter().map(|(_, id, content)| (id, content)));
+        self.changes.lock().unwrap().insert(
+            change.id,
+            Node {
+                change: change.clone(),
+                parents: tips,
+            },
+        );
+
+        Ok(change)
+    }
+
+    fn load(&self, id: Self::ObjectId) -> Result<Change, Self::LoadError> {
+        self.changes
+            .lock()
+            .unwrap()
+            .get(&id)
+            .map(|node| node.change.clone())
+            .ok_or(error::Load::NotFound(id))
+    }
+
+    fn attachment(&self, id: Self::ObjectId) -> Result<Vec<u8>, Self::LoadError> {
+        self.blobs
+            .lock()
+            .unwrap()
+            .get(&id)
+            .cloned()
+            .ok_or(error::Load::NotFound(id))
+    }
+
+    fn cosign<Signer>(
+        &self,
+        id: Self::ObjectId,
+        signer: &Signer,
+    ) -> Result<Change, Self::StoreError>
+    where
+        Signer: crypto::Signer,
+    {
+        let mut changes = self.changes.lock().unwrap();
+        let node = changes.get_mut(&id).ok_or(error::Load::NotFound(id))?;
+        let key = signer.public_key();
+
+        // Co-signatures are stored apart from the change, see `change::Attestations`.
+        if !node.change.signers().any(|k| k == key) {
+            let signature = signer.sign(node.change.revision.as_bytes());
+            node.change.cosignatures.insert(*key, signature);
+        }
+        Ok(node.change.clone())
+    }
+
+    fn notarize<Signer>(
+        &self,
+        id: Self::ObjectId,
+        signer: &Signer,
+        timestamp: Timestamp,
+    ) -> Result<Change, Self::StoreError>
+    where
+        Signer: crypto::Signer,
+    {
+        let mut changes = self.changes.lock().unwrap();
+        let node = changes.get_mut(&id).ok_or(error::Load::NotFound(id))?;
+        let change = &mut node.change;
+
+        // Notarizations are stored apart from the change, see `change::Attestations`.
+        if !change
+            .notarizations
+            .iter()
+            .any(|n| &n.key == signer.public_key())
+        {
+            let notarization = Notarization::new(signer, change.revision.as_bytes(), timestamp);
+            change.notarizations.push(notarization);
+        }
+        Ok(change.clone())
+    }
+}
+
+impl object::Storage for Memory {
+    type ObjectsError = git_ref_format::Error;
+    type TypesError = git_ref_format::Error;
+    type UpdateError = std::convert::Infallible;
+    type RemoveError = std::convert::Infallible;
+
+    type Identifier = PublicKey;
+
+    fn objects(
+        &self,
+        typename: &TypeName,
+        object_id: &ObjectId,
+    ) -> Result<Objects, Self::ObjectsError> {
+        let refs = self.refs.lock().unwrap().clone();
+        let references = refs
+            .iter()
+            .filter(|((t, o, _), _)| t == typename && o == object_id)
+            .map(|((t, o, i), target)| self.reference(t, o, i, *target))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(references.into())
+    }
+
+    fn types(&self, typename: &TypeName) -> Result<HashMap<ObjectId, Objects>, Self::TypesError> {
+        let refs = self.refs.lock().unwrap().clone();
+        let mut objects = HashMap::new();
+
+        for ((t, o, i), target) in refs.iter().filter(|((t, _, _), _)| t == typename) {
+            let reference = self.reference(t, o, i, *target)?;
+            objects
+                .entry(*o)
+                .and_modify(|objs: &mut Objects| objs.push(reference.clone()))
+                .or_insert_with(|| Objects::new(reference));
+        }
+        Ok(objects)
+    }
+
+    fn update(
+        &self,
+        identifier: &Self::Identifier,
+        typename: &TypeName,
+        object_id: &ObjectId,
+        change: &Change,
+    ) -> Result<(), Self::UpdateError> {
+        self.refs
+            .lock()
+            .unwrap()
+            .insert((typename.clone(), *object_id, *identifier), *change.id());
+
+        Ok(())
+    }
+
+    fn remove(
+        &self,
+        identifier: &Self::Identifier,
+        typename: &TypeName,
+        object_id: &ObjectId,
+    ) -> Result<(), Self::RemoveError> {
+        self.refs
+            .lock()
+            .unwrap()
+            .remove(&(typename.clone(), *object_id, *identifier));
+
+        Ok(())
+    }
+}
+
+/// Compute the identifier of a change, in the format of a commit header.
+fn change_id(change: &Change, parents: &[Oid]) -> Result<Oid, git2::Error> {
+    let mut header = format!("tree {}\n", change.revision);
+    for parent in parents {
+        header.push_str(&format!("parent {parent}\n"));
+    }
+    header.push_str(&format!(
+        "resource {}\ntime {}\n",
+        change.resource, change.timestamp
+    ));
+    header.push_str(&format!("signer {}\n", change.signature.key()));
+    let id = git2::Oid::hash_object(git2::ObjectType::Commit, header.as_bytes())?;
+
+    Ok(id.into())
+}
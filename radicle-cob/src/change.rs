@@ -5,8 +5,9 @@
 
 use git_ext::Oid;
 
+pub mod error;
 pub mod store;
-pub use store::{Storage, Template};
+pub use store::{Attestations, Storage, Template};
 
 use crate::signatures::Signature;
 
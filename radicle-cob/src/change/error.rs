@@ -0,0 +1,71 @@
+// Copyright © 2022 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! Errors of [`super::Storage`] backends.
+
+use std::str::Utf8Error;
+use std::string::FromUtf8Error;
+
+use git_ext::Oid;
+use git_trailers::Error as TrailerError;
+use thiserror::Error;
+
+use crate::signatures::error::Signatures;
+
+#[derive(Debug, Error)]
+pub enum Create {
+    #[error(transparent)]
+    FromUtf8(#[from] FromUtf8Error),
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+    #[error(transparent)]
+    Signer(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    Utf8(#[from] Utf8Error),
+    #[error(transparent)]
+    Encryption(#[from] crate::encryption::error::Encryption),
+    #[error(transparent)]
+    Attachment(#[from] crate::attachment::error::Attachment),
+    #[error(transparent)]
+    Load(#[from] Load),
+    #[error(transparent)]
+    Read(#[from] git_commit::error::Read),
+}
+
+#[derive(Debug, Error)]
+pub enum Load {
+    #[error(transparent)]
+    Read(#[from] git_commit::error::Read),
+    #[error(transparent)]
+    Signatures(#[from] Signatures),
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+    #[error("the change '{0}' was not found")]
+    NotFound(Oid),
+    #[error("a 'manifest' file was expected be found in '{0}'")]
+    NoManifest(Oid),
+    #[error("the 'manifest' found at '{0}' was not a blob")]
+    ManifestIsNotBlob(Oid),
+    #[error("the 'manifest' found at '{id}' was invalid: {err}")]
+    InvalidManifest {
+        id: Oid,
+        #[source]
+        err: serde_json::Error,
+    },
+    #[error("a 'change' file was expected be found in '{0}'")]
+    NoChange(Oid),
+    #[error("the 'change' found at '{0}' was not a blob")]
+    ChangeNotBlob(Oid),
+    #[error("the 'change' found at '{0}' was not signed")]
+    ChangeNotSigned(Oid),
+    #[error("the 'attachments' found at '{0}' were not a tree")]
+    AttachmentsNotTree(Oid),
+    #[error(transparent)]
+    ResourceTrailer(#[from] crate::trailers::error::InvalidResourceTrailer),
+    #[error("non utf-8 characters in commit message")]
+    Utf8(#[from] FromUtf8Error),
+    #[error(transparent)]
+    Trailer(#[from] TrailerError),
+}
@@ -3,13 +3,15 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::{error::Error, fmt};
+use std::{collections::BTreeMap, error::Error, fmt};
 
+use crypto::PublicKey;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    history::{Contents, Timestamp},
-    signatures, TypeName,
+    encoding::Typed,
+    history::{Clock, Contents, Timestamp},
+    signatures, Attachments, Encoding, Encryption, Notarization, TypeName,
 };
 
 /// Change storage.
@@ -38,15 +40,57 @@ pub trait Storage {
         &self,
         id: Self::ObjectId,
     ) -> Result<Change<Self::Resource, Self::ObjectId, Self::Signatures>, Self::LoadError>;
+
+    /// Load the contents of an attachment, given its identifier from [`Change::attachments`].
+    fn attachment(&self, id: Self::ObjectId) -> Result<Vec<u8>, Self::LoadError>;
+
+    /// Co-sign an existing change, eg. a delegate countersigning a contributor's change.
+    ///
+    /// The co-signature is stored separately from the change, keyed by its identifier,
+    /// see [`Attestations`]. The change and the changes depending on it are left
+    /// untouched. Returns the change along with its co-signatures.
+    #[allow(clippy::type_complexity)]
+    fn cosign<G>(
+        &self,
+        id: Self::ObjectId,
+        signer: &G,
+    ) -> Result<Change<Self::Resource, Self::ObjectId, Self::Signatures>, Self::StoreError>
+    where
+        G: crypto::Signer;
+
+    /// Notarize an existing change, attesting that it was seen at the given time.
+    ///
+    /// Like co-signatures, the notarization is stored separately from the change,
+    /// see [`Attestations`]. Returns the change along with its notarizations.
+    #[allow(clippy::type_complexity)]
+    fn notarize<G>(
+        &self,
+        id: Self::ObjectId,
+        signer: &G,
+        timestamp: Timestamp,
+    ) -> Result<Change<Self::Resource, Self::ObjectId, Self::Signatures>, Self::StoreError>
+    where
+        G: crypto::Signer;
 }
 
 /// Change template, used to create a new change.
 pub struct Template<Id> {
     pub typename: TypeName,
     pub history_type: String,
+    /// Version of the encoding of the contents, for the history type.
+    pub version: u32,
+    /// Encoding of the contents.
+    pub encoding: Encoding,
     pub tips: Vec<Id>,
     pub message: String,
     pub contents: Contents,
+    /// Encryption of the contents, if any.
+    pub encryption: Option<Encryption>,
+    /// Named attachments to store with the change.
+    pub attachments: BTreeMap<String, Vec<u8>>,
+    /// The logical clock of the change, if it is a snapshot of the changes it descends
+    /// from. See [`Manifest::snapshot`].
+    pub snapshot: Option<Clock>,
 }
 
 #[derive(Clone, Debug)]
@@ -58,6 +102,10 @@ pub struct Change<Resource, Id, Signature> {
     /// The cryptographic signature(s) and their public keys of the
     /// authors.
     pub signature: Signature,
+    /// Additional signatures over the change, by keys other than the author's. These
+    /// are stored separately from the change, and aren't verified on load, see
+    /// [`Change::cosigners`].
+    pub cosignatures: signatures::Signatures,
     /// The parent resource that this change lives under. For example,
     /// this change could be for a patch of a project.
     pub resource: Resource,
@@ -66,8 +114,14 @@ pub struct Change<Resource, Id, Signature> {
     pub manifest: Manifest,
     /// The contents that describe `Change`.
     pub contents: Contents,
+    /// The attachments of the `Change`. Their contents are loaded separately.
+    pub attachments: Attachments,
     /// Timestamp of change.
     pub timestamp: Timestamp,
+    /// Attestations by third parties that they had seen the change. These are
+    /// stored separately from the change, and aren't verified on load, see
+    /// [`Change::notarizations`].
+    pub notarizations: Vec<Notarization>,
 }
 
 impl<Resource, Id, S> fmt::Display for Change<Resource, Id, S>
@@ -92,9 +146,43 @@ impl<Resource, Id, Signatures> Change<Resource, Id, Signatures> {
         &self.contents
     }
 
+    /// The contents, along with their encoding.
+    pub fn typed_contents(&self) -> Typed<'_> {
+        Typed::new(self.manifest.encoding, &self.contents)
+    }
+
     pub fn resource(&self) -> &Resource {
         &self.resource
     }
+
+    pub fn attachments(&self) -> &Attachments {
+        &self.attachments
+    }
+
+    /// Keys that validly co-signed this change, besides its author. Invalid
+    /// co-signatures are ignored rather than rejecting the change, since anyone
+    /// can attach them to it.
+    pub fn cosigners(&self) -> impl Iterator<Item = &PublicKey>
+    where
+        Id: AsRef<[u8]>,
+    {
+        self.cosignatures
+            .iter()
+            .filter(|(key, sig)| key.verify(self.revision.as_ref(), sig).is_ok())
+            .map(|(key, _)| key)
+    }
+
+    /// Notarizations of this change that are valid for its revision. Invalid
+    /// notarizations are ignored rather than rejecting the change, since they
+    /// don't affect its contents.
+    pub fn notarizations(&self) -> impl Iterator<Item = &Notarization>
+    where
+        Id: AsRef<[u8]>,
+    {
+        self.notarizations
+            .iter()
+            .filter(|n| n.verify(self.revision.as_ref()))
+    }
 }
 
 impl<R, Id> Change<R, Id, signatures::Signatures>
@@ -115,6 +203,45 @@ where
     pub fn valid_signatures(&self) -> bool {
         self.signature.verify(self.revision.as_ref())
     }
+
+    /// The author of the change, followed by its co-signers.
+    pub fn signers(&self) -> impl Iterator<Item = &PublicKey> {
+        std::iter::once(self.signature.key()).chain(self.cosigners())
+    }
+
+    /// Add attestations of this change, eg. loaded from another location. Co-signatures
+    /// by the author of the change and duplicate notarizations are ignored.
+    pub fn attest(&mut self, attestations: Attestations) {
+        let author = *self.signature.key();
+
+        self.cosignatures.extend(
+            attestations
+                .cosignatures
+                .into_iter()
+                .filter(|(key, _)| *key != author),
+        );
+        for notarization in attestations.notarizations {
+            if !self.notarizations.contains(&notarization) {
+                self.notarizations.push(notarization);
+            }
+        }
+    }
+}
+
+/// Attestations of a change by other keys than its author.
+///
+/// Attestations are stored separately from the change, keyed by its identifier,
+/// so that attesting a change doesn't change its identifier, which would orphan
+/// the changes depending on it. Since anyone can attach attestations to a change,
+/// they are verified when they are used, and invalid ones are ignored.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attestations {
+    /// Signatures over the change revision.
+    #[serde(default)]
+    pub cosignatures: signatures::Signatures,
+    /// Notarizations of the change revision.
+    #[serde(default)]
+    pub notarizations: Vec<Notarization>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -123,4 +250,23 @@ pub struct Manifest {
     pub typename: TypeName,
     /// The type of history for the collaborative oject.
     pub history_type: String,
+    /// The version of the encoding of the change contents, for its history type.
+    /// This is incremented whenever the encoding changes, so that changes written
+    /// with an older encoding can be migrated. Changes written before versioning
+    /// was introduced have version `0`.
+    #[serde(default)]
+    pub version: u32,
+    /// The encoding of the change contents. Changes written before encodings were
+    /// declared are JSON-encoded.
+    #[serde(default, skip_serializing_if = "Encoding::is_json")]
+    pub encoding: Encoding,
+    /// The encryption of the change contents, if they are encrypted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<Encryption>,
+    /// If set, the change contents are a snapshot of the changes it descends from, and
+    /// this is the logical clock of the change. Since the changes covered by a snapshot
+    /// aren't always loaded, its clock can't be computed from its ancestors. See
+    /// [`crate::squash`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<Clock>,
 }
@@ -3,17 +3,22 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::{collections::BTreeSet, convert::TryInto};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    convert::TryInto,
+};
 
+use crypto::{revocation::Revocations, PublicKey};
 use git_ext::Oid;
-use radicle_dag::{Dag, Node};
+use radicle_dag::Dag;
 
 use crate::{
-    change, object, signatures::Signature, Change, CollaborativeObject, ObjectId, TypeName,
+    change, object, signatures::Signature, Change, CollaborativeObject, ObjectId, Schemas, TypeName,
 };
 
 mod evaluation;
 use evaluation::evaluate;
+pub(crate) use evaluation::evaluate_change;
 
 /// The graph of changes for a particular collaborative object
 pub(super) struct ChangeGraph {
@@ -23,7 +28,8 @@ pub(super) struct ChangeGraph {
 
 impl ChangeGraph {
     /// Load the change graph from the underlying git store by walking
-    /// backwards from references to the object
+    /// backwards from references to the object. The changes replaced by a
+    /// snapshot are only loaded if the snapshot isn't the single root of the graph.
     pub(crate) fn load<'a, S>(
         storage: &S,
         tip_refs: impl Iterator<Item = &'a object::Reference> + 'a,
@@ -36,6 +42,10 @@ impl ChangeGraph {
         log::info!("loading object '{}' '{}'", typename, oid);
         let mut builder = GraphBuilder::default();
         let mut edges_to_process: Vec<(object::Commit, Oid)> = Vec::new();
+        // Edges to the parents of snapshots, which are only loaded if the snapshots
+        // don't cover the whole graph.
+        let mut deferred: Vec<(object::Commit, Oid)> = Vec::new();
+        let mut defer = true;
 
         // Populate the initial set of edges_to_process from the refs we have
         for reference in tip_refs {
@@ -43,8 +53,12 @@ impl ChangeGraph {
             match storage.load(reference.target.id) {
                 Ok(change) => {
                     let commit = reference.target.clone();
-                    let new_edges = builder.add_change(commit, change);
-                    edges_to_process.extend(new_edges);
+                    let queue = if defer && change.manifest.snapshot.is_some() {
+                        &mut deferred
+                    } else {
+                        &mut edges_to_process
+                    };
+                    queue.extend(builder.add_change(commit, change));
                 }
                 Err(e) => {
                     log::warn!(
@@ -58,48 +72,89 @@ impl ChangeGraph {
         }
 
         // Process edges until we have no more to process
-        while let Some((parent_commit, child_commit_id)) = edges_to_process.pop() {
-            log::trace!(
-                "loading change parent='{}', child='{}'",
-                parent_commit.id,
-                child_commit_id
-            );
-            match storage.load(parent_commit.id) {
-                Ok(change) => {
-                    let parent_commit_id = parent_commit.id;
-                    let new_edges = builder.add_change(parent_commit, change);
-                    edges_to_process.extend(new_edges);
+        loop {
+            while let Some((parent_commit, child_commit_id)) = edges_to_process.pop() {
+                log::trace!(
+                    "loading change parent='{}', child='{}'",
+                    parent_commit.id,
+                    child_commit_id
+                );
+                let parent_commit_id = parent_commit.id;
+
+                if builder.graph.contains(&parent_commit_id) {
                     builder.add_edge(child_commit_id, parent_commit_id);
+                    continue;
                 }
-                Err(e) => {
-                    log::warn!(
-                        "unable to load changetree from commit '{}', error '{}'",
-                        parent_commit.id,
-                        e
-                    );
+                match storage.load(parent_commit_id) {
+                    Ok(change) => {
+                        let queue = if defer && change.manifest.snapshot.is_some() {
+                            &mut deferred
+                        } else {
+                            &mut edges_to_process
+                        };
+                        queue.extend(builder.add_change(parent_commit, change));
+                        builder.add_edge(child_commit_id, parent_commit_id);
+                    }
+                    Err(e) => {
+                        log::warn!(
+                            "unable to load changetree from commit '{}', error '{}'",
+                            parent_commit_id,
+                            e
+                        );
+                    }
                 }
             }
+            // If a single snapshot is the root of the graph, the changes it replaced
+            // aren't needed. Otherwise, load the whole history.
+            if deferred.is_empty() || snapshot_root(&builder.graph).is_some() {
+                break;
+            }
+            edges_to_process.append(&mut deferred);
+            defer = false;
         }
         builder.build(*oid)
     }
 
     /// Given a graph evaluate it to produce a collaborative object. This will
-    /// filter out branches of the graph which do not have valid signatures.
-    pub(crate) fn evaluate(&self) -> CollaborativeObject {
-        let mut roots: Vec<(&Oid, &Node<_, _>)> = self.graph.roots().collect();
-        roots.sort_by_key(|(k, _)| *k);
-        // This is okay because we check that the graph has a root node in
-        // GraphBuilder::build
-        let (root, root_node) = roots.first().unwrap();
-        let manifest = root_node.manifest.clone();
+    /// filter out branches of the graph which do not have valid signatures,
+    /// which were signed by a revoked key, or whose contents are invalid.
+    /// Returns `None` if the graph doesn't start from the object's root or a snapshot.
+    ///
+    /// The `owners` are the keys whose references point to each tip, see
+    /// [`crate::Store::owner`].
+    pub(crate) fn evaluate(
+        &self,
+        owners: impl IntoIterator<Item = (PublicKey, Oid)>,
+        revocations: &Revocations,
+        schemas: &Schemas,
+    ) -> Option<CollaborativeObject> {
+        let root = self.root()?;
+        let manifest = self.graph[&root].manifest.clone();
         let rng = fastrand::Rng::new();
-        let history = evaluate(*self.graph[*root].id(), &self.graph, rng);
+        let frozen = Frozen::new(owners, revocations, |oid| {
+            self.graph
+                .get(oid)
+                .map(|node| node.dependencies.iter().copied().collect())
+                .unwrap_or_default()
+        });
+        let history = evaluate(root, &self.graph, revocations, &frozen, schemas, rng);
 
-        CollaborativeObject {
+        Some(CollaborativeObject {
             manifest,
             history,
             id: self.object_id,
+        })
+    }
+
+    /// The change the history starts from: the change that created the object, or
+    /// a snapshot that replaced it, if it's the only root. Other roots are ignored.
+    fn root(&self) -> Option<Oid> {
+        let root = Oid::from(self.object_id);
+
+        if self.graph.roots().any(|(oid, _)| *oid == root) {
+            return Some(root);
         }
+        snapshot_root(&self.graph)
     }
 
     /// Get the tips of the collaborative object
@@ -112,6 +167,62 @@ impl ChangeGraph {
     }
 }
 
+/// The changes that revoked keys are still trusted for.
+///
+/// Once a revocation is known, storage no longer updates the references of the revoked
+/// key, see `Repository::verify_frozen`. The changes reachable from those references are
+/// therefore the ones we had before the revocation was known, and are the only changes
+/// signed by the revoked key that are accepted. Change timestamps are chosen by their
+/// signer, and can't be used for this.
+#[derive(Debug, Default)]
+pub(crate) struct Frozen(HashMap<PublicKey, HashSet<Oid>>);
+
+impl Frozen {
+    /// Collect the changes reachable from the references of revoked keys, given the
+    /// owner of each reference and its target, and the parents of each change.
+    pub(crate) fn new<F>(
+        owners: impl IntoIterator<Item = (PublicKey, Oid)>,
+        revocations: &Revocations,
+        parents: F,
+    ) -> Self
+    where
+        F: Fn(&Oid) -> Vec<Oid>,
+    {
+        let mut frozen = HashMap::<PublicKey, HashSet<Oid>>::new();
+
+        for (key, tip) in owners {
+            if !revocations.is_revoked(&key) {
+                continue;
+            }
+            let reachable = frozen.entry(key).or_default();
+            let mut stack = vec![tip];
+
+            while let Some(oid) = stack.pop() {
+                if reachable.insert(oid) {
+                    stack.extend(parents(&oid));
+                }
+            }
+        }
+        Self(frozen)
+    }
+
+    /// Whether the given change is trusted for the given revoked key.
+    pub(crate) fn contains(&self, key: &PublicKey, change: &Oid) -> bool {
+        self.0
+            .get(key)
+            .map_or(false, |changes| changes.contains(change))
+    }
+}
+
+/// The root of the graph, if there is only one and it is a snapshot.
+fn snapshot_root(graph: &Dag<Oid, Change>) -> Option<Oid> {
+    let mut roots = graph.roots();
+    match (roots.next(), roots.next()) {
+        (Some((oid, change)), None) if change.manifest.snapshot.is_some() => Some(*oid),
+        _ => None,
+    }
+}
+
 struct GraphBuilder {
     graph: Dag<Oid, Change>,
 }
@@ -5,17 +5,26 @@
 
 use std::{collections::HashMap, ops::ControlFlow};
 
+use crypto::revocation::Revocations;
 use git_ext::Oid;
 use radicle_dag::Dag;
 
+use super::Frozen;
 use crate::history::entry::{EntryId, EntryWithClock};
 use crate::history::Clock;
-use crate::{change::Change, history, pruning_fold};
+use crate::{change::Change, history, pruning_fold, schema, Schemas};
 
 /// # Panics
 ///
 /// If the change corresponding to the root OID is not in `items`
-pub fn evaluate(root: Oid, graph: &Dag<Oid, Change>, rng: fastrand::Rng) -> history::History {
+pub fn evaluate(
+    root: Oid,
+    graph: &Dag<Oid, Change>,
+    revocations: &Revocations,
+    frozen: &Frozen,
+    schemas: &Schemas,
+    rng: fastrand::Rng,
+) -> history::History {
     let entries = pruning_fold::pruning_fold(
         HashMap::<EntryId, EntryWithClock>::new(),
         graph.sorted(rng).into_iter().map(|oid| {
@@ -28,7 +37,13 @@ pub fn evaluate(root: Oid, graph: &Dag<Oid, Change>, rng: fastrand::Rng) -> hist
                 child_commits,
             }
         }),
-        |mut entries, c| match evaluate_change(c.change, &c.child_commits) {
+        |mut entries, c| match evaluate_change(
+            c.change,
+            &c.child_commits,
+            revocations,
+            frozen,
+            schemas,
+        ) {
             Err(RejectionReason::InvalidSignatures) => {
                 log::warn!(
                     "rejecting change '{}' because its signatures were invalid",
@@ -36,9 +51,22 @@ pub fn evaluate(root: Oid, graph: &Dag<Oid, Change>, rng: fastrand::Rng) -> hist
                 );
                 ControlFlow::Break(entries)
             }
+            Err(RejectionReason::RevokedKey) => {
+                log::warn!(
+                    "rejecting change '{}' because it was signed by a revoked key",
+                    c.change.id(),
+                );
+                ControlFlow::Break(entries)
+            }
+            Err(RejectionReason::InvalidContents(err)) => {
+                log::warn!("rejecting change '{}': {err}", c.change.id());
+                ControlFlow::Break(entries)
+            }
             Ok(entry) => {
                 // Get parent commits and calculate this node's clock based on theirs.
-                let clock = graph[&c.oid]
+                // Snapshots record their clock, since their parents may not be loaded.
+                let clock = c.change.manifest.snapshot.unwrap_or_else(|| {
+                    graph[&c.oid]
                     .dependencies
                     .iter()
                     .map(|e| {
@@ -49,7 +77,8 @@ pub fn evaluate(root: Oid, graph: &Dag<Oid, Change>, rng: fastrand::Rng) -> hist
                     })
                     .max()
                     .unwrap_or_default() // When there are no operations, the clock is zero.
-                    + 1;
+                    + 1
+                });
                 log::trace!("change '{}' accepted", c.change.id());
 
                 entries.insert(*entry.id(), EntryWithClock { entry, clock });
@@ -59,17 +88,37 @@ pub fn evaluate(root: Oid, graph: &Dag<Oid, Change>, rng: fastrand::Rng) -> hist
         },
     );
     // SAFETY: The caller must guarantee that `root` is in `items`
-    history::History::new(root, entries).unwrap()
+    let mut history = history::History::new(root, entries).unwrap();
+    history.start_from_snapshot();
+    history
 }
 
-fn evaluate_change(
+/// Check a change and convert it to a history entry, given the commits of the
+/// changes that depend on it.
+pub(crate) fn evaluate_change(
     change: &Change,
     child_commits: &[Oid],
+    revocations: &Revocations,
+    frozen: &Frozen,
+    schemas: &Schemas,
 ) -> Result<history::Entry, RejectionReason> {
     // Check the change signatures are valid
     if !change.valid_signatures() {
         return Err(RejectionReason::InvalidSignatures);
     };
+    // Check that changes signed by revoked keys were known before the revocation.
+    // Co-signatures are stored apart from the change, and don't affect it.
+    let author = change.signature.key();
+    if revocations.is_revoked(author) && !frozen.contains(author, change.id()) {
+        return Err(RejectionReason::RevokedKey);
+    }
+    // Check the change contents are valid for its type. Encrypted contents can't be
+    // validated until they are decrypted.
+    if change.manifest.encryption.is_none() {
+        schemas
+            .validate(change.typename(), change.contents())
+            .map_err(RejectionReason::InvalidContents)?;
+    }
 
     Ok(history::Entry::new(
         *change.id(),
@@ -78,7 +127,12 @@ fn evaluate_change(
         child_commits.iter().cloned(),
         change.contents().clone(),
         change.timestamp,
-    ))
+    )
+    .with_attachments(change.attachments.clone())
+    .with_version(change.manifest.version)
+    .with_encoding(change.manifest.encoding)
+    .with_snapshot(change.manifest.snapshot.is_some())
+    .with_notarizations(change.notarizations().cloned().collect()))
 }
 
 struct ChangeWithChildren<'a> {
@@ -100,6 +154,8 @@ impl<'a> pruning_fold::GraphNode for ChangeWithChildren<'a> {
 }
 
 #[derive(Debug)]
-enum RejectionReason {
+pub(crate) enum RejectionReason {
     InvalidSignatures,
+    RevokedKey,
+    InvalidContents(schema::error::Invalid),
 }
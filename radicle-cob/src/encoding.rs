@@ -0,0 +1,109 @@
+// Copyright © 2022 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! Encoding of change contents.
+//!
+//! Each element of the [`Contents`] of a change is an opaque byte string. The
+//! [`Encoding`] of these byte strings is declared in the change
+//! [`crate::change::store::Manifest`], so that they can be decoded with
+//! [`Typed::decode`], rather than every object type assuming an encoding.
+
+use std::io;
+
+use nonempty::NonEmpty;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::history::Contents;
+
+pub mod error {
+    use std::io;
+
+    use thiserror::Error;
+
+    #[derive(Debug, Error)]
+    pub enum Encoding {
+        #[error("invalid JSON contents: {0}")]
+        Json(#[from] serde_json::Error),
+        #[error("invalid CBOR contents: {0}")]
+        CborDecode(#[from] ciborium::de::Error<io::Error>),
+        #[error("unable to encode CBOR contents: {0}")]
+        CborEncode(#[from] ciborium::ser::Error<io::Error>),
+    }
+}
+
+/// The encoding of change contents.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    /// JSON, the encoding of changes written before encodings were declared.
+    #[default]
+    Json,
+    /// CBOR, see RFC 8949. More compact than JSON.
+    Cbor,
+}
+
+impl Encoding {
+    /// Whether this is the [`Encoding::Json`] encoding.
+    pub fn is_json(&self) -> bool {
+        matches!(self, Self::Json)
+    }
+
+    /// Encode a value.
+    pub fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, error::Encoding> {
+        match self {
+            Self::Json => Ok(serde_json::to_vec(value)?),
+            Self::Cbor => {
+                let mut buf = Vec::new();
+                ciborium::ser::into_writer(value, &mut buf)?;
+
+                Ok(buf)
+            }
+        }
+    }
+
+    /// Decode a value.
+    pub fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, error::Encoding> {
+        match self {
+            Self::Json => Ok(serde_json::from_slice(bytes)?),
+            Self::Cbor => Ok(ciborium::de::from_reader(io::Cursor::new(bytes))?),
+        }
+    }
+}
+
+/// Change contents, along with their encoding.
+#[derive(Clone, Copy, Debug)]
+pub struct Typed<'a> {
+    encoding: Encoding,
+    contents: &'a Contents,
+}
+
+impl<'a> Typed<'a> {
+    pub fn new(encoding: Encoding, contents: &'a Contents) -> Self {
+        Self { encoding, contents }
+    }
+
+    /// The encoding of the contents.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// The raw contents.
+    pub fn raw(&self) -> &'a Contents {
+        self.contents
+    }
+
+    /// Decode every element of the contents.
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<NonEmpty<T>, error::Encoding> {
+        let head = self.encoding.decode(&self.contents.head)?;
+        let tail = self
+            .contents
+            .tail
+            .iter()
+            .map(|bytes| self.encoding.decode(bytes))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(NonEmpty::from((head, tail)))
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright © 2022 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! Encryption of change contents.
+//!
+//! The contents of the changes of an encrypted object are stored as
+//! ciphertext only. The [`Encryption`] scheme and its recipients are
+//! recorded in the object's [`crate::change::store::Manifest`], so that
+//! non-recipients can skip the object without attempting to decrypt it.
+
+use std::collections::BTreeSet;
+
+use crypto::seal::Envelope;
+use crypto::{PublicKey, SecretKey};
+use nonempty::nonempty;
+use serde::{Deserialize, Serialize};
+
+use crate::history::Contents;
+
+pub mod error {
+    use thiserror::Error;
+
+    #[derive(Debug, Error)]
+    pub enum Encryption {
+        #[error(transparent)]
+        Seal(#[from] crypto::seal::Error),
+        #[error("invalid encrypted contents: {0}")]
+        Encoding(#[from] serde_json::Error),
+    }
+}
+
+/// An encryption scheme for change contents.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "scheme")]
+pub enum Encryption {
+    /// The contents of each change are sealed in a single [`Envelope`],
+    /// for the given recipients.
+    #[serde(rename = "radicle-seal-v1")]
+    Seal { recipients: BTreeSet<PublicKey> },
+}
+
+impl Encryption {
+    /// Encrypt contents to the given recipients.
+    pub fn seal(recipients: impl IntoIterator<Item = PublicKey>) -> Self {
+        Self::Seal {
+            recipients: recipients.into_iter().collect(),
+        }
+    }
+
+    /// The recipients of encrypted contents.
+    pub fn recipients(&self) -> impl Iterator<Item = &PublicKey> {
+        match self {
+            Self::Seal { recipients } => recipients.iter(),
+        }
+    }
+
+    /// Check whether the given key can decrypt contents.
+    pub fn is_recipient(&self, key: &PublicKey) -> bool {
+        match self {
+            Self::Seal { recipients } => recipients.contains(key),
+        }
+    }
+
+    /// Encrypt change contents.
+    pub fn encrypt(&self, contents: &Contents) -> Result<Contents, error::Encryption> {
+        let plaintext = serde_json::to_vec(contents)?;
+
+        Ok(nonempty![self.encrypt_bytes(&plaintext)?])
+    }
+
+    /// Decrypt change contents encrypted with [`Encryption::encrypt`], using the
+    /// secret key of one of the recipients.
+    pub fn decrypt(
+        &self,
+        secret: &SecretKey,
+        contents: &Contents,
+    ) -> Result<Contents, error::Encryption> {
+        let plaintext = self.decrypt_bytes(secret, &contents.head)?;
+
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    /// Encrypt arbitrary bytes, eg. the contents of an attachment.
+    pub fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<Vec<u8>, error::Encryption> {
+        match self {
+            Self::Seal { recipients } => {
+                let envelope = Envelope::seal(recipients, plaintext)?;
+
+                Ok(serde_json::to_vec(&envelope)?)
+            }
+        }
+    }
+
+    /// Decrypt bytes encrypted with [`Encryption::encrypt_bytes`].
+    pub fn decrypt_bytes(
+        &self,
+        secret: &SecretKey,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, error::Encryption> {
+        match self {
+            Self::Seal { .. } => {
+                let envelope: Envelope = serde_json::from_slice(ciphertext)?;
+
+                Ok(envelope.open(secret)?)
+            }
+        }
+    }
+}
@@ -4,7 +4,8 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 use std::{
-    collections::{BTreeSet, HashMap},
+    cmp::Reverse,
+    collections::{BTreeSet, BinaryHeap, HashMap, HashSet},
     ops::ControlFlow,
 };
 
@@ -12,11 +13,15 @@ use git_ext::Oid;
 use radicle_crypto::PublicKey;
 use radicle_dag::Dag;
 
-use crate::pruning_fold;
+use crate::object::collaboration::error;
+use crate::{pruning_fold, Attachments, Change, Encoding, ObjectId, Store, TypeName};
 
 pub mod entry;
 pub use entry::{Clock, Contents, Entry, EntryId, EntryWithClock, Timestamp};
 
+mod ops;
+pub use ops::IterOps;
+
 /// The DAG of changes making up the history of a collaborative object.
 #[derive(Clone, Debug)]
 pub struct History {
@@ -56,6 +61,11 @@ impl History {
             children: vec![],
             contents,
             timestamp,
+            attachments: Attachments::default(),
+            version: 0,
+            encoding: Encoding::default(),
+            notarizations: Vec::new(),
+            snapshot: false,
         };
         let mut entries = HashMap::new();
         entries.insert(id, EntryWithClock::root(root_entry));
@@ -101,17 +111,60 @@ impl History {
     /// accumulator value of type `A`. However, unlike `fold` the function `f`
     /// may prune branches from the dependency graph by returning
     /// `ControlFlow::Break`.
+    ///
+    /// Concurrent changes are traversed in order of timestamp, and then entry id, so that
+    /// the traversal order is stable.
     pub fn traverse<F, A>(&self, init: A, f: F) -> A
     where
         F: for<'r> FnMut(A, &'r EntryWithClock) -> ControlFlow<A, A>,
     {
-        let items = self
-            .graph
-            .sorted(fastrand::Rng::new())
-            .into_iter()
-            .map(|idx| &self.graph[&idx]);
+        pruning_fold::pruning_fold(init, self.iter(), f)
+    }
 
-        pruning_fold::pruning_fold(init, items, f)
+    /// Iterate over the changes of this history in topological order, like
+    /// [`History::traverse`]. Changes are ordered lazily, as the iterator is advanced.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter::new(self)
+    }
+
+    /// Iterate over the changes of an object in reverse topological order, ie. latest
+    /// first, reading them from storage as the iterator is advanced. Concurrent changes
+    /// are ordered by timestamp, and then entry id, latest first.
+    ///
+    /// Unlike [`CollaborativeObject`](crate::CollaborativeObject), no history is built
+    /// up front, so consumers that only need the latest changes, eg. the current value
+    /// of a register, don't pay for loading the whole object. Changes are checked the
+    /// same way as when loading an object, but since they are yielded before their
+    /// ancestors, changes that depend on a rejected change aren't pruned. The changes
+    /// replaced by a snapshot aren't yielded, see [`Entry::is_snapshot`]. Entries
+    /// yielded by this iterator have no logical clock, and the contents of encrypted
+    /// objects are yielded as ciphertext.
+    pub fn iter_ops<'a, S: Store>(
+        storage: &'a S,
+        typename: &TypeName,
+        object_id: &ObjectId,
+    ) -> Result<IterOps<'a, S>, error::Retrieve> {
+        let refs = storage
+            .objects(typename, object_id)
+            .map_err(|err| error::Retrieve::Refs { err: Box::new(err) })?;
+
+        Ok(IterOps::new(storage, &refs))
+    }
+
+    /// Return the number of changes in this history.
+    pub fn len(&self) -> usize {
+        self.graph.len()
+    }
+
+    /// Check whether this history has no changes.
+    pub fn is_empty(&self) -> bool {
+        self.graph.is_empty()
+    }
+
+    /// The root entry of this history, ie. the change that created the object, or
+    /// the snapshot that replaced its history if it was squashed.
+    pub fn root(&self) -> Option<&EntryWithClock> {
+        self.graph.roots().next().map(|(_, node)| &node.value)
     }
 
     pub fn tips(&self) -> BTreeSet<Oid> {
@@ -156,6 +209,126 @@ impl History {
     pub fn merge(&mut self, other: Self) {
         self.graph.merge(other.graph);
     }
+
+    /// Restrict this history to the given changes and their ancestors. Changes that
+    /// aren't in the history are ignored.
+    pub fn truncate(&mut self, tips: &BTreeSet<Oid>) {
+        let mut keep = HashSet::new();
+
+        for tip in tips {
+            let tip = EntryId::from(*tip);
+            if self.graph.contains(&tip) {
+                keep.extend(self.graph.ancestors(&tip).map(|(id, _)| *id));
+                keep.insert(tip);
+            }
+        }
+        self.graph.retain(|id, _| keep.contains(id));
+    }
+
+    /// Start this history from its latest snapshot, if any, by dropping the changes the
+    /// snapshot replaced. Changes concurrent to the snapshot are applied after it.
+    pub(crate) fn start_from_snapshot(&mut self) {
+        let Some(snapshot) = self
+            .iter()
+            .filter(|e| e.is_snapshot())
+            .last()
+            .map(|e| *e.id())
+        else {
+            return;
+        };
+        let replaced = self
+            .graph
+            .ancestors(&snapshot)
+            .map(|(id, _)| *id)
+            .collect::<HashSet<_>>();
+
+        if replaced.is_empty() {
+            return;
+        }
+        self.graph.retain(|id, _| !replaced.contains(id));
+
+        let roots = self
+            .graph
+            .roots()
+            .map(|(id, _)| *id)
+            .filter(|id| *id != snapshot)
+            .collect::<Vec<_>>();
+        for root in roots {
+            self.graph.dependency(root, snapshot);
+        }
+    }
+
+    /// Copy the attachments and version of a stored change to its entry.
+    pub(crate) fn annotate(&mut self, change: &Change) {
+        if let Some(node) = self.graph.get_mut(&change.id.into()) {
+            node.entry.attachments = change.attachments.clone();
+            node.entry.version = change.manifest.version;
+            node.entry.encoding = change.manifest.encoding;
+            node.entry.snapshot = change.manifest.snapshot.is_some();
+        }
+    }
+
+    /// Replace the contents of every entry, failing if any of them can't be replaced.
+    pub(crate) fn try_map_contents<F, E>(&mut self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(&Contents) -> Result<Contents, E>,
+    {
+        for (_, node) in self.graph.iter_mut() {
+            node.entry.contents = f(&node.entry.contents)?;
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over the changes of a [`History`], in topological order.
+/// Concurrent changes are ordered by timestamp, and then entry id.
+pub struct Iter<'a> {
+    history: &'a History,
+    /// Number of dependencies of each change that haven't been yielded yet.
+    pending: HashMap<EntryId, usize>,
+    /// Changes whose dependencies have all been yielded, least first.
+    ready: BinaryHeap<Reverse<(Timestamp, EntryId)>>,
+}
+
+impl<'a> Iter<'a> {
+    fn new(history: &'a History) -> Self {
+        let pending = history
+            .graph
+            .iter()
+            .map(|(id, node)| (*id, node.dependencies.len()))
+            .collect();
+        let ready = history
+            .graph
+            .roots()
+            .map(|(id, node)| Reverse((node.timestamp(), *id)))
+            .collect();
+
+        Self {
+            history,
+            pending,
+            ready,
+        }
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a EntryWithClock;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((_, id)) = self.ready.pop()?;
+        let node = &self.history.graph[&id];
+
+        for dependent in &node.dependents {
+            if let Some(n) = self.pending.get_mut(dependent) {
+                *n -= 1;
+                if *n == 0 {
+                    let timestamp = self.history.graph[dependent].timestamp();
+                    self.ready.push(Reverse((timestamp, *dependent)));
+                }
+            }
+        }
+        Some(&node.value)
+    }
 }
 
 fn create_dag<'a>(root: &'a EntryId, entries: &'a HashMap<EntryId, EntryWithClock>) -> History {
@@ -165,10 +338,14 @@ fn create_dag<'a>(root: &'a EntryId, entries: &'a HashMap<EntryId, EntryWithCloc
 
     while let Some(entry) = to_process.pop() {
         for child_id in entry.children() {
-            let child = entries[child_id].clone();
-            graph.node(*child_id, child.clone());
+            // Changes with several parents are reached once per parent. Inserting them
+            // again would drop the dependencies that were already added.
+            if !graph.contains(child_id) {
+                let child = entries[child_id].clone();
+                graph.node(*child_id, child.clone());
+                to_process.push(child);
+            }
             graph.dependency(*child_id, entry.id);
-            to_process.push(child.clone());
         }
     }
     History { graph }
@@ -6,8 +6,10 @@
 use git_ext::Oid;
 use nonempty::NonEmpty;
 use radicle_crypto::PublicKey;
+use serde::{Deserialize, Serialize};
 
-use crate::pruning_fold;
+use crate::encoding::Typed;
+use crate::{pruning_fold, Attachments, Encoding, Notarization};
 
 /// Entry contents.
 /// This is the change payload.
@@ -20,7 +22,8 @@ pub type Clock = u64;
 pub type Timestamp = u64;
 
 /// A unique identifier for a history entry.
-#[derive(Clone, Copy, Debug, PartialEq, Hash, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Hash, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct EntryId(Oid);
 
 impl From<git2::Oid> for EntryId {
@@ -42,7 +45,7 @@ impl From<EntryId> for Oid {
 }
 
 /// One entry in the dependency graph for a change
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Entry {
     /// The identifier for this entry
     pub(super) id: EntryId,
@@ -57,6 +60,21 @@ pub struct Entry {
     pub(super) contents: Contents,
     /// The entry timestamp, as seconds since epoch.
     pub(super) timestamp: Timestamp,
+    /// The attachments of this entry.
+    #[serde(default, skip_serializing_if = "Attachments::is_empty")]
+    pub(super) attachments: Attachments,
+    /// The version of the encoding of the contents.
+    #[serde(default)]
+    pub(super) version: u32,
+    /// The encoding of the contents.
+    #[serde(default, skip_serializing_if = "Encoding::is_json")]
+    pub(super) encoding: Encoding,
+    /// The valid notarizations of this entry.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) notarizations: Vec<Notarization>,
+    /// Whether the contents are a snapshot of the entries this entry replaced.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(super) snapshot: bool,
 }
 
 impl Entry {
@@ -80,9 +98,45 @@ impl Entry {
             children: children.into_iter().map(|id| id.into()).collect(),
             contents,
             timestamp,
+            attachments: Attachments::default(),
+            version: 0,
+            encoding: Encoding::default(),
+            notarizations: Vec::new(),
+            snapshot: false,
         }
     }
 
+    /// Set the attachments of this entry.
+    pub fn with_attachments(self, attachments: Attachments) -> Self {
+        Self {
+            attachments,
+            ..self
+        }
+    }
+
+    /// Set the version of the encoding of the contents of this entry.
+    pub fn with_version(self, version: u32) -> Self {
+        Self { version, ..self }
+    }
+
+    /// Set the encoding of the contents of this entry.
+    pub fn with_encoding(self, encoding: Encoding) -> Self {
+        Self { encoding, ..self }
+    }
+
+    /// Set the notarizations of this entry.
+    pub fn with_notarizations(self, notarizations: Vec<Notarization>) -> Self {
+        Self {
+            notarizations,
+            ..self
+        }
+    }
+
+    /// Mark this entry as a snapshot, see [`Entry::is_snapshot`].
+    pub fn with_snapshot(self, snapshot: bool) -> Self {
+        Self { snapshot, ..self }
+    }
+
     /// The ids of the changes this change depends on
     pub fn children(&self) -> impl Iterator<Item = &EntryId> {
         self.children.iter()
@@ -108,6 +162,47 @@ impl Entry {
         &self.contents
     }
 
+    /// The version of the encoding of the contents, as recorded in the change
+    /// manifest. See [`crate::change::store::Manifest::version`].
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// The encoding of the contents, as recorded in the change manifest.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// The contents of this change, along with their encoding.
+    pub fn typed_contents(&self) -> Typed<'_> {
+        Typed::new(self.encoding, &self.contents)
+    }
+
+    /// The attachments of this change. See [`crate::attachment`].
+    pub fn attachments(&self) -> &Attachments {
+        &self.attachments
+    }
+
+    /// Third-party attestations of when this change was seen. Unlike the
+    /// [`Entry::timestamp`], these can't be forged by the author.
+    /// See [`crate::notarization`].
+    pub fn notarizations(&self) -> &[Notarization] {
+        &self.notarizations
+    }
+
+    /// Whether the contents of this change are a snapshot of the changes it replaced.
+    /// In a [`crate::History`], only the root entry holds the snapshot the history
+    /// starts from: other snapshots only repeat changes that are already in the history.
+    /// See [`crate::squash`].
+    pub fn is_snapshot(&self) -> bool {
+        self.snapshot
+    }
+
+    /// The earliest time at which this change was notarized, if any.
+    pub fn first_seen(&self) -> Option<Timestamp> {
+        self.notarizations.iter().map(|n| n.timestamp).min()
+    }
+
     pub fn id(&self) -> &EntryId {
         &self.id
     }
@@ -126,7 +221,7 @@ impl pruning_fold::GraphNode for Entry {
 }
 
 /// Wraps an [`Entry`], adding a logical clock to it.
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct EntryWithClock {
     pub entry: Entry,
     pub clock: Clock,
@@ -0,0 +1,159 @@
+// Copyright © 2022 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crypto::revocation::Revocations;
+use git_ext::Oid;
+
+use crate::change_graph::{evaluate_change, Frozen};
+use crate::{object, Change, Schemas, Store};
+
+use super::{Entry, Timestamp};
+
+/// Iterator over the changes of an object, read lazily from storage, latest first.
+/// See [`super::History::iter_ops`].
+pub struct IterOps<'a, S> {
+    storage: &'a S,
+    revocations: Revocations,
+    /// Changes that revoked keys are still trusted for.
+    frozen: Frozen,
+    schemas: Schemas,
+    /// The parent commits of each commit reachable from the object's references.
+    parents: HashMap<Oid, Vec<Oid>>,
+    /// The child commits of each commit reachable from the object's references.
+    children: HashMap<Oid, Vec<Oid>>,
+    /// Number of children of each change that haven't been yielded yet.
+    pending: HashMap<Oid, usize>,
+    /// Loaded changes whose children have all been yielded.
+    loaded: HashMap<Oid, Change>,
+    /// Changes whose children have all been yielded, latest first.
+    ready: BinaryHeap<(Timestamp, Oid)>,
+}
+
+impl<'a, S: Store> IterOps<'a, S> {
+    pub(super) fn new(storage: &'a S, refs: &object::Objects) -> Self {
+        let mut parents = HashMap::new();
+        let mut children = HashMap::<Oid, Vec<Oid>>::new();
+        let mut visited = HashSet::new();
+        let mut stack = refs.iter().map(|r| &r.target).collect::<Vec<_>>();
+
+        // Only commit ids are walked here: changes are loaded once they are ready to
+        // be yielded.
+        while let Some(commit) = stack.pop() {
+            if !visited.insert(commit.id) {
+                continue;
+            }
+            for parent in &commit.parents {
+                children.entry(parent.id).or_default().push(commit.id);
+                stack.push(parent);
+            }
+            parents.insert(commit.id, commit.parents.iter().map(|p| p.id).collect());
+        }
+        let pending = children.iter().map(|(id, c)| (*id, c.len())).collect();
+        let revocations = storage.revocations();
+        let frozen = Frozen::new(
+            refs.iter()
+                .filter_map(|r| storage.owner(r).map(|key| (key, r.target.id))),
+            &revocations,
+            |oid| parents.get(oid).cloned().unwrap_or_default(),
+        );
+        let mut iter = Self {
+            storage,
+            revocations,
+            frozen,
+            schemas: storage.schemas(),
+            parents,
+            children,
+            pending,
+            loaded: HashMap::new(),
+            ready: BinaryHeap::new(),
+        };
+        for tip in refs.iter().map(|r| r.target.id).collect::<HashSet<_>>() {
+            if !iter.pending.contains_key(&tip) {
+                iter.load(tip);
+            }
+        }
+        iter
+    }
+
+    /// Group the remaining changes in pages of the given size, latest first. Only the
+    /// last page may be smaller. Changes are still read from storage lazily, one page
+    /// at a time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn pages(mut self, size: usize) -> impl Iterator<Item = Vec<Entry>> + 'a
+    where
+        S: 'a,
+    {
+        assert!(
+            size > 0,
+            "IterOps::pages: page size must be greater than zero"
+        );
+
+        std::iter::from_fn(move || {
+            let page = self.by_ref().take(size).collect::<Vec<_>>();
+            (!page.is_empty()).then_some(page)
+        })
+    }
+
+    fn load(&mut self, id: Oid) {
+        match self.storage.load(id) {
+            Ok(change) => {
+                self.ready.push((change.timestamp, id));
+                self.loaded.insert(id, change);
+            }
+            Err(err) => {
+                log::warn!("unable to load change '{id}', error '{err}'");
+            }
+        }
+    }
+}
+
+impl<'a, S: Store> Iterator for IterOps<'a, S> {
+    type Item = Entry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((_, id)) = self.ready.pop() {
+            let change = self.loaded.remove(&id)?;
+            let mut parents = self.parents.remove(&id).unwrap_or_default();
+
+            // The ancestors of a snapshot are replaced by it, and never yielded.
+            if change.manifest.snapshot.is_some() {
+                parents.clear();
+            }
+            for parent in parents {
+                // The resource commit isn't part of the change graph.
+                if parent == change.resource {
+                    continue;
+                }
+                if let Some(pending) = self.pending.get_mut(&parent) {
+                    *pending -= 1;
+
+                    if *pending == 0 {
+                        self.load(parent);
+                    }
+                }
+            }
+            let children = self.children.remove(&id).unwrap_or_default();
+
+            match evaluate_change(
+                &change,
+                &children,
+                &self.revocations,
+                &self.frozen,
+                &self.schemas,
+            ) {
+                Ok(entry) => return Some(entry),
+                Err(reason) => {
+                    log::warn!("rejecting change '{id}': {reason:?}");
+                }
+            }
+        }
+        None
+    }
+}
@@ -40,6 +40,10 @@
 //!   * [`change::Storage`] -- **Note**: there is already an
 //!   implementation for this for [`git2::Repository`] for convenience.
 //!
+//! For tests, or for experimenting with other storage backends,
+//! [`memory::Memory`] implements both of these, keeping all changes
+//! and references in memory.
+//!
 //! ## Resource
 //!
 //! The [`create`] and [`update`] functions take a `Resource`. It
@@ -80,8 +84,11 @@ extern crate qcheck_macros;
 extern crate radicle_crypto as crypto;
 extern crate radicle_git_ext as git_ext;
 
+pub mod attachment;
+pub use attachment::Attachments;
+
 mod backend;
-pub use backend::git;
+pub use backend::{git, memory};
 
 mod change_graph;
 mod trailers;
@@ -89,13 +96,25 @@ mod trailers;
 pub mod change;
 pub use change::Change;
 
+pub mod encoding;
+pub use encoding::Encoding;
+
+pub mod encryption;
+pub use encryption::Encryption;
+
 pub mod identity;
 
 pub mod history;
 pub use history::{Contents, Entry, History};
 
+pub mod notarization;
+pub use notarization::Notarization;
+
 mod pruning_fold;
 
+pub mod schema;
+pub use schema::Schemas;
+
 pub mod signatures;
 use signatures::Signature;
 
@@ -104,7 +123,8 @@ pub use type_name::TypeName;
 
 pub mod object;
 pub use object::{
-    create, get, info, list, remove, update, CollaborativeObject, Create, ObjectId, Update,
+    create, get, info, list, remove, squash, update, verify, CollaborativeObject, Create, ObjectId,
+    Squash, Update,
 };
 
 #[cfg(test)]
@@ -127,15 +147,40 @@ mod tests;
 /// [`git2::Repository`]. It is expected that the underlying storage
 /// for `object::Storage` will also be `git2::Repository`, but if not
 /// please open an issue to change the definition of `Store` :)
+///
+/// Backends that don't use `git` must report their errors with the
+/// backend-neutral [`change::error`] types. See [`memory::Memory`]
+/// for an example.
 pub trait Store
 where
     Self: object::Storage
         + change::Storage<
-            StoreError = git::change::error::Create,
-            LoadError = git::change::error::Load,
+            StoreError = change::error::Create,
+            LoadError = change::error::Load,
             ObjectId = git_ext::Oid,
             Resource = git_ext::Oid,
             Signatures = Signature,
         >,
 {
+    /// Key revocations to apply when evaluating changes. Changes signed by a revoked
+    /// key are rejected, unless they are reachable from the key's own references.
+    fn revocations(&self) -> crypto::revocation::Revocations {
+        crypto::revocation::Revocations::default()
+    }
+
+    /// The key that owns the given reference, ie. the only key allowed to update it.
+    ///
+    /// The references of revoked keys are expected to be left as they were when the
+    /// revocation became known, so that the changes reachable from them can still be
+    /// trusted. Stores that don't know the owner of references reject every change
+    /// signed by a revoked key.
+    fn owner(&self, _reference: &object::Reference) -> Option<crypto::PublicKey> {
+        None
+    }
+
+    /// Validators to run against change contents, per typename. Changes with invalid
+    /// contents are rejected.
+    fn schemas(&self) -> Schemas {
+        Schemas::default()
+    }
 }
@@ -0,0 +1,58 @@
+// Copyright © 2022 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! Notarization of changes by third parties.
+//!
+//! The timestamp of a change is chosen by its author, and can't be trusted
+//! on its own. A [`Notarization`] is a signed attestation by another key,
+//! eg. a seed node, that it had seen a change at a given time. Notarizations
+//! are stored separately from the change, see [`crate::change::Attestations`],
+//! and sign the change revision along with their timestamp, so they can't be
+//! forged by the author alone.
+
+use crypto::PublicKey;
+use serde::{Deserialize, Serialize};
+
+use crate::history::Timestamp;
+
+/// A signed attestation that a change was seen at a given time.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Notarization {
+    /// The key of the notary.
+    pub key: PublicKey,
+    /// The time at which the notary attests to have seen the change, as
+    /// seconds since epoch.
+    pub timestamp: Timestamp,
+    /// The signature of the notary over the change revision and timestamp.
+    pub signature: crypto::Signature,
+}
+
+impl Notarization {
+    /// Notarize a change revision at the given time.
+    pub fn new<G>(signer: &G, revision: &[u8], timestamp: Timestamp) -> Self
+    where
+        G: crypto::Signer,
+    {
+        Self {
+            key: *signer.public_key(),
+            timestamp,
+            signature: signer.sign(&Self::payload(revision, timestamp)),
+        }
+    }
+
+    /// Verify this notarization against a change revision.
+    pub fn verify(&self, revision: &[u8]) -> bool {
+        self.key
+            .verify(&Self::payload(revision, self.timestamp), &self.signature)
+            .is_ok()
+    }
+
+    /// The signed payload: the revision, followed by the big-endian timestamp.
+    fn payload(revision: &[u8], timestamp: Timestamp) -> Vec<u8> {
+        let mut payload = revision.to_vec();
+        payload.extend_from_slice(&timestamp.to_be_bytes());
+        payload
+    }
+}
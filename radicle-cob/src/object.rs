@@ -12,7 +12,8 @@ use thiserror::Error;
 
 pub mod collaboration;
 pub use collaboration::{
-    create, get, info, list, parse_refstr, remove, update, CollaborativeObject, Create, Update,
+    create, get, info, list, parse_refstr, remove, squash, update, verify, CollaborativeObject,
+    Create, Squash, Update,
 };
 
 pub mod storage;
@@ -55,6 +56,12 @@ impl From<git2::Oid> for ObjectId {
     }
 }
 
+impl From<ObjectId> for Oid {
+    fn from(ObjectId(oid): ObjectId) -> Self {
+        oid
+    }
+}
+
 impl From<&git2::Oid> for ObjectId {
     fn from(oid: &git2::Oid) -> Self {
         ObjectId(Oid::from(*oid))
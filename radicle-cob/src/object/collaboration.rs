@@ -8,7 +8,11 @@ use std::collections::BTreeSet;
 use git_ext::Oid;
 
 use crate::change::store::Manifest;
-use crate::{change, identity::Identity, Contents, History, ObjectId, TypeName};
+use crate::change_graph::ChangeGraph;
+use crate::{change, encryption, identity::Identity, Contents, History, ObjectId, Store, TypeName};
+use crypto::SecretKey;
+
+use super::Objects;
 
 pub mod error;
 
@@ -26,9 +30,15 @@ pub use list::list;
 mod remove;
 pub use remove::remove;
 
+mod squash;
+pub use squash::{squash, Squash};
+
 mod update;
 pub use update::{update, Update};
 
+pub mod verify;
+pub use verify::verify;
+
 /// A collaborative object
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CollaborativeObject {
@@ -57,11 +67,44 @@ impl CollaborativeObject {
         &self.manifest
     }
 
+    /// Decrypt the contents of an encrypted object, using the secret key of one of
+    /// its recipients. Objects that aren't encrypted are returned as-is.
+    pub fn decrypt(&self, secret: &SecretKey) -> Result<Self, encryption::error::Encryption> {
+        let mut object = self.clone();
+
+        if let Some(encryption) = &self.manifest.encryption {
+            object
+                .history
+                .try_map_contents(|contents| encryption.decrypt(secret, contents))?;
+            object.manifest.encryption = None;
+        }
+        Ok(object)
+    }
+
     fn tips(&self) -> BTreeSet<Oid> {
         self.history.tips().into_iter().map(Oid::from).collect()
     }
 }
 
+/// Load and evaluate an object from the references pointing to its tips.
+fn load<S>(
+    storage: &S,
+    tip_refs: &Objects,
+    typename: &TypeName,
+    oid: &ObjectId,
+) -> Option<CollaborativeObject>
+where
+    S: Store,
+{
+    let revocations = storage.revocations();
+    let schemas = storage.schemas();
+    let owners = tip_refs
+        .iter()
+        .filter_map(|r| storage.owner(r).map(|key| (key, r.target.id)));
+    ChangeGraph::load(storage, tip_refs.iter(), typename, oid)
+        .and_then(|graph| graph.evaluate(owners, &revocations, &schemas))
+}
+
 /// Takes a `refname` and performs a best attempt to extract out the
 /// [`TypeName`] and [`ObjectId`] from it.
 ///
@@ -3,7 +3,9 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use crate::Store;
+use std::collections::BTreeMap;
+
+use crate::{Encoding, Encryption, Store};
 
 use super::*;
 
@@ -11,12 +13,21 @@ use super::*;
 pub struct Create {
     /// The type of history that will be used for this object.
     pub history_type: String,
+    /// The version of the encoding of the contents, for the history type.
+    pub version: u32,
+    /// The encoding of the contents.
+    pub encoding: Encoding,
     /// The CRDT history to initialize this object with.
     pub contents: Contents,
     /// The typename for this object.
     pub typename: TypeName,
     /// The message to add when creating this object.
     pub message: String,
+    /// The encryption of the object's contents, if any. All subsequent
+    /// changes to the object use the same encryption.
+    pub encryption: Option<Encryption>,
+    /// Named attachments of the initial change. See [`crate::attachment`].
+    pub attachments: BTreeMap<String, Vec<u8>>,
 }
 
 impl Create {
@@ -24,9 +35,14 @@ impl Create {
         change::Template {
             typename: self.typename.clone(),
             history_type: self.history_type.clone(),
+            version: self.version,
+            encoding: self.encoding,
             tips: Vec::new(),
             message: self.message.clone(),
             contents: self.contents.clone(),
+            encryption: self.encryption.clone(),
+            attachments: self.attachments.clone(),
+            snapshot: None,
         }
     }
 }
@@ -67,17 +83,20 @@ where
         ..
     } = &args;
 
+    storage.schemas().validate(typename, contents)?;
+
     let init_change = storage
         .store(resource.content_id(), signer, args.template())
         .map_err(error::Create::from)?;
 
-    let history = History::new_from_root(
+    let mut history = History::new_from_root(
         *init_change.id(),
         init_change.signature.key,
         resource.content_id(),
-        contents.clone(),
+        init_change.contents.clone(),
         init_change.timestamp,
     );
+    history.annotate(&init_change);
 
     let object_id = init_change.id().into();
     storage
@@ -88,6 +107,10 @@ where
         manifest: Manifest {
             typename: args.typename,
             history_type: args.history_type,
+            version: args.version,
+            encoding: args.encoding,
+            encryption: args.encryption,
+            snapshot: None,
         },
         history,
         id: init_change.id().into(),
@@ -3,16 +3,19 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
+use std::collections::BTreeSet;
+
+use git_ext::Oid;
 use thiserror::Error;
 
-use crate::git;
+use crate::{change, schema};
 
 #[derive(Debug, Error)]
 pub enum Create {
     #[error("Invalid automerge history")]
     InvalidAutomergeHistory,
     #[error(transparent)]
-    CreateChange(#[from] git::change::error::Create),
+    CreateChange(#[from] change::error::Create),
     #[error("failed to updated references for during object creation")]
     Refs {
         #[source]
@@ -22,6 +25,8 @@ pub enum Create {
     Io(#[from] std::io::Error),
     #[error("signer must belong to the author")]
     SignerIsNotAuthor,
+    #[error(transparent)]
+    InvalidContents(#[from] schema::error::Invalid),
 }
 
 #[derive(Debug, Error)]
@@ -49,7 +54,7 @@ pub enum Update {
     #[error("no object found")]
     NoSuchObject,
     #[error(transparent)]
-    CreateChange(#[from] git::change::error::Create),
+    CreateChange(#[from] change::error::Create),
     #[error("failed to get references during object update")]
     Refs {
         #[source]
@@ -61,4 +66,30 @@ pub enum Update {
     Io(#[from] std::io::Error),
     #[error("signer must belong to the author")]
     SignerIsNotAuthor,
+    #[error(transparent)]
+    InvalidContents(#[from] schema::error::Invalid),
+    #[error("object was modified concurrently: expected tips {expected:?}, found {actual:?}")]
+    Conflict {
+        expected: BTreeSet<Oid>,
+        actual: BTreeSet<Oid>,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum Squash {
+    #[error("no object found")]
+    NoSuchObject,
+    #[error(transparent)]
+    CreateChange(#[from] change::error::Create),
+    #[error("failed to get references during object squash")]
+    Refs {
+        #[source]
+        err: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+    #[error(transparent)]
+    InvalidContents(#[from] schema::error::Invalid),
+    #[error("change '{0}' is not part of the object's history")]
+    UnknownTip(Oid),
+    #[error("change '{0}' of the signer's history is not replaced by the snapshot")]
+    Uncovered(Oid),
 }
@@ -3,7 +3,7 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use crate::{change_graph::ChangeGraph, CollaborativeObject, ObjectId, Store, TypeName};
+use crate::{CollaborativeObject, ObjectId, Store, TypeName};
 
 use super::error;
 
@@ -27,5 +27,5 @@ where
     let tip_refs = storage
         .objects(typename, oid)
         .map_err(|err| error::Retrieve::Refs { err: Box::new(err) })?;
-    Ok(ChangeGraph::load(storage, tip_refs.iter(), typename, oid).map(|graph| graph.evaluate()))
+    Ok(super::load(storage, &tip_refs, typename, oid))
 }
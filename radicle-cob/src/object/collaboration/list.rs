@@ -3,7 +3,7 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use crate::{change_graph::ChangeGraph, CollaborativeObject, Store, TypeName};
+use crate::{CollaborativeObject, Store, TypeName};
 
 use super::error;
 
@@ -28,8 +28,7 @@ where
     let mut result = Vec::new();
     for (oid, tip_refs) in references {
         log::trace!("loading object '{}'", oid);
-        let loaded = ChangeGraph::load(storage, tip_refs.iter(), typename, &oid)
-            .map(|graph| graph.evaluate());
+        let loaded = super::load(storage, &tip_refs, typename, &oid);
 
         match loaded {
             Some(obj) => {
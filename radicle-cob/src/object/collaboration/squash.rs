@@ -0,0 +1,146 @@
+// Copyright © 2022 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use git_ext::Oid;
+
+use crate::{
+    change, identity::Identity, CollaborativeObject, Contents, Encoding, ObjectId, Store, TypeName,
+};
+
+use super::error;
+
+/// The data required to squash the history of an object.
+pub struct Squash {
+    /// The type of history used for the snapshot.
+    pub history_type: String,
+    /// The version of the encoding of the snapshot, for the history type.
+    pub version: u32,
+    /// The encoding of the snapshot.
+    pub encoding: Encoding,
+    /// The snapshot replacing the object's history, up to and including `tips`.
+    pub snapshot: Contents,
+    /// The changes replaced by the snapshot, along with their ancestors. These become
+    /// the parents of the snapshot.
+    pub tips: BTreeSet<Oid>,
+    /// The object ID of the object to be squashed.
+    pub object_id: ObjectId,
+    /// The typename of the object to be squashed.
+    pub typename: TypeName,
+    /// The message to add when squashing this object.
+    pub message: String,
+}
+
+/// Squash the history of an existing [`CollaborativeObject`], up to the
+/// given tips, into a single snapshot change, signed by `signer`.
+///
+/// The snapshot change descends from the tips it replaces, and records
+/// the logical clock of the history it replaces. Readers start from the
+/// latest snapshot, and only load the changes it replaced if some
+/// references don't descend from it. Changes concurrent to the snapshot
+/// are applied after it. The [`ObjectId`] of the object is left unchanged.
+///
+/// It is up to the caller to ensure that `args.snapshot` is equivalent to
+/// the squashed history. If the object is encrypted, the snapshot is
+/// encrypted in the same way. Attachments of the squashed changes are not
+/// carried over to the snapshot.
+///
+/// The reference under `identifier` is updated to point to the snapshot,
+/// so the changes of the signer's own reference must be replaced by the
+/// snapshot.
+///
+/// The `storage` is the backing storage for storing
+/// [`crate::Change`]s at content-addressable locations. Please see
+/// [`Store`] for further information.
+///
+/// The `resource` is the parent of this object, for example a
+/// software project. Its content-address is stored in the
+/// object's history.
+pub fn squash<S, G, Resource>(
+    storage: &S,
+    signer: &G,
+    resource: &Resource,
+    identifier: &S::Identifier,
+    args: Squash,
+) -> Result<CollaborativeObject, error::Squash>
+where
+    S: Store,
+    G: crypto::Signer,
+    Resource: Identity,
+{
+    let Squash {
+        typename,
+        object_id,
+        history_type,
+        version,
+        encoding,
+        snapshot,
+        tips,
+        message,
+    } = args;
+
+    let existing_refs = storage
+        .objects(&typename, &object_id)
+        .map_err(|err| error::Squash::Refs { err: Box::new(err) })?;
+
+    let object = super::load(storage, &existing_refs, &typename, &object_id)
+        .ok_or(error::Squash::NoSuchObject)?;
+    let mut history = object.history.clone();
+    let known = history
+        .iter()
+        .map(|entry| Oid::from(*entry.id()))
+        .collect::<HashSet<_>>();
+
+    if let Some(tip) = tips.iter().find(|tip| !known.contains(tip)) {
+        return Err(error::Squash::UnknownTip(*tip));
+    }
+    history.truncate(&tips);
+
+    let replaced = history
+        .iter()
+        .map(|entry| Oid::from(*entry.id()))
+        .collect::<HashSet<_>>();
+    let signer_key = signer.public_key();
+
+    // Changes that aren't part of the history, eg. because they were replaced by an
+    // earlier snapshot, aren't lost when the signer's reference is updated.
+    for reference in existing_refs.iter() {
+        let tip = reference.target.id;
+        let owned = storage.owner(reference).as_ref() == Some(signer_key);
+
+        if owned && known.contains(&tip) && !replaced.contains(&tip) {
+            return Err(error::Squash::Uncovered(tip));
+        }
+    }
+    let encryption = object.manifest.encryption;
+    storage.schemas().validate(&typename, &snapshot)?;
+
+    let change = storage.store(
+        resource.content_id(),
+        signer,
+        change::Template {
+            tips: history.tips().into_iter().collect(),
+            history_type,
+            version,
+            encoding,
+            contents: snapshot,
+            typename: typename.clone(),
+            message,
+            encryption,
+            attachments: BTreeMap::new(),
+            snapshot: Some(history.clock() + 1),
+        },
+    )?;
+    storage
+        .update(identifier, &typename, &object_id, &change)
+        .map_err(|err| error::Squash::Refs { err: Box::new(err) })?;
+
+    let refs = storage
+        .objects(&typename, &object_id)
+        .map_err(|err| error::Squash::Refs { err: Box::new(err) })?;
+
+    super::load(storage, &refs, &typename, &object_id).ok_or(error::Squash::NoSuchObject)
+}
@@ -3,9 +3,12 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
+use std::collections::{BTreeMap, BTreeSet};
+
+use git_ext::Oid;
+
 use crate::{
-    change, change_graph::ChangeGraph, identity::Identity, CollaborativeObject, Contents, ObjectId,
-    Store, TypeName,
+    change, identity::Identity, CollaborativeObject, Contents, Encoding, ObjectId, Store, TypeName,
 };
 
 use super::error;
@@ -14,6 +17,10 @@ use super::error;
 pub struct Update {
     /// The type of history that will be used for this object.
     pub history_type: String,
+    /// The version of the encoding of the changes, for the history type.
+    pub version: u32,
+    /// The encoding of the changes.
+    pub encoding: Encoding,
     /// The CRDT changes to add to the object.
     pub changes: Contents,
     /// The object ID of the object to be updated.
@@ -22,6 +29,12 @@ pub struct Update {
     pub typename: TypeName,
     /// The message to add when updating this object.
     pub message: String,
+    /// Named attachments of the new change. See [`crate::attachment`].
+    pub attachments: BTreeMap<String, Vec<u8>>,
+    /// The tips of the object the changes were made against. If set, the update
+    /// fails with [`error::Update::Conflict`] when the object's tips differ, ie.
+    /// when the object was modified since it was read.
+    pub expected_tips: Option<BTreeSet<Oid>>,
 }
 
 /// Update an existing [`CollaborativeObject`].
@@ -58,36 +71,54 @@ where
         ref typename,
         object_id,
         history_type,
+        version,
+        encoding,
         changes,
         message,
+        attachments,
+        expected_tips,
     } = args;
 
     let existing_refs = storage
         .objects(typename, &object_id)
         .map_err(|err| error::Update::Refs { err: Box::new(err) })?;
 
-    let mut object = ChangeGraph::load(storage, existing_refs.iter(), typename, &object_id)
-        .map(|graph| graph.evaluate())
+    let mut object = super::load(storage, &existing_refs, typename, &object_id)
         .ok_or(error::Update::NoSuchObject)?;
 
+    if let Some(expected) = expected_tips {
+        let actual = object.history.tips();
+        if actual != expected {
+            return Err(error::Update::Conflict { expected, actual });
+        }
+    }
+
+    storage.schemas().validate(typename, &changes)?;
+
     let change = storage.store(
         resource.content_id(),
         signer,
         change::Template {
             tips: object.tips().iter().cloned().collect(),
             history_type,
+            version,
+            encoding,
             contents: changes.clone(),
             typename: typename.clone(),
             message,
+            encryption: object.manifest.encryption.clone(),
+            attachments,
+            snapshot: None,
         },
     )?;
     object.history.extend(
         change.id,
         change.signature.key,
         change.resource,
-        changes,
+        change.contents.clone(),
         change.timestamp,
     );
+    object.history.annotate(&change);
     storage
         .update(identifier, typename, &object_id, &change)
         .map_err(|err| error::Update::Refs { err: Box::new(err) })?;
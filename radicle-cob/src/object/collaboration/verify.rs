@@ -0,0 +1,164 @@
+// Copyright © 2022 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! Integrity verification of the changes of a [`crate::CollaborativeObject`].
+//!
+//! Unlike loading an object, which silently skips changes that can't be
+//! loaded or that have invalid signatures, [`verify()`] checks every change
+//! reachable from the object's references and reports all failures, so that
+//! corrupted or tampered objects can be found.
+
+use std::collections::{HashMap, HashSet};
+
+use crypto::PublicKey;
+use git_ext::Oid;
+use thiserror::Error;
+
+use crate::change;
+use crate::{ObjectId, Store, TypeName};
+
+use super::error;
+
+/// Why a change failed verification.
+#[derive(Debug, Error)]
+pub enum Invalid {
+    #[error("unable to load change: {0}")]
+    Load(#[source] change::error::Load),
+    #[error("invalid signatures")]
+    Signatures,
+    #[error("expected type '{expected}', found '{found}'")]
+    TypeName { expected: TypeName, found: TypeName },
+    #[error("encryption doesn't match the encryption of the root change")]
+    Encryption,
+}
+
+/// A change that failed verification.
+#[derive(Debug)]
+pub struct Failure {
+    /// The change commit.
+    pub change: Oid,
+    /// The key the change claims to be signed by, if it could be loaded.
+    pub signer: Option<PublicKey>,
+    /// Why the change is invalid.
+    pub reason: Invalid,
+}
+
+/// The result of verifying an object.
+#[derive(Debug, Default)]
+pub struct Verification {
+    /// The number of changes that were checked.
+    pub changes: usize,
+    /// The changes that failed verification.
+    pub failures: Vec<Failure>,
+}
+
+impl Verification {
+    /// Whether all changes passed verification.
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    fn fail(&mut self, change: Oid, signer: Option<PublicKey>, reason: Invalid) {
+        self.failures.push(Failure {
+            change,
+            signer,
+            reason,
+        });
+    }
+}
+
+/// Verify the integrity of every change of an object.
+///
+/// For every change reachable from the object's references, this checks that:
+///
+///   * The change can be loaded, ie. it has a manifest, contents and a resource
+///     trailer.
+///   * Its signatures are valid.
+///   * Its manifest has the expected `typename`, and the same encryption as
+///     the root change, whose id is the `oid`.
+///
+/// Changes that are older than one of their parents are only logged: timestamps
+/// are chosen by the signer, so this is usually clock skew rather than corruption.
+///
+/// The history type and encoding version of changes may differ, eg. after an
+/// object was migrated or squashed. If the root change isn't reachable, eg.
+/// because the object was squashed, the first change checked is compared
+/// against instead.
+///
+/// Key revocations and content schemas aren't checked, since they depend on
+/// the state of the [`Store`] rather than on the changes themselves.
+pub fn verify<S>(
+    storage: &S,
+    typename: &TypeName,
+    oid: &ObjectId,
+) -> Result<Verification, error::Retrieve>
+where
+    S: Store,
+{
+    let tip_refs = storage
+        .objects(typename, oid)
+        .map_err(|err| error::Retrieve::Refs { err: Box::new(err) })?;
+    let mut encryption = storage
+        .load(Oid::from(*oid))
+        .ok()
+        .map(|c| c.manifest.encryption);
+
+    let mut verification = Verification::default();
+    let mut timestamps = HashMap::new();
+    let mut edges = Vec::new();
+    let mut visited = HashSet::new();
+    let mut stack = tip_refs.iter().map(|r| &r.target).collect::<Vec<_>>();
+
+    while let Some(commit) = stack.pop() {
+        if !visited.insert(commit.id) {
+            continue;
+        }
+        verification.changes += 1;
+
+        let change = match storage.load(commit.id) {
+            Ok(change) => change,
+            Err(err) => {
+                verification.fail(commit.id, None, Invalid::Load(err));
+                continue;
+            }
+        };
+        let signer = Some(*change.signature.key());
+
+        if !change.valid_signatures() {
+            verification.fail(commit.id, signer, Invalid::Signatures);
+        }
+        if change.typename() != typename {
+            verification.fail(
+                commit.id,
+                signer,
+                Invalid::TypeName {
+                    expected: typename.clone(),
+                    found: change.typename().clone(),
+                },
+            );
+        } else if encryption.get_or_insert_with(|| change.manifest.encryption.clone())
+            != &change.manifest.encryption
+        {
+            verification.fail(commit.id, signer, Invalid::Encryption);
+        }
+
+        // The resource commit is a parent of every change, but isn't part of the
+        // change graph.
+        for parent in commit.parents.iter().filter(|p| p.id != change.resource) {
+            edges.push((commit.id, parent.id));
+            stack.push(parent);
+        }
+        timestamps.insert(commit.id, change.timestamp);
+    }
+
+    for (child, parent) in edges {
+        if let (Some(c), Some(p)) = (timestamps.get(&child), timestamps.get(&parent)) {
+            if c < p {
+                log::warn!("change '{child}' is older than its parent '{parent}'");
+            }
+        }
+    }
+    Ok(verification)
+}
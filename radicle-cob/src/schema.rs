@@ -0,0 +1,66 @@
+// Copyright © 2022 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! Validation of change contents, per [`TypeName`].
+//!
+//! A [`Schemas`] registry associates typenames with a [`Validator`]. Changes
+//! whose contents fail validation are rejected when creating or updating an
+//! object, and pruned from the history when it is evaluated, similar to
+//! changes with invalid signatures.
+
+use std::collections::HashMap;
+
+use crate::{history::Contents, TypeName};
+
+pub mod error {
+    use thiserror::Error;
+
+    use crate::TypeName;
+
+    #[derive(Debug, Error)]
+    #[error("invalid contents for object of type '{typename}': {err}")]
+    pub struct Invalid {
+        pub typename: TypeName,
+        #[source]
+        pub err: Box<dyn std::error::Error + Send + Sync + 'static>,
+    }
+}
+
+/// A function validating the contents of a change.
+pub type Validator = fn(&Contents) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+/// A registry of validators, keyed by typename.
+///
+/// Typenames without a registered validator accept any contents.
+#[derive(Clone, Debug, Default)]
+pub struct Schemas(HashMap<TypeName, Validator>);
+
+impl Schemas {
+    /// Register a validator for a typename, returning the previous validator, if any.
+    pub fn register(&mut self, typename: TypeName, validator: Validator) -> Option<Validator> {
+        self.0.insert(typename, validator)
+    }
+
+    /// Remove the validator of a typename.
+    pub fn unregister(&mut self, typename: &TypeName) -> Option<Validator> {
+        self.0.remove(typename)
+    }
+
+    /// Get the validator of a typename.
+    pub fn get(&self, typename: &TypeName) -> Option<&Validator> {
+        self.0.get(typename)
+    }
+
+    /// Validate change contents against the validator registered for `typename`.
+    pub fn validate(&self, typename: &TypeName, contents: &Contents) -> Result<(), error::Invalid> {
+        match self.0.get(typename) {
+            Some(validate) => validate(contents).map_err(|err| error::Invalid {
+                typename: typename.clone(),
+                err,
+            }),
+            None => Ok(()),
+        }
+    }
+}
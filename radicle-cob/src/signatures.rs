@@ -28,6 +28,30 @@ impl Signature {
     pub fn verify(&self, payload: &[u8]) -> bool {
         self.key.verify(payload, &self.sig).is_ok()
     }
+
+    /// The key that produced this signature.
+    pub fn key(&self) -> &PublicKey {
+        &self.key
+    }
+
+    /// Parse the SSH signatures of a commit, in the order they appear in its headers.
+    /// PGP signatures are skipped.
+    pub fn all(commit: &Commit) -> Result<Vec<Self>, error::Signatures> {
+        commit
+            .signatures()
+            .filter_map(|signature| {
+                match signature {
+                    // Skip PGP signatures
+                    Pgp(_) => None,
+                    Ssh(armored) => Some(
+                        ExtendedSignature::from_armored(armored.as_bytes())
+                            .map(Self::from)
+                            .map_err(error::Signatures::from),
+                    ),
+                }
+            })
+            .collect()
+    }
 }
 
 impl From<Signature> for ExtendedSignature {
@@ -51,7 +75,7 @@ impl From<(PublicKey, crypto::Signature)> for Signature {
 
 // FIXME(kim): This should really be a HashMap with a no-op Hasher -- PublicKey
 // collisions are catastrophic
-#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Signatures(BTreeMap<PublicKey, crypto::Signature>);
 
 impl Deref for Signatures {
@@ -92,20 +116,10 @@ impl TryFrom<&Commit> for Signatures {
     type Error = error::Signatures;
 
     fn try_from(value: &Commit) -> Result<Self, Self::Error> {
-        value
-            .signatures()
-            .filter_map(|signature| {
-                match signature {
-                    // Skip PGP signatures
-                    Pgp(_) => None,
-                    Ssh(armored) => Some(
-                        ExtendedSignature::from_armored(armored.as_bytes())
-                            .map_err(error::Signatures::from),
-                    ),
-                }
-            })
-            .map(|ex| ex.map(|ex| ex.into()))
-            .collect::<Result<_, _>>()
+        let mut signatures = Self::default();
+        signatures.extend(Signature::all(value)?);
+
+        Ok(signatures)
     }
 }
 
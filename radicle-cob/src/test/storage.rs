@@ -90,6 +90,39 @@ impl change::Storage for Storage {
     > {
         self.as_raw().load(id)
     }
+
+    fn attachment(&self, id: Self::ObjectId) -> Result<Vec<u8>, Self::LoadError> {
+        self.as_raw().attachment(id)
+    }
+
+    fn cosign<Signer>(
+        &self,
+        id: Self::ObjectId,
+        signer: &Signer,
+    ) -> Result<
+        change::store::Change<Self::Resource, Self::ObjectId, Self::Signatures>,
+        Self::StoreError,
+    >
+    where
+        Signer: crypto::Signer,
+    {
+        self.as_raw().cosign(id, signer)
+    }
+
+    fn notarize<Signer>(
+        &self,
+        id: Self::ObjectId,
+        signer: &Signer,
+        timestamp: crate::history::Timestamp,
+    ) -> Result<
+        change::store::Change<Self::Resource, Self::ObjectId, Self::Signatures>,
+        Self::StoreError,
+    >
+    where
+        Signer: crypto::Signer,
+    {
+        self.as_raw().notarize(id, signer, timestamp)
+    }
 }
 
 impl object::Storage for Storage {
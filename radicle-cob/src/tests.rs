@@ -1,13 +1,24 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::ops::ControlFlow;
 
+use crypto::revocation::Revocation;
 use crypto::test::signer::MockSigner;
+use crypto::{KeyPair, SecretKey, Seed};
 use git_ref_format::{refname, Component, RefString};
 use nonempty::nonempty;
 use qcheck::Arbitrary;
 use radicle_crypto::Signer;
 
 use crate::{
-    create, get, list, object, test::arbitrary::Invalid, update, Create, ObjectId, TypeName, Update,
+    attachment,
+    change::Storage as _,
+    create, get,
+    history::{Entry, EntryWithClock},
+    list,
+    memory::Memory,
+    object,
+    test::arbitrary::Invalid,
+    update, verify, Create, Encoding, Encryption, History, ObjectId, Schemas, TypeName, Update,
 };
 
 use super::test;
@@ -30,9 +41,13 @@ fn roundtrip() {
         &proj.identifier(),
         Create {
             history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
             contents: nonempty!(Vec::new()),
             typename: typename.clone(),
             message: "creating xyz.rad.issue".to_string(),
+            encryption: None,
+            attachments: BTreeMap::new(),
         },
     )
     .unwrap();
@@ -62,9 +77,13 @@ fn list_cobs() {
         &proj.identifier(),
         Create {
             history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
             contents: nonempty!(b"issue 1".to_vec()),
             typename: typename.clone(),
             message: "creating xyz.rad.issue".to_string(),
+            encryption: None,
+            attachments: BTreeMap::new(),
         },
     )
     .unwrap();
@@ -76,9 +95,13 @@ fn list_cobs() {
         &proj.identifier(),
         Create {
             history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
             contents: nonempty!(b"issue 2".to_vec()),
             typename: typename.clone(),
             message: "commenting xyz.rad.issue".to_string(),
+            encryption: None,
+            attachments: BTreeMap::new(),
         },
     )
     .unwrap();
@@ -110,9 +133,13 @@ fn update_cob() {
         &proj.identifier(),
         Create {
             history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
             contents: nonempty!(Vec::new()),
             typename: typename.clone(),
             message: "creating xyz.rad.issue".to_string(),
+            encryption: None,
+            attachments: BTreeMap::new(),
         },
     )
     .unwrap();
@@ -129,9 +156,13 @@ fn update_cob() {
         Update {
             changes: nonempty!(b"issue 1".to_vec()),
             history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
             object_id: *cob.id(),
             typename: typename.clone(),
             message: "commenting xyz.rad.issue".to_string(),
+            attachments: BTreeMap::new(),
+            expected_tips: None,
         },
     )
     .unwrap();
@@ -144,6 +175,229 @@ fn update_cob() {
     assert_eq!(updated, expected);
 }
 
+#[test]
+fn memory_storage() {
+    struct Resource(git_ext::Oid);
+
+    impl crate::identity::Identity for Resource {
+        type Identifier = git_ext::Oid;
+
+        fn content_id(&self) -> git_ext::Oid {
+            self.0
+        }
+    }
+
+    let storage = Memory::new();
+    let signer = gen::<MockSigner>(1);
+    let resource = Resource(
+        git2::Oid::hash_object(git2::ObjectType::Blob, b"discworld")
+            .unwrap()
+            .into(),
+    );
+    let typename = "xyz.rad.issue".parse::<TypeName>().unwrap();
+    let cob = create(
+        &storage,
+        &signer,
+        &resource,
+        signer.public_key(),
+        Create {
+            history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
+            contents: nonempty!(b"issue 1".to_vec()),
+            typename: typename.clone(),
+            message: "creating xyz.rad.issue".to_string(),
+            encryption: None,
+            attachments: BTreeMap::from([("log.txt".to_owned(), b"oops".to_vec())]),
+        },
+    )
+    .unwrap();
+
+    let updated = update(
+        &storage,
+        &signer,
+        &resource,
+        signer.public_key(),
+        Update {
+            changes: nonempty!(b"comment 1".to_vec()),
+            history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
+            object_id: *cob.id(),
+            typename: typename.clone(),
+            message: "commenting xyz.rad.issue".to_string(),
+            attachments: BTreeMap::new(),
+            expected_tips: None,
+        },
+    )
+    .unwrap();
+
+    let expected = get(&storage, &typename, cob.id())
+        .unwrap()
+        .expect("BUG: cob was missing");
+    assert_eq!(updated, expected);
+    assert_eq!(list(&storage, &typename).unwrap(), vec![expected]);
+    assert_eq!(storage.len(), 2);
+
+    let root = storage.load((*cob.id()).into()).unwrap();
+    let id = root.attachments().get("log.txt").unwrap();
+    assert_eq!(storage.attachment(*id).unwrap(), b"oops");
+}
+
+#[test]
+fn revoked_key() {
+    struct Resource(git_ext::Oid);
+
+    impl crate::identity::Identity for Resource {
+        type Identifier = git_ext::Oid;
+
+        fn content_id(&self) -> git_ext::Oid {
+            self.0
+        }
+    }
+
+    let storage = Memory::new();
+    let alice = MockSigner::from_seed([1; 32]);
+    let bob = MockSigner::from_seed([2; 32]);
+    let eve = MockSigner::from_seed([3; 32]);
+    let resource = Resource(
+        git2::Oid::hash_object(git2::ObjectType::Blob, b"discworld")
+            .unwrap()
+            .into(),
+    );
+    let typename = "xyz.rad.issue".parse::<TypeName>().unwrap();
+    let cob = create(
+        &storage,
+        &alice,
+        &resource,
+        alice.public_key(),
+        Create {
+            history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
+            contents: nonempty!(b"issue 1".to_vec()),
+            typename: typename.clone(),
+            message: "creating xyz.rad.issue".to_string(),
+            encryption: None,
+            attachments: BTreeMap::new(),
+        },
+    )
+    .unwrap();
+    let comment = |signer: &MockSigner, identifier: &MockSigner, body: &[u8]| {
+        update(
+            &storage,
+            signer,
+            &resource,
+            identifier.public_key(),
+            Update {
+                changes: nonempty!(body.to_vec()),
+                history_type: "test".to_string(),
+                version: 0,
+                encoding: Encoding::Json,
+                object_id: *cob.id(),
+                typename: typename.clone(),
+                message: "commenting xyz.rad.issue".to_string(),
+                attachments: BTreeMap::new(),
+                expected_tips: None,
+            },
+        )
+        .unwrap()
+    };
+    comment(&bob, &bob, b"comment 1");
+
+    // The revocation time is in the future, so that any change timestamp would be
+    // accepted, if timestamps were trusted.
+    storage
+        .revoke(Revocation::new(*bob.public_key(), u64::MAX).sign(&bob))
+        .unwrap();
+
+    let object = get(&storage, &typename, cob.id()).unwrap().unwrap();
+    assert_eq!(
+        object.history().len(),
+        2,
+        "Changes known before the revocation are kept"
+    );
+
+    // Bob's key is used to publish a change through another peer's reference.
+    comment(&bob, &eve, b"comment 2");
+
+    let object = get(&storage, &typename, cob.id()).unwrap().unwrap();
+    assert_eq!(
+        object.history().len(),
+        2,
+        "Changes that aren't reachable from the revoked key's references are rejected"
+    );
+}
+
+#[test]
+fn update_conflict() {
+    let storage = test::Storage::new();
+    let signer = gen::<MockSigner>(1);
+    let terry = test::Person::new(&storage, "terry", *signer.public_key()).unwrap();
+    let proj = test::Project::new(&storage, "discworld", *signer.public_key()).unwrap();
+    let proj = test::RemoteProject {
+        project: proj,
+        person: terry,
+    };
+    let typename = "xyz.rad.issue".parse::<TypeName>().unwrap();
+    let cob = create(
+        &storage,
+        &signer,
+        &proj,
+        &proj.identifier(),
+        Create {
+            history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
+            contents: nonempty!(Vec::new()),
+            typename: typename.clone(),
+            message: "creating xyz.rad.issue".to_string(),
+            encryption: None,
+            attachments: BTreeMap::new(),
+        },
+    )
+    .unwrap();
+    let tips = cob.history().tips();
+    let comment = |message: &str| Update {
+        changes: nonempty!(message.as_bytes().to_vec()),
+        history_type: "test".to_string(),
+        version: 0,
+        encoding: Encoding::Json,
+        object_id: *cob.id(),
+        typename: typename.clone(),
+        message: message.to_string(),
+        attachments: BTreeMap::new(),
+        expected_tips: Some(tips.clone()),
+    };
+
+    let updated = update(
+        &storage,
+        &signer,
+        &proj,
+        &proj.identifier(),
+        comment("first"),
+    )
+    .unwrap();
+
+    // The tips have moved since they were read.
+    let err = update(
+        &storage,
+        &signer,
+        &proj,
+        &proj.identifier(),
+        comment("second"),
+    )
+    .unwrap_err();
+
+    match err {
+        object::collaboration::error::Update::Conflict { expected, actual } => {
+            assert_eq!(expected, tips);
+            assert_eq!(actual, updated.history().tips());
+        }
+        err => panic!("unexpected error: {err}"),
+    }
+}
+
 #[test]
 fn traverse_cobs() {
     let storage = test::Storage::new();
@@ -169,8 +423,12 @@ fn traverse_cobs() {
         Create {
             contents: nonempty!(b"issue 1".to_vec()),
             history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
             typename: typename.clone(),
             message: "creating xyz.rad.issue".to_string(),
+            encryption: None,
+            attachments: BTreeMap::new(),
         },
     )
     .unwrap();
@@ -191,9 +449,13 @@ fn traverse_cobs() {
         Update {
             changes: nonempty!(b"issue 2".to_vec()),
             history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
             object_id: *cob.id(),
             typename,
             message: "commenting on xyz.rad.issue".to_string(),
+            attachments: BTreeMap::new(),
+            expected_tips: None,
         },
     )
     .unwrap();
@@ -217,6 +479,547 @@ fn traverse_cobs() {
     assert_eq!(contents, vec![b"issue 1".to_vec(), b"issue 2".to_vec()]);
 }
 
+#[test]
+fn iter_ops() {
+    let storage = test::Storage::new();
+    let signer = gen::<MockSigner>(1);
+    let terry = test::Person::new(&storage, "terry", *signer.public_key()).unwrap();
+    let proj = test::Project::new(&storage, "discworld", *signer.public_key()).unwrap();
+    let proj = test::RemoteProject {
+        project: proj,
+        person: terry,
+    };
+    let typename = "xyz.rad.issue".parse::<TypeName>().unwrap();
+    let mut cob = create(
+        &storage,
+        &signer,
+        &proj,
+        &proj.identifier(),
+        Create {
+            history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
+            contents: nonempty!(b"issue 1".to_vec()),
+            typename: typename.clone(),
+            message: "creating xyz.rad.issue".to_string(),
+            encryption: None,
+            attachments: BTreeMap::new(),
+        },
+    )
+    .unwrap();
+
+    for comment in [b"comment 1", b"comment 2"] {
+        cob = update(
+            &storage,
+            &signer,
+            &proj,
+            &proj.identifier(),
+            Update {
+                changes: nonempty!(comment.to_vec()),
+                history_type: "test".to_string(),
+                version: 0,
+                encoding: Encoding::Json,
+                object_id: *cob.id(),
+                typename: typename.clone(),
+                message: "commenting xyz.rad.issue".to_string(),
+                attachments: BTreeMap::new(),
+                expected_tips: None,
+            },
+        )
+        .unwrap();
+    }
+
+    let mut expected = cob
+        .history()
+        .iter()
+        .map(|e| e.contents().clone())
+        .collect::<Vec<_>>();
+    expected.reverse();
+
+    let actual = History::iter_ops(&storage, &typename, cob.id())
+        .unwrap()
+        .map(|e| e.contents().clone())
+        .collect::<Vec<_>>();
+    assert_eq!(actual, expected);
+
+    let latest = History::iter_ops(&storage, &typename, cob.id())
+        .unwrap()
+        .next()
+        .unwrap();
+    assert_eq!(latest.contents(), &nonempty!(b"comment 2".to_vec()));
+
+    let pages = History::iter_ops(&storage, &typename, cob.id())
+        .unwrap()
+        .pages(2)
+        .map(|page| {
+            page.into_iter()
+                .map(|e| e.contents().clone())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(pages, vec![expected[..2].to_vec(), expected[2..].to_vec()]);
+}
+
+#[test]
+fn cosign_change() {
+    let storage = test::Storage::new();
+    let signer = gen::<MockSigner>(1);
+    let delegate = gen::<MockSigner>(1);
+    let terry = test::Person::new(&storage, "terry", *signer.public_key()).unwrap();
+    let proj = test::Project::new(&storage, "discworld", *signer.public_key()).unwrap();
+    let proj = test::RemoteProject {
+        project: proj,
+        person: terry,
+    };
+    let cob = create(
+        &storage,
+        &signer,
+        &proj,
+        &proj.identifier(),
+        Create {
+            history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
+            contents: nonempty!(b"issue 1".to_vec()),
+            typename: "xyz.rad.issue".parse::<TypeName>().unwrap(),
+            message: "creating xyz.rad.issue".to_string(),
+            encryption: None,
+            attachments: BTreeMap::new(),
+        },
+    )
+    .unwrap();
+    let id = *cob.history().tips().iter().next().unwrap();
+
+    // The co-signature is stored apart from the change, which keeps its id.
+    let change = storage.cosign(id, &delegate).unwrap();
+    assert_eq!(change.id, id);
+    assert!(change.valid_signatures());
+
+    let loaded = storage.load(id).unwrap();
+    assert!(loaded.valid_signatures());
+    assert_eq!(
+        loaded.signers().collect::<Vec<_>>(),
+        vec![signer.public_key(), delegate.public_key()]
+    );
+    // Co-signing with a key that already signed is a no-op.
+    assert_eq!(storage.cosign(id, &signer).unwrap().cosignatures.len(), 1);
+
+    // Invalid co-signatures are ignored.
+    let other = gen::<MockSigner>(1);
+    let mut forged = loaded.clone();
+    forged
+        .cosignatures
+        .insert(*other.public_key(), delegate.sign(b"forged"));
+    assert!(forged.valid_signatures());
+    assert_eq!(forged.cosigners().count(), 1);
+}
+
+#[test]
+fn notarize_change() {
+    let storage = test::Storage::new();
+    let signer = gen::<MockSigner>(1);
+    let seed = gen::<MockSigner>(1);
+    let terry = test::Person::new(&storage, "terry", *signer.public_key()).unwrap();
+    let proj = test::Project::new(&storage, "discworld", *signer.public_key()).unwrap();
+    let proj = test::RemoteProject {
+        project: proj,
+        person: terry,
+    };
+    let cob = create(
+        &storage,
+        &signer,
+        &proj,
+        &proj.identifier(),
+        Create {
+            history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
+            contents: nonempty!(b"issue 1".to_vec()),
+            typename: "xyz.rad.issue".parse::<TypeName>().unwrap(),
+            message: "creating xyz.rad.issue".to_string(),
+            encryption: None,
+            attachments: BTreeMap::new(),
+        },
+    )
+    .unwrap();
+    let id = *cob.history().tips().iter().next().unwrap();
+
+    // The notarization is stored apart from the change, which keeps its id.
+    let change = storage.notarize(id, &seed, 1_000).unwrap();
+    assert_eq!(change.id, id);
+    assert!(change.valid_signatures());
+
+    let mut loaded = storage.load(id).unwrap();
+    let notarizations = loaded.notarizations().collect::<Vec<_>>();
+    assert_eq!(notarizations.len(), 1);
+    assert_eq!(&notarizations[0].key, seed.public_key());
+    assert_eq!(notarizations[0].timestamp, 1_000);
+
+    // Notarizing again with the same key is a no-op.
+    let change = storage.notarize(id, &seed, 2_000).unwrap();
+    assert_eq!(change.notarizations().count(), 1);
+    assert_eq!(change.notarizations[0].timestamp, 1_000);
+
+    // Notarizations can't be altered without invalidating them.
+    loaded.notarizations[0].timestamp = 1;
+    assert_eq!(loaded.notarizations().count(), 0);
+}
+
+#[test]
+fn verify_cob() {
+    let storage = test::Storage::new();
+    let signer = gen::<MockSigner>(1);
+    let terry = test::Person::new(&storage, "terry", *signer.public_key()).unwrap();
+    let proj = test::Project::new(&storage, "discworld", *signer.public_key()).unwrap();
+    let proj = test::RemoteProject {
+        project: proj,
+        person: terry,
+    };
+    let typename = "xyz.rad.issue".parse::<TypeName>().unwrap();
+    let cob = create(
+        &storage,
+        &signer,
+        &proj,
+        &proj.identifier(),
+        Create {
+            history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
+            contents: nonempty!(b"issue 1".to_vec()),
+            typename: typename.clone(),
+            message: "creating xyz.rad.issue".to_string(),
+            encryption: None,
+            attachments: BTreeMap::new(),
+        },
+    )
+    .unwrap();
+    update(
+        &storage,
+        &signer,
+        &proj,
+        &proj.identifier(),
+        Update {
+            changes: nonempty!(b"comment 1".to_vec()),
+            history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
+            object_id: *cob.id(),
+            typename: typename.clone(),
+            message: "commenting xyz.rad.issue".to_string(),
+            attachments: BTreeMap::new(),
+            expected_tips: None,
+        },
+    )
+    .unwrap();
+
+    let verification = verify(&storage, &typename, cob.id()).unwrap();
+    assert!(verification.is_ok());
+    assert_eq!(verification.changes, 2);
+
+    // Point the issue reference to a change of another type.
+    let patch = create(
+        &storage,
+        &signer,
+        &proj,
+        &proj.identifier(),
+        Create {
+            history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
+            contents: nonempty!(b"patch 1".to_vec()),
+            typename: "xyz.rad.patch".parse().unwrap(),
+            message: "creating xyz.rad.patch".to_string(),
+            encryption: None,
+            attachments: BTreeMap::new(),
+        },
+    )
+    .unwrap();
+    let change = storage.load((*patch.id()).into()).unwrap();
+    object::Storage::update(&storage, &proj.identifier(), &typename, cob.id(), &change).unwrap();
+
+    let verification = verify(&storage, &typename, cob.id()).unwrap();
+    assert_eq!(verification.failures.len(), 1);
+    assert_eq!(verification.failures[0].change, change.id);
+    assert_eq!(verification.failures[0].signer, Some(*signer.public_key()));
+    assert!(matches!(
+        verification.failures[0].reason,
+        object::verify::Invalid::TypeName { .. }
+    ));
+}
+
+#[test]
+fn schema_validation() {
+    let typename = "xyz.rad.issue".parse::<TypeName>().unwrap();
+    let mut schemas = Schemas::default();
+
+    assert!(schemas.validate(&typename, &nonempty![vec![]]).is_ok());
+
+    schemas.register(typename.clone(), |contents| {
+        if contents.iter().all(|op| !op.is_empty()) {
+            Ok(())
+        } else {
+            Err("empty operation".into())
+        }
+    });
+    assert!(schemas.validate(&typename, &nonempty![vec![1]]).is_ok());
+    assert!(schemas
+        .validate(&typename, &nonempty![vec![1], vec![]])
+        .is_err());
+}
+
+#[test]
+fn encrypted_contents() {
+    let storage = test::Storage::new();
+    let sk = SecretKey::from(KeyPair::from_seed(Seed::new([1; 32])).sk);
+    let signer = MockSigner::from(sk.clone());
+    let eve = gen::<MockSigner>(1);
+    let terry = test::Person::new(&storage, "terry", *signer.public_key()).unwrap();
+    let proj = test::Project::new(&storage, "discworld", *signer.public_key()).unwrap();
+    let proj = test::RemoteProject {
+        project: proj,
+        person: terry,
+    };
+    let typename = "xyz.rad.issue".parse::<TypeName>().unwrap();
+    let encryption = Encryption::seal([*signer.public_key()]);
+    let cob = create(
+        &storage,
+        &signer,
+        &proj,
+        &proj.identifier(),
+        Create {
+            history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
+            contents: nonempty!(b"issue 1".to_vec()),
+            typename: typename.clone(),
+            message: "creating xyz.rad.issue".to_string(),
+            encryption: Some(encryption.clone()),
+            attachments: BTreeMap::new(),
+        },
+    )
+    .unwrap();
+
+    let cob = get(&storage, &typename, cob.id()).unwrap().unwrap();
+    assert_eq!(cob.manifest().encryption, Some(encryption.clone()));
+    assert!(encryption.is_recipient(signer.public_key()));
+    assert!(!encryption.is_recipient(eve.public_key()));
+
+    // Only ciphertext is stored.
+    assert!(cob
+        .history()
+        .iter()
+        .all(|e| e.contents() != &nonempty!(b"issue 1".to_vec())));
+
+    let decrypted = cob.decrypt(&sk).unwrap();
+    let contents = decrypted.history().iter().map(|e| e.contents().clone());
+    assert_eq!(
+        contents.collect::<Vec<_>>(),
+        vec![nonempty!(b"issue 1".to_vec())]
+    );
+    assert_eq!(decrypted.manifest().encryption, None);
+}
+
+#[test]
+fn attachments() {
+    let storage = test::Storage::new();
+    let signer = gen::<MockSigner>(1);
+    let terry = test::Person::new(&storage, "terry", *signer.public_key()).unwrap();
+    let proj = test::Project::new(&storage, "discworld", *signer.public_key()).unwrap();
+    let proj = test::RemoteProject {
+        project: proj,
+        person: terry,
+    };
+    let typename = "xyz.rad.issue".parse::<TypeName>().unwrap();
+    let cob = create(
+        &storage,
+        &signer,
+        &proj,
+        &proj.identifier(),
+        Create {
+            history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
+            contents: nonempty!(b"issue 1".to_vec()),
+            typename: typename.clone(),
+            message: "creating xyz.rad.issue".to_string(),
+            encryption: None,
+            attachments: BTreeMap::from([("screenshot.png".to_owned(), vec![1, 2, 3])]),
+        },
+    )
+    .unwrap();
+
+    let cob = get(&storage, &typename, cob.id()).unwrap().unwrap();
+    let root = cob.history().iter().next().unwrap();
+    let id = root.attachments().get("screenshot.png").unwrap();
+    assert_eq!(root.attachments().len(), 1);
+    assert_eq!(storage.attachment(*id).unwrap(), vec![1, 2, 3]);
+
+    let err = update(
+        &storage,
+        &signer,
+        &proj,
+        &proj.identifier(),
+        Update {
+            changes: nonempty!(b"comment".to_vec()),
+            object_id: *cob.id(),
+            typename: typename.clone(),
+            message: "commenting xyz.rad.issue".to_string(),
+            history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
+            attachments: BTreeMap::from([(
+                "huge.log".to_owned(),
+                vec![0; attachment::MAX_SIZE + 1],
+            )]),
+            expected_tips: None,
+        },
+    );
+    assert!(err.is_err());
+
+    let err = update(
+        &storage,
+        &signer,
+        &proj,
+        &proj.identifier(),
+        Update {
+            changes: nonempty!(b"comment".to_vec()),
+            object_id: *cob.id(),
+            typename,
+            message: "commenting xyz.rad.issue".to_string(),
+            history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Json,
+            attachments: BTreeMap::from([("../log".to_owned(), vec![])]),
+            expected_tips: None,
+        },
+    );
+    assert!(err.is_err());
+}
+
+#[test]
+fn typed_contents() {
+    let storage = test::Storage::new();
+    let signer = gen::<MockSigner>(1);
+    let terry = test::Person::new(&storage, "terry", *signer.public_key()).unwrap();
+    let proj = test::Project::new(&storage, "discworld", *signer.public_key()).unwrap();
+    let proj = test::RemoteProject {
+        project: proj,
+        person: terry,
+    };
+    let typename = "xyz.rad.issue".parse::<TypeName>().unwrap();
+    let ops = vec![
+        BTreeMap::from([("title", "The Colour of Magic")]),
+        BTreeMap::from([("comment", "Rincewind")]),
+    ];
+    let contents = ops
+        .iter()
+        .map(|op| Encoding::Cbor.encode(op))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert!(contents[0].len() < Encoding::Json.encode(&ops[0]).unwrap().len());
+
+    let cob = create(
+        &storage,
+        &signer,
+        &proj,
+        &proj.identifier(),
+        Create {
+            history_type: "test".to_string(),
+            version: 0,
+            encoding: Encoding::Cbor,
+            contents: nonempty::NonEmpty::from_vec(contents).unwrap(),
+            typename: typename.clone(),
+            message: "creating xyz.rad.issue".to_string(),
+            encryption: None,
+            attachments: BTreeMap::new(),
+        },
+    )
+    .unwrap();
+    let cob = get(&storage, &typename, cob.id()).unwrap().unwrap();
+    let entry = cob.history().root().unwrap();
+
+    assert_eq!(entry.encoding(), Encoding::Cbor);
+    assert_eq!(
+        entry
+            .typed_contents()
+            .decode::<BTreeMap<String, String>>()
+            .unwrap()
+            .into_iter()
+            .map(|op| op.into_values().collect::<Vec<_>>())
+            .collect::<Vec<_>>(),
+        vec![
+            vec!["The Colour of Magic".to_owned()],
+            vec!["Rincewind".to_owned()]
+        ]
+    );
+    // Decoding with the wrong encoding fails.
+    assert!(Encoding::Json
+        .decode::<BTreeMap<String, String>>(&entry.contents().head)
+        .is_err());
+}
+
+#[test]
+fn history_iter() {
+    let signer = gen::<MockSigner>(1);
+    let actor = *signer.public_key();
+    let oid = |i: u8| git2::Oid::from_bytes(&[i; 20]).unwrap();
+    let mut history = History::new_from_root(oid(0), actor, oid(0).into(), nonempty![vec![0]], 0);
+
+    for i in 1..5 {
+        history.extend(oid(i), actor, oid(0).into(), nonempty![vec![i]], i as u64);
+    }
+    assert_eq!(history.len(), 5);
+
+    let all = history.iter().map(|e| e.contents().head[0]);
+    assert_eq!(all.collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+
+    let latest = history.iter().skip(3).map(|e| e.contents().head[0]);
+    assert_eq!(latest.collect::<Vec<_>>(), vec![3, 4]);
+}
+
+#[test]
+fn history_diamond() {
+    let signer = gen::<MockSigner>(1);
+    let actor = *signer.public_key();
+    let oid = |i: u8| git2::Oid::from_bytes(&[i; 20]).unwrap();
+    let entry = |i: u8, children: &[u8], clock| EntryWithClock {
+        entry: Entry::new(
+            oid(i),
+            actor,
+            oid(0).into(),
+            children.iter().map(|c| oid(*c)),
+            nonempty![vec![i]],
+            i as u64,
+        ),
+        clock,
+    };
+    //     0
+    //    / \
+    //   1   2
+    //    \ /
+    //     3
+    let entries = [
+        entry(0, &[1, 2], 1),
+        entry(1, &[3], 2),
+        entry(2, &[3], 2),
+        entry(3, &[], 3),
+    ]
+    .into_iter()
+    .map(|e| (*e.id(), e))
+    .collect();
+    let history = History::new(oid(0), entries).unwrap();
+
+    assert_eq!(history.len(), 4);
+    assert_eq!(history.tips(), BTreeSet::from([oid(3).into()]));
+    assert_eq!(
+        history
+            .iter()
+            .map(|e| e.contents().head[0])
+            .collect::<Vec<_>>(),
+        vec![0, 1, 2, 3]
+    );
+}
+
 #[quickcheck]
 fn parse_refstr(oid: ObjectId, typename: TypeName) {
     let suffix = refname!("refs/cobs")
@@ -0,0 +1,33 @@
+//! Compare the performance of `VecMap` and `GMap` on a merge-heavy workload, similar
+//! to replaying the reactions of a large issue thread.
+//!
+//! Run with `cargo bench -p radicle-crdt --bench merge`.
+use std::time::Instant;
+
+use radicle_crdt::{GMap, Max, Semilattice, VecMap};
+
+fn main() {
+    let rng = fastrand::Rng::with_seed(0);
+    let ops = (0..1_000_000)
+        .map(|_| (rng.u8(..8), Max::from(rng.u64(..))))
+        .collect::<Vec<_>>();
+
+    let start = Instant::now();
+    let mut gmap = GMap::default();
+    for (k, v) in ops.iter().copied() {
+        gmap = gmap.join(GMap::singleton(k, v));
+    }
+    println!("GMap: {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let mut vecmap = VecMap::default();
+    for (k, v) in ops.iter().copied() {
+        vecmap = vecmap.join(VecMap::singleton(k, v));
+    }
+    println!("VecMap: {:?}", start.elapsed());
+
+    assert_eq!(
+        vecmap.iter().collect::<Vec<_>>(),
+        gmap.iter().collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,135 @@
+//! [`qcheck::Arbitrary`] instances for the CRDT types, to property-test compositions of them.
+use qcheck::Arbitrary;
+
+use crate::clock::{Hybrid, Lamport, Physical, VClock};
+use crate::{
+    GMap, GSet, LWWMap, LWWReg, LWWSet, Max, Min, PNCounter, Redactable, RedactableWith,
+    Semilattice, VecMap,
+};
+
+impl<T: Arbitrary> Arbitrary for Max<T> {
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        Self::from(T::arbitrary(g))
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for Min<T> {
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        Self::from(T::arbitrary(g))
+    }
+}
+
+impl Arbitrary for Lamport {
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        Self::from(u64::arbitrary(g))
+    }
+}
+
+impl Arbitrary for Physical {
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        Self::new(u64::arbitrary(g))
+    }
+}
+
+impl Arbitrary for Hybrid {
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        Self::new(Physical::arbitrary(g), u64::arbitrary(g))
+    }
+}
+
+impl<A: Arbitrary + Ord> Arbitrary for VClock<A> {
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        let mut clock = Self::default();
+        for actor in Vec::<A>::arbitrary(g) {
+            clock.tick(actor);
+        }
+        clock
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for Redactable<T> {
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        Self::from(Option::<T>::arbitrary(g))
+    }
+}
+
+impl<T: Arbitrary, M: Arbitrary> Arbitrary for RedactableWith<T, M> {
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        if bool::arbitrary(g) {
+            Self::Present(T::arbitrary(g))
+        } else {
+            Self::Redacted(M::arbitrary(g))
+        }
+    }
+}
+
+impl<T, C> Arbitrary for LWWReg<T, C>
+where
+    T: Arbitrary + Semilattice,
+    C: Arbitrary + PartialOrd,
+{
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        Self::new(T::arbitrary(g), C::arbitrary(g))
+    }
+}
+
+impl<K, V, C> Arbitrary for LWWMap<K, V, C>
+where
+    K: Arbitrary + Ord,
+    V: Arbitrary + Semilattice,
+    C: Arbitrary + Ord,
+{
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        Self::from_iter(Vec::<(K, V, C)>::arbitrary(g))
+    }
+}
+
+impl<T, C> Arbitrary for LWWSet<T, C>
+where
+    T: Arbitrary + Ord,
+    C: Arbitrary + Ord,
+{
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        Self::from_iter(Vec::<(T, C)>::arbitrary(g))
+    }
+}
+
+impl<K, V> Arbitrary for GMap<K, V>
+where
+    K: Arbitrary + Ord,
+    V: Arbitrary + Semilattice,
+{
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        Self::from_iter(Vec::<(K, V)>::arbitrary(g))
+    }
+}
+
+impl<K, V> Arbitrary for VecMap<K, V>
+where
+    K: Arbitrary + Ord,
+    V: Arbitrary + Semilattice,
+{
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        Self::from_iter(Vec::<(K, V)>::arbitrary(g))
+    }
+}
+
+impl<T: Arbitrary + Ord> Arbitrary for GSet<T> {
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        Self::from_iter(Vec::<T>::arbitrary(g))
+    }
+}
+
+impl<A: Arbitrary + Ord> Arbitrary for PNCounter<A> {
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        let mut counter = Self::default();
+        for (actor, incr, n) in Vec::<(A, bool, u32)>::arbitrary(g) {
+            if incr {
+                counter.incr(actor, n as u64);
+            } else {
+                counter.decr(actor, n as u64);
+            }
+        }
+        counter
+    }
+}
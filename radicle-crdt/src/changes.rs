@@ -0,0 +1,73 @@
+/// Changes between two states of a CRDT container, eg. an older and a newer state.
+///
+/// Elements are identified by their key in maps, or their value in sets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Changes<'a, K> {
+    /// Elements that are only in the newer state.
+    pub added: Vec<&'a K>,
+    /// Elements that are only in the older state.
+    pub removed: Vec<&'a K>,
+    /// Elements that are in both states, but with a different value.
+    pub updated: Vec<&'a K>,
+}
+
+impl<'a, K> Changes<'a, K> {
+    /// Check whether there are no changes.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+
+    /// Return the total number of changes.
+    pub fn len(&self) -> usize {
+        self.added.len() + self.removed.len() + self.updated.len()
+    }
+
+    /// Compute the changes between two sequences of key-value pairs, sorted by key.
+    pub(crate) fn between<V: PartialEq + 'a>(
+        old: impl IntoIterator<Item = (&'a K, &'a V)>,
+        new: impl IntoIterator<Item = (&'a K, &'a V)>,
+    ) -> Self
+    where
+        K: Ord,
+    {
+        let mut changes = Self::default();
+        let mut old = old.into_iter().peekable();
+        let mut new = new.into_iter().peekable();
+
+        loop {
+            match (old.peek(), new.peek()) {
+                (Some((a, _)), Some((b, _))) if a < b => {
+                    changes.removed.extend(old.next().map(|(k, _)| k));
+                }
+                (Some((a, _)), Some((b, _))) if a > b => {
+                    changes.added.extend(new.next().map(|(k, _)| k));
+                }
+                (Some(_), Some(_)) => {
+                    if let (Some((k, a)), Some((_, b))) = (old.next(), new.next()) {
+                        if a != b {
+                            changes.updated.push(k);
+                        }
+                    }
+                }
+                (Some(_), None) => {
+                    changes.removed.extend(old.next().map(|(k, _)| k));
+                }
+                (None, Some(_)) => {
+                    changes.added.extend(new.next().map(|(k, _)| k));
+                }
+                (None, None) => break,
+            }
+        }
+        changes
+    }
+}
+
+impl<'a, K> Default for Changes<'a, K> {
+    fn default() -> Self {
+        Self {
+            added: Vec::new(),
+            removed: Vec::new(),
+            updated: Vec::new(),
+        }
+    }
+}
@@ -1,15 +1,20 @@
+use std::cmp::Ordering;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
 use num_traits::Bounded;
-use serde::{Deserialize, Serialize};
 
+use crate::gmap::GMap;
 use crate::ord::Max;
-use crate::Semilattice as _;
+use crate::{Delta, Semilattice};
 
 /// Lamport clock.
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-#[serde(transparent)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Lamport {
     counter: Max<u64>,
 }
@@ -64,8 +69,12 @@ impl Bounded for Lamport {
 }
 
 /// Physical clock. Tracks real-time by the second.
-#[derive(Debug, Default, Copy, Clone, PartialOrd, PartialEq, Ord, Eq, Serialize, Deserialize)]
-#[serde(transparent)]
+#[derive(Debug, Default, Copy, Clone, PartialOrd, PartialEq, Ord, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Physical {
     seconds: u64,
 }
@@ -118,3 +127,320 @@ impl Bounded for Physical {
         }
     }
 }
+
+/// Hybrid logical clock.
+///
+/// Combines a physical timestamp with a logical counter, so that timestamps are monotonic
+/// and respect causality, even if the physical clocks of replicas go backwards or drift
+/// apart, while staying close to wall-clock time.
+#[derive(Debug, Default, Copy, Clone, PartialOrd, PartialEq, Ord, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hybrid {
+    /// The greatest physical time seen.
+    physical: Physical,
+    /// Counter for events with the same physical time.
+    counter: u64,
+}
+
+impl Hybrid {
+    /// Maximum number of seconds a received clock may be ahead of the local physical time.
+    /// Received clocks that are further ahead are clamped to this bound when merged, so that
+    /// a single replica with a skewed or malicious clock can't push every other clock into
+    /// the future.
+    pub const MAX_DRIFT: u64 = 60 * 5;
+
+    pub fn new(physical: Physical, counter: u64) -> Self {
+        Self { physical, counter }
+    }
+
+    /// The initial value of the clock.
+    pub fn initial() -> Self {
+        Self::default()
+    }
+
+    /// Return the physical component of the clock.
+    pub fn physical(&self) -> Physical {
+        self.physical
+    }
+
+    /// Return the logical component of the clock.
+    pub fn counter(&self) -> u64 {
+        self.counter
+    }
+
+    /// Advance the clock, given the current physical time, and return the new value.
+    /// Must be called before sending a message.
+    pub fn tick(&mut self, now: Physical) -> Self {
+        if now > self.physical {
+            self.physical = now;
+            self.counter = 0;
+        } else {
+            self.counter = self.counter.saturating_add(1);
+        }
+        *self
+    }
+
+    /// Merge clock with the clock of a received message, given the current physical time,
+    /// and return the new value. Must be called whenever a message is received.
+    ///
+    /// The physical time of the received clock is bounded by [`Hybrid::MAX_DRIFT`].
+    pub fn merge(&mut self, mut other: Self, now: Physical) -> Self {
+        other.physical = other
+            .physical
+            .min(Physical::new(now.as_secs().saturating_add(Self::MAX_DRIFT)));
+
+        let physical = self.physical.max(other.physical).max(now);
+
+        self.counter = if physical == self.physical && physical == other.physical {
+            self.counter.max(other.counter).saturating_add(1)
+        } else if physical == self.physical {
+            self.counter.saturating_add(1)
+        } else if physical == other.physical {
+            other.counter.saturating_add(1)
+        } else {
+            0
+        };
+        self.physical = physical;
+
+        *self
+    }
+}
+
+impl Bounded for Hybrid {
+    fn min_value() -> Self {
+        Self::new(Physical::min_value(), u64::min_value())
+    }
+
+    fn max_value() -> Self {
+        Self::new(Physical::max_value(), u64::max_value())
+    }
+}
+
+/// Vector clock. Tracks a logical counter per actor, which allows detecting whether two
+/// events are causally related or concurrent.
+///
+/// The causal order is partial, and is given by [`VClock::causal_cmp`]. To be usable as the
+/// clock of [`crate::LWWReg`] and [`crate::LWWMap`], vector clocks are also totally ordered
+/// via [`Ord`], using a linear extension of the causal order: clocks are compared by the sum
+/// of their counters, and then by their entries. This means that causally ordered writes
+/// always resolve in favor of the later write, while concurrent writes resolve
+/// deterministically; use [`VClock::is_concurrent`] to tell them apart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct VClock<A> {
+    counters: GMap<A, Max<u64>>,
+}
+
+impl<A: Ord> VClock<A> {
+    /// Return the counter of the given actor.
+    pub fn get(&self, actor: &A) -> u64 {
+        self.counters.get(actor).map_or(0, |c| *c.get())
+    }
+
+    /// Increment the counter of the given actor, eg. before an actor emits an event.
+    pub fn tick(&mut self, actor: A) {
+        let counter = self.get(&actor);
+        self.counters
+            .insert(actor, Max::from(counter.saturating_add(1)));
+    }
+
+    /// Compare two clocks causally. Returns `None` if the clocks are concurrent.
+    pub fn causal_cmp(&self, other: &Self) -> Option<Ordering> {
+        let le = self.counters.iter().all(|(a, c)| *c.get() <= other.get(a));
+        let ge = other.counters.iter().all(|(a, c)| *c.get() <= self.get(a));
+
+        match (le, ge) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Less),
+            (false, true) => Some(Ordering::Greater),
+            (false, false) => None,
+        }
+    }
+
+    /// Check whether two clocks are concurrent, ie. neither happened before the other.
+    pub fn is_concurrent(&self, other: &Self) -> bool {
+        self.causal_cmp(other).is_none()
+    }
+
+    /// Return the sum of all counters.
+    fn sum(&self) -> u64 {
+        self.counters
+            .values()
+            .fold(0, |acc, c| acc.saturating_add(*c.get()))
+    }
+}
+
+impl<A> Default for VClock<A> {
+    fn default() -> Self {
+        Self {
+            counters: GMap::default(),
+        }
+    }
+}
+
+impl<A: Ord> PartialOrd for VClock<A> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<A: Ord> Ord for VClock<A> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sum()
+            .cmp(&other.sum())
+            .then_with(|| self.counters.iter().cmp(other.counters.iter()))
+    }
+}
+
+impl<A: Ord> Semilattice for VClock<A> {
+    fn merge(&mut self, other: Self) {
+        self.counters.merge(other.counters);
+    }
+}
+
+impl<A: Ord> Delta for VClock<A> {
+    /// Tick the clock of the given actor.
+    type Op = A;
+
+    fn delta(&self, actor: Self::Op) -> Self {
+        let counter = self.get(&actor);
+
+        Self {
+            counters: GMap::singleton(actor, Max::from(counter.saturating_add(1))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use qcheck_macros::quickcheck;
+
+    use super::*;
+    use crate::LWWReg;
+
+    fn vclock(ticks: Vec<u8>) -> VClock<u8> {
+        let mut clock = VClock::default();
+        for actor in ticks {
+            clock.tick(actor % 4);
+        }
+        clock
+    }
+
+    #[quickcheck]
+    fn prop_vclock_semilattice(a: Vec<u8>, b: Vec<u8>, c: Vec<u8>) {
+        crate::test::assert_laws(&vclock(a), &vclock(b), &vclock(c));
+    }
+
+    #[quickcheck]
+    fn prop_vclock_order(a: Vec<u8>, b: Vec<u8>) {
+        let (a, b) = (vclock(a), vclock(b));
+
+        // The total order extends the causal order.
+        if let Some(ordering) = a.causal_cmp(&b) {
+            assert_eq!(a.cmp(&b), ordering);
+        }
+        // The join of two clocks is causally after both.
+        let joined = a.clone().join(b.clone());
+        assert_ne!(joined.causal_cmp(&a), Some(Ordering::Less));
+        assert_ne!(joined.causal_cmp(&b), Some(Ordering::Less));
+    }
+
+    #[quickcheck]
+    fn prop_lwwreg_semilattice(a: (u8, Vec<u8>), b: (u8, Vec<u8>), c: (u8, Vec<u8>)) {
+        let a = LWWReg::new(Max::from(a.0), vclock(a.1));
+        let b = LWWReg::new(Max::from(b.0), vclock(b.1));
+        let c = LWWReg::new(Max::from(c.0), vclock(c.1));
+
+        crate::test::assert_laws(&a, &b, &c);
+    }
+
+    #[test]
+    fn test_hybrid() {
+        let mut alice = Hybrid::initial();
+        let mut bob = Hybrid::initial();
+
+        let a1 = alice.tick(Physical::new(10));
+        assert_eq!(a1, Hybrid::new(Physical::new(10), 0));
+
+        // Alice's physical clock goes backwards.
+        let a2 = alice.tick(Physical::new(5));
+        assert!(a2 > a1);
+        assert_eq!(a2, Hybrid::new(Physical::new(10), 1));
+
+        // Bob's physical clock is behind, but receiving a message moves him forward.
+        let b1 = bob.merge(a2, Physical::new(3));
+        assert!(b1 > a2);
+        assert_eq!(b1, Hybrid::new(Physical::new(10), 2));
+
+        // When physical time catches up, the counter is reset.
+        let b2 = bob.tick(Physical::new(11));
+        assert_eq!(b2, Hybrid::new(Physical::new(11), 0));
+
+        let a3 = alice.merge(b2, Physical::new(12));
+        assert_eq!(a3, Hybrid::new(Physical::new(12), 0));
+    }
+
+    #[test]
+    fn test_hybrid_drift() {
+        let now = Physical::new(100);
+        let bound = Physical::new(100 + Hybrid::MAX_DRIFT);
+        let mut alice = Hybrid::initial();
+
+        // Clocks within the drift bound are merged as-is.
+        let a1 = alice.merge(Hybrid::new(bound, 3), now);
+        assert_eq!(a1, Hybrid::new(bound, 4));
+
+        // Clocks beyond the drift bound are clamped.
+        let mut bob = Hybrid::initial();
+        let b1 = bob.merge(Hybrid::new(Physical::max_value(), 3), now);
+        assert_eq!(b1, Hybrid::new(bound, 4));
+
+        let b2 = bob.merge(Hybrid::new(bound + 1, 0), now);
+        assert_eq!(b2, Hybrid::new(bound, 5));
+
+        // Once physical time catches up, the clock moves forward again.
+        let b3 = bob.tick(bound + 1);
+        assert_eq!(b3, Hybrid::new(bound + 1, 0));
+    }
+
+    #[test]
+    fn test_causal_cmp() {
+        let mut alice = VClock::default();
+        alice.tick('a');
+
+        let mut bob = alice.clone();
+        assert_eq!(alice.causal_cmp(&bob), Some(Ordering::Equal));
+
+        bob.tick('b');
+        assert_eq!(alice.causal_cmp(&bob), Some(Ordering::Less));
+        assert_eq!(bob.causal_cmp(&alice), Some(Ordering::Greater));
+
+        alice.tick('a');
+        assert!(alice.is_concurrent(&bob));
+        assert!(!alice.is_concurrent(&alice.clone().join(bob.clone())));
+    }
+
+    #[test]
+    fn test_lwwreg_causal() {
+        let mut clock = VClock::default();
+        clock.tick('a');
+        clock.tick('a');
+
+        let mut reg = LWWReg::new(Max::from("first"), clock.clone());
+
+        // A causally later write wins.
+        let mut later = clock.clone();
+        later.tick('b');
+        reg.set("second", later.clone());
+        assert_eq!(*reg.get(), Max::from("second"));
+
+        // An older write loses.
+        reg.set("zeroth", clock);
+        assert_eq!(*reg.get(), Max::from("second"));
+        assert!(!reg.clock().get().is_concurrent(&later));
+    }
+}
@@ -1,13 +1,18 @@
-use std::collections::btree_map::{Entry, IntoIter};
+use std::collections::btree_map::{self, IntoIter};
 use std::collections::BTreeMap;
 use std::ops::Deref;
 
-use crate::Semilattice;
+use crate::{Changes, Delta, Semilattice, Tombstone};
 
 /// Grow-only map.
 ///
 /// Conflicting elements are merged via the [`Semilattice`] instance.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct GMap<K, V> {
     inner: BTreeMap<K, V>,
 }
@@ -24,32 +29,108 @@ impl<K: Ord, V: Semilattice> GMap<K, V> {
     }
 
     pub fn insert(&mut self, key: K, value: V) {
-        match self.inner.entry(key) {
-            Entry::Occupied(mut e) => {
-                e.get_mut().merge(value);
+        self.entry(key).merge(value);
+    }
+
+    /// Get the entry of a key, for in-place manipulation.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        Entry {
+            inner: self.inner.entry(key),
+        }
+    }
+
+    /// Merge many entries at once. This is faster than inserting entries one by one,
+    /// especially into an empty map.
+    pub fn merge_from(&mut self, iter: impl IntoIterator<Item = (K, V)>) {
+        let mut entries = iter.into_iter().collect::<Vec<_>>();
+        // Stable sort, so that entries with the same key are merged in order.
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut merged: Vec<(K, V)> = Vec::with_capacity(entries.len());
+        for (k, v) in entries {
+            match merged.last_mut() {
+                Some((last, value)) if *last == k => value.merge(v),
+                _ => merged.push((k, v)),
             }
-            Entry::Vacant(e) => {
-                e.insert(value);
+        }
+
+        if self.inner.is_empty() {
+            // Building a map from sorted entries is done in linear time.
+            self.inner = BTreeMap::from_iter(merged);
+        } else {
+            for (k, v) in merged {
+                self.insert(k, v);
             }
         }
     }
 }
 
+/// An entry of a [`GMap`], returned by [`GMap::entry`].
+pub struct Entry<'a, K, V> {
+    inner: btree_map::Entry<'a, K, V>,
+}
+
+impl<'a, K: Ord, V: Semilattice> Entry<'a, K, V> {
+    /// Get the entry key.
+    pub fn key(&self) -> &K {
+        self.inner.key()
+    }
+
+    /// Merge a value into the entry, inserting it if the entry is vacant.
+    pub fn merge(self, value: V) -> &'a mut V {
+        match self.inner {
+            btree_map::Entry::Occupied(e) => {
+                let v = e.into_mut();
+                v.merge(value);
+                v
+            }
+            btree_map::Entry::Vacant(e) => e.insert(value),
+        }
+    }
+
+    /// Insert a value computed by the given function if the entry is vacant.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, f: F) -> &'a mut V {
+        self.inner.or_insert_with(f)
+    }
+
+    /// Insert the default value if the entry is vacant.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.inner.or_default()
+    }
+}
+
+impl<K: Ord, V: PartialEq> GMap<K, V> {
+    /// Compute the changes from this map to the other map, eg. a newer state of it.
+    pub fn diff<'a>(&'a self, other: &'a Self) -> Changes<'a, K> {
+        Changes::between(&self.inner, &other.inner)
+    }
+}
+
+impl<K: Ord, V: Semilattice + Tombstone> GMap<K, V> {
+    /// Drop the entries whose value is a tombstone, and for which `stable` returns `true`.
+    ///
+    /// This is only safe for tombstones that are causally stable, ie. that were seen by all
+    /// replicas, otherwise removed entries could reappear on merge.
+    pub fn compact(&mut self, mut stable: impl FnMut(&K, &V) -> bool) {
+        self.inner
+            .retain(|k, v| !(v.is_tombstone() && stable(k, v)));
+    }
+}
+
 impl<K: Ord, V: Semilattice> FromIterator<(K, V)> for GMap<K, V> {
     fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
         let mut map = GMap::default();
-        for (k, v) in iter.into_iter() {
-            map.insert(k, v);
-        }
+        map.merge_from(iter);
         map
     }
 }
 
 impl<K: Ord, V: Semilattice> Extend<(K, V)> for GMap<K, V> {
     fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
-        for (k, v) in iter.into_iter() {
-            self.insert(k, v);
-        }
+        self.merge_from(iter);
     }
 }
 
@@ -78,6 +159,14 @@ impl<K: Ord, V: Semilattice> Semilattice for GMap<K, V> {
     }
 }
 
+impl<K: Ord, V: Semilattice> Delta for GMap<K, V> {
+    type Op = (K, V);
+
+    fn delta(&self, (key, value): Self::Op) -> Self {
+        Self::singleton(key, value)
+    }
+}
+
 impl<K, V> Deref for GMap<K, V> {
     type Target = BTreeMap<K, V>;
 
@@ -92,6 +181,7 @@ mod tests {
 
     use super::*;
     use crate::ord::Max;
+    use crate::Redactable;
 
     #[quickcheck]
     fn prop_semilattice(
@@ -109,4 +199,50 @@ mod tests {
 
         crate::test::assert_laws(&a, &b, &c);
     }
+
+    #[quickcheck]
+    fn prop_merge_from(a: Vec<(u8, Max<u8>)>, b: Vec<(u8, Max<u8>)>) {
+        let mut expected = GMap::default();
+        for (k, v) in a.iter().chain(b.iter()) {
+            expected.insert(*k, *v);
+        }
+        let mut map = GMap::default();
+        map.merge_from(a);
+        map.merge_from(b);
+
+        assert_eq!(map, expected);
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut map = GMap::singleton('a', Max::from(1));
+
+        assert_eq!(*map.entry('a').merge(Max::from(0)), Max::from(1));
+        assert_eq!(*map.entry('a').merge(Max::from(2)), Max::from(2));
+        assert_eq!(*map.entry('b').merge(Max::from(3)), Max::from(3));
+        assert_eq!(
+            *map.entry('c').or_insert_with(|| Max::from(4)),
+            Max::from(4)
+        );
+        assert_eq!(map.entry('c').key(), &'c');
+
+        map.entry('d').or_default().merge(Max::from(5));
+        assert_eq!(map.get(&'d'), Some(&Max::from(5)));
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut map = GMap::default();
+
+        map.insert(0, Redactable::Present('a'));
+        map.insert(1, Redactable::Present('b'));
+        map.insert(2, Redactable::Present('c'));
+        map.insert(0, Redactable::Redacted);
+        map.insert(2, Redactable::Redacted);
+        map.compact(|k, _| *k < 2);
+
+        assert!(!map.contains_key(&0));
+        assert_eq!(map.get(&1), Some(&Redactable::Present('b')));
+        assert_eq!(map.get(&2), Some(&Redactable::Redacted));
+    }
 }
@@ -0,0 +1,162 @@
+use std::collections::btree_set::{IntoIter, Iter};
+use std::collections::BTreeSet;
+
+use crate::{Changes, Delta, Semilattice};
+
+/// Grow-only set.
+///
+/// Values can be added but never removed, which makes merging a simple union.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct GSet<T: Ord> {
+    inner: BTreeSet<T>,
+}
+
+impl<T: Ord> GSet<T> {
+    pub fn singleton(value: T) -> Self {
+        Self {
+            inner: BTreeSet::from_iter([value]),
+        }
+    }
+
+    /// Add a value to the set. Returns `false` if the value was already present.
+    pub fn insert(&mut self, value: T) -> bool {
+        self.inner.insert(value)
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.inner.contains(value)
+    }
+
+    /// Iterate over the values of the set, in ascending order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.inner.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Compute the values added from this set to the other set, eg. a newer state of it.
+    pub fn diff<'a>(&'a self, other: &'a Self) -> Changes<'a, T> {
+        Changes {
+            added: other.difference(self).collect(),
+            removed: self.difference(other).collect(),
+            updated: Vec::new(),
+        }
+    }
+
+    /// Check whether all values of this set are in the other set.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.inner.is_subset(&other.inner)
+    }
+
+    /// Iterate over the values of this set that aren't in the other set.
+    pub fn difference<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a T> {
+        self.inner.difference(&other.inner)
+    }
+}
+
+impl<T: Ord> Default for GSet<T> {
+    fn default() -> Self {
+        Self {
+            inner: BTreeSet::default(),
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for GSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self {
+            inner: BTreeSet::from_iter(iter),
+        }
+    }
+}
+
+impl<T: Ord> Extend<T> for GSet<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.inner.extend(iter)
+    }
+}
+
+impl<T: Ord> IntoIterator for GSet<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a GSet<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.iter()
+    }
+}
+
+impl<T: Ord> Semilattice for GSet<T> {
+    fn merge(&mut self, other: Self) {
+        self.inner.extend(other.inner);
+    }
+}
+
+impl<T: Ord> Delta for GSet<T> {
+    type Op = T;
+
+    fn delta(&self, value: Self::Op) -> Self {
+        Self::singleton(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use qcheck_macros::quickcheck;
+
+    use super::*;
+
+    #[quickcheck]
+    fn prop_semilattice(a: Vec<u8>, b: Vec<u8>, c: Vec<u8>, mix: Vec<u8>) {
+        let mut a = GSet::from_iter(a);
+        let mut b = GSet::from_iter(b);
+        let c = GSet::from_iter(c);
+
+        a.extend(mix.clone());
+        b.extend(mix);
+
+        crate::test::assert_laws(&a, &b, &c);
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut set = GSet::default();
+
+        assert!(set.insert('a'));
+        assert!(set.insert('b'));
+        assert!(!set.insert('a'));
+        assert!(set.contains(&'a'));
+        assert!(!set.contains(&'c'));
+        assert_eq!(set.len(), 2);
+
+        let other = GSet::from_iter(['b', 'c']);
+        assert_eq!(set.difference(&other).collect::<Vec<_>>(), vec![&'a']);
+        assert!(!set.is_subset(&other));
+
+        let changes = set.diff(&other);
+        assert_eq!(changes.added, vec![&'c']);
+        assert_eq!(changes.removed, vec![&'a']);
+
+        let merged = set.join(other);
+        assert_eq!(merged.iter().collect::<String>(), "abc");
+    }
+}
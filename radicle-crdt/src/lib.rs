@@ -2,26 +2,42 @@
 #![allow(clippy::bool_assert_comparison)]
 #![allow(clippy::collapsible_else_if)]
 #![allow(clippy::type_complexity)]
+#[cfg(any(test, feature = "arbitrary"))]
+pub mod arbitrary;
+pub mod changes;
 pub mod clock;
 pub mod gmap;
+pub mod gset;
 pub mod lwwmap;
 pub mod lwwreg;
 pub mod lwwset;
+pub mod mvreg;
 pub mod ord;
+pub mod pncounter;
 pub mod redactable;
+pub mod seq;
+pub mod tree;
+pub mod vecmap;
 
 #[cfg(any(test, feature = "test"))]
 pub mod test;
 
 ////////////////////////////////////////////////////////////////////////////////
 
-pub use clock::Lamport;
+pub use changes::Changes;
+pub use clock::{Hybrid, Lamport, VClock};
 pub use gmap::GMap;
+pub use gset::GSet;
 pub use lwwmap::LWWMap;
-pub use lwwreg::LWWReg;
+pub use lwwreg::{LWWReg, LWWRegBy};
 pub use lwwset::LWWSet;
+pub use mvreg::MVReg;
 pub use ord::{Max, Min};
-pub use redactable::Redactable;
+pub use pncounter::PNCounter;
+pub use redactable::{Redactable, RedactableWith};
+pub use seq::Seq;
+pub use tree::Tree;
+pub use vecmap::VecMap;
 
 ////////////////////////////////////////////////////////////////////////////////
 
@@ -69,6 +85,42 @@ impl Semilattice for bool {
     }
 }
 
+/// A join-semilattice supporting delta-mutations.
+///
+/// Instead of producing a new full state, a delta-mutator computes a *delta*: a usually
+/// small state which has the same effect as the operation when merged into this replica,
+/// or any other replica that has seen the same state. Deltas are themselves semilattices,
+/// and can be joined together before being sent, so that replicas exchange deltas instead
+/// of their full state.
+pub trait Delta: Semilattice {
+    /// The operations supported by the delta-mutator.
+    type Op;
+
+    /// Compute the delta of an operation on this state, without applying it.
+    fn delta(&self, op: Self::Op) -> Self;
+
+    /// Apply an operation to this state, and return its delta.
+    fn mutate(&mut self, op: Self::Op) -> Self
+    where
+        Self: Clone,
+    {
+        let delta = self.delta(op);
+        self.merge(delta.clone());
+
+        delta
+    }
+}
+
+/// A value which can mark the removal of an element, eg. in a map.
+///
+/// Tombstones are kept so that removals take precedence over older insertions, but can be
+/// garbage collected once the removal is *causally stable*, ie. once every replica has seen
+/// it, since no older insertion can then be received.
+pub trait Tombstone {
+    /// Check whether this value is a tombstone.
+    fn is_tombstone(&self) -> bool;
+}
+
 pub fn fold<S>(i: impl IntoIterator<Item = S>) -> S
 where
     S: Semilattice + Default,
@@ -78,9 +130,45 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::{test, Max, Min, Semilattice};
+    use crate::{lwwset, pncounter, test, Delta, LWWSet, Max, Min, PNCounter, Semilattice};
     use qcheck_macros::quickcheck;
 
+    #[quickcheck]
+    fn prop_delta_lwwset(base: Vec<(u8, u16)>, ops: Vec<(bool, u8, u16)>) {
+        let mut a = LWWSet::from_iter(base);
+        let mut b = a.clone();
+        let mut deltas = LWWSet::default();
+
+        for (insert, value, clock) in ops {
+            let op = if insert {
+                lwwset::Op::Insert(value, clock)
+            } else {
+                lwwset::Op::Remove(value, clock)
+            };
+            deltas.merge(a.mutate(op));
+        }
+        b.merge(deltas);
+
+        assert_eq!(a, b);
+    }
+
+    #[quickcheck]
+    fn prop_delta_pncounter(ops: Vec<(bool, u8, u8)>) {
+        let mut a = PNCounter::default();
+        let mut b = PNCounter::default();
+
+        for (incr, actor, n) in ops {
+            let op = if incr {
+                pncounter::Op::Incr(actor, n as u64)
+            } else {
+                pncounter::Op::Decr(actor, n as u64)
+            };
+            let delta = a.mutate(op);
+            b.merge(delta);
+        }
+        assert_eq!(a, b);
+    }
+
     #[quickcheck]
     fn prop_option_laws(a: Max<u8>, b: Max<u8>, c: Max<u8>) {
         test::assert_laws(&a, &b, &c);
@@ -91,6 +179,44 @@ mod tests {
         test::assert_laws(&a, &b, &c);
     }
 
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        use crate::{GMap, LWWMap, LWWReg, Lamport, Redactable, VClock};
+
+        fn roundtrip<T>(value: T)
+        where
+            T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+        {
+            let json = serde_json::to_string(&value).unwrap();
+            assert_eq!(serde_json::from_str::<T>(&json).unwrap(), value);
+        }
+
+        let mut clock = VClock::default();
+        clock.tick(String::from("alice"));
+
+        roundtrip(Max::from(42));
+        roundtrip(Min::from(42));
+        roundtrip(Lamport::from(7));
+        roundtrip(clock);
+        roundtrip(LWWReg::new(Max::from(1), Lamport::from(2)));
+        roundtrip(LWWMap::from_iter([(String::from("a"), Max::from(1), 3)]));
+        roundtrip(LWWSet::from_iter([(String::from("a"), 3)]));
+        roundtrip(GMap::from_iter([
+            (1, Redactable::Present(2)),
+            (2, Redactable::Redacted),
+        ]));
+
+        let mut counter = PNCounter::default();
+        counter.incr(String::from("alice"), 3);
+        roundtrip(counter);
+
+        assert_eq!(
+            serde_json::to_string(&LWWReg::new(Max::from(1), Lamport::from(2))).unwrap(),
+            r#"{"clock":2,"value":1}"#
+        );
+    }
+
     #[test]
     fn test_bool() {
         assert_eq!(false.join(false), false);
@@ -1,12 +1,17 @@
 use crate::gmap::GMap;
 use crate::lwwreg::LWWReg;
-use crate::{clock, Semilattice};
+use crate::{clock, Changes, Delta, Semilattice};
 
 /// Last-Write-Wins Map.
 ///
 /// In case a value is added and removed under a key at the same time,
 /// the "add" takes precedence over the "remove".
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct LWWMap<K, V, C = clock::Lamport> {
     inner: GMap<K, LWWReg<Option<V>, C>>,
 }
@@ -26,6 +31,11 @@ impl<K: Ord, V: Semilattice, C: PartialOrd + Ord> LWWMap<K, V, C> {
         value.get().as_ref()
     }
 
+    /// Return the time at which a key was last inserted or removed.
+    pub fn clock(&self, key: &K) -> Option<&C> {
+        self.inner.get(key).map(|reg| reg.clock().get())
+    }
+
     pub fn insert(&mut self, key: K, value: V, clock: C) {
         self.inner.insert(key, LWWReg::new(Some(value), clock));
     }
@@ -55,6 +65,23 @@ impl<K: Ord, V: Semilattice, C: PartialOrd + Ord> LWWMap<K, V, C> {
     pub fn is_empty(&self) -> bool {
         self.iter().next().is_none()
     }
+
+    /// Compute the changes from this map to the other map, eg. a newer state of it.
+    /// Removed keys are not considered part of the map.
+    pub fn diff<'a>(&'a self, other: &'a Self) -> Changes<'a, K>
+    where
+        V: PartialEq,
+    {
+        Changes::between(self.iter(), other.iter())
+    }
+
+    /// Drop the tombstones of keys removed at or before the given clock.
+    ///
+    /// The horizon must be causally stable, ie. all replicas must have seen every operation
+    /// up to it, otherwise removed keys could reappear on merge.
+    pub fn compact(&mut self, horizon: &C) {
+        self.inner.compact(|_, reg| reg.clock().get() <= horizon);
+    }
 }
 
 impl<K, V, C> Default for LWWMap<K, V, C> {
@@ -94,6 +121,34 @@ where
     }
 }
 
+/// Operation on an [`LWWMap`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op<K, V, C> {
+    /// Insert a value under a key.
+    Insert(K, V, C),
+    /// Remove a key.
+    Remove(K, C),
+}
+
+impl<K, V, C> Delta for LWWMap<K, V, C>
+where
+    K: Ord,
+    V: Semilattice,
+    C: Ord,
+{
+    type Op = Op<K, V, C>;
+
+    fn delta(&self, op: Self::Op) -> Self {
+        let (key, reg) = match op {
+            Op::Insert(key, value, clock) => (key, LWWReg::new(Some(value), clock)),
+            Op::Remove(key, clock) => (key, LWWReg::new(None, clock)),
+        };
+        Self {
+            inner: GMap::singleton(key, reg),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use qcheck_macros::quickcheck;
@@ -118,6 +173,44 @@ mod tests {
         crate::test::assert_laws(&a, &b, &c);
     }
 
+    #[test]
+    fn test_diff() {
+        let old = LWWMap::from_iter([
+            ('a', Max::from(1), 0),
+            ('b', Max::from(2), 0),
+            ('c', Max::from(3), 0),
+        ]);
+        let mut new = old.clone();
+
+        new.insert('b', Max::from(4), 1);
+        new.remove('c', 1);
+        new.insert('d', Max::from(5), 1);
+
+        let changes = old.diff(&new);
+        assert_eq!(changes.added, vec![&'d']);
+        assert_eq!(changes.removed, vec![&'c']);
+        assert_eq!(changes.updated, vec![&'b']);
+        assert_eq!(changes.len(), 3);
+        assert!(new.diff(&new).is_empty());
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut map = LWWMap::default();
+
+        map.insert('a', Max::from(1), 0);
+        map.insert('b', Max::from(2), 0);
+        map.insert('c', Max::from(3), 0);
+        map.remove('a', 1);
+        map.remove('b', 3);
+        map.compact(&2);
+
+        assert!(!map.inner.contains_key(&'a'));
+        assert!(map.inner.contains_key(&'b'));
+        assert_eq!(map.get(&'c'), Some(&Max::from(3)));
+        assert_eq!(map.len(), 1);
+    }
+
     #[test]
     fn test_insert() {
         let mut map = LWWMap::default();
@@ -1,13 +1,17 @@
+use std::cmp::Ordering;
+use std::fmt;
+
 use num_traits::Bounded;
 
 use crate::clock;
 use crate::ord::Max;
-use crate::Semilattice;
+use crate::{Delta, Semilattice, Tombstone};
 
 /// Last-Write-Wins Register.
 ///
 /// In case of conflict, uses the [`Semilattice`] instance of `T` to merge.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LWWReg<T, C = clock::Lamport> {
     clock: Max<C>,
     value: T,
@@ -46,6 +50,12 @@ impl<T: Semilattice, C: PartialOrd> LWWReg<T, C> {
     }
 }
 
+impl<T, C> Tombstone for LWWReg<Option<T>, C> {
+    fn is_tombstone(&self) -> bool {
+        self.value.is_none()
+    }
+}
+
 impl<T, C: Default> From<T> for LWWReg<T, C> {
     fn from(value: T) -> Self {
         Self {
@@ -74,6 +84,111 @@ where
     }
 }
 
+impl<T, C> Delta for LWWReg<T, C>
+where
+    T: Semilattice,
+    C: PartialOrd,
+{
+    type Op = (T, C);
+
+    fn delta(&self, (value, clock): Self::Op) -> Self {
+        Self::new(value, clock)
+    }
+}
+
+/// Resolves conflicts between values written with the same clock.
+///
+/// To preserve the semilattice laws, the resolver must define a total order over values:
+/// the greater value wins. Implemented for functions and closures.
+pub trait Resolver<T> {
+    /// Compare two conflicting values. The greater value wins.
+    fn resolve(&self, a: &T, b: &T) -> Ordering;
+}
+
+impl<T, F> Resolver<T> for F
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    fn resolve(&self, a: &T, b: &T) -> Ordering {
+        self(a, b)
+    }
+}
+
+/// Last-Write-Wins Register with a custom conflict resolver.
+///
+/// Like [`LWWReg`], but in case of conflict, uses the given [`Resolver`] to pick a value,
+/// instead of the [`Semilattice`] instance of `T`. This allows for domain-specific
+/// tie-breaking, eg. preferring the longest string, or the value of a given author.
+#[derive(Clone)]
+pub struct LWWRegBy<T, R, C = clock::Lamport> {
+    clock: Max<C>,
+    value: T,
+    resolver: R,
+}
+
+impl<T, R: Resolver<T>, C: PartialOrd> LWWRegBy<T, R, C> {
+    pub fn new(value: T, clock: C, resolver: R) -> Self {
+        Self {
+            clock: Max::from(clock),
+            value,
+            resolver,
+        }
+    }
+
+    pub fn set(&mut self, value: impl Into<T>, clock: C) {
+        let clock = Max::from(clock);
+        let value = value.into();
+
+        if clock == self.clock {
+            if self.resolver.resolve(&value, &self.value) == Ordering::Greater {
+                self.value = value;
+            }
+        } else if clock > self.clock {
+            self.clock.merge(clock);
+            self.value = value;
+        }
+    }
+
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    pub fn clock(&self) -> &Max<C> {
+        &self.clock
+    }
+
+    pub fn into_inner(self) -> (T, C) {
+        (self.value, self.clock.into_inner())
+    }
+}
+
+impl<T: PartialEq, R, C: PartialEq> PartialEq for LWWRegBy<T, R, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.clock == other.clock && self.value == other.value
+    }
+}
+
+impl<T: Eq, R, C: Eq> Eq for LWWRegBy<T, R, C> {}
+
+impl<T: fmt::Debug, R, C: fmt::Debug> fmt::Debug for LWWRegBy<T, R, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LWWRegBy")
+            .field("clock", &self.clock)
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl<T, R, C> Semilattice for LWWRegBy<T, R, C>
+where
+    R: Resolver<T>,
+    C: PartialOrd,
+{
+    fn merge(&mut self, other: Self) {
+        self.set(other.value, other.clock.into_inner());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use qcheck_macros::quickcheck;
@@ -90,6 +205,36 @@ mod tests {
         crate::test::assert_laws(&a, &b, &c);
     }
 
+    #[quickcheck]
+    fn prop_semilattice_by(a: (String, u8), b: (String, u8), c: (String, u8)) {
+        let longest = |a: &String, b: &String| a.len().cmp(&b.len()).then_with(|| a.cmp(b));
+        let a = LWWRegBy::new(a.0, a.1, longest);
+        let b = LWWRegBy::new(b.0, b.1, longest);
+        let c = LWWRegBy::new(c.0, c.1, longest);
+
+        crate::test::assert_laws(&a, &b, &c);
+    }
+
+    #[test]
+    fn test_resolver() {
+        // Prefer the value of a specific author, then the greatest value.
+        let author = 'b';
+        let resolver = move |a: &(char, &str), b: &(char, &str)| {
+            (a.0 == author).cmp(&(b.0 == author)).then_with(|| a.cmp(b))
+        };
+        let mut reg = LWWRegBy::new(('a', "zzz"), 0, resolver);
+
+        reg.set(('b', "aaa"), 0);
+        assert_eq!(reg.get(), &('b', "aaa"));
+
+        reg.set(('c', "zzz"), 0);
+        assert_eq!(reg.get(), &('b', "aaa"));
+
+        // Newer clocks still win, regardless of the resolver.
+        reg.set(('c', "zzz"), 1);
+        assert_eq!(reg.get(), &('c', "zzz"));
+    }
+
     #[test]
     fn test_merge() {
         let a = LWWReg::new(Max::from(0), 0);
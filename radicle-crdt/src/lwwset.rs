@@ -1,11 +1,19 @@
 use crate::clock;
-use crate::{lwwmap::LWWMap, Semilattice};
+use crate::{lwwmap, lwwmap::LWWMap, Changes, Delta, Semilattice};
 
 /// Last-Write-Wins Set.
 ///
+/// Each element has its own clock, which is the time it was last added or removed, so
+/// concurrent additions and removals of different elements are all preserved on merge.
+///
 /// In case the same value is added and removed at the same time,
 /// the "add" takes precedence over the "remove".
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct LWWSet<T, C = clock::Lamport> {
     inner: LWWMap<T, (), C>,
 }
@@ -29,6 +37,11 @@ impl<T: Ord, C: Ord> LWWSet<T, C> {
         self.inner.contains_key(value)
     }
 
+    /// Return the time at which a value was last added or removed.
+    pub fn clock(&self, value: &T) -> Option<&C> {
+        self.inner.clock(value)
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &T> {
         self.inner.iter().map(|(k, _)| k)
     }
@@ -36,6 +49,17 @@ impl<T: Ord, C: Ord> LWWSet<T, C> {
     pub fn is_empty(&self) -> bool {
         self.inner.is_empty()
     }
+
+    /// Compute the values added and removed from this set to the other set.
+    pub fn diff<'a>(&'a self, other: &'a Self) -> Changes<'a, T> {
+        self.inner.diff(&other.inner)
+    }
+
+    /// Drop the tombstones of values removed at or before the given clock.
+    /// See [`LWWMap::compact`].
+    pub fn compact(&mut self, horizon: &C) {
+        self.inner.compact(horizon);
+    }
 }
 
 impl<T, C> Default for LWWSet<T, C> {
@@ -74,6 +98,33 @@ where
     }
 }
 
+/// Operation on an [`LWWSet`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op<T, C> {
+    /// Insert a value.
+    Insert(T, C),
+    /// Remove a value.
+    Remove(T, C),
+}
+
+impl<T, C> Delta for LWWSet<T, C>
+where
+    T: Ord,
+    C: Ord + Default,
+{
+    type Op = Op<T, C>;
+
+    fn delta(&self, op: Self::Op) -> Self {
+        let op = match op {
+            Op::Insert(value, clock) => lwwmap::Op::Insert(value, (), clock),
+            Op::Remove(value, clock) => lwwmap::Op::Remove(value, clock),
+        };
+        Self {
+            inner: self.inner.delta(op),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,6 +193,28 @@ mod tests {
         assert!(set.contains(&'c')); // Insert precedence.
     }
 
+    #[test]
+    fn test_element_wise() {
+        let base = LWWSet::from_iter([('a', 0), ('b', 0)]);
+
+        let mut alice = base.clone();
+        alice.insert('c', 1);
+        alice.remove('a', 1);
+
+        let mut bob = base;
+        bob.insert('d', 1);
+        bob.remove('b', 2);
+
+        let merged = alice.join(bob);
+        let mut values = merged.iter().copied().collect::<Vec<_>>();
+        values.sort();
+
+        assert_eq!(values, vec!['c', 'd']);
+        assert_eq!(merged.clock(&'a'), Some(&1));
+        assert_eq!(merged.clock(&'b'), Some(&2));
+        assert_eq!(merged.clock(&'?'), None);
+    }
+
     #[test]
     fn test_remove_insert() {
         let mut set = LWWSet::default();
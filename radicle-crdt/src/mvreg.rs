@@ -0,0 +1,137 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{Delta, Semilattice};
+
+/// Multi-Value Register.
+///
+/// Unlike [`crate::LWWReg`], concurrent writes are all kept, so that conflicts can be
+/// surfaced and resolved by the user, by writing a new value.
+///
+/// Every write is identified by a unique id, and overwrites the values that were visible
+/// to the writer at the time. Values that were written concurrently don't overwrite each
+/// other, and are all visible after a merge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MVReg<I, T> {
+    /// All values ever written, by id.
+    values: BTreeMap<I, T>,
+    /// Ids of the values that were overwritten.
+    overwritten: BTreeSet<I>,
+}
+
+impl<I: Ord + Copy, T> MVReg<I, T> {
+    /// Create a new register with the given value.
+    pub fn new(value: T, id: I) -> Self {
+        Self {
+            values: BTreeMap::from_iter([(id, value)]),
+            overwritten: BTreeSet::new(),
+        }
+    }
+
+    /// Set the value of the register, overwriting all currently visible values, including
+    /// conflicting ones.
+    pub fn set(&mut self, value: T, id: I) {
+        let visible = self.ids().collect::<Vec<_>>();
+
+        self.overwritten.extend(visible);
+        self.values.entry(id).or_insert(value);
+    }
+
+    /// Iterate over the current values of the register, by ascending id. There is more than
+    /// one value if there were concurrent writes.
+    pub fn get(&self) -> impl Iterator<Item = &T> {
+        self.values
+            .iter()
+            .filter(|(id, _)| !self.overwritten.contains(id))
+            .map(|(_, v)| v)
+    }
+
+    /// Iterate over the ids of the current values of the register.
+    pub fn ids(&self) -> impl Iterator<Item = I> + '_ {
+        self.values
+            .keys()
+            .filter(|id| !self.overwritten.contains(id))
+            .copied()
+    }
+
+    /// Check whether the register has conflicting values.
+    pub fn is_conflicted(&self) -> bool {
+        self.get().nth(1).is_some()
+    }
+
+    /// Check whether the register has no value.
+    pub fn is_empty(&self) -> bool {
+        self.get().next().is_none()
+    }
+}
+
+impl<I, T> Default for MVReg<I, T> {
+    fn default() -> Self {
+        Self {
+            values: BTreeMap::default(),
+            overwritten: BTreeSet::default(),
+        }
+    }
+}
+
+impl<I: Ord, T> Semilattice for MVReg<I, T> {
+    fn merge(&mut self, other: Self) {
+        for (id, value) in other.values {
+            self.values.entry(id).or_insert(value);
+        }
+        self.overwritten.extend(other.overwritten);
+    }
+}
+
+impl<I: Ord + Copy, T> Delta for MVReg<I, T> {
+    type Op = (T, I);
+
+    fn delta(&self, (value, id): Self::Op) -> Self {
+        Self {
+            values: BTreeMap::from_iter([(id, value)]),
+            overwritten: self.ids().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use qcheck_macros::quickcheck;
+
+    use super::*;
+
+    #[quickcheck]
+    fn prop_semilattice(a: Vec<u8>, b: Vec<u8>, c: Vec<u8>, mix: Vec<u8>) {
+        let mut base = MVReg::default();
+        for (i, v) in mix.into_iter().enumerate() {
+            base.set(v, (i, 0));
+        }
+        let (mut a_, mut b_, mut c_) = (base.clone(), base.clone(), base);
+
+        for (actor, (reg, values)) in [(&mut a_, a), (&mut b_, b), (&mut c_, c)]
+            .into_iter()
+            .enumerate()
+        {
+            for (i, v) in values.into_iter().enumerate() {
+                reg.set(v, (i, actor + 1));
+            }
+        }
+        crate::test::assert_laws(&a_, &b_, &c_);
+    }
+
+    #[test]
+    fn test_concurrent_writes() {
+        let mut alice = MVReg::new("title", (0, 'a'));
+        let mut bob = alice.clone();
+
+        alice.set("Title", (1, 'a'));
+        bob.set("A title", (1, 'b'));
+
+        let mut merged = alice.join(bob);
+        assert!(merged.is_conflicted());
+        assert_eq!(merged.get().collect::<Vec<_>>(), vec![&"Title", &"A title"]);
+
+        merged.set("The title", (2, 'a'));
+        assert!(!merged.is_conflicted());
+        assert_eq!(merged.get().collect::<Vec<_>>(), vec![&"The title"]);
+    }
+}
@@ -1,12 +1,14 @@
 use std::{cmp, ops};
 
-use num_traits::Bounded;
-use serde::{Deserialize, Serialize};
-
 use crate::Semilattice;
+use num_traits::Bounded;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-#[serde(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Max<T>(T);
 
 impl<T> Max<T> {
@@ -67,8 +69,12 @@ impl<T: Bounded> Bounded for Max<T> {
 }
 
 #[allow(clippy::derive_ord_xor_partial_ord)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Ord, Serialize, Deserialize)]
-#[serde(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Min<T>(pub T);
 
 impl<T> Default for Min<T>
@@ -110,20 +116,3 @@ impl<T: PartialOrd> Semilattice for Min<T> {
         }
     }
 }
-
-#[cfg(any(test, feature = "test"))]
-mod arbitrary {
-    use super::*;
-
-    impl<T: qcheck::Arbitrary> qcheck::Arbitrary for Max<T> {
-        fn arbitrary(g: &mut qcheck::Gen) -> Self {
-            Self::from(T::arbitrary(g))
-        }
-    }
-
-    impl<T: qcheck::Arbitrary> qcheck::Arbitrary for Min<T> {
-        fn arbitrary(g: &mut qcheck::Gen) -> Self {
-            Self::from(T::arbitrary(g))
-        }
-    }
-}
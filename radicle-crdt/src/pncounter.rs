@@ -0,0 +1,143 @@
+use crate::gmap::GMap;
+use crate::ord::Max;
+use crate::{Delta, Semilattice};
+
+/// Positive-Negative Counter.
+///
+/// A counter that can be incremented and decremented. Each actor keeps track of its own
+/// increments and decrements, which only grow, and the counter value is the difference
+/// between their sums.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PNCounter<A> {
+    incs: GMap<A, Max<u64>>,
+    decs: GMap<A, Max<u64>>,
+}
+
+impl<A: Ord> PNCounter<A> {
+    /// Increment the counter by the given amount, on behalf of an actor.
+    pub fn incr(&mut self, actor: A, n: u64) {
+        Self::add(&mut self.incs, actor, n);
+    }
+
+    /// Decrement the counter by the given amount, on behalf of an actor.
+    pub fn decr(&mut self, actor: A, n: u64) {
+        Self::add(&mut self.decs, actor, n);
+    }
+
+    /// Return the counter value.
+    pub fn value(&self) -> i64 {
+        Self::sum(&self.incs) as i64 - Self::sum(&self.decs) as i64
+    }
+
+    /// Return the total of increments.
+    pub fn increments(&self) -> u64 {
+        Self::sum(&self.incs)
+    }
+
+    /// Return the total of decrements.
+    pub fn decrements(&self) -> u64 {
+        Self::sum(&self.decs)
+    }
+
+    fn add(map: &mut GMap<A, Max<u64>>, actor: A, n: u64) {
+        let total = map.get(&actor).map_or(0, |m| *m.get());
+        map.insert(actor, Max::from(total.saturating_add(n)));
+    }
+
+    fn sum(map: &GMap<A, Max<u64>>) -> u64 {
+        map.values().fold(0, |acc, m| acc.saturating_add(*m.get()))
+    }
+}
+
+impl<A> Default for PNCounter<A> {
+    fn default() -> Self {
+        Self {
+            incs: GMap::default(),
+            decs: GMap::default(),
+        }
+    }
+}
+
+impl<A: Ord> Semilattice for PNCounter<A> {
+    fn merge(&mut self, other: Self) {
+        self.incs.merge(other.incs);
+        self.decs.merge(other.decs);
+    }
+}
+
+/// Operation on a [`PNCounter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op<A> {
+    /// Increment the counter on behalf of an actor.
+    Incr(A, u64),
+    /// Decrement the counter on behalf of an actor.
+    Decr(A, u64),
+}
+
+impl<A: Ord> Delta for PNCounter<A> {
+    type Op = Op<A>;
+
+    fn delta(&self, op: Self::Op) -> Self {
+        // The delta only holds the new total of the actor.
+        let mut delta = Self::default();
+        match op {
+            Op::Incr(actor, n) => {
+                let total = self.incs.get(&actor).map_or(0, |m| *m.get());
+                delta.incr(actor, total.saturating_add(n));
+            }
+            Op::Decr(actor, n) => {
+                let total = self.decs.get(&actor).map_or(0, |m| *m.get());
+                delta.decr(actor, total.saturating_add(n));
+            }
+        }
+        delta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use qcheck_macros::quickcheck;
+
+    use super::*;
+
+    fn build(ops: Vec<(u8, i8)>) -> PNCounter<u8> {
+        let mut counter = PNCounter::default();
+        for (actor, n) in ops {
+            if n >= 0 {
+                counter.incr(actor, n as u64);
+            } else {
+                counter.decr(actor, n.unsigned_abs() as u64);
+            }
+        }
+        counter
+    }
+
+    #[quickcheck]
+    fn prop_semilattice(a: Vec<(u8, i8)>, b: Vec<(u8, i8)>, c: Vec<(u8, i8)>) {
+        let a = build(a);
+        let b = build(b);
+        let c = build(c);
+
+        crate::test::assert_laws(&a, &b, &c);
+    }
+
+    #[test]
+    fn test_incr_decr() {
+        let mut alice = PNCounter::default();
+        alice.incr('a', 3);
+        alice.decr('a', 1);
+        assert_eq!(alice.value(), 2);
+
+        let mut bob = alice.clone();
+        bob.decr('b', 5);
+        assert_eq!(bob.value(), -3);
+
+        alice.incr('a', 1);
+
+        let merged = alice.join(bob);
+        assert_eq!(merged.value(), -2);
+        assert_eq!(merged.increments(), 4);
+        assert_eq!(merged.decrements(), 6);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::Semilattice;
+use crate::{Semilattice, Tombstone};
 
 /// An object that can be either present or removed.
 ///
@@ -11,6 +11,11 @@ use crate::Semilattice;
 /// values present are merged; the result is redacted. This is the preserve
 /// the semilattice laws.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub enum Redactable<T> {
     /// When the object is present.
     Present(T),
@@ -27,6 +32,12 @@ impl<T> Redactable<T> {
     }
 }
 
+impl<T> Tombstone for Redactable<T> {
+    fn is_tombstone(&self) -> bool {
+        matches!(self, Self::Redacted)
+    }
+}
+
 impl<T> From<Option<T>> for Redactable<T> {
     fn from(option: Option<T>) -> Self {
         match option {
@@ -67,12 +78,74 @@ impl<T: PartialEq> Semilattice for Redactable<T> {
     }
 }
 
+/// Like [`Redactable`], but redactions carry metadata, eg. who redacted the object
+/// and why, for auditing purposes.
+///
+/// The metadata of concurrent redactions are merged via their [`Semilattice`] instance,
+/// eg. a [`crate::GSet`] of actor and reason pairs keeps all of them. When two different
+/// present values are merged, the result is redacted with the default metadata, which should
+/// thus be the bottom element of the metadata semilattice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum RedactableWith<T, M> {
+    /// When the object is present.
+    Present(T),
+    /// When the object has been removed, with the redaction metadata.
+    Redacted(M),
+}
+
+impl<T, M> RedactableWith<T, M> {
+    pub fn get(&self) -> Option<&T> {
+        match self {
+            Self::Present(val) => Some(val),
+            Self::Redacted(_) => None,
+        }
+    }
+
+    /// Get the redaction metadata, if the object was redacted.
+    pub fn redaction(&self) -> Option<&M> {
+        match self {
+            Self::Present(_) => None,
+            Self::Redacted(meta) => Some(meta),
+        }
+    }
+}
+
+impl<T, M> Tombstone for RedactableWith<T, M> {
+    fn is_tombstone(&self) -> bool {
+        matches!(self, Self::Redacted(_))
+    }
+}
+
+impl<T: PartialEq, M: Semilattice + Default> Semilattice for RedactableWith<T, M> {
+    fn merge(&mut self, other: Self) {
+        match (&mut *self, other) {
+            (Self::Redacted(a), Self::Redacted(b)) => {
+                a.merge(b);
+            }
+            (Self::Redacted(_), Self::Present(_)) => {}
+            (Self::Present(_), other @ Self::Redacted(_)) => {
+                *self = other;
+            }
+            (Self::Present(a), Self::Present(b)) => {
+                if a != &b {
+                    *self = Self::Redacted(M::default());
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use qcheck_macros::quickcheck;
 
     use super::*;
-    use crate::test;
+    use crate::{test, GSet};
 
     #[quickcheck]
     fn prop_invariants(a: Option<u8>, b: Option<u8>, c: Option<u8>) {
@@ -83,6 +156,33 @@ mod test {
         test::assert_laws(&a, &b, &c);
     }
 
+    #[quickcheck]
+    fn prop_invariants_with(
+        a: Result<u8, Vec<u8>>,
+        b: Result<u8, Vec<u8>>,
+        c: Result<u8, Vec<u8>>,
+    ) {
+        let from = |r: Result<u8, Vec<u8>>| match r {
+            Ok(v) => RedactableWith::Present(v),
+            Err(m) => RedactableWith::Redacted(GSet::from_iter(m)),
+        };
+        test::assert_laws(&from(a), &from(b), &from(c));
+    }
+
+    #[test]
+    fn test_redacted_with() {
+        let a = RedactableWith::Present("comment");
+        let b = RedactableWith::Redacted(GSet::singleton(("alice", "spam")));
+        let c = RedactableWith::Redacted(GSet::singleton(("bob", "off-topic")));
+
+        let merged = a.join(b).join(c);
+        let redaction = merged.redaction().unwrap();
+
+        assert!(merged.get().is_none());
+        assert!(redaction.contains(&("alice", "spam")));
+        assert!(redaction.contains(&("bob", "off-topic")));
+    }
+
     #[test]
     fn test_redacted() {
         let a = Redactable::Present(0);
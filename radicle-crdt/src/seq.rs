@@ -0,0 +1,374 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+
+use crate::Semilattice;
+
+/// Replicated Growable Array (RGA).
+///
+/// A sequence supporting concurrent insertions and removals at arbitrary positions, suitable
+/// for collaborative editing of text, eg. as a sequence of characters or lines.
+///
+/// Every element is identified by a unique id, and records the id of the element it was
+/// inserted after. Elements inserted after the same element are ordered by descending id,
+/// so that the most recent insertion comes first. Removed elements are kept as tombstones,
+/// so that concurrent insertions relative to them can still be ordered.
+///
+/// Ids must be unique across replicas, and an element's id must be greater than the ids
+/// of all elements known when it was inserted. A pair of a [`crate::Lamport`] clock and
+/// an actor id satisfies both requirements.
+///
+/// The order of elements is maintained as they are inserted, rather than recomputed when
+/// the sequence is read.
+#[derive(Debug, Clone)]
+pub struct Seq<I, T> {
+    /// All elements, including removed ones.
+    elements: BTreeMap<I, Element<I, T>>,
+    /// Ids of the elements whose parent is known, including removed ones, in order.
+    order: Vec<I>,
+    /// Ids of the elements whose parent isn't known yet, by parent id. Merging can
+    /// deliver an element before the element it was inserted after.
+    orphans: BTreeMap<I, Vec<I>>,
+    /// Number of values in the sequence, not counting removed values.
+    len: usize,
+}
+
+/// An element of a [`Seq`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Element<I, T> {
+    /// The element this element was inserted after, or `None` if it was inserted at the
+    /// start of the sequence.
+    parent: Option<I>,
+    /// The element value.
+    value: T,
+    /// Whether the element was removed.
+    removed: bool,
+}
+
+impl<I: Ord + Copy, T> Seq<I, T> {
+    /// Insert a value at the given position, with the given id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the sequence.
+    pub fn insert(&mut self, index: usize, value: T, id: I) {
+        let parent = if index == 0 {
+            None
+        } else {
+            let Some(parent) = self.visible().nth(index - 1) else {
+                panic!(
+                    "Seq::insert: index {} is out of bounds (len {})",
+                    index, self.len
+                );
+            };
+            Some(parent)
+        };
+        self.insert_after(parent, value, id);
+    }
+
+    /// Insert a value after the element with the given id, or at the start of the sequence
+    /// if `parent` is `None`. The parent may have been removed.
+    pub fn insert_after(&mut self, parent: Option<I>, value: T, id: I) {
+        if let Entry::Vacant(e) = self.elements.entry(id) {
+            e.insert(Element {
+                parent,
+                value,
+                removed: false,
+            });
+            self.place(id);
+        }
+    }
+
+    /// Append a value to the end of the sequence.
+    pub fn push(&mut self, value: T, id: I) {
+        self.insert(self.len(), value, id);
+    }
+
+    /// Remove the value at the given position, returning its id.
+    pub fn remove(&mut self, index: usize) -> Option<I> {
+        let id = self.visible().nth(index)?;
+        self.remove_id(&id);
+
+        Some(id)
+    }
+
+    /// Remove the element with the given id. Returns `false` if the element isn't known.
+    pub fn remove_id(&mut self, id: &I) -> bool {
+        let Some(e) = self.elements.get_mut(id) else {
+            return false;
+        };
+        if !e.removed {
+            e.removed = true;
+
+            if self.order.contains(id) {
+                self.len -= 1;
+            }
+        }
+        true
+    }
+
+    /// Get the value at the given position.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.iter().nth(index)
+    }
+
+    /// Check whether the element with the given id is in the sequence and wasn't removed.
+    pub fn contains(&self, id: &I) -> bool {
+        self.elements.get(id).map_or(false, |e| !e.removed)
+    }
+
+    /// Return the number of values in the sequence, not counting removed values.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check whether the sequence has no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over the values of the sequence, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.order
+            .iter()
+            .map(|id| &self.elements[id])
+            .filter(|e| !e.removed)
+            .map(|e| &e.value)
+    }
+
+    /// Return the ids of the values of the sequence, in order.
+    pub fn ids(&self) -> Vec<I> {
+        self.visible().collect()
+    }
+
+    /// Iterate over the ids of the values of the sequence, in order.
+    fn visible(&self) -> impl Iterator<Item = I> + '_ {
+        self.order
+            .iter()
+            .filter(|id| !self.elements[id].removed)
+            .copied()
+    }
+
+    /// Place a new element in the order, after its parent. If the parent isn't known, the
+    /// element is placed once the parent is. Elements that were waiting for this element
+    /// are placed along with it.
+    fn place(&mut self, id: I) {
+        let mut pending = vec![id];
+
+        while let Some(id) = pending.pop() {
+            let e = &self.elements[&id];
+            let start = match e.parent {
+                None => 0,
+                Some(parent) => match self.order.iter().position(|o| *o == parent) {
+                    Some(i) => i + 1,
+                    None => {
+                        self.orphans.entry(parent).or_default().push(id);
+                        continue;
+                    }
+                },
+            };
+            // Skip the elements inserted after the same parent with greater ids, and the
+            // elements inserted after those, which have greater ids still.
+            let index = start + self.order[start..].iter().take_while(|o| **o > id).count();
+
+            self.order.insert(index, id);
+
+            if !e.removed {
+                self.len += 1;
+            }
+            if let Some(orphans) = self.orphans.remove(&id) {
+                pending.extend(orphans);
+            }
+        }
+    }
+}
+
+impl<I> Seq<I, char>
+where
+    I: Ord + Copy,
+{
+    /// Return the sequence as a string.
+    pub fn text(&self) -> String {
+        self.iter().collect()
+    }
+}
+
+impl<I, T> Default for Seq<I, T> {
+    fn default() -> Self {
+        Self {
+            elements: BTreeMap::default(),
+            order: Vec::default(),
+            orphans: BTreeMap::default(),
+            len: 0,
+        }
+    }
+}
+
+// The order of elements only depends on the elements themselves.
+impl<I: PartialEq, T: PartialEq> PartialEq for Seq<I, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.elements == other.elements
+    }
+}
+
+impl<I: Eq, T: Eq> Eq for Seq<I, T> {}
+
+impl<I: Ord + Copy, T> FromIterator<(T, I)> for Seq<I, T> {
+    fn from_iter<It: IntoIterator<Item = (T, I)>>(iter: It) -> Self {
+        let mut seq = Seq::default();
+        for (v, id) in iter.into_iter() {
+            seq.push(v, id);
+        }
+        seq
+    }
+}
+
+impl<I: Ord + Copy, T> Semilattice for Seq<I, T> {
+    fn merge(&mut self, other: Self) {
+        // Elements are merged in id order, so parents are usually merged before the
+        // elements inserted after them.
+        for (id, theirs) in other.elements {
+            self.insert_after(theirs.parent, theirs.value, id);
+
+            if theirs.removed {
+                self.remove_id(&id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use qcheck_macros::quickcheck;
+
+    use super::*;
+
+    /// Build a sequence from a list of operations, using the given actor for ids.
+    /// Operations are either insertions or, when the value is `None`, removals.
+    fn build(ops: Vec<(u8, Option<char>)>, actor: u8, seq: &mut Seq<(u16, u8), char>) {
+        for (index, value) in ops {
+            let clock = seq.elements.keys().map(|(c, _)| c + 1).max().unwrap_or(0);
+
+            match value {
+                Some(v) => {
+                    seq.insert(index as usize % (seq.len() + 1), v, (clock, actor));
+                }
+                None if !seq.is_empty() => {
+                    seq.remove(index as usize % seq.len());
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Compute the order of all elements from scratch: a depth-first traversal of the
+    /// elements, where elements inserted after the same element are visited by
+    /// descending id.
+    fn order<I: Ord + Copy, T>(seq: &Seq<I, T>) -> Vec<I> {
+        let mut children: BTreeMap<Option<I>, Vec<I>> = BTreeMap::new();
+        for (id, e) in &seq.elements {
+            children.entry(e.parent).or_default().push(*id);
+        }
+        let mut order = Vec::with_capacity(seq.elements.len());
+        let mut stack = children.remove(&None).unwrap_or_default();
+
+        while let Some(id) = stack.pop() {
+            order.push(id);
+
+            if let Some(ids) = children.remove(&Some(id)) {
+                stack.extend(ids);
+            }
+        }
+        order
+    }
+
+    #[quickcheck]
+    fn prop_order(a: Vec<(u8, Option<char>)>, b: Vec<(u8, Option<char>)>) {
+        let mut x = Seq::default();
+        build(a, 1, &mut x);
+        assert_eq!(x.order, order(&x));
+
+        let mut y = x.clone();
+        build(b, 2, &mut y);
+        assert_eq!(y.order, order(&y));
+
+        // Merging in either direction yields the same order.
+        let xy = x.clone().join(y.clone());
+        let yx = y.join(x);
+        assert_eq!(xy.order, order(&xy));
+        assert_eq!(xy.order, yx.order);
+        assert_eq!(xy.len(), xy.iter().count());
+    }
+
+    #[quickcheck]
+    fn prop_semilattice(
+        a: Vec<(u8, Option<char>)>,
+        b: Vec<(u8, Option<char>)>,
+        c: Vec<(u8, Option<char>)>,
+        mix: Vec<(u8, Option<char>)>,
+    ) {
+        let mut base = Seq::default();
+        build(mix, 0, &mut base);
+
+        let (mut x, mut y, mut z) = (base.clone(), base.clone(), base);
+        build(a, 1, &mut x);
+        build(b, 2, &mut y);
+        build(c, 3, &mut z);
+
+        crate::test::assert_laws(&x, &y, &z);
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut seq = Seq::default();
+
+        seq.push('a', (0, 0));
+        seq.push('c', (1, 0));
+        seq.insert(1, 'b', (2, 0));
+        seq.insert(0, '>', (3, 0));
+        assert_eq!(seq.text(), ">abc");
+        assert_eq!(seq.len(), 4);
+        assert_eq!(seq.get(1), Some(&'a'));
+
+        assert_eq!(seq.remove(0), Some((3, 0)));
+        assert_eq!(seq.text(), "abc");
+        assert!(!seq.contains(&(3, 0)));
+        assert!(seq.remove(3).is_none());
+
+        // Inserting after a removed element.
+        seq.insert_after(Some((3, 0)), '!', (4, 0));
+        assert_eq!(seq.text(), "!abc");
+    }
+
+    #[test]
+    fn test_insert_before_parent() {
+        let mut seq = Seq::default();
+
+        // Elements inserted after unknown elements are placed once those are inserted.
+        seq.insert_after(Some((1, 0)), 'c', (2, 0));
+        seq.insert_after(Some((0, 0)), 'b', (1, 0));
+        assert_eq!(seq.text(), "");
+        assert_eq!(seq.len(), 0);
+
+        seq.insert_after(None, 'a', (0, 0));
+        assert_eq!(seq.text(), "abc");
+        assert_eq!(seq.len(), 3);
+    }
+
+    #[test]
+    fn test_concurrent_edits() {
+        let base = Seq::from_iter([('h', (0, 0)), ('i', (1, 0))]);
+
+        let mut alice = base.clone();
+        alice.push('!', (2, 1));
+        alice.remove(0);
+        alice.insert(0, 'H', (3, 1));
+
+        let mut bob = base;
+        bob.push('?', (2, 2));
+        bob.insert(0, '¡', (3, 2));
+
+        let merged = alice.clone().join(bob.clone());
+        assert_eq!(merged, bob.join(alice));
+        assert_eq!(merged.text(), "¡Hi?!");
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::Semilattice;
+
+/// An operation on a [`Tree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", rename_all = "camelCase")
+)]
+pub enum Op<N, M> {
+    /// Move a node under a parent, or to the top-level if there is no parent. Moving a node
+    /// that isn't in the tree yet inserts it.
+    Move { node: N, parent: Option<N>, meta: M },
+    /// Remove a node, and with it all its descendants.
+    Remove { node: N },
+}
+
+/// Tree CRDT.
+///
+/// A tree whose nodes can be inserted, moved and removed concurrently. The tree is stored as
+/// the set of operations applied to it, each identified by a unique id, and is materialized
+/// by applying the operations in id order. Operations are thus totally ordered, eg. with a
+/// pair of a [`crate::Lamport`] clock and an actor id.
+///
+/// Moves that would introduce a cycle, ie. moving a node under one of its descendants,
+/// are ignored, which is what makes concurrent moves safe. This follows "A highly-available
+/// move operation for replicated trees" by Kleppmann et al.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tree<I, N, M> {
+    ops: BTreeMap<I, Op<N, M>>,
+}
+
+impl<I: Ord, N: Ord + Clone, M> Tree<I, N, M> {
+    /// Insert a node under a parent. Same as [`Tree::move_node`].
+    pub fn insert(&mut self, id: I, node: N, parent: Option<N>, meta: M) {
+        self.move_node(id, node, parent, meta);
+    }
+
+    /// Move a node under a parent, or to the top-level.
+    pub fn move_node(&mut self, id: I, node: N, parent: Option<N>, meta: M) {
+        self.apply(id, Op::Move { node, parent, meta });
+    }
+
+    /// Remove a node and its descendants.
+    pub fn remove(&mut self, id: I, node: N) {
+        self.apply(id, Op::Remove { node });
+    }
+
+    /// Apply an operation.
+    pub fn apply(&mut self, id: I, op: Op<N, M>) {
+        self.ops.entry(id).or_insert(op);
+    }
+
+    /// Materialize the tree.
+    pub fn view(&self) -> View<'_, I, N, M> {
+        let mut view = View {
+            nodes: BTreeMap::new(),
+            removed: BTreeSet::new(),
+        };
+
+        for (id, op) in &self.ops {
+            match op {
+                Op::Move { node, parent, meta } => {
+                    if let Some(parent) = parent {
+                        if parent == node || view.is_ancestor(node, parent) {
+                            // Moving a node under itself would introduce a cycle.
+                            continue;
+                        }
+                    }
+                    view.nodes.insert(
+                        node.clone(),
+                        Placement {
+                            parent: parent.as_ref(),
+                            meta,
+                            id,
+                        },
+                    );
+                    view.removed.remove(node);
+                }
+                Op::Remove { node } => {
+                    if view.nodes.contains_key(node) {
+                        view.removed.insert(node.clone());
+                    }
+                }
+            }
+        }
+        view
+    }
+}
+
+impl<I, N, M> Default for Tree<I, N, M> {
+    fn default() -> Self {
+        Self {
+            ops: BTreeMap::new(),
+        }
+    }
+}
+
+impl<I: Ord, N, M> Semilattice for Tree<I, N, M> {
+    fn merge(&mut self, other: Self) {
+        for (id, op) in other.ops {
+            self.ops.entry(id).or_insert(op);
+        }
+    }
+}
+
+/// A node of a materialized tree.
+#[derive(Debug)]
+struct Placement<'a, I, N, M> {
+    /// The node parent.
+    parent: Option<&'a N>,
+    /// The metadata of the last effective move.
+    meta: &'a M,
+    /// The id of the last effective move.
+    id: &'a I,
+}
+
+/// A materialized [`Tree`].
+#[derive(Debug)]
+pub struct View<'a, I, N, M> {
+    nodes: BTreeMap<N, Placement<'a, I, N, M>>,
+    removed: BTreeSet<N>,
+}
+
+impl<'a, I: Ord, N: Ord, M> View<'a, I, N, M> {
+    /// Check whether the node is in the tree, ie. it was inserted, and neither it nor any
+    /// of its ancestors were removed.
+    pub fn contains(&self, node: &N) -> bool {
+        self.nodes.contains_key(node) && !self.is_removed(node)
+    }
+
+    /// Get the parent of a node. Returns `Some(None)` for top-level nodes.
+    pub fn parent(&self, node: &N) -> Option<Option<&N>> {
+        if !self.contains(node) {
+            return None;
+        }
+        self.nodes.get(node).map(|e| e.parent)
+    }
+
+    /// Get the metadata of a node, as given when it was last moved.
+    pub fn meta(&self, node: &N) -> Option<&M> {
+        if !self.contains(node) {
+            return None;
+        }
+        self.nodes.get(node).map(|e| e.meta)
+    }
+
+    /// Get the children of a node, or the top-level nodes if `parent` is `None`, in the
+    /// order they were moved under it.
+    pub fn children(&self, parent: Option<&N>) -> Vec<&N> {
+        let mut children = self
+            .nodes
+            .iter()
+            .filter(|(_, e)| e.parent == parent)
+            .filter(|(n, _)| !self.is_removed(n))
+            .map(|(n, e)| (e.id, n))
+            .collect::<Vec<_>>();
+
+        children.sort();
+        children.into_iter().map(|(_, n)| n).collect()
+    }
+
+    /// Iterate over the nodes of the tree, in no particular order.
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.nodes.keys().filter(|n| !self.is_removed(n))
+    }
+
+    /// Check whether `ancestor` is an ancestor of `node`.
+    fn is_ancestor(&self, ancestor: &N, node: &N) -> bool {
+        let mut current = self.nodes.get(node).and_then(|e| e.parent);
+
+        while let Some(parent) = current {
+            if parent == ancestor {
+                return true;
+            }
+            current = self.nodes.get(parent).and_then(|e| e.parent);
+        }
+        false
+    }
+
+    /// Check whether the node or one of its ancestors was removed.
+    fn is_removed(&self, node: &N) -> bool {
+        let mut current = Some(node);
+
+        while let Some(n) = current {
+            if self.removed.contains(n) {
+                return true;
+            }
+            current = self.nodes.get(n).and_then(|e| e.parent);
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use qcheck_macros::quickcheck;
+
+    use super::*;
+
+    fn build(ops: Vec<(u8, u8, Option<u8>)>, actor: u8) -> Tree<(u16, u8), u8, ()> {
+        let mut tree = Tree::default();
+
+        for (i, (kind, node, parent)) in ops.into_iter().enumerate() {
+            let id = (i as u16, actor);
+            let (node, parent) = (node % 8, parent.map(|p| p % 8));
+
+            if kind % 4 == 0 {
+                tree.remove(id, node);
+            } else {
+                tree.move_node(id, node, parent, ());
+            }
+        }
+        tree
+    }
+
+    #[quickcheck]
+    fn prop_semilattice(
+        a: Vec<(u8, u8, Option<u8>)>,
+        b: Vec<(u8, u8, Option<u8>)>,
+        c: Vec<(u8, u8, Option<u8>)>,
+    ) {
+        let a = build(a, 0);
+        let b = build(b, 1);
+        let c = build(c, 2);
+
+        crate::test::assert_laws(&a, &b, &c);
+    }
+
+    #[quickcheck]
+    fn prop_acyclic(a: Vec<(u8, u8, Option<u8>)>, b: Vec<(u8, u8, Option<u8>)>) {
+        let tree = build(a, 0).join(build(b, 1));
+        let view = tree.view();
+
+        for node in view.nodes.keys() {
+            assert!(!view.is_ancestor(node, node));
+        }
+    }
+
+    #[test]
+    fn test_concurrent_moves() {
+        let mut base = Tree::default();
+        base.insert((0, 0), 'x', None, "x");
+        base.insert((1, 0), 'y', None, "y");
+
+        // Concurrently move `x` under `y`, and `y` under `x`.
+        let mut alice = base.clone();
+        alice.move_node((2, 1), 'x', Some('y'), "x");
+
+        let mut bob = base;
+        bob.move_node((2, 2), 'y', Some('x'), "y");
+
+        let merged = alice.join(bob);
+        let view = merged.view();
+
+        // Only the first move is applied.
+        assert_eq!(view.parent(&'x'), Some(Some(&'y')));
+        assert_eq!(view.parent(&'y'), Some(None));
+        assert_eq!(view.children(None), vec![&'y']);
+        assert_eq!(view.children(Some(&'y')), vec![&'x']);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut tree = Tree::default();
+        tree.insert((0, 0), 'a', None, ());
+        tree.insert((1, 0), 'b', Some('a'), ());
+        tree.insert((2, 0), 'c', Some('b'), ());
+        tree.insert((3, 0), 'd', None, ());
+        tree.remove((4, 0), 'b');
+
+        let view = tree.view();
+        assert!(view.contains(&'a'));
+        assert!(!view.contains(&'b'));
+        assert!(!view.contains(&'c'));
+        assert_eq!(view.children(None), vec![&'a', &'d']);
+        assert!(view.children(Some(&'a')).is_empty());
+
+        // Moving a removed node restores it.
+        tree.move_node((5, 0), 'c', Some('d'), ());
+
+        let view = tree.view();
+        assert_eq!(view.parent(&'c'), Some(Some(&'d')));
+        assert_eq!(view.meta(&'c'), Some(&()));
+        assert!(!view.contains(&'b'));
+    }
+}
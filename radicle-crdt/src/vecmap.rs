@@ -0,0 +1,201 @@
+use std::cmp::Ordering;
+use std::{mem, slice, vec};
+
+use crate::{Delta, Semilattice};
+
+/// Grow-only map backed by a sorted vector.
+///
+/// Has the same semantics as [`crate::GMap`], but is faster and uses less memory for maps
+/// with few entries, eg. the reactions or verdicts of a single comment or revision, since
+/// lookups are binary searches over contiguous memory and merges are linear.
+/// Insertions are linear in the size of the map, so prefer [`crate::GMap`] for large maps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct VecMap<K, V> {
+    entries: Vec<(K, V)>,
+}
+
+impl<K: Ord, V: Semilattice> VecMap<K, V> {
+    pub fn singleton(key: K, value: V) -> Self {
+        Self {
+            entries: vec![(key, value)],
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.search(key).ok().map(|i| &self.entries[i].1)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.search(key).ok().map(|i| &mut self.entries[i].1)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.search(key).is_ok()
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        match self.search(&key) {
+            Ok(i) => self.entries[i].1.merge(value),
+            Err(i) => self.entries.insert(i, (key, value)),
+        }
+    }
+
+    /// Iterate over the entries of the map, in ascending key order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.iter().map(|(_, v)| v)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn search(&self, key: &K) -> Result<usize, usize> {
+        self.entries.binary_search_by(|(k, _)| k.cmp(key))
+    }
+}
+
+impl<K, V> Default for VecMap<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<K: Ord, V: Semilattice> FromIterator<(K, V)> for VecMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = VecMap::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord, V: Semilattice> Extend<(K, V)> for VecMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let mut entries = iter.into_iter().collect::<Vec<_>>();
+        // Stable sort, so that entries with the same key are merged in order.
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        self.merge(Self { entries });
+    }
+}
+
+impl<K, V> IntoIterator for VecMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a VecMap<K, V> {
+    type Item = &'a (K, V);
+    type IntoIter = slice::Iter<'a, (K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+impl<K: Ord, V: Semilattice> Semilattice for VecMap<K, V> {
+    fn merge(&mut self, other: Self) {
+        if other.entries.len() <= 1 {
+            // Avoid re-allocating for the common case of merging a single entry.
+            for (k, v) in other.entries {
+                self.insert(k, v);
+            }
+            return;
+        }
+        let ours = mem::take(&mut self.entries);
+        let mut merged: Vec<(K, V)> = Vec::with_capacity(ours.len() + other.entries.len());
+        let mut ours = ours.into_iter().peekable();
+        let mut theirs = other.entries.into_iter().peekable();
+
+        // Merge both sorted sequences. Entries of the other map may have duplicate keys,
+        // which are merged with the last entry.
+        loop {
+            let next = match (ours.peek(), theirs.peek()) {
+                (Some((a, _)), Some((b, _))) => match a.cmp(b) {
+                    Ordering::Greater => theirs.next(),
+                    Ordering::Less | Ordering::Equal => ours.next(),
+                },
+                (Some(_), None) => ours.next(),
+                (None, Some(_)) => theirs.next(),
+                (None, None) => break,
+            };
+            let Some((k, v)) = next else { break };
+
+            match merged.last_mut() {
+                Some((last, value)) if *last == k => value.merge(v),
+                _ => merged.push((k, v)),
+            }
+        }
+        self.entries = merged;
+    }
+}
+
+impl<K: Ord, V: Semilattice> Delta for VecMap<K, V> {
+    type Op = (K, V);
+
+    fn delta(&self, (key, value): Self::Op) -> Self {
+        Self::singleton(key, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use qcheck_macros::quickcheck;
+
+    use super::*;
+    use crate::{GMap, Max};
+
+    #[quickcheck]
+    fn prop_semilattice(
+        a: Vec<(u8, Max<u8>)>,
+        b: Vec<(u8, Max<u8>)>,
+        c: Vec<(u8, Max<u8>)>,
+        mix: Vec<(u8, Max<u8>)>,
+    ) {
+        let mut a = VecMap::from_iter(a);
+        let mut b = VecMap::from_iter(b);
+        let c = VecMap::from_iter(c);
+
+        a.extend(mix.clone());
+        b.extend(mix);
+
+        crate::test::assert_laws(&a, &b, &c);
+    }
+
+    #[quickcheck]
+    fn prop_same_as_gmap(a: Vec<(u8, Max<u8>)>, b: Vec<(u8, Max<u8>)>) {
+        let mut vecmap = VecMap::from_iter(a.clone());
+        let mut gmap = GMap::from_iter(a);
+
+        for (k, v) in b {
+            vecmap.insert(k, v);
+            gmap.insert(k, v);
+        }
+        assert_eq!(
+            vecmap.iter().collect::<Vec<_>>(),
+            gmap.iter().collect::<Vec<_>>()
+        );
+    }
+}
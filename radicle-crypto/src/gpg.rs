@@ -0,0 +1,463 @@
+//! OpenPGP signing via `gpg-agent`.
+//!
+//! Only Ed25519 keys are supported. Since an OpenPGP Ed25519 key is a plain Ed25519 key,
+//! signatures produced by the agent are regular [`Signature`]s and the corresponding
+//! [`PublicKey`] can be used anywhere a radicle key is expected, eg. as an identity delegate.
+//!
+//! The agent is spoken to over its Assuan socket. Keys are addressed by their *keygrip*,
+//! which can be obtained with `gpg --list-secret-keys --with-keygrip`.
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::process::Command;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::{fmt, io};
+
+use thiserror::Error;
+
+use crate::{PublicKey, Signature, Signer, SignerError};
+
+/// Maximum length of an Assuan line, not including the line terminator.
+const MAX_LINE_LEN: usize = 1000;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("i/o error: {0}")]
+    Io(#[from] io::Error),
+    #[error("agent socket not found: {0}")]
+    SocketNotFound(String),
+    #[error("agent error: {0}")]
+    Agent(String),
+    #[error("unexpected agent response: {0}")]
+    UnexpectedResponse(String),
+    #[error("invalid S-expression")]
+    InvalidSexp,
+    #[error("unsupported key: only Ed25519 keys are supported")]
+    UnsupportedKey,
+    #[error("invalid keygrip `{0}`")]
+    InvalidKeygrip(String),
+}
+
+/// A key identifier used by `gpg-agent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Keygrip([u8; 20]);
+
+impl fmt::Display for Keygrip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02X}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Keygrip {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidKeygrip(s.to_owned());
+
+        if s.len() != 40 || !s.is_ascii() {
+            return Err(invalid());
+        }
+        let mut grip = [0; 20];
+        for (i, byte) in grip.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+        }
+        Ok(Self(grip))
+    }
+}
+
+/// A connection to a running `gpg-agent`.
+pub struct Agent {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+impl Agent {
+    /// Connect to the agent of the current user.
+    ///
+    /// The socket location is obtained from `gpgconf`.
+    pub fn connect() -> Result<Self, Error> {
+        let output = Command::new("gpgconf")
+            .args(["--list-dirs", "agent-socket"])
+            .output()?;
+        if !output.status.success() {
+            return Err(Error::SocketNotFound(
+                String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+            ));
+        }
+        let path = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+
+        Self::connect_to(PathBuf::from(path))
+    }
+
+    /// Connect to the agent listening on the given socket.
+    pub fn connect_to(path: PathBuf) -> Result<Self, Error> {
+        let writer = UnixStream::connect(&path)
+            .map_err(|e| Error::SocketNotFound(format!("{}: {e}", path.display())))?;
+        let reader = BufReader::new(writer.try_clone()?);
+        let mut agent = Self { reader, writer };
+
+        // The agent greets us with an `OK` line.
+        agent.response()?;
+
+        Ok(agent)
+    }
+
+    /// Get the public key of the given keygrip.
+    pub fn read_key(&mut self, grip: &Keygrip) -> Result<PublicKey, Error> {
+        self.command(&format!("READKEY {grip}"))?;
+
+        let sexp = Sexp::parse(&self.response()?)?;
+        let q = sexp
+            .find(b"q")
+            .and_then(Sexp::value)
+            .and_then(Sexp::atom)
+            .ok_or(Error::UnsupportedKey)?;
+        // Ed25519 points are prefixed with `0x40` to signal the native encoding.
+        let q = match q {
+            [0x40, rest @ ..] => rest,
+            other => other,
+        };
+
+        PublicKey::try_from(q).map_err(|_| Error::UnsupportedKey)
+    }
+
+    /// Sign a message with the key of the given keygrip.
+    pub fn sign(&mut self, grip: &Keygrip, msg: &[u8]) -> Result<Signature, Error> {
+        self.command(&format!("SIGKEY {grip}"))?;
+        self.response()?;
+
+        // EdDSA signs the message itself, not a digest of it, so we pass the full
+        // message to the agent when it asks for it.
+        self.command("SETHASH --inquire")?;
+        match self.line()? {
+            Line::Inquire(keyword) if keyword.starts_with("TBSDATA") => {
+                self.data(msg)?;
+                self.command("END")?;
+                self.response()?;
+            }
+            Line::Err(e) => return Err(Error::Agent(e)),
+            other => return Err(Error::UnexpectedResponse(format!("{other:?}"))),
+        }
+        self.command("PKSIGN")?;
+
+        let sexp = Sexp::parse(&self.response()?)?;
+        let eddsa = sexp.find(b"eddsa").ok_or(Error::UnsupportedKey)?;
+        let r = eddsa.find(b"r").and_then(Sexp::value).and_then(Sexp::atom);
+        let s = eddsa.find(b"s").and_then(Sexp::value).and_then(Sexp::atom);
+
+        let (Some(r), Some(s)) = (r, s) else {
+            return Err(Error::InvalidSexp);
+        };
+        if r.len() > 32 || s.len() > 32 {
+            return Err(Error::InvalidSexp);
+        }
+        // Leading zeros of `r` and `s` may be stripped by the agent.
+        let mut sig = [0; 64];
+        sig[32 - r.len()..32].copy_from_slice(r);
+        sig[64 - s.len()..].copy_from_slice(s);
+
+        Ok(Signature::from(sig))
+    }
+
+    /// Get a signer from this agent, given the keygrip.
+    pub fn signer(mut self, grip: Keygrip) -> Result<GpgSigner, Error> {
+        let public = self.read_key(&grip)?;
+
+        Ok(GpgSigner {
+            agent: Mutex::new(self),
+            grip,
+            public,
+        })
+    }
+
+    fn command(&mut self, cmd: &str) -> Result<(), Error> {
+        writeln!(self.writer, "{cmd}")?;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+
+    fn data(&mut self, data: &[u8]) -> Result<(), Error> {
+        // Leave room for the `D ` prefix. Chunks are split on byte boundaries so that
+        // escape sequences are never split across lines.
+        for chunk in data.chunks((MAX_LINE_LEN - 2) / 3) {
+            self.command(&format!("D {}", escape(chunk)))?;
+        }
+        Ok(())
+    }
+
+    /// Read lines until the end of a response, returning the data sent by the agent.
+    fn response(&mut self) -> Result<Vec<u8>, Error> {
+        let mut data = Vec::new();
+
+        loop {
+            match self.line()? {
+                Line::Ok => return Ok(data),
+                Line::Err(e) => return Err(Error::Agent(e)),
+                Line::Data(d) => data.extend(d),
+                Line::Status | Line::Comment => continue,
+                Line::Inquire(_) => {
+                    // We don't answer any other inquiries.
+                    self.command("CAN")?;
+                }
+            }
+        }
+    }
+
+    fn line(&mut self) -> Result<Line, Error> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(Error::Io(io::ErrorKind::UnexpectedEof.into()));
+        }
+        Line::parse(line.trim_end_matches(['\r', '\n']))
+    }
+}
+
+/// A [`Signer`] that uses `gpg-agent`.
+pub struct GpgSigner {
+    agent: Mutex<Agent>,
+    grip: Keygrip,
+    public: PublicKey,
+}
+
+impl GpgSigner {
+    /// The keygrip of the signing key.
+    pub fn keygrip(&self) -> &Keygrip {
+        &self.grip
+    }
+
+    /// Box this signer into a [`Signer`].
+    pub fn boxed(self) -> Box<dyn Signer> {
+        Box::new(self)
+    }
+}
+
+impl Signer for GpgSigner {
+    fn public_key(&self) -> &PublicKey {
+        &self.public
+    }
+
+    fn sign(&self, msg: &[u8]) -> Signature {
+        self.try_sign(msg).unwrap()
+    }
+
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature, SignerError> {
+        self.agent
+            .lock()
+            // We'll take our chances here; the worse that can happen is the agent returns an error.
+            .unwrap_or_else(|e| e.into_inner())
+            .sign(&self.grip, msg)
+            .map_err(SignerError::new)
+    }
+}
+
+/// A line received from the agent.
+#[derive(Debug, PartialEq, Eq)]
+enum Line {
+    Ok,
+    Err(String),
+    Data(Vec<u8>),
+    Inquire(String),
+    Status,
+    Comment,
+}
+
+impl Line {
+    fn parse(line: &str) -> Result<Self, Error> {
+        let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+
+        match kind {
+            "OK" => Ok(Self::Ok),
+            "ERR" => Ok(Self::Err(rest.to_owned())),
+            "D" => unescape(rest).map(Self::Data),
+            "INQUIRE" => Ok(Self::Inquire(rest.to_owned())),
+            "S" => Ok(Self::Status),
+            "#" => Ok(Self::Comment),
+            _ => Err(Error::UnexpectedResponse(line.to_owned())),
+        }
+    }
+}
+
+/// Percent-escape data for an Assuan `D` line.
+fn escape(data: &[u8]) -> String {
+    let mut escaped = String::with_capacity(data.len());
+
+    for &byte in data {
+        match byte {
+            b'%' | b'\r' | b'\n' | b'\\' => escaped.push_str(&format!("%{byte:02X}")),
+            0x20..=0x7e => escaped.push(byte as char),
+            _ => escaped.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    escaped
+}
+
+/// Decode a percent-escaped Assuan `D` line.
+fn unescape(data: &str) -> Result<Vec<u8>, Error> {
+    let bytes = data.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = data
+                .get(i + 1..i + 3)
+                .ok_or_else(|| Error::UnexpectedResponse(data.to_owned()))?;
+            let byte = u8::from_str_radix(hex, 16)
+                .map_err(|_| Error::UnexpectedResponse(data.to_owned()))?;
+            out.push(byte);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Ok(out)
+}
+
+/// A canonical S-expression, as returned by the agent.
+#[derive(Debug, PartialEq, Eq)]
+enum Sexp {
+    Atom(Vec<u8>),
+    List(Vec<Sexp>),
+}
+
+impl Sexp {
+    fn parse(input: &[u8]) -> Result<Self, Error> {
+        let (sexp, rest) = Self::parse_one(input)?;
+        if !rest.is_empty() {
+            return Err(Error::InvalidSexp);
+        }
+        Ok(sexp)
+    }
+
+    fn parse_one(input: &[u8]) -> Result<(Self, &[u8]), Error> {
+        match input.first() {
+            Some(b'(') => {
+                let mut items = Vec::new();
+                let mut rest = &input[1..];
+
+                loop {
+                    match rest.first() {
+                        Some(b')') => return Ok((Self::List(items), &rest[1..])),
+                        Some(_) => {
+                            let (item, tail) = Self::parse_one(rest)?;
+                            items.push(item);
+                            rest = tail;
+                        }
+                        None => return Err(Error::InvalidSexp),
+                    }
+                }
+            }
+            Some(b'0'..=b'9') => {
+                let colon = input
+                    .iter()
+                    .position(|b| *b == b':')
+                    .ok_or(Error::InvalidSexp)?;
+                let len = std::str::from_utf8(&input[..colon])
+                    .ok()
+                    .and_then(|s| s.parse::<usize>().ok())
+                    .ok_or(Error::InvalidSexp)?;
+                let start = colon + 1;
+                let atom = input.get(start..start + len).ok_or(Error::InvalidSexp)?;
+
+                Ok((Self::Atom(atom.to_vec()), &input[start + len..]))
+            }
+            _ => Err(Error::InvalidSexp),
+        }
+    }
+
+    /// Find the first list, at any depth, whose first element is the given atom.
+    fn find(&self, name: &[u8]) -> Option<&Self> {
+        let Self::List(items) = self else {
+            return None;
+        };
+        if let [Self::Atom(head), ..] = items.as_slice() {
+            if head == name {
+                return Some(self);
+            }
+        }
+        items.iter().find_map(|item| item.find(name))
+    }
+
+    /// The value of a `(name value)` list, ie. its second element.
+    fn value(&self) -> Option<&Self> {
+        match self {
+            Self::List(items) => items.get(1),
+            Self::Atom(_) => None,
+        }
+    }
+
+    fn atom(&self) -> Option<&[u8]> {
+        match self {
+            Self::Atom(bytes) => Some(bytes),
+            Self::List(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_keygrip() {
+        let s = "7C8C1E5E8A4F0AB1F6E4AD03B7DA1C6D2BD5A0E1";
+        let grip = Keygrip::from_str(s).unwrap();
+
+        assert_eq!(grip.to_string(), s);
+        assert!(Keygrip::from_str("7C8C").is_err());
+    }
+
+    #[test]
+    fn test_escape_unescape() {
+        let data = b"hello\n100%\r\x00\xff world";
+        let escaped = escape(data);
+
+        assert!(!escaped.contains('\n'));
+        assert_eq!(unescape(&escaped).unwrap(), data);
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(Line::parse("OK Pleased to meet you").unwrap(), Line::Ok);
+        assert_eq!(
+            Line::parse("D (3:foo%0A)").unwrap(),
+            Line::Data(b"(3:foo\n)".to_vec())
+        );
+        assert_eq!(
+            Line::parse("INQUIRE TBSDATA").unwrap(),
+            Line::Inquire("TBSDATA".to_owned())
+        );
+        assert!(matches!(
+            Line::parse("ERR 67108881 No secret key"),
+            Ok(Line::Err(_))
+        ));
+    }
+
+    #[test]
+    fn test_sexp() {
+        let mut input = b"(7:sig-val(5:eddsa(1:r3:abc)(1:s2:de)))".to_vec();
+        let sexp = Sexp::parse(&input).unwrap();
+        let eddsa = sexp.find(b"eddsa").unwrap();
+
+        let value = |name: &[u8]| eddsa.find(name).and_then(Sexp::value).and_then(Sexp::atom);
+
+        assert_eq!(value(b"r"), Some(&b"abc"[..]));
+        assert_eq!(value(b"s"), Some(&b"de"[..]));
+        assert_eq!(value(b"q"), None);
+
+        let key = Sexp::parse(b"(10:public-key(3:ecc(5:curve7:Ed25519)(1:q3:xyz)))").unwrap();
+        assert_eq!(
+            key.find(b"q").and_then(Sexp::value).and_then(Sexp::atom),
+            Some(&b"xyz"[..])
+        );
+
+        input.pop();
+        assert!(Sexp::parse(&input).is_err());
+    }
+}
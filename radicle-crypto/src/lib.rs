@@ -1,4 +1,6 @@
 use std::cmp::Ordering;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
 use std::{fmt, ops::Deref, str::FromStr};
 
@@ -10,11 +12,22 @@ use thiserror::Error;
 
 pub use ed25519::{Error, KeyPair, Seed};
 
+#[cfg(all(unix, feature = "gpg"))]
+pub mod gpg;
 pub mod hash;
+#[cfg(feature = "mnemonic")]
+pub mod mnemonic;
+pub mod revocation;
+#[cfg(feature = "seal")]
+pub mod seal;
+#[cfg(feature = "shamir")]
+pub mod shamir;
 #[cfg(feature = "ssh")]
 pub mod ssh;
 #[cfg(any(test, feature = "test"))]
 pub mod test;
+#[cfg(feature = "threshold")]
+pub mod threshold;
 
 /// Verified (used as type witness).
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
@@ -69,6 +82,35 @@ where
     }
 }
 
+/// A boxed future, as returned by [`AsyncSigner`].
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A signer that can be used from an async runtime without blocking it, eg. when
+/// signatures are produced by a hardware token or a remote service.
+///
+/// Synchronous [`Signer`]s are adapted by running them on a thread where blocking
+/// is allowed, which is up to the runtime in use.
+pub trait AsyncSigner: Send + Sync {
+    /// Return this signer's public/verification key.
+    fn public_key(&self) -> &PublicKey;
+    /// Sign a message and return the signature, or fail if the signer was unable
+    /// to produce a signature.
+    fn try_sign<'a>(&'a self, msg: &'a [u8]) -> BoxFuture<'a, Result<Signature, SignerError>>;
+}
+
+impl<T> AsyncSigner for Box<T>
+where
+    T: AsyncSigner + ?Sized,
+{
+    fn public_key(&self) -> &PublicKey {
+        self.deref().public_key()
+    }
+
+    fn try_sign<'a>(&'a self, msg: &'a [u8]) -> BoxFuture<'a, Result<Signature, SignerError>> {
+        self.deref().try_sign(msg)
+    }
+}
+
 /// Cryptographic signature.
 #[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize)]
 #[serde(into = "String", try_from = "String")]
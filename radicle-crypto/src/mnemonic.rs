@@ -0,0 +1,119 @@
+//! BIP39 mnemonic encoding of secret key seeds, for paper backups.
+//!
+//! The 32-byte Ed25519 seed is used directly as BIP39 entropy, which yields a phrase of
+//! [`Mnemonic::WORDS`] words. Note that the phrase encodes the seed itself, and not a BIP39
+//! wallet seed derived from it.
+use std::{fmt, str::FromStr};
+
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::{KeyPair, SecretKey, Seed};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("invalid mnemonic: {0}")]
+    Invalid(#[from] bip39::Error),
+    #[error("invalid mnemonic length: expected {expected} words, got {actual}")]
+    InvalidLength { expected: usize, actual: usize },
+}
+
+/// A mnemonic phrase encoding a secret key seed.
+pub struct Mnemonic(bip39::Mnemonic);
+
+impl Mnemonic {
+    /// Number of words in a mnemonic.
+    pub const WORDS: usize = 24;
+
+    /// Encode a secret key seed as a mnemonic.
+    pub fn from_seed(seed: &Seed) -> Self {
+        Self(
+            bip39::Mnemonic::from_entropy(&seed[..])
+                .expect("Mnemonic::from_seed: seed is valid entropy"),
+        )
+    }
+
+    /// Encode the seed of a secret key as a mnemonic.
+    pub fn from_secret_key(secret: &SecretKey) -> Self {
+        Self::from_seed(&secret.seed())
+    }
+
+    /// Decode the secret key seed.
+    pub fn seed(&self) -> Seed {
+        let entropy = Zeroizing::new(self.0.to_entropy());
+
+        Seed::from_slice(&entropy).expect("Mnemonic::seed: entropy is a valid seed")
+    }
+
+    /// Decode the key pair.
+    pub fn keypair(&self) -> KeyPair {
+        KeyPair::from_seed(self.seed())
+    }
+
+    /// Iterate over the words of the mnemonic.
+    pub fn words(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.0.word_iter()
+    }
+}
+
+impl FromStr for Mnemonic {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mnemonic = bip39::Mnemonic::parse(s)?;
+        let actual = mnemonic.word_count();
+
+        if actual != Self::WORDS {
+            return Err(Error::InvalidLength {
+                expected: Self::WORDS,
+                actual,
+            });
+        }
+        Ok(Self(mnemonic))
+    }
+}
+
+impl fmt::Display for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Debug for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Mnemonic(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let pair = KeyPair::from_seed(Seed::new([0xab; 32]));
+        let mnemonic = Mnemonic::from_secret_key(&pair.sk.into());
+        let phrase = mnemonic.to_string();
+
+        assert_eq!(mnemonic.words().count(), Mnemonic::WORDS);
+
+        let decoded = Mnemonic::from_str(&phrase).unwrap();
+        assert_eq!(decoded.keypair().pk, pair.pk);
+        assert_eq!(*decoded.seed(), [0xab; 32]);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let phrase = Mnemonic::from_seed(&Seed::new([0x01; 32])).to_string();
+        let words = phrase.split(' ').collect::<Vec<_>>();
+
+        assert!(Mnemonic::from_str(&words[..23].join(" ")).is_err());
+
+        // Valid BIP39 phrases that don't encode a full seed are rejected.
+        let short = bip39::Mnemonic::from_entropy(&[0; 16]).unwrap().to_string();
+        assert!(matches!(
+            Mnemonic::from_str(&short),
+            Err(Error::InvalidLength { actual: 12, .. })
+        ));
+    }
+}
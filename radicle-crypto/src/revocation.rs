@@ -0,0 +1,168 @@
+//! Signed key revocation statements.
+//!
+//! A revocation states that a key must no longer be trusted for signatures made at or
+//! after a given time. Signatures made before that time remain valid, so that history
+//! signed with the key can still be verified.
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{PublicKey, Signature, Signer};
+
+/// Domain separation prefix for revocation statements.
+const CONTEXT: &[u8] = b"radicle-revocation-v1";
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("invalid revocation signature: {0}")]
+    Signature(#[from] crate::Error),
+}
+
+/// An unsigned key revocation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Revocation {
+    /// The revoked key.
+    pub key: PublicKey,
+    /// Time in seconds since the epoch, from which signatures by the key are rejected.
+    pub timestamp: u64,
+}
+
+impl Revocation {
+    pub fn new(key: PublicKey, timestamp: u64) -> Self {
+        Self { key, timestamp }
+    }
+
+    /// Canonical encoding of the revocation, as signed by the revoker.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(CONTEXT.len() + 32 + 8);
+        bytes.extend_from_slice(CONTEXT);
+        bytes.extend_from_slice(&**self.key);
+        bytes.extend_from_slice(&self.timestamp.to_be_bytes());
+        bytes
+    }
+
+    /// Sign the revocation.
+    pub fn sign<G: Signer>(self, signer: &G) -> SignedRevocation {
+        SignedRevocation {
+            signature: signer.sign(&self.encode()),
+            revoker: *signer.public_key(),
+            revocation: self,
+        }
+    }
+}
+
+/// A key revocation, signed by the revoker. The revoker is either the revoked key itself,
+/// or a key that is authoritative over it, eg. an identity delegate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedRevocation {
+    #[serde(flatten)]
+    pub revocation: Revocation,
+    pub revoker: PublicKey,
+    pub signature: Signature,
+}
+
+impl SignedRevocation {
+    /// Verify the revocation signature.
+    pub fn verify(&self) -> Result<(), Error> {
+        self.revoker
+            .verify(self.revocation.encode(), &self.signature)
+            .map_err(Error::from)
+    }
+
+    /// Whether the key revoked itself.
+    pub fn is_self_revocation(&self) -> bool {
+        self.revoker == self.revocation.key
+    }
+}
+
+/// A set of verified revocations, at most one per key.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Revocations(BTreeMap<PublicKey, SignedRevocation>);
+
+impl Revocations {
+    /// Add a revocation, after verifying it. If the key was already revoked, the earliest
+    /// revocation is kept. Returns `true` if the set was updated.
+    pub fn insert(&mut self, revocation: SignedRevocation) -> Result<bool, Error> {
+        revocation.verify()?;
+
+        let key = revocation.revocation.key;
+        match self.0.get(&key) {
+            Some(existing) if existing.revocation.timestamp <= revocation.revocation.timestamp => {
+                Ok(false)
+            }
+            _ => {
+                self.0.insert(key, revocation);
+                Ok(true)
+            }
+        }
+    }
+
+    /// Get the revocation of the given key, if any.
+    pub fn get(&self, key: &PublicKey) -> Option<&SignedRevocation> {
+        self.0.get(key)
+    }
+
+    /// Whether the given key is revoked.
+    pub fn is_revoked(&self, key: &PublicKey) -> bool {
+        self.0.contains_key(key)
+    }
+
+    /// Whether a signature made by the given key at the given time, in seconds since the
+    /// epoch, is acceptable.
+    pub fn is_valid_at(&self, key: &PublicKey, timestamp: u64) -> bool {
+        match self.0.get(key) {
+            Some(r) => timestamp < r.revocation.timestamp,
+            None => true,
+        }
+    }
+
+    /// Iterate over the revocations.
+    pub fn iter(&self) -> impl Iterator<Item = &SignedRevocation> {
+        self.0.values()
+    }
+
+    /// Number of revoked keys.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether no keys are revoked.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::signer::MockSigner;
+
+    #[test]
+    fn test_revocations() {
+        let device = MockSigner::from_seed([1; 32]);
+        let owner = MockSigner::from_seed([2; 32]);
+        let mut revocations = Revocations::default();
+
+        let late = Revocation::new(*device.public_key(), 200).sign(&owner);
+        let early = Revocation::new(*device.public_key(), 100).sign(&device);
+
+        assert!(late.verify().is_ok());
+        assert!(early.is_self_revocation());
+        assert!(revocations.insert(late).unwrap());
+        assert!(revocations.insert(early).unwrap());
+        assert!(!revocations.insert(late).unwrap());
+        assert_eq!(revocations.len(), 1);
+
+        assert!(revocations.is_revoked(device.public_key()));
+        assert!(revocations.is_valid_at(device.public_key(), 99));
+        assert!(!revocations.is_valid_at(device.public_key(), 100));
+        assert!(revocations.is_valid_at(owner.public_key(), 1000));
+
+        let mut forged = Revocation::new(*owner.public_key(), 100).sign(&device);
+        forged.revoker = *owner.public_key();
+        assert!(revocations.insert(forged).is_err());
+        assert!(!revocations.is_revoked(owner.public_key()));
+    }
+}
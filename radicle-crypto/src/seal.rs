@@ -0,0 +1,222 @@
+//! Sealed-box encryption to Ed25519 public keys.
+//!
+//! Recipients' Ed25519 keys are converted to X25519 keys, and a fresh ephemeral X25519 key
+//! is used for every sealed box, so that the sender remains anonymous. The symmetric cipher
+//! is ChaCha20-Poly1305.
+//!
+//! For multiple recipients, an [`Envelope`] encrypts the message once under a random
+//! content key, which is then sealed to each recipient.
+use std::collections::BTreeMap;
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use ed25519_compact::x25519;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::{PublicKey, SecretKey, SharedSecret};
+
+/// Domain separation prefix for key derivation.
+const CONTEXT: &[u8] = b"radicle-seal-v1";
+/// Length of the ephemeral public key prefixed to sealed boxes.
+const EPHEMERAL_LEN: usize = 32;
+/// Every key is used for a single encryption, so the nonce can be fixed.
+const NONCE: [u8; 12] = [0; 12];
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("invalid key: {0}")]
+    Key(#[from] ed25519_compact::Error),
+    #[error("sealed box is too short")]
+    Truncated,
+    #[error("decryption failed")]
+    Decrypt,
+    #[error("encryption failed")]
+    Encrypt,
+    #[error("key `{0}` is not a recipient of this envelope")]
+    NotRecipient(PublicKey),
+}
+
+/// Encrypt a message to a single recipient.
+///
+/// The output is the ephemeral public key, followed by the ciphertext.
+pub fn seal(recipient: &PublicKey, msg: &[u8]) -> Result<Vec<u8>, Error> {
+    let ephemeral = x25519::KeyPair::generate();
+    let key = derive(&ephemeral.sk, &ephemeral.pk, recipient)?;
+    let ciphertext = encrypt(&key, &ephemeral.pk[..], msg)?;
+
+    let mut sealed = Vec::with_capacity(EPHEMERAL_LEN + ciphertext.len());
+    sealed.extend_from_slice(&ephemeral.pk[..]);
+    sealed.extend_from_slice(&ciphertext);
+
+    Ok(sealed)
+}
+
+/// Decrypt a message sealed to the given secret key with [`seal`].
+pub fn open(secret: &SecretKey, sealed: &[u8]) -> Result<Vec<u8>, Error> {
+    if sealed.len() < EPHEMERAL_LEN {
+        return Err(Error::Truncated);
+    }
+    let (ephemeral, ciphertext) = sealed.split_at(EPHEMERAL_LEN);
+    let key = derive_open(secret, ephemeral)?;
+
+    decrypt(&key, ephemeral, ciphertext)
+}
+
+/// A message encrypted to multiple recipients.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Envelope {
+    /// Ephemeral public key used to wrap the content key for each recipient.
+    ephemeral: [u8; EPHEMERAL_LEN],
+    /// The content key, wrapped for each recipient.
+    recipients: BTreeMap<PublicKey, Vec<u8>>,
+    /// The message, encrypted with the content key.
+    ciphertext: Vec<u8>,
+}
+
+impl Envelope {
+    /// Encrypt a message to the given recipients.
+    pub fn seal<'a>(
+        recipients: impl IntoIterator<Item = &'a PublicKey>,
+        msg: &[u8],
+    ) -> Result<Self, Error> {
+        let ephemeral = x25519::KeyPair::generate();
+        let content = Zeroizing::new(*ed25519_compact::Seed::generate());
+        let ciphertext = encrypt(&content, &ephemeral.pk[..], msg)?;
+        let recipients = recipients
+            .into_iter()
+            .map(|r| {
+                let key = derive(&ephemeral.sk, &ephemeral.pk, r)?;
+                let wrapped = encrypt(&key, &ephemeral.pk[..], &content[..])?;
+
+                Ok((*r, wrapped))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Self {
+            ephemeral: *ephemeral.pk,
+            recipients,
+            ciphertext,
+        })
+    }
+
+    /// Decrypt the message with the secret key of one of the recipients.
+    pub fn open(&self, secret: &SecretKey) -> Result<Vec<u8>, Error> {
+        let public = PublicKey::from(secret.public_key());
+        let wrapped = self
+            .recipients
+            .get(&public)
+            .ok_or(Error::NotRecipient(public))?;
+        let key = derive_open(secret, &self.ephemeral)?;
+        let content = Zeroizing::new(decrypt(&key, &self.ephemeral, wrapped)?);
+        let content =
+            Zeroizing::new(SharedSecret::try_from(&content[..]).map_err(|_| Error::Decrypt)?);
+
+        decrypt(&content, &self.ephemeral, &self.ciphertext)
+    }
+
+    /// The recipients of this envelope.
+    pub fn recipients(&self) -> impl Iterator<Item = &PublicKey> {
+        self.recipients.keys()
+    }
+}
+
+/// Derive the symmetric key used to seal a message to the given recipient.
+fn derive(
+    ephemeral: &x25519::SecretKey,
+    ephemeral_pk: &x25519::PublicKey,
+    recipient: &PublicKey,
+) -> Result<Zeroizing<SharedSecret>, Error> {
+    let recipient = x25519::PublicKey::from_ed25519(recipient)?;
+    let shared = Zeroizing::new(*ephemeral.dh(&recipient)?);
+
+    Ok(kdf(&shared, &ephemeral_pk[..], &recipient[..]))
+}
+
+/// Derive the symmetric key used to open a message sealed to us.
+fn derive_open(secret: &SecretKey, ephemeral: &[u8]) -> Result<Zeroizing<SharedSecret>, Error> {
+    let ephemeral_pk = x25519::PublicKey::from_slice(ephemeral)?;
+    let shared = Zeroizing::new(*x25519::SecretKey::from_ed25519(secret)?.dh(&ephemeral_pk)?);
+    let recipient = x25519::PublicKey::from_ed25519(&secret.public_key())?;
+
+    Ok(kdf(&shared, ephemeral, &recipient[..]))
+}
+
+fn kdf(shared: &SharedSecret, ephemeral: &[u8], recipient: &[u8]) -> Zeroizing<SharedSecret> {
+    let mut hasher = Sha256::new();
+    hasher.update(CONTEXT);
+    hasher.update(shared);
+    hasher.update(ephemeral);
+    hasher.update(recipient);
+
+    Zeroizing::new(hasher.finalize().into())
+}
+
+fn encrypt(key: &SharedSecret, aad: &[u8], msg: &[u8]) -> Result<Vec<u8>, Error> {
+    ChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(Nonce::from_slice(&NONCE), Payload { msg, aad })
+        .map_err(|_| Error::Encrypt)
+}
+
+fn decrypt(key: &SharedSecret, aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+    ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(
+            Nonce::from_slice(&NONCE),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| Error::Decrypt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KeyPair, Seed};
+
+    fn keypair(seed: u8) -> (SecretKey, PublicKey) {
+        let pair = KeyPair::from_seed(Seed::new([seed; 32]));
+        (pair.sk.into(), pair.pk.into())
+    }
+
+    #[test]
+    fn test_seal_open() {
+        let (alice_sk, alice) = keypair(1);
+        let (bob_sk, _) = keypair(2);
+        let sealed = seal(&alice, b"hello").unwrap();
+
+        assert_eq!(open(&alice_sk, &sealed).unwrap(), b"hello");
+        assert!(open(&bob_sk, &sealed).is_err());
+        assert!(matches!(
+            open(&alice_sk, &sealed[..16]),
+            Err(Error::Truncated)
+        ));
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(matches!(open(&alice_sk, &tampered), Err(Error::Decrypt)));
+    }
+
+    #[test]
+    fn test_envelope() {
+        let (alice_sk, alice) = keypair(1);
+        let (bob_sk, bob) = keypair(2);
+        let (eve_sk, eve) = keypair(3);
+        let envelope = Envelope::seal([&alice, &bob], b"hello").unwrap();
+
+        assert_eq!(envelope.recipients().collect::<Vec<_>>().len(), 2);
+        assert_eq!(envelope.open(&alice_sk).unwrap(), b"hello");
+        assert_eq!(envelope.open(&bob_sk).unwrap(), b"hello");
+        assert!(matches!(
+            envelope.open(&eve_sk),
+            Err(Error::NotRecipient(pk)) if pk == eve
+        ));
+
+        let json = serde_json::to_string(&envelope).unwrap();
+        let decoded: Envelope = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.open(&bob_sk).unwrap(), b"hello");
+    }
+}
@@ -0,0 +1,318 @@
+//! Shamir secret sharing over GF(2^8), for splitting a secret key seed into shares, any
+//! `threshold` of which are enough to recover it.
+//!
+//! Each byte of the secret is shared independently, with a random polynomial of degree
+//! `threshold - 1` whose constant term is the secret byte. Share `i` holds the evaluations
+//! of these polynomials at `x = i`. Field arithmetic uses the AES polynomial and is
+//! branch-free, so that timing doesn't depend on secret values.
+use std::{fmt, str::FromStr};
+
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::Seed;
+
+/// Length of the share header: threshold and index.
+const HEADER_LEN: usize = 2;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("invalid threshold {threshold} for {count} share(s)")]
+    InvalidThreshold { threshold: u8, count: u8 },
+    #[error("not enough shares: expected {expected}, got {actual}")]
+    NotEnoughShares { expected: u8, actual: usize },
+    #[error("shares are not from the same secret")]
+    Mismatch,
+    #[error("duplicate share with index {0}")]
+    Duplicate(u8),
+    #[error("invalid share encoding")]
+    Encoding,
+}
+
+/// One share of a secret.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Share {
+    threshold: u8,
+    index: u8,
+    value: Zeroizing<Vec<u8>>,
+}
+
+impl Share {
+    /// Number of shares needed to recover the secret.
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// Index of the share, starting at `1`.
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Encode the share as bytes: threshold, index, followed by the share value.
+    pub fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut bytes = Zeroizing::new(Vec::with_capacity(HEADER_LEN + self.value.len()));
+        bytes.push(self.threshold);
+        bytes.push(self.index);
+        bytes.extend_from_slice(&self.value);
+        bytes
+    }
+
+    /// Decode a share encoded with [`Share::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        match bytes {
+            [threshold, index, value @ ..]
+                if *threshold >= 2 && *index != 0 && !value.is_empty() =>
+            {
+                Ok(Self {
+                    threshold: *threshold,
+                    index: *index,
+                    value: Zeroizing::new(value.to_vec()),
+                })
+            }
+            _ => Err(Error::Encoding),
+        }
+    }
+}
+
+impl fmt::Display for Share {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&multibase::encode(
+            multibase::Base::Base58Btc,
+            &self.to_bytes()[..],
+        ))
+    }
+}
+
+impl fmt::Debug for Share {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Share")
+            .field("threshold", &self.threshold)
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FromStr for Share {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match multibase::decode(s) {
+            Ok((multibase::Base::Base58Btc, bytes)) => Self::from_bytes(&Zeroizing::new(bytes)),
+            _ => Err(Error::Encoding),
+        }
+    }
+}
+
+/// Split a secret into `count` shares, `threshold` of which are needed to recover it.
+pub fn split(secret: &[u8], threshold: u8, count: u8) -> Result<Vec<Share>, Error> {
+    let degree = threshold.saturating_sub(1) as usize;
+    let mut random = Zeroizing::new(Vec::with_capacity(secret.len() * degree + 32));
+    while random.len() < secret.len() * degree {
+        random.extend_from_slice(&Seed::generate()[..]);
+    }
+    split_with(secret, threshold, count, &random)
+}
+
+/// Split a seed into `count` shares, `threshold` of which are needed to recover it.
+pub fn split_seed(seed: &Seed, threshold: u8, count: u8) -> Result<Vec<Share>, Error> {
+    split(&seed[..], threshold, count)
+}
+
+/// Recover a secret from at least `threshold` of its shares.
+pub fn combine(shares: &[Share]) -> Result<Zeroizing<Vec<u8>>, Error> {
+    let Some(first) = shares.first() else {
+        return Err(Error::NotEnoughShares {
+            expected: 2,
+            actual: 0,
+        });
+    };
+    if shares.len() < first.threshold as usize {
+        return Err(Error::NotEnoughShares {
+            expected: first.threshold,
+            actual: shares.len(),
+        });
+    }
+    for (i, share) in shares.iter().enumerate() {
+        if share.threshold != first.threshold || share.value.len() != first.value.len() {
+            return Err(Error::Mismatch);
+        }
+        if shares[..i].iter().any(|s| s.index == share.index) {
+            return Err(Error::Duplicate(share.index));
+        }
+    }
+    let shares = &shares[..first.threshold as usize];
+    let mut secret = Zeroizing::new(vec![0; first.value.len()]);
+
+    // Lagrange interpolation at `x = 0`.
+    for (j, share) in shares.iter().enumerate() {
+        let mut basis = 1;
+        for (m, other) in shares.iter().enumerate() {
+            if m != j {
+                basis = mul(basis, mul(other.index, inv(other.index ^ share.index)));
+            }
+        }
+        for (s, y) in secret.iter_mut().zip(share.value.iter()) {
+            *s ^= mul(*y, basis);
+        }
+    }
+    Ok(secret)
+}
+
+/// Recover a seed from at least `threshold` of its shares.
+pub fn combine_seed(shares: &[Share]) -> Result<Seed, Error> {
+    let secret = combine(shares)?;
+
+    Seed::from_slice(&secret).map_err(|_| Error::Mismatch)
+}
+
+/// Split a secret using the given random bytes for the polynomial coefficients.
+/// The coefficients of the polynomial for byte `k` of the secret are taken from
+/// `random[k * (threshold - 1)..]`, by increasing degree.
+fn split_with(secret: &[u8], threshold: u8, count: u8, random: &[u8]) -> Result<Vec<Share>, Error> {
+    if threshold < 2 || threshold > count {
+        return Err(Error::InvalidThreshold { threshold, count });
+    }
+    let degree = threshold as usize - 1;
+    debug_assert!(random.len() >= secret.len() * degree);
+
+    Ok((1..=count)
+        .map(|x| {
+            let value = secret
+                .iter()
+                .zip(random.chunks(degree))
+                .map(|(s, coeffs)| {
+                    // Horner's method, from the highest degree down to the secret.
+                    let y = coeffs.iter().rev().fold(0, |y, c| mul(y, x) ^ c);
+                    mul(y, x) ^ s
+                })
+                .collect::<Vec<_>>();
+
+            Share {
+                threshold,
+                index: x,
+                value: Zeroizing::new(value),
+            }
+        })
+        .collect())
+}
+
+/// Multiplication in GF(2^8), modulo `x^8 + x^4 + x^3 + x + 1`.
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut p = 0;
+    for _ in 0..8 {
+        p ^= a & (b & 1).wrapping_neg();
+        let carry = (a >> 7).wrapping_neg();
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    p
+}
+
+/// Multiplicative inverse in GF(2^8), computed as `a^254`.
+fn inv(a: u8) -> u8 {
+    let mut x = mul(a, a);
+    let mut r = x;
+    for _ in 0..6 {
+        x = mul(x, x);
+        r = mul(r, x);
+    }
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn share(index: u8, threshold: u8, hex: &str) -> Share {
+        let value = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect();
+
+        Share {
+            threshold,
+            index,
+            value: Zeroizing::new(value),
+        }
+    }
+
+    #[test]
+    fn test_field() {
+        for a in 1..=255 {
+            assert_eq!(mul(a, inv(a)), 1);
+        }
+        assert_eq!(mul(0x57, 0x83), 0xc1);
+    }
+
+    #[test]
+    fn test_vectors() {
+        let random = (1..=14).collect::<Vec<u8>>();
+        let shares = split_with(b"radicle", 3, 5, &random).unwrap();
+        let expected = [
+            share(1, 3, "71666766606b66"),
+            share(2, 3, "78777647594a47"),
+            share(3, 3, "7b7075485a4d44"),
+            share(4, 3, "562d10f5e780b1"),
+            share(5, 3, "552a13fae487b2"),
+        ];
+        assert_eq!(shares, expected);
+        assert_eq!(&**combine(&expected[2..]).unwrap(), b"radicle");
+        assert_eq!(
+            &**combine(&[
+                expected[4].clone(),
+                expected[0].clone(),
+                expected[2].clone()
+            ])
+            .unwrap(),
+            b"radicle"
+        );
+
+        let shares = split_with(b"radicle", 2, 3, &[0x9b; 7]).unwrap();
+        let expected = [
+            share(1, 2, "e9fafff2f8f7fe"),
+            share(2, 2, "5f4c49444e4148"),
+            share(3, 2, "c4d7d2dfd5dad3"),
+        ];
+        assert_eq!(shares, expected);
+        assert_eq!(&**combine(&expected[1..]).unwrap(), b"radicle");
+    }
+
+    #[test]
+    fn test_split_combine() {
+        let seed = Seed::generate();
+        let shares = split_seed(&seed, 2, 3).unwrap();
+
+        for (i, j) in [(0, 1), (1, 2), (2, 0)] {
+            let subset = [shares[i].clone(), shares[j].clone()];
+            assert_eq!(*combine_seed(&subset).unwrap(), *seed);
+        }
+        assert_eq!(
+            combine(&shares[..1]),
+            Err(Error::NotEnoughShares {
+                expected: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(
+            combine(&[shares[0].clone(), shares[0].clone()]),
+            Err(Error::Duplicate(1))
+        );
+        assert_eq!(
+            split(&seed[..], 4, 3),
+            Err(Error::InvalidThreshold {
+                threshold: 4,
+                count: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_encoding() {
+        let shares = split(b"radicle", 2, 3).unwrap();
+        for share in shares {
+            let decoded = share.to_string().parse::<Share>().unwrap();
+            assert_eq!(decoded, share);
+        }
+        assert_eq!("zabc".parse::<Share>(), Err(Error::Encoding));
+    }
+}
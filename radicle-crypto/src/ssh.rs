@@ -1,5 +1,6 @@
 pub mod agent;
 pub mod keystore;
+pub mod signer;
 
 use std::io;
 
@@ -14,6 +15,7 @@ use crate as crypto;
 use crate::PublicKey;
 
 pub use keystore::{Keystore, Passphrase};
+pub use signer::SshSigner;
 
 pub mod fmt {
     use radicle_ssh::encoding::Encoding as _;
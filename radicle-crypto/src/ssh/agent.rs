@@ -1,4 +1,5 @@
 use std::ops::{Deref, DerefMut};
+use std::path::Path;
 use std::sync::Mutex;
 
 pub use radicle_ssh::agent::client::AgentClient;
@@ -22,6 +23,12 @@ impl Agent {
         Stream::connect_env().map(|client| Self { client })
     }
 
+    /// Connect to the SSH agent listening on the given socket, instead of the one
+    /// set in the environment.
+    pub fn connect_to<P: AsRef<Path> + Send>(socket: P) -> Result<Self, ssh::agent::client::Error> {
+        <Stream as ClientStream>::connect(socket).map(|client| Self { client })
+    }
+
     /// Register a key with the agent.
     pub fn register(&mut self, key: &SecretKey) -> Result<(), ssh::Error> {
         self.client.add_identity(key, &[])
@@ -31,6 +38,20 @@ impl Agent {
     pub fn signer(self, key: PublicKey) -> AgentSigner {
         AgentSigner::new(self, key)
     }
+
+    /// List the Ed25519 keys registered with the agent.
+    pub fn keys(&mut self) -> Result<Vec<PublicKey>, Error> {
+        self.client.request_identities()
+    }
+
+    /// Get a signer from this agent for the given key, if the agent holds it.
+    pub fn select(mut self, key: &PublicKey) -> Result<Option<AgentSigner>, Error> {
+        if self.keys()?.contains(key) {
+            Ok(Some(self.signer(*key)))
+        } else {
+            Ok(None)
+        }
+    }
 }
 
 impl Deref for Agent {
@@ -47,17 +68,40 @@ impl DerefMut for Agent {
     }
 }
 
+/// Error returned when the agent refuses to sign.
+#[derive(Debug, thiserror::Error)]
+#[error("ssh-agent refused to sign with key `{0}`; was the confirmation declined?")]
+pub struct RefusedError(PublicKey);
+
+/// Called before each signature request, eg. to tell the user to confirm it.
+pub type Confirm = Box<dyn Fn(&PublicKey) + Send + Sync>;
+
 /// A [`Signer`] that uses `ssh-agent`.
 pub struct AgentSigner {
     agent: Mutex<Agent>,
     public: PublicKey,
+    confirm: Option<Confirm>,
 }
 
 impl AgentSigner {
     pub fn new(agent: Agent, public: PublicKey) -> Self {
         let agent = Mutex::new(agent);
 
-        Self { agent, public }
+        Self {
+            agent,
+            public,
+            confirm: None,
+        }
+    }
+
+    /// Call the given function before each signature request. This is useful for keys that
+    /// were added to the agent with `ssh-add -c`, where every signature has to be confirmed.
+    pub fn with_confirmation(
+        mut self,
+        confirm: impl Fn(&PublicKey) + Send + Sync + 'static,
+    ) -> Self {
+        self.confirm = Some(Box::new(confirm));
+        self
     }
 
     pub fn is_ready(&self) -> Result<bool, Error> {
@@ -82,13 +126,19 @@ impl Signer for AgentSigner {
     }
 
     fn try_sign(&self, msg: &[u8]) -> Result<Signature, SignerError> {
-        let sig = self
+        let mut agent = self
             .agent
             .lock()
             // We'll take our chances here; the worse that can happen is the agent returns an error.
-            .unwrap_or_else(|e| e.into_inner())
-            .sign(&self.public, msg)
-            .map_err(SignerError::new)?;
+            .unwrap_or_else(|e| e.into_inner());
+
+        if let Some(confirm) = &self.confirm {
+            confirm(&self.public);
+        }
+        let sig = agent.sign(&self.public, msg).map_err(|e| match e {
+            Error::AgentFailure => SignerError::new(RefusedError(self.public)),
+            e => SignerError::new(e),
+        })?;
 
         Ok(Signature::from(sig))
     }
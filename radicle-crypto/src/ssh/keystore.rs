@@ -13,6 +13,47 @@ use crate::{keypair, KeyPair, PublicKey, SecretKey, Signature, Signer, SignerErr
 /// A secret key passphrase.
 pub type Passphrase = Zeroizing<String>;
 
+/// Directory under the keystore path where device keys are stored.
+pub const DEVICES_DIR: &str = "devices";
+
+/// Parameters of the key derivation function used to encrypt the secret key.
+///
+/// Keys are encrypted with `bcrypt-pbkdf`, as specified by the OpenSSH key format.
+/// The parameters are stored in the key file header, so keys encrypted with different
+/// parameters can always be decrypted.
+///
+/// Other KDFs, eg. scrypt or Argon2, are not supported: the OpenSSH key format only
+/// defines `bcrypt-pbkdf`, and keys encrypted otherwise couldn't be read by `ssh-keygen`
+/// or added to `ssh-agent`. Hardening is done by increasing the number of rounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Number of `bcrypt-pbkdf` rounds.
+    pub rounds: u32,
+}
+
+impl KdfParams {
+    /// Number of rounds used by `ssh-keygen` by default.
+    pub const DEFAULT_ROUNDS: u32 = 16;
+    /// Number of rounds used by [`KdfParams::hardened`].
+    pub const HARDENED_ROUNDS: u32 = 128;
+
+    /// Parameters that are considerably more expensive to brute-force, at the cost
+    /// of slower key unsealing.
+    pub fn hardened() -> Self {
+        Self {
+            rounds: Self::HARDENED_ROUNDS,
+        }
+    }
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            rounds: Self::DEFAULT_ROUNDS,
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error(transparent)]
@@ -23,12 +64,19 @@ pub enum Error {
     InvalidKeyType,
     #[error("keystore already initialized")]
     AlreadyInitialized,
+    #[error("invalid kdf parameters: {0}")]
+    InvalidKdf(&'static str),
+    #[error("secret key not found in '{0}'")]
+    NotFound(PathBuf),
+    #[error("invalid device name `{0}`")]
+    InvalidDeviceName(String),
 }
 
 /// Stores keys on disk, in OpenSSH format.
 #[derive(Debug, Clone)]
 pub struct Keystore {
     path: PathBuf,
+    kdf: KdfParams,
 }
 
 impl Keystore {
@@ -36,9 +84,17 @@ impl Keystore {
     pub fn new<P: AsRef<Path>>(path: &P) -> Self {
         Self {
             path: path.as_ref().to_path_buf(),
+            kdf: KdfParams::default(),
         }
     }
 
+    /// Use the given KDF parameters when encrypting keys.
+    /// Existing keys are always decrypted with the parameters they were encrypted with.
+    pub fn with_kdf(mut self, kdf: KdfParams) -> Self {
+        self.kdf = kdf;
+        self
+    }
+
     /// Get the path to the keystore.
     pub fn path(&self) -> &Path {
         self.path.as_path()
@@ -63,22 +119,108 @@ impl Keystore {
         keypair: KeyPair,
         comment: &str,
         passphrase: impl Into<Passphrase>,
+    ) -> Result<PublicKey, Error> {
+        self.store_at(self.path.join("radicle"), keypair, comment, passphrase)
+    }
+
+    /// Generate a new device key pair, and store it under the given device name.
+    /// Returns an error if a device with that name already exists.
+    pub fn init_device(
+        &self,
+        name: &str,
+        passphrase: impl Into<Passphrase>,
+    ) -> Result<PublicKey, Error> {
+        let path = self.device_path(name)?;
+
+        self.store_at(path, keypair::generate(), name, passphrase)
+    }
+
+    /// List the device keys in the store, by name.
+    pub fn devices(&self) -> Result<Vec<(String, PublicKey)>, Error> {
+        let dir = self.path.join(DEVICES_DIR);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut devices = Vec::new();
+
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().map_or(true, |ext| ext != "pub") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if let Some(public) = Self::public_key_at(&path)? {
+                devices.push((name.to_owned(), public));
+            }
+        }
+        devices.sort();
+
+        Ok(devices)
+    }
+
+    /// Load the public key of the given device. Returns `None` if it wasn't found.
+    pub fn device_public_key(&self, name: &str) -> Result<Option<PublicKey>, Error> {
+        Self::public_key_at(&self.device_path(name)?.with_extension("pub"))
+    }
+
+    /// Load the secret key of the given device, decrypting it with the given passphrase.
+    /// Returns `None` if it wasn't found.
+    pub fn device_secret_key(
+        &self,
+        name: &str,
+        passphrase: Passphrase,
+    ) -> Result<Option<Zeroizing<SecretKey>>, Error> {
+        Self::secret_key_at(&self.device_path(name)?, passphrase)
+    }
+
+    /// Remove the keys of the given device from the store.
+    /// Returns `false` if the device wasn't found.
+    pub fn remove_device(&self, name: &str) -> Result<bool, Error> {
+        let path = self.device_path(name)?;
+        if !path.exists() {
+            return Ok(false);
+        }
+        fs::remove_file(&path)?;
+        fs::remove_file(path.with_extension("pub")).ok();
+
+        Ok(true)
+    }
+
+    fn device_path(&self, name: &str) -> Result<PathBuf, Error> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(Error::InvalidDeviceName(name.to_owned()));
+        }
+        Ok(self.path.join(DEVICES_DIR).join(name))
+    }
+
+    fn store_at(
+        &self,
+        path: PathBuf,
+        keypair: KeyPair,
+        comment: &str,
+        passphrase: impl Into<Passphrase>,
     ) -> Result<PublicKey, Error> {
         let ssh_pair = ssh_key::private::Ed25519Keypair::from_bytes(&keypair)?;
         let ssh_pair = ssh_key::private::KeypairData::Ed25519(ssh_pair);
         let secret = ssh_key::PrivateKey::new(ssh_pair, comment)?;
-        let secret = secret.encrypt(ssh_key::rand_core::OsRng, passphrase.into())?;
+        let secret = self.encrypt(&secret, passphrase.into())?;
         let public = secret.public_key();
-        let path = self.path.join("radicle");
 
         if path.exists() {
             return Err(Error::AlreadyInitialized);
         }
-
-        fs::DirBuilder::new()
-            .recursive(true)
-            .mode(0o700)
-            .create(&self.path)?;
+        if let Some(parent) = path.parent() {
+            fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(parent)?;
+        }
 
         secret.write_openssh_file(&path, ssh_key::LineEnding::default())?;
         public.write_openssh_file(&path.with_extension("pub"))?;
@@ -86,32 +228,104 @@ impl Keystore {
         Ok(keypair.pk.into())
     }
 
+    /// Get the KDF parameters the stored secret key was encrypted with.
+    /// Returns `None` if the key wasn't found.
+    pub fn kdf_params(&self) -> Result<Option<KdfParams>, Error> {
+        let path = self.path.join("radicle");
+        if !path.exists() {
+            return Ok(None);
+        }
+        let encrypted = ssh_key::PrivateKey::read_openssh_file(&path)?;
+
+        match encrypted.kdf() {
+            ssh_key::Kdf::Bcrypt { rounds, .. } => Ok(Some(KdfParams { rounds: *rounds })),
+            _ => Err(Error::InvalidKdf("key is not encrypted with bcrypt-pbkdf")),
+        }
+    }
+
+    /// Re-encrypt the stored secret key with this keystore's KDF parameters.
+    /// This can be used to upgrade a key to stronger parameters.
+    pub fn rekey(&self, passphrase: Passphrase) -> Result<(), Error> {
+        let path = self.path.join("radicle");
+        if !path.exists() {
+            return Err(Error::NotFound(path));
+        }
+        let encrypted = ssh_key::PrivateKey::read_openssh_file(&path)?;
+        let secret = encrypted.decrypt(passphrase.as_bytes())?;
+        let secret = self.encrypt(&secret, passphrase)?;
+
+        // Write to a temporary file first, so that we never end up with a partially
+        // written key.
+        let tmp = path.with_extension("tmp");
+        secret.write_openssh_file(&tmp, ssh_key::LineEnding::default())?;
+        fs::rename(tmp, path)?;
+
+        Ok(())
+    }
+
+    fn encrypt(
+        &self,
+        secret: &ssh_key::PrivateKey,
+        passphrase: Passphrase,
+    ) -> Result<ssh_key::PrivateKey, Error> {
+        use ssh_key::rand_core::{OsRng, RngCore as _};
+
+        if self.kdf.rounds == 0 {
+            return Err(Error::InvalidKdf("rounds must be greater than zero"));
+        }
+        let mut salt = vec![0; 16];
+        OsRng.fill_bytes(&mut salt);
+
+        let kdf = ssh_key::Kdf::Bcrypt {
+            salt,
+            rounds: self.kdf.rounds,
+        };
+        let checkint = OsRng.next_u32();
+        let secret = secret.encrypt_with(
+            ssh_key::Cipher::default(),
+            kdf,
+            checkint,
+            passphrase.as_bytes(),
+        )?;
+
+        Ok(secret)
+    }
+
     /// Load the public key from the store. Returns `None` if it wasn't found.
     pub fn public_key(&self) -> Result<Option<PublicKey>, Error> {
-        let path = self.path.join("radicle.pub");
+        Self::public_key_at(&self.path.join("radicle.pub"))
+    }
+
+    /// Load the secret key from the store, decrypting it with the given passphrase.
+    /// Returns `None` if it wasn't found.
+    pub fn secret_key(
+        &self,
+        passphrase: Passphrase,
+    ) -> Result<Option<Zeroizing<SecretKey>>, Error> {
+        Self::secret_key_at(&self.path.join("radicle"), passphrase)
+    }
+
+    fn public_key_at(path: &Path) -> Result<Option<PublicKey>, Error> {
         if !path.exists() {
             return Ok(None);
         }
 
-        let public = ssh_key::PublicKey::read_openssh_file(&path)?;
+        let public = ssh_key::PublicKey::read_openssh_file(path)?;
         match public.try_into() {
             Ok(public) => Ok(Some(public)),
             _ => Err(Error::InvalidKeyType),
         }
     }
 
-    /// Load the secret key from the store, decrypting it with the given passphrase.
-    /// Returns `None` if it wasn't found.
-    pub fn secret_key(
-        &self,
+    fn secret_key_at(
+        path: &Path,
         passphrase: Passphrase,
     ) -> Result<Option<Zeroizing<SecretKey>>, Error> {
-        let path = self.path.join("radicle");
         if !path.exists() {
             return Ok(None);
         }
 
-        let encrypted = ssh_key::PrivateKey::read_openssh_file(&path)?;
+        let encrypted = ssh_key::PrivateKey::read_openssh_file(path)?;
         let secret = encrypted.decrypt(passphrase)?;
 
         match secret.key_data() {
@@ -194,6 +408,22 @@ impl MemorySigner {
         Ok(Self { public, secret })
     }
 
+    /// Load the signer of the given device from a keystore, given a secret key passphrase.
+    pub fn load_device(
+        keystore: &Keystore,
+        name: &str,
+        passphrase: Passphrase,
+    ) -> Result<Self, MemorySignerError> {
+        let not_found =
+            || MemorySignerError::NotFound(keystore.path().join(DEVICES_DIR).join(name));
+        let public = keystore.device_public_key(name)?.ok_or_else(not_found)?;
+        let secret = keystore
+            .device_secret_key(name, passphrase)?
+            .ok_or_else(not_found)?;
+
+        Ok(Self { public, secret })
+    }
+
     /// Box this signer into a trait object.
     pub fn boxed(self) -> Box<dyn Signer> {
         Box::new(self)
@@ -246,6 +476,63 @@ mod tests {
         store.secret_key("blunder".to_owned().into()).unwrap_err(); // Wrong passphrase.
     }
 
+    #[test]
+    fn test_kdf_params() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = Keystore::new(&tmp.path()).with_kdf(KdfParams { rounds: 4 });
+        let public = store.init("test", "hunter".to_owned()).unwrap();
+
+        assert_eq!(store.kdf_params().unwrap(), Some(KdfParams { rounds: 4 }));
+
+        // Keys can be read regardless of the configured parameters.
+        let store = Keystore::new(&tmp.path());
+        let secret = store
+            .secret_key("hunter".to_owned().into())
+            .unwrap()
+            .unwrap();
+        assert_eq!(PublicKey::from(secret.public_key()), public);
+
+        // Upgrade the key parameters.
+        let store = store.with_kdf(KdfParams { rounds: 8 });
+        store.rekey("hunter".to_owned().into()).unwrap();
+        assert_eq!(store.kdf_params().unwrap(), Some(KdfParams { rounds: 8 }));
+        assert!(store
+            .secret_key("hunter".to_owned().into())
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_devices() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = Keystore::new(&tmp.path()).with_kdf(KdfParams { rounds: 1 });
+        let main = store.init("test", "hunter".to_owned()).unwrap();
+        let laptop = store.init_device("laptop", "hunter".to_owned()).unwrap();
+        let phone = store.init_device("phone", "hunter".to_owned()).unwrap();
+
+        assert_ne!(main, laptop);
+        assert_eq!(
+            store.devices().unwrap(),
+            vec![("laptop".to_owned(), laptop), ("phone".to_owned(), phone)]
+        );
+        assert!(store.init_device("laptop", "hunter".to_owned()).is_err());
+        assert!(store
+            .init_device("../radicle", "hunter".to_owned())
+            .is_err());
+
+        let signer =
+            MemorySigner::load_device(&store, "phone", "hunter".to_owned().into()).unwrap();
+        assert_eq!(*signer.public_key(), phone);
+
+        assert!(store.remove_device("phone").unwrap());
+        assert!(!store.remove_device("phone").unwrap());
+        assert_eq!(
+            store.devices().unwrap(),
+            vec![("laptop".to_owned(), laptop)]
+        );
+        assert_eq!(store.public_key().unwrap(), Some(main));
+    }
+
     #[test]
     fn test_signer() {
         let tmp = tempfile::tempdir().unwrap();
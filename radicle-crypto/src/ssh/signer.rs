@@ -0,0 +1,145 @@
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::ssh::agent::{self, Agent, AgentSigner};
+use crate::ssh::keystore::{self, Keystore, MemorySigner, MemorySignerError, Passphrase};
+use crate::{PublicKey, Signature, Signer, SignerError};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Keystore(#[from] keystore::Error),
+    #[error(transparent)]
+    MemorySigner(#[from] MemorySignerError),
+    #[error("key not found in '{0}'")]
+    NotFound(PathBuf),
+    #[error("key `{0}` is not registered with ssh-agent, and no passphrase was provided")]
+    NoSigner(PublicKey),
+}
+
+/// A [`Signer`] for the keystore key.
+///
+/// Uses `ssh-agent` if it holds the key, and otherwise falls back to unsealing the
+/// key from the keystore.
+pub enum SshSigner {
+    /// Signs via `ssh-agent`.
+    Agent(AgentSigner),
+    /// Signs with the unsealed keystore key.
+    Memory(MemorySigner),
+}
+
+impl SshSigner {
+    /// Load a signer for the keystore key.
+    ///
+    /// If `ssh-agent` is running and holds the key, it is used. Otherwise, `passphrase` is
+    /// called to unseal the key from the keystore.
+    pub fn load(
+        keystore: &Keystore,
+        passphrase: impl FnOnce() -> Option<Passphrase>,
+    ) -> Result<Self, Error> {
+        Self::load_with(keystore, Agent::connect(), passphrase)
+    }
+
+    /// Like [`SshSigner::load`], but uses the `ssh-agent` listening on the given socket,
+    /// instead of the one set in `SSH_AUTH_SOCK`.
+    pub fn load_from<P: AsRef<Path> + Send>(
+        keystore: &Keystore,
+        socket: P,
+        passphrase: impl FnOnce() -> Option<Passphrase>,
+    ) -> Result<Self, Error> {
+        Self::load_with(keystore, Agent::connect_to(socket), passphrase)
+    }
+
+    fn load_with(
+        keystore: &Keystore,
+        agent: Result<Agent, agent::Error>,
+        passphrase: impl FnOnce() -> Option<Passphrase>,
+    ) -> Result<Self, Error> {
+        let public = keystore
+            .public_key()?
+            .ok_or_else(|| Error::NotFound(keystore.path().to_path_buf()))?;
+
+        // Any error talking to the agent is treated as the key not being available there.
+        if let Some(signer) = agent
+            .ok()
+            .and_then(|agent| agent.select(&public).ok().flatten())
+        {
+            return Ok(Self::Agent(signer));
+        }
+
+        match passphrase() {
+            Some(passphrase) => Ok(Self::Memory(MemorySigner::load(keystore, passphrase)?)),
+            None => Err(Error::NoSigner(public)),
+        }
+    }
+
+    /// Call the given function before each signature request sent to `ssh-agent`.
+    /// Has no effect if the key was unsealed from the keystore.
+    ///
+    /// See [`AgentSigner::with_confirmation`].
+    pub fn with_confirmation(self, confirm: impl Fn(&PublicKey) + Send + Sync + 'static) -> Self {
+        match self {
+            Self::Agent(signer) => Self::Agent(signer.with_confirmation(confirm)),
+            Self::Memory(signer) => Self::Memory(signer),
+        }
+    }
+
+    /// Whether signing goes through `ssh-agent`.
+    pub fn is_agent(&self) -> bool {
+        matches!(self, Self::Agent(_))
+    }
+
+    /// Box this signer into a [`Signer`].
+    pub fn boxed(self) -> Box<dyn Signer> {
+        Box::new(self)
+    }
+}
+
+impl Signer for SshSigner {
+    fn public_key(&self) -> &PublicKey {
+        match self {
+            Self::Agent(signer) => signer.public_key(),
+            Self::Memory(signer) => signer.public_key(),
+        }
+    }
+
+    fn sign(&self, msg: &[u8]) -> Signature {
+        match self {
+            Self::Agent(signer) => signer.sign(msg),
+            Self::Memory(signer) => signer.sign(msg),
+        }
+    }
+
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature, SignerError> {
+        match self {
+            Self::Agent(signer) => signer.try_sign(msg),
+            Self::Memory(signer) => signer.try_sign(msg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback_to_keystore() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = Keystore::new(&tmp.path());
+        let public = store.init("test", "hunter".to_owned()).unwrap();
+
+        // No agent is listening on this socket.
+        let socket = tmp.path().join("agent.sock");
+
+        let signer =
+            SshSigner::load_from(&store, &socket, || Some("hunter".to_owned().into())).unwrap();
+        assert!(!signer.is_agent());
+        assert_eq!(*signer.public_key(), public);
+
+        let err = SshSigner::load_from(&store, &socket, || None)
+            .err()
+            .unwrap();
+        assert!(matches!(err, Error::NoSigner(pk) if pk == public));
+    }
+}
@@ -0,0 +1,812 @@
+//! Threshold Ed25519 signatures, based on FROST.
+//!
+//! Allows `t` out of `n` delegates to produce a single Ed25519 signature under a shared
+//! group key, instead of collecting `t` independent signatures. The resulting signature
+//! is indistinguishable from a regular Ed25519 signature, and verifies under the group
+//! [`PublicKey`].
+//!
+//! Key generation is distributed, and happens in two rounds:
+//!
+//! 1. Each participant calls [`keygen::begin`], and broadcasts the resulting
+//!    [`keygen::Round1Package`] to all other participants.
+//! 2. Once all round 1 packages are received, each participant calls
+//!    [`keygen::Round1Secret::shares`], and sends each [`keygen::SecretShare`] privately to the
+//!    participant it is addressed to.
+//! 3. Once all shares addressed to it are received, each participant calls
+//!    [`keygen::Round2Secret::finish`] to obtain its [`KeyPackage`].
+//!
+//! Signing also happens in two rounds:
+//!
+//! 1. Each signer calls [`KeyPackage::commit`], keeps the [`SigningNonces`] secret, and
+//!    sends the [`SigningCommitments`] to the coordinator.
+//! 2. The coordinator builds a [`SigningPackage`] out of at least `t` commitments and
+//!    the message, and sends it to the signers, who each call [`KeyPackage::sign`].
+//!
+//! Finally, the coordinator combines the [`SignatureShare`]s with
+//! [`PublicKeyPackage::aggregate`].
+use std::collections::{BTreeMap, BTreeSet};
+
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha512};
+use thiserror::Error;
+use zeroize::Zeroize;
+
+use crate::{PublicKey, Seed, Signature};
+
+/// Domain separation prefix for all non-signature hashes.
+const CONTEXT: &[u8] = b"radicle-frost-ed25519-v1";
+
+/// Identifies a participant. Must be non-zero and unique within a group.
+pub type ParticipantId = u16;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum Error {
+    #[error("invalid threshold parameters: {0}")]
+    InvalidParams(&'static str),
+    #[error("invalid participant `{0}`")]
+    InvalidParticipant(ParticipantId),
+    #[error("missing data from participant `{0}`")]
+    Missing(ParticipantId),
+    #[error("invalid proof of knowledge from participant `{0}`")]
+    InvalidProof(ParticipantId),
+    #[error("invalid secret share from participant `{0}`")]
+    InvalidShare(ParticipantId),
+    #[error("invalid signature share from participant `{0}`")]
+    InvalidSignatureShare(ParticipantId),
+    #[error("not enough signers: {signers} out of a threshold of {threshold}")]
+    NotEnoughSigners { signers: usize, threshold: u16 },
+    #[error("invalid curve point encoding")]
+    InvalidPoint,
+    #[error("invalid scalar encoding")]
+    InvalidScalar,
+    #[error("aggregate signature is invalid")]
+    InvalidSignature,
+}
+
+/// Threshold parameters: `threshold` out of `participants`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Params {
+    pub threshold: u16,
+    pub participants: u16,
+}
+
+impl Params {
+    pub fn new(threshold: u16, participants: u16) -> Result<Self, Error> {
+        if threshold == 0 {
+            return Err(Error::InvalidParams("threshold must be at least one"));
+        }
+        if threshold > participants {
+            return Err(Error::InvalidParams(
+                "threshold must not exceed the number of participants",
+            ));
+        }
+        Ok(Self {
+            threshold,
+            participants,
+        })
+    }
+
+    fn validate(&self, id: ParticipantId) -> Result<(), Error> {
+        if id == 0 || id > self.participants {
+            return Err(Error::InvalidParticipant(id));
+        }
+        Ok(())
+    }
+}
+
+/// A compressed curve point, ie. a public value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Point([u8; 32]);
+
+impl Point {
+    fn decode(&self) -> Result<EdwardsPoint, Error> {
+        let point = CompressedEdwardsY(self.0)
+            .decompress()
+            .ok_or(Error::InvalidPoint)?;
+        if point.is_small_order() {
+            return Err(Error::InvalidPoint);
+        }
+        Ok(point)
+    }
+}
+
+impl From<EdwardsPoint> for Point {
+    fn from(point: EdwardsPoint) -> Self {
+        Self(point.compress().to_bytes())
+    }
+}
+
+/// An encoded scalar.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+struct EncodedScalar([u8; 32]);
+
+impl EncodedScalar {
+    fn decode(&self) -> Result<Scalar, Error> {
+        Scalar::from_canonical_bytes(self.0).ok_or(Error::InvalidScalar)
+    }
+}
+
+impl From<Scalar> for EncodedScalar {
+    fn from(scalar: Scalar) -> Self {
+        Self(scalar.to_bytes())
+    }
+}
+
+/// Distributed key generation.
+pub mod keygen {
+    use super::*;
+
+    /// Public output of the first key generation round, to be broadcast to all participants.
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct Round1Package {
+        pub id: ParticipantId,
+        /// Commitments to the coefficients of the participant's secret polynomial.
+        commitments: Vec<Point>,
+        /// Proof of knowledge of the participant's secret, ie. the first coefficient.
+        proof: (Point, EncodedScalar),
+    }
+
+    impl Round1Package {
+        pub(super) fn commitments(&self) -> &[Point] {
+            &self.commitments
+        }
+    }
+
+    /// Secret state kept by a participant between the first and second rounds.
+    pub struct Round1Secret {
+        id: ParticipantId,
+        params: Params,
+        coefficients: Vec<Scalar>,
+    }
+
+    impl Drop for Round1Secret {
+        fn drop(&mut self) {
+            self.coefficients.zeroize();
+        }
+    }
+
+    /// Secret share of a participant's polynomial, addressed to a single other participant.
+    /// Must be sent over a private channel.
+    #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct SecretShare {
+        pub from: ParticipantId,
+        pub to: ParticipantId,
+        value: EncodedScalar,
+    }
+
+    /// Secret state kept by a participant between the second round and the end of key generation.
+    pub struct Round2Secret {
+        id: ParticipantId,
+        params: Params,
+        own: Scalar,
+        packages: BTreeMap<ParticipantId, Round1Package>,
+    }
+
+    impl Drop for Round2Secret {
+        fn drop(&mut self) {
+            self.own.zeroize();
+        }
+    }
+
+    /// Begin key generation as the given participant.
+    ///
+    /// The `context` should be unique to this key generation session, eg. it could include
+    /// the repository identifier, and is used to prevent replay of proofs.
+    pub fn begin(
+        id: ParticipantId,
+        params: Params,
+        context: &[u8],
+    ) -> Result<(Round1Secret, Round1Package), Error> {
+        params.validate(id)?;
+
+        let coefficients = (0..params.threshold)
+            .map(|_| random_scalar(&[]))
+            .collect::<Vec<_>>();
+        let commitments = coefficients
+            .iter()
+            .map(|a| ED25519_BASEPOINT_POINT * a)
+            .collect::<Vec<_>>();
+
+        let k = random_scalar(&coefficients[0].to_bytes());
+        let r = ED25519_BASEPOINT_POINT * k;
+        let c = proof_challenge(id, context, &commitments[0], &r);
+        let mu = k + coefficients[0] * c;
+
+        let package = Round1Package {
+            id,
+            commitments: commitments.into_iter().map(Point::from).collect(),
+            proof: (r.into(), mu.into()),
+        };
+        let secret = Round1Secret {
+            id,
+            params,
+            coefficients,
+        };
+        Ok((secret, package))
+    }
+
+    impl Round1Secret {
+        /// Verify the other participants' round 1 packages, and compute the secret shares to
+        /// be sent to each of them.
+        pub fn shares(
+            self,
+            packages: impl IntoIterator<Item = Round1Package>,
+            context: &[u8],
+        ) -> Result<(Round2Secret, Vec<SecretShare>), Error> {
+            let mut verified = BTreeMap::new();
+
+            for pkg in packages {
+                if pkg.id == self.id {
+                    continue;
+                }
+                self.params.validate(pkg.id)?;
+
+                if pkg.commitments.len() != self.params.threshold as usize {
+                    return Err(Error::InvalidProof(pkg.id));
+                }
+                let a0 = pkg.commitments[0].decode()?;
+                let r = pkg.proof.0.decode()?;
+                let mu = pkg.proof.1.decode()?;
+                let c = proof_challenge(pkg.id, context, &a0, &r);
+
+                if ED25519_BASEPOINT_POINT * mu - a0 * c != r {
+                    return Err(Error::InvalidProof(pkg.id));
+                }
+                verified.insert(pkg.id, pkg);
+            }
+            for id in 1..=self.params.participants {
+                if id != self.id && !verified.contains_key(&id) {
+                    return Err(Error::Missing(id));
+                }
+            }
+            verified.insert(self.id, self.package());
+
+            let shares = verified
+                .keys()
+                .filter(|to| **to != self.id)
+                .map(|to| SecretShare {
+                    from: self.id,
+                    to: *to,
+                    value: self.evaluate(*to).into(),
+                })
+                .collect();
+            let secret = Round2Secret {
+                id: self.id,
+                params: self.params,
+                own: self.evaluate(self.id),
+                packages: verified,
+            };
+            Ok((secret, shares))
+        }
+
+        /// Our own round 1 commitments. The proof is omitted, since it is never verified.
+        fn package(&self) -> Round1Package {
+            Round1Package {
+                id: self.id,
+                commitments: self
+                    .coefficients
+                    .iter()
+                    .map(|a| Point::from(ED25519_BASEPOINT_POINT * a))
+                    .collect(),
+                proof: (
+                    Point::from(EdwardsPoint::identity()),
+                    EncodedScalar::from(Scalar::zero()),
+                ),
+            }
+        }
+
+        /// Evaluate the secret polynomial at the given participant's index.
+        fn evaluate(&self, id: ParticipantId) -> Scalar {
+            let x = Scalar::from(id as u64);
+
+            self.coefficients
+                .iter()
+                .rev()
+                .fold(Scalar::zero(), |acc, a| acc * x + a)
+        }
+    }
+
+    impl Round2Secret {
+        /// Verify the secret shares addressed to us, and compute our long-lived key package.
+        pub fn finish(
+            self,
+            shares: impl IntoIterator<Item = SecretShare>,
+        ) -> Result<KeyPackage, Error> {
+            let mut secret = self.own;
+            let mut received = BTreeSet::from([self.id]);
+
+            for share in shares {
+                if share.to != self.id {
+                    return Err(Error::InvalidParticipant(share.to));
+                }
+                let pkg = self
+                    .packages
+                    .get(&share.from)
+                    .filter(|_| share.from != self.id)
+                    .ok_or(Error::InvalidParticipant(share.from))?;
+                let value = share.value.decode()?;
+
+                if ED25519_BASEPOINT_POINT * value != commitment_at(&pkg.commitments, self.id)? {
+                    return Err(Error::InvalidShare(share.from));
+                }
+                if received.insert(share.from) {
+                    secret += value;
+                }
+            }
+            if let Some(id) = self.packages.keys().find(|id| !received.contains(id)) {
+                return Err(Error::Missing(*id));
+            }
+
+            let public = PublicKeyPackage::derive(self.params, &self.packages)?;
+            let key = KeyPackage {
+                id: self.id,
+                secret: secret.into(),
+                public,
+            };
+            secret.zeroize();
+
+            // Sanity check that our secret share matches our public verification share.
+            if key.public.verifying_share(self.id)? != ED25519_BASEPOINT_POINT * key.secret()? {
+                return Err(Error::InvalidShare(self.id));
+            }
+            Ok(key)
+        }
+    }
+    /// Evaluate a commitment polynomial at the given participant's index, ie. compute
+    /// the public counterpart of the secret share.
+    pub(super) fn commitment_at(
+        commitments: &[Point],
+        id: ParticipantId,
+    ) -> Result<EdwardsPoint, Error> {
+        let x = Scalar::from(id as u64);
+        let mut result = EdwardsPoint::identity();
+
+        for c in commitments.iter().rev() {
+            result = result * x + c.decode()?;
+        }
+        Ok(result)
+    }
+
+    fn proof_challenge(
+        id: ParticipantId,
+        context: &[u8],
+        commitment: &EdwardsPoint,
+        r: &EdwardsPoint,
+    ) -> Scalar {
+        hash_to_scalar(&[
+            b"dkg",
+            &id.to_be_bytes(),
+            context,
+            commitment.compress().as_bytes(),
+            r.compress().as_bytes(),
+        ])
+    }
+}
+
+/// A participant's long-lived key material, output of key generation.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct KeyPackage {
+    pub id: ParticipantId,
+    secret: EncodedScalar,
+    pub public: PublicKeyPackage,
+}
+
+impl Drop for KeyPackage {
+    fn drop(&mut self) {
+        self.secret.0.zeroize();
+    }
+}
+
+impl KeyPackage {
+    /// The group public key.
+    pub fn group_key(&self) -> PublicKey {
+        self.public.group
+    }
+
+    /// Start a signing session, by generating single-use nonces and their commitments.
+    /// The nonces must be kept secret, and used for at most one signature.
+    pub fn commit(&self) -> Result<(SigningNonces, SigningCommitments), Error> {
+        let secret = self.secret()?;
+        let nonces = SigningNonces {
+            hiding: random_scalar(secret.as_bytes()),
+            binding: random_scalar(secret.as_bytes()),
+        };
+        let commitments = SigningCommitments {
+            id: self.id,
+            hiding: (ED25519_BASEPOINT_POINT * nonces.hiding).into(),
+            binding: (ED25519_BASEPOINT_POINT * nonces.binding).into(),
+        };
+        Ok((nonces, commitments))
+    }
+
+    /// Produce our signature share for the given signing package.
+    /// Consumes the nonces, which must have been committed to in the package.
+    pub fn sign(
+        &self,
+        nonces: SigningNonces,
+        package: &SigningPackage,
+    ) -> Result<SignatureShare, Error> {
+        let ours = package
+            .commitments
+            .get(&self.id)
+            .ok_or(Error::Missing(self.id))?;
+        if ours.hiding != Point::from(ED25519_BASEPOINT_POINT * nonces.hiding)
+            || ours.binding != Point::from(ED25519_BASEPOINT_POINT * nonces.binding)
+        {
+            return Err(Error::InvalidSignatureShare(self.id));
+        }
+        let session = package.session(self.public.group)?;
+        let rho = session.binding_factor(self.id)?;
+        let lambda = lagrange(self.id, package.commitments.keys())?;
+        let z = nonces.hiding + nonces.binding * rho + lambda * self.secret()? * session.challenge;
+
+        Ok(SignatureShare {
+            id: self.id,
+            value: z.into(),
+        })
+    }
+
+    fn secret(&self) -> Result<Scalar, Error> {
+        self.secret.decode()
+    }
+}
+
+/// Public key material of a threshold group, ie. the group key and each participant's
+/// verification share.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyPackage {
+    pub params: Params,
+    pub group: PublicKey,
+    shares: BTreeMap<ParticipantId, Point>,
+}
+
+impl PublicKeyPackage {
+    /// Derive the group's public key material from all participants' round 1 packages.
+    fn derive(
+        params: Params,
+        packages: &BTreeMap<ParticipantId, keygen::Round1Package>,
+    ) -> Result<Self, Error> {
+        let mut group = EdwardsPoint::identity();
+        let mut shares = BTreeMap::new();
+
+        for pkg in packages.values() {
+            group += pkg
+                .commitments()
+                .first()
+                .ok_or(Error::Missing(pkg.id))?
+                .decode()?;
+        }
+        for id in 1..=params.participants {
+            let mut share = EdwardsPoint::identity();
+            for pkg in packages.values() {
+                share += keygen::commitment_at(pkg.commitments(), id)?;
+            }
+            shares.insert(id, Point::from(share));
+        }
+
+        Ok(Self {
+            params,
+            group: PublicKey::from(group.compress().to_bytes()),
+            shares,
+        })
+    }
+
+    /// Combine signature shares into a single signature, verifying each share.
+    /// At least `threshold` shares are required.
+    pub fn aggregate(
+        &self,
+        package: &SigningPackage,
+        shares: impl IntoIterator<Item = SignatureShare>,
+    ) -> Result<Signature, Error> {
+        let signers = package.commitments.len();
+        if signers < self.params.threshold as usize {
+            return Err(Error::NotEnoughSigners {
+                signers,
+                threshold: self.params.threshold,
+            });
+        }
+        let shares = shares
+            .into_iter()
+            .map(|s| (s.id, s))
+            .collect::<BTreeMap<_, _>>();
+        let session = package.session(self.group)?;
+        let mut z = Scalar::zero();
+
+        for (id, commitments) in &package.commitments {
+            let share = shares.get(id).ok_or(Error::Missing(*id))?;
+            let value = share.value.decode()?;
+            let rho = session.binding_factor(*id)?;
+            let lambda = lagrange(*id, package.commitments.keys())?;
+            let r = commitments.hiding.decode()? + commitments.binding.decode()? * rho;
+
+            if ED25519_BASEPOINT_POINT * value
+                != r + self.verifying_share(*id)? * (lambda * session.challenge)
+            {
+                return Err(Error::InvalidSignatureShare(*id));
+            }
+            z += value;
+        }
+
+        let mut bytes = [0; 64];
+        bytes[..32].copy_from_slice(session.commitment.compress().as_bytes());
+        bytes[32..].copy_from_slice(z.as_bytes());
+
+        let signature = Signature::from(bytes);
+        self.group
+            .verify(&package.message, &signature)
+            .map_err(|_| Error::InvalidSignature)?;
+
+        Ok(signature)
+    }
+
+    fn verifying_share(&self, id: ParticipantId) -> Result<EdwardsPoint, Error> {
+        self.shares
+            .get(&id)
+            .ok_or(Error::InvalidParticipant(id))?
+            .decode()
+    }
+}
+
+/// Secret single-use nonces of a signer.
+pub struct SigningNonces {
+    hiding: Scalar,
+    binding: Scalar,
+}
+
+impl Drop for SigningNonces {
+    fn drop(&mut self) {
+        self.hiding.zeroize();
+        self.binding.zeroize();
+    }
+}
+
+/// Public commitments to a signer's nonces, sent to the coordinator.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningCommitments {
+    pub id: ParticipantId,
+    hiding: Point,
+    binding: Point,
+}
+
+/// A message to be signed, along with the commitments of the signers taking part.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningPackage {
+    message: Vec<u8>,
+    commitments: BTreeMap<ParticipantId, SigningCommitments>,
+}
+
+impl SigningPackage {
+    pub fn new(
+        message: impl Into<Vec<u8>>,
+        commitments: impl IntoIterator<Item = SigningCommitments>,
+    ) -> Self {
+        Self {
+            message: message.into(),
+            commitments: commitments.into_iter().map(|c| (c.id, c)).collect(),
+        }
+    }
+
+    /// The message being signed.
+    pub fn message(&self) -> &[u8] {
+        &self.message
+    }
+
+    /// The signers taking part.
+    pub fn signers(&self) -> impl Iterator<Item = &ParticipantId> {
+        self.commitments.keys()
+    }
+
+    /// Compute the binding factors, group commitment and challenge for this package.
+    fn session(&self, group: PublicKey) -> Result<Session, Error> {
+        let mut encoded = Vec::with_capacity(self.commitments.len() * 66);
+        for c in self.commitments.values() {
+            encoded.extend_from_slice(&c.id.to_be_bytes());
+            encoded.extend_from_slice(&c.hiding.0);
+            encoded.extend_from_slice(&c.binding.0);
+        }
+        let message = Sha512::digest(&self.message);
+        let mut factors = BTreeMap::new();
+        let mut commitment = EdwardsPoint::identity();
+
+        for c in self.commitments.values() {
+            let rho = hash_to_scalar(&[
+                b"rho",
+                &c.id.to_be_bytes(),
+                &**group,
+                message.as_slice(),
+                &encoded,
+            ]);
+            commitment += c.hiding.decode()? + c.binding.decode()? * rho;
+            factors.insert(c.id, rho);
+        }
+
+        // The challenge is computed exactly as in Ed25519, so that the aggregate signature
+        // verifies as a regular Ed25519 signature.
+        let mut hasher = Sha512::new();
+        hasher.update(commitment.compress().as_bytes());
+        hasher.update(&**group);
+        hasher.update(&self.message);
+        let challenge = reduce(hasher);
+
+        Ok(Session {
+            factors,
+            commitment,
+            challenge,
+        })
+    }
+}
+
+/// A signer's share of the aggregate signature.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureShare {
+    pub id: ParticipantId,
+    value: EncodedScalar,
+}
+
+struct Session {
+    factors: BTreeMap<ParticipantId, Scalar>,
+    commitment: EdwardsPoint,
+    challenge: Scalar,
+}
+
+impl Session {
+    fn binding_factor(&self, id: ParticipantId) -> Result<Scalar, Error> {
+        self.factors.get(&id).copied().ok_or(Error::Missing(id))
+    }
+}
+
+/// Compute the Lagrange coefficient of the given participant, for interpolation at zero
+/// over the given set of participants.
+fn lagrange<'a>(
+    id: ParticipantId,
+    signers: impl IntoIterator<Item = &'a ParticipantId>,
+) -> Result<Scalar, Error> {
+    let x = Scalar::from(id as u64);
+    let mut num = Scalar::one();
+    let mut den = Scalar::one();
+
+    for j in signers {
+        if *j == id {
+            continue;
+        }
+        if *j == 0 {
+            return Err(Error::InvalidParticipant(*j));
+        }
+        let xj = Scalar::from(*j as u64);
+        num *= xj;
+        den *= xj - x;
+    }
+    Ok(num * den.invert())
+}
+
+fn hash_to_scalar(parts: &[&[u8]]) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(CONTEXT);
+    for part in parts {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+    reduce(hasher)
+}
+
+/// Reduce a SHA-512 digest to a scalar.
+fn reduce(hasher: Sha512) -> Scalar {
+    let mut wide = [0; 64];
+    wide.copy_from_slice(&hasher.finalize());
+
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+/// Generate a random scalar. The given secret, if any, is mixed in to hedge against a weak
+/// source of randomness.
+fn random_scalar(secret: &[u8]) -> Scalar {
+    let seed = Seed::generate();
+    hash_to_scalar(&[b"random", &seed[..], secret])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::keygen::*;
+    use super::*;
+
+    fn keygen(threshold: u16, participants: u16) -> Vec<KeyPackage> {
+        let params = Params::new(threshold, participants).unwrap();
+        let context = b"test";
+        let (secrets, packages): (Vec<_>, Vec<_>) = (1..=participants)
+            .map(|id| begin(id, params, context).unwrap())
+            .unzip();
+        let (secrets, shares): (Vec<_>, Vec<_>) = secrets
+            .into_iter()
+            .map(|s| s.shares(packages.clone(), context).unwrap())
+            .unzip();
+        let shares = shares.into_iter().flatten().collect::<Vec<_>>();
+
+        secrets
+            .into_iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let id = i as u16 + 1;
+                s.finish(shares.iter().filter(|s| s.to == id).cloned())
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    fn sign(keys: &[&KeyPackage], msg: &[u8]) -> Result<Signature, Error> {
+        let (nonces, commitments): (Vec<_>, Vec<_>) =
+            keys.iter().map(|k| k.commit().unwrap()).unzip();
+        let package = SigningPackage::new(msg, commitments);
+        let shares = keys
+            .iter()
+            .zip(nonces)
+            .map(|(k, n)| k.sign(n, &package).unwrap())
+            .collect::<Vec<_>>();
+
+        keys[0].public.aggregate(&package, shares)
+    }
+
+    #[test]
+    fn test_threshold_sign() {
+        let keys = keygen(2, 3);
+        let group = keys[0].group_key();
+
+        assert!(keys.iter().all(|k| k.group_key() == group));
+
+        for signers in [[0, 1], [1, 2], [0, 2]] {
+            let signers = signers.map(|i| &keys[i]);
+            let sig = sign(&signers, b"hello").unwrap();
+
+            assert!(group.verify(b"hello", &sig).is_ok());
+            assert!(group.verify(b"olleh", &sig).is_err());
+        }
+        let sig = sign(&keys.iter().collect::<Vec<_>>(), b"hello").unwrap();
+        assert!(group.verify(b"hello", &sig).is_ok());
+    }
+
+    #[test]
+    fn test_not_enough_signers() {
+        let keys = keygen(2, 3);
+        let err = sign(&[&keys[0]], b"hello").unwrap_err();
+
+        assert_eq!(
+            err,
+            Error::NotEnoughSigners {
+                signers: 1,
+                threshold: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_signature_share() {
+        let keys = keygen(2, 2);
+        let (n1, c1) = keys[0].commit().unwrap();
+        let (n2, c2) = keys[1].commit().unwrap();
+        let package = SigningPackage::new(b"hello".to_vec(), [c1, c2]);
+        let s1 = keys[0].sign(n1, &package).unwrap();
+        let mut s2 = keys[1].sign(n2, &package).unwrap();
+
+        s2.value = EncodedScalar::from(s2.value.decode().unwrap() + Scalar::one());
+
+        assert_eq!(
+            keys[0].public.aggregate(&package, [s1, s2]),
+            Err(Error::InvalidSignatureShare(2))
+        );
+    }
+
+    #[test]
+    fn test_invalid_proof() {
+        let params = Params::new(2, 2).unwrap();
+        let (s1, _) = begin(1, params, b"a").unwrap();
+        let (_, p2) = begin(2, params, b"b").unwrap();
+
+        assert_eq!(s1.shares([p2], b"a").err(), Some(Error::InvalidProof(2)));
+    }
+}
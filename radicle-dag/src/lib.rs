@@ -1,6 +1,7 @@
 use std::{
     borrow::Borrow,
-    collections::{HashMap, HashSet},
+    cmp::Ordering,
+    collections::{hash_map, HashMap, HashSet},
     fmt,
     hash::Hash,
     ops::{Deref, Index},
@@ -108,6 +109,40 @@ impl<K: Eq + Copy + Hash, V> Dag<K, V> {
         self.graph.get(key)
     }
 
+    /// Get a mutable reference to a node's value. Edges can only be changed through
+    /// the graph, so that they remain consistent.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.graph.get_mut(key).map(|n| &mut n.value)
+    }
+
+    /// Check whether the graph contains a node.
+    pub fn contains(&self, key: &K) -> bool {
+        self.graph.contains_key(key)
+    }
+
+    /// Iterate over all nodes in the graph, in arbitrary order.
+    pub fn iter(&self) -> hash_map::Iter<'_, K, Node<K, V>> {
+        self.graph.iter()
+    }
+
+    /// Iterate over all nodes in the graph, in arbitrary order, with mutable access
+    /// to their values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.graph.iter_mut(),
+        }
+    }
+
+    /// Iterate over the keys of all nodes in the graph, in arbitrary order.
+    pub fn keys(&self) -> hash_map::Keys<'_, K, Node<K, V>> {
+        self.graph.keys()
+    }
+
+    /// Iterate over the values of all nodes in the graph, in arbitrary order.
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.graph.values().map(|n| &n.value)
+    }
+
     /// Check whether there is a dependency between two nodes.
     pub fn has_dependency(&self, from: &K, to: &K) -> bool {
         self.graph
@@ -116,6 +151,21 @@ impl<K: Eq + Copy + Hash, V> Dag<K, V> {
             .unwrap_or_default()
     }
 
+    /// Get the direct dependencies of a node, ie. the nodes it depends on.
+    pub fn dependencies<'a>(
+        &'a self,
+        key: &K,
+    ) -> impl Iterator<Item = (&'a K, &'a Node<K, V>)> + 'a {
+        self.edges(key, |n| &n.dependencies)
+    }
+
+    /// Get the direct dependents of a node, ie. the nodes depending on it.
+    ///
+    /// Dependents are indexed on insertion, so this doesn't require a scan of the graph.
+    pub fn dependents<'a>(&'a self, key: &K) -> impl Iterator<Item = (&'a K, &'a Node<K, V>)> + 'a {
+        self.edges(key, |n| &n.dependents)
+    }
+
     /// Get the graph's root nodes, ie. nodes which don't depend on other nodes.
     pub fn roots(&self) -> impl Iterator<Item = (&K, &Node<K, V>)> + '_ {
         self.roots
@@ -130,19 +180,207 @@ impl<K: Eq + Copy + Hash, V> Dag<K, V> {
             .filter_map(|k| self.graph.get(k).map(|n| (k, n)))
     }
 
+    /// Iterate over the ancestors of a node, ie. all nodes it depends on, directly or
+    /// transitively, in depth-first order. The node itself is not included.
+    pub fn ancestors<'a>(&'a self, key: &K) -> impl Iterator<Item = (&'a K, &'a Node<K, V>)> + 'a {
+        Walk::new(self, key, |n| &n.dependencies)
+    }
+
+    /// Iterate over the descendants of a node, ie. all nodes depending on it, directly or
+    /// transitively, in depth-first order. The node itself is not included.
+    pub fn descendants<'a>(
+        &'a self,
+        key: &K,
+    ) -> impl Iterator<Item = (&'a K, &'a Node<K, V>)> + 'a {
+        Walk::new(self, key, |n| &n.dependents)
+    }
+
+    /// Retain only the nodes for which the predicate returns `true`. Edges to removed nodes
+    /// are dropped, and roots and tips are recomputed.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        self.graph.retain(|k, n| f(k, &n.value));
+        self.prune();
+    }
+
+    /// Return the sub-graph made of the given nodes and their descendants. The given nodes
+    /// that are in the graph become the roots of the sub-graph.
+    pub fn subgraph(&self, roots: impl IntoIterator<Item = K>) -> Self
+    where
+        V: Clone,
+    {
+        let mut keys = HashSet::new();
+
+        for root in roots {
+            if self.graph.contains_key(&root) {
+                keys.extend(self.descendants(&root).map(|(k, _)| *k));
+                keys.insert(root);
+            }
+        }
+        let mut dag = Self {
+            graph: keys
+                .into_iter()
+                .filter_map(|k| self.graph.get(&k).map(|n| (k, n.clone())))
+                .collect(),
+            tips: HashSet::new(),
+            roots: HashSet::new(),
+        };
+        dag.prune();
+        dag
+    }
+
+    /// Iterate over the nodes adjacent to the given node, following the given edges.
+    fn edges<'a>(
+        &'a self,
+        key: &K,
+        edges: fn(&Node<K, V>) -> &HashSet<K>,
+    ) -> impl Iterator<Item = (&'a K, &'a Node<K, V>)> + 'a {
+        self.graph
+            .get(key)
+            .into_iter()
+            .flat_map(edges)
+            .filter_map(|k| self.graph.get_key_value(k))
+    }
+
+    /// Remove redundant edges, ie. dependencies which are also transitive dependencies
+    /// through another path. The reachability of every node is preserved.
+    ///
+    /// The graph is assumed to be acyclic.
+    pub fn transitive_reduction(&mut self) {
+        let mut redundant = Vec::new();
+
+        for (key, node) in &self.graph {
+            if node.dependencies.len() < 2 {
+                continue;
+            }
+            let indirect = node
+                .dependencies
+                .iter()
+                .flat_map(|d| self.ancestors(d))
+                .map(|(k, _)| *k)
+                .collect::<HashSet<_>>();
+
+            redundant.extend(
+                node.dependencies
+                    .iter()
+                    .filter(|d| indirect.contains(d))
+                    .map(|d| (*key, *d)),
+            );
+        }
+        // Since there is another path between the nodes of a redundant edge, removing it
+        // doesn't change the roots or tips of the graph.
+        for (from, to) in redundant {
+            if let Some(node) = self.graph.get_mut(&from) {
+                node.dependencies.remove(&to);
+            }
+            if let Some(node) = self.graph.get_mut(&to) {
+                node.dependents.remove(&from);
+            }
+        }
+    }
+
+    /// Drop edges to nodes that aren't in the graph, and recompute roots and tips.
+    fn prune(&mut self) {
+        let keys = self.graph.keys().copied().collect::<HashSet<_>>();
+
+        self.tips.clear();
+        self.roots.clear();
+
+        for (k, node) in self.graph.iter_mut() {
+            node.dependencies.retain(|d| keys.contains(d));
+            node.dependents.retain(|d| keys.contains(d));
+
+            if node.dependencies.is_empty() {
+                self.roots.insert(*k);
+            }
+            if node.dependents.is_empty() {
+                self.tips.insert(*k);
+            }
+        }
+    }
+
+    /// Render the graph in Graphviz DOT format, for debugging. Nodes are identified by their
+    /// key, and labeled with the output of `label`. Edges point from a node to its
+    /// dependencies.
+    pub fn to_dot<F, L>(&self, mut label: F) -> String
+    where
+        K: fmt::Display,
+        F: FnMut(&K, &V) -> L,
+        L: fmt::Display,
+    {
+        let escape = |s: String| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut nodes = self.graph.iter().collect::<Vec<_>>();
+        let mut out = String::from("digraph {\n");
+
+        // Sort nodes, so that the output is stable.
+        nodes.sort_by_cached_key(|(k, _)| k.to_string());
+
+        for (key, node) in &nodes {
+            out.push_str(&format!(
+                "    \"{}\" [label=\"{}\"];\n",
+                escape(key.to_string()),
+                escape(label(*key, &node.value).to_string())
+            ));
+        }
+        for (key, node) in &nodes {
+            let mut deps = node.dependencies.iter().collect::<Vec<_>>();
+            deps.sort_by_cached_key(|k| k.to_string());
+
+            for dep in deps {
+                out.push_str(&format!(
+                    "    \"{}\" -> \"{}\";\n",
+                    escape(key.to_string()),
+                    escape(dep.to_string())
+                ));
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
     /// Merge a DAG into this one.
     ///
     /// If a key exists in both graphs, its value is set to that of the other graph.
     pub fn merge(&mut self, other: Self) {
-        for k in other.tips.into_iter() {
-            self.tips.insert(k);
-        }
-        for k in other.roots.into_iter() {
-            self.roots.insert(k);
-        }
-        for (k, v) in other.graph.into_iter() {
-            self.graph.insert(k, v);
+        self.merge_with(other, |value, other| *value = other);
+    }
+
+    /// Merge a DAG into this one, taking the union of their nodes and edges.
+    ///
+    /// If a key exists in both graphs, the values are merged with the given function,
+    /// which is passed this graph's value and the other graph's value.
+    pub fn merge_with<F>(&mut self, other: Self, mut f: F)
+    where
+        F: FnMut(&mut V, V),
+    {
+        for (k, theirs) in other.graph.into_iter() {
+            match self.graph.get_mut(&k) {
+                Some(ours) => {
+                    ours.dependencies.extend(theirs.dependencies);
+                    ours.dependents.extend(theirs.dependents);
+
+                    f(&mut ours.value, theirs.value);
+                }
+                None => {
+                    self.graph.insert(k, theirs);
+                }
+            }
         }
+        // A root or tip of either graph may have gained edges from the other graph.
+        self.roots = self
+            .graph
+            .iter()
+            .filter(|(_, n)| n.dependencies.is_empty())
+            .map(|(k, _)| *k)
+            .collect();
+        self.tips = self
+            .graph
+            .iter()
+            .filter(|(_, n)| n.dependents.is_empty())
+            .map(|(k, _)| *k)
+            .collect();
     }
 
     /// Return a topological ordering of the graph's nodes, using the given RNG.
@@ -150,33 +388,300 @@ impl<K: Eq + Copy + Hash, V> Dag<K, V> {
     ///
     /// Calling this function over and over will eventually yield all possible orderings.
     pub fn sorted(&self, rng: fastrand::Rng) -> Vec<K> {
-        let mut order = Vec::new(); // Stores the topological order.
-        let mut visited = HashSet::new(); // Nodes that have been visited.
-        let mut keys = self.graph.keys().collect::<Vec<_>>();
+        let mut order = Vec::with_capacity(self.graph.len());
+        let mut pending = self.pending();
+        let mut ready = pending
+            .iter()
+            .filter(|(_, n)| **n == 0)
+            .map(|(k, _)| *k)
+            .collect::<Vec<_>>();
+
+        // Kahn's algorithm, picking the next node at random among the nodes whose
+        // dependencies have all been ordered.
+        while !ready.is_empty() {
+            let key = ready.swap_remove(rng.usize(..ready.len()));
 
-        rng.shuffle(&mut keys);
+            order.push(key);
+            pending.remove(&key);
+
+            for dependent in &self.graph[&key].dependents {
+                if let Some(n) = pending.get_mut(dependent) {
+                    *n -= 1;
+                    if *n == 0 {
+                        ready.push(*dependent);
+                    }
+                }
+            }
+        }
+
+        if !pending.is_empty() {
+            let mut rest = pending.into_keys().collect::<Vec<_>>();
+            rng.shuffle(&mut rest);
+            order.extend(rest);
+        }
+        order
+    }
+
+    /// Return a topological ordering of the graph's nodes, breaking ties with the given
+    /// comparator. Among the nodes whose dependencies have all been ordered, the least
+    /// according to `cmp` comes first, which makes the ordering deterministic.
+    ///
+    /// Nodes that are part of a cycle are ordered last, according to `cmp`.
+    pub fn sorted_by<F>(&self, mut cmp: F) -> Vec<K>
+    where
+        F: FnMut(&K, &K) -> Ordering,
+    {
+        let mut order = Vec::with_capacity(self.graph.len());
+        let mut pending = self.pending();
+        // Nodes ready to be ordered, greatest first, so that the least is popped first.
+        let mut ready = pending
+            .iter()
+            .filter(|(_, n)| **n == 0)
+            .map(|(k, _)| *k)
+            .collect::<Vec<_>>();
+
+        ready.sort_by(|a, b| cmp(b, a));
+
+        while let Some(key) = ready.pop() {
+            order.push(key);
+            pending.remove(&key);
+
+            for dependent in &self.graph[&key].dependents {
+                let Some(n) = pending.get_mut(dependent) else {
+                    continue;
+                };
+                *n -= 1;
+
+                if *n == 0 {
+                    let ix = ready
+                        .binary_search_by(|k| cmp(dependent, k))
+                        .unwrap_or_else(|ix| ix);
+                    ready.insert(ix, *dependent);
+                }
+            }
+        }
 
-        for node in keys {
-            self.visit(node, &mut visited, &mut order);
+        if !pending.is_empty() {
+            let mut rest = pending.into_keys().collect::<Vec<_>>();
+            rest.sort_by(&mut cmp);
+            order.extend(rest);
         }
         order
     }
 
-    /// Add nodes recursively to the topological order, starting from the given node.
-    fn visit(&self, key: &K, visited: &mut HashSet<K>, order: &mut Vec<K>) {
-        if visited.contains(key) {
-            return;
+    /// Compute the depth, or generation number, of every node, ie. the length of the
+    /// longest path from a root to the node. Roots have a depth of zero.
+    ///
+    /// If a node is an ancestor of another, its depth is strictly smaller, which allows
+    /// ruling out ancestry without traversing the graph. Nodes that are part of a cycle,
+    /// or depend on one, are not included.
+    pub fn depths(&self) -> HashMap<K, usize> {
+        let mut depths = HashMap::with_capacity(self.graph.len());
+        let mut pending = self.pending();
+        let mut ready = pending
+            .iter()
+            .filter(|(_, n)| **n == 0)
+            .map(|(k, _)| *k)
+            .collect::<Vec<_>>();
+
+        while let Some(key) = ready.pop() {
+            let node = &self.graph[&key];
+            let depth = node
+                .dependencies
+                .iter()
+                .filter_map(|d| depths.get(d))
+                .map(|d| d + 1)
+                .max()
+                .unwrap_or(0);
+
+            depths.insert(key, depth);
+
+            for dependent in &node.dependents {
+                if let Some(n) = pending.get_mut(dependent) {
+                    *n -= 1;
+                    if *n == 0 {
+                        ready.push(*dependent);
+                    }
+                }
+            }
+        }
+        depths
+    }
+
+    /// Number of dependencies of each node that haven't been ordered yet, for use in
+    /// topological sorts.
+    ///
+    /// Counts are derived from the `dependents` edges, since these are the edges that
+    /// are followed to decrement them as nodes are ordered. Deriving them from the
+    /// `dependencies` edges instead would underflow if both sets ever disagreed.
+    fn pending(&self) -> HashMap<K, usize> {
+        let mut pending = self
+            .graph
+            .keys()
+            .map(|k| (*k, 0))
+            .collect::<HashMap<_, _>>();
+
+        for node in self.graph.values() {
+            for dependent in &node.dependents {
+                if let Some(n) = pending.get_mut(dependent) {
+                    *n += 1;
+                }
+            }
         }
-        visited.insert(*key);
+        pending
+    }
+}
+
+#[cfg(feature = "serde")]
+mod encoding {
+    use std::collections::HashSet;
+    use std::hash::Hash;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Dag;
+
+    /// A serialized node. Only dependencies are encoded, since dependents, roots and tips
+    /// can be derived from them.
+    #[derive(Serialize, Deserialize)]
+    struct Node<K: Eq + Hash, V> {
+        key: K,
+        value: V,
+        dependencies: HashSet<K>,
+    }
+
+    #[derive(Serialize)]
+    struct NodeRef<'a, K: Eq + Hash, V> {
+        key: &'a K,
+        value: &'a V,
+        dependencies: &'a HashSet<K>,
+    }
+
+    /// A graph is encoded as a sequence of nodes.
+    impl<K: Eq + Hash + Serialize, V: Serialize> Serialize for Dag<K, V> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.graph.iter().map(|(key, node)| NodeRef {
+                key,
+                value: &node.value,
+                dependencies: &node.dependencies,
+            }))
+        }
+    }
+
+    impl<'de, K, V> Deserialize<'de> for Dag<K, V>
+    where
+        K: Eq + Copy + Hash + Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let nodes = Vec::<Node<K, V>>::deserialize(deserializer)?;
+            let mut dag = Dag::new();
+            let mut edges = Vec::new();
+
+            for node in nodes {
+                edges.extend(node.dependencies.into_iter().map(|to| (node.key, to)));
+                dag.node(node.key, node.value);
+            }
+            for (from, to) in edges {
+                if !dag.graph.contains_key(&to) {
+                    return Err(serde::de::Error::custom(
+                        "dependency on a node that is not in the graph",
+                    ));
+                }
+                dag.dependency(from, to);
+            }
+            Ok(dag)
+        }
+    }
+}
+
+/// Depth-first walk of the nodes reachable from a node, following the edges returned
+/// by `edges`.
+struct Walk<'a, K: Eq + Hash, V> {
+    dag: &'a Dag<K, V>,
+    edges: fn(&Node<K, V>) -> &HashSet<K>,
+    stack: Vec<&'a K>,
+    visited: HashSet<K>,
+}
+
+impl<'a, K: Eq + Copy + Hash, V> Walk<'a, K, V> {
+    fn new(dag: &'a Dag<K, V>, key: &K, edges: fn(&Node<K, V>) -> &HashSet<K>) -> Self {
+        let stack = dag
+            .graph
+            .get(key)
+            .map(|n| edges(n).iter().collect())
+            .unwrap_or_default();
+
+        Self {
+            dag,
+            edges,
+            stack,
+            visited: HashSet::from_iter([*key]),
+        }
+    }
+}
+
+impl<'a, K: Eq + Copy + Hash, V> Iterator for Walk<'a, K, V> {
+    type Item = (&'a K, &'a Node<K, V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(key) = self.stack.pop() {
+            if !self.visited.insert(*key) {
+                continue;
+            }
+            if let Some(node) = self.dag.graph.get(key) {
+                self.stack.extend((self.edges)(node).iter());
 
-        // Recursively visit all of the node's dependencies.
-        if let Some(node) = self.graph.get(key) {
-            for dependency in &node.dependencies {
-                self.visit(dependency, visited, order);
+                return Some((key, node));
             }
         }
-        // Add the node to the topological order.
-        order.push(*key);
+        None
+    }
+}
+
+/// Mutable iterator over the nodes of a [`Dag`], returned by [`Dag::iter_mut`].
+pub struct IterMut<'a, K: Eq + Hash, V> {
+    inner: hash_map::IterMut<'a, K, Node<K, V>>,
+}
+
+impl<'a, K: Eq + Hash, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, n)| (k, &mut n.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K: Eq + Hash, V> IntoIterator for Dag<K, V> {
+    type Item = (K, Node<K, V>);
+    type IntoIter = hash_map::IntoIter<K, Node<K, V>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.graph.into_iter()
+    }
+}
+
+impl<'a, K: Eq + Hash, V> IntoIterator for &'a Dag<K, V> {
+    type Item = (&'a K, &'a Node<K, V>);
+    type IntoIter = hash_map::Iter<'a, K, Node<K, V>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.graph.iter()
+    }
+}
+
+impl<'a, K: Eq + Hash, V> IntoIterator for &'a mut Dag<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IterMut {
+            inner: self.graph.iter_mut(),
+        }
     }
 }
 
@@ -213,6 +718,46 @@ mod tests {
         assert!(!dag.is_empty());
     }
 
+    #[test]
+    fn test_iter() {
+        let mut dag = Dag::new();
+
+        dag.node(0, 'a');
+        dag.node(1, 'b');
+        dag.node(2, 'c');
+        dag.dependency(1, 0);
+
+        assert!(dag.contains(&1));
+        assert!(!dag.contains(&3));
+
+        let mut keys = dag.keys().copied().collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, vec![0, 1, 2]);
+
+        let mut values = dag.values().copied().collect::<Vec<_>>();
+        values.sort();
+        assert_eq!(values, vec!['a', 'b', 'c']);
+
+        *dag.get_mut(&0).unwrap() = 'x';
+        for (_, v) in dag.iter_mut() {
+            *v = v.to_ascii_uppercase();
+        }
+        for (_, v) in &mut dag {
+            *v = (*v as u8 + 1) as char;
+        }
+        let mut nodes = dag.iter().map(|(k, n)| (*k, n.value)).collect::<Vec<_>>();
+        nodes.sort();
+        assert_eq!(nodes, vec![(0, 'Y'), (1, 'C'), (2, 'D')]);
+        assert!(dag.has_dependency(&1, &0));
+
+        let mut nodes = dag
+            .into_iter()
+            .map(|(k, n)| (k, n.value))
+            .collect::<Vec<_>>();
+        nodes.sort();
+        assert_eq!(nodes, vec![(0, 'Y'), (1, 'C'), (2, 'D')]);
+    }
+
     #[test]
     fn test_dependencies() {
         let mut dag = Dag::new();
@@ -225,6 +770,36 @@ mod tests {
         assert!(!dag.has_dependency(&1, &0));
     }
 
+    #[test]
+    fn test_dependents() {
+        let mut dag = Dag::new();
+
+        dag.node(0, ());
+        dag.node(1, ());
+        dag.node(2, ());
+        dag.node(3, ());
+
+        dag.dependency(1, 0);
+        dag.dependency(2, 0);
+        dag.dependency(3, 1);
+
+        let mut dependents = dag.dependents(&0).map(|(k, _)| *k).collect::<Vec<_>>();
+        dependents.sort();
+
+        assert_eq!(dependents, vec![1, 2]);
+        assert_eq!(
+            dag.dependents(&1).map(|(k, _)| *k).collect::<Vec<_>>(),
+            vec![3]
+        );
+        assert_eq!(dag.dependents(&3).count(), 0);
+        assert_eq!(dag.dependents(&4).count(), 0);
+        assert_eq!(
+            dag.dependencies(&3).map(|(k, _)| *k).collect::<Vec<_>>(),
+            vec![1]
+        );
+        assert_eq!(dag.dependencies(&0).count(), 0);
+    }
+
     #[test]
     fn test_get() {
         let mut dag = Dag::new();
@@ -277,6 +852,33 @@ mod tests {
         assert!(c.has_dependency(&2, &0));
     }
 
+    #[test]
+    fn test_merge_with() {
+        let mut a = Dag::new();
+        let mut b = Dag::new();
+
+        a.node(0, vec!["a"]);
+        a.node(1, vec!["a"]);
+        a.dependency(1, 0);
+
+        b.node(1, vec!["b"]);
+        b.node(2, vec!["b"]);
+        b.dependency(2, 1);
+
+        a.merge_with(b, |ours, theirs| ours.extend(theirs));
+
+        assert_eq!(a.len(), 3);
+        assert_eq!(a[&0].value, vec!["a"]);
+        assert_eq!(a[&1].value, vec!["a", "b"]);
+        assert_eq!(a[&2].value, vec!["b"]);
+        assert!(a.has_dependency(&1, &0));
+        assert!(a.has_dependency(&2, &1));
+        assert!(a[&1].dependents.contains(&2));
+        assert_eq!(a.roots().map(|(k, _)| *k).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(a.tips().map(|(k, _)| *k).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(a.sorted_by(|x, y| x.cmp(y)), vec![0, 1, 2]);
+    }
+
     #[test]
     fn test_diamond() {
         let mut dag = Dag::new();
@@ -301,6 +903,289 @@ mod tests {
         assert!(expected.contains(&actual.as_slice()), "{:?}", actual);
     }
 
+    #[test]
+    fn test_sorted_by() {
+        let mut dag = Dag::new();
+
+        dag.node(0, ());
+        dag.node(1, ());
+        dag.node(2, ());
+        dag.node(3, ());
+
+        dag.dependency(1, 0);
+        dag.dependency(2, 0);
+        dag.dependency(3, 1);
+        dag.dependency(3, 2);
+
+        assert_eq!(dag.sorted_by(|a, b| a.cmp(b)), vec![0, 1, 2, 3]);
+        assert_eq!(dag.sorted_by(|a, b| b.cmp(a)), vec![0, 2, 1, 3]);
+
+        // Ties between independent nodes are broken by the comparator.
+        dag.node(4, ());
+        assert_eq!(dag.sorted_by(|a, b| a.cmp(b)), vec![0, 1, 2, 3, 4]);
+        assert_eq!(dag.sorted_by(|a, b| b.cmp(a)), vec![4, 0, 2, 1, 3]);
+    }
+
+    #[test]
+    fn test_sorted_by_cycle() {
+        let mut dag = Dag::new();
+
+        dag.node(0, ());
+        dag.node(1, ());
+        dag.node(2, ());
+
+        dag.dependency(0, 1);
+        dag.dependency(1, 0);
+
+        assert_eq!(dag.sorted_by(|a, b| a.cmp(b)), vec![2, 0, 1]);
+    }
+
+    #[test]
+    fn test_ancestors_descendants() {
+        let mut dag = Dag::new();
+
+        dag.node(0, ());
+        dag.node(1, ());
+        dag.node(2, ());
+        dag.node(3, ());
+        dag.node(4, ());
+
+        dag.dependency(1, 0);
+        dag.dependency(2, 0);
+        dag.dependency(3, 1);
+        dag.dependency(3, 2);
+
+        let ancestors = |k| dag.ancestors(&k).map(|(k, _)| *k).collect::<HashSet<_>>();
+        let descendants = |k| dag.descendants(&k).map(|(k, _)| *k).collect::<HashSet<_>>();
+
+        assert_eq!(ancestors(3), HashSet::from_iter([0, 1, 2]));
+        assert_eq!(ancestors(1), HashSet::from_iter([0]));
+        assert_eq!(ancestors(0), HashSet::new());
+        assert_eq!(descendants(0), HashSet::from_iter([1, 2, 3]));
+        assert_eq!(descendants(2), HashSet::from_iter([3]));
+        assert_eq!(descendants(4), HashSet::new());
+        assert_eq!(descendants(5), HashSet::new());
+
+        // Shared ancestors are only visited once.
+        assert_eq!(dag.ancestors(&3).count(), 3);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        let mut dag = Dag::new();
+
+        dag.node(0, "rad");
+        dag.node(1, "dar");
+        dag.node(2, "ard");
+        dag.dependency(1, 0);
+        dag.dependency(2, 0);
+        dag.dependency(2, 1);
+
+        let json = serde_json::to_string(&dag).unwrap();
+        let decoded: Dag<i32, String> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded.len(), 3);
+        assert_eq!(decoded[&2].value, "ard");
+        assert!(decoded.has_dependency(&2, &1));
+        assert!(decoded.has_dependency(&2, &0));
+        assert!(decoded[&0].dependents.contains(&1));
+        assert_eq!(
+            decoded.roots().map(|(k, _)| *k).collect::<Vec<_>>(),
+            vec![0]
+        );
+        assert_eq!(decoded.tips().map(|(k, _)| *k).collect::<Vec<_>>(), vec![2]);
+
+        assert!(serde_json::from_str::<Dag<i32, String>>(
+            r#"[{"key":0,"value":"rad","dependencies":[1]}]"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut dag = Dag::new();
+
+        dag.node(0, ());
+        dag.node(1, ());
+        dag.node(2, ());
+        dag.node(3, ());
+
+        dag.dependency(1, 0);
+        dag.dependency(2, 0);
+        dag.dependency(3, 1);
+        dag.dependency(3, 2);
+
+        dag.retain(|k, _| *k != 0 && *k != 2);
+
+        assert_eq!(dag.len(), 2);
+        assert!(dag.has_dependency(&3, &1));
+        assert!(!dag.has_dependency(&3, &2));
+        assert!(dag[&1].dependencies.is_empty());
+        assert_eq!(dag.roots().map(|(k, _)| *k).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(dag.tips().map(|(k, _)| *k).collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn test_subgraph() {
+        let mut dag = Dag::new();
+
+        dag.node(0, ());
+        dag.node(1, ());
+        dag.node(2, ());
+        dag.node(3, ());
+        dag.node(4, ());
+
+        dag.dependency(1, 0);
+        dag.dependency(2, 1);
+        dag.dependency(3, 2);
+        dag.dependency(4, 0);
+
+        let sub = dag.subgraph([2]);
+
+        assert_eq!(sub.len(), 2);
+        assert!(sub.has_dependency(&3, &2));
+        assert!(!sub.has_dependency(&2, &1));
+        assert_eq!(sub.roots().map(|(k, _)| *k).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(sub.tips().map(|(k, _)| *k).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(sub.sorted_by(|a, b| a.cmp(b)), vec![2, 3]);
+
+        assert!(dag.subgraph([5]).is_empty());
+        assert_eq!(dag.subgraph([0]).len(), 5);
+    }
+
+    #[test]
+    fn test_transitive_reduction() {
+        let mut dag = Dag::new();
+
+        for i in 0..5 {
+            dag.node(i, ());
+        }
+        dag.dependency(1, 0);
+        dag.dependency(2, 1);
+        dag.dependency(2, 0); // Redundant: 2 -> 1 -> 0.
+        dag.dependency(3, 1);
+        dag.dependency(4, 2);
+        dag.dependency(4, 3);
+        dag.dependency(4, 0); // Redundant: 4 -> 3 -> 1 -> 0.
+
+        dag.transitive_reduction();
+
+        assert!(!dag.has_dependency(&2, &0));
+        assert!(!dag.has_dependency(&4, &0));
+        assert!(!dag[&0].dependents.contains(&2));
+        assert!(!dag[&0].dependents.contains(&4));
+
+        assert!(dag.has_dependency(&1, &0));
+        assert!(dag.has_dependency(&2, &1));
+        assert!(dag.has_dependency(&3, &1));
+        assert!(dag.has_dependency(&4, &2));
+        assert!(dag.has_dependency(&4, &3));
+
+        assert_eq!(dag.roots().map(|(k, _)| *k).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(dag.tips().map(|(k, _)| *k).collect::<Vec<_>>(), vec![4]);
+        assert_eq!(dag.sorted_by(|a, b| a.cmp(b)), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_depths() {
+        let mut dag = Dag::new();
+
+        for i in 0..6 {
+            dag.node(i, ());
+        }
+        dag.dependency(1, 0);
+        dag.dependency(2, 1);
+        dag.dependency(3, 0);
+        dag.dependency(4, 2);
+        dag.dependency(4, 3);
+
+        let depths = dag.depths();
+
+        assert_eq!(depths[&0], 0);
+        assert_eq!(depths[&1], 1);
+        assert_eq!(depths[&2], 2);
+        assert_eq!(depths[&3], 1);
+        assert_eq!(depths[&4], 3);
+        assert_eq!(depths[&5], 0);
+        assert_eq!(depths.len(), 6);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut dag = Dag::new();
+
+        dag.node(0, "rad");
+        dag.node(1, "\"dar\"");
+        dag.node(2, "ard");
+        dag.dependency(1, 0);
+        dag.dependency(2, 0);
+        dag.dependency(2, 1);
+
+        assert_eq!(
+            dag.to_dot(|_, v| *v),
+            r#"digraph {
+    "0" [label="rad"];
+    "1" [label="\"dar\""];
+    "2" [label="ard"];
+    "1" -> "0";
+    "2" -> "0";
+    "2" -> "1";
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_sorted_multiple_parents() {
+        let mut dag = Dag::new();
+
+        // `0` has children `1` and `2`, which are both parents of `3` and `4`.
+        for i in 0..5 {
+            dag.node(i, ());
+        }
+        dag.dependency(1, 0);
+        dag.dependency(2, 0);
+        for child in [3, 4] {
+            dag.dependency(child, 1);
+            dag.dependency(child, 2);
+        }
+        // Re-inserting a node drops its own edges, but not those of its neighbours,
+        // leaving `2 -> 3` only as a dependent edge.
+        dag.node(3, ());
+        dag.dependency(3, 1);
+
+        assert_eq!(dag.sorted_by(|a, b| a.cmp(b)), vec![0, 1, 2, 3, 4]);
+
+        for _ in 0..32 {
+            let order = dag.sorted(fastrand::Rng::new());
+            let ix = |k| order.iter().position(|o| *o == k).unwrap();
+
+            assert_eq!(order.len(), 5);
+            assert_eq!(order[0], 0);
+            assert!(ix(3) > ix(1) && ix(3) > ix(2));
+            assert!(ix(4) > ix(1) && ix(4) > ix(2));
+        }
+        assert_eq!(dag.depths().len(), 5);
+    }
+
+    #[test]
+    fn test_long_chain() {
+        let mut dag = Dag::new();
+        let len = 100_000;
+
+        dag.node(0, ());
+        for i in 1..len {
+            dag.node(i, ());
+            dag.dependency(i, i - 1);
+        }
+        let expected = (0..len).collect::<Vec<_>>();
+
+        assert_eq!(dag.sorted(fastrand::Rng::new()), expected);
+        assert_eq!(dag.sorted_by(|a, b| a.cmp(b)), expected);
+        assert_eq!(dag.ancestors(&(len - 1)).count(), len - 1);
+    }
+
     #[test]
     fn test_complex() {
         let mut dag = Dag::new();
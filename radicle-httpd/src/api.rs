@@ -14,6 +14,7 @@ use tower_http::cors::{self, CorsLayer};
 
 use radicle::cob::issue::Issues;
 use radicle::identity::Id;
+use radicle::storage::index::Index;
 use radicle::storage::{ReadRepository, WriteStorage};
 use radicle::Profile;
 
@@ -44,10 +45,19 @@ impl Context {
         }
     }
 
+    /// Get the projects in storage. Uses the node's refs index if it exists, and
+    /// falls back to listing storage otherwise.
+    pub fn projects(&self) -> Result<Vec<Id>, error::Error> {
+        if let Some(index) = Index::open_existing(self.profile.paths().index())? {
+            return Ok(index.projects()?);
+        }
+        Ok(self.profile.storage.projects()?)
+    }
+
     pub fn project_info(&self, id: Id) -> Result<project::Info, error::Error> {
         let storage = &self.profile.storage;
         let repo = storage.repository(id)?;
-        let (_, head) = repo.head()?;
+        let (_, head) = repo.canonical_head()?;
         let payload = repo.project_of(self.profile.id())?;
         let issues = (Issues::open(self.profile.public_key, &repo)?).count()?;
 
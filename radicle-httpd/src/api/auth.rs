@@ -1,11 +1,14 @@
 use std::convert::TryFrom;
 use std::str::FromStr;
 
+use axum::http::header::AUTHORIZATION;
+use axum::http::HeaderMap;
 use ethers_core::types::{Signature, H160};
 use serde::{Deserialize, Serialize, Serializer};
 use time::OffsetDateTime;
 
 use crate::api::error::Error;
+use crate::api::Context;
 
 #[derive(Clone)]
 pub struct DateTime(pub OffsetDateTime);
@@ -76,6 +79,29 @@ impl TryFrom<siwe::Message> for Session {
     }
 }
 
+/// Check that a request carries the id of an authorized, unexpired session as a bearer
+/// token in its `Authorization` header, and return the session.
+pub async fn authorize(ctx: &Context, headers: &HeaderMap) -> Result<Session, Error> {
+    let id = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(Error::Unauthorized)?;
+    let sessions = ctx.sessions.read().await;
+
+    match sessions.get(id.trim()) {
+        Some(AuthState::Authorized(session)) => {
+            if let Some(DateTime(expiration)) = &session.expiration_time {
+                if *expiration <= OffsetDateTime::now_utc() {
+                    return Err(Error::Unauthorized);
+                }
+            }
+            Ok(session.clone())
+        }
+        _ => Err(Error::Unauthorized),
+    }
+}
+
 #[cfg(test)]
 mod test {
     #[test]
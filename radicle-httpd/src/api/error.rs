@@ -14,6 +14,10 @@ pub enum Error {
     #[error("could not authenticate: {0}")]
     Auth(&'static str),
 
+    /// The request lacks a valid session.
+    #[error("unauthorized")]
+    Unauthorized,
+
     /// An error occurred with env variables.
     #[error(transparent)]
     Env(#[from] std::env::VarError),
@@ -34,6 +38,18 @@ pub enum Error {
     #[error(transparent)]
     CobStore(#[from] radicle::cob::store::Error),
 
+    /// Patch error.
+    #[error(transparent)]
+    CobPatch(#[from] radicle::cob::patch::Error),
+
+    /// Issue template error.
+    #[error(transparent)]
+    IssueTemplate(#[from] radicle::cob::issue::template::Error),
+
+    /// Identity document error.
+    #[error(transparent)]
+    Identity(#[from] radicle::identity::doc::DocError),
+
     /// Git project error.
     #[error(transparent)]
     GitProject(#[from] radicle::storage::git::ProjectError),
@@ -53,6 +69,10 @@ pub enum Error {
     /// Storage refs error.
     #[error(transparent)]
     StorageRef(#[from] radicle::storage::refs::Error),
+
+    /// Refs index error.
+    #[error(transparent)]
+    Index(#[from] radicle::storage::index::Error),
 }
 
 impl IntoResponse for Error {
@@ -60,6 +80,7 @@ impl IntoResponse for Error {
         let (status, msg) = match &self {
             Error::NotFound => (StatusCode::NOT_FOUND, None),
             Error::Auth(msg) => (StatusCode::BAD_REQUEST, Some(msg.to_string())),
+            Error::Unauthorized => (StatusCode::UNAUTHORIZED, None),
             Error::SiweParse(msg) => (StatusCode::BAD_REQUEST, Some(msg.to_string())),
             Error::SiweVerification(msg) => (StatusCode::BAD_REQUEST, Some(msg.to_string())),
             Error::Git2(e) => (
@@ -3,9 +3,11 @@ use std::sync::Arc;
 use std::{env, fs};
 
 use axum::body::Body;
-use axum::http::Request;
+use axum::http::{header, Method, Request};
 use axum::Router;
+use ethers_core::types::H160;
 use serde_json::Value;
+use time::OffsetDateTime;
 use tower::ServiceExt;
 
 use radicle::cob::issue::Issues;
@@ -15,6 +17,7 @@ use radicle_cli::commands::rad_init;
 use radicle_crypto::ssh::keystore::MemorySigner;
 use radicle_crypto::Signer;
 
+use crate::api::auth::{AuthState, DateTime, Session};
 use crate::api::Context;
 
 pub const HEAD: &str = "1e978d19f251cd9821d9d9a76d1bd436bf0690d5";
@@ -127,6 +130,44 @@ pub async fn request(app: &Router, path: impl ToString) -> Response {
     )
 }
 
+pub async fn delete(app: &Router, path: impl ToString, session: Option<&str>) -> Response {
+    let mut request = Request::builder()
+        .method(Method::DELETE)
+        .uri(path.to_string());
+    if let Some(id) = session {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {id}"));
+    }
+    Response(
+        app.clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap(),
+    )
+}
+
+/// Add an authorized session to the context, and return its id.
+pub async fn authorize(ctx: &Context) -> String {
+    let id = String::from("u9MGAkkfkMOv0uDDB2WeUHBT7HbsO2Dy");
+    let session = Session {
+        domain: String::from("localhost"),
+        address: H160::zero(),
+        statement: None,
+        uri: String::from("http://localhost"),
+        version: 1,
+        chain_id: 1,
+        nonce: String::from("nonce"),
+        issued_at: DateTime(OffsetDateTime::now_utc()),
+        expiration_time: None,
+        resources: Vec::new(),
+    };
+    ctx.sessions
+        .write()
+        .await
+        .insert(id.clone(), AuthState::Authorized(session));
+
+    id
+}
+
 pub struct Response(axum::response::Response);
 
 impl Response {
@@ -38,7 +38,7 @@ async fn delegates_projects_handler(
         .into_iter()
         .filter_map(|id| {
             let Ok(repo) = storage.repository(id) else { return None };
-            let Ok((_, head)) = repo.head() else { return None };
+            let Ok((_, head)) = repo.canonical_head() else { return None };
             let Ok(doc) = repo.identity_of(ctx.profile.id()) else { return None };
             let Ok(payload) = doc.project() else { return None };
 
@@ -57,7 +57,7 @@ async fn delegates_projects_handler(
                 id,
             })
         })
-        .skip(page * per_page)
+        .skip(page.saturating_mul(per_page))
         .take(per_page)
         .collect::<Vec<_>>();
 
@@ -97,4 +97,22 @@ mod routes {
             ])
         );
     }
+
+    #[tokio::test]
+    async fn test_delegates_projects_pagination() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(test::seed(tmp.path()));
+        let response = request(
+            &app,
+            format!(
+                "/delegates/did:key:z6MknSLrJoTcukLrE435hVNQT4JUhbvWLX4kUzqkEStBU8Vi/projects?page={}&per-page={}",
+                usize::MAX,
+                usize::MAX
+            ),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.json().await, json!([]));
+    }
 }
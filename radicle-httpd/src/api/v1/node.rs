@@ -4,24 +4,66 @@ use axum::routing::get;
 use axum::{Json, Router};
 use serde_json::json;
 
-use radicle::node::NodeId;
+use radicle::node::Handle as _;
 
 use crate::api::Context;
 
 pub fn router(ctx: Context) -> Router {
-    let node_id = ctx.profile.public_key;
-
     Router::new()
         .route("/node", get(node_handler))
-        .with_state(node_id)
+        .with_state(ctx)
 }
 
-/// Return the node id for the node identity.
+/// Return the node status.
+/// If the node is not running, only the node id is returned.
 /// `GET /node`
-async fn node_handler(State(node_id): State<NodeId>) -> impl IntoResponse {
-    let response = json!({
-        "id": node_id.to_string(),
-    });
+async fn node_handler(State(ctx): State<Context>) -> impl IntoResponse {
+    let node_id = ctx.profile.public_key;
+    let info = radicle::node::connect(ctx.profile.socket())
+        .and_then(|node| node.info())
+        .map_err(|e| tracing::debug!("Unable to query node status: {e}"))
+        .ok();
+
+    let response = match info {
+        Some(info) => json!({
+            "id": node_id.to_string(),
+            "state": "running",
+            "agent": info.agent,
+            "peers": info.peers,
+            "repos": info.repos,
+            "uptime": info.uptime,
+        }),
+        None => json!({
+            "id": node_id.to_string(),
+            "state": "stopped",
+        }),
+    };
 
     Json(response)
 }
+
+#[cfg(test)]
+mod routes {
+    use axum::http::StatusCode;
+    use serde_json::json;
+
+    use crate::api::test::{self, request};
+
+    #[tokio::test]
+    async fn test_node_stopped() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let id = ctx.profile.public_key;
+        let app = super::router(ctx);
+        let response = request(&app, "/node").await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.json().await,
+            json!({
+                "id": id.to_string(),
+                "state": "stopped",
+            })
+        );
+    }
+}
@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 
 use axum::extract::State;
 use axum::handler::Handler;
-use axum::http::{header, HeaderValue};
+use axum::http::{header, HeaderMap, HeaderValue};
 use axum::response::IntoResponse;
 use axum::routing::get;
 use axum::{Json, Router};
@@ -11,10 +11,14 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tower_http::set_header::SetResponseHeaderLayer;
 
-use radicle::cob::issue::Issues;
+use radicle::cob::discussion::{Discussion, Discussions};
+use radicle::cob::issue::{self, Issues};
+use radicle::cob::patch::{Patches, RevisionIx};
+use radicle::cob::release::{Release, Releases};
+use radicle::cob::store::Filter;
 use radicle::cob::thread::{self, CommentId};
 use radicle::cob::Timestamp;
-use radicle::identity::{Id, PublicKey};
+use radicle::identity::{Doc, Id, PublicKey, Untrusted};
 use radicle::node::NodeId;
 use radicle::storage::{git::paths, ReadRepository, WriteStorage};
 use radicle_surf::{Glob, Oid, Repository};
@@ -48,7 +52,30 @@ pub fn router(ctx: Context) -> Router {
         .route("/projects/:project/blob/:sha/*path", get(blob_handler))
         .route("/projects/:project/readme/:sha", get(readme_handler))
         .route("/projects/:project/issues", get(issues_handler))
-        .route("/projects/:project/issues/:id", get(issue_handler))
+        .route(
+            "/projects/:project/issues/:id",
+            get(issue_handler).delete(issue_delete_handler),
+        )
+        .route(
+            "/projects/:project/issue-templates",
+            get(issue_templates_handler),
+        )
+        .route("/projects/:project/issues/:id/ops", get(issue_ops_handler))
+        .route(
+            "/projects/:project/issues/:id/attachments/:oid",
+            get(issue_attachment_handler),
+        )
+        .route(
+            "/projects/:project/patches/:id/interdiff",
+            get(patch_interdiff_handler),
+        )
+        .route("/projects/:project/discussions", get(discussions_handler))
+        .route(
+            "/projects/:project/discussions/:id",
+            get(discussion_handler),
+        )
+        .route("/projects/:project/releases", get(releases_handler))
+        .route("/projects/:project/releases/:id", get(release_handler))
         .with_state(ctx)
 }
 
@@ -62,12 +89,12 @@ async fn project_root_handler(
     let page = page.unwrap_or(0);
     let per_page = per_page.unwrap_or(10);
     let storage = &ctx.profile.storage;
-    let projects = storage
+    let projects = ctx
         .projects()?
         .into_iter()
         .filter_map(|id| {
             let Ok(repo) = storage.repository(id) else { return None };
-            let Ok((_, head)) = repo.head() else { return None };
+            let Ok((_, head)) = repo.canonical_head() else { return None };
             let Ok(payload) = repo.project_of(ctx.profile.id()) else { return None };
             let Ok(issues) = Issues::open(ctx.profile.public_key, &repo) else { return None };
             let Ok(issues) = (*issues).count() else { return None };
@@ -80,7 +107,7 @@ async fn project_root_handler(
                 id,
             })
         })
-        .skip(page * per_page)
+        .skip(page.saturating_mul(per_page))
         .take(per_page)
         .collect::<Vec<_>>();
 
@@ -158,7 +185,7 @@ async fn history_handler(
                 false
             }
         })
-        .skip(page * per_page)
+        .skip(page.saturating_mul(per_page))
         .take(per_page)
         .map(|r| {
             r.and_then(|c| {
@@ -216,31 +243,146 @@ async fn commit_handler(
     Ok::<_, Error>(Json(response))
 }
 
-/// Get project activity for the past year.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct ActivityQueryString {
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+}
+
+/// Get project activity.
+///
+/// Returns the commit timestamps of the past year under `activity`, and a paginated,
+/// reverse-chronological feed of commits, issue and patch events and identity changes
+/// under `feed`.
 /// `GET /projects/:project/activity`
 async fn activity_handler(
     State(ctx): State<Context>,
     Path(project): Path<Id>,
+    Query(qs): Query<ActivityQueryString>,
 ) -> impl IntoResponse {
+    let ActivityQueryString { page, per_page } = qs;
+    let page = page.unwrap_or(0);
+    let per_page = per_page.unwrap_or(30);
     let current_date = chrono::Utc::now().timestamp();
     let one_year_ago = chrono::Duration::weeks(52);
     let storage = &ctx.profile.storage;
     let repo = Repository::open(paths::repository(storage, &project))?;
     let head = repo.head()?;
-    let timestamps = repo
-        .history(head)?
-        .filter_map(|a| {
-            if let Ok(a) = a {
-                let seconds = a.committer.time.seconds();
-                if seconds > current_date - one_year_ago.num_seconds() {
-                    return Some(seconds);
-                }
+    let mut timestamps = Vec::new();
+    let mut feed: Vec<(i64, serde_json::Value)> = Vec::new();
+
+    for commit in repo.history(head)? {
+        let commit = commit?;
+        let seconds = commit.committer.time.seconds();
+
+        if seconds > current_date - one_year_ago.num_seconds() {
+            timestamps.push(seconds);
+        }
+        feed.push((
+            seconds,
+            json!({
+                "type": "commit",
+                "timestamp": seconds,
+                "commit": api::json::commit(&commit),
+            }),
+        ));
+    }
+
+    let stored = storage.repository(project)?;
+    let issues = Issues::open(ctx.profile.public_key, &stored)?;
+    for (id, issue, _) in issues.all()?.filter_map(|r| r.ok()) {
+        for (ix, (comment_id, comment)) in issue.comments().enumerate() {
+            let timestamp = comment.timestamp().as_secs() as i64;
+            let event = if ix == 0 {
+                json!({
+                    "type": "issue.opened",
+                    "timestamp": timestamp,
+                    "issue": id,
+                    "title": issue.title(),
+                    "author": Author { id: comment.author() },
+                })
+            } else {
+                json!({
+                    "type": "issue.comment",
+                    "timestamp": timestamp,
+                    "issue": id,
+                    "comment": comment_id,
+                    "author": Author { id: comment.author() },
+                })
+            };
+            feed.push((timestamp, event));
+        }
+    }
+
+    let patches = Patches::open(ctx.profile.public_key, &stored)?;
+    for (id, patch, _) in patches.all()?.filter_map(|r| r.ok()) {
+        for (ix, (revision_id, revision)) in patch.revisions().enumerate() {
+            let timestamp = revision.timestamp.as_secs() as i64;
+            let kind = if ix == 0 {
+                "patch.opened"
+            } else {
+                "patch.updated"
+            };
+            feed.push((
+                timestamp,
+                json!({
+                    "type": kind,
+                    "timestamp": timestamp,
+                    "patch": id,
+                    "revision": revision_id,
+                    "title": patch.title(),
+                    "author": Author { id: *revision.author.id() },
+                }),
+            ));
+
+            for merge in revision.merges.iter() {
+                let merge = merge.get();
+                let timestamp = merge.timestamp.as_secs() as i64;
+                feed.push((
+                    timestamp,
+                    json!({
+                        "type": "patch.merged",
+                        "timestamp": timestamp,
+                        "patch": id,
+                        "revision": revision_id,
+                        "commit": merge.commit,
+                        "author": Author { id: merge.node },
+                    }),
+                ));
             }
-            None
-        })
-        .collect::<Vec<i64>>();
+        }
+    }
+
+    let identity = Doc::<Untrusted>::head(ctx.profile.id(), &stored)?;
+    for oid in stored.revwalk(identity)? {
+        let commit = stored.raw().find_commit(oid?)?;
+        let timestamp = commit.time().seconds();
+        feed.push((
+            timestamp,
+            json!({
+                "type": "identity.updated",
+                "timestamp": timestamp,
+                "commit": commit.id().to_string(),
+                "summary": commit.summary().unwrap_or_default(),
+            }),
+        ));
+    }
+    // Most recent events first. The sort is stable, so events with equal
+    // timestamps keep the order in which they were collected.
+    feed.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+    let feed = feed
+        .into_iter()
+        .map(|(_, event)| event)
+        .skip(page.saturating_mul(per_page))
+        .take(per_page)
+        .collect::<Vec<_>>();
 
-    Ok::<_, Error>((StatusCode::OK, Json(json!({ "activity": timestamps }))))
+    Ok::<_, Error>((
+        StatusCode::OK,
+        Json(json!({ "activity": timestamps, "feed": feed })),
+    ))
 }
 
 /// Get project source tree for '/' path.
@@ -365,21 +507,53 @@ async fn readme_handler(
     Err(Error::NotFound)
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum IssueState {
+    Open,
+    Closed,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct IssuesQueryString {
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+    pub state: Option<IssueState>,
+    pub author: Option<PublicKey>,
+}
+
 /// Get project issues list.
-/// `GET /projects/:project/issues`
+/// `GET /projects/:project/issues?state=<open|closed>&author=<key>`
 async fn issues_handler(
     State(ctx): State<Context>,
     Path(project): Path<Id>,
-    Query(qs): Query<PaginationQuery>,
+    Query(qs): Query<IssuesQueryString>,
 ) -> impl IntoResponse {
-    let PaginationQuery { page, per_page } = qs;
+    let IssuesQueryString {
+        page,
+        per_page,
+        state,
+        author,
+    } = qs;
     let page = page.unwrap_or(0);
     let per_page = per_page.unwrap_or(10);
     let storage = &ctx.profile.storage;
     let repo = storage.repository(project)?;
     let issues = Issues::open(ctx.profile.public_key, &repo)?;
+    let mut filter = Filter::default();
+    if let Some(author) = author {
+        filter = filter.author(author);
+    }
+    match state {
+        Some(IssueState::Open) => filter = filter.state(|s| *s == issue::State::Open),
+        Some(IssueState::Closed) => {
+            filter = filter.state(|s| matches!(s, issue::State::Closed { .. }))
+        }
+        None => {}
+    }
     let issues = issues
-        .all()?
+        .filter(filter)?
         .into_iter()
         .filter_map(|r| r.ok())
         .map(|(id, issue, _)| {
@@ -388,17 +562,33 @@ async fn issues_handler(
                 "author": issue.author(),
                 "title": issue.title(),
                 "state": issue.state(),
+                "closedBy": issue.closed_by(),
+                "reopenedBy": issue.reopened_by(),
                 "discussion": issue.comments().collect::<Comments>(),
                 "tags": issue.tags().collect::<Vec<_>>(),
             })
         })
-        .skip(page * per_page)
+        .skip(page.saturating_mul(per_page))
         .take(per_page)
         .collect::<Vec<_>>();
 
     Ok::<_, Error>(Json(issues))
 }
 
+/// Get project issue templates, as of the project head.
+/// `GET /projects/:project/issue-templates`
+async fn issue_templates_handler(
+    State(ctx): State<Context>,
+    Path(project): Path<Id>,
+) -> impl IntoResponse {
+    let storage = &ctx.profile.storage;
+    let repo = storage.repository(project)?;
+    let (_, head) = repo.head()?;
+    let templates = issue::template::templates(repo.raw(), head)?;
+
+    Ok::<_, Error>(Json(templates))
+}
+
 /// Get project issue.
 /// `GET /projects/:project/issues/:id`
 async fn issue_handler(
@@ -410,18 +600,280 @@ async fn issue_handler(
     let issue = Issues::open(ctx.profile.public_key, &repo)?
         .get(&issue_id.into())?
         .ok_or(Error::NotFound)?;
+    let patches = Patches::open(ctx.profile.public_key, &repo)?
+        .referencing(&issue::TYPENAME, &issue_id.into())?
+        .filter_map(|r| r.ok())
+        .map(|(id, _, _)| id.to_string())
+        .collect::<Vec<_>>();
     let issue = json!({
         "id": issue_id,
         "author": issue.author(),
         "title": issue.title(),
         "state": issue.state(),
+        "closedBy": issue.closed_by(),
+        "reopenedBy": issue.reopened_by(),
         "discussion": issue.comments().collect::<Comments>(),
         "tags": issue.tags().collect::<Vec<_>>(),
+        "references": issue.references().collect::<Vec<_>>(),
+        "patches": patches,
+        "attachments": issue
+            .comments()
+            .flat_map(|(id, _)| {
+                issue.attachments(id).map(move |a| {
+                    json!({ "comment": id, "name": a.name, "oid": a.oid })
+                })
+            })
+            .collect::<Vec<_>>(),
     });
 
     Ok::<_, Error>(Json(issue))
 }
 
+/// Get the latest changes to a project issue, latest first.
+/// `GET /projects/:project/issues/:id/ops?page=<page>&per-page=<count>`
+async fn issue_ops_handler(
+    State(ctx): State<Context>,
+    Path((project, issue_id)): Path<(Id, Oid)>,
+    Query(qs): Query<PaginationQuery>,
+) -> impl IntoResponse {
+    let PaginationQuery { page, per_page } = qs;
+    let page = page.unwrap_or(0);
+    let per_page = per_page.unwrap_or(10).max(1);
+    let storage = &ctx.profile.storage;
+    let repo = storage.repository(project)?;
+    let issues = Issues::open(ctx.profile.public_key, &repo)?;
+    let changes = issues
+        .pages(&issue_id.into(), per_page)?
+        .nth(page)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|entry| {
+            let actions = entry
+                .typed_contents()
+                .decode::<serde_json::Value>()
+                .map(Vec::from)
+                .ok();
+
+            json!({
+                "id": entry.id(),
+                "author": entry.actor(),
+                "timestamp": entry.timestamp(),
+                "actions": actions,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok::<_, Error>(Json(changes))
+}
+
+/// Download a file attached to a project issue.
+/// `GET /projects/:project/issues/:id/attachments/:oid`
+async fn issue_attachment_handler(
+    State(ctx): State<Context>,
+    Path((project, issue_id, oid)): Path<(Id, Oid, Oid)>,
+) -> impl IntoResponse {
+    let storage = &ctx.profile.storage;
+    let repo = storage.repository(project)?;
+    let issues = Issues::open(ctx.profile.public_key, &repo)?;
+    let issue = issues.get(&issue_id.into())?.ok_or(Error::NotFound)?;
+    let attachment = issue.attachment(&oid.into()).ok_or(Error::NotFound)?;
+    let content = issues.attachment(&attachment.oid)?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/octet-stream"),
+    );
+    if let Ok(disposition) =
+        HeaderValue::from_str(&format!("attachment; filename=\"{}\"", attachment.name))
+    {
+        headers.insert(header::CONTENT_DISPOSITION, disposition);
+    }
+
+    Ok::<_, Error>((headers, content))
+}
+
+/// Delete project issue.
+/// `DELETE /projects/:project/issues/:id`
+async fn issue_delete_handler(
+    State(ctx): State<Context>,
+    headers: HeaderMap,
+    Path((project, issue_id)): Path<(Id, Oid)>,
+) -> impl IntoResponse {
+    api::auth::authorize(&ctx, &headers).await?;
+
+    let storage = &ctx.profile.storage;
+    let repo = storage.repository(project)?;
+    let issues = Issues::open(ctx.profile.public_key, &repo)?;
+    let issue_id = issue_id.into();
+
+    if issues.get(&issue_id)?.is_none() {
+        return Err(Error::NotFound);
+    }
+    issues.remove(&issue_id)?;
+
+    Ok::<_, Error>(StatusCode::NO_CONTENT)
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct InterdiffQueryString {
+    pub from: Option<RevisionIx>,
+    pub to: Option<RevisionIx>,
+}
+
+/// Get the changes made to a patch between two of its revisions.
+///
+/// Revisions are given by index, and default to the previous and latest revision.
+/// `GET /projects/:project/patches/:id/interdiff`
+async fn patch_interdiff_handler(
+    State(ctx): State<Context>,
+    Path((project, patch_id)): Path<(Id, Oid)>,
+    Query(qs): Query<InterdiffQueryString>,
+) -> impl IntoResponse {
+    let storage = &ctx.profile.storage;
+    let repo = storage.repository(project)?;
+    let patch_id = patch_id.into();
+    let patches = Patches::open(ctx.profile.public_key, &repo)?;
+    let patch = patches.get(&patch_id)?.ok_or(Error::NotFound)?;
+    let revisions = patch.revisions().collect::<Vec<_>>();
+    let to = qs.to.unwrap_or(revisions.len().saturating_sub(1));
+    let from = qs.from.unwrap_or(to.saturating_sub(1));
+    let ((from_id, from_rev), (to_id, to_rev)) = revisions
+        .get(from)
+        .zip(revisions.get(to))
+        .ok_or(Error::NotFound)?;
+
+    let diff = patches.interdiff(&patch_id, from_id, to_id)?;
+    let mut output = Vec::new();
+    diff.print(radicle::git::raw::DiffFormat::Patch, |_, _, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            output.push(line.origin() as u8);
+        }
+        output.extend_from_slice(line.content());
+        true
+    })?;
+
+    Ok::<_, Error>(Json(json!({
+        "from": { "revision": from, "oid": from_rev.oid },
+        "to": { "revision": to, "oid": to_rev.oid },
+        "diff": String::from_utf8_lossy(&output),
+    })))
+}
+
+/// Get project discussions list.
+/// `GET /projects/:project/discussions`
+async fn discussions_handler(
+    State(ctx): State<Context>,
+    Path(project): Path<Id>,
+    Query(qs): Query<PaginationQuery>,
+) -> impl IntoResponse {
+    let PaginationQuery { page, per_page } = qs;
+    let page = page.unwrap_or(0);
+    let per_page = per_page.unwrap_or(10);
+    let storage = &ctx.profile.storage;
+    let repo = storage.repository(project)?;
+    let discussions = Discussions::open(ctx.profile.public_key, &repo)?;
+    let discussions = discussions
+        .all()?
+        .into_iter()
+        .filter_map(|r| r.ok())
+        .map(|(id, discussion, _)| discussion_json(id.to_string(), &discussion))
+        .skip(page.saturating_mul(per_page))
+        .take(per_page)
+        .collect::<Vec<_>>();
+
+    Ok::<_, Error>(Json(discussions))
+}
+
+/// Get project discussion.
+/// `GET /projects/:project/discussions/:id`
+async fn discussion_handler(
+    State(ctx): State<Context>,
+    Path((project, discussion_id)): Path<(Id, Oid)>,
+) -> impl IntoResponse {
+    let storage = &ctx.profile.storage;
+    let repo = storage.repository(project)?;
+    let discussion = Discussions::open(ctx.profile.public_key, &repo)?
+        .get(&discussion_id.into())?
+        .ok_or(Error::NotFound)?;
+
+    Ok::<_, Error>(Json(discussion_json(
+        discussion_id.to_string(),
+        &discussion,
+    )))
+}
+
+fn discussion_json(id: String, discussion: &Discussion) -> serde_json::Value {
+    json!({
+        "id": id,
+        "author": discussion.author(),
+        "title": discussion.title(),
+        "discussion": discussion.comments().collect::<Comments>(),
+        "references": discussion.references().collect::<Vec<_>>(),
+    })
+}
+
+/// Get project releases list.
+/// `GET /projects/:project/releases`
+async fn releases_handler(
+    State(ctx): State<Context>,
+    Path(project): Path<Id>,
+    Query(qs): Query<PaginationQuery>,
+) -> impl IntoResponse {
+    let PaginationQuery { page, per_page } = qs;
+    let page = page.unwrap_or(0);
+    let per_page = per_page.unwrap_or(10);
+    let storage = &ctx.profile.storage;
+    let repo = storage.repository(project)?;
+    let doc = repo.identity_of(ctx.profile.id())?;
+    let releases = Releases::open(ctx.profile.public_key, &repo)?;
+    let releases = releases
+        .all()?
+        .into_iter()
+        .filter_map(|r| r.ok())
+        .map(|(id, release, _)| release_json(id.to_string(), &release, &doc))
+        .skip(page.saturating_mul(per_page))
+        .take(per_page)
+        .collect::<Vec<_>>();
+
+    Ok::<_, Error>(Json(releases))
+}
+
+/// Get project release.
+/// `GET /projects/:project/releases/:id`
+async fn release_handler(
+    State(ctx): State<Context>,
+    Path((project, release_id)): Path<(Id, Oid)>,
+) -> impl IntoResponse {
+    let storage = &ctx.profile.storage;
+    let repo = storage.repository(project)?;
+    let doc = repo.identity_of(ctx.profile.id())?;
+    let release = Releases::open(ctx.profile.public_key, &repo)?
+        .get(&release_id.into())?
+        .ok_or(Error::NotFound)?;
+
+    Ok::<_, Error>(Json(release_json(release_id.to_string(), &release, &doc)))
+}
+
+fn release_json<V>(id: String, release: &Release, doc: &Doc<V>) -> serde_json::Value {
+    json!({
+        "id": id,
+        "author": release.author(),
+        "oid": release.oid(),
+        "tag": release.tag(),
+        "version": release.version(),
+        "notes": release.notes(),
+        "artifacts": release.artifacts().collect::<Vec<_>>(),
+        "signatures": release
+            .signatures()
+            .map(|(signer, signature)| json!({ "signer": signer, "signature": signature }))
+            .collect::<Vec<_>>(),
+        "threshold": doc.threshold,
+        "attested": release.is_attested(doc),
+    })
+}
+
 #[derive(Serialize)]
 struct Author {
     id: PublicKey,
@@ -490,6 +942,25 @@ mod routes {
         );
     }
 
+    #[tokio::test]
+    async fn test_projects_root_pagination() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(test::seed(tmp.path()));
+        let response = request(&app, "/projects?page=1&per-page=1").await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.json().await, json!([]));
+
+        let response = request(
+            &app,
+            format!("/projects?page={}&per-page={}", usize::MAX, usize::MAX),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.json().await, json!([]));
+    }
+
     #[tokio::test]
     async fn test_projects() {
         let tmp = tempfile::tempdir().unwrap();
@@ -633,6 +1104,47 @@ mod routes {
         );
     }
 
+    #[tokio::test]
+    async fn test_projects_commits_pagination() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(test::seed(tmp.path()));
+        let response = request(
+            &app,
+            "/projects/rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp/commits?page=1&per-page=1",
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::FOUND);
+
+        let json = response.json().await;
+        let headers = json["headers"].as_array().unwrap();
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0]["header"]["sha1"], HEAD_1);
+
+        // Filtering by time returns all commits on the first page.
+        let response = request(
+            &app,
+            "/projects/rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp/commits?page=1&since=0",
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::FOUND);
+        assert_eq!(response.json().await["headers"], json!([]));
+
+        let response = request(
+            &app,
+            format!(
+                "/projects/rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp/commits?page={}&per-page={}",
+                usize::MAX,
+                usize::MAX
+            ),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::FOUND);
+        assert_eq!(response.json().await["headers"], json!([]));
+    }
+
     #[tokio::test]
     async fn test_projects_commits() {
         let tmp = tempfile::tempdir().unwrap();
@@ -699,6 +1211,35 @@ mod routes {
         );
     }
 
+    #[tokio::test]
+    async fn test_projects_activity() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(test::seed(tmp.path()));
+        let response = request(&app, "/projects/rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp/activity").await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let json = response.json().await;
+        let feed = json["feed"].as_array().unwrap();
+        let count = |kind: &str| feed.iter().filter(|e| e["type"] == kind).count();
+
+        assert_eq!(count("commit"), 2);
+        assert_eq!(count("issue.opened"), 1);
+        assert_eq!(count("identity.updated"), 1);
+        assert!(feed
+            .windows(2)
+            .all(|w| w[0]["timestamp"].as_i64() >= w[1]["timestamp"].as_i64()));
+
+        let response = request(
+            &app,
+            "/projects/rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp/activity?page=1&per-page=3",
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.json().await["feed"].as_array().unwrap().len(), 1);
+    }
+
     #[tokio::test]
     async fn test_projects_tree() {
         let tmp = tempfile::tempdir().unwrap();
@@ -907,6 +1448,44 @@ mod routes {
         );
     }
 
+    #[tokio::test]
+    async fn test_projects_releases_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(test::seed(tmp.path()));
+        let response = request(&app, "/projects/rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp/releases").await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.json().await, json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_projects_discussions_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(test::seed(tmp.path()));
+        let response = request(
+            &app,
+            "/projects/rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp/discussions",
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.json().await, json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_projects_issue_templates() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(test::seed(tmp.path()));
+        let response = request(
+            &app,
+            "/projects/rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp/issue-templates",
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.json().await, json!([]));
+    }
+
     #[tokio::test]
     async fn test_projects_issues_root() {
         let tmp = tempfile::tempdir().unwrap();
@@ -926,6 +1505,8 @@ mod routes {
                 "state": {
                     "status": "open"
                 },
+                "closedBy": null,
+                "reopenedBy": null,
                 "discussion": [
                   {
                     "author": {
@@ -942,4 +1523,71 @@ mod routes {
             ])
         );
     }
+
+    #[tokio::test]
+    async fn test_projects_issues_filter() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(test::seed(tmp.path()));
+        let response = request(
+            &app,
+            "/projects/rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp/issues?state=closed",
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.json().await, json!([]));
+
+        let response = request(
+            &app,
+            "/projects/rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp/issues?state=open&author=z6MknSLrJoTcukLrE435hVNQT4JUhbvWLX4kUzqkEStBU8Vi",
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.json().await.as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_projects_issues_ops() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(test::seed(tmp.path()));
+        let path = "/projects/rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp/issues/458bbd9f6d47eed3d60cd905141687ad1f99251e/ops";
+
+        let response = request(&app, format!("{path}?per-page=1")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let changes = response.json().await;
+        let changes = changes.as_array().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(
+            changes[0]["id"],
+            json!("458bbd9f6d47eed3d60cd905141687ad1f99251e")
+        );
+        assert_eq!(
+            changes[0]["author"],
+            json!("z6MknSLrJoTcukLrE435hVNQT4JUhbvWLX4kUzqkEStBU8Vi")
+        );
+
+        let response = request(&app, format!("{path}?page=1&per-page=1")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.json().await, json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_projects_issues_delete() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let path = "/projects/rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp/issues/458bbd9f6d47eed3d60cd905141687ad1f99251e";
+
+        let response = test::delete(&app, path, None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let session = test::authorize(&ctx).await;
+        let response = test::delete(&app, path, Some(&session)).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let response = request(&app, path).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
 }
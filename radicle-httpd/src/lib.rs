@@ -33,6 +33,8 @@ use radicle::profile::Profile;
 use error::Error;
 
 mod api;
+#[cfg(feature = "web")]
+mod web;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -62,9 +64,11 @@ pub async fn run(options: Options) -> anyhow::Result<()> {
 
     tracing::info!("listening on http://{}", options.listen);
 
-    let app = Router::new()
-        .merge(git_router)
-        .nest("/api", api_router)
+    let app = Router::new().merge(git_router).nest("/api", api_router);
+    #[cfg(feature = "web")]
+    let app = app.fallback(web::handler);
+
+    let app = app
         .layer(
             TraceLayer::new_for_http()
                 .make_span_with(|request: &Request<Body>| {
@@ -103,7 +107,20 @@ async fn git_handler(
     body: Bytes,
 ) -> impl IntoResponse {
     let query = query.0.unwrap_or_default();
-    let id: Id = project.strip_suffix(".git").unwrap_or(&project).parse()?;
+    let id = project
+        .strip_suffix(".git")
+        .unwrap_or(&project)
+        .parse::<Id>();
+
+    // Paths that aren't git requests belong to the web interface, when it's embedded.
+    #[cfg(feature = "web")]
+    if id.is_err() {
+        return Ok(web::serve(&format!(
+            "/{project}/{}",
+            request.trim_start_matches('/')
+        )));
+    }
+    let id = id?;
 
     let (status, headers, body) =
         git_http_backend(&profile, method, headers, body, remote, id, &request, query).await?;
@@ -116,7 +133,7 @@ async fn git_handler(
         }
     }
 
-    Ok::<_, Error>((status, response_headers, body))
+    Ok::<_, Error>((status, response_headers, body).into_response())
 }
 
 async fn git_http_backend(
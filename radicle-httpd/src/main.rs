@@ -41,8 +41,9 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-/// Parse command-line arguments into HTTP options.
-fn parse_options() -> Result<httpd::Options, lexopt::Error> {
+/// Parse command-line arguments into HTTP options. The listen address falls back to the
+/// one configured in the profile.
+fn parse_options() -> anyhow::Result<httpd::Options> {
     use lexopt::prelude::*;
 
     let mut parser = lexopt::Parser::from_env();
@@ -58,10 +59,15 @@ fn parse_options() -> Result<httpd::Options, lexopt::Error> {
                 println!("usage: radicle-httpd [--listen <addr>]");
                 process::exit(0);
             }
-            _ => return Err(arg.unexpected()),
+            _ => return Err(arg.unexpected().into()),
         }
     }
-    Ok(httpd::Options {
-        listen: listen.unwrap_or_else(|| ([0, 0, 0, 0], 8080).into()),
-    })
+    let listen = match listen {
+        Some(listen) => listen,
+        None => {
+            let home = radicle::profile::home()?;
+            radicle::profile::Config::load(&home.config())?.httpd_listen()
+        }
+    };
+    Ok(httpd::Options { listen })
 }
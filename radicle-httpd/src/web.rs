@@ -0,0 +1,107 @@
+//! Embedded web interface.
+//!
+//! The compiled assets of the web interface are embedded in the binary when building with
+//! the `web` feature. They are read from the `web` directory of this crate, which should
+//! contain the output of the web interface build, eg. `index.html` and `assets/`. Tests
+//! embed the `fixtures/web` directory instead.
+use std::path::Path;
+
+use axum::body::{boxed, Full};
+use axum::http::{header, HeaderValue, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use rust_embed::RustEmbed;
+
+/// Cache header for content-addressed assets, which never change.
+const CACHE_IMMUTABLE: &str = "public, max-age=31536000, immutable";
+/// Cache header for other static files.
+const CACHE_1_HOUR: &str = "public, max-age=3600, must-revalidate";
+/// Cache header for the index, which must always be revalidated to pick up new assets.
+const CACHE_NONE: &str = "no-cache";
+
+#[derive(RustEmbed)]
+#[cfg_attr(not(test), folder = "web/")]
+#[cfg_attr(test, folder = "fixtures/web/")]
+#[exclude = ".gitignore"]
+struct Assets;
+
+/// Fallback handler serving the web interface.
+pub async fn handler(uri: Uri) -> Response {
+    serve(uri.path())
+}
+
+/// Serve the asset at the given path. Paths that don't have a file extension are routes of
+/// the web interface, and are served the index, so that routing happens client-side.
+pub fn serve(path: &str) -> Response {
+    let path = path.trim_start_matches('/');
+
+    if let Some(file) = Assets::get(path) {
+        let cache = if path.starts_with("assets/") {
+            CACHE_IMMUTABLE
+        } else if path == "index.html" {
+            CACHE_NONE
+        } else {
+            CACHE_1_HOUR
+        };
+        return asset(file, cache);
+    }
+    if Path::new(path).extension().is_some() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    match Assets::get("index.html") {
+        Some(file) => asset(file, CACHE_NONE),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+fn asset(file: rust_embed::EmbeddedFile, cache: &'static str) -> Response {
+    let mime = file.metadata.mimetype().to_owned();
+    let mut response = Response::new(boxed(Full::from(file.data)));
+    let headers = response.headers_mut();
+
+    if let Ok(mime) = HeaderValue::from_str(&mime) {
+        headers.insert(header::CONTENT_TYPE, mime);
+    }
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(cache));
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serve_asset() {
+        let response = serve("/assets/index.js");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/javascript"
+        );
+        assert_eq!(response.headers()[header::CACHE_CONTROL], CACHE_IMMUTABLE);
+
+        let response = serve("/assets/index.css");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/css");
+
+        let response = serve("/favicon.svg");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/svg+xml");
+        assert_eq!(response.headers()[header::CACHE_CONTROL], CACHE_1_HOUR);
+    }
+
+    #[test]
+    fn test_serve_index() {
+        for path in [
+            "/",
+            "/index.html",
+            "/projects/rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp",
+        ] {
+            let response = serve(path);
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()[header::CONTENT_TYPE], "text/html");
+            assert_eq!(response.headers()[header::CACHE_CONTROL], CACHE_NONE);
+        }
+        assert_eq!(serve("/missing.png").status(), StatusCode::NOT_FOUND);
+    }
+}
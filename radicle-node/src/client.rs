@@ -4,6 +4,9 @@ use crossbeam_channel as chan;
 use cyphernet::{Cert, EcSign};
 use netservices::resource::NetAccept;
 use radicle::profile::Home;
+use radicle::storage::git::migrate;
+use radicle::storage::hooks::Hooks;
+use radicle::storage::index::{self, Index};
 use radicle::Storage;
 use reactor::poller::popol;
 use reactor::Reactor;
@@ -12,6 +15,8 @@ use thiserror::Error;
 use crate::address;
 use crate::control;
 use crate::crypto::{Signature, Signer};
+use crate::maintenance::Maintainer;
+use crate::mirror::Mirrorer;
 use crate::node::NodeId;
 use crate::service::{routing, tracking};
 use crate::wire;
@@ -49,6 +54,12 @@ pub enum Error {
     /// A control socket error.
     #[error("control socket error: {0}")]
     Control(#[from] control::Error),
+    /// A storage migration error.
+    #[error("storage migration error: {0}")]
+    Migrate(#[from] migrate::Error),
+    /// A refs index error.
+    #[error("refs index error: {0}")]
+    Index(#[from] index::Error),
 }
 
 /// Holds join handles to the client threads, as well as a client handle.
@@ -58,6 +69,8 @@ pub struct Runtime<G: Signer + EcSign> {
     pub control: thread::JoinHandle<Result<(), control::Error>>,
     pub reactor: Reactor<wire::Control<G>>,
     pub pool: WorkerPool,
+    pub maintenance: thread::JoinHandle<()>,
+    pub mirror: thread::JoinHandle<()>,
     pub local_addrs: Vec<net::SocketAddr>,
 }
 
@@ -81,10 +94,12 @@ impl<G: Signer + EcSign> Runtime<G> {
         let network = config.network;
         let rng = fastrand::Rng::new();
         let clock = LocalTime::now();
-        let storage = Storage::open(home.storage())?;
+        let storage = Storage::open(home.storage())?.with_hooks(Hooks::load(home.hooks())?);
+        storage.migrate()?;
         let address_db = node_dir.join(ADDRESS_DB_FILE);
         let routing_db = node_dir.join(ROUTING_DB_FILE);
         let tracking_db = node_dir.join(TRACKING_DB_FILE);
+        let index_db = home.index();
 
         log::info!("Opening address book {}..", address_db.display());
         let addresses = address::Book::open(address_db)?;
@@ -95,7 +110,16 @@ impl<G: Signer + EcSign> Runtime<G> {
         log::info!("Opening tracking policy table {}..", tracking_db.display());
         let tracking = tracking::Config::open(tracking_db)?;
 
+        log::info!("Opening refs index {}..", index_db.display());
+        let index = Index::open(index_db)?;
+        if index.is_empty()? {
+            log::info!("Indexing storage..");
+            index.reindex(&storage)?;
+        }
+        storage.hooks().register(index);
+
         log::info!("Initializing service ({:?})..", network);
+        let maintenance = config.maintenance.clone();
         let service = service::Service::new(
             config,
             clock,
@@ -107,13 +131,23 @@ impl<G: Signer + EcSign> Runtime<G> {
             rng,
         );
 
+        let locks = service.locks().clone();
         let cert = Cert {
             pk: id,
             sig: EcSign::sign(&signer, id.as_slice()),
         };
 
         let (worker_send, worker_recv) = chan::unbounded::<WorkerReq<G>>();
-        let mut wire = Wire::new(service, worker_send, cert, signer, proxy, clock);
+        let (events_send, events_recv) = chan::unbounded::<service::Event>();
+        let mut wire = Wire::new(
+            service,
+            worker_send,
+            events_send,
+            cert,
+            signer,
+            proxy,
+            clock,
+        );
         let mut local_addrs = Vec::new();
 
         for addr in listen {
@@ -135,11 +169,16 @@ impl<G: Signer + EcSign> Runtime<G> {
         let pool = WorkerPool::with(
             8,
             time::Duration::from_secs(9),
-            storage,
+            storage.clone(),
             worker_recv,
             handle.clone(),
+            locks.clone(),
             id.to_human(),
         );
+        let mirror = Mirrorer::new(storage.clone(), locks.clone(), events_recv)
+            .spawn(format!("{}-mirror", id.to_human()))?;
+        let maintenance =
+            Maintainer::new(storage, locks, maintenance).spawn(format!("{}-gc", id.to_human()))?;
 
         Ok(Runtime {
             id,
@@ -147,6 +186,8 @@ impl<G: Signer + EcSign> Runtime<G> {
             reactor,
             handle,
             pool,
+            maintenance,
+            mirror,
             local_addrs,
         })
     }
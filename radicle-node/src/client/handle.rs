@@ -9,6 +9,7 @@ use thiserror::Error;
 
 use crate::crypto::Signer;
 use crate::identity::Id;
+use crate::node::Info;
 use crate::profile::Home;
 use crate::service;
 use crate::service::{CommandError, FetchLookup, QueryState};
@@ -16,6 +17,9 @@ use crate::service::{NodeId, Sessions};
 use crate::wire;
 use crate::worker::WorkerResp;
 
+/// Agent string reported by the node.
+pub const AGENT: &str = concat!("radicle-node/", env!("CARGO_PKG_VERSION"));
+
 /// An error resulting from a handle method.
 #[derive(Error, Debug)]
 pub enum Error {
@@ -190,6 +194,29 @@ impl<G: Signer + EcSign + 'static> radicle::node::Handle for Handle<G> {
         Ok(receiver)
     }
 
+    fn info(&self) -> Result<Info, Error> {
+        let (sender, receiver) = chan::bounded(1);
+        let query: Arc<QueryState> = Arc::new(move |state| {
+            let info = Info {
+                id: state.node_id(),
+                agent: AGENT.to_owned(),
+                peers: state.sessions().negotiated().count(),
+                repos: state.tracking().repo_entries()?.count(),
+                uptime: (*state.clock() - state.start_time()).as_secs(),
+            };
+            sender.send(info).ok();
+
+            Ok(())
+        });
+        let (err_sender, err_receiver) = chan::bounded(1);
+        self.command(service::Command::QueryState(query, err_sender))?;
+        err_receiver.recv()??;
+
+        let info = receiver.recv()?;
+
+        Ok(info)
+    }
+
     fn shutdown(self) -> Result<(), Error> {
         // If the current value is `false`, set it to `true`, otherwise error.
         if self
@@ -209,6 +209,13 @@ fn drain<H: Handle<Error = client::handle::Error, FetchLookup = FetchLookup>>(
                     }
                     Err(e) => return Err(DrainError::Client(e)),
                 },
+                "info" => match handle.info() {
+                    Ok(info) => {
+                        let json = serde_json::to_string(&info).map_err(io::Error::from)?;
+                        writeln!(writer, "{json}")?;
+                    }
+                    Err(e) => return Err(DrainError::Client(e)),
+                },
                 "shutdown" => {
                     return Err(DrainError::Shutdown);
                 }
@@ -352,4 +359,31 @@ mod tests {
         assert!(handle.untrack_node(peer).unwrap());
         assert!(!handle.untrack_node(peer).unwrap());
     }
+
+    #[test]
+    fn test_info() {
+        let tmp = tempfile::tempdir().unwrap();
+        let socket = tmp.path().join("node.sock");
+        let proj = test::arbitrary::gen::<Id>(1);
+
+        thread::spawn({
+            let socket = socket.clone();
+            let handle = crate::test::handle::Handle::default();
+
+            move || crate::control::listen(socket, handle)
+        });
+
+        let mut handle = loop {
+            if let Ok(conn) = Node::connect(&socket) {
+                break conn;
+            }
+        };
+
+        assert_eq!(handle.info().unwrap().repos, 0);
+        assert!(handle.track_repo(proj).unwrap());
+
+        let info = handle.info().unwrap();
+        assert_eq!(info.repos, 1);
+        assert_eq!(info.agent, crate::client::handle::AGENT);
+    }
 }
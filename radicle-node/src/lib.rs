@@ -5,6 +5,8 @@ pub mod clock;
 pub mod control;
 pub mod deserializer;
 pub mod logger;
+pub mod maintenance;
+pub mod mirror;
 pub mod service;
 pub mod sql;
 #[cfg(any(test, feature = "test"))]
@@ -45,6 +45,15 @@ impl Options {
                 Long("limit-routing-max-size") => {
                     limits.routing_max_size = parser.value()?.parse()?;
                 }
+                Long("limit-storage-size") => {
+                    limits.quota.total = Some(parser.value()?.parse()?);
+                }
+                Long("limit-repository-size") => {
+                    limits.quota.repository = Some(parser.value()?.parse()?);
+                }
+                Long("limit-storage-evict") => {
+                    limits.quota.policy = service::config::QuotaPolicy::Evict;
+                }
                 Long("listen") => {
                     let addr = parser.value()?.parse()?;
                     listen.push(addr);
@@ -78,19 +87,30 @@ fn main() -> anyhow::Result<()> {
 
     let options = Options::from_env()?;
     let home = profile::home()?;
+    let profile = profile::Config::load(&home.config())?;
     let passphrase = env::var(profile::env::RAD_PASSPHRASE)
         .context("`RAD_PASSPHRASE` is required to be set for the node to establish connections")?
         .into();
     let keystore = Keystore::new(&home.keys());
     let signer = MemorySigner::load(&keystore, passphrase)?;
+    let connect = options
+        .connect
+        .into_iter()
+        .chain(profile.connect().map(|p| (p.id, p.addr.clone())))
+        .collect();
+    let listen = if options.listen.is_empty() {
+        profile.listen().to_vec()
+    } else {
+        options.listen
+    };
     let config = service::Config {
-        connect: options.connect.into_iter().collect(),
+        connect,
         external_addresses: options.external_addresses,
         limits: options.limits,
         ..service::Config::default()
     };
     let proxy = net::SocketAddr::new(net::Ipv4Addr::LOCALHOST.into(), 9050);
-    let runtime = Runtime::with(home, config, options.listen, proxy, signer)?;
+    let runtime = Runtime::with(home, config, listen, proxy, signer)?;
 
     runtime.run()?;
 
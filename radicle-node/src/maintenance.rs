@@ -0,0 +1,271 @@
+//! Git maintenance of stored repositories.
+//!
+//! Fetching, and creating collaborative objects, leaves loose objects behind in storage.
+//! The [`Maintainer`] periodically checks repositories and runs `git gc` on the ones that
+//! have accumulated too many loose objects. Fetches hold a shared [`Locks`] entry for the
+//! repository they write to, and maintenance is skipped for repositories that are being
+//! fetched, to be retried on the next run. Conversely, fetches made by the service thread
+//! don't wait for maintenance to finish, and are retried later instead.
+//!
+//! When a remote is removed from a repository, its objects are left behind in the
+//! repository's object database. Such repositories are marked for pruning, and the
+//! maintainer removes the unreachable objects that are older than the configured expiry
+//! from them on its next run. Since processes other than the node may write to storage,
+//! younger objects are kept.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock, TryLockError};
+use std::{io, thread, time};
+
+use radicle::identity::Id;
+use radicle::storage::git::{Repository, Storage};
+use radicle::storage::{ReadStorage, WriteStorage};
+
+use crate::service::config::Maintenance;
+
+/// Per-repository locks, coordinating fetches with maintenance.
+#[derive(Debug, Clone, Default)]
+pub struct Locks(Arc<Mutex<HashMap<Id, Arc<RwLock<()>>>>>);
+
+impl Locks {
+    /// Run a fetch on the given repository. Multiple fetches may run concurrently,
+    /// but not while the repository is under maintenance.
+    pub fn fetch<T>(&self, repo: Id, f: impl FnOnce() -> T) -> T {
+        let lock = self.get(repo);
+        let _guard = lock.read().unwrap_or_else(|e| e.into_inner());
+
+        f()
+    }
+
+    /// Run a fetch on the given repository, if it isn't under maintenance.
+    /// Returns `None` if the repository is busy.
+    pub fn try_fetch<T>(&self, repo: Id, f: impl FnOnce() -> T) -> Option<T> {
+        let lock = self.get(repo);
+        let _guard = match lock.try_read() {
+            Ok(guard) => guard,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => return None,
+        };
+        Some(f())
+    }
+
+    /// Run maintenance on the given repository, if it isn't being fetched.
+    /// Returns `None` if the repository is busy.
+    pub fn try_maintain<T>(&self, repo: Id, f: impl FnOnce() -> T) -> Option<T> {
+        let lock = self.get(repo);
+        let _guard = match lock.try_write() {
+            Ok(guard) => guard,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => return None,
+        };
+        Some(f())
+    }
+
+    fn get(&self, repo: Id) -> Arc<RwLock<()>> {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(repo)
+            .or_default()
+            .clone()
+    }
+}
+
+/// Outcome of a maintenance check on a repository.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The repository was packed.
+    Packed,
+    /// Unreachable objects were pruned from the repository.
+    Pruned,
+    /// The repository is below the maintenance thresholds.
+    Skipped,
+    /// The repository is being fetched.
+    Busy,
+}
+
+/// Runs git maintenance on stored repositories.
+pub struct Maintainer {
+    storage: Storage,
+    locks: Locks,
+    config: Maintenance,
+}
+
+impl Maintainer {
+    pub fn new(storage: Storage, locks: Locks, config: Maintenance) -> Self {
+        Self {
+            storage,
+            locks,
+            config,
+        }
+    }
+
+    /// Spawn the maintenance thread.
+    pub fn spawn(self, name: String) -> io::Result<thread::JoinHandle<()>> {
+        thread::Builder::new().name(name).spawn(move || self.run())
+    }
+
+    /// Run maintenance on a schedule. Never returns.
+    pub fn run(self) {
+        let interval: time::Duration = self.config.interval.into();
+
+        loop {
+            thread::sleep(interval);
+
+            if let Err(err) = self.maintain_all() {
+                log::error!(target: "maintenance", "Error running maintenance: {err}");
+            }
+        }
+    }
+
+    /// Run maintenance on all repositories that need it.
+    pub fn maintain_all(&self) -> Result<(), radicle::storage::Error> {
+        for id in self.storage.inventory()? {
+            match self.maintain(id) {
+                Ok(Outcome::Packed) => {
+                    log::debug!(target: "maintenance", "Packed repository {id}");
+                }
+                Ok(Outcome::Pruned) => {
+                    log::debug!(target: "maintenance", "Pruned repository {id}");
+                }
+                Ok(Outcome::Busy) => {
+                    log::debug!(target: "maintenance", "Repository {id} is busy, skipping..");
+                }
+                Ok(Outcome::Skipped) => {}
+                Err(err) => {
+                    log::error!(target: "maintenance", "Error maintaining repository {id}: {err}");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Run maintenance on a repository, if it has reached one of the thresholds, or
+    /// needs pruning.
+    pub fn maintain(&self, id: Id) -> Result<Outcome, radicle::storage::Error> {
+        let repo = self.storage.repository(id)?;
+
+        if repo.is_prune_pending()? {
+            let expiry = self.config.prune_expiry.into();
+
+            return match self.locks.try_maintain(id, || repo.prune(expiry)) {
+                Some(result) => result.map(|()| Outcome::Pruned).map_err(From::from),
+                None => Ok(Outcome::Busy),
+            };
+        }
+        if !self.is_needed(&repo)? {
+            return Ok(Outcome::Skipped);
+        }
+        match self.locks.try_maintain(id, || repo.gc()) {
+            Some(result) => result.map(|()| Outcome::Packed).map_err(From::from),
+            None => Ok(Outcome::Busy),
+        }
+    }
+
+    fn is_needed(&self, repo: &Repository) -> Result<bool, io::Error> {
+        let (count, size) = repo.loose_objects()?;
+
+        Ok(count >= self.config.loose_objects || size >= self.config.loose_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::crypto::test::signer::MockSigner;
+    use crate::crypto::Signer as _;
+    use crate::test::fixtures;
+    use crate::LocalDuration;
+
+    #[test]
+    fn test_maintain() {
+        let tmp = tempfile::tempdir().unwrap();
+        let signer = MockSigner::default();
+        let storage = fixtures::storage(tmp.path(), &signer).unwrap();
+        let id = *storage.inventory().unwrap().first().unwrap();
+        let locks = Locks::default();
+        let maintainer = Maintainer::new(
+            storage,
+            locks.clone(),
+            Maintenance {
+                loose_objects: 1,
+                ..Maintenance::default()
+            },
+        );
+
+        // Maintenance is skipped while the repository is being fetched.
+        let (started, wait) = mpsc::channel();
+        let (done, finish) = mpsc::channel::<()>();
+        let fetch = thread::spawn({
+            let locks = locks.clone();
+            move || {
+                locks.fetch(id, || {
+                    started.send(()).unwrap();
+                    finish.recv().unwrap();
+                })
+            }
+        });
+        wait.recv().unwrap();
+        assert_eq!(maintainer.maintain(id).unwrap(), Outcome::Busy);
+
+        done.send(()).unwrap();
+        fetch.join().unwrap();
+        assert_eq!(maintainer.maintain(id).unwrap(), Outcome::Packed);
+    }
+
+    #[test]
+    fn test_prune_removed_remote() {
+        let tmp = tempfile::tempdir().unwrap();
+        let signer = MockSigner::default();
+        let storage = fixtures::storage(tmp.path(), &signer).unwrap();
+        let id = *storage.inventory().unwrap().first().unwrap();
+        let maintainer = Maintainer::new(storage.clone(), Locks::default(), Maintenance::default());
+        let repo = storage.repository(id).unwrap();
+        let remote = *signer.public_key();
+
+        // Create an object only reachable from the remote's namespace.
+        let blob = repo.raw().blob(b"dangling").unwrap();
+        let tree = {
+            let mut builder = repo.raw().treebuilder(None).unwrap();
+            builder.insert("file", blob, 0o100644).unwrap();
+            builder.write().unwrap()
+        };
+        repo.raw()
+            .reference(
+                &format!("refs/namespaces/{remote}/refs/tags/tree"),
+                tree,
+                false,
+                "test",
+            )
+            .unwrap();
+        assert!(!repo.is_prune_pending().unwrap());
+        assert_eq!(maintainer.maintain(id).unwrap(), Outcome::Skipped);
+
+        repo.remove_remote(&remote).unwrap();
+        assert!(repo.is_prune_pending().unwrap());
+        assert!(repo.raw().find_blob(blob).is_ok());
+
+        // Objects younger than the expiry are kept.
+        assert_eq!(maintainer.maintain(id).unwrap(), Outcome::Pruned);
+        assert!(!repo.is_prune_pending().unwrap());
+        let reopened = storage.repository(id).unwrap();
+        assert!(reopened.raw().find_blob(blob).is_ok());
+
+        let maintainer = Maintainer::new(
+            storage.clone(),
+            Locks::default(),
+            Maintenance {
+                prune_expiry: LocalDuration::from_secs(0),
+                ..Maintenance::default()
+            },
+        );
+        repo.remove_remote(&remote).unwrap();
+        assert_eq!(maintainer.maintain(id).unwrap(), Outcome::Pruned);
+        assert!(!repo.is_prune_pending().unwrap());
+
+        let repo = storage.repository(id).unwrap();
+        assert!(repo.raw().find_blob(blob).is_err());
+        assert!(repo.raw().find_tree(tree).is_err());
+    }
+}
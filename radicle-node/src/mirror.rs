@@ -0,0 +1,114 @@
+//! Mirroring of stored repositories to external git remotes.
+//!
+//! The [`Mirrorer`] listens for service events, and whenever the references of a repository
+//! are updated, either by a fetch or by the local user, pushes the repository to the mirrors
+//! configured for it. See [`radicle::storage::git::mirror`] for how mirrors are configured.
+use std::{io, thread};
+
+use crossbeam_channel as chan;
+use radicle::identity::Id;
+use radicle::storage::git::mirror;
+use radicle::storage::git::Storage;
+use radicle::storage::WriteStorage;
+
+use crate::maintenance::Locks;
+use crate::service::Event;
+
+/// Pushes stored repositories to their mirrors.
+pub struct Mirrorer {
+    storage: Storage,
+    locks: Locks,
+    events: chan::Receiver<Event>,
+}
+
+impl Mirrorer {
+    pub fn new(storage: Storage, locks: Locks, events: chan::Receiver<Event>) -> Self {
+        Self {
+            storage,
+            locks,
+            events,
+        }
+    }
+
+    /// Spawn the mirroring thread.
+    pub fn spawn(self, name: String) -> io::Result<thread::JoinHandle<()>> {
+        thread::Builder::new().name(name).spawn(move || self.run())
+    }
+
+    /// Process events until the event channel is disconnected.
+    pub fn run(self) {
+        while let Ok(event) = self.events.recv() {
+            let id = match event {
+                Event::RefsAnnounced { project } => project,
+                Event::RefsFetched {
+                    project, updated, ..
+                } if !updated.is_empty() => project,
+                _ => continue,
+            };
+            if let Err(err) = self.mirror(id) {
+                log::error!(target: "mirror", "Error mirroring repository {id}: {err}");
+            }
+        }
+    }
+
+    /// Push a repository to all of its mirrors. Returns the number of mirrors that
+    /// were successfully pushed to.
+    pub fn mirror(&self, id: Id) -> Result<usize, mirror::Error> {
+        let repo = self.storage.repository(id)?;
+        let results = self.locks.fetch(id, || repo.push_mirrors())?;
+        let mut count = 0;
+
+        for (mirror, result) in results {
+            match result {
+                Ok(refs) => {
+                    log::debug!(
+                        target: "mirror",
+                        "Pushed {} reference(s) of {id} to mirror `{}`", refs.len(), mirror.name
+                    );
+                    count += 1;
+                }
+                Err(err) => {
+                    log::error!(
+                        target: "mirror",
+                        "Error pushing {id} to mirror `{}`: {err}", mirror.name
+                    );
+                }
+            }
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::test::signer::MockSigner;
+    use crate::test::fixtures;
+    use radicle::git;
+    use radicle::storage::ReadStorage;
+
+    #[test]
+    fn test_mirror() {
+        let tmp = tempfile::tempdir().unwrap();
+        let signer = MockSigner::default();
+        let storage = fixtures::storage(tmp.path(), &signer).unwrap();
+        let id = *storage.inventory().unwrap().first().unwrap();
+        let remote = git::raw::Repository::init_bare(tmp.path().join("mirror")).unwrap();
+        let (_, events) = chan::unbounded();
+        let mirrorer = Mirrorer::new(storage.clone(), Locks::default(), events);
+
+        // Nothing to push to.
+        assert_eq!(mirrorer.mirror(id).unwrap(), 0);
+
+        storage
+            .repository(id)
+            .unwrap()
+            .add_mirror(&mirror::Mirror::new(
+                "local",
+                git::url::File::new(remote.path().to_path_buf()).to_string(),
+            ))
+            .unwrap();
+        assert_eq!(mirrorer.mirror(id).unwrap(), 1);
+        assert!(!remote.is_empty().unwrap());
+    }
+}
@@ -9,10 +9,10 @@ pub mod session;
 pub mod tracking;
 
 use std::collections::hash_map::Entry;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::ops::{Deref, DerefMut};
 use std::sync::Arc;
-use std::{fmt, io, net, str};
+use std::{fmt, io, mem, net, str};
 
 use crossbeam_channel as chan;
 use fastrand::Rng;
@@ -26,13 +26,15 @@ use crate::address;
 use crate::address::AddressBook;
 use crate::clock::Timestamp;
 use crate::crypto;
+use crate::crypto::revocation::{Revocations, SignedRevocation};
 use crate::crypto::{Signer, Verified};
 use crate::git;
 use crate::identity::{Doc, Id};
+use crate::maintenance::Locks;
 use crate::node;
 use crate::prelude::*;
 use crate::service::message::{Announcement, AnnouncementMessage, Ping};
-use crate::service::message::{NodeAnnouncement, RefsAnnouncement};
+use crate::service::message::{NodeAnnouncement, RefsAnnouncement, RevocationAnnouncement};
 use crate::service::session::Protocol;
 use crate::storage;
 use crate::storage::{Inventory, ReadRepository, RefUpdate, WriteRepository, WriteStorage};
@@ -45,7 +47,7 @@ pub use crate::service::session::Session;
 
 use self::gossip::Gossip;
 use self::message::InventoryAnnouncement;
-use self::reactor::Reactor;
+use self::reactor::{Fetch, Reactor};
 
 /// Target number of peers to maintain connections to.
 pub const TARGET_OUTBOUND_PEERS: usize = 8;
@@ -67,6 +69,11 @@ pub const MAX_TIME_DELTA: LocalDuration = LocalDuration::from_mins(60);
 pub const MAX_CONNECTION_ATTEMPTS: usize = 3;
 /// How far back from the present time should we request gossip messages when connecting to a peer.
 pub const SUBSCRIBE_BACKLOG_DELTA: LocalDuration = LocalDuration::from_mins(60);
+/// Maximum number of key revocations kept by the service.
+pub const MAX_REVOCATIONS: usize = 1024;
+/// How long to wait before retrying a fetch that was deferred because the repository was
+/// under maintenance.
+pub const FETCH_RETRY_INTERVAL: LocalDuration = LocalDuration::from_secs(10);
 
 /// Maximum external address limit imposed by message size limits.
 pub use message::ADDRESS_LIMIT;
@@ -74,16 +81,8 @@ pub use message::ADDRESS_LIMIT;
 pub use message::INVENTORY_LIMIT;
 /// Maximum number of project git references imposed by message size limits.
 pub use message::REF_LIMIT;
-
-/// A service event.
-#[derive(Debug, Clone)]
-pub enum Event {
-    RefsFetched {
-        from: NodeId,
-        project: Id,
-        updated: Vec<RefUpdate>,
-    },
-}
+/// Events emitted by the service.
+pub use radicle::node::Event;
 
 /// General service error.
 #[derive(thiserror::Error, Debug)]
@@ -109,6 +108,17 @@ pub enum FetchError {
     Io(#[from] io::Error),
     #[error(transparent)]
     Project(#[from] storage::ProjectError),
+    #[error(transparent)]
+    Quota(#[from] QuotaError),
+}
+
+/// Error returned when a fetch would exceed the storage quota.
+#[derive(thiserror::Error, Debug)]
+pub enum QuotaError {
+    #[error("repository {id} has a size of {size} bytes, exceeding the quota of {quota} bytes")]
+    Repository { id: Id, size: u64, quota: u64 },
+    #[error("storage has a size of {size} bytes, exceeding the quota of {quota} bytes")]
+    Total { size: u64, quota: u64 },
 }
 
 /// Result of looking up seeds in our routing table.
@@ -172,6 +182,8 @@ pub enum Command {
     UntrackNode(NodeId, chan::Sender<bool>),
     /// Query the internal service state.
     QueryState(Arc<QueryState>, chan::Sender<Result<(), CommandError>>),
+    /// Announce a key revocation to peers.
+    AnnounceRevocation(SignedRevocation),
 }
 
 impl fmt::Debug for Command {
@@ -185,6 +197,7 @@ impl fmt::Debug for Command {
             Self::TrackNode(id, _, _) => write!(f, "TrackNode({})", id),
             Self::UntrackNode(id, _) => write!(f, "UntrackNode({})", id),
             Self::QueryState { .. } => write!(f, "QueryState(..)"),
+            Self::AnnounceRevocation(r) => write!(f, "AnnounceRevocation({})", r.revocation.key),
         }
     }
 }
@@ -196,6 +209,8 @@ pub enum CommandError {
     Storage(#[from] storage::Error),
     #[error(transparent)]
     Routing(#[from] routing::Error),
+    #[error(transparent)]
+    Tracking(#[from] tracking::Error),
 }
 
 #[derive(Debug)]
@@ -228,6 +243,22 @@ pub struct Service<R, A, S, G> {
     out_of_sync: bool,
     /// Current tracked repository bloom filter.
     filter: Filter,
+    /// Key revocations received from the network, or announced by us. These only decide
+    /// which revocations are relayed: revocations are enforced by storage and COBs from
+    /// the identity document of each repository, see [`Doc::revocations`].
+    revocations: Revocations,
+    /// Repositories in which each key is a delegate, device or remote, used to authorize
+    /// revocations. Computed on the first revocation received, and kept up to date as
+    /// repositories are fetched.
+    keys: Option<BTreeMap<PublicKey, BTreeSet<Id>>>,
+    /// Repository locks, shared with the maintenance subsystem.
+    locks: Locks,
+    /// On-disk size of each repository in storage, used to check quotas. Computed on
+    /// the first quota check, and kept up to date as repositories are fetched.
+    sizes: Option<BTreeMap<Id, u64>>,
+    /// Fetches deferred because the repository was under maintenance, and the peer
+    /// to fetch from.
+    deferred: BTreeMap<Id, NodeId>,
     /// Last time the service was idle.
     last_idle: LocalTime,
     /// Last time the service synced.
@@ -253,6 +284,11 @@ where
     pub fn local_time(&self) -> LocalTime {
         self.clock
     }
+
+    /// Get the repository locks used to coordinate fetches with maintenance.
+    pub fn locks(&self) -> &Locks {
+        &self.locks
+    }
 }
 
 impl<R, A, S, G> Service<R, A, S, G>
@@ -290,6 +326,11 @@ where
             sessions,
             out_of_sync: false,
             filter: Filter::empty(),
+            revocations: Revocations::default(),
+            keys: None,
+            sizes: None,
+            deferred: BTreeMap::new(),
+            locks: Locks::default(),
             last_idle: LocalTime::default(),
             last_sync: LocalTime::default(),
             last_prune: LocalTime::default(),
@@ -319,6 +360,144 @@ where
         self.tracking.untrack_repo(id)
     }
 
+    /// Check that fetching the given repository doesn't exceed the storage quota.
+    /// Depending on the quota policy, untracked repositories may be evicted to make room.
+    pub fn check_quota(&mut self, id: &Id) -> Result<(), FetchError> {
+        let quota = &self.config.limits.quota;
+        if quota.is_unlimited() {
+            return Ok(());
+        }
+        if self.sizes.is_none() {
+            let mut sizes = BTreeMap::new();
+            for rid in self.storage.inventory()? {
+                sizes.insert(rid, self.storage.repository(rid)?.disk_size()?);
+            }
+            self.sizes = Some(sizes);
+        }
+        let sizes = self.sizes.get_or_insert_with(BTreeMap::new);
+
+        if let Some(limit) = quota.repository {
+            if let Some(size) = sizes.get(id) {
+                if *size >= limit {
+                    return Err(QuotaError::Repository {
+                        id: *id,
+                        size: *size,
+                        quota: limit,
+                    }
+                    .into());
+                }
+            }
+        }
+        let Some(limit) = quota.total else {
+            return Ok(());
+        };
+        let mut total = sizes.values().sum::<u64>();
+
+        if total >= limit && quota.policy == config::QuotaPolicy::Evict {
+            // Evict the largest untracked repositories first.
+            let mut largest = sizes.iter().map(|(r, s)| (*r, *s)).collect::<Vec<_>>();
+            largest.sort_by_key(|(_, s)| std::cmp::Reverse(*s));
+
+            for (rid, size) in largest {
+                if total < limit {
+                    break;
+                }
+                if rid == *id || self.tracking.is_repo_tracked(&rid).unwrap_or(true) {
+                    continue;
+                }
+                self.storage.remove(rid)?;
+                sizes.remove(&rid);
+                total -= size;
+
+                info!("Evicted untracked repository {rid} ({size} bytes)");
+            }
+        }
+        if total >= limit {
+            return Err(QuotaError::Total {
+                size: total,
+                quota: limit,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Fetch a repository from the given peer, following a refs announcement. Returns
+    /// whether any refs were updated.
+    ///
+    /// Since this runs on the service thread, it doesn't wait for maintenance of the
+    /// repository to finish. The fetch is instead deferred, and retried on a later wake-up.
+    fn fetch_refs(&mut self, rid: Id, from: NodeId) -> bool {
+        let result = self.locks.try_fetch(rid, || {
+            self.storage
+                .repository(rid)
+                .map_err(storage::FetchError::from)
+                .and_then(|mut r| r.fetch(&from, Namespaces::default()))
+        });
+        let updated = match result {
+            Some(Ok(updated)) => updated,
+            Some(Err(err)) => {
+                error!("Error fetching repository {rid} from {from}: {err}");
+                self.reactor.event(Event::FetchFailed {
+                    from,
+                    project: rid,
+                    reason: err.to_string(),
+                });
+                return false;
+            }
+            None => {
+                debug!("Repository {rid} is under maintenance, deferring fetch from {from}..");
+                self.deferred.insert(rid, from);
+                self.reactor.wakeup(FETCH_RETRY_INTERVAL);
+
+                return false;
+            }
+        };
+        let is_updated = !updated.is_empty();
+
+        if is_updated {
+            self.update_size(rid);
+            self.update_keys(rid);
+        }
+        self.reactor.event(Event::RefsFetched {
+            from,
+            project: rid,
+            updated,
+        });
+
+        is_updated
+    }
+
+    /// Update the known size of a repository, after it was fetched.
+    fn update_size(&mut self, id: Id) {
+        let Some(sizes) = &mut self.sizes else {
+            return;
+        };
+        match self.storage.repository(id).and_then(|r| r.disk_size()) {
+            Ok(size) => {
+                sizes.insert(id, size);
+            }
+            Err(err) => {
+                error!("Error computing size of repository {id}: {err}");
+                sizes.remove(&id);
+            }
+        }
+    }
+
+    /// Update the known keys of a repository, after it was fetched.
+    fn update_keys(&mut self, id: Id) {
+        let Some(keys) = &mut self.keys else {
+            return;
+        };
+        keys.retain(|_, ids| {
+            ids.remove(&id);
+            !ids.is_empty()
+        });
+        for key in repository_keys(&self.storage, id) {
+            keys.entry(key).or_default().insert(id);
+        }
+    }
+
     /// Check whether we are tracking a certain repository.
     pub fn is_tracking(&self, id: &Id) -> Result<bool, tracking::Error> {
         self.tracking.is_repo_tracked(id)
@@ -439,6 +618,10 @@ where
             self.reactor.wakeup(PRUNE_INTERVAL);
             self.last_prune = now;
         }
+        for (rid, from) in mem::take(&mut self.deferred) {
+            debug!("Retrying deferred fetch of {rid} from {from}..");
+            self.fetch_refs(rid, from);
+        }
     }
 
     pub fn command(&mut self, cmd: Command) {
@@ -455,6 +638,10 @@ where
                     resp.send(FetchLookup::NotTracking).ok();
                     return;
                 }
+                if let Err(err) = self.check_quota(&id) {
+                    resp.send(FetchLookup::Error(err)).ok();
+                    return;
+                }
 
                 let Ok(seeds) = self.routing.get(&id) else {
                     todo!();
@@ -492,12 +679,24 @@ where
                 let tracked = self
                     .track_repo(&id, tracking::Scope::All)
                     .expect("Service::command: error tracking repository");
+                if tracked {
+                    self.reactor.event(Event::RepoTrackingChanged {
+                        project: id,
+                        tracked: true,
+                    });
+                }
                 resp.send(tracked).ok();
             }
             Command::UntrackRepo(id, resp) => {
                 let untracked = self
                     .untrack_repo(&id)
                     .expect("Service::command: error untracking repository");
+                if untracked {
+                    self.reactor.event(Event::RepoTrackingChanged {
+                        project: id,
+                        tracked: false,
+                    });
+                }
                 resp.send(untracked).ok();
             }
             Command::TrackNode(id, alias, resp) => {
@@ -505,6 +704,12 @@ where
                     .tracking
                     .track_node(&id, alias.as_deref())
                     .expect("Service::command: error tracking node");
+                if tracked {
+                    self.reactor.event(Event::NodeTrackingChanged {
+                        nid: id,
+                        tracked: true,
+                    });
+                }
                 resp.send(tracked).ok();
             }
             Command::UntrackNode(id, resp) => {
@@ -512,6 +717,12 @@ where
                     .tracking
                     .untrack_node(&id)
                     .expect("Service::command: error untracking node");
+                if untracked {
+                    self.reactor.event(Event::NodeTrackingChanged {
+                        nid: id,
+                        tracked: false,
+                    });
+                }
                 resp.send(untracked).ok();
             }
             Command::AnnounceRefs(id) => {
@@ -522,10 +733,34 @@ where
             Command::QueryState(query, sender) => {
                 sender.send(query(self)).ok();
             }
+            Command::AnnounceRevocation(revocation) => {
+                if let Err(err) = self.announce_revocation(revocation) {
+                    error!("Error announcing revocation: {}", err);
+                }
+            }
         }
     }
 
-    pub fn repo_fetched(&mut self, result: FetchResult) {
+    pub fn repo_fetched(&mut self, fetch: &Fetch, result: FetchResult) {
+        if matches!(result, FetchResult::Fetched { .. }) {
+            self.update_size(fetch.repo);
+            self.update_keys(fetch.repo);
+        }
+        // Fetches initiated by the remote don't update our refs.
+        if fetch.initiated {
+            self.reactor.event(match &result {
+                FetchResult::Fetched { from, updated } => Event::RefsFetched {
+                    from: *from,
+                    project: fetch.repo,
+                    updated: updated.clone(),
+                },
+                FetchResult::Error { from, error } => Event::FetchFailed {
+                    from: *from,
+                    project: fetch.repo,
+                    reason: error.to_string(),
+                },
+            });
+        }
         // TODO(cloudhead): handle completed job with service business logic
         // TODO: Downgrade session to gossip protocol.
         if let Some(session) = self.sessions.get_mut(result.remote()) {
@@ -559,6 +794,8 @@ where
     pub fn connected(&mut self, remote: NodeId, link: Link) {
         info!("Connected to {} ({:?})", remote, link);
 
+        self.reactor.event(Event::PeerConnected { nid: remote });
+
         // For outbound connections, we are the first to say "Hello".
         // For inbound connections, we wait for the remote to say "Hello" first.
         // TODO: How should we deal with multiple peers connecting from the same IP address?
@@ -595,6 +832,11 @@ where
 
         debug!("Disconnected from {} ({})", remote, reason);
 
+        self.reactor.event(Event::PeerDisconnected {
+            nid: remote,
+            reason: reason.to_string(),
+        });
+
         if let Some(session) = self.sessions.get_mut(&remote) {
             session.to_disconnected(since);
 
@@ -720,34 +962,15 @@ where
                         debug!("Ignoring stale refs announcement from {announcer}");
                         return Ok(false);
                     }
+                    if let Err(err) = self.check_quota(&message.id) {
+                        warn!("Not fetching repository {}: {err}", message.id);
+                        return Ok(false);
+                    }
                     // TODO: Check refs to see if we should try to fetch or not.
                     // Refs are only supposed to be relayed by peers who are tracking
                     // the resource. Therefore, it's safe to fetch from the remote
                     // peer, even though it isn't the announcer.
-                    let updated = match self
-                        .storage
-                        .repository(message.id)
-                        .map_err(storage::FetchError::from)
-                        .and_then(|mut r| r.fetch(relayer, Namespaces::default()))
-                    {
-                        Ok(updated) => updated,
-                        Err(err) => {
-                            error!(
-                                "Error fetching repository {} from {}: {}",
-                                message.id, relayer, err
-                            );
-                            return Ok(false);
-                        }
-                    };
-                    let is_updated = !updated.is_empty();
-
-                    self.reactor.event(Event::RefsFetched {
-                        from: *relayer,
-                        project: message.id,
-                        updated,
-                    });
-
-                    if is_updated {
+                    if self.fetch_refs(message.id, *relayer) {
                         return Ok(relay);
                     }
                 } else {
@@ -815,6 +1038,45 @@ where
                     }
                 }
             }
+            AnnouncementMessage::Revocation(RevocationAnnouncement { revocation, .. }) => {
+                let key = &revocation.revocation.key;
+
+                // Check for known revocations first, so that replaying them is cheap.
+                if let Some(known) = self.revocations.get(key) {
+                    if known.revocation.timestamp <= revocation.revocation.timestamp {
+                        debug!("Ignoring known revocation from {announcer}");
+                        return Ok(false);
+                    }
+                }
+                if let Err(err) = revocation.verify() {
+                    warn!("Dropping revocation announcement from {announcer}: {err}");
+                    return Err(session::Error::Misbehavior);
+                }
+                if !self.may_revoke(revocation) {
+                    debug!("Ignoring unauthorized revocation of {key} from {announcer}");
+                    return Ok(false);
+                }
+                if self.revocations.len() >= MAX_REVOCATIONS && !self.revocations.is_revoked(key) {
+                    warn!("Ignoring revocation of {key} from {announcer}: too many revocations");
+                    return Ok(false);
+                }
+                match self.revocations.insert(*revocation) {
+                    // Only relay revocations we didn't already know about.
+                    Ok(true) => {
+                        self.reactor.event(Event::KeyRevoked {
+                            revocation: *revocation,
+                        });
+                        return Ok(relay);
+                    }
+                    Ok(false) => {
+                        debug!("Ignoring known revocation from {announcer}");
+                    }
+                    Err(err) => {
+                        warn!("Dropping revocation announcement from {announcer}: {err}");
+                        return Err(session::Error::Misbehavior);
+                    }
+                }
+            }
         }
         Ok(false)
     }
@@ -994,6 +1256,56 @@ where
         });
         let ann = msg.signed(&self.signer);
 
+        self.reactor.broadcast(ann, peers);
+        self.reactor.event(Event::RefsAnnounced { project: id });
+
+        Ok(())
+    }
+
+    /// Whether a revocation received from the network may be stored and relayed.
+    ///
+    /// The revoked key must be a delegate, device or remote of a project we have, and
+    /// the revoker must be allowed to revoke it by that project's identity document.
+    /// Revocations of keys we know nothing about are ignored, which bounds the set of
+    /// revocations by the contents of storage rather than by what peers send us.
+    fn may_revoke(&mut self, revocation: &SignedRevocation) -> bool {
+        let storage = &self.storage;
+        let keys = self.keys.get_or_insert_with(|| {
+            let mut keys = BTreeMap::<_, BTreeSet<_>>::new();
+            for id in storage.inventory().unwrap_or_default() {
+                for key in repository_keys(storage, id) {
+                    keys.entry(key).or_default().insert(id);
+                }
+            }
+            keys
+        });
+        let Some(ids) = keys.get(&revocation.revocation.key) else {
+            return false;
+        };
+        ids.iter().any(|id| {
+            storage
+                .repository(*id)
+                .ok()
+                .and_then(|repo| repo.project().ok())
+                .map_or(false, |doc| doc.may_revoke(revocation))
+        })
+    }
+
+    /// Announce a key revocation to all connected peers.
+    fn announce_revocation(
+        &mut self,
+        revocation: SignedRevocation,
+    ) -> Result<(), crypto::revocation::Error> {
+        if !self.revocations.insert(revocation)? {
+            return Ok(());
+        }
+        let peers = self.sessions.negotiated().map(|(_, p)| p);
+        let msg = AnnouncementMessage::from(RevocationAnnouncement {
+            revocation,
+            timestamp: self.clock.as_secs(),
+        });
+        let ann = msg.signed(&self.signer);
+
         self.reactor.broadcast(ann, peers);
 
         Ok(())
@@ -1092,6 +1404,8 @@ where
 
 /// Gives read access to the service state.
 pub trait ServiceState {
+    /// Get the local node id.
+    fn node_id(&self) -> NodeId;
     /// Get the connected peers.
     fn sessions(&self) -> &Sessions;
     /// Get the current inventory.
@@ -1102,10 +1416,14 @@ pub trait ServiceState {
     fn clock(&self) -> &LocalTime;
     /// Get the clock mutably.
     fn clock_mut(&mut self) -> &mut LocalTime;
+    /// Get the time at which the service was initialized.
+    fn start_time(&self) -> LocalTime;
     /// Get service configuration.
     fn config(&self) -> &Config;
     /// Get reference to routing table.
     fn routing(&self) -> &dyn routing::Store;
+    /// Get the tracking policy configuration.
+    fn tracking(&self) -> &tracking::Config;
 }
 
 impl<R, A, S, G> ServiceState for Service<R, A, S, G>
@@ -1114,6 +1432,10 @@ where
     G: Signer,
     S: ReadStorage,
 {
+    fn node_id(&self) -> NodeId {
+        *self.signer.public_key()
+    }
+
     fn sessions(&self) -> &Sessions {
         &self.sessions
     }
@@ -1134,6 +1456,10 @@ where
         &mut self.clock
     }
 
+    fn start_time(&self) -> LocalTime {
+        self.start_time
+    }
+
     fn config(&self) -> &Config {
         &self.config
     }
@@ -1141,6 +1467,10 @@ where
     fn routing(&self) -> &dyn routing::Store {
         &self.routing
     }
+
+    fn tracking(&self) -> &tracking::Config {
+        &self.tracking
+    }
 }
 
 /// Disconnect reason.
@@ -1192,6 +1522,25 @@ impl<R, A, S, G> Iterator for Service<R, A, S, G> {
     }
 }
 
+/// Get the keys that are delegates, devices or remotes of a repository in storage.
+fn repository_keys<S: WriteStorage>(storage: &S, id: Id) -> BTreeSet<PublicKey> {
+    let Ok(repo) = storage.repository(id) else {
+        return BTreeSet::new();
+    };
+    let mut keys = repo
+        .remotes()
+        .map(|remotes| remotes.keys().copied().collect::<BTreeSet<_>>())
+        .unwrap_or_default();
+
+    if let Ok(doc) = repo.project() {
+        keys.extend(doc.delegates.iter().map(|did| **did));
+        if let Ok(devices) = doc.devices() {
+            keys.extend(devices.iter().map(|(key, _)| *key));
+        }
+    }
+    keys
+}
+
 /// Result of a project lookup.
 #[derive(Debug)]
 pub struct Lookup {
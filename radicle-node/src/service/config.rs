@@ -12,6 +12,34 @@ pub enum Network {
     Test,
 }
 
+/// What to do when fetching would exceed the storage quota.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum QuotaPolicy {
+    /// Refuse to fetch.
+    #[default]
+    Refuse,
+    /// Evict untracked repositories, largest first, to make room.
+    Evict,
+}
+
+/// Storage quotas, in bytes. Checked before fetching.
+#[derive(Default, Debug, Clone)]
+pub struct Quota {
+    /// Maximum size of all stored repositories.
+    pub total: Option<u64>,
+    /// Maximum size of a single repository.
+    pub repository: Option<u64>,
+    /// What to do when the total quota is exceeded.
+    pub policy: QuotaPolicy,
+}
+
+impl Quota {
+    /// Whether no quota is set.
+    pub fn is_unlimited(&self) -> bool {
+        self.total.is_none() && self.repository.is_none()
+    }
+}
+
 /// Configuration parameters defining attributes of minima and maxima.
 #[derive(Debug, Clone)]
 pub struct Limits {
@@ -19,6 +47,8 @@ pub struct Limits {
     pub routing_max_size: usize,
     /// How long to keep a routing table entry before being pruned.
     pub routing_max_age: LocalDuration,
+    /// Storage quotas.
+    pub quota: Quota,
 }
 
 impl Default for Limits {
@@ -26,6 +56,31 @@ impl Default for Limits {
         Self {
             routing_max_size: 1000,
             routing_max_age: LocalDuration::from_mins(7 * 24 * 60),
+            quota: Quota::default(),
+        }
+    }
+}
+
+/// Git maintenance of stored repositories.
+#[derive(Debug, Clone)]
+pub struct Maintenance {
+    /// How often to check repositories for maintenance.
+    pub interval: LocalDuration,
+    /// Number of loose objects in a repository that triggers maintenance.
+    pub loose_objects: usize,
+    /// Total size of loose objects in a repository, in bytes, that triggers maintenance.
+    pub loose_size: u64,
+    /// Minimum age of unreachable objects removed when pruning a repository.
+    pub prune_expiry: LocalDuration,
+}
+
+impl Default for Maintenance {
+    fn default() -> Self {
+        Self {
+            interval: LocalDuration::from_mins(60),
+            loose_objects: 1000,
+            loose_size: 16 * 1024 * 1024,
+            prune_expiry: LocalDuration::from_mins(60),
         }
     }
 }
@@ -44,6 +99,8 @@ pub struct Config {
     pub relay: bool,
     /// Configured service limits.
     pub limits: Limits,
+    /// Git maintenance of stored repositories.
+    pub maintenance: Maintenance,
 }
 
 impl Default for Config {
@@ -54,6 +111,7 @@ impl Default for Config {
             network: Network::default(),
             relay: true,
             limits: Limits::default(),
+            maintenance: Maintenance::default(),
         }
     }
 }
@@ -1,6 +1,7 @@
 use std::{fmt, io, mem};
 
 use crate::crypto;
+use crate::crypto::revocation::SignedRevocation;
 use crate::git;
 use crate::identity::Id;
 use crate::node;
@@ -159,6 +160,18 @@ pub struct InventoryAnnouncement {
     pub timestamp: Timestamp,
 }
 
+/// Node announcing a key revocation.
+///
+/// Revocations are relayed so that peers learn about them early. They are only enforced
+/// once recorded in the identity document of a repository, by one of its delegates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevocationAnnouncement {
+    /// The signed revocation.
+    pub revocation: SignedRevocation,
+    /// Time of announcement.
+    pub timestamp: Timestamp,
+}
+
 /// Announcement messages are messages that are relayed between peers.
 #[derive(Clone, PartialEq, Eq)]
 pub enum AnnouncementMessage {
@@ -168,6 +181,8 @@ pub enum AnnouncementMessage {
     Node(NodeAnnouncement),
     /// Refs announcement.
     Refs(RefsAnnouncement),
+    /// Key revocation announcement.
+    Revocation(RevocationAnnouncement),
 }
 
 impl AnnouncementMessage {
@@ -188,6 +203,7 @@ impl AnnouncementMessage {
             Self::Inventory(InventoryAnnouncement { timestamp, .. }) => *timestamp,
             Self::Refs(RefsAnnouncement { timestamp, .. }) => *timestamp,
             Self::Node(NodeAnnouncement { timestamp, .. }) => *timestamp,
+            Self::Revocation(RevocationAnnouncement { timestamp, .. }) => *timestamp,
         }
     }
 }
@@ -210,6 +226,12 @@ impl From<RefsAnnouncement> for AnnouncementMessage {
     }
 }
 
+impl From<RevocationAnnouncement> for AnnouncementMessage {
+    fn from(ann: RevocationAnnouncement) -> Self {
+        Self::Revocation(ann)
+    }
+}
+
 impl fmt::Debug for AnnouncementMessage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -230,6 +252,13 @@ impl fmt::Debug for AnnouncementMessage {
             Self::Refs(message) => {
                 write!(f, "Refs({}, {:?})", message.id, message.refs)
             }
+            Self::Revocation(message) => {
+                write!(
+                    f,
+                    "Revocation({}, {})",
+                    message.revocation.revocation.key, message.timestamp
+                )
+            }
         }
     }
 }
@@ -274,6 +303,7 @@ impl Announcement {
             AnnouncementMessage::Inventory(_) => true,
             AnnouncementMessage::Node(_) => true,
             AnnouncementMessage::Refs(RefsAnnouncement { id, .. }) => filter.contains(id),
+            AnnouncementMessage::Revocation(_) => true,
         }
     }
 }
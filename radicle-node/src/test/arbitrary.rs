@@ -2,11 +2,12 @@ use bloomy::BloomFilter;
 use qcheck::Arbitrary;
 
 use crate::crypto;
-use crate::prelude::{BoundedVec, Id, NodeId, Refs, Timestamp};
+use crate::crypto::revocation::{Revocation, SignedRevocation};
+use crate::prelude::{BoundedVec, Id, NodeId, PublicKey, Refs, Timestamp};
 use crate::service::filter::{Filter, FILTER_SIZE_L, FILTER_SIZE_M, FILTER_SIZE_S};
 use crate::service::message::{
     Announcement, InventoryAnnouncement, Message, NodeAnnouncement, Ping, RefsAnnouncement,
-    Subscribe, ZeroBytes,
+    RevocationAnnouncement, Subscribe, ZeroBytes,
 };
 use crate::wire::MessageType;
 
@@ -33,6 +34,7 @@ impl Arbitrary for Message {
                 MessageType::InventoryAnnouncement,
                 MessageType::NodeAnnouncement,
                 MessageType::RefsAnnouncement,
+                MessageType::RevocationAnnouncement,
                 MessageType::Subscribe,
                 MessageType::Ping,
                 MessageType::Pong,
@@ -63,6 +65,20 @@ impl Arbitrary for Message {
                 signature: crypto::Signature::from(<[u8; 64]>::arbitrary(g)),
             }
             .into(),
+            MessageType::RevocationAnnouncement => Announcement {
+                node: NodeId::arbitrary(g),
+                message: RevocationAnnouncement {
+                    revocation: SignedRevocation {
+                        revocation: Revocation::new(PublicKey::arbitrary(g), u64::arbitrary(g)),
+                        revoker: PublicKey::arbitrary(g),
+                        signature: crypto::Signature::from(<[u8; 64]>::arbitrary(g)),
+                    },
+                    timestamp: Timestamp::arbitrary(g),
+                }
+                .into(),
+                signature: crypto::Signature::from(<[u8; 64]>::arbitrary(g)),
+            }
+            .into(),
             MessageType::NodeAnnouncement => {
                 let message = NodeAnnouncement {
                     features: u64::arbitrary(g).into(),
@@ -3,8 +3,9 @@ use std::sync::{Arc, Mutex};
 
 use crossbeam_channel as chan;
 
-use crate::client::handle::Error;
+use crate::client::handle::{Error, AGENT};
 use crate::identity::Id;
+use crate::node::Info;
 use crate::service;
 use crate::service::FetchLookup;
 use crate::service::NodeId;
@@ -63,6 +64,16 @@ impl radicle::node::Handle for Handle {
         unimplemented!();
     }
 
+    fn info(&self) -> Result<Info, Error> {
+        Ok(Info {
+            id: NodeId::from([0; 32]),
+            agent: AGENT.to_owned(),
+            peers: 0,
+            repos: self.tracking_repos.len(),
+            uptime: 0,
+        })
+    }
+
     fn shutdown(self) -> Result<(), Error> {
         Ok(())
     }
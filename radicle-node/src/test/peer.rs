@@ -8,6 +8,7 @@ use log::*;
 use crate::address;
 use crate::address::Store;
 use crate::clock::Timestamp;
+use crate::crypto::revocation::SignedRevocation;
 use crate::crypto::test::signer::MockSigner;
 use crate::crypto::Signer;
 use crate::identity::Id;
@@ -231,6 +232,16 @@ where
         msg.into()
     }
 
+    pub fn revocation_announcement(&self, revocation: SignedRevocation) -> Message {
+        let ann = AnnouncementMessage::from(RevocationAnnouncement {
+            revocation,
+            timestamp: self.timestamp(),
+        });
+        let msg = ann.signed(self.signer());
+
+        msg.into()
+    }
+
     pub fn connect_from(&mut self, peer: &Self) {
         let remote_id = simulator::Peer::<S, G>::id(peer);
 
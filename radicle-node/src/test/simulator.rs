@@ -18,6 +18,7 @@ use crate::service::reactor::Io;
 use crate::service::{DisconnectReason, Event, Message, NodeId};
 use crate::storage::WriteStorage;
 use crate::test::peer::Service;
+use crate::wire;
 use crate::Link;
 
 /// Minimum latency between peers.
@@ -161,6 +162,19 @@ impl Default for Options {
     }
 }
 
+/// Simulation statistics, accumulated over the course of a simulation.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of messages sent between nodes.
+    pub messages: usize,
+    /// Total size of the messages sent between nodes, in bytes, as encoded on the wire.
+    pub bytes: usize,
+    /// Number of connection attempts.
+    pub connections: usize,
+    /// Number of fetches requested. Fetches are not simulated.
+    pub fetches: usize,
+}
+
 /// A peer-to-peer node simulation.
 pub struct Simulation<S, G> {
     /// Inbox of inputs to be delivered by the simulation.
@@ -179,6 +193,8 @@ pub struct Simulation<S, G> {
     attempts: BTreeSet<(NodeId, NodeId)>,
     /// Simulation options.
     opts: Options,
+    /// Simulation statistics.
+    stats: Stats,
     /// Start time of simulation.
     start_time: LocalTime,
     /// Current simulation time. Updated when a scheduled message is processed.
@@ -205,6 +221,7 @@ impl<S: WriteStorage + 'static, G: Signer> Simulation<S, G> {
             connections: BTreeSet::new(),
             attempts: BTreeSet::new(),
             opts,
+            stats: Stats::default(),
             start_time: time,
             time,
             rng,
@@ -233,6 +250,11 @@ impl<S: WriteStorage + 'static, G: Signer> Simulation<S, G> {
             .all(|(_, s)| matches!(s.input, Input::Wake))
     }
 
+    /// Get the simulation statistics.
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
     /// Get a node's emitted events.
     pub fn events(&mut self, node: &NodeId) -> impl Iterator<Item = Event> + '_ {
         self.events.entry(*node).or_default().drain(..)
@@ -427,6 +449,8 @@ impl<S: WriteStorage + 'static, G: Signer> Simulation<S, G> {
                 if self.connections.get(&(node, receiver)).is_none() {
                     return;
                 }
+                self.stats.messages += msgs.len();
+                self.stats.bytes += msgs.iter().map(|m| wire::serialize(m).len()).sum::<usize>();
                 let sender = node;
 
                 if self.is_partitioned(sender, receiver) {
@@ -469,6 +493,7 @@ impl<S: WriteStorage + 'static, G: Signer> Simulation<S, G> {
             }
             Io::Connect(remote, addr) => {
                 assert!(remote != node, "self-connections are not allowed");
+                self.stats.connections += 1;
 
                 let latency = self.latency(node, remote);
 
@@ -593,7 +618,11 @@ impl<S: WriteStorage + 'static, G: Signer> Simulation<S, G> {
                     events.push_back(event);
                 }
             }
-            Io::Fetch(..) => todo!("I have no idea what to do here"),
+            Io::Fetch(fetch) => {
+                // Fetches require actual storage and networking, so they are only counted.
+                debug!(target: "sim", "{} => {}: Fetch {} (not simulated)", node, fetch.remote, fetch.repo);
+                self.stats.fetches += 1;
+            }
         }
     }
 
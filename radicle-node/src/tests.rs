@@ -7,6 +7,7 @@ use std::sync::Arc;
 use crossbeam_channel as chan;
 
 use crate::collections::{HashMap, HashSet};
+use crate::crypto::revocation::Revocation;
 use crate::crypto::test::signer::MockSigner;
 use crate::identity::Id;
 use crate::prelude::*;
@@ -201,6 +202,53 @@ fn test_persistent_peer_connect() {
     assert_matches!(outbox.next(), None);
 }
 
+#[test]
+fn test_storage_quota() {
+    let tmp = tempfile::tempdir().unwrap();
+    let signer = MockSigner::default();
+    let id: Id = arbitrary::gen(1);
+    let quota = |policy| Config {
+        limits: Limits {
+            quota: Quota {
+                total: Some(1),
+                repository: None,
+                policy,
+            },
+            ..Limits::default()
+        },
+        ..Config::default()
+    };
+
+    let storage = fixtures::storage(tmp.path().join("alice"), &signer).unwrap();
+    let mut alice = Peer::config(
+        "alice",
+        [7, 7, 7, 7],
+        storage.clone(),
+        peer::Config {
+            config: quota(QuotaPolicy::Refuse),
+            ..peer::Config::default()
+        },
+    );
+    assert_matches!(
+        alice.check_quota(&id),
+        Err(FetchError::Quota(QuotaError::Total { quota: 1, .. }))
+    );
+    assert!(!storage.inventory().unwrap().is_empty());
+
+    // Untracked repositories are evicted to make room.
+    let mut bob = Peer::config(
+        "bob",
+        [8, 8, 8, 8],
+        storage.clone(),
+        peer::Config {
+            config: quota(QuotaPolicy::Evict),
+            ..peer::Config::default()
+        },
+    );
+    assert_matches!(bob.check_quota(&id), Ok(()));
+    assert!(storage.inventory().unwrap().is_empty());
+}
+
 #[test]
 fn test_inventory_sync() {
     let tmp = tempfile::tempdir().unwrap();
@@ -249,6 +297,7 @@ fn test_inventory_pruning() {
             limits: Limits {
                 routing_max_size: 0,
                 routing_max_age: LocalDuration::from_secs(0),
+                quota: Quota::default(),
             },
             peer_projects: vec![10; 5],
             wait_time: LocalDuration::from_mins(7 * 24 * 60) + LocalDuration::from_secs(1),
@@ -259,6 +308,7 @@ fn test_inventory_pruning() {
             limits: Limits {
                 routing_max_size: 0,
                 routing_max_age: LocalDuration::from_mins(7 * 24 * 60),
+                quota: Quota::default(),
             },
             peer_projects: vec![10; 5],
             wait_time: LocalDuration::from_mins(7 * 24 * 60) + LocalDuration::from_secs(1),
@@ -269,6 +319,7 @@ fn test_inventory_pruning() {
             limits: Limits {
                 routing_max_size: 50,
                 routing_max_age: LocalDuration::from_mins(0),
+                quota: Quota::default(),
             },
             peer_projects: vec![10; 5],
             wait_time: LocalDuration::from_mins(7 * 24 * 60) + LocalDuration::from_secs(1),
@@ -279,6 +330,7 @@ fn test_inventory_pruning() {
             limits: Limits {
                 routing_max_size: 25,
                 routing_max_age: LocalDuration::from_mins(7 * 24 * 60),
+                quota: Quota::default(),
             },
             peer_projects: vec![10; 5],
             wait_time: LocalDuration::from_mins(7 * 24 * 60) + LocalDuration::from_secs(1),
@@ -582,6 +634,142 @@ fn test_refs_announcement_relay() {
     );
 }
 
+#[test]
+fn test_refs_announcement_deferred_fetch() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut alice = Peer::config(
+        "alice",
+        [7, 7, 7, 7],
+        Storage::open(tmp.path().join("alice")).unwrap(),
+        peer::Config::default(),
+    );
+    let eve = Peer::config(
+        "eve",
+        [8, 8, 8, 8],
+        Storage::open(tmp.path().join("eve")).unwrap(),
+        peer::Config::default(),
+    );
+    let bob = {
+        let mut rng = fastrand::Rng::new();
+        let signer = MockSigner::new(&mut rng);
+        let storage = fixtures::storage(tmp.path().join("bob"), &signer).unwrap();
+
+        Peer::config(
+            "bob",
+            [9, 9, 9, 9],
+            storage,
+            peer::Config {
+                signer,
+                rng,
+                ..peer::Config::default()
+            },
+        )
+    };
+    let rid = bob.inventory().unwrap()[0];
+
+    alice.track_repo(&rid, tracking::Scope::All).unwrap();
+    alice.connect_to(&bob);
+    alice.connect_to(&eve);
+    alice.receive(eve.id(), Message::Subscribe(Subscribe::all()));
+    alice.outbox().for_each(drop);
+
+    // Hold the repository under maintenance until we're done.
+    let (started, wait) = std::sync::mpsc::channel();
+    let (done, finish) = std::sync::mpsc::channel::<()>();
+    let maintenance = std::thread::spawn({
+        let locks = alice.locks().clone();
+        move || {
+            locks.try_maintain(rid, || {
+                started.send(()).unwrap();
+                finish.recv().unwrap();
+            })
+        }
+    });
+    wait.recv().unwrap();
+
+    alice.receive(bob.id(), bob.refs_announcement(rid));
+    assert!(
+        alice.messages(eve.id()).next().is_none(),
+        "The announcement isn't relayed, since nothing was fetched"
+    );
+    assert!(
+        !alice
+            .events()
+            .any(|e| matches!(e, Event::RefsFetched { .. } | Event::FetchFailed { .. })),
+        "The fetch is deferred while the repository is under maintenance"
+    );
+    assert!(alice.storage().get(&bob.node_id(), rid).unwrap().is_none());
+
+    done.send(()).unwrap();
+    maintenance.join().unwrap().unwrap();
+
+    alice.elapse(FETCH_RETRY_INTERVAL);
+    assert_matches!(
+        alice.events().find(|e| matches!(e, Event::RefsFetched { .. })),
+        Some(Event::RefsFetched { from, project, updated })
+        if from == bob.id() && project == rid && !updated.is_empty(),
+        "The fetch is retried once maintenance is done"
+    );
+    assert!(alice.storage().get(&bob.node_id(), rid).unwrap().is_some());
+}
+
+#[test]
+fn test_revocation_announcement_relay() {
+    let tmp = tempfile::tempdir().unwrap();
+    let owner = MockSigner::from_seed([1; 32]);
+    let stranger = MockSigner::from_seed([2; 32]);
+    let mut alice = Peer::config(
+        "alice",
+        [7, 7, 7, 7],
+        fixtures::storage(tmp.path().join("alice"), &owner).unwrap(),
+        peer::Config::default(),
+    );
+    let bob = Peer::new("bob", [8, 8, 8, 8]);
+    let eve = Peer::new("eve", [9, 9, 9, 9]);
+
+    alice.connect_to(&bob);
+    alice.connect_to(&eve);
+
+    let revocation = Revocation::new(*stranger.public_key(), 1);
+    alice.receive(
+        bob.id(),
+        bob.revocation_announcement(revocation.sign(&stranger)),
+    );
+    assert!(
+        alice.messages(eve.id()).next().is_none(),
+        "A revocation of a key that isn't in storage is not relayed"
+    );
+
+    let revocation = Revocation::new(*owner.public_key(), 1);
+    alice.receive(
+        bob.id(),
+        bob.revocation_announcement(revocation.sign(&stranger)),
+    );
+    assert!(
+        alice.messages(eve.id()).next().is_none(),
+        "A revocation by a key that isn't a delegate is not relayed"
+    );
+
+    alice.receive(
+        bob.id(),
+        bob.revocation_announcement(revocation.sign(&owner)),
+    );
+    assert_matches!(
+        alice.messages(eve.id()).next(),
+        Some(Message::Announcement(_)),
+        "A self-revocation of a delegate key is relayed"
+    );
+
+    alice.receive(
+        bob.id(),
+        bob.revocation_announcement(revocation.sign(&owner)),
+    );
+    assert!(
+        alice.messages(eve.id()).next().is_none(),
+        "A known revocation is not relayed again"
+    );
+}
+
 #[test]
 fn test_refs_announcement_no_subscribe() {
     let mut alice = Peer::new("alice", [7, 7, 7, 7]);
@@ -879,7 +1067,8 @@ fn test_push_and_pull() {
         .unwrap()
         .is_some());
     assert_matches!(
-        sim.events(&bob.id).next(),
+        sim.events(&bob.id)
+            .find(|e| matches!(e, service::Event::RefsFetched { .. })),
         Some(service::Event::RefsFetched { from, .. })
         if from == eve.node_id(),
         "Bob fetched from Eve"
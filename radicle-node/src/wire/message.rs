@@ -2,6 +2,7 @@ use std::{io, mem, net};
 
 use byteorder::{NetworkEndian, ReadBytesExt};
 use cyphernet::addr::{Addr, HostName, NetAddr};
+use radicle::crypto::revocation::{Revocation, SignedRevocation};
 use radicle::node::Address;
 
 use crate::prelude::*;
@@ -21,6 +22,7 @@ pub enum MessageType {
     Ping = 10,
     Pong = 12,
     Fetch = 14,
+    RevocationAnnouncement = 16,
 }
 
 impl From<MessageType> for u16 {
@@ -42,6 +44,7 @@ impl TryFrom<u16> for MessageType {
             10 => Ok(MessageType::Ping),
             12 => Ok(MessageType::Pong),
             14 => Ok(MessageType::Fetch),
+            16 => Ok(MessageType::RevocationAnnouncement),
             _ => Err(other),
         }
     }
@@ -60,6 +63,7 @@ impl Message {
                 AnnouncementMessage::Node(_) => MessageType::NodeAnnouncement,
                 AnnouncementMessage::Inventory(_) => MessageType::InventoryAnnouncement,
                 AnnouncementMessage::Refs(_) => MessageType::RefsAnnouncement,
+                AnnouncementMessage::Revocation(_) => MessageType::RevocationAnnouncement,
             },
             Self::Ping { .. } => MessageType::Ping,
             Self::Pong { .. } => MessageType::Pong,
@@ -133,10 +137,49 @@ impl wire::Encode for AnnouncementMessage {
             Self::Node(ann) => ann.encode(writer),
             Self::Inventory(ann) => ann.encode(writer),
             Self::Refs(ann) => ann.encode(writer),
+            Self::Revocation(ann) => ann.encode(writer),
         }
     }
 }
 
+impl wire::Encode for RevocationAnnouncement {
+    fn encode<W: io::Write + ?Sized>(&self, writer: &mut W) -> Result<usize, io::Error> {
+        let SignedRevocation {
+            revocation,
+            revoker,
+            signature,
+        } = &self.revocation;
+        let mut n = 0;
+
+        n += revocation.key.encode(writer)?;
+        n += revocation.timestamp.encode(writer)?;
+        n += revoker.encode(writer)?;
+        n += signature.encode(writer)?;
+        n += self.timestamp.encode(writer)?;
+
+        Ok(n)
+    }
+}
+
+impl wire::Decode for RevocationAnnouncement {
+    fn decode<R: std::io::Read + ?Sized>(reader: &mut R) -> Result<Self, wire::Error> {
+        let key = PublicKey::decode(reader)?;
+        let revoked_at = u64::decode(reader)?;
+        let revoker = PublicKey::decode(reader)?;
+        let signature = Signature::decode(reader)?;
+        let timestamp = Timestamp::decode(reader)?;
+
+        Ok(Self {
+            revocation: SignedRevocation {
+                revocation: Revocation::new(key, revoked_at),
+                revoker,
+                signature,
+            },
+            timestamp,
+        })
+    }
+}
+
 impl wire::Encode for RefsAnnouncement {
     fn encode<W: io::Write + ?Sized>(&self, writer: &mut W) -> Result<usize, io::Error> {
         let mut n = 0;
@@ -285,6 +328,18 @@ impl wire::Decode for Message {
                 }
                 .into())
             }
+            Ok(MessageType::RevocationAnnouncement) => {
+                let node = NodeId::decode(reader)?;
+                let message = RevocationAnnouncement::decode(reader)?.into();
+                let signature = Signature::decode(reader)?;
+
+                Ok(Announcement {
+                    node,
+                    message,
+                    signature,
+                }
+                .into())
+            }
             Ok(MessageType::Ping) => {
                 let ponglen = u16::decode(reader)?;
                 let zeroes = ZeroBytes::decode(reader)?;
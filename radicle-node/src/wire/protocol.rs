@@ -25,7 +25,7 @@ use radicle::storage::WriteStorage;
 
 use crate::crypto::Signer;
 use crate::service::reactor::{Fetch, Io};
-use crate::service::{routing, session, DisconnectReason, Message, Service};
+use crate::service::{routing, session, DisconnectReason, Event, Message, Service};
 use crate::wire::{Decode, Encode};
 use crate::worker::{WorkerReq, WorkerResp};
 use crate::Link;
@@ -187,6 +187,8 @@ pub struct Wire<R, S, W, G: Signer + EcSign> {
     service: Service<R, S, W, G>,
     /// Worker pool interface.
     worker: chan::Sender<WorkerReq<G>>,
+    /// Service event subscribers.
+    events: chan::Sender<Event>,
     /// Used for authentication; keeps local identity.
     cert: Cert<Signature>,
     /// Used for authentication.
@@ -211,6 +213,7 @@ where
     pub fn new(
         mut service: Service<R, S, W, G>,
         worker: chan::Sender<WorkerReq<G>>,
+        events: chan::Sender<Event>,
         cert: Cert<Signature>,
         signer: G,
         proxy: net::SocketAddr,
@@ -223,6 +226,7 @@ where
         Self {
             service,
             worker,
+            events,
             cert,
             signer,
             proxy,
@@ -342,7 +346,7 @@ where
         peer.downgrade();
 
         self.actions.push_back(Action::RegisterTransport(session));
-        self.service.repo_fetched(resp.result);
+        self.service.repo_fetched(&resp.fetch, resp.result);
     }
 }
 
@@ -593,10 +597,12 @@ where
                     }
                     self.actions.push_back(reactor::Action::Send(fd, data));
                 }
-                Io::Event(_e) => {
-                    log::warn!(
-                        target: "wire", "Events are not currently supported"
-                    );
+                Io::Event(e) => {
+                    if self.events.send(e).is_err() {
+                        log::error!(
+                            target: "wire", "Unable to emit event: event channel disconnected"
+                        );
+                    }
                 }
                 Io::Connect(node_id, addr) => {
                     if self.connected().any(|(_, id)| id == &node_id) {
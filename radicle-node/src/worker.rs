@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::io::prelude::*;
 use std::thread::JoinHandle;
 use std::{env, io, net, process, str, thread, time};
@@ -9,15 +10,36 @@ use netservices::{NetSession, SplitIo};
 
 use radicle::crypto::Signer;
 use radicle::identity::Id;
-use radicle::storage::{ReadRepository, RefUpdate, WriteRepository, WriteStorage};
+use radicle::storage::git::Repository;
+use radicle::storage::hooks;
+use radicle::storage::refs::Refs;
+use radicle::storage::{RefUpdate, WriteRepository, WriteStorage};
 use radicle::{git, Storage};
 use reactor::poller::popol;
 
 use crate::client::handle::Handle;
+use crate::maintenance::Locks;
 use crate::service::reactor::Fetch;
 use crate::service::{FetchError, FetchResult};
 use crate::wire::{WireReader, WireSession, WireWriter};
 
+/// Get all namespaced references of a repository.
+fn namespaced_refs(repo: &Repository) -> Result<Refs, git::raw::Error> {
+    let mut refs = BTreeMap::new();
+
+    for r in repo.raw().references_glob("refs/namespaces/*")? {
+        let r = r?;
+        let (Some(name), Some(oid)) = (r.name(), r.target()) else {
+            continue;
+        };
+        let Ok(name) = git::RefString::try_from(name) else {
+            continue;
+        };
+        refs.insert(name, oid.into());
+    }
+    Ok(Refs::from(refs))
+}
+
 /// Worker request.
 pub struct WorkerReq<G: Signer + EcSign> {
     pub fetch: Fetch,
@@ -27,6 +49,7 @@ pub struct WorkerReq<G: Signer + EcSign> {
 
 /// Worker response.
 pub struct WorkerResp<G: Signer + EcSign> {
+    pub fetch: Fetch,
     pub result: FetchResult,
     pub session: WireSession<G>,
 }
@@ -37,6 +60,7 @@ struct Worker<G: Signer + EcSign> {
     tasks: chan::Receiver<WorkerReq<G>>,
     timeout: time::Duration,
     handle: Handle<G>,
+    locks: Locks,
 }
 
 impl<G: Signer + EcSign + 'static> Worker<G> {
@@ -71,7 +95,11 @@ impl<G: Signer + EcSign + 'static> Worker<G> {
 
         if self
             .handle
-            .worker_result(WorkerResp { result, session })
+            .worker_result(WorkerResp {
+                fetch,
+                result,
+                session,
+            })
             .is_err()
         {
             log::error!("Unable to report fetch result: worker channel disconnected");
@@ -91,7 +119,9 @@ impl<G: Signer + EcSign + 'static> Worker<G> {
                 Ok(tunnel) => tunnel,
                 Err((session, err)) => return (session, Err(err.into())),
             };
-            let result = self.fetch(fetch, &mut tunnel);
+            let result = self
+                .locks
+                .fetch(fetch.repo, || self.fetch(fetch, &mut tunnel));
             let session = tunnel.into_session();
 
             (session, result)
@@ -120,6 +150,7 @@ impl<G: Signer + EcSign + 'static> Worker<G> {
         tunnel: &mut Tunnel<WireSession<G>>,
     ) -> Result<Vec<RefUpdate>, FetchError> {
         let repo = self.storage.repository(fetch.repo)?;
+        let before = namespaced_refs(&repo)?;
         let tunnel_addr = tunnel.local_addr()?;
         let mut cmd = process::Command::new("git");
         cmd.current_dir(repo.path())
@@ -143,7 +174,6 @@ impl<G: Signer + EcSign + 'static> Worker<G> {
         let _ = tunnel.tunnel_once(popol::Poller::new(), self.timeout)?;
         let status = child.wait()?;
 
-        // TODO: Parse fetch output to return updates.
         log::debug!(target: "worker", "Fetch for {} exited with status {:?}", fetch.repo, status.code());
 
         if let Some(status) = status.code() {
@@ -162,7 +192,10 @@ impl<G: Signer + EcSign + 'static> Worker<G> {
         let head = repo.set_head()?;
         log::debug!(target: "worker", "Setting head for {} to {head}", fetch.repo);
 
-        Ok(vec![])
+        let updates = hooks::diff(&before, &namespaced_refs(&repo)?);
+        self.storage.hooks().run_namespaced(repo.id, &updates);
+
+        Ok(updates)
     }
 
     fn upload_pack(
@@ -254,6 +287,7 @@ impl WorkerPool {
         storage: Storage,
         tasks: chan::Receiver<WorkerReq<G>>,
         handle: Handle<G>,
+        locks: Locks,
         name: String,
     ) -> Self {
         let mut pool = Vec::with_capacity(capacity);
@@ -262,6 +296,7 @@ impl WorkerPool {
                 tasks: tasks.clone(),
                 storage: storage.clone(),
                 handle: handle.clone(),
+                locks: locks.clone(),
                 timeout,
             };
             let thread = thread::Builder::new()
@@ -0,0 +1,338 @@
+//! Benchmark gossip replication in a simulated network.
+//!
+//! Usage: rad-bench [--nodes <n>] [--repos <n>] [--degree <n>] [--latency <secs>] [--seed <n>]
+//!
+//! Spins up `nodes` in-process nodes, each seeding `repos` synthetic repositories, and
+//! connects every node to `degree` random peers. The network is then simulated until every
+//! node's routing table knows about all the seeds of every repository. Prints the simulated
+//! convergence time, along with the number and size of messages exchanged, so that protocol
+//! changes can be compared with each other.
+//!
+//! Replication is then measured on a second network of the same shape, backed by real
+//! storage. Each of the `repos` repositories is created by one of the nodes and tracked by
+//! all the others; once its refs are announced, nodes fetch it from each other over the mock
+//! git transport. Prints the wall time taken until the network settles, and the number of
+//! fetches that succeeded or failed. Finally, every repository is cloned into a fresh
+//! storage, and the wall time of each clone is reported.
+//!
+//! Requires the `bench` feature, eg. `cargo run --features bench --bin rad-bench`.
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::{env, fs, net, process, time};
+
+use radicle::git;
+use radicle::identity::Id;
+use radicle::node::NodeId;
+use radicle::storage::git::transport::{local, remote};
+use radicle::storage::git::Storage;
+use radicle::storage::{ReadStorage, WriteStorage};
+use radicle::test::fixtures;
+use radicle_node::crypto::test::signer::MockSigner;
+use radicle_node::service::{tracking, Command, Event};
+use radicle_node::test::arbitrary;
+use radicle_node::test::peer::{Config, Peer};
+use radicle_node::test::simulator::{self, Peer as _, Simulation};
+use radicle_node::test::storage::MockStorage;
+use radicle_node::{LocalDuration, LocalTime};
+
+/// Number of simulation steps between convergence checks.
+const CHECK_INTERVAL: usize = 64;
+/// Maximum simulated time before giving up on convergence.
+const TIMEOUT: LocalDuration = LocalDuration::from_mins(60);
+
+#[derive(Debug)]
+struct Options {
+    nodes: usize,
+    repos: usize,
+    degree: usize,
+    latency: u64,
+    seed: u64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            nodes: 16,
+            repos: 4,
+            degree: 3,
+            latency: 1,
+            seed: 0,
+        }
+    }
+}
+
+impl Options {
+    fn from_env() -> anyhow::Result<Self> {
+        let mut options = Self::default();
+        let mut args = env::args().skip(1);
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| anyhow::anyhow!("`{arg}` requires a value"))
+            };
+            match arg.as_str() {
+                "--nodes" => options.nodes = value()?.parse()?,
+                "--repos" => options.repos = value()?.parse()?,
+                "--degree" => options.degree = value()?.parse()?,
+                "--latency" => options.latency = value()?.parse()?,
+                "--seed" => options.seed = value()?.parse()?,
+                _ => anyhow::bail!("unknown argument `{arg}`"),
+            }
+        }
+        if options.nodes < 2 {
+            anyhow::bail!("at least two nodes are required");
+        }
+        Ok(options)
+    }
+}
+
+/// Create the `i`th node of the network, with the given storage.
+fn peer<S: WriteStorage + 'static>(
+    i: usize,
+    storage: S,
+    rng: &fastrand::Rng,
+) -> Peer<S, MockSigner> {
+    let name: &'static str = Box::leak(format!("node-{i}").into_boxed_str());
+    let ip = net::Ipv4Addr::new(10, 0, (i / 256) as u8, (i % 256) as u8);
+    let mut peer_rng = fastrand::Rng::with_seed(rng.u64(..));
+    let signer = MockSigner::new(&mut peer_rng);
+
+    Peer::config(
+        name,
+        ip,
+        storage,
+        Config {
+            signer,
+            rng: peer_rng,
+            ..Config::default()
+        },
+    )
+}
+
+/// Connect every node to `degree` random peers.
+fn connect<S: WriteStorage + 'static>(
+    peers: &mut [Peer<S, MockSigner>],
+    degree: usize,
+    rng: &fastrand::Rng,
+) {
+    for i in 0..peers.len() {
+        for _ in 0..degree {
+            let j = rng.usize(..peers.len());
+            if i == j {
+                continue;
+            }
+            let (id, addr) = (peers[j].id(), peers[j].address());
+            peers[i].command(Command::Connect(id, addr));
+        }
+    }
+}
+
+/// Check whether every node knows all the seeds of every repository.
+fn is_converged(
+    peers: &[Peer<MockStorage, MockSigner>],
+    seeds: &HashMap<Id, BTreeSet<NodeId>>,
+) -> bool {
+    peers.iter().all(|peer| {
+        seeds.iter().all(|(id, seeds)| {
+            peer.lookup(*id)
+                .map(|l| l.remote.into_iter().collect::<BTreeSet<_>>() == *seeds)
+                .unwrap_or(false)
+        })
+    })
+}
+
+/// Measure routing table convergence, with mock storage.
+fn gossip(options: &Options, rng: &fastrand::Rng) -> anyhow::Result<()> {
+    let mut peers = Vec::with_capacity(options.nodes);
+    let mut seeds: HashMap<Id, BTreeSet<NodeId>> = HashMap::new();
+
+    for i in 0..options.nodes {
+        let storage = MockStorage::new(
+            (0..options.repos)
+                .map(|_| (arbitrary::gen::<Id>(1), arbitrary::gen(1)))
+                .collect(),
+        );
+        let peer = peer(i, storage, rng);
+
+        for id in peer.storage().inventory.keys() {
+            seeds.entry(*id).or_default().insert(peer.id());
+        }
+        peers.push(peer);
+    }
+    connect(&mut peers, options.degree, rng);
+
+    let mut sim = Simulation::new(
+        LocalTime::now(),
+        rng.clone(),
+        simulator::Options {
+            latency: 0..options.latency,
+            failure_rate: 0.,
+        },
+    )
+    .initialize(peers.iter_mut());
+
+    let start = time::Instant::now();
+    let mut steps = 0;
+    let converged = loop {
+        if !sim.step(peers.iter_mut()) {
+            break is_converged(&peers, &seeds);
+        }
+        steps += 1;
+
+        if steps % CHECK_INTERVAL == 0 && is_converged(&peers, &seeds) {
+            break true;
+        }
+        if sim.elapsed() >= TIMEOUT {
+            break false;
+        }
+    };
+    let wall = start.elapsed();
+    let stats = sim.stats();
+
+    println!("nodes: {}", options.nodes);
+    println!("repos: {}", seeds.len());
+    println!("degree: {}", options.degree);
+    println!("converged: {converged}");
+    println!("convergence-time: {}", sim.elapsed());
+    println!("wall-time: {:?}", wall);
+    println!("steps: {steps}");
+    println!("connections: {}", stats.connections);
+    println!("messages: {}", stats.messages);
+    println!("bytes: {}", stats.bytes);
+    println!("bytes-per-node: {}", stats.bytes / options.nodes);
+
+    if !converged {
+        anyhow::bail!("network did not converge within {TIMEOUT}");
+    }
+    Ok(())
+}
+
+/// Measure fetches and clones of real repositories, stored under `dir`.
+fn replicate(options: &Options, rng: &fastrand::Rng, dir: &Path) -> anyhow::Result<()> {
+    let mut peers = Vec::with_capacity(options.nodes);
+    for i in 0..options.nodes {
+        let storage = Storage::open(dir.join(format!("node-{i}")).join("storage"))?;
+        let peer = peer(i, storage, rng);
+
+        remote::mock::register(&peer.node_id(), peer.storage().path());
+        peers.push(peer);
+    }
+
+    // Each repository is created by one node, and tracked by all of them.
+    let mut repos = Vec::with_capacity(options.repos);
+    for r in 0..options.repos {
+        let creator = &peers[r % peers.len()];
+        let (working, _) = fixtures::repository(dir.join("working").join(format!("repo-{r}")));
+
+        local::register(creator.storage().clone());
+
+        let (rid, _, _) = radicle::rad::init(
+            &working,
+            &format!("repo-{r}"),
+            "Benchmark repository",
+            git::refname!("master"),
+            creator.signer(),
+            creator.storage(),
+        )?;
+        repos.push((rid, creator.node_id()));
+    }
+    for peer in peers.iter_mut() {
+        for (rid, _) in &repos {
+            peer.track_repo(rid, tracking::Scope::All)?;
+        }
+    }
+    connect(&mut peers, options.degree, rng);
+
+    let mut sim = Simulation::new(
+        LocalTime::now(),
+        rng.clone(),
+        simulator::Options {
+            latency: 0..options.latency,
+            failure_rate: 0.,
+        },
+    )
+    .initialize(peers.iter_mut());
+
+    sim.run_while(peers.iter_mut(), |s| {
+        !s.is_settled() && s.elapsed() < TIMEOUT
+    });
+
+    // Announcing refs triggers fetches from every tracking node that hears about them.
+    for (r, (rid, _)) in repos.iter().enumerate() {
+        let creator = &mut peers[r % options.nodes];
+
+        creator.elapse(LocalDuration::from_secs(1));
+        creator.command(Command::AnnounceRefs(*rid));
+    }
+    let start = time::Instant::now();
+    sim.run_while(peers.iter_mut(), |s| {
+        !s.is_settled() && s.elapsed() < TIMEOUT
+    });
+    let wall = start.elapsed();
+
+    let (mut fetched, mut failed) = (0, 0);
+    for peer in &peers {
+        for event in sim.events(&peer.id()) {
+            match event {
+                Event::RefsFetched { .. } => fetched += 1,
+                Event::FetchFailed { .. } => failed += 1,
+                _ => {}
+            }
+        }
+    }
+    let mut replicas = 0;
+    for peer in &peers {
+        for (rid, creator) in &repos {
+            if peer.storage().get(creator, *rid)?.is_some() {
+                replicas += 1;
+            }
+        }
+    }
+
+    println!("fetch-wall-time: {:?}", wall);
+    println!("fetches: {fetched}");
+    println!("fetches-failed: {failed}");
+    println!("replicas: {replicas}/{}", peers.len() * repos.len());
+
+    // Clone every repository from a random seed, into a fresh storage.
+    let mut total = time::Duration::ZERO;
+    for (r, (rid, creator)) in repos.iter().enumerate() {
+        let seed = &peers[rng.usize(..peers.len())];
+        let url = seed.git_url(*rid, Some(*creator));
+        let path = dir.join("clones").join(format!("repo-{r}"));
+        let storage = Storage::open(path.join("storage"))?;
+        let signer = MockSigner::new(&mut fastrand::Rng::with_seed(rng.u64(..)));
+
+        local::register(storage.clone());
+
+        let start = time::Instant::now();
+        radicle::rad::clone_url(&url, path.join("working"), &signer, &storage)?;
+        let elapsed = start.elapsed();
+
+        println!("clone-wall-time: {rid}: {:?}", elapsed);
+        total += elapsed;
+    }
+    if !repos.is_empty() {
+        println!("clone-wall-time-mean: {:?}", total / repos.len() as u32);
+    }
+
+    if replicas < peers.len() * repos.len() {
+        anyhow::bail!("repositories were not replicated to every node");
+    }
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    let options = Options::from_env()?;
+    let rng = fastrand::Rng::with_seed(options.seed);
+
+    gossip(&options, &rng)?;
+
+    let dir = env::temp_dir().join(format!("rad-bench-{}", process::id()));
+    let result = replicate(&options, &rng, &dir);
+
+    fs::remove_dir_all(&dir).ok();
+
+    result
+}
@@ -0,0 +1,171 @@
+//! Check the integrity of all projects in storage.
+//!
+//! Usage: rad-fsck [--quarantine | --remove] [--jobs <n>] [<id>...]
+//!
+//! Verifies the signed refs of every remote, and the integrity of all collaborative
+//! objects. With `--quarantine`, remotes that fail verification, or that published
+//! changes which can't be loaded or whose signatures are invalid, are moved out of
+//! their namespace, see [`Repository::quarantine_remote`]. With `--remove`, they are
+//! removed instead, and their objects are pruned by the node's next maintenance run, see
+//! [`Repository::remove_remote`]. Other invalid changes are only reported. Exits with a
+//! non-zero status if any project failed verification.
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::{env, process, thread};
+
+use radicle::identity::Id;
+use radicle::storage::git::Repository;
+use radicle::storage::RemoteId;
+use radicle::storage::{ReadStorage, WriteStorage};
+
+/// Maximum number of times a project is re-verified after repairing a remote.
+const MAX_REPAIRS: usize = 16;
+
+/// What to do with remotes that fail verification.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Repair {
+    /// Move the remote out of its namespace.
+    Quarantine,
+    /// Remove the remote.
+    Remove,
+}
+
+impl Repair {
+    fn run(self, repo: &Repository, remote: &RemoteId) -> anyhow::Result<String> {
+        match self {
+            Self::Quarantine => {
+                repo.quarantine_remote(remote)?;
+                Ok(format!("quarantined remote {remote}"))
+            }
+            Self::Remove => {
+                repo.remove_remote(remote)?;
+                Ok(format!("removed remote {remote}"))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Options {
+    repair: Option<Repair>,
+    jobs: Option<usize>,
+    ids: Vec<Id>,
+}
+
+impl Options {
+    fn from_env() -> anyhow::Result<Self> {
+        let mut options = Self::default();
+        let mut args = env::args().skip(1);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--quarantine" | "--remove" if options.repair.is_some() => {
+                    anyhow::bail!("`--quarantine` and `--remove` can't be used together");
+                }
+                "--quarantine" => options.repair = Some(Repair::Quarantine),
+                "--remove" => options.repair = Some(Repair::Remove),
+                "--jobs" => {
+                    let jobs = args
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("`--jobs` requires a value"))?;
+                    options.jobs = Some(jobs.parse()?);
+                }
+                id => options.ids.push(Id::from_str(id)?),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Check a single project. Returns the errors found.
+fn check(repo: &Repository, repair: Option<Repair>) -> anyhow::Result<Vec<String>> {
+    let mut errors = Vec::new();
+
+    for _ in 0..MAX_REPAIRS {
+        let Err(err) = repo.verify() else {
+            break;
+        };
+        errors.push(err.to_string());
+
+        match (err.remote(), repair) {
+            (Some(remote), Some(repair)) => {
+                errors.push(repair.run(repo, remote)?);
+            }
+            _ => break,
+        }
+    }
+
+    let mut repaired = Vec::new();
+    for change in repo.verify_cobs()? {
+        let Some(remote) = change.remote else {
+            errors.push(format!("invalid change {}: {}", change.oid, change.reason));
+            continue;
+        };
+        errors.push(format!(
+            "invalid change {} of remote {remote}: {}",
+            change.oid, change.reason
+        ));
+        if let Some(repair) = repair {
+            if !repaired.contains(&remote) {
+                errors.push(repair.run(repo, &remote)?);
+                repaired.push(remote);
+            }
+        }
+    }
+    Ok(errors)
+}
+
+fn main() -> anyhow::Result<()> {
+    let options = Options::from_env()?;
+    let profile = radicle::Profile::load()?;
+    let storage = &profile.storage;
+    let ids = if options.ids.is_empty() {
+        storage.inventory()?
+    } else {
+        options.ids
+    };
+    let jobs = options
+        .jobs
+        .or_else(|| thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1)
+        .max(1);
+    let queue = Mutex::new(ids.into_iter());
+    let failed = Mutex::new(0);
+
+    thread::scope(|s| {
+        for _ in 0..jobs {
+            s.spawn(|| loop {
+                let Some(id) = queue.lock().unwrap().next() else {
+                    break;
+                };
+                let result = storage
+                    .repository(id)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|repo| check(&repo, options.repair));
+
+                match result {
+                    Ok(errors) if errors.is_empty() => {
+                        println!("ok: {id}");
+                    }
+                    Ok(errors) => {
+                        for err in errors {
+                            println!("error: {id}: {err}");
+                        }
+                        *failed.lock().unwrap() += 1;
+                    }
+                    Err(err) => {
+                        println!("error: {id}: {err}");
+                        *failed.lock().unwrap() += 1;
+                    }
+                }
+            });
+        }
+    });
+
+    let failed = failed.into_inner().unwrap();
+    if failed > 0 {
+        eprintln!("{failed} project(s) failed verification");
+        process::exit(1);
+    }
+    Ok(())
+}
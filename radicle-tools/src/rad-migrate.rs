@@ -0,0 +1,95 @@
+//! Upgrade a radicle home to the current format.
+//!
+//! Usage: rad-migrate [--dry-run]
+//!
+//! Runs pending storage migrations, creates missing profile directories, and re-encrypts
+//! the secret key if it was encrypted with different KDF parameters than the ones
+//! configured via `RAD_KDF_ROUNDS` or the profile configuration. Re-encrypting the key requires `RAD_PASSPHRASE` to be
+//! set. Prints a report of what was changed.
+use std::{env, fs};
+
+use radicle::crypto::ssh::Keystore;
+use radicle::profile;
+use radicle::Storage;
+
+fn main() -> anyhow::Result<()> {
+    let dry_run = match env::args().nth(1).as_deref() {
+        Some("--dry-run") => true,
+        Some(arg) => anyhow::bail!("Error: unknown argument `{arg}`"),
+        None => false,
+    };
+    let home = profile::home()?;
+    let mut changes = Vec::new();
+
+    if !home.path().exists() {
+        anyhow::bail!("Error: no profile found at {}", home.path().display());
+    }
+
+    // Profile layout.
+    let node = home.node();
+    if !node.exists() {
+        if !dry_run {
+            fs::create_dir_all(&node)?;
+        }
+        changes.push(format!("created node directory {}", node.display()));
+    }
+
+    // Storage layout.
+    let storage = Storage::open(home.storage())?;
+    let version = storage.version()?;
+    let migrations = if dry_run {
+        storage.pending_migrations()?
+    } else {
+        storage.migrate()?
+    };
+    for migration in migrations {
+        changes.push(format!(
+            "migrated storage from version {version} to {}: {}",
+            migration.version, migration.description
+        ));
+    }
+
+    // Keystore KDF parameters. Keys are only re-encrypted if parameters were configured,
+    // so that keys aren't downgraded to the default parameters.
+    let config = profile::Config::load(&home.config())?;
+    if let Some(kdf) = profile::kdf_params(&config) {
+        let keystore = Keystore::new(&home.keys()).with_kdf(kdf);
+        let current = keystore.kdf_params()?.filter(|current| *current != kdf);
+
+        if let Some(current) = current {
+            match profile::env::read_passphrase() {
+                Some(passphrase) => {
+                    if !dry_run {
+                        keystore.rekey(passphrase)?;
+                    }
+                    changes.push(format!(
+                        "re-encrypted secret key with {} KDF rounds (was {})",
+                        kdf.rounds, current.rounds
+                    ));
+                }
+                None => {
+                    eprintln!(
+                        "warning: secret key is encrypted with {} KDF rounds instead of {}; \
+                         set `{}` to re-encrypt it",
+                        current.rounds,
+                        kdf.rounds,
+                        profile::env::RAD_PASSPHRASE
+                    );
+                }
+            }
+        }
+    }
+
+    if changes.is_empty() {
+        println!("ok: {} is up to date", home.path().display());
+    }
+    for change in changes {
+        if dry_run {
+            println!("would have {change}");
+        } else {
+            println!("{change}");
+        }
+    }
+
+    Ok(())
+}
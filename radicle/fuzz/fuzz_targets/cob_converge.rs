@@ -0,0 +1,43 @@
+//! Apply causally valid operation sequences in different orders, and check that the
+//! resulting states converge.
+//!
+//! The input selects the COB type, the number of operations, and seeds the generator of
+//! operations and of their delivery orders.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use radicle::cob::{issue, patch, thread, ActorId};
+use radicle::test::arbitrary::cob::{converges, Actions, Changes};
+
+/// Maximum number of operations generated.
+const MAX_OPS: usize = 64;
+
+fn changes<A: Actions + Clone>(seed: u64, size: usize) -> Changes<A, 3> {
+    let rng = fastrand::Rng::with_seed(seed);
+    let mut ops = A::ops(rng.clone(), ActorId::from([0; 32]), size);
+    let permutations = std::array::from_fn(|_| {
+        let p = ops.clone();
+        rng.shuffle(&mut ops);
+        p
+    });
+
+    Changes { permutations }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let [kind, size, seed @ ..] = data else {
+        return;
+    };
+    let Ok(seed) = <[u8; 8]>::try_from(seed) else {
+        return;
+    };
+    let seed = u64::from_le_bytes(seed);
+    let size = *size as usize % MAX_OPS;
+
+    let result = match kind % 3 {
+        0 => converges::<issue::Issue, 3>(changes(seed, size)),
+        1 => converges::<patch::Patch, 3>(changes(seed, size)),
+        _ => converges::<thread::Thread, 3>(changes(seed, size)),
+    };
+    assert!(!result.is_failure());
+});
@@ -0,0 +1,17 @@
+//! Decode change manifests and change contents, in every encoding.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nonempty::NonEmpty;
+use radicle_cob::change::store::Manifest;
+use radicle_cob::encoding::Typed;
+use radicle_cob::Encoding;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<Manifest>(data);
+
+    let contents = NonEmpty::new(data.to_vec());
+    for encoding in [Encoding::Json, Encoding::Cbor] {
+        let _ = Typed::new(encoding, &contents).decode::<serde_json::Value>();
+    }
+});
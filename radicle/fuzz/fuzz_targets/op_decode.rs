@@ -0,0 +1,49 @@
+//! Decode the operations of a change, for every COB type.
+//!
+//! The first byte of the input selects the encoding and the version of the change. The
+//! rest of the input is split into the change contents, on zero bytes.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nonempty::NonEmpty;
+use radicle::cob::op::{Migrate, Ops, Snapshot};
+use radicle::cob::{issue, patch, thread, ActorId, Encoding, Entry};
+use radicle::git;
+use radicle_cob::history::EntryWithClock;
+
+fn decode<A: Migrate>(entry: &EntryWithClock) {
+    let _ = Ops::<A>::try_from(entry);
+    let _ = Snapshot::<A>::try_from(entry);
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((header, data)) = data.split_first() else {
+        return;
+    };
+    let encoding = if header & 0x80 == 0 {
+        Encoding::Json
+    } else {
+        Encoding::Cbor
+    };
+    let version = u32::from(header & 0x7f);
+    let mut chunks = data.split(|b| *b == 0).map(|c| c.to_vec());
+    let Some(head) = chunks.next() else {
+        return;
+    };
+    let contents = NonEmpty::from((head, chunks.collect()));
+    let entry = Entry::new(
+        git::raw::Oid::zero(),
+        ActorId::from([0; 32]),
+        git::raw::Oid::zero().into(),
+        std::iter::empty::<git::raw::Oid>(),
+        contents,
+        0,
+    )
+    .with_version(version)
+    .with_encoding(encoding);
+    let entry = EntryWithClock::root(entry);
+
+    decode::<issue::Action>(&entry);
+    decode::<patch::Action>(&entry);
+    decode::<thread::Action>(&entry);
+});
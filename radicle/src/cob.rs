@@ -1,18 +1,25 @@
+pub mod cache;
 pub mod common;
+pub mod discussion;
+#[cfg(feature = "sql")]
+pub mod inbox;
 pub mod issue;
+pub mod milestone;
 pub mod op;
 pub mod patch;
+pub mod poll;
+pub mod release;
 pub mod store;
 pub mod thread;
 
 #[cfg(test)]
 pub mod test;
 
-pub use cob::{create, get, list, remove, update};
 pub use cob::{
-    identity, object::collaboration::error, CollaborativeObject, Contents, Create, Entry, History,
-    ObjectId, TypeName, Update,
+    attachment, identity, object::collaboration::error, CollaborativeObject, Contents, Create,
+    Encoding, Entry, History, ObjectId, Squash, TypeName, Update,
 };
+pub use cob::{create, get, list, remove, squash, update};
 pub use common::*;
 pub use op::{Actor, ActorId, Op, OpId};
 
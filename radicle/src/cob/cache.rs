@@ -0,0 +1,196 @@
+//! Caching of materialized objects.
+//!
+//! Materializing an object requires loading and verifying every change of its history,
+//! and replaying its operations. A [`FileCache`] stores the materialized state of
+//! objects, keyed by the tips of their references, and the identity document and key
+//! revocations they were materialized against, see [`key`]. Objects whose key hasn't
+//! changed since they were cached are read back from the cache, and are otherwise
+//! materialized again, replacing their cache entry.
+//!
+//! Caches are best-effort: failing to read or write an entry is not an error, and
+//! results in the object being materialized from storage.
+use std::collections::BTreeSet;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use radicle_crdt::Lamport;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::cob::{ActorId, Encoding, ObjectId, Timestamp, TypeName};
+use crate::crypto::hash::Digest;
+use crate::crypto::revocation::Revocations;
+use crate::git;
+
+/// Compute the key of an object in the cache, from the tips of its references, and the
+/// blob of the identity document and the revocations it is materialized against.
+pub fn key(tips: &BTreeSet<git::Oid>, doc: &git::Oid, revocations: &Revocations) -> Digest {
+    // Cached states are only valid for the code that materialized them.
+    let mut bytes = env!("CARGO_PKG_VERSION").as_bytes().to_vec();
+
+    for tip in tips {
+        bytes.extend_from_slice(tip.to_string().as_bytes());
+    }
+    bytes.extend_from_slice(doc.to_string().as_bytes());
+    bytes.extend(serde_json::to_vec(revocations).unwrap_or_default());
+
+    Digest::new(bytes)
+}
+
+/// A materialized object, as stored in the cache.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cached<T> {
+    /// Key the object was cached under, see [`key`].
+    pub key: Digest,
+    /// Author of the root of the object's history.
+    pub author: ActorId,
+    /// Timestamp of the root of the object's history.
+    pub created: Timestamp,
+    /// Tips of the object's history. Unlike the tips its key is computed from, these
+    /// only include valid changes.
+    pub tips: BTreeSet<git::Oid>,
+    /// Materialized state of the object.
+    pub state: T,
+    /// Clock of the object's history.
+    pub clock: Lamport,
+}
+
+/// A cache storing each object in a file under a directory, at
+/// `<typename>/<object-id>`.
+#[derive(Clone, Debug)]
+pub struct FileCache {
+    path: PathBuf,
+}
+
+impl FileCache {
+    /// Create a new cache under the given directory. The directory is created
+    /// lazily, when the first object is cached.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The directory of this cache.
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    /// Get a cached object, if it was cached under the given key.
+    pub fn get<T: DeserializeOwned>(
+        &self,
+        typename: &TypeName,
+        id: &ObjectId,
+        key: &Digest,
+    ) -> Option<Cached<T>> {
+        let bytes = fs::read(self.entry(typename, id)).ok()?;
+        let cached = match Encoding::Cbor.decode::<Cached<T>>(&bytes) {
+            Ok(cached) => cached,
+            Err(err) => {
+                log::warn!("Invalid cache entry for `{typename}` object {id}: {err}");
+                return None;
+            }
+        };
+        (&cached.key == key).then_some(cached)
+    }
+
+    /// Cache an object, replacing any previous entry.
+    pub fn insert<T: Serialize>(&self, typename: &TypeName, id: &ObjectId, cached: &Cached<T>) {
+        if let Err(err) = self.write(typename, id, cached) {
+            log::warn!("Unable to cache `{typename}` object {id}: {err}");
+        }
+    }
+
+    /// Remove an object from the cache.
+    pub fn remove(&self, typename: &TypeName, id: &ObjectId) {
+        match fs::remove_file(self.entry(typename, id)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                log::warn!("Unable to remove cached `{typename}` object {id}: {err}");
+            }
+            _ => {}
+        }
+    }
+
+    fn entry(&self, typename: &TypeName, id: &ObjectId) -> PathBuf {
+        self.path.join(typename.as_str()).join(id.to_string())
+    }
+
+    fn write<T: Serialize>(
+        &self,
+        typename: &TypeName,
+        id: &ObjectId,
+        cached: &Cached<T>,
+    ) -> io::Result<()> {
+        let path = self.entry(typename, id);
+        let bytes = Encoding::Cbor
+            .encode(cached)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        let dir = path.parent().unwrap_or(&self.path);
+
+        fs::create_dir_all(dir)?;
+
+        // Write to a uniquely named file first, so that readers never see a partial
+        // entry, and concurrent writers don't write to the same file.
+        let mut tmp = tempfile::NamedTempFile::new_in(dir)?;
+        tmp.write_all(&bytes)?;
+        tmp.persist(path).map_err(|err| err.error)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::crypto::revocation::Revocation;
+    use crate::crypto::test::signer::MockSigner;
+    use crate::crypto::Signer as _;
+    use crate::test::arbitrary;
+
+    #[test]
+    fn test_file_cache() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache = FileCache::new(tmp.path().join("cobs"));
+        let typename = "xyz.radicle.test".parse::<TypeName>().unwrap();
+        let id = ObjectId::from(arbitrary::oid());
+        let tips = BTreeSet::from([arbitrary::oid()]);
+        let doc = arbitrary::oid();
+        let mut revocations = Revocations::default();
+        let current = key(&tips, &doc, &revocations);
+        let cached = Cached {
+            key: current.clone(),
+            author: arbitrary::gen::<ActorId>(1),
+            created: Timestamp::from(1),
+            tips: tips.clone(),
+            state: String::from("state"),
+            clock: Lamport::initial(),
+        };
+
+        assert!(cache.get::<String>(&typename, &id, &current).is_none());
+        cache.insert(&typename, &id, &cached);
+        assert_eq!(
+            cache
+                .get::<String>(&typename, &id, &current)
+                .map(|c| c.state),
+            Some(cached.state.clone())
+        );
+
+        // Entries are invalidated when the tips, the identity document or the
+        // revocations change.
+        let other = key(&BTreeSet::from([arbitrary::oid()]), &doc, &revocations);
+        assert!(cache.get::<String>(&typename, &id, &other).is_none());
+
+        let other = key(&tips, &arbitrary::oid(), &revocations);
+        assert!(cache.get::<String>(&typename, &id, &other).is_none());
+
+        let eve = MockSigner::default();
+        revocations
+            .insert(Revocation::new(*eve.public_key(), 1).sign(&eve))
+            .unwrap();
+        let other = key(&tips, &doc, &revocations);
+        assert!(cache.get::<String>(&typename, &id, &other).is_none());
+
+        cache.remove(&typename, &id);
+        assert!(cache.get::<String>(&typename, &id, &current).is_none());
+    }
+}
@@ -3,6 +3,7 @@ use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
+use crate::cob::{ObjectId, TypeName};
 use crate::prelude::*;
 
 pub use radicle_crdt::clock::Physical as Timestamp;
@@ -98,6 +99,68 @@ impl From<Tag> for String {
     }
 }
 
+/// A reference to a collaborative object, eg. from an issue to the patch fixing it.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reference {
+    /// Type of the referenced object.
+    pub typename: TypeName,
+    /// Id of the referenced object.
+    pub object_id: ObjectId,
+}
+
+impl Reference {
+    pub fn new(typename: TypeName, object_id: ObjectId) -> Self {
+        Self {
+            typename,
+            object_id,
+        }
+    }
+}
+
+/// A mention of a user in a comment body, eg. `@did:key:z6Mk..` or `@alice`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Mention {
+    /// User mentioned by key, given either as a DID or as a bare public key.
+    Key(PublicKey),
+    /// User mentioned by alias. See [`crate::profile::Config::alias`].
+    Alias(String),
+}
+
+impl Mention {
+    /// Parse the mentions in a comment body. Mentions start with `@` and end at the next
+    /// whitespace or punctuation, such that eg. email addresses are not mentions.
+    pub fn parse(body: &str) -> Vec<Self> {
+        body.split_whitespace()
+            .filter_map(|word| word.strip_prefix('@'))
+            .map(|word| word.trim_end_matches(|c: char| c.is_ascii_punctuation()))
+            .filter_map(|word| {
+                if let Ok(did) = Did::decode(word) {
+                    Some(Self::Key(*did))
+                } else if let Ok(key) = PublicKey::from_str(word) {
+                    Some(Self::Key(key))
+                } else if !word.is_empty()
+                    && word
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+                {
+                    Some(Self::Alias(word.to_owned()))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Whether this mentions the user with the given key and alias.
+    pub fn is(&self, key: &PublicKey, alias: Option<&str>) -> bool {
+        match self {
+            Self::Key(k) => k == key,
+            Self::Alias(a) => alias.map_or(false, |alias| a == alias),
+        }
+    }
+}
+
 /// RGB color.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct Color(u32);
@@ -157,6 +220,26 @@ impl<'a> Deserialize<'a> for Color {
 mod test {
     use super::*;
 
+    #[test]
+    fn test_mentions() {
+        let key = PublicKey::from_str("z6MknSLrJoTcukLrE435hVNQT4JUhbvWLX4kUzqkEStBU8Vi").unwrap();
+        let body = format!(
+            "Thanks @alice, @did:key:{key}! cc @{key}. Mail me at bob@radicle.xyz, @ or @#?"
+        );
+
+        assert_eq!(
+            Mention::parse(&body),
+            vec![
+                Mention::Alias(String::from("alice")),
+                Mention::Key(key),
+                Mention::Key(key)
+            ]
+        );
+        assert!(Mention::Alias(String::from("alice")).is(&key, Some("alice")));
+        assert!(!Mention::Alias(String::from("alice")).is(&key, None));
+        assert!(Mention::Key(key).is(&key, None));
+    }
+
     #[test]
     fn test_color() {
         let c = Color::from_str("#ffccaa").unwrap();
@@ -0,0 +1,444 @@
+use std::collections::BTreeSet;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use radicle_crdt::clock;
+use radicle_crdt::{LWWReg, Max};
+
+use crate::cob;
+use crate::cob::common::{Author, Reaction, Reference};
+use crate::cob::store::FromHistory as _;
+use crate::cob::store::Transaction;
+use crate::cob::thread;
+use crate::cob::thread::{CommentId, Thread};
+use crate::cob::{store, ObjectId, OpId, TypeName};
+use crate::crypto::{PublicKey, Signer, Verified};
+use crate::git;
+use crate::identity::Doc;
+use crate::storage::git as storage;
+
+/// Discussion operation.
+pub type Op = cob::Op<Action>;
+
+/// Type name of a discussion.
+pub static TYPENAME: Lazy<TypeName> =
+    Lazy::new(|| FromStr::from_str("xyz.radicle.discussion").expect("type name is valid"));
+
+/// Identifier for a discussion.
+pub type DiscussionId = ObjectId;
+
+/// Error updating or creating discussions.
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("thread apply failed: {0}")]
+    Thread(#[from] thread::OpError),
+    #[error("store: {0}")]
+    Store(#[from] store::Error),
+}
+
+/// Discussion state. Accumulates [`Action`].
+///
+/// A discussion is a titled thread that isn't attached to code, for conversations
+/// that are neither issues nor patches.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Discussion {
+    title: LWWReg<Max<String>, clock::Lamport>,
+    thread: Thread,
+}
+
+impl Default for Discussion {
+    fn default() -> Self {
+        Self {
+            title: Max::from(String::default()).into(),
+            thread: Thread::default(),
+        }
+    }
+}
+
+impl store::FromHistory for Discussion {
+    type Action = Action;
+    type Error = Error;
+    type State = ();
+
+    fn type_name() -> &'static TypeName {
+        &*TYPENAME
+    }
+
+    fn state(&self) {}
+
+    /// Only delegates, their devices and the discussion author may change the title.
+    /// Operations in the change that creates the discussion are always authorized.
+    fn authorize(&self, op: &Op, doc: &Doc<Verified>) -> bool {
+        match op.action {
+            Action::Edit { .. } => {
+                doc.is_authorized(&op.author)
+                    || self.author().map_or(true, |a| *a.id() == op.author)
+            }
+            Action::Thread { .. } => true,
+        }
+    }
+
+    fn references(&self) -> Vec<Reference> {
+        self.thread.references().cloned().collect()
+    }
+
+    fn apply(&mut self, ops: impl IntoIterator<Item = Op>) -> Result<(), Error> {
+        for op in ops {
+            match op.action {
+                Action::Edit { title } => {
+                    self.title.set(title, op.clock);
+                }
+                Action::Thread { action } => {
+                    self.thread
+                        .apply([cob::Op::new(action, op.author, op.timestamp, op.clock)])?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Discussion {
+    /// Discussion title.
+    pub fn title(&self) -> &str {
+        self.title.get().as_str()
+    }
+
+    /// Discussion author, ie. the author of the first comment.
+    pub fn author(&self) -> Option<Author> {
+        self.thread
+            .comments()
+            .next()
+            .map(|(_, c)| Author::new(c.author()))
+    }
+
+    /// Body of the first comment.
+    pub fn description(&self) -> Option<&str> {
+        self.thread.comments().next().map(|(_, c)| c.body())
+    }
+
+    /// Discussion comments, including the first one.
+    pub fn comments(&self) -> impl Iterator<Item = (&CommentId, &thread::Comment)> {
+        self.thread.comments()
+    }
+}
+
+impl Deref for Discussion {
+    type Target = Thread;
+
+    fn deref(&self) -> &Self::Target {
+        &self.thread
+    }
+}
+
+impl store::Transaction<Discussion> {
+    /// Set the discussion title.
+    pub fn edit(&mut self, title: impl ToString) -> OpId {
+        self.push(Action::Edit {
+            title: title.to_string(),
+        })
+    }
+
+    /// Create the discussion thread.
+    pub fn thread<S: ToString>(&mut self, body: S) -> CommentId {
+        self.push(Action::from(thread::Action::Comment {
+            body: body.to_string(),
+            reply_to: None,
+        }))
+    }
+
+    /// Comment on a discussion.
+    pub fn comment<S: ToString>(&mut self, body: S, reply_to: CommentId) -> CommentId {
+        self.push(Action::from(thread::Action::Comment {
+            body: body.to_string(),
+            reply_to: Some(reply_to),
+        }))
+    }
+
+    /// Edit a discussion comment.
+    pub fn edit_comment<S: ToString>(&mut self, id: CommentId, body: S) -> OpId {
+        self.push(Action::from(thread::Action::Edit {
+            id,
+            body: body.to_string(),
+        }))
+    }
+
+    /// Redact a discussion comment.
+    pub fn redact_comment(&mut self, id: CommentId) -> OpId {
+        self.push(Action::from(thread::Action::Redact { id }))
+    }
+
+    /// React to a discussion comment.
+    pub fn react(&mut self, to: CommentId, reaction: Reaction) -> OpId {
+        self.push(Action::from(thread::Action::React {
+            to,
+            reaction,
+            active: true,
+        }))
+    }
+
+    /// Reference another object from the discussion, eg. an issue.
+    pub fn reference(&mut self, typename: TypeName, object_id: ObjectId) -> OpId {
+        self.push(Action::from(thread::Action::Reference {
+            typename,
+            object_id,
+        }))
+    }
+}
+
+pub struct DiscussionMut<'a, 'g> {
+    id: ObjectId,
+    clock: clock::Lamport,
+    tips: BTreeSet<git::Oid>,
+    discussion: Discussion,
+    store: &'g mut Discussions<'a>,
+}
+
+impl<'a, 'g> DiscussionMut<'a, 'g> {
+    /// Get the discussion id.
+    pub fn id(&self) -> &DiscussionId {
+        &self.id
+    }
+
+    /// Get the internal logical clock.
+    pub fn clock(&self) -> &clock::Lamport {
+        &self.clock
+    }
+
+    /// Set the discussion title.
+    pub fn edit<G: Signer>(&mut self, title: impl ToString, signer: &G) -> Result<OpId, Error> {
+        self.transaction("Edit", signer, |tx| tx.edit(title))
+    }
+
+    /// Comment on a discussion.
+    pub fn comment<G: Signer, S: ToString>(
+        &mut self,
+        body: S,
+        reply_to: CommentId,
+        signer: &G,
+    ) -> Result<CommentId, Error> {
+        assert!(self.thread.comment(&reply_to).is_some());
+        self.transaction("Comment", signer, |tx| tx.comment(body, reply_to))
+    }
+
+    /// Edit a discussion comment. Only the comment author can edit a comment.
+    pub fn edit_comment<G: Signer, S: ToString>(
+        &mut self,
+        id: CommentId,
+        body: S,
+        signer: &G,
+    ) -> Result<OpId, Error> {
+        self.transaction("Edit comment", signer, |tx| tx.edit_comment(id, body))
+    }
+
+    /// Redact a discussion comment. Only the comment author can redact a comment.
+    pub fn redact_comment<G: Signer>(&mut self, id: CommentId, signer: &G) -> Result<OpId, Error> {
+        self.transaction("Redact comment", signer, |tx| tx.redact_comment(id))
+    }
+
+    /// React to a discussion comment.
+    pub fn react<G: Signer>(
+        &mut self,
+        to: CommentId,
+        reaction: Reaction,
+        signer: &G,
+    ) -> Result<OpId, Error> {
+        self.transaction("React", signer, |tx| tx.react(to, reaction))
+    }
+
+    /// Reference another object from the discussion, eg. an issue.
+    pub fn reference<G: Signer>(
+        &mut self,
+        typename: TypeName,
+        object_id: ObjectId,
+        signer: &G,
+    ) -> Result<OpId, Error> {
+        self.transaction("Reference", signer, |tx| tx.reference(typename, object_id))
+    }
+
+    pub fn transaction<G, F, T>(
+        &mut self,
+        message: &str,
+        signer: &G,
+        operations: F,
+    ) -> Result<T, Error>
+    where
+        G: Signer,
+        F: FnOnce(&mut Transaction<Discussion>) -> T,
+    {
+        let mut tx = Transaction::new(*signer.public_key(), self.clock, self.tips.clone());
+        let output = operations(&mut tx);
+        let (ops, clock, tips) = tx.commit(message, self.id, &mut self.store.raw, signer)?;
+
+        self.store.raw.apply(&mut self.discussion, ops)?;
+        self.clock = clock;
+        self.tips = tips;
+
+        Ok(output)
+    }
+}
+
+impl<'a, 'g> Deref for DiscussionMut<'a, 'g> {
+    type Target = Discussion;
+
+    fn deref(&self) -> &Self::Target {
+        &self.discussion
+    }
+}
+
+pub struct Discussions<'a> {
+    raw: store::Store<'a, Discussion>,
+}
+
+impl<'a> Deref for Discussions<'a> {
+    type Target = store::Store<'a, Discussion>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.raw
+    }
+}
+
+impl<'a> Discussions<'a> {
+    /// Open a discussions store.
+    pub fn open(
+        whoami: PublicKey,
+        repository: &'a storage::Repository,
+    ) -> Result<Self, store::Error> {
+        let raw = store::Store::open(whoami, repository)?;
+
+        Ok(Self { raw })
+    }
+
+    /// Get a discussion.
+    pub fn get(&self, id: &ObjectId) -> Result<Option<Discussion>, store::Error> {
+        self.raw.get(id).map(|r| r.map(|(d, _clock)| d))
+    }
+
+    /// Get a discussion mutably.
+    pub fn get_mut<'g>(&'g mut self, id: &ObjectId) -> Result<DiscussionMut<'a, 'g>, store::Error> {
+        let (discussion, clock, tips) = self
+            .raw
+            .get_with_tips(id)?
+            .ok_or_else(move || store::Error::NotFound(TYPENAME.clone(), *id))?;
+
+        Ok(DiscussionMut {
+            id: *id,
+            clock,
+            tips,
+            discussion,
+            store: self,
+        })
+    }
+
+    /// Start a new discussion.
+    pub fn create<'g, G: Signer>(
+        &'g mut self,
+        title: impl ToString,
+        body: impl ToString,
+        signer: &G,
+    ) -> Result<DiscussionMut<'a, 'g>, Error> {
+        let (id, discussion, clock) =
+            Transaction::initial("Create discussion", &mut self.raw, signer, |tx| {
+                tx.thread(body);
+                tx.edit(title);
+            })?;
+
+        Ok(DiscussionMut {
+            id,
+            clock,
+            tips: BTreeSet::from([id.into()]),
+            discussion,
+            store: self,
+        })
+    }
+
+    /// Remove a discussion.
+    pub fn remove(&self, id: &ObjectId) -> Result<(), store::Error> {
+        self.raw.remove(id)
+    }
+}
+
+/// Discussion operation.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Action {
+    /// Set the title.
+    Edit { title: String },
+    /// Act on the discussion thread.
+    Thread { action: thread::Action },
+}
+
+impl cob::op::Migrate for Action {}
+
+impl From<thread::Action> for Action {
+    fn from(action: thread::Action) -> Self {
+        Self::Thread { action }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::cob::issue;
+    use crate::crypto::test::signer::MockSigner;
+    use crate::test;
+    use crate::test::arbitrary;
+
+    #[test]
+    fn test_discussion_create_and_get() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let mut discussions = Discussions::open(*signer.public_key(), &project).unwrap();
+        let created = discussions
+            .create("Roadmap", "What should we work on next?", &signer)
+            .unwrap();
+        let id = *created.id();
+        let discussion = discussions.get(&id).unwrap().unwrap();
+
+        assert_eq!(discussion.title(), "Roadmap");
+        assert_eq!(
+            discussion.description(),
+            Some("What should we work on next?")
+        );
+        assert_eq!(discussion.author(), Some(discussions.author()));
+        assert_eq!(discussion.comments().count(), 1);
+    }
+
+    #[test]
+    fn test_discussion_comment_and_edit() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let bob = MockSigner::default();
+        let mut discussions = Discussions::open(*signer.public_key(), &project).unwrap();
+        let issue_id = ObjectId::from(arbitrary::oid());
+        let mut discussion = discussions
+            .create("Roadmap", "What should we work on next?", &signer)
+            .unwrap();
+        let (root, _) = discussion.root().unwrap();
+        let root = *root;
+
+        discussion.comment("Releases!", root, &bob).unwrap();
+        discussion
+            .reference(issue::TYPENAME.clone(), issue_id, &bob)
+            .unwrap();
+        discussion.edit("Roadmap for 2024", &signer).unwrap();
+
+        let id = *discussion.id();
+        let discussion = discussions.get(&id).unwrap().unwrap();
+        let (_, reply) = discussion.comments().nth(1).unwrap();
+
+        assert_eq!(discussion.title(), "Roadmap for 2024");
+        assert_eq!(reply.body(), "Releases!");
+        assert_eq!(reply.reply_to(), Some(root));
+        assert_eq!(
+            discussion.references(),
+            vec![Reference::new(issue::TYPENAME.clone(), issue_id)]
+        );
+    }
+}
@@ -0,0 +1,386 @@
+//! Local notification inbox.
+//!
+//! Notifications are derived from the collaborative objects of a repository, see
+//! [`Inbox::sync`], and persisted in a SQLite database under the profile home, so that
+//! they can be listed and cleared. Cleared notifications aren't added back to the inbox
+//! when the repository is synced again.
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use sqlite as sql;
+use thiserror::Error;
+
+use crate::cob::common::Timestamp;
+use crate::cob::issue::{self, Issues};
+use crate::cob::patch::{self, Patches};
+use crate::cob::{store, ActorId, ObjectId, TypeName};
+use crate::crypto::PublicKey;
+use crate::identity::Id;
+use crate::storage::git::{ProjectError, Repository};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("internal error: {0}")]
+    Internal(#[from] sql::Error),
+    #[error("store: {0}")]
+    Store(#[from] store::Error),
+    #[error("project: {0}")]
+    Project(#[from] ProjectError),
+    #[error("invalid entry in inbox: {0}")]
+    InvalidEntry(String),
+}
+
+/// Notification identifier, local to the inbox.
+pub type NotificationId = i64;
+
+/// Kind of notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Kind {
+    /// We were mentioned in a comment.
+    Mention,
+    /// A patch revision was proposed in a repository we're a delegate of.
+    ReviewRequest,
+    /// A patch revision of ours was reviewed with a verdict.
+    Verdict,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mention => write!(f, "mention"),
+            Self::ReviewRequest => write!(f, "review-request"),
+            Self::Verdict => write!(f, "verdict"),
+        }
+    }
+}
+
+impl FromStr for Kind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mention" => Ok(Self::Mention),
+            "review-request" => Ok(Self::ReviewRequest),
+            "verdict" => Ok(Self::Verdict),
+            _ => Err(Error::InvalidEntry(format!(
+                "unknown notification kind `{s}`"
+            ))),
+        }
+    }
+}
+
+/// A notification about a collaborative object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    /// Notification identifier.
+    pub id: NotificationId,
+    /// Repository of the object.
+    pub repo: Id,
+    /// Type of the object.
+    pub typename: TypeName,
+    /// The object the notification is about.
+    pub object: ObjectId,
+    /// Kind of notification.
+    pub kind: Kind,
+    /// User who caused the notification.
+    pub author: ActorId,
+    /// When the notification was caused.
+    pub timestamp: Timestamp,
+}
+
+/// Notification inbox, backed by SQLite. Clones share the same database connection.
+#[derive(Clone)]
+pub struct Inbox {
+    db: Arc<Mutex<sql::Connection>>,
+}
+
+impl fmt::Debug for Inbox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Inbox(..)")
+    }
+}
+
+impl Inbox {
+    const SCHEMA: &str = include_str!("inbox/schema.sql");
+
+    /// Open an inbox at the given path. Creates a new empty inbox if an existing
+    /// inbox isn't found.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let db = sql::Connection::open(path)?;
+        db.execute(Self::SCHEMA)?;
+
+        Ok(Self {
+            db: Arc::new(Mutex::new(db)),
+        })
+    }
+
+    /// Create a new in-memory inbox.
+    pub fn memory() -> Result<Self, Error> {
+        Self::open(":memory:")
+    }
+
+    /// Add the notifications for `whoami` found in the issues and patches of a repository.
+    /// Returns the number of new notifications.
+    ///
+    /// We are notified of comments mentioning our key or alias, patch revisions proposed
+    /// by others if we're a delegate of the repository, and verdicts on our patches.
+    pub fn sync(
+        &self,
+        repo: &Repository,
+        whoami: &PublicKey,
+        alias: Option<&str>,
+    ) -> Result<usize, Error> {
+        let (_, doc) = repo.identity_doc()?;
+        let is_delegate = doc.is_delegate(whoami);
+        let mut count = 0;
+
+        for result in Issues::open(*whoami, repo)?.all()? {
+            let (id, issue, _) = result?;
+
+            for (comment_id, comment) in issue.comments() {
+                if comment.author() == *whoami
+                    || !comment.mentions().iter().any(|m| m.is(whoami, alias))
+                {
+                    continue;
+                }
+                count += self.insert(
+                    &repo.id,
+                    &issue::TYPENAME,
+                    &id,
+                    Kind::Mention,
+                    &comment.author(),
+                    comment_id.clock().get(),
+                    comment.timestamp(),
+                )?;
+            }
+        }
+
+        for result in Patches::open(*whoami, repo)?.all()? {
+            let (id, patch, _) = result?;
+            let is_author = patch.author().id() == whoami;
+
+            for (revision_id, revision) in patch.revisions() {
+                let clock = revision_id.clock().get();
+
+                for (comment_id, comment) in revision.discussion.comments() {
+                    if comment.author() == *whoami
+                        || !comment.mentions().iter().any(|m| m.is(whoami, alias))
+                    {
+                        continue;
+                    }
+                    count += self.insert(
+                        &repo.id,
+                        &patch::TYPENAME,
+                        &id,
+                        Kind::Mention,
+                        &comment.author(),
+                        comment_id.clock().get(),
+                        comment.timestamp(),
+                    )?;
+                }
+                if is_delegate && revision.author.id() != whoami {
+                    count += self.insert(
+                        &repo.id,
+                        &patch::TYPENAME,
+                        &id,
+                        Kind::ReviewRequest,
+                        revision.author.id(),
+                        clock,
+                        revision.timestamp,
+                    )?;
+                }
+                if is_author {
+                    for (reviewer, review) in revision.reviews.iter() {
+                        if reviewer == whoami || review.verdict().is_none() {
+                            continue;
+                        }
+                        count += self.insert(
+                            &repo.id,
+                            &patch::TYPENAME,
+                            &id,
+                            Kind::Verdict,
+                            reviewer,
+                            clock,
+                            *review.timestamp.get(),
+                        )?;
+                    }
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// Get the notifications that weren't cleared, latest first.
+    pub fn all(&self) -> Result<Vec<Notification>, Error> {
+        let db = self.db();
+        let stmt = db.prepare(
+            "SELECT id, repo, typename, object, kind, author, timestamp
+             FROM notifications
+             WHERE cleared = 0
+             ORDER BY timestamp DESC, id DESC",
+        )?;
+        let mut notifications = Vec::new();
+
+        for row in stmt.into_iter() {
+            let row = row?;
+            let typename = row.try_read::<&str, _>("typename")?;
+            let object = row.try_read::<&str, _>("object")?;
+            let kind = row.try_read::<&str, _>("kind")?;
+
+            notifications.push(Notification {
+                id: row.try_read::<i64, _>("id")?,
+                repo: row.try_read::<Id, _>("repo")?,
+                typename: TypeName::from_str(typename)
+                    .map_err(|e| Error::InvalidEntry(e.to_string()))?,
+                object: ObjectId::from_str(object)
+                    .map_err(|e| Error::InvalidEntry(e.to_string()))?,
+                kind: Kind::from_str(kind)?,
+                author: row.try_read::<PublicKey, _>("author")?,
+                timestamp: Timestamp::from(row.try_read::<i64, _>("timestamp")? as u64),
+            });
+        }
+        Ok(notifications)
+    }
+
+    /// Clear a notification from the inbox. Returns whether it was found.
+    pub fn clear(&self, id: NotificationId) -> Result<bool, Error> {
+        let db = self.db();
+        let mut stmt =
+            db.prepare("UPDATE notifications SET cleared = 1 WHERE id = ? AND cleared = 0")?;
+        stmt.bind((1, id))?;
+        stmt.next()?;
+
+        Ok(db.change_count() > 0)
+    }
+
+    /// Clear all notifications from the inbox. Returns the number of cleared notifications.
+    pub fn clear_all(&self) -> Result<usize, Error> {
+        let db = self.db();
+        db.execute("UPDATE notifications SET cleared = 1 WHERE cleared = 0")?;
+
+        Ok(db.change_count())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn insert(
+        &self,
+        repo: &Id,
+        typename: &TypeName,
+        object: &ObjectId,
+        kind: Kind,
+        author: &ActorId,
+        clock: u64,
+        timestamp: Timestamp,
+    ) -> Result<usize, Error> {
+        let db = self.db();
+        let mut stmt = db.prepare(
+            "INSERT INTO notifications (repo, typename, object, kind, author, clock, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT DO NOTHING",
+        )?;
+        stmt.bind((1, repo))?;
+        stmt.bind((2, typename.to_string().as_str()))?;
+        stmt.bind((3, object.to_string().as_str()))?;
+        stmt.bind((4, kind.to_string().as_str()))?;
+        stmt.bind((5, author))?;
+        stmt.bind((6, clock as i64))?;
+        stmt.bind((7, timestamp.as_secs() as i64))?;
+        stmt.next()?;
+
+        Ok(db.change_count())
+    }
+
+    fn db(&self) -> MutexGuard<sql::Connection> {
+        self.db.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cob::patch::MergeTarget;
+    use crate::crypto::test::signer::MockSigner;
+    use crate::crypto::Signer;
+    use crate::test;
+    use crate::test::arbitrary;
+
+    #[test]
+    fn test_sync() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let bob = MockSigner::default();
+        let inbox = Inbox::memory().unwrap();
+        let mut issues = Issues::open(*signer.public_key(), &project).unwrap();
+        let mut issue = issues
+            .create("My first issue", "Blah blah blah.", &[], &signer)
+            .unwrap();
+        let (root, _) = issue.root().unwrap();
+        let root = *root;
+
+        issue.comment("Thoughts, @alice?", root, &bob).unwrap();
+        issue
+            .comment(format!("@{}", signer.public_key()), root, &bob)
+            .unwrap();
+        issue.comment("Hello @bob!", root, &signer).unwrap();
+        issue.comment("Hello @carol!", root, &bob).unwrap();
+
+        let mut patches = Patches::open(*signer.public_key(), &project).unwrap();
+        let mut patch = patches
+            .create(
+                "My first patch",
+                "Blah blah blah.",
+                MergeTarget::Delegates,
+                arbitrary::oid(),
+                arbitrary::oid(),
+                &[],
+                &signer,
+            )
+            .unwrap();
+        let (revision, _) = patch.latest().unwrap();
+        let revision = *revision;
+        patch
+            .review(revision, Some(patch::Verdict::Accept), None, vec![], &bob)
+            .unwrap();
+
+        assert_eq!(
+            inbox
+                .sync(&project, signer.public_key(), Some("alice"))
+                .unwrap(),
+            3
+        );
+        assert_eq!(
+            inbox
+                .sync(&project, signer.public_key(), Some("alice"))
+                .unwrap(),
+            0,
+            "notifications aren't duplicated"
+        );
+
+        let notifications = inbox.all().unwrap();
+        let kinds = notifications.iter().map(|n| n.kind).collect::<Vec<_>>();
+        assert_eq!(notifications.len(), 3);
+        assert!(notifications.iter().all(|n| n.author == *bob.public_key()));
+        assert_eq!(
+            kinds.iter().filter(|k| **k == Kind::Mention).count(),
+            2,
+            "{kinds:?}"
+        );
+        assert!(kinds.contains(&Kind::Verdict));
+
+        assert!(inbox.clear(notifications[0].id).unwrap());
+        assert!(!inbox.clear(notifications[0].id).unwrap());
+        assert_eq!(inbox.all().unwrap().len(), 2);
+        assert_eq!(inbox.clear_all().unwrap(), 2);
+        assert!(inbox.all().unwrap().is_empty());
+
+        inbox
+            .sync(&project, signer.public_key(), Some("alice"))
+            .unwrap();
+        assert!(
+            inbox.all().unwrap().is_empty(),
+            "cleared notifications stay cleared"
+        );
+    }
+}
@@ -0,0 +1,25 @@
+--
+-- Notification inbox SQL schema.
+--
+create table if not exists "notifications" (
+  -- Notification identifier.
+  "id"           integer   primary key autoincrement,
+  -- Repository the notification is about.
+  "repo"         text      not null,
+  -- Type name of the object the notification is about, eg. `xyz.radicle.issue`.
+  "typename"     text      not null,
+  -- Object the notification is about.
+  "object"       text      not null,
+  -- Kind of notification, eg. `mention`.
+  "kind"         text      not null,
+  -- User who caused the notification.
+  "author"       text      not null,
+  -- Logical clock of the operation that caused the notification, within the object.
+  "clock"        integer   not null,
+  -- When the operation that caused the notification happened, in seconds.
+  "timestamp"    integer   not null,
+  -- Whether the notification was cleared from the inbox.
+  "cleared"      integer   not null default 0,
+
+  unique ("repo", "object", "kind", "author", "clock")
+);
@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::ops::Deref;
 use std::str::FromStr;
 
@@ -9,15 +10,19 @@ use radicle_crdt::clock;
 use radicle_crdt::{LWWReg, LWWSet, Max, Semilattice};
 
 use crate::cob;
-use crate::cob::common::{Author, Reaction, Tag};
+use crate::cob::common::{Author, Reaction, Reference, Tag};
 use crate::cob::store::FromHistory as _;
 use crate::cob::store::Transaction;
 use crate::cob::thread;
 use crate::cob::thread::{CommentId, Thread};
 use crate::cob::{store, ActorId, ObjectId, OpId, TypeName};
-use crate::crypto::{PublicKey, Signer};
+use crate::crypto::{PublicKey, Signer, Verified};
+use crate::git;
+use crate::identity::Doc;
 use crate::storage::git as storage;
 
+pub mod template;
+
 /// Issue operation.
 pub type Op = cob::Op<Action>;
 
@@ -37,6 +42,8 @@ pub enum Error {
     Thread(#[from] thread::OpError),
     #[error("store: {0}")]
     Store(#[from] store::Error),
+    #[error("attachment: {0}")]
+    Attachment(#[from] cob::attachment::error::Attachment),
 }
 
 /// Reason why an issue was closed.
@@ -44,9 +51,26 @@ pub enum Error {
 #[serde(rename_all = "camelCase")]
 pub enum CloseReason {
     Other,
+    /// The issue won't be fixed, eg. because it isn't considered a defect.
+    WontFix,
+    /// The issue is a duplicate of another issue.
+    Duplicate {
+        of: IssueId,
+    },
     Solved,
 }
 
+impl std::fmt::Display for CloseReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Other => write!(f, "other"),
+            Self::WontFix => write!(f, "won't fix"),
+            Self::Duplicate { of } => write!(f, "duplicate of {of}"),
+            Self::Solved => write!(f, "solved"),
+        }
+    }
+}
+
 /// Issue state.
 #[derive(Debug, Default, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", tag = "status")]
@@ -77,11 +101,12 @@ impl State {
 }
 
 /// Issue state. Accumulates [`Action`].
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Issue {
     assignees: LWWSet<ActorId>,
     title: LWWReg<Max<String>, clock::Lamport>,
-    state: LWWReg<Max<State>, clock::Lamport>,
+    /// The state of the issue, along with the author of the last state change.
+    state: LWWReg<Max<(State, Option<ActorId>)>, clock::Lamport>,
     tags: LWWSet<Tag>,
     thread: Thread,
 }
@@ -101,7 +126,7 @@ impl Default for Issue {
         Self {
             assignees: LWWSet::default(),
             title: Max::from(String::default()).into(),
-            state: Max::from(State::default()).into(),
+            state: Max::from((State::default(), None)).into(),
             tags: LWWSet::default(),
             thread: Thread::default(),
         }
@@ -111,11 +136,32 @@ impl Default for Issue {
 impl store::FromHistory for Issue {
     type Action = Action;
     type Error = Error;
+    type State = State;
 
     fn type_name() -> &'static TypeName {
         &*TYPENAME
     }
 
+    fn state(&self) -> State {
+        self.state.get().get().0
+    }
+
+    /// Only delegates, their devices and the issue author may open or close an issue.
+    /// Operations in the change that creates the issue are always authorized.
+    fn authorize(&self, op: &Op, doc: &Doc<Verified>) -> bool {
+        match op.action {
+            Action::Lifecycle { .. } => {
+                doc.is_authorized(&op.author)
+                    || self.author().map_or(true, |a| *a.id() == op.author)
+            }
+            _ => true,
+        }
+    }
+
+    fn references(&self) -> Vec<Reference> {
+        self.thread.references().cloned().collect()
+    }
+
     fn apply(&mut self, ops: impl IntoIterator<Item = Op>) -> Result<(), Error> {
         for op in ops {
             match op.action {
@@ -131,7 +177,7 @@ impl store::FromHistory for Issue {
                     self.title.set(title, op.clock);
                 }
                 Action::Lifecycle { state } => {
-                    self.state.set(state, op.clock);
+                    self.state.set((state, Some(op.author)), op.clock);
                 }
                 Action::Tag { add, remove } => {
                     for tag in add {
@@ -161,7 +207,24 @@ impl Issue {
     }
 
     pub fn state(&self) -> &State {
-        self.state.get()
+        &self.state.get().get().0
+    }
+
+    /// The actor who closed the issue, if it is closed.
+    pub fn closed_by(&self) -> Option<&ActorId> {
+        match self.state.get().get() {
+            (State::Closed { .. }, by) => by.as_ref(),
+            (State::Open, _) => None,
+        }
+    }
+
+    /// The actor who reopened the issue, if it is open and was closed since it was
+    /// created.
+    pub fn reopened_by(&self) -> Option<&ActorId> {
+        match self.state.get().get() {
+            (State::Open, by) => by.as_ref(),
+            (State::Closed { .. }, _) => None,
+        }
     }
 
     pub fn tags(&self) -> impl Iterator<Item = &Tag> {
@@ -228,6 +291,19 @@ impl store::Transaction<Issue> {
         }))
     }
 
+    /// Edit an issue comment.
+    pub fn edit_comment<S: ToString>(&mut self, id: CommentId, body: S) -> OpId {
+        self.push(Action::from(thread::Action::Edit {
+            id,
+            body: body.to_string(),
+        }))
+    }
+
+    /// Redact an issue comment.
+    pub fn redact_comment(&mut self, id: CommentId) -> OpId {
+        self.push(Action::from(thread::Action::Redact { id }))
+    }
+
     /// Tag an issue.
     pub fn tag(
         &mut self,
@@ -250,11 +326,37 @@ impl store::Transaction<Issue> {
             },
         })
     }
+
+    /// Reference another object from the issue, eg. a patch.
+    pub fn reference(&mut self, typename: TypeName, object_id: ObjectId) -> OpId {
+        self.push(Action::from(thread::Action::Reference {
+            typename,
+            object_id,
+        }))
+    }
+
+    /// Attach files to an issue comment.
+    pub fn attach_to(
+        &mut self,
+        to: CommentId,
+        files: impl IntoIterator<Item = (String, Vec<u8>)>,
+    ) -> OpId {
+        let attachments = files
+            .into_iter()
+            .map(|(name, content)| {
+                let oid = self.attach(&name, content);
+                thread::Attachment { name, oid }
+            })
+            .collect();
+
+        self.push(Action::from(thread::Action::Attach { to, attachments }))
+    }
 }
 
 pub struct IssueMut<'a, 'g> {
     id: ObjectId,
     clock: clock::Lamport,
+    tips: BTreeSet<git::Oid>,
     issue: Issue,
     store: &'g mut Issues<'a>,
 }
@@ -299,6 +401,21 @@ impl<'a, 'g> IssueMut<'a, 'g> {
         self.transaction("Comment", signer, |tx| tx.comment(body, reply_to))
     }
 
+    /// Edit an issue comment. Only the comment author can edit a comment.
+    pub fn edit_comment<G: Signer, S: ToString>(
+        &mut self,
+        id: CommentId,
+        body: S,
+        signer: &G,
+    ) -> Result<OpId, Error> {
+        self.transaction("Edit comment", signer, |tx| tx.edit_comment(id, body))
+    }
+
+    /// Redact an issue comment. Only the comment author can redact a comment.
+    pub fn redact_comment<G: Signer>(&mut self, id: CommentId, signer: &G) -> Result<OpId, Error> {
+        self.transaction("Redact comment", signer, |tx| tx.redact_comment(id))
+    }
+
     /// Tag an issue.
     pub fn tag<G: Signer>(
         &mut self,
@@ -319,6 +436,30 @@ impl<'a, 'g> IssueMut<'a, 'g> {
         self.transaction("React", signer, |tx| tx.react(to, reaction))
     }
 
+    /// Reference another object from the issue, eg. a patch.
+    pub fn reference<G: Signer>(
+        &mut self,
+        typename: TypeName,
+        object_id: ObjectId,
+        signer: &G,
+    ) -> Result<OpId, Error> {
+        self.transaction("Reference", signer, |tx| tx.reference(typename, object_id))
+    }
+
+    /// Attach files to an issue comment. Only the comment author can attach files
+    /// to a comment.
+    pub fn attach<G: Signer>(
+        &mut self,
+        to: CommentId,
+        files: Vec<(String, Vec<u8>)>,
+        signer: &G,
+    ) -> Result<OpId, Error> {
+        for (name, content) in &files {
+            cob::attachment::validate(name, content)?;
+        }
+        self.transaction("Attach", signer, |tx| tx.attach_to(to, files))
+    }
+
     /// Unassign one or more actors from an issue.
     pub fn unassign<G: Signer>(
         &mut self,
@@ -338,12 +479,13 @@ impl<'a, 'g> IssueMut<'a, 'g> {
         G: Signer,
         F: FnOnce(&mut Transaction<Issue>) -> T,
     {
-        let mut tx = Transaction::new(*signer.public_key(), self.clock);
+        let mut tx = Transaction::new(*signer.public_key(), self.clock, self.tips.clone());
         let output = operations(&mut tx);
-        let (ops, clock) = tx.commit(message, self.id, &mut self.store.raw, signer)?;
+        let (ops, clock, tips) = tx.commit(message, self.id, &mut self.store.raw, signer)?;
 
-        self.issue.apply(ops)?;
+        self.store.raw.apply(&mut self.issue, ops)?;
         self.clock = clock;
+        self.tips = tips;
 
         Ok(output)
     }
@@ -387,14 +529,15 @@ impl<'a> Issues<'a> {
 
     /// Get an issue mutably.
     pub fn get_mut<'g>(&'g mut self, id: &ObjectId) -> Result<IssueMut<'a, 'g>, store::Error> {
-        let (issue, clock) = self
+        let (issue, clock, tips) = self
             .raw
-            .get(id)?
+            .get_with_tips(id)?
             .ok_or_else(move || store::Error::NotFound(TYPENAME.clone(), *id))?;
 
         Ok(IssueMut {
             id: *id,
             clock,
+            tips,
             issue,
             store: self,
         })
@@ -420,6 +563,7 @@ impl<'a> Issues<'a> {
         Ok(IssueMut {
             id,
             clock,
+            tips: BTreeSet::from([id.into()]),
             issue,
             store: self,
         })
@@ -454,6 +598,8 @@ pub enum Action {
     },
 }
 
+impl cob::op::Migrate for Action {}
+
 impl From<thread::Action> for Action {
     fn from(action: thread::Action) -> Self {
         Self::Thread { action }
@@ -463,11 +609,31 @@ impl From<thread::Action> for Action {
 #[cfg(test)]
 mod test {
     use pretty_assertions::assert_eq;
+    use qcheck::TestResult;
+    use qcheck_macros::quickcheck;
 
     use super::*;
     use crate::cob::Reaction;
+    use crate::crypto::test::signer::MockSigner;
     use crate::test;
     use crate::test::arbitrary;
+    use crate::test::arbitrary::cob::{converges, Changes};
+
+    #[test]
+    fn prop_invariants() {
+        qcheck::QuickCheck::new()
+            .min_tests_passed(100)
+            .gen(qcheck::Gen::new(7))
+            .quickcheck(converges::<Issue, 3> as fn(Changes<Action, 3>) -> TestResult);
+    }
+
+    #[quickcheck]
+    fn prop_action_encoding(action: Action) {
+        let json = serde_json::to_vec(&action).unwrap();
+        let decoded: Action = serde_json::from_slice(&json).unwrap();
+
+        assert_eq!(decoded, action);
+    }
 
     #[test]
     fn test_ordering() {
@@ -523,6 +689,79 @@ mod test {
         assert!(assignees.contains(&assignee));
     }
 
+    #[test]
+    fn test_issue_concurrent_update() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let mut issues = Issues::open(*signer.public_key(), &project).unwrap();
+        let mut other = Issues::open(*signer.public_key(), &project).unwrap();
+
+        let id = issues
+            .create("My first issue", "Blah blah blah.", &[], &signer)
+            .unwrap()
+            .id;
+        let mut issue = issues.get_mut(&id).unwrap();
+        let mut stale = other.get_mut(&id).unwrap();
+
+        issue
+            .transaction("Edit", &signer, |tx| tx.edit("Edited"))
+            .unwrap();
+
+        // The issue was modified since `stale` was read.
+        let err = stale
+            .transaction("Edit", &signer, |tx| tx.edit("Edited concurrently"))
+            .unwrap_err();
+        assert!(matches!(err, Error::Store(store::Error::Conflict(i)) if i == id));
+
+        // Reading the issue again allows the change to go through.
+        let mut issue = other.get_mut(&id).unwrap();
+        issue
+            .transaction("Edit", &signer, |tx| tx.edit("Edited again"))
+            .unwrap();
+        assert_eq!(issue.title(), "Edited again");
+    }
+
+    #[test]
+    fn test_issue_filter() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let mut issues = Issues::open(*signer.public_key(), &project).unwrap();
+
+        let open = issues
+            .create("Open issue", "Blah blah blah.", &[], &signer)
+            .unwrap()
+            .id;
+        let mut closed = issues
+            .create("Closed issue", "Blah blah blah.", &[], &signer)
+            .unwrap();
+        closed
+            .lifecycle(
+                State::Closed {
+                    reason: CloseReason::Solved,
+                },
+                &signer,
+            )
+            .unwrap();
+
+        let ids = |filter: store::Filter<Issue>| {
+            issues
+                .filter(filter)
+                .unwrap()
+                .map(|r| r.unwrap().0)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(store::Filter::default().state(|s| *s == State::Open)),
+            vec![open]
+        );
+        assert_eq!(
+            ids(store::Filter::default().author(*signer.public_key())).len(),
+            2
+        );
+        assert!(ids(store::Filter::default().author(arbitrary::gen(1))).is_empty());
+        assert!(ids(store::Filter::default().since(u64::MAX.into())).is_empty());
+    }
+
     #[test]
     fn test_issue_create_and_get() {
         let tmp = tempfile::tempdir().unwrap();
@@ -576,6 +815,174 @@ mod test {
         assert_eq!(*issue.state(), State::Open);
     }
 
+    #[test]
+    fn test_issue_references() {
+        use crate::cob::patch::{self, MergeTarget, Patches};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let mut issues = Issues::open(*signer.public_key(), &project).unwrap();
+        let mut patches = Patches::open(*signer.public_key(), &project).unwrap();
+        let other = issues
+            .create("My first issue", "Blah blah blah.", &[], &signer)
+            .unwrap()
+            .id;
+        let mut issue = issues
+            .create("My second issue", "Blah blah blah.", &[], &signer)
+            .unwrap();
+        let mut patch = patches
+            .create(
+                "My first patch",
+                "Blah blah blah.",
+                MergeTarget::Delegates,
+                arbitrary::oid(),
+                arbitrary::oid(),
+                &[],
+                &signer,
+            )
+            .unwrap();
+        let (revision, _) = patch.latest().unwrap();
+        let revision = *revision;
+
+        patch
+            .reference(revision, TYPENAME.clone(), issue.id, &signer)
+            .unwrap();
+        issue
+            .reference(patch::TYPENAME.clone(), patch.id, &signer)
+            .unwrap();
+
+        let (issue_id, patch_id) = (issue.id, patch.id);
+        let referencing = patches
+            .referencing(&TYPENAME, &issue_id)
+            .unwrap()
+            .map(|r| r.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(referencing, vec![patch_id]);
+        assert_eq!(patches.referencing(&TYPENAME, &other).unwrap().count(), 0);
+
+        let issue = issues.get(&issue_id).unwrap().unwrap();
+        assert_eq!(
+            issue.references(),
+            vec![Reference::new(patch::TYPENAME.clone(), patch_id)]
+        );
+    }
+
+    #[test]
+    fn test_issue_attachments() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let bob = MockSigner::default();
+        let mut issues = Issues::open(*signer.public_key(), &project).unwrap();
+        let mut issue = issues
+            .create("My first issue", "Blah blah blah.", &[], &signer)
+            .unwrap();
+        let (root, _) = issue.root().unwrap();
+        let root = *root;
+
+        issue
+            .attach(
+                root,
+                vec![(String::from("crash.log"), b"segfault".to_vec())],
+                &signer,
+            )
+            .unwrap();
+        // Only the comment author can attach files to it.
+        issue
+            .attach(
+                root,
+                vec![(String::from("other.log"), b"nope".to_vec())],
+                &bob,
+            )
+            .unwrap();
+        assert!(matches!(
+            issue.attach(
+                root,
+                vec![(
+                    String::from("huge.log"),
+                    vec![0; cob::attachment::MAX_SIZE + 1]
+                )],
+                &signer,
+            ),
+            Err(Error::Attachment(_))
+        ));
+        assert!(matches!(
+            issue.attach(root, vec![(String::from("../log"), vec![])], &signer),
+            Err(Error::Attachment(_))
+        ));
+
+        let id = issue.id;
+        let issue = issues.get(&id).unwrap().unwrap();
+        let attachments = issue.attachments(&root).collect::<Vec<_>>();
+        assert_eq!(attachments.len(), 1);
+        assert_eq!(attachments[0].name, "crash.log");
+        assert_eq!(issue.attachment(&attachments[0].oid), Some(attachments[0]));
+        assert_eq!(
+            issues.attachment(&attachments[0].oid).unwrap(),
+            b"segfault".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_issue_close_and_reopen() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let mut issues = Issues::open(*signer.public_key(), &project).unwrap();
+        let original = issues
+            .create("My first issue", "Blah blah blah.", &[], &signer)
+            .unwrap()
+            .id;
+        let mut issue = issues
+            .create("My second issue", "Blah blah blah.", &[], &signer)
+            .unwrap();
+        assert_eq!(issue.closed_by(), None);
+        assert_eq!(issue.reopened_by(), None);
+
+        let reason = CloseReason::Duplicate { of: original };
+        issue.lifecycle(State::Closed { reason }, &signer).unwrap();
+
+        let id = issue.id;
+        let mut issue = issues.get_mut(&id).unwrap();
+        assert_eq!(*issue.state(), State::Closed { reason });
+        assert_eq!(issue.closed_by(), Some(signer.public_key()));
+        assert_eq!(issue.reopened_by(), None);
+
+        issue.lifecycle(State::Open, &signer).unwrap();
+
+        let issue = issues.get(&id).unwrap().unwrap();
+        assert_eq!(*issue.state(), State::Open);
+        assert_eq!(issue.closed_by(), None);
+        assert_eq!(issue.reopened_by(), Some(signer.public_key()));
+    }
+
+    #[test]
+    fn test_issue_unauthorized_lifecycle() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let mut issues = Issues::open(*signer.public_key(), &project).unwrap();
+        let other = MockSigner::default();
+        let mut issue = issues
+            .create("My first issue", "Blah blah blah.", &[], &signer)
+            .unwrap();
+
+        // Neither a delegate nor the author of the issue.
+        issue
+            .lifecycle(
+                State::Closed {
+                    reason: CloseReason::Other,
+                },
+                &other,
+            )
+            .unwrap();
+
+        assert_eq!(*issue.state(), State::Open);
+
+        // The in-memory issue matches the stored one.
+        let id = issue.id;
+        let issue = (*issue).clone();
+        let stored = issues.get(&id).unwrap().unwrap();
+        assert_eq!(issue, stored);
+    }
+
     #[test]
     fn test_issue_create_and_unassign() {
         let tmp = tempfile::tempdir().unwrap();
@@ -598,6 +1005,31 @@ mod test {
         assert!(assignees.contains(&assignee_two));
     }
 
+    #[test]
+    fn test_issue_edit_and_redact_comment() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let mut issues = Issues::open(*signer.public_key(), &project).unwrap();
+        let mut issue = issues
+            .create("My first issue", "Blah blah blah.", &[], &signer)
+            .unwrap();
+        let root = OpId::root(*signer.public_key());
+
+        let c1 = issue.comment("Hi hi hi.", root, &signer).unwrap();
+        let c2 = issue.comment("Ha ha ha.", root, &signer).unwrap();
+        issue.edit_comment(c1, "Ho ho ho.", &signer).unwrap();
+        issue.redact_comment(c2, &signer).unwrap();
+
+        let id = issue.id;
+        let issue = issues.get(&id).unwrap().unwrap();
+        let comment = issue.comment(&c1).unwrap();
+
+        assert_eq!(comment.body(), "Ho ho ho.");
+        assert!(comment.is_edited());
+        assert!(issue.comment(&c2).is_none());
+        assert_eq!(issue.comments().count(), 2);
+    }
+
     #[test]
     fn test_issue_react() {
         let tmp = tempfile::tempdir().unwrap();
@@ -620,6 +1052,43 @@ mod test {
         // TODO: Test multiple reactions from same author and different authors
     }
 
+    #[test]
+    fn test_issue_cached() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let mut issues = Issues::open(*signer.public_key(), &project).unwrap();
+        let mut issue = issues
+            .create("My first issue", "Blah blah blah.", &[], &signer)
+            .unwrap();
+        let root = OpId::root(*signer.public_key());
+
+        issue.comment("Hi hi hi.", root, &signer).unwrap();
+        issue
+            .react(root, Reaction::new('🥳').unwrap(), &signer)
+            .unwrap();
+
+        let id = issue.id;
+        let entry = project
+            .backend
+            .path()
+            .join(crate::storage::git::cob::COB_CACHE_DIR)
+            .join(TYPENAME.as_str())
+            .join(id.to_string());
+        let materialized = issues.get(&id).unwrap().unwrap();
+        assert!(entry.exists());
+
+        // The issue is now read back from the cache.
+        let cached = issues.get(&id).unwrap().unwrap();
+        assert_eq!(cached, materialized);
+
+        // Until it is updated.
+        let mut issue = issues.get_mut(&id).unwrap();
+        issue.comment("Ha ha ha.", root, &signer).unwrap();
+
+        let issue = issues.get(&id).unwrap().unwrap();
+        assert_eq!(issue.comments().count(), 3);
+    }
+
     #[test]
     fn test_issue_reply() {
         let tmp = tempfile::tempdir().unwrap();
@@ -710,6 +1179,155 @@ mod test {
         assert_eq!(c2.author(), author);
     }
 
+    #[test]
+    fn test_issue_squash() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let author = *signer.public_key();
+        let mut issues = Issues::open(*signer.public_key(), &project).unwrap();
+        let mut issue = issues
+            .create("My first issue", "Blah blah blah.", &[], &signer)
+            .unwrap();
+        let c0 = OpId::root(author);
+
+        issue.comment("Ho ho ho.", c0, &signer).unwrap();
+        issue.comment("Ha ha ha.", c0, &signer).unwrap();
+
+        let id = issue.id;
+        let expected = issues.get(&id).unwrap().unwrap();
+        let (squashed, _) = issues.squash(&id, "Squash issue", &signer).unwrap();
+        assert_eq!(squashed, expected);
+        assert_eq!(issues.get(&id).unwrap().unwrap(), expected);
+
+        // The object can still be updated after it was squashed.
+        let mut issue = issues.get_mut(&id).unwrap();
+        issue.comment("He he he.", c0, &signer).unwrap();
+
+        let issue = issues.get(&id).unwrap().unwrap();
+        assert_eq!(issue.comments().count(), 4);
+        assert_eq!(issue.comments().last().unwrap().1.body(), "He he he.");
+    }
+
+    #[test]
+    fn test_issue_squash_concurrent() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let author = *signer.public_key();
+        let other = MockSigner::default();
+        let mut issues = Issues::open(author, &project).unwrap();
+        let mut others = Issues::open(author, &project).unwrap();
+        let mut issue = issues
+            .create("My first issue", "Blah blah blah.", &[], &signer)
+            .unwrap();
+        let id = issue.id;
+        let c0 = OpId::root(author);
+        let mut theirs = others.get_mut(&id).unwrap();
+
+        issue.comment("Ho ho ho.", c0, &signer).unwrap();
+        theirs.comment("Ha ha ha.", c0, &other).unwrap();
+
+        let tip = |key: &PublicKey| -> git::Oid {
+            project
+                .raw()
+                .find_reference(&git::refs::storage::cob(key, &TYPENAME, &id))
+                .unwrap()
+                .target()
+                .unwrap()
+                .into()
+        };
+        let ours = tip(&author);
+
+        // The changes of the signer's own reference must be replaced by the snapshot.
+        assert!(matches!(
+            issues.squash_with(&id, Some([git::Oid::from(id)].into()), "Squash", &signer),
+            Err(store::Error::Squash(cob::error::Squash::Uncovered(oid))) if oid == ours
+        ));
+
+        // Only squash our own changes, the other remote's comment is concurrent.
+        let expected = issues.get(&id).unwrap().unwrap();
+        let (squashed, _) = issues
+            .squash_with(&id, Some([ours].into()), "Squash issue", &signer)
+            .unwrap();
+        assert_eq!(squashed, expected);
+        assert_eq!(issues.get(&id).unwrap().unwrap(), expected);
+
+        // The snapshot descends from the changes it replaces, and the history starts from it.
+        let cob = cob::get(&project, &TYPENAME, &id).unwrap().unwrap();
+        let root = cob.history().root().unwrap();
+        assert!(root.is_snapshot());
+        assert_eq!(git::Oid::from(*root.id()), tip(&author));
+        assert_eq!(cob.history().len(), 2);
+
+        // The other remote's history isn't modified, and keeps building on it.
+        let mut theirs = others.get_mut(&id).unwrap();
+        theirs.comment("He he he.", c0, &other).unwrap();
+
+        let issue = issues.get(&id).unwrap().unwrap();
+        assert_eq!(issue.comments().count(), 4);
+        assert_eq!(issue.comments().last().unwrap().1.body(), "He he he.");
+    }
+
+    #[test]
+    fn test_issue_forged_snapshot() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let author = *signer.public_key();
+        let other = MockSigner::default();
+        let mut issues = Issues::open(author, &project).unwrap();
+        let issue = issues
+            .create("My first issue", "Blah blah blah.", &[], &signer)
+            .unwrap();
+        let id = issue.id;
+
+        // Only a delegate may squash operations by other authors.
+        assert!(matches!(
+            issues.squash(&id, "Squash issue", &other),
+            Err(store::Error::SnapshotUnauthorized(_))
+        ));
+
+        // Forge a snapshot closing the issue in the name of the delegate.
+        let op = Op::new(
+            Action::Lifecycle {
+                state: State::Closed {
+                    reason: CloseReason::Other,
+                },
+            },
+            author,
+            clock::Physical::now(),
+            clock::Lamport::initial().tick(),
+        );
+        let identity = crate::identity::Identity::load(&author, &project).unwrap();
+        cob::squash(
+            &project,
+            &other,
+            &identity,
+            other.public_key(),
+            cob::Squash {
+                object_id: id,
+                history_type: store::HISTORY_TYPE.to_owned(),
+                version: <Action as cob::op::Migrate>::VERSION,
+                encoding: cob::Encoding::Json,
+                typename: TYPENAME.clone(),
+                message: "Forged".to_owned(),
+                snapshot: nonempty::NonEmpty::new(store::encoding::encode(&op).unwrap()),
+                tips: [git::Oid::from(id)].into(),
+            },
+        )
+        .unwrap();
+
+        // Leave the forged snapshot as the only history of the issue.
+        project
+            .raw()
+            .find_reference(&git::refs::storage::cob(&author, &TYPENAME, &id))
+            .unwrap()
+            .delete()
+            .unwrap();
+
+        let issue = issues.get(&id).unwrap().unwrap();
+        assert_eq!(*issue.state(), State::Open);
+        assert_eq!(issue.title(), "");
+    }
+
     #[test]
     fn test_issue_state_serde() {
         assert_eq!(
@@ -0,0 +1,263 @@
+//! Issue templates.
+//!
+//! Templates are markdown files stored in the project repository under [`PATH`], with an
+//! optional YAML front-matter section for the issue title, labels and assignees, eg.
+//!
+//! ```text
+//! ---
+//! title: "Bug: "
+//! labels: [bug]
+//! assignees: []
+//! ---
+//!
+//! Describe the bug...
+//! ```
+use std::path::Path;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::cob::common::Tag;
+use crate::cob::ActorId;
+use crate::git;
+
+/// Path of the issue templates directory, relative to the repository root.
+pub static PATH: Lazy<&Path> = Lazy::new(|| Path::new(".radicle/templates/issue"));
+/// File extension of issue templates.
+pub const EXTENSION: &str = "md";
+
+/// Error loading issue templates.
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("git: {0}")]
+    Git(#[from] git::raw::Error),
+    #[error("template '{name}' is not valid UTF-8")]
+    Utf8 { name: String },
+    #[error("template '{name}' has invalid front-matter: {err}")]
+    FrontMatter {
+        name: String,
+        #[source]
+        err: serde_yaml::Error,
+    },
+}
+
+/// Template front-matter.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FrontMatter {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    labels: Vec<Tag>,
+    #[serde(default)]
+    assignees: Vec<ActorId>,
+}
+
+/// An issue template.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Template {
+    /// Template name, ie. the file name without extension.
+    pub name: String,
+    /// Initial issue title.
+    pub title: String,
+    /// Labels added to the issue.
+    pub labels: Vec<Tag>,
+    /// Keys assigned to the issue.
+    pub assignees: Vec<ActorId>,
+    /// Initial issue description.
+    pub description: String,
+}
+
+impl Template {
+    /// Parse a template from its text. The front-matter, if any, must be delimited
+    /// by `---` lines at the start of the text.
+    pub fn parse(name: impl ToString, text: &str) -> Result<Self, Error> {
+        let name = name.to_string();
+        let (front, body) = split(text);
+        let front = match front {
+            Some(yaml) if !yaml.trim().is_empty() => {
+                serde_yaml::from_str(&yaml).map_err(|err| Error::FrontMatter {
+                    name: name.clone(),
+                    err,
+                })?
+            }
+            _ => FrontMatter::default(),
+        };
+
+        Ok(Self {
+            name,
+            title: front.title,
+            labels: front.labels,
+            assignees: front.assignees,
+            description: body.trim().to_owned(),
+        })
+    }
+
+    /// Render the template back to text, in the format accepted by [`Template::parse`].
+    pub fn to_text(&self) -> String {
+        let front = FrontMatter {
+            title: self.title.clone(),
+            labels: self.labels.clone(),
+            assignees: self.assignees.clone(),
+        };
+        let yaml = serde_yaml::to_string(&front).expect("front-matter serializes");
+        let yaml = yaml.strip_prefix("---\n").unwrap_or(&yaml);
+
+        format!("---\n{}---\n\n{}\n", yaml, self.description)
+    }
+}
+
+/// Load the issue templates found in the tree of the given commit, sorted by name.
+///
+/// Returns an empty list if the commit has no templates directory. Templates that can't
+/// be parsed are skipped.
+pub fn templates(repo: &git::raw::Repository, commit: git::Oid) -> Result<Vec<Template>, Error> {
+    let tree = repo.find_commit(commit.into())?.tree()?;
+    let entry = match tree.get_path(*PATH) {
+        Ok(entry) => entry,
+        Err(err) if err.code() == git::raw::ErrorCode::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err.into()),
+    };
+    let Some(dir) = entry.to_object(repo)?.into_tree().ok() else {
+        return Ok(vec![]);
+    };
+    let mut templates = Vec::new();
+
+    for entry in dir.iter() {
+        if entry.kind() != Some(git::raw::ObjectType::Blob) {
+            continue;
+        }
+        let Some(name) = entry
+            .name()
+            .and_then(|n| n.strip_suffix(EXTENSION))
+            .and_then(|n| n.strip_suffix('.'))
+            .filter(|n| !n.is_empty())
+        else {
+            continue;
+        };
+        let blob = repo.find_blob(entry.id())?;
+        let result = std::str::from_utf8(blob.content())
+            .map_err(|_| Error::Utf8 {
+                name: name.to_owned(),
+            })
+            .and_then(|text| Template::parse(name, text));
+
+        match result {
+            Ok(template) => templates.push(template),
+            Err(err) => log::warn!("Skipping issue template: {err}"),
+        }
+    }
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(templates)
+}
+
+/// Split a text into its front-matter and body.
+fn split(text: &str) -> (Option<String>, &str) {
+    let mut lines = text.split_inclusive('\n');
+    let mut front = String::new();
+    let mut offset = 0;
+
+    match lines.next() {
+        Some(line) if line.trim() == "---" => offset += line.len(),
+        _ => return (None, text),
+    }
+    for line in lines {
+        offset += line.len();
+
+        if line.trim() == "---" {
+            return (Some(front), &text[offset..]);
+        }
+        front.push_str(line);
+    }
+    // The front-matter was never closed, treat the whole text as the body.
+    (None, text)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::test::arbitrary;
+    use crate::test::fixtures;
+
+    #[test]
+    fn test_template_parse() {
+        let assignee: ActorId = arbitrary::gen(1);
+        let text = format!(
+            "---\ntitle: \"Bug: \"\nlabels: [bug, triage]\nassignees: [{assignee}]\n---\n\nSteps to reproduce:\n"
+        );
+        let template = Template::parse("bug", &text).unwrap();
+
+        assert_eq!(template.name, "bug");
+        assert_eq!(template.title, "Bug: ");
+        assert_eq!(
+            template.labels,
+            vec![Tag::new("bug").unwrap(), Tag::new("triage").unwrap()]
+        );
+        assert_eq!(template.assignees, vec![assignee]);
+        assert_eq!(template.description, "Steps to reproduce:");
+        assert_eq!(
+            Template::parse("bug", &template.to_text()).unwrap(),
+            template
+        );
+
+        let template = Template::parse("plain", "Just a body.\n---\n").unwrap();
+        assert_eq!(template.title, "");
+        assert!(template.labels.is_empty());
+        assert_eq!(template.description, "Just a body.\n---");
+
+        assert!(matches!(
+            Template::parse("invalid", "---\nlabels: 1\n---\n"),
+            Err(Error::FrontMatter { .. })
+        ));
+    }
+
+    #[test]
+    fn test_templates() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (repo, head) = fixtures::repository(tmp.path());
+
+        assert!(templates(&repo, head.into()).unwrap().is_empty());
+
+        let dir = tmp.path().join(*PATH);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("feature.md"), "---\ntitle: Feature\n---\nWhat?").unwrap();
+        fs::write(dir.join("bug.md"), "---\nlabels: [bug]\n---\nHow?").unwrap();
+        fs::write(dir.join("broken.md"), "---\nlabels: 1\n---\n").unwrap();
+        fs::write(dir.join("README"), "Not a template").unwrap();
+
+        let mut index = repo.index().unwrap();
+        for file in ["feature.md", "bug.md", "broken.md", "README"] {
+            index.add_path(&PATH.join(file)).unwrap();
+        }
+        index.write().unwrap();
+
+        let sig = git::raw::Signature::now("anonymous", "anonymous@radicle.xyz").unwrap();
+        let parent = repo.find_commit(head).unwrap();
+        let commit = git::commit(
+            &repo,
+            &parent,
+            git::refname!("refs/heads/master").as_refstr(),
+            "Add issue templates",
+            &sig,
+        )
+        .unwrap();
+
+        let templates = templates(&repo, commit.id().into()).unwrap();
+        let names = templates
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["bug", "feature"]);
+        assert_eq!(templates[0].labels, vec![Tag::new("bug").unwrap()]);
+        assert_eq!(templates[1].title, "Feature");
+        assert_eq!(templates[1].description, "What?");
+    }
+}
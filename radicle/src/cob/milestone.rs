@@ -0,0 +1,392 @@
+use std::collections::BTreeSet;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use radicle_crdt::clock;
+use radicle_crdt::{LWWReg, LWWSet, Max};
+
+use crate::cob;
+use crate::cob::common::{Author, Timestamp};
+use crate::cob::store::FromHistory as _;
+use crate::cob::store::Transaction;
+use crate::cob::{store, ActorId, ObjectId, OpId, TypeName};
+use crate::crypto::{PublicKey, Signer};
+use crate::git;
+use crate::storage::git as storage;
+
+/// Milestone operation.
+pub type Op = cob::Op<Action>;
+
+/// Type name of a milestone.
+pub static TYPENAME: Lazy<TypeName> =
+    Lazy::new(|| FromStr::from_str("xyz.radicle.milestone").expect("type name is valid"));
+
+/// Identifier for a milestone.
+pub type MilestoneId = ObjectId;
+
+/// Error updating or creating milestones.
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("store: {0}")]
+    Store(#[from] store::Error),
+}
+
+/// Milestone state. Accumulates [`Action`].
+///
+/// A milestone groups the issues and patches planned for eg. a release, identified
+/// by their object ids.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Milestone {
+    author: Option<ActorId>,
+    title: LWWReg<Max<String>, clock::Lamport>,
+    description: LWWReg<Max<String>, clock::Lamport>,
+    due: LWWReg<Max<Option<Timestamp>>, clock::Lamport>,
+    items: LWWSet<ObjectId>,
+}
+
+impl Default for Milestone {
+    fn default() -> Self {
+        Self {
+            author: None,
+            title: Max::from(String::default()).into(),
+            description: Max::from(String::default()).into(),
+            due: Max::from(None).into(),
+            items: LWWSet::default(),
+        }
+    }
+}
+
+impl store::FromHistory for Milestone {
+    type Action = Action;
+    type Error = Error;
+    type State = ();
+
+    fn type_name() -> &'static TypeName {
+        &*TYPENAME
+    }
+
+    fn state(&self) {}
+
+    fn apply(&mut self, ops: impl IntoIterator<Item = Op>) -> Result<(), Error> {
+        for op in ops {
+            // The first operation is part of the change that creates the milestone.
+            self.author.get_or_insert(op.author);
+
+            match op.action {
+                Action::Edit { title, description } => {
+                    self.title.set(title, op.clock);
+                    self.description.set(description, op.clock);
+                }
+                Action::Due { due } => {
+                    self.due.set(due, op.clock);
+                }
+                Action::Items { add, remove } => {
+                    for item in add {
+                        self.items.insert(item, op.clock);
+                    }
+                    for item in remove {
+                        self.items.remove(item, op.clock);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Milestone {
+    /// Milestone author.
+    pub fn author(&self) -> Option<Author> {
+        self.author.map(Author::new)
+    }
+
+    /// Milestone title.
+    pub fn title(&self) -> &str {
+        self.title.get().as_str()
+    }
+
+    /// Milestone description.
+    pub fn description(&self) -> &str {
+        self.description.get().as_str()
+    }
+
+    /// Date by which the milestone is due, if any.
+    pub fn due(&self) -> Option<Timestamp> {
+        *self.due.get().get()
+    }
+
+    /// Issues and patches associated with the milestone.
+    pub fn items(&self) -> impl Iterator<Item = &ObjectId> {
+        self.items.iter()
+    }
+
+    /// Whether the given issue or patch is associated with the milestone.
+    pub fn contains(&self, id: &ObjectId) -> bool {
+        self.items.contains(id)
+    }
+}
+
+impl store::Transaction<Milestone> {
+    /// Edit the milestone title and description.
+    pub fn edit(&mut self, title: impl ToString, description: impl ToString) -> OpId {
+        self.push(Action::Edit {
+            title: title.to_string(),
+            description: description.to_string(),
+        })
+    }
+
+    /// Set or unset the milestone due date.
+    pub fn due(&mut self, due: Option<Timestamp>) -> OpId {
+        self.push(Action::Due { due })
+    }
+
+    /// Associate or dissociate issues and patches.
+    pub fn items(
+        &mut self,
+        add: impl IntoIterator<Item = ObjectId>,
+        remove: impl IntoIterator<Item = ObjectId>,
+    ) -> OpId {
+        let add = add.into_iter().collect::<Vec<_>>();
+        let remove = remove.into_iter().collect::<Vec<_>>();
+
+        self.push(Action::Items { add, remove })
+    }
+}
+
+pub struct MilestoneMut<'a, 'g> {
+    id: ObjectId,
+    clock: clock::Lamport,
+    tips: BTreeSet<git::Oid>,
+    milestone: Milestone,
+    store: &'g mut Milestones<'a>,
+}
+
+impl<'a, 'g> MilestoneMut<'a, 'g> {
+    /// Get the milestone id.
+    pub fn id(&self) -> &MilestoneId {
+        &self.id
+    }
+
+    /// Get the internal logical clock.
+    pub fn clock(&self) -> &clock::Lamport {
+        &self.clock
+    }
+
+    /// Edit the milestone title and description.
+    pub fn edit<G: Signer>(
+        &mut self,
+        title: impl ToString,
+        description: impl ToString,
+        signer: &G,
+    ) -> Result<OpId, Error> {
+        self.transaction("Edit", signer, |tx| tx.edit(title, description))
+    }
+
+    /// Set or unset the milestone due date.
+    pub fn set_due<G: Signer>(
+        &mut self,
+        due: Option<Timestamp>,
+        signer: &G,
+    ) -> Result<OpId, Error> {
+        self.transaction("Due", signer, |tx| tx.due(due))
+    }
+
+    /// Associate issues and patches with the milestone.
+    pub fn add<G: Signer>(
+        &mut self,
+        items: impl IntoIterator<Item = ObjectId>,
+        signer: &G,
+    ) -> Result<OpId, Error> {
+        self.transaction("Add items", signer, |tx| tx.items(items, []))
+    }
+
+    /// Dissociate issues and patches from the milestone.
+    pub fn remove<G: Signer>(
+        &mut self,
+        items: impl IntoIterator<Item = ObjectId>,
+        signer: &G,
+    ) -> Result<OpId, Error> {
+        self.transaction("Remove items", signer, |tx| tx.items([], items))
+    }
+
+    pub fn transaction<G, F, T>(
+        &mut self,
+        message: &str,
+        signer: &G,
+        operations: F,
+    ) -> Result<T, Error>
+    where
+        G: Signer,
+        F: FnOnce(&mut Transaction<Milestone>) -> T,
+    {
+        let mut tx = Transaction::new(*signer.public_key(), self.clock, self.tips.clone());
+        let output = operations(&mut tx);
+        let (ops, clock, tips) = tx.commit(message, self.id, &mut self.store.raw, signer)?;
+
+        self.store.raw.apply(&mut self.milestone, ops)?;
+        self.clock = clock;
+        self.tips = tips;
+
+        Ok(output)
+    }
+}
+
+impl<'a, 'g> Deref for MilestoneMut<'a, 'g> {
+    type Target = Milestone;
+
+    fn deref(&self) -> &Self::Target {
+        &self.milestone
+    }
+}
+
+pub struct Milestones<'a> {
+    raw: store::Store<'a, Milestone>,
+}
+
+impl<'a> Deref for Milestones<'a> {
+    type Target = store::Store<'a, Milestone>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.raw
+    }
+}
+
+impl<'a> Milestones<'a> {
+    /// Open a milestones store.
+    pub fn open(
+        whoami: PublicKey,
+        repository: &'a storage::Repository,
+    ) -> Result<Self, store::Error> {
+        let raw = store::Store::open(whoami, repository)?;
+
+        Ok(Self { raw })
+    }
+
+    /// Get a milestone.
+    pub fn get(&self, id: &ObjectId) -> Result<Option<Milestone>, store::Error> {
+        self.raw.get(id).map(|r| r.map(|(m, _clock)| m))
+    }
+
+    /// Get a milestone mutably.
+    pub fn get_mut<'g>(&'g mut self, id: &ObjectId) -> Result<MilestoneMut<'a, 'g>, store::Error> {
+        let (milestone, clock, tips) = self
+            .raw
+            .get_with_tips(id)?
+            .ok_or_else(move || store::Error::NotFound(TYPENAME.clone(), *id))?;
+
+        Ok(MilestoneMut {
+            id: *id,
+            clock,
+            tips,
+            milestone,
+            store: self,
+        })
+    }
+
+    /// Create a new milestone.
+    pub fn create<'g, G: Signer>(
+        &'g mut self,
+        title: impl ToString,
+        description: impl ToString,
+        due: Option<Timestamp>,
+        signer: &G,
+    ) -> Result<MilestoneMut<'a, 'g>, Error> {
+        let (id, milestone, clock) =
+            Transaction::initial("Create milestone", &mut self.raw, signer, |tx| {
+                tx.edit(title, description);
+                tx.due(due);
+            })?;
+
+        Ok(MilestoneMut {
+            id,
+            clock,
+            tips: BTreeSet::from([id.into()]),
+            milestone,
+            store: self,
+        })
+    }
+
+    /// Remove a milestone.
+    pub fn remove(&self, id: &ObjectId) -> Result<(), store::Error> {
+        self.raw.remove(id)
+    }
+}
+
+/// Milestone operation.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Action {
+    /// Edit the title and description.
+    Edit { title: String, description: String },
+    /// Set or unset the due date.
+    Due { due: Option<Timestamp> },
+    /// Associate or dissociate issues and patches.
+    Items {
+        add: Vec<ObjectId>,
+        remove: Vec<ObjectId>,
+    },
+}
+
+impl cob::op::Migrate for Action {}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::test;
+    use crate::test::arbitrary;
+
+    #[test]
+    fn test_milestone_create_and_get() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let mut milestones = Milestones::open(*signer.public_key(), &project).unwrap();
+        let due = Timestamp::from(1700000000);
+        let created = milestones
+            .create("v1.0", "First stable release.", Some(due), &signer)
+            .unwrap();
+        let id = *created.id();
+        let milestone = milestones.get(&id).unwrap().unwrap();
+
+        assert_eq!(milestone.title(), "v1.0");
+        assert_eq!(milestone.description(), "First stable release.");
+        assert_eq!(milestone.due(), Some(due));
+        assert_eq!(milestone.author(), Some(milestones.author()));
+        assert_eq!(milestone.items().count(), 0);
+    }
+
+    #[test]
+    fn test_milestone_update() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let mut milestones = Milestones::open(*signer.public_key(), &project).unwrap();
+        let issue = ObjectId::from(arbitrary::oid());
+        let patch = ObjectId::from(arbitrary::oid());
+        let mut milestone = milestones
+            .create("v1.0", "First stable release.", None, &signer)
+            .unwrap();
+
+        milestone.add([issue, patch], &signer).unwrap();
+        milestone.remove([patch], &signer).unwrap();
+        milestone
+            .edit("v1.0.0", "First stable release!", &signer)
+            .unwrap();
+        milestone
+            .set_due(Some(Timestamp::from(1700000000)), &signer)
+            .unwrap();
+
+        let id = *milestone.id();
+        let milestone = milestones.get(&id).unwrap().unwrap();
+        assert_eq!(milestone.title(), "v1.0.0");
+        assert_eq!(milestone.description(), "First stable release!");
+        assert_eq!(milestone.due(), Some(Timestamp::from(1700000000)));
+        assert_eq!(milestone.items().collect::<Vec<_>>(), vec![&issue]);
+        assert!(milestone.contains(&issue));
+        assert!(!milestone.contains(&patch));
+    }
+}
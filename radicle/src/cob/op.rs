@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
 
 use nonempty::NonEmpty;
@@ -32,6 +33,11 @@ impl OpId {
     pub fn clock(&self) -> Lamport {
         self.0
     }
+
+    /// Get operation id actor.
+    pub fn actor(&self) -> &ActorId {
+        &self.1
+    }
 }
 
 /// The author of an [`Op`].
@@ -44,13 +50,45 @@ pub enum OpEncodingError {
     Encoding(#[from] serde_json::Error),
     #[error("git: {0}")]
     Git(#[from] git2::Error),
+    #[error("unsupported encoding version {0}")]
+    UnsupportedVersion(u32),
+    #[error(transparent)]
+    Contents(#[from] radicle_cob::encoding::error::Encoding),
+}
+
+/// Decoding of actions encoded by older versions of their type.
+///
+/// Every change records the version of the encoding of its actions. When an action
+/// type changes shape, its [`Migrate::VERSION`] should be incremented, and
+/// [`Migrate::migrate`] should decode actions encoded with older versions.
+/// Actions encoded with a newer version than [`Migrate::VERSION`] can't be decoded,
+/// and are skipped when materializing objects.
+pub trait Migrate: for<'de> Deserialize<'de> {
+    /// The version of the encoding of actions written by this code.
+    const VERSION: u32 = 0;
+
+    /// Decode an action encoded with an older version.
+    fn migrate(version: u32, action: serde_json::Value) -> Result<Self, serde_json::Error> {
+        let _ = version;
+
+        serde_json::from_value(action)
+    }
+
+    /// Decode an action encoded with the given version.
+    fn decode(version: u32, action: serde_json::Value) -> Result<Self, OpEncodingError> {
+        match version.cmp(&Self::VERSION) {
+            Ordering::Equal => Ok(serde_json::from_value(action)?),
+            Ordering::Less => Ok(Self::migrate(version, action)?),
+            Ordering::Greater => Err(OpEncodingError::UnsupportedVersion(version)),
+        }
+    }
 }
 
 /// The `Op` is the operation that is applied onto a state to form a CRDT.
 ///
 /// Everything that can be done in the system is represented by an `Op`.
 /// Operations are applied to an accumulator to yield a final state.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Op<A> {
     /// The action carried out by this operation.
     pub action: A,
@@ -92,20 +130,17 @@ impl<A: Serialize> Op<A> {
 
 pub struct Ops<A>(pub NonEmpty<Op<A>>);
 
-impl<'a, A> TryFrom<&'a EntryWithClock> for Ops<A>
-where
-    for<'de> A: serde::Deserialize<'de>,
-{
+impl<'a, A: Migrate> TryFrom<&'a EntryWithClock> for Ops<A> {
     type Error = OpEncodingError;
 
     fn try_from(entry: &'a EntryWithClock) -> Result<Self, Self::Error> {
         let mut clock = entry.clock().into();
 
         entry
-            .contents()
-            .clone()
-            .try_map(|op| {
-                let action = serde_json::from_slice(&op)?;
+            .typed_contents()
+            .decode::<serde_json::Value>()?
+            .try_map(|action| {
+                let action = A::decode(entry.version(), action)?;
                 let op = Op {
                     action,
                     author: *entry.actor(),
@@ -120,6 +155,33 @@ where
     }
 }
 
+/// Operations recorded in a history snapshot, along with their original authors,
+/// clocks and timestamps.
+///
+/// Nb. Unlike [`Ops`], the authors of these operations are not backed by a signature,
+/// they are taken from the snapshot contents. See [`crate::cob::store`] for how
+/// snapshots are trusted.
+pub struct Snapshot<A>(pub NonEmpty<Op<A>>);
+
+impl<'a, A: Migrate> TryFrom<&'a EntryWithClock> for Snapshot<A> {
+    type Error = OpEncodingError;
+
+    fn try_from(entry: &'a EntryWithClock) -> Result<Self, Self::Error> {
+        entry
+            .typed_contents()
+            .decode::<Op<serde_json::Value>>()?
+            .try_map(|op| {
+                Ok(Op {
+                    action: A::decode(entry.version(), op.action)?,
+                    author: op.author,
+                    clock: op.clock,
+                    timestamp: op.timestamp,
+                })
+            })
+            .map(Self)
+    }
+}
+
 impl<A> Op<A> {
     /// Get the op id.
     /// This uniquely identifies each operation in the CRDT.
@@ -182,3 +244,59 @@ impl<G: Signer, A: Clone> Actor<G, A> {
         op
     }
 }
+
+#[cfg(test)]
+mod test {
+    use nonempty::nonempty;
+    use radicle_cob::history::Entry;
+
+    use super::*;
+    use crate::test::arbitrary;
+
+    /// An action whose `count` field was renamed from `n`, in version `1`.
+    #[derive(Debug, PartialEq, Eq, Deserialize)]
+    struct Count {
+        count: u64,
+    }
+
+    impl Migrate for Count {
+        const VERSION: u32 = 1;
+
+        fn migrate(version: u32, action: serde_json::Value) -> Result<Self, serde_json::Error> {
+            #[derive(Deserialize)]
+            struct V0 {
+                n: u64,
+            }
+            debug_assert_eq!(version, 0);
+            let V0 { n } = serde_json::from_value(action)?;
+
+            Ok(Self { count: n })
+        }
+    }
+
+    fn entry(version: u32, contents: &[u8]) -> EntryWithClock {
+        let entry = Entry::new(
+            arbitrary::oid(),
+            arbitrary::gen(1),
+            arbitrary::oid(),
+            std::iter::empty::<git2::Oid>(),
+            nonempty![contents.to_vec()],
+            0,
+        );
+        EntryWithClock::root(entry.with_version(version))
+    }
+
+    #[test]
+    fn test_migrate() {
+        let Ops(ops) = Ops::<Count>::try_from(&entry(0, br#"{"n":7}"#)).unwrap();
+        assert_eq!(ops.head.action, Count { count: 7 });
+
+        let Ops(ops) = Ops::<Count>::try_from(&entry(1, br#"{"count":7}"#)).unwrap();
+        assert_eq!(ops.head.action, Count { count: 7 });
+
+        assert!(matches!(
+            Ops::<Count>::try_from(&entry(2, br#"{"total":7}"#)),
+            Err(OpEncodingError::UnsupportedVersion(2))
+        ));
+    }
+}
@@ -1,7 +1,9 @@
 #![allow(clippy::too_many_arguments)]
+use std::collections::BTreeSet;
 use std::fmt;
 use std::ops::Deref;
 use std::ops::Range;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use once_cell::sync::Lazy;
@@ -9,18 +11,20 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use radicle_crdt::clock;
-use radicle_crdt::{GMap, LWWReg, LWWSet, Max, Redactable, Semilattice};
+use radicle_crdt::{GMap, LWWReg, LWWSet, Max, Redactable, Semilattice, VecMap};
 
 use crate::cob;
-use crate::cob::common::{Author, Tag, Timestamp};
+use crate::cob::common::{Author, Reference, Tag, Timestamp};
+use crate::cob::issue;
 use crate::cob::store::FromHistory as _;
 use crate::cob::store::Transaction;
 use crate::cob::thread;
 use crate::cob::thread::CommentId;
 use crate::cob::thread::Thread;
 use crate::cob::{store, ActorId, ObjectId, OpId, TypeName};
-use crate::crypto::{PublicKey, Signer};
+use crate::crypto::{PublicKey, Signer, Verified};
 use crate::git;
+use crate::identity::Doc;
 use crate::prelude::*;
 use crate::storage::git as storage;
 
@@ -67,6 +71,14 @@ pub enum Error {
     Apply(#[from] ApplyError),
     #[error("store: {0}")]
     Store(#[from] store::Error),
+    #[error("git: {0}")]
+    Git(#[from] git::raw::Error),
+    #[error("revision {0:?} not found")]
+    RevisionNotFound(RevisionId),
+    #[error("revision {0:?} does not apply cleanly onto the new base")]
+    Conflict(RevisionId),
+    #[error("issue: {0}")]
+    Issue(#[from] issue::Error),
 }
 
 /// Patch operation.
@@ -103,8 +115,15 @@ pub enum Action {
         revision: RevisionId,
         action: thread::Action,
     },
+    CodeComment {
+        revision: RevisionId,
+        location: CodeLocation,
+        body: String,
+    },
 }
 
+impl cob::op::Migrate for Action {}
+
 /// Where a patch is intended to be merged.
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -117,7 +136,7 @@ pub enum MergeTarget {
     Delegates,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Patch {
     /// Title of the patch.
     pub title: LWWReg<Max<String>>,
@@ -200,6 +219,11 @@ impl Patch {
             })
     }
 
+    /// Get a revision of the patch, if it wasn't redacted.
+    pub fn revision(&self, id: &RevisionId) -> Option<&Revision> {
+        self.revisions.get(id).and_then(|r| r.get())
+    }
+
     pub fn head(&self) -> &git::Oid {
         &self
             .latest()
@@ -231,11 +255,34 @@ impl Patch {
 impl store::FromHistory for Patch {
     type Action = Action;
     type Error = ApplyError;
+    type State = State;
 
     fn type_name() -> &'static TypeName {
         &*TYPENAME
     }
 
+    fn state(&self) -> State {
+        *self.state.get().get()
+    }
+
+    /// Only delegates and their devices may merge a patch.
+    fn authorize(&self, op: &Op, doc: &Doc<Verified>) -> bool {
+        match op.action {
+            Action::Merge { .. } => doc.is_authorized(&op.author),
+            _ => true,
+        }
+    }
+
+    fn references(&self) -> Vec<Reference> {
+        let references = self
+            .revisions()
+            .flat_map(|(_, r)| r.discussion.references())
+            .cloned()
+            .collect::<BTreeSet<_>>();
+
+        references.into_iter().collect()
+    }
+
     fn apply(&mut self, ops: impl IntoIterator<Item = Op>) -> Result<(), ApplyError> {
         for op in ops {
             let id = op.id();
@@ -303,6 +350,24 @@ impl store::FromHistory for Patch {
                         return Err(ApplyError::Missing(revision));
                     }
                 }
+                Action::CodeComment {
+                    revision,
+                    location,
+                    body,
+                } => {
+                    if let Some(Redactable::Present(revision)) = self.revisions.get_mut(&revision) {
+                        revision.code_comments.insert(
+                            id,
+                            Max::from(CodeComment {
+                                location,
+                                comment: body,
+                                timestamp,
+                            }),
+                        );
+                    } else {
+                        return Err(ApplyError::Missing(revision));
+                    }
+                }
                 Action::Thread { revision, action } => {
                     // TODO(cloudhead): Make sure we can deal with redacted revisions which are added
                     // to out of order, like in the `Merge` case.
@@ -321,7 +386,7 @@ impl store::FromHistory for Patch {
 }
 
 /// A patch revision.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Revision {
     /// Author of the revision.
     pub author: Author,
@@ -334,7 +399,9 @@ pub struct Revision {
     /// Merges of this revision into other repositories.
     pub merges: LWWSet<Max<Merge>>,
     /// Reviews of this revision's changes (one per actor).
-    pub reviews: GMap<ActorId, Review>,
+    pub reviews: VecMap<ActorId, Review>,
+    /// Comments on this revision's code, outside of reviews.
+    pub code_comments: GMap<CommentId, Max<CodeComment>>,
     /// When this revision was created.
     pub timestamp: Timestamp,
 }
@@ -347,7 +414,8 @@ impl Revision {
             oid,
             discussion: Thread::default(),
             merges: LWWSet::default(),
-            reviews: GMap::default(),
+            reviews: VecMap::default(),
+            code_comments: GMap::default(),
             timestamp,
         }
     }
@@ -356,6 +424,54 @@ impl Revision {
         let (_, comment) = self.discussion.root()?;
         Some(comment.body())
     }
+
+    /// Issues closed by this revision, ie. referenced by `Fixes:` or `Closes:` trailers
+    /// in the commits between the revision base and head.
+    pub fn closes(
+        &self,
+        repo: &git::raw::Repository,
+    ) -> Result<BTreeSet<ObjectId>, git::raw::Error> {
+        let mut walk = repo.revwalk()?;
+        walk.push(self.oid.into())?;
+        walk.hide(self.base.into())?;
+
+        let mut closes = BTreeSet::new();
+        for oid in walk {
+            let commit = repo.find_commit(oid?)?;
+            let msg = commit.message().unwrap_or_default();
+
+            closes.extend(storage::trailers::parse_closes(msg));
+        }
+        Ok(closes)
+    }
+
+    /// Delegates of the given identity whose review accepts the revision.
+    pub fn approvals<'a, V>(&'a self, doc: &'a Doc<V>) -> impl Iterator<Item = &'a ActorId> {
+        self.reviews
+            .iter()
+            .filter(|(key, review)| {
+                doc.is_delegate(key) && review.verdict() == Some(Verdict::Accept)
+            })
+            .map(|(key, _)| key)
+    }
+
+    /// Whether the revision was accepted by at least as many delegates of the given
+    /// identity as its threshold requires, ie. whether it has a quorum to be merged.
+    pub fn is_approved<V>(&self, doc: &Doc<V>) -> bool {
+        self.approvals(doc).count() >= doc.threshold
+    }
+
+    /// Comments on this revision's code, ordered by location. The author of a
+    /// comment is the actor of its [`CommentId`].
+    pub fn code_comments(&self) -> impl Iterator<Item = (&CommentId, &CodeComment)> {
+        let mut comments = self
+            .code_comments
+            .iter()
+            .map(|(id, c)| (id, c.get()))
+            .collect::<Vec<_>>();
+        comments.sort_by(|(a, x), (b, y)| (&x.location, a).cmp(&(&y.location, b)));
+        comments.into_iter()
+    }
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -410,6 +526,10 @@ impl fmt::Display for Verdict {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CodeLocation {
+    /// Path of the file being commented on, relative to the repository root.
+    /// Empty for comments made before paths were recorded.
+    #[serde(default)]
+    pub path: PathBuf,
     /// File being commented on.
     pub blob: git::Oid,
     /// Commit commented on.
@@ -426,12 +546,20 @@ impl PartialOrd for CodeLocation {
 
 impl Ord for CodeLocation {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        (&self.blob, &self.commit, &self.lines.start, &self.lines.end).cmp(&(
-            &other.blob,
-            &other.commit,
-            &other.lines.start,
-            &other.lines.end,
-        ))
+        (
+            &self.path,
+            &self.blob,
+            &self.commit,
+            &self.lines.start,
+            &self.lines.end,
+        )
+            .cmp(&(
+                &other.path,
+                &other.blob,
+                &other.commit,
+                &other.lines.start,
+                &other.lines.end,
+            ))
     }
 }
 
@@ -448,7 +576,7 @@ pub struct CodeComment {
 }
 
 /// A patch review on a revision.
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Review {
     /// Review verdict.
     pub verdict: LWWReg<Option<Verdict>>,
@@ -543,6 +671,60 @@ impl store::Transaction<Patch> {
         })
     }
 
+    /// Edit a comment on a patch revision.
+    pub fn edit_comment<S: ToString>(
+        &mut self,
+        revision: RevisionId,
+        id: CommentId,
+        body: S,
+    ) -> OpId {
+        self.push(Action::Thread {
+            revision,
+            action: thread::Action::Edit {
+                id,
+                body: body.to_string(),
+            },
+        })
+    }
+
+    /// Comment on the code of a patch revision.
+    pub fn code_comment<S: ToString>(
+        &mut self,
+        revision: RevisionId,
+        location: CodeLocation,
+        body: S,
+    ) -> CommentId {
+        self.push(Action::CodeComment {
+            revision,
+            location,
+            body: body.to_string(),
+        })
+    }
+
+    /// Redact a comment on a patch revision.
+    pub fn redact_comment(&mut self, revision: RevisionId, id: CommentId) -> OpId {
+        self.push(Action::Thread {
+            revision,
+            action: thread::Action::Redact { id },
+        })
+    }
+
+    /// Reference another object from a patch revision discussion, eg. an issue.
+    pub fn reference(
+        &mut self,
+        revision: RevisionId,
+        typename: TypeName,
+        object_id: ObjectId,
+    ) -> OpId {
+        self.push(Action::Thread {
+            revision,
+            action: thread::Action::Reference {
+                typename,
+                object_id,
+            },
+        })
+    }
+
     /// Review a patch revision.
     pub fn review(
         &mut self,
@@ -603,6 +785,7 @@ pub struct PatchMut<'a, 'g> {
 
     patch: Patch,
     clock: clock::Lamport,
+    tips: BTreeSet<git::Oid>,
     store: &'g mut Patches<'a>,
 }
 
@@ -611,11 +794,13 @@ impl<'a, 'g> PatchMut<'a, 'g> {
         id: ObjectId,
         patch: Patch,
         clock: clock::Lamport,
+        tips: BTreeSet<git::Oid>,
         store: &'g mut Patches<'a>,
     ) -> Self {
         Self {
             id,
             clock,
+            tips,
             patch,
             store,
         }
@@ -631,12 +816,13 @@ impl<'a, 'g> PatchMut<'a, 'g> {
         G: Signer,
         F: FnOnce(&mut Transaction<Patch>) -> T,
     {
-        let mut tx = Transaction::new(*signer.public_key(), self.clock);
+        let mut tx = Transaction::new(*signer.public_key(), self.clock, self.tips.clone());
         let output = operations(&mut tx);
-        let (ops, clock) = tx.commit(message, self.id, &mut self.store.raw, signer)?;
+        let (ops, clock, tips) = tx.commit(message, self.id, &mut self.store.raw, signer)?;
 
-        self.patch.apply(ops)?;
+        self.store.raw.apply(&mut self.patch, ops)?;
         self.clock = clock;
+        self.tips = tips;
 
         Ok(output)
     }
@@ -668,6 +854,57 @@ impl<'a, 'g> PatchMut<'a, 'g> {
         self.transaction("Comment", signer, |tx| tx.comment(revision, body, reply_to))
     }
 
+    /// Edit a comment on a patch revision. Only the comment author can edit a comment.
+    pub fn edit_comment<G: Signer, S: ToString>(
+        &mut self,
+        revision: RevisionId,
+        id: CommentId,
+        body: S,
+        signer: &G,
+    ) -> Result<OpId, Error> {
+        self.transaction("Edit comment", signer, |tx| {
+            tx.edit_comment(revision, id, body)
+        })
+    }
+
+    /// Comment on the code of a patch revision.
+    pub fn code_comment<G: Signer, S: ToString>(
+        &mut self,
+        revision: RevisionId,
+        location: CodeLocation,
+        body: S,
+        signer: &G,
+    ) -> Result<CommentId, Error> {
+        self.transaction("Code comment", signer, |tx| {
+            tx.code_comment(revision, location, body)
+        })
+    }
+
+    /// Redact a comment on a patch revision. Only the comment author can redact a comment.
+    pub fn redact_comment<G: Signer>(
+        &mut self,
+        revision: RevisionId,
+        id: CommentId,
+        signer: &G,
+    ) -> Result<OpId, Error> {
+        self.transaction("Redact comment", signer, |tx| {
+            tx.redact_comment(revision, id)
+        })
+    }
+
+    /// Reference another object from a patch revision discussion, eg. an issue.
+    pub fn reference<G: Signer>(
+        &mut self,
+        revision: RevisionId,
+        typename: TypeName,
+        object_id: ObjectId,
+        signer: &G,
+    ) -> Result<OpId, Error> {
+        self.transaction("Reference", signer, |tx| {
+            tx.reference(revision, typename, object_id)
+        })
+    }
+
     /// Review a patch revision.
     pub fn review<G: Signer>(
         &mut self,
@@ -683,13 +920,46 @@ impl<'a, 'g> PatchMut<'a, 'g> {
     }
 
     /// Merge a patch revision.
+    ///
+    /// Issues referenced by `Fixes:` or `Closes:` trailers in the commits of the revision
+    /// are closed as solved, on behalf of the merger.
     pub fn merge<G: Signer>(
         &mut self,
         revision: RevisionId,
         commit: git::Oid,
         signer: &G,
     ) -> Result<OpId, Error> {
-        self.transaction("Merge revision", signer, |tx| tx.merge(revision, commit))
+        let op = self.transaction("Merge revision", signer, |tx| tx.merge(revision, commit))?;
+        let Some(r) = self.patch.revision(&revision) else {
+            return Ok(op);
+        };
+        let repo = self.store.raw.as_ref();
+        let closes = match r.closes(&repo.backend) {
+            Ok(closes) => closes,
+            Err(err) if err.code() == git::raw::ErrorCode::NotFound => {
+                log::warn!("Revision {} of patch {} not found: {err}", r.oid, self.id);
+                return Ok(op);
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let mut issues = issue::Issues::open(*signer.public_key(), repo)?;
+
+        for id in closes {
+            let mut issue = match issues.get_mut(&id) {
+                Ok(issue) => issue,
+                Err(store::Error::NotFound(_, _)) => continue,
+                Err(err) => return Err(err.into()),
+            };
+            if let issue::State::Open = issue.state() {
+                issue.lifecycle(
+                    issue::State::Closed {
+                        reason: issue::CloseReason::Solved,
+                    },
+                    signer,
+                )?;
+            }
+        }
+        Ok(op)
     }
 
     /// Update a patch with a new revision.
@@ -770,7 +1040,13 @@ impl<'a> Patches<'a> {
         // Just a sanity check that our clock is advancing as expected.
         debug_assert_eq!(clock.get(), 3);
 
-        Ok(PatchMut::new(id, patch, clock, self))
+        Ok(PatchMut::new(
+            id,
+            patch,
+            clock,
+            BTreeSet::from([id.into()]),
+            self,
+        ))
     }
 
     /// Get a patch.
@@ -780,19 +1056,57 @@ impl<'a> Patches<'a> {
 
     /// Get a patch mutably.
     pub fn get_mut<'g>(&'g mut self, id: &ObjectId) -> Result<PatchMut<'a, 'g>, store::Error> {
-        let (patch, clock) = self
+        let (patch, clock, tips) = self
             .raw
-            .get(id)?
+            .get_with_tips(id)?
             .ok_or_else(move || store::Error::NotFound(TYPENAME.clone(), *id))?;
 
         Ok(PatchMut {
             id: *id,
             clock,
+            tips,
             patch,
             store: self,
         })
     }
 
+    /// Compute the difference between the code of two revisions of a patch, ie. the changes
+    /// made to the patch between revision `from` and revision `to`.
+    ///
+    /// If the revisions have different bases, `from` is first rebased onto the base of `to`,
+    /// so that changes to the base branch don't show up in the diff.
+    pub fn interdiff(
+        &self,
+        id: &PatchId,
+        from: &RevisionId,
+        to: &RevisionId,
+    ) -> Result<git::raw::Diff<'_>, Error> {
+        let patch = self
+            .get(id)?
+            .ok_or_else(|| store::Error::NotFound(TYPENAME.clone(), *id))?;
+        let from_id = *from;
+        let from = patch.revision(from).ok_or(Error::RevisionNotFound(*from))?;
+        let to = patch.revision(to).ok_or(Error::RevisionNotFound(*to))?;
+        let repo = &self.raw.as_ref().backend;
+        let old = repo.find_commit(from.oid.into())?.tree()?;
+        let new = repo.find_commit(to.oid.into())?.tree()?;
+        let old = if from.base == to.base {
+            old
+        } else {
+            let ancestor = repo.find_commit(from.base.into())?.tree()?;
+            let base = repo.find_commit(to.base.into())?.tree()?;
+            let mut index = repo.merge_trees(&ancestor, &base, &old, None)?;
+
+            if index.has_conflicts() {
+                return Err(Error::Conflict(from_id));
+            }
+            repo.find_tree(index.write_tree_to(repo)?)?
+        };
+        let diff = repo.diff_tree_to_tree(Some(&old), Some(&new), None)?;
+
+        Ok(diff)
+    }
+
     /// Get proposed patches.
     pub fn proposed(
         &self,
@@ -819,156 +1133,22 @@ impl<'a> Patches<'a> {
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
-    use std::{array, iter};
-
-    use radicle_crdt::test::{assert_laws, WeightedGenerator};
 
     use pretty_assertions::assert_eq;
-    use qcheck::{Arbitrary, TestResult};
+    use qcheck::TestResult;
 
     use super::*;
-    use crate::cob::op::{Actor, ActorId};
+    use crate::cob::op::Actor;
     use crate::crypto::test::signer::MockSigner;
     use crate::test;
-
-    #[derive(Clone)]
-    struct Changes<const N: usize> {
-        permutations: [Vec<Op>; N],
-    }
-
-    impl<const N: usize> std::fmt::Debug for Changes<N> {
-        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            for (i, p) in self.permutations.iter().enumerate() {
-                writeln!(
-                    f,
-                    "{i}: {:#?}",
-                    p.iter().map(|c| &c.action).collect::<Vec<_>>()
-                )?;
-            }
-            Ok(())
-        }
-    }
-
-    impl<const N: usize> Arbitrary for Changes<N> {
-        fn arbitrary(g: &mut qcheck::Gen) -> Self {
-            type State = (clock::Lamport, Vec<OpId>, Vec<Tag>);
-
-            let author = ActorId::from([0; 32]);
-            let rng = fastrand::Rng::with_seed(u64::arbitrary(g));
-            let oids = iter::repeat_with(|| {
-                git::Oid::try_from(
-                    iter::repeat_with(|| rng.u8(..))
-                        .take(20)
-                        .collect::<Vec<_>>()
-                        .as_slice(),
-                )
-                .unwrap()
-            })
-            .take(16)
-            .collect::<Vec<_>>();
-
-            let gen = WeightedGenerator::<(clock::Lamport, Action), State>::new(rng.clone())
-                .variant(1, |(clock, _, _), rng| {
-                    Some((
-                        clock.tick(),
-                        Action::Edit {
-                            title: iter::repeat_with(|| rng.alphabetic()).take(8).collect(),
-                            description: iter::repeat_with(|| rng.alphabetic()).take(16).collect(),
-                            target: MergeTarget::Delegates,
-                        },
-                    ))
-                })
-                .variant(1, |(clock, revisions, _), rng| {
-                    if revisions.is_empty() {
-                        return None;
-                    }
-                    let revision = revisions[rng.usize(..revisions.len())];
-                    let commit = oids[rng.usize(..oids.len())];
-
-                    Some((clock.tick(), Action::Merge { revision, commit }))
-                })
-                .variant(1, |(clock, revisions, _), rng| {
-                    if revisions.is_empty() {
-                        return None;
-                    }
-                    let revision = revisions[rng.usize(..revisions.len())];
-
-                    Some((clock.tick(), Action::Redact { revision }))
-                })
-                .variant(1, |(clock, _, tags), rng| {
-                    let add = iter::repeat_with(|| rng.alphabetic())
-                        .take(rng.usize(0..=3))
-                        .map(|c| Tag::new(c).unwrap())
-                        .collect::<Vec<_>>();
-                    let remove = tags
-                        .iter()
-                        .take(rng.usize(0..=tags.len()))
-                        .cloned()
-                        .collect();
-                    for tag in &add {
-                        tags.push(tag.clone());
-                    }
-                    Some((clock.tick(), Action::Tag { add, remove }))
-                })
-                .variant(1, |(clock, revisions, _), rng| {
-                    let oid = oids[rng.usize(..oids.len())];
-                    let base = oids[rng.usize(..oids.len())];
-
-                    if rng.bool() {
-                        revisions.push(OpId::new(clock.tick(), author));
-                    }
-                    Some((*clock, Action::Revision { base, oid }))
-                });
-
-            let mut changes = Vec::new();
-            let mut permutations: [Vec<Op>; N] = array::from_fn(|_| Vec::new());
-            let timestamp = Timestamp::now() + rng.u64(..60);
-
-            for (clock, action) in gen.take(g.size()) {
-                changes.push(Op::new(action, author, timestamp, clock));
-            }
-
-            for p in &mut permutations {
-                *p = changes.clone();
-                rng.shuffle(&mut changes);
-            }
-
-            Changes { permutations }
-        }
-    }
+    use crate::test::arbitrary::cob::{converges, Changes};
 
     #[test]
     fn prop_invariants() {
-        fn property(log: Changes<3>) -> TestResult {
-            let t = Patch::default();
-            let [p1, p2, p3] = log.permutations;
-
-            let mut t1 = t.clone();
-            if t1.apply(p1).is_err() {
-                return TestResult::discard();
-            }
-
-            let mut t2 = t.clone();
-            if t2.apply(p2).is_err() {
-                return TestResult::discard();
-            }
-
-            let mut t3 = t;
-            if t3.apply(p3).is_err() {
-                return TestResult::discard();
-            }
-
-            assert_eq!(t1, t2);
-            assert_eq!(t2, t3);
-            assert_laws(&t1, &t2, &t3);
-
-            TestResult::passed()
-        }
-
         qcheck::QuickCheck::new()
             .min_tests_passed(100)
             .gen(qcheck::Gen::new(7))
-            .quickcheck(property as fn(Changes<3>) -> TestResult);
+            .quickcheck(converges::<Patch, 3> as fn(Changes<Action, 3>) -> TestResult);
     }
 
     #[test]
@@ -1059,6 +1239,123 @@ mod test {
         assert_eq!(merge.commit, base);
     }
 
+    #[test]
+    fn test_patch_unauthorized_merge() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let oid = git::Oid::from_str("e2a85016a458cd809c0ecee81f8c99613b0b0945").unwrap();
+        let base = git::Oid::from_str("cb18e95ada2bb38aadd8e6cef0963ce37a87add3").unwrap();
+        let mut patches = Patches::open(*signer.public_key(), &project).unwrap();
+        let other = MockSigner::default();
+        let mut patch = patches
+            .create(
+                "My first patch",
+                "Blah blah blah.",
+                MergeTarget::Delegates,
+                base,
+                oid,
+                &[],
+                &signer,
+            )
+            .unwrap();
+
+        // Not a delegate.
+        let (rid, _) = patch.revisions().next().unwrap();
+        let _merge = patch.merge(*rid, base, &other).unwrap();
+
+        let (_, r) = patch.revisions().next().unwrap();
+        assert!(r.merges.is_empty());
+
+        // The in-memory patch matches the stored one.
+        let id = patch.id;
+        let patch = (*patch).clone();
+        let stored = patches.get(&id).unwrap().unwrap();
+        assert_eq!(patch, stored);
+    }
+
+    #[test]
+    fn test_patch_merge_closes_issues() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let mut issues = issue::Issues::open(*signer.public_key(), &project).unwrap();
+        let fixed = issues
+            .create("My first issue", "Blah blah blah.", &[], &signer)
+            .unwrap()
+            .id;
+        let closed = issues
+            .create("My second issue", "Blah blah blah.", &[], &signer)
+            .unwrap()
+            .id;
+        let open = issues
+            .create("My third issue", "Blah blah blah.", &[], &signer)
+            .unwrap()
+            .id;
+
+        let repo = &project.backend;
+        let sig = git2::Signature::now("anonymous", "anonymous@radicle.xyz").unwrap();
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let base = repo
+            .commit(None, &sig, &sig, "Initial commit", &tree, &[])
+            .unwrap();
+        let base = repo.find_commit(base).unwrap();
+        let first = repo
+            .commit(
+                None,
+                &sig,
+                &sig,
+                &format!("Fix the thing\n\nFixes: {fixed}\n"),
+                &tree,
+                &[&base],
+            )
+            .unwrap();
+        let first = repo.find_commit(first).unwrap();
+        let head = repo
+            .commit(
+                None,
+                &sig,
+                &sig,
+                &format!(
+                    "Fix the other thing\n\nCloses: {closed}\nCloses: {}\n",
+                    test::arbitrary::oid()
+                ),
+                &tree,
+                &[&first],
+            )
+            .unwrap();
+
+        let mut patches = Patches::open(*signer.public_key(), &project).unwrap();
+        let mut patch = patches
+            .create(
+                "My first patch",
+                "Blah blah blah.",
+                MergeTarget::Delegates,
+                base.id(),
+                head,
+                &[],
+                &signer,
+            )
+            .unwrap();
+        let (rid, _) = patch.latest().unwrap();
+        let rid = *rid;
+        patch.merge(rid, base.id().into(), &signer).unwrap();
+
+        let issues = issue::Issues::open(*signer.public_key(), &project).unwrap();
+        for id in [fixed, closed] {
+            let issue = issues.get(&id).unwrap().unwrap();
+            assert_eq!(
+                *issue.state(),
+                issue::State::Closed {
+                    reason: issue::CloseReason::Solved
+                }
+            );
+            assert_eq!(issue.closed_by(), Some(signer.public_key()));
+        }
+        let issue = issues.get(&open).unwrap().unwrap();
+        assert_eq!(*issue.state(), issue::State::Open);
+    }
+
     #[test]
     fn test_patch_review() {
         let tmp = tempfile::tempdir().unwrap();
@@ -1099,6 +1396,106 @@ mod test {
         assert_eq!(review.comment(), Some("LGTM"));
     }
 
+    #[test]
+    fn test_patch_code_comment() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let base = git::Oid::from_str("cb18e95ada2bb38aadd8e6cef0963ce37a87add3").unwrap();
+        let oid = git::Oid::from_str("518d5069f94c03427f694bb494ac1cd7d1339380").unwrap();
+        let blob = git::Oid::from_str("e2a85016a458cd809c0ecee81f8c99613b0b0945").unwrap();
+        let mut patches = Patches::open(*signer.public_key(), &project).unwrap();
+        let mut patch = patches
+            .create(
+                "My first patch",
+                "Blah blah blah.",
+                MergeTarget::Delegates,
+                base,
+                oid,
+                &[],
+                &signer,
+            )
+            .unwrap();
+
+        let location = |path: &str, start| CodeLocation {
+            path: PathBuf::from(path),
+            blob,
+            commit: oid,
+            lines: start..start + 2,
+        };
+        let (rid, _) = patch.latest().unwrap();
+        let rid = *rid;
+        let c1 = patch
+            .code_comment(rid, location("src/main.rs", 10), "Typo.", &signer)
+            .unwrap();
+        let c2 = patch
+            .code_comment(rid, location("README", 1), "Outdated.", &signer)
+            .unwrap();
+
+        let id = patch.id;
+        let patch = patches.get(&id).unwrap().unwrap();
+        let (_, revision) = patch.latest().unwrap();
+        let comments = revision.code_comments().collect::<Vec<_>>();
+
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].0, &c2);
+        assert_eq!(comments[0].1.location, location("README", 1));
+        assert_eq!(comments[0].1.comment, "Outdated.");
+        assert_eq!(comments[1].0, &c1);
+        assert_eq!(comments[1].0.actor(), signer.public_key());
+        assert_eq!(comments[1].1.location.lines, 10..12);
+    }
+
+    #[test]
+    fn test_patch_review_quorum() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let base = git::Oid::from_str("cb18e95ada2bb38aadd8e6cef0963ce37a87add3").unwrap();
+        let oid = git::Oid::from_str("518d5069f94c03427f694bb494ac1cd7d1339380").unwrap();
+        let other = MockSigner::default();
+        let stranger = MockSigner::default();
+        let mut doc = project.identity_of(signer.public_key()).unwrap();
+        let mut patches = Patches::open(*signer.public_key(), &project).unwrap();
+        let mut patch = patches
+            .create(
+                "My first patch",
+                "Blah blah blah.",
+                MergeTarget::Delegates,
+                base,
+                oid,
+                &[],
+                &signer,
+            )
+            .unwrap();
+        let (rid, _) = patch.latest().unwrap();
+        let rid = *rid;
+
+        doc.delegates.push((*other.public_key()).into());
+        doc.threshold = 2;
+
+        patch
+            .review(rid, Some(Verdict::Accept), None, vec![], &signer)
+            .unwrap();
+        let (_, revision) = patch.latest().unwrap();
+        assert_eq!(
+            revision.approvals(&doc).collect::<Vec<_>>(),
+            vec![signer.public_key()]
+        );
+        assert!(!revision.is_approved(&doc));
+
+        patch
+            .review(rid, Some(Verdict::Accept), None, vec![], &stranger)
+            .unwrap();
+        let (_, revision) = patch.latest().unwrap();
+        assert!(!revision.is_approved(&doc), "only delegates count");
+
+        patch
+            .review(rid, Some(Verdict::Accept), None, vec![], &other)
+            .unwrap();
+        let (_, revision) = patch.latest().unwrap();
+        assert_eq!(revision.approvals(&doc).count(), 2);
+        assert!(revision.is_approved(&doc));
+    }
+
     #[test]
     fn test_revision_redacted() {
         let base = git::Oid::from_str("cb18e95ada2bb38aadd8e6cef0963ce37a87add3").unwrap();
@@ -1240,4 +1637,109 @@ mod test {
         assert_eq!(revision.oid, rev1_oid);
         assert_eq!(revision.description(), Some("I've made changes."));
     }
+
+    #[test]
+    fn test_patch_interdiff() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let repo = &project.backend;
+        let sig = git2::Signature::now("anonymous", "anonymous@radicle.xyz").unwrap();
+        let commit = |content: &[u8], parents: &[&git2::Commit]| {
+            let blob = repo.blob(content).unwrap();
+            let mut tree = repo.treebuilder(None).unwrap();
+            tree.insert("README", blob, 0o100644).unwrap();
+            let tree = repo.find_tree(tree.write().unwrap()).unwrap();
+            let oid = repo
+                .commit(None, &sig, &sig, "Update README", &tree, parents)
+                .unwrap();
+
+            repo.find_commit(oid).unwrap()
+        };
+        let base = commit(b"Hello World!\n", &[]);
+        let rev0 = commit(b"Hello Radicle!\n", &[&base]);
+        let rev1 = commit(b"Hello Radicle!\nBye.\n", &[&base]);
+
+        let mut patches = Patches::open(*signer.public_key(), &project).unwrap();
+        let mut patch = patches
+            .create(
+                "My first patch",
+                "Blah blah blah.",
+                MergeTarget::Delegates,
+                base.id(),
+                rev0.id(),
+                &[],
+                &signer,
+            )
+            .unwrap();
+        let (r0, _) = patch.latest().unwrap();
+        let r0 = *r0;
+        let (r1, _) = patch
+            .update("I've made changes.", base.id(), rev1.id(), &signer)
+            .unwrap();
+        let id = patch.id;
+
+        let diff = patches.interdiff(&id, &r0, &r1).unwrap();
+        let stats = diff.stats().unwrap();
+        assert_eq!(stats.files_changed(), 1);
+        assert_eq!(stats.insertions(), 1);
+        assert_eq!(stats.deletions(), 0);
+
+        let diff = patches.interdiff(&id, &r0, &r0).unwrap();
+        assert_eq!(diff.deltas().len(), 0);
+    }
+
+    #[test]
+    fn test_patch_interdiff_rebased() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let repo = &project.backend;
+        let sig = git2::Signature::now("anonymous", "anonymous@radicle.xyz").unwrap();
+        let commit = |readme: &[u8], license: &[u8], parents: &[&git2::Commit]| {
+            let mut tree = repo.treebuilder(None).unwrap();
+            tree.insert("README", repo.blob(readme).unwrap(), 0o100644)
+                .unwrap();
+            tree.insert("LICENSE", repo.blob(license).unwrap(), 0o100644)
+                .unwrap();
+            let tree = repo.find_tree(tree.write().unwrap()).unwrap();
+            let oid = repo
+                .commit(None, &sig, &sig, "Update", &tree, parents)
+                .unwrap();
+
+            repo.find_commit(oid).unwrap()
+        };
+        let base0 = commit(b"Hello World!\n", b"MIT\n", &[]);
+        let base1 = commit(b"Hello World!\n", b"Apache-2.0\n", &[&base0]);
+        let rev0 = commit(b"Hello Radicle!\n", b"MIT\n", &[&base0]);
+        let rev1 = commit(b"Hello Radicle!\nBye.\n", b"Apache-2.0\n", &[&base1]);
+
+        let mut patches = Patches::open(*signer.public_key(), &project).unwrap();
+        let mut patch = patches
+            .create(
+                "My first patch",
+                "Blah blah blah.",
+                MergeTarget::Delegates,
+                base0.id(),
+                rev0.id(),
+                &[],
+                &signer,
+            )
+            .unwrap();
+        let (r0, _) = patch.latest().unwrap();
+        let r0 = *r0;
+        let (r1, _) = patch
+            .update("Rebased.", base1.id(), rev1.id(), &signer)
+            .unwrap();
+        let id = patch.id;
+
+        // Only the README change shows up, not the change to the base branch.
+        let diff = patches.interdiff(&id, &r0, &r1).unwrap();
+        let stats = diff.stats().unwrap();
+        assert_eq!(stats.files_changed(), 1);
+        assert_eq!(stats.insertions(), 1);
+        assert_eq!(stats.deletions(), 0);
+        assert_eq!(
+            diff.deltas().next().unwrap().new_file().path(),
+            Some(std::path::Path::new("README"))
+        );
+    }
 }
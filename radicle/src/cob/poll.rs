@@ -0,0 +1,519 @@
+use std::collections::BTreeSet;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use radicle_crdt::clock;
+use radicle_crdt::{LWWMap, Max};
+
+use crate::cob;
+use crate::cob::common::{Author, Timestamp};
+use crate::cob::store::FromHistory as _;
+use crate::cob::store::Transaction;
+use crate::cob::{store, ActorId, ObjectId, OpId, TypeName};
+use crate::crypto::{PublicKey, Signer};
+use crate::git;
+use crate::identity::Doc;
+use crate::storage::git as storage;
+
+/// Poll operation.
+pub type Op = cob::Op<Action>;
+
+/// Type name of a poll.
+pub static TYPENAME: Lazy<TypeName> =
+    Lazy::new(|| FromStr::from_str("xyz.radicle.poll").expect("type name is valid"));
+
+/// Identifier for a poll.
+pub type PollId = ObjectId;
+
+/// Index of a poll option.
+pub type OptionIx = usize;
+
+/// Error updating or creating polls.
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("poll was already opened")]
+    Opened,
+    #[error("poll was not opened")]
+    NotOpened,
+    #[error("poll must have at least two options")]
+    NotEnoughOptions,
+    #[error("poll is closed")]
+    Closed,
+    #[error("invalid vote: {0}")]
+    InvalidVote(&'static str),
+    #[error("store: {0}")]
+    Store(#[from] store::Error),
+}
+
+/// Poll settings, set when the poll is opened.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Settings {
+    /// Whether voters rank options, instead of choosing a single one.
+    pub ranked: bool,
+    /// When the poll closes. Votes cast after this time are not counted.
+    pub closes: Option<Timestamp>,
+    /// How much a vote by a delegate of the repository counts for. Other votes
+    /// count for one.
+    pub delegate_weight: Option<u32>,
+}
+
+/// Poll state. Accumulates [`Action`].
+///
+/// Participants vote by choosing a single option, or by ranking options if the poll
+/// is ranked. A participant's latest vote replaces their previous one. Votes are
+/// tallied against the identity document of the repository, so that delegates' votes
+/// can be weighted, see [`Settings::delegate_weight`].
+///
+/// Note that whether a vote was cast before the poll closed is based on the
+/// vote's timestamp, which is set by the voter.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Poll {
+    author: Option<ActorId>,
+    question: String,
+    options: Vec<String>,
+    settings: Option<Settings>,
+    votes: LWWMap<ActorId, Max<Vec<OptionIx>>>,
+}
+
+impl store::FromHistory for Poll {
+    type Action = Action;
+    type Error = Error;
+    type State = ();
+
+    fn type_name() -> &'static TypeName {
+        &*TYPENAME
+    }
+
+    fn state(&self) {}
+
+    fn apply(&mut self, ops: impl IntoIterator<Item = Op>) -> Result<(), Error> {
+        for op in ops {
+            match op.action {
+                Action::Open {
+                    question,
+                    options,
+                    settings,
+                } => {
+                    if self.settings.is_some() {
+                        return Err(Error::Opened);
+                    }
+                    if options.len() < 2 {
+                        return Err(Error::NotEnoughOptions);
+                    }
+                    self.author = Some(op.author);
+                    self.question = question;
+                    self.options = options;
+                    self.settings = Some(settings);
+                }
+                Action::Vote { choices } => {
+                    if self.settings.is_none() {
+                        return Err(Error::NotOpened);
+                    }
+                    // Invalid and late votes are ignored, so that they don't prevent
+                    // other votes from being applied.
+                    if self.validate(&choices, op.timestamp).is_err() {
+                        continue;
+                    }
+                    if choices.is_empty() {
+                        self.votes.remove(op.author, op.clock);
+                    } else {
+                        self.votes.insert(op.author, Max::from(choices), op.clock);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Poll {
+    /// Poll author.
+    pub fn author(&self) -> Option<Author> {
+        self.author.map(Author::new)
+    }
+
+    /// Poll question.
+    pub fn question(&self) -> &str {
+        self.question.as_str()
+    }
+
+    /// Poll options, in the order they are indexed by votes.
+    pub fn options(&self) -> impl Iterator<Item = &str> {
+        self.options.iter().map(|o| o.as_str())
+    }
+
+    /// Whether votes rank options.
+    pub fn is_ranked(&self) -> bool {
+        self.settings.as_ref().map_or(false, |s| s.ranked)
+    }
+
+    /// When the poll closes, if ever.
+    pub fn closes(&self) -> Option<Timestamp> {
+        self.settings.as_ref().and_then(|s| s.closes)
+    }
+
+    /// Whether the poll is closed at the given time.
+    pub fn is_closed(&self, now: Timestamp) -> bool {
+        self.closes().map_or(false, |closes| now > closes)
+    }
+
+    /// Weight of a delegate's vote.
+    pub fn delegate_weight(&self) -> Option<u32> {
+        self.settings.as_ref().and_then(|s| s.delegate_weight)
+    }
+
+    /// Current votes, ie. the options chosen by each voter, in order of preference.
+    pub fn votes(&self) -> impl Iterator<Item = (&ActorId, &[OptionIx])> {
+        self.votes
+            .iter()
+            .map(|(voter, v)| (voter, v.get().as_slice()))
+    }
+
+    /// Tally votes, given the identity document of the repository. Returns a score
+    /// for each option, in the same order as [`Poll::options`].
+    ///
+    /// Votes are counted as a Borda count: out of `n` options, an option ranked first
+    /// scores `n` times the vote weight, an option ranked second scores `n - 1` times
+    /// the vote weight, and so on. For single-choice polls, this amounts to counting
+    /// weighted votes.
+    pub fn tally<V>(&self, doc: &Doc<V>) -> Vec<u64> {
+        let n = self.options.len();
+        let mut scores = vec![0; n];
+
+        for (voter, choices) in self.votes() {
+            let weight = if doc.is_delegate(voter) {
+                self.delegate_weight().unwrap_or(1)
+            } else {
+                1
+            } as u64;
+
+            for (rank, ix) in choices.iter().enumerate() {
+                if let Some(score) = scores.get_mut(*ix) {
+                    *score += weight * (n - rank) as u64;
+                }
+            }
+        }
+        scores
+    }
+
+    /// Options with the highest score. There is more than one in case of a tie, and
+    /// none if no votes were cast.
+    pub fn winners<V>(&self, doc: &Doc<V>) -> Vec<OptionIx> {
+        let scores = self.tally(doc);
+        let max = scores.iter().copied().max().unwrap_or_default();
+
+        if max == 0 {
+            return vec![];
+        }
+        scores
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| **s == max)
+            .map(|(ix, _)| ix)
+            .collect()
+    }
+
+    /// Check that a vote cast at the given time is valid.
+    fn validate(&self, choices: &[OptionIx], timestamp: Timestamp) -> Result<(), Error> {
+        if self.is_closed(timestamp) {
+            return Err(Error::Closed);
+        }
+        if !self.is_ranked() && choices.len() > 1 {
+            return Err(Error::InvalidVote("only one option can be chosen"));
+        }
+        if choices.iter().any(|ix| *ix >= self.options.len()) {
+            return Err(Error::InvalidVote("option does not exist"));
+        }
+        if choices.iter().collect::<BTreeSet<_>>().len() != choices.len() {
+            return Err(Error::InvalidVote("options can only be chosen once"));
+        }
+        Ok(())
+    }
+}
+
+impl store::Transaction<Poll> {
+    /// Open a poll.
+    pub fn open(&mut self, question: String, options: Vec<String>, settings: Settings) -> OpId {
+        self.push(Action::Open {
+            question,
+            options,
+            settings,
+        })
+    }
+
+    /// Vote on a poll.
+    pub fn vote(&mut self, choices: Vec<OptionIx>) -> OpId {
+        self.push(Action::Vote { choices })
+    }
+}
+
+pub struct PollMut<'a, 'g> {
+    id: ObjectId,
+    clock: clock::Lamport,
+    tips: BTreeSet<git::Oid>,
+    poll: Poll,
+    store: &'g mut Polls<'a>,
+}
+
+impl<'a, 'g> PollMut<'a, 'g> {
+    /// Get the poll id.
+    pub fn id(&self) -> &PollId {
+        &self.id
+    }
+
+    /// Get the internal logical clock.
+    pub fn clock(&self) -> &clock::Lamport {
+        &self.clock
+    }
+
+    /// Vote on the poll, replacing our previous vote. For ranked polls, options are
+    /// given in order of preference.
+    pub fn vote<G: Signer>(&mut self, choices: Vec<OptionIx>, signer: &G) -> Result<OpId, Error> {
+        if choices.is_empty() {
+            return Err(Error::InvalidVote("at least one option must be chosen"));
+        }
+        self.poll.validate(&choices, Timestamp::now())?;
+        self.transaction("Vote", signer, |tx| tx.vote(choices))
+    }
+
+    /// Retract our vote.
+    pub fn retract<G: Signer>(&mut self, signer: &G) -> Result<OpId, Error> {
+        if self.poll.is_closed(Timestamp::now()) {
+            return Err(Error::Closed);
+        }
+        self.transaction("Retract vote", signer, |tx| tx.vote(vec![]))
+    }
+
+    pub fn transaction<G, F, T>(
+        &mut self,
+        message: &str,
+        signer: &G,
+        operations: F,
+    ) -> Result<T, Error>
+    where
+        G: Signer,
+        F: FnOnce(&mut Transaction<Poll>) -> T,
+    {
+        let mut tx = Transaction::new(*signer.public_key(), self.clock, self.tips.clone());
+        let output = operations(&mut tx);
+        let (ops, clock, tips) = tx.commit(message, self.id, &mut self.store.raw, signer)?;
+
+        self.store.raw.apply(&mut self.poll, ops)?;
+        self.clock = clock;
+        self.tips = tips;
+
+        Ok(output)
+    }
+}
+
+impl<'a, 'g> Deref for PollMut<'a, 'g> {
+    type Target = Poll;
+
+    fn deref(&self) -> &Self::Target {
+        &self.poll
+    }
+}
+
+pub struct Polls<'a> {
+    raw: store::Store<'a, Poll>,
+}
+
+impl<'a> Deref for Polls<'a> {
+    type Target = store::Store<'a, Poll>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.raw
+    }
+}
+
+impl<'a> Polls<'a> {
+    /// Open a polls store.
+    pub fn open(
+        whoami: PublicKey,
+        repository: &'a storage::Repository,
+    ) -> Result<Self, store::Error> {
+        let raw = store::Store::open(whoami, repository)?;
+
+        Ok(Self { raw })
+    }
+
+    /// Get a poll.
+    pub fn get(&self, id: &ObjectId) -> Result<Option<Poll>, store::Error> {
+        self.raw.get(id).map(|r| r.map(|(p, _clock)| p))
+    }
+
+    /// Get a poll mutably.
+    pub fn get_mut<'g>(&'g mut self, id: &ObjectId) -> Result<PollMut<'a, 'g>, store::Error> {
+        let (poll, clock, tips) = self
+            .raw
+            .get_with_tips(id)?
+            .ok_or_else(move || store::Error::NotFound(TYPENAME.clone(), *id))?;
+
+        Ok(PollMut {
+            id: *id,
+            clock,
+            tips,
+            poll,
+            store: self,
+        })
+    }
+
+    /// Open a new poll.
+    pub fn create<'g, G: Signer>(
+        &'g mut self,
+        question: impl ToString,
+        options: Vec<String>,
+        settings: Settings,
+        signer: &G,
+    ) -> Result<PollMut<'a, 'g>, Error> {
+        if options.len() < 2 {
+            return Err(Error::NotEnoughOptions);
+        }
+        let (id, poll, clock) = Transaction::initial("Open poll", &mut self.raw, signer, |tx| {
+            tx.open(question.to_string(), options, settings);
+        })?;
+
+        Ok(PollMut {
+            id,
+            clock,
+            tips: BTreeSet::from([id.into()]),
+            poll,
+            store: self,
+        })
+    }
+
+    /// Remove a poll.
+    pub fn remove(&self, id: &ObjectId) -> Result<(), store::Error> {
+        self.raw.remove(id)
+    }
+}
+
+/// Poll operation.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Action {
+    /// Open the poll. Can only happen once per poll.
+    Open {
+        question: String,
+        options: Vec<String>,
+        settings: Settings,
+    },
+    /// Vote, replacing the author's previous vote. An empty list of choices
+    /// retracts the vote.
+    Vote { choices: Vec<OptionIx> },
+}
+
+impl cob::op::Migrate for Action {}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::crypto::test::signer::MockSigner;
+    use crate::test;
+
+    fn options() -> Vec<String> {
+        vec![
+            String::from("Monthly"),
+            String::from("Quarterly"),
+            String::from("Yearly"),
+        ]
+    }
+
+    #[test]
+    fn test_poll_single_choice() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let (bob, carol) = (MockSigner::default(), MockSigner::default());
+        let doc = project.identity_of(signer.public_key()).unwrap();
+        let mut polls = Polls::open(*signer.public_key(), &project).unwrap();
+        let settings = Settings {
+            ranked: false,
+            closes: None,
+            delegate_weight: Some(3),
+        };
+        let mut poll = polls
+            .create("How often should we release?", options(), settings, &signer)
+            .unwrap();
+
+        assert!(matches!(
+            poll.vote(vec![0, 1], &bob),
+            Err(Error::InvalidVote(_))
+        ));
+        assert!(matches!(
+            poll.vote(vec![3], &bob),
+            Err(Error::InvalidVote(_))
+        ));
+
+        poll.vote(vec![0], &bob).unwrap();
+        poll.vote(vec![0], &carol).unwrap();
+        poll.vote(vec![2], &carol).unwrap();
+        poll.vote(vec![1], &signer).unwrap();
+
+        let id = *poll.id();
+        let poll = polls.get(&id).unwrap().unwrap();
+        assert_eq!(poll.question(), "How often should we release?");
+        assert_eq!(poll.votes().count(), 3);
+        // Options score the number of options times the vote weight.
+        assert_eq!(poll.tally(&doc), vec![3, 9, 3]);
+        assert_eq!(poll.winners(&doc), vec![1]);
+    }
+
+    #[test]
+    fn test_poll_ranked() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let bob = MockSigner::default();
+        let doc = project.identity_of(signer.public_key()).unwrap();
+        let mut polls = Polls::open(*signer.public_key(), &project).unwrap();
+        let settings = Settings {
+            ranked: true,
+            closes: None,
+            delegate_weight: None,
+        };
+        let mut poll = polls
+            .create("How often should we release?", options(), settings, &signer)
+            .unwrap();
+
+        assert!(matches!(
+            poll.vote(vec![0, 0], &bob),
+            Err(Error::InvalidVote(_))
+        ));
+        poll.vote(vec![2, 0], &bob).unwrap();
+        poll.vote(vec![0, 1, 2], &signer).unwrap();
+        assert_eq!(poll.tally(&doc), vec![5, 2, 4]);
+        assert_eq!(poll.winners(&doc), vec![0]);
+
+        poll.retract(&bob).unwrap();
+        assert_eq!(poll.tally(&doc), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_poll_closed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let mut polls = Polls::open(*signer.public_key(), &project).unwrap();
+        let settings = Settings {
+            ranked: false,
+            closes: Some(Timestamp::from(1)),
+            delegate_weight: None,
+        };
+        let mut poll = polls
+            .create("How often should we release?", options(), settings, &signer)
+            .unwrap();
+
+        assert!(poll.is_closed(Timestamp::now()));
+        assert!(matches!(poll.vote(vec![0], &signer), Err(Error::Closed)));
+
+        // Late votes are ignored when applied.
+        poll.transaction("Vote", &signer, |tx| tx.vote(vec![0]))
+            .unwrap();
+        assert_eq!(poll.votes().count(), 0);
+    }
+}
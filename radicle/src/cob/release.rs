@@ -0,0 +1,492 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Deref;
+use std::str::FromStr;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use radicle_crdt::clock;
+
+use crate::cob;
+use crate::cob::common::Author;
+use crate::cob::store::encoding;
+use crate::cob::store::FromHistory as _;
+use crate::cob::store::Transaction;
+use crate::cob::{store, ActorId, ObjectId, OpId, TypeName};
+use crate::crypto::{PublicKey, Signature, Signer};
+use crate::git;
+use crate::identity::Doc;
+use crate::storage::git as storage;
+
+/// Release operation.
+pub type Op = cob::Op<Action>;
+
+/// Type name of a release.
+pub static TYPENAME: Lazy<TypeName> =
+    Lazy::new(|| FromStr::from_str("xyz.radicle.release").expect("type name is valid"));
+
+/// Identifier for a release.
+pub type ReleaseId = ObjectId;
+
+/// Error updating or creating releases.
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("release was not published")]
+    NotPublished,
+    #[error("store: {0}")]
+    Store(#[from] store::Error),
+}
+
+/// A release artifact, eg. a source tarball or binary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Artifact {
+    /// Artifact file name.
+    pub name: String,
+    /// Artifact hash, prefixed with the hash algorithm, eg. `sha256:<hex>`.
+    pub hash: String,
+}
+
+/// What was released. This is what delegates sign.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    /// Tag or commit that was released.
+    pub oid: git::Oid,
+    /// Name of the released tag, if a tag was released, eg. `v1.0.2`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// Release version, eg. `1.0.2`.
+    pub version: String,
+    /// Release notes.
+    pub notes: String,
+    /// Released artifacts.
+    pub artifacts: Vec<Artifact>,
+}
+
+impl Manifest {
+    /// Canonical encoding of the manifest, over which signatures are made.
+    pub fn encode(&self) -> Vec<u8> {
+        encoding::encode(self).expect("Manifest::encode: manifest is serializable")
+    }
+
+    /// Sign the manifest.
+    pub fn sign<G: Signer>(&self, signer: &G) -> Signature {
+        signer.sign(&self.encode())
+    }
+
+    /// Verify a signature over the manifest.
+    pub fn verify(&self, key: &PublicKey, signature: &Signature) -> bool {
+        key.verify(self.encode(), signature).is_ok()
+    }
+}
+
+/// Release state. Accumulates [`Action`].
+///
+/// A release is published by a delegate, with a signature over its [`Manifest`], and
+/// co-signed by other delegates. Signatures are verified when operations are applied,
+/// so a release only ever holds valid signatures. Whether a release is attested, ie.
+/// signed by enough delegates, depends on the identity document it is checked against.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Release {
+    author: Option<ActorId>,
+    manifest: Option<Manifest>,
+    signatures: BTreeMap<ActorId, Signature>,
+}
+
+impl store::FromHistory for Release {
+    type Action = Action;
+    type Error = Error;
+    type State = ();
+
+    fn type_name() -> &'static TypeName {
+        &*TYPENAME
+    }
+
+    fn state(&self) {}
+
+    fn apply(&mut self, ops: impl IntoIterator<Item = Op>) -> Result<(), Error> {
+        for op in ops {
+            match op.action {
+                // Invalid signatures and later publications are ignored, so that they
+                // don't prevent other operations from being applied.
+                Action::Publish {
+                    manifest,
+                    signature,
+                } => {
+                    if self.manifest.is_some() || !manifest.verify(&op.author, &signature) {
+                        continue;
+                    }
+                    self.author = Some(op.author);
+                    self.manifest = Some(manifest);
+                    self.signatures.insert(op.author, signature);
+                }
+                Action::Sign { signature } => {
+                    let Some(manifest) = &self.manifest else {
+                        return Err(Error::NotPublished);
+                    };
+                    if !manifest.verify(&op.author, &signature) {
+                        continue;
+                    }
+                    self.signatures.insert(op.author, signature);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Release {
+    /// Release author.
+    pub fn author(&self) -> Option<Author> {
+        self.author.map(Author::new)
+    }
+
+    /// Release manifest.
+    pub fn manifest(&self) -> Option<&Manifest> {
+        self.manifest.as_ref()
+    }
+
+    /// Released tag or commit.
+    pub fn oid(&self) -> Option<git::Oid> {
+        self.manifest.as_ref().map(|m| m.oid)
+    }
+
+    /// Name of the released tag, if any.
+    pub fn tag(&self) -> Option<&str> {
+        self.manifest.as_ref().and_then(|m| m.tag.as_deref())
+    }
+
+    /// Release version.
+    pub fn version(&self) -> &str {
+        self.manifest
+            .as_ref()
+            .map(|m| m.version.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Release notes.
+    pub fn notes(&self) -> &str {
+        self.manifest
+            .as_ref()
+            .map(|m| m.notes.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Released artifacts.
+    pub fn artifacts(&self) -> impl Iterator<Item = &Artifact> {
+        self.manifest.iter().flat_map(|m| m.artifacts.iter())
+    }
+
+    /// All valid signatures over the release manifest.
+    pub fn signatures(&self) -> impl Iterator<Item = (&ActorId, &Signature)> {
+        self.signatures.iter()
+    }
+
+    /// Delegates of the given identity who signed the release.
+    pub fn signers<'a, V>(&'a self, doc: &'a Doc<V>) -> impl Iterator<Item = &'a ActorId> {
+        self.signatures.keys().filter(|key| doc.is_delegate(key))
+    }
+
+    /// Whether the release was signed by at least as many delegates of the given
+    /// identity as its threshold requires.
+    pub fn is_attested<V>(&self, doc: &Doc<V>) -> bool {
+        self.manifest.is_some() && self.signers(doc).count() >= doc.threshold
+    }
+}
+
+impl store::Transaction<Release> {
+    /// Publish a release.
+    pub fn publish(&mut self, manifest: Manifest, signature: Signature) -> OpId {
+        self.push(Action::Publish {
+            manifest,
+            signature,
+        })
+    }
+
+    /// Co-sign a release.
+    pub fn sign(&mut self, signature: Signature) -> OpId {
+        self.push(Action::Sign { signature })
+    }
+}
+
+pub struct ReleaseMut<'a, 'g> {
+    id: ObjectId,
+    clock: clock::Lamport,
+    tips: BTreeSet<git::Oid>,
+    release: Release,
+    store: &'g mut Releases<'a>,
+}
+
+impl<'a, 'g> ReleaseMut<'a, 'g> {
+    /// Get the release id.
+    pub fn id(&self) -> &ReleaseId {
+        &self.id
+    }
+
+    /// Get the internal logical clock.
+    pub fn clock(&self) -> &clock::Lamport {
+        &self.clock
+    }
+
+    /// Co-sign the release.
+    pub fn sign<G: Signer>(&mut self, signer: &G) -> Result<OpId, Error> {
+        let signature = self
+            .release
+            .manifest
+            .as_ref()
+            .ok_or(Error::NotPublished)?
+            .sign(signer);
+
+        self.transaction("Sign", signer, |tx| tx.sign(signature))
+    }
+
+    pub fn transaction<G, F, T>(
+        &mut self,
+        message: &str,
+        signer: &G,
+        operations: F,
+    ) -> Result<T, Error>
+    where
+        G: Signer,
+        F: FnOnce(&mut Transaction<Release>) -> T,
+    {
+        let mut tx = Transaction::new(*signer.public_key(), self.clock, self.tips.clone());
+        let output = operations(&mut tx);
+        let (ops, clock, tips) = tx.commit(message, self.id, &mut self.store.raw, signer)?;
+
+        self.store.raw.apply(&mut self.release, ops)?;
+        self.clock = clock;
+        self.tips = tips;
+
+        Ok(output)
+    }
+}
+
+impl<'a, 'g> Deref for ReleaseMut<'a, 'g> {
+    type Target = Release;
+
+    fn deref(&self) -> &Self::Target {
+        &self.release
+    }
+}
+
+pub struct Releases<'a> {
+    raw: store::Store<'a, Release>,
+}
+
+impl<'a> Deref for Releases<'a> {
+    type Target = store::Store<'a, Release>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.raw
+    }
+}
+
+impl<'a> Releases<'a> {
+    /// Open a releases store.
+    pub fn open(
+        whoami: PublicKey,
+        repository: &'a storage::Repository,
+    ) -> Result<Self, store::Error> {
+        let raw = store::Store::open(whoami, repository)?;
+
+        Ok(Self { raw })
+    }
+
+    /// Get a release.
+    pub fn get(&self, id: &ObjectId) -> Result<Option<Release>, store::Error> {
+        self.raw.get(id).map(|r| r.map(|(r, _clock)| r))
+    }
+
+    /// Get a release mutably.
+    pub fn get_mut<'g>(&'g mut self, id: &ObjectId) -> Result<ReleaseMut<'a, 'g>, store::Error> {
+        let (release, clock, tips) = self
+            .raw
+            .get_with_tips(id)?
+            .ok_or_else(move || store::Error::NotFound(TYPENAME.clone(), *id))?;
+
+        Ok(ReleaseMut {
+            id: *id,
+            clock,
+            tips,
+            release,
+            store: self,
+        })
+    }
+
+    /// Publish a new release, signed by the given signer.
+    pub fn publish<'g, G: Signer>(
+        &'g mut self,
+        manifest: Manifest,
+        signer: &G,
+    ) -> Result<ReleaseMut<'a, 'g>, Error> {
+        let signature = manifest.sign(signer);
+        let (id, release, clock) =
+            Transaction::initial("Publish release", &mut self.raw, signer, |tx| {
+                tx.publish(manifest, signature);
+            })?;
+
+        Ok(ReleaseMut {
+            id,
+            clock,
+            tips: BTreeSet::from([id.into()]),
+            release,
+            store: self,
+        })
+    }
+
+    /// Remove a release.
+    pub fn remove(&self, id: &ObjectId) -> Result<(), store::Error> {
+        self.raw.remove(id)
+    }
+}
+
+/// Release operation.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Action {
+    /// Publish a release. Can only happen once per release.
+    Publish {
+        manifest: Manifest,
+        signature: Signature,
+    },
+    /// Co-sign a published release.
+    Sign { signature: Signature },
+}
+
+impl cob::op::Migrate for Action {}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::cob::Timestamp;
+    use crate::crypto::test::signer::MockSigner;
+    use crate::test;
+    use crate::test::arbitrary;
+
+    fn manifest() -> Manifest {
+        Manifest {
+            oid: arbitrary::oid(),
+            tag: Some(String::from("v1.0.0")),
+            version: String::from("1.0.0"),
+            notes: String::from("First release."),
+            artifacts: vec![Artifact {
+                name: String::from("acme-1.0.0.tar.gz"),
+                hash: String::from("sha256:fdd1d2c2a1ad6f1e9c3d2e4b6e8f4c2a"),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_release_publish_and_get() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let mut releases = Releases::open(*signer.public_key(), &project).unwrap();
+        let manifest = manifest();
+        let release = releases.publish(manifest.clone(), &signer).unwrap();
+        let id = *release.id();
+        let release = releases.get(&id).unwrap().unwrap();
+        let doc = project.identity_of(signer.public_key()).unwrap();
+
+        assert_eq!(release.manifest(), Some(&manifest));
+        assert_eq!(release.version(), "1.0.0");
+        assert_eq!(release.tag(), Some("v1.0.0"));
+        assert_eq!(release.author(), Some(releases.author()));
+        assert_eq!(
+            release.signers(&doc).collect::<Vec<_>>(),
+            vec![signer.public_key()]
+        );
+        assert!(release.is_attested(&doc));
+    }
+
+    #[test]
+    fn test_release_threshold() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (_, signer, project) = test::setup::context(&tmp);
+        let mut releases = Releases::open(*signer.public_key(), &project).unwrap();
+        let other = MockSigner::default();
+        let mut doc = project.identity_of(signer.public_key()).unwrap();
+
+        doc.delegates.push((*other.public_key()).into());
+        doc.threshold = 2;
+
+        let mut release = releases.publish(manifest(), &signer).unwrap();
+        assert!(!release.is_attested(&doc));
+
+        release.sign(&other).unwrap();
+        assert!(release.is_attested(&doc));
+        assert_eq!(release.signers(&doc).count(), 2);
+    }
+
+    #[test]
+    fn test_release_invalid_signature() {
+        let other = MockSigner::default();
+        let manifest = manifest();
+        let signature = manifest.sign(&other);
+        let author = arbitrary::gen::<ActorId>(1);
+        let op = Op::new(
+            Action::Publish {
+                manifest,
+                signature,
+            },
+            author,
+            Timestamp::default(),
+            clock::Lamport::initial(),
+        );
+
+        let release = Release::from_ops([op]).unwrap();
+        assert!(release.manifest().is_none());
+    }
+
+    #[test]
+    fn test_release_invalid_ops_skipped() {
+        let alice = MockSigner::default();
+        let bob = MockSigner::default();
+        let manifest = manifest();
+        let clock = clock::Lamport::initial();
+        let op = |action, signer: &MockSigner| {
+            Op::new(action, *signer.public_key(), Timestamp::default(), clock)
+        };
+        let publish = op(
+            Action::Publish {
+                manifest: manifest.clone(),
+                signature: manifest.sign(&alice),
+            },
+            &alice,
+        );
+        let republish = op(
+            Action::Publish {
+                manifest: Manifest {
+                    version: String::from("2.0.0"),
+                    ..manifest.clone()
+                },
+                signature: manifest.sign(&bob),
+            },
+            &bob,
+        );
+        let forged = op(
+            Action::Sign {
+                signature: manifest.sign(&alice),
+            },
+            &bob,
+        );
+        let sign = op(
+            Action::Sign {
+                signature: manifest.sign(&bob),
+            },
+            &bob,
+        );
+        let release = Release::from_ops([publish, republish, forged, sign]).unwrap();
+
+        assert_eq!(release.manifest(), Some(&manifest));
+        assert_eq!(release.signatures().count(), 2);
+        assert_eq!(
+            release.signatures.get(bob.public_key()),
+            Some(&manifest.sign(&bob))
+        );
+    }
+}
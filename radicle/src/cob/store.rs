@@ -1,22 +1,26 @@
 //! Generic COB storage.
 #![allow(clippy::large_enum_variant)]
 #![allow(clippy::type_complexity)]
+use std::collections::{BTreeMap, BTreeSet};
 use std::marker::PhantomData;
 use std::ops::ControlFlow;
 
 use nonempty::NonEmpty;
+use radicle_cob::object::Storage as _;
 use radicle_crdt::Lamport;
-use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
 use crate::cob;
-use crate::cob::common::Author;
-use crate::cob::op::{Op, OpId, Ops};
+use crate::cob::cache::{self, Cached, FileCache};
+use crate::cob::common::{Author, Reference, Timestamp};
+use crate::cob::op::{Migrate, Op, OpEncodingError, OpId, Ops, Snapshot};
 use crate::cob::CollaborativeObject;
-use crate::cob::{ActorId, Create, History, ObjectId, TypeName, Update};
-use crate::crypto::PublicKey;
+use crate::cob::{ActorId, Create, Encoding, History, ObjectId, Squash, TypeName, Update};
+use crate::crypto::{PublicKey, Verified};
 use crate::git;
 use crate::identity;
-use crate::identity::Identity;
+use crate::identity::{Doc, Identity};
 use crate::prelude::*;
 use crate::storage::git as storage;
 
@@ -27,30 +31,48 @@ pub const HISTORY_TYPE: &str = "radicle";
 /// All collaborative objects implement this trait.
 pub trait FromHistory: Sized + Default {
     /// The underlying action composing each operation.
-    type Action: for<'de> Deserialize<'de>;
+    type Action: Migrate;
     /// Error returned by `apply` function.
     type Error: std::error::Error;
+    /// The state of an object, eg. whether it is open or closed, by which objects
+    /// can be filtered. See [`Filter::state`].
+    type State;
 
     /// The object type name.
     fn type_name() -> &'static TypeName;
 
+    /// Get the state of the object.
+    fn state(&self) -> Self::State;
+
     /// Apply a list of operations to the state.
     fn apply(&mut self, ops: impl IntoIterator<Item = Op<Self::Action>>)
         -> Result<(), Self::Error>;
 
+    /// Check whether an operation is authorized, given the identity document of the
+    /// repository. This is called with the state the operation's change is applied to,
+    /// when an object is loaded from a [`Store`]. Unauthorized operations are skipped,
+    /// eg. so that only delegates can perform privileged actions.
+    ///
+    /// By default, all operations are authorized.
+    fn authorize(&self, op: &Op<Self::Action>, doc: &Doc<Verified>) -> bool {
+        let _ = (op, doc);
+        true
+    }
+
+    /// Other objects referenced by this object, see [`Store::referencing`].
+    ///
+    /// By default, objects don't reference other objects.
+    fn references(&self) -> Vec<Reference> {
+        Vec::new()
+    }
+
     /// Create an object from a history.
+    /// Operations are not authorized, see [`FromHistory::authorize`].
+    ///
+    /// Since there is no identity document to check the signer of a squashed history
+    /// against, snapshots with operations by other authors than their signer are rejected.
     fn from_history(history: &History) -> Result<(Self, Lamport), Error> {
-        let obj = history.traverse(Self::default(), |mut acc, entry| {
-            if let Ok(Ops(ops)) = Ops::try_from(entry) {
-                if let Err(err) = acc.apply(ops) {
-                    log::warn!("Error applying op to `{}` state: {err}", Self::type_name());
-                    return ControlFlow::Break(acc);
-                }
-            } else {
-                return ControlFlow::Break(acc);
-            }
-            ControlFlow::Continue(acc)
-        });
+        let obj = replay(history, None, |_| {});
 
         Ok((obj, history.clock().into()))
     }
@@ -65,6 +87,170 @@ pub trait FromHistory: Sized + Default {
     }
 }
 
+/// Replay a history onto the default state, calling `f` with every batch of operations
+/// that is successfully applied. If the root entry is a snapshot, it is decoded as
+/// a [`Snapshot`]. Other snapshots repeat operations that are already in the history,
+/// and are skipped. If an identity document is given, operations that aren't authorized
+/// by it are skipped.
+///
+/// The authors of snapshot operations are not signed, only the snapshot as a whole is.
+/// A snapshot with operations by other authors than its signer is therefore only
+/// accepted if it was signed by a delegate or one of their devices, and otherwise yields
+/// the default state.
+fn replay<T: FromHistory>(
+    history: &History,
+    doc: Option<&Doc<Verified>>,
+    mut f: impl FnMut(&NonEmpty<Op<T::Action>>),
+) -> T {
+    let mut root = true;
+
+    history.traverse(T::default(), |mut acc, entry| {
+        let root = std::mem::take(&mut root);
+
+        let ops = if root && entry.is_snapshot() {
+            let signer = entry.actor();
+            let trusted = doc.map_or(false, |doc| doc.is_authorized(signer));
+
+            match Snapshot::try_from(entry) {
+                Ok(Snapshot(ops)) if !trusted && ops.iter().any(|op| op.author != *signer) => {
+                    log::warn!(
+                        "Rejecting `{}` snapshot {} by {} with operations by other authors",
+                        T::type_name(),
+                        git::Oid::from(*entry.id()),
+                        signer
+                    );
+                    return ControlFlow::Break(acc);
+                }
+                result => result.map(|Snapshot(ops)| ops),
+            }
+        } else if entry.is_snapshot() {
+            return ControlFlow::Continue(acc);
+        } else {
+            Ops::try_from(entry).map(|Ops(ops)| ops)
+        };
+        let ops = match ops {
+            Ok(ops) => ops,
+            // Skip changes written by newer versions of this type, which we can't decode.
+            Err(OpEncodingError::UnsupportedVersion(version)) => {
+                log::warn!(
+                    "Skipping `{}` change {} with unsupported version {version}",
+                    T::type_name(),
+                    git::Oid::from(*entry.id())
+                );
+                return ControlFlow::Continue(acc);
+            }
+            Err(_) => return ControlFlow::Break(acc),
+        };
+        let ops = match doc {
+            Some(doc) => match NonEmpty::from_vec(authorized(&acc, ops, doc)) {
+                Some(ops) => ops,
+                None => return ControlFlow::Continue(acc),
+            },
+            None => ops,
+        };
+        f(&ops);
+
+        if let Err(err) = acc.apply(ops) {
+            log::warn!("Error applying op to `{}` state: {err}", T::type_name());
+            return ControlFlow::Break(acc);
+        }
+        ControlFlow::Continue(acc)
+    })
+}
+
+/// Keep the operations that are authorized by the given identity document, when applied
+/// to the given state. Operations are all checked against the state before any of them
+/// is applied.
+fn authorized<T: FromHistory>(
+    state: &T,
+    ops: impl IntoIterator<Item = Op<T::Action>>,
+    doc: &Doc<Verified>,
+) -> Vec<Op<T::Action>> {
+    let (authorized, unauthorized): (Vec<_>, Vec<_>) =
+        ops.into_iter().partition(|op| state.authorize(op, doc));
+
+    for op in unauthorized {
+        log::warn!(
+            "Skipping unauthorized `{}` op {} by {}",
+            T::type_name(),
+            op.id(),
+            op.author
+        );
+    }
+    authorized
+}
+
+/// Filter applied when listing objects, see [`Store::filter`].
+///
+/// Objects are filtered by author and creation time based on the root of their
+/// history. For squashed objects, this is the snapshot that replaced their history.
+pub struct Filter<T: FromHistory> {
+    author: Option<ActorId>,
+    since: Option<Timestamp>,
+    until: Option<Timestamp>,
+    state: Option<Box<dyn Fn(&T::State) -> bool>>,
+    reference: Option<Reference>,
+}
+
+impl<T: FromHistory> Default for Filter<T> {
+    fn default() -> Self {
+        Self {
+            author: None,
+            since: None,
+            until: None,
+            state: None,
+            reference: None,
+        }
+    }
+}
+
+impl<T: FromHistory> Filter<T> {
+    /// Only keep objects created by the given author.
+    pub fn author(mut self, author: ActorId) -> Self {
+        self.author = Some(author);
+        self
+    }
+
+    /// Only keep objects created at or after the given time.
+    pub fn since(mut self, since: Timestamp) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// Only keep objects created before the given time.
+    pub fn until(mut self, until: Timestamp) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Only keep objects whose state matches the predicate.
+    pub fn state(mut self, predicate: impl Fn(&T::State) -> bool + 'static) -> Self {
+        self.state = Some(Box::new(predicate));
+        self
+    }
+
+    /// Only keep objects referencing the given object.
+    pub fn references(mut self, reference: Reference) -> Self {
+        self.reference = Some(reference);
+        self
+    }
+
+    /// Check whether a materialized object matches the filter.
+    fn matches(&self, object: &Cached<T>) -> bool {
+        self.author.map_or(true, |a| object.author == a)
+            && self.since.map_or(true, |t| object.created >= t)
+            && self.until.map_or(true, |t| object.created < t)
+            && self
+                .state
+                .as_ref()
+                .map_or(true, |predicate| predicate(&object.state.state()))
+            && self
+                .reference
+                .as_ref()
+                .map_or(true, |r| object.state.references().contains(r))
+    }
+}
+
 /// Store error.
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -76,6 +262,8 @@ pub enum Error {
     Retrieve(#[from] cob::error::Retrieve),
     #[error("remove error: {0}")]
     Remove(#[from] cob::error::Remove),
+    #[error("squash error: {0}")]
+    Squash(#[from] cob::error::Squash),
     #[error(transparent)]
     Identity(#[from] identity::IdentityError),
     #[error(transparent)]
@@ -84,6 +272,18 @@ pub enum Error {
     HistoryType(String),
     #[error("object `{1}` of type `{0}` was not found")]
     NotFound(TypeName, ObjectId),
+    #[error("snapshot of object `{0}` does not match its history")]
+    SnapshotMismatch(ObjectId),
+    #[error(
+        "snapshot of object `{0}` has operations by other authors, only a delegate may sign it"
+    )]
+    SnapshotUnauthorized(ObjectId),
+    #[error("object `{0}` is encrypted")]
+    Encrypted(ObjectId),
+    #[error("object `{0}` was modified concurrently")]
+    Conflict(ObjectId),
+    #[error("git: {0}")]
+    Git(#[from] git::raw::Error),
 }
 
 /// Storage for collaborative objects of a specific type `T` in a single repository.
@@ -91,6 +291,7 @@ pub struct Store<'a, T> {
     whoami: PublicKey,
     identity: Identity<git::Oid>,
     raw: &'a storage::Repository,
+    cache: FileCache,
     witness: PhantomData<T>,
 }
 
@@ -104,11 +305,13 @@ impl<'a, T> Store<'a, T> {
     /// Open a new generic store.
     pub fn open(whoami: PublicKey, store: &'a storage::Repository) -> Result<Self, Error> {
         let identity = Identity::load(&whoami, store)?;
+        let cache = FileCache::new(store.backend.path().join(storage::cob::COB_CACHE_DIR));
 
         Ok(Self {
             identity,
             whoami,
             raw: store,
+            cache,
             witness: PhantomData,
         })
     }
@@ -126,8 +329,22 @@ impl<'a, T> Store<'a, T> {
 
 impl<'a, T: FromHistory> Store<'a, T>
 where
+    T: Serialize + DeserializeOwned,
     T::Action: Serialize,
 {
+    /// Apply operations to an in-memory object, eg. after committing a [`Transaction`].
+    /// Operations that aren't authorized by the identity document are skipped, as when
+    /// the object is loaded, so that the in-memory object matches the stored one.
+    pub fn apply(
+        &self,
+        state: &mut T,
+        ops: impl IntoIterator<Item = Op<T::Action>>,
+    ) -> Result<(), T::Error> {
+        let ops = authorized(state, ops, &self.identity.doc);
+
+        state.apply(ops)
+    }
+
     /// Update an object.
     pub fn update<G: Signer>(
         &self,
@@ -135,6 +352,21 @@ where
         message: &str,
         actions: impl Into<NonEmpty<T::Action>>,
         signer: &G,
+    ) -> Result<CollaborativeObject, Error> {
+        self.update_with(object_id, None, message, actions, BTreeMap::new(), signer)
+    }
+
+    /// Update an object, attaching the given files to the change. If `tips` are given,
+    /// the update fails with [`Error::Conflict`] if the object's tips changed since they
+    /// were read.
+    fn update_with<G: Signer>(
+        &self,
+        object_id: ObjectId,
+        tips: Option<BTreeSet<git::Oid>>,
+        message: &str,
+        actions: impl Into<NonEmpty<T::Action>>,
+        attachments: BTreeMap<String, Vec<u8>>,
+        signer: &G,
     ) -> Result<CollaborativeObject, Error> {
         let changes = actions.into().try_map(|e| encoding::encode(&e))?;
 
@@ -146,12 +378,19 @@ where
             Update {
                 object_id,
                 history_type: HISTORY_TYPE.to_owned(),
+                version: T::Action::VERSION,
+                encoding: Encoding::Json,
                 typename: T::type_name().clone(),
                 message: message.to_owned(),
                 changes,
+                attachments,
+                expected_tips: tips,
             },
         )
-        .map_err(Error::from)
+        .map_err(|err| match err {
+            cob::error::Update::Conflict { .. } => Error::Conflict(object_id),
+            err => Error::from(err),
+        })
     }
 
     /// Create an object.
@@ -160,6 +399,17 @@ where
         message: &str,
         actions: impl Into<NonEmpty<T::Action>>,
         signer: &G,
+    ) -> Result<(ObjectId, T, Lamport), Error> {
+        self.create_with(message, actions, BTreeMap::new(), signer)
+    }
+
+    /// Create an object, attaching the given files to its initial change.
+    fn create_with<G: Signer>(
+        &self,
+        message: &str,
+        actions: impl Into<NonEmpty<T::Action>>,
+        attachments: BTreeMap<String, Vec<u8>>,
+        signer: &G,
     ) -> Result<(ObjectId, T, Lamport), Error> {
         let contents = actions.into().try_map(|e| encoding::encode(&e))?;
         let cob = cob::create(
@@ -169,42 +419,251 @@ where
             signer.public_key(),
             Create {
                 history_type: HISTORY_TYPE.to_owned(),
+                version: T::Action::VERSION,
+                encoding: Encoding::Json,
                 typename: T::type_name().clone(),
                 message: message.to_owned(),
                 contents,
+                encryption: None,
+                attachments,
             },
         )?;
-        let (object, clock) = T::from_history(cob.history())?;
+        let (object, clock) = Self::materialize(&cob, &self.identity.doc)?;
 
         Ok((*cob.id(), object, clock))
     }
 
     /// Get an object.
     pub fn get(&self, id: &ObjectId) -> Result<Option<(T, Lamport)>, Error> {
-        let cob = cob::get(self.raw, T::type_name(), id)?;
+        let object = self.load(id)?;
 
-        if let Some(cob) = cob {
-            if cob.manifest().history_type != HISTORY_TYPE {
-                return Err(Error::HistoryType(cob.manifest().history_type.clone()));
-            }
-            let (obj, clock) = T::from_history(cob.history())?;
+        Ok(object.map(|o| (o.state, o.clock)))
+    }
 
-            Ok(Some((obj, clock)))
-        } else {
-            Ok(None)
-        }
+    /// Get an object, along with the tips of its history. The tips identify the
+    /// state the object was read in, see [`Transaction::new`].
+    pub fn get_with_tips(
+        &self,
+        id: &ObjectId,
+    ) -> Result<Option<(T, Lamport, BTreeSet<git::Oid>)>, Error> {
+        let object = self.load(id)?;
+
+        Ok(object.map(|o| (o.state, o.clock, o.tips)))
+    }
+
+    /// Get the changes of an object in pages of the given size, latest first. Changes
+    /// are read from storage as pages are requested, so that the latest changes of an
+    /// object can be read without loading its whole history. See [`History::iter_ops`].
+    ///
+    /// Nb. Unlike [`Store::get`], changes aren't checked against the identity document.
+    pub fn pages(
+        &self,
+        id: &ObjectId,
+        size: usize,
+    ) -> Result<impl Iterator<Item = Vec<cob::Entry>> + 'a, Error> {
+        let ops = History::iter_ops(self.raw, T::type_name(), id)?;
+
+        Ok(ops.pages(size))
     }
 
     /// Return all objects.
     pub fn all(
         &self,
     ) -> Result<impl Iterator<Item = Result<(ObjectId, T, Lamport), Error>>, Error> {
-        let raw = cob::list(self.raw, T::type_name())?;
+        self.filter(Filter::default())
+    }
 
-        Ok(raw.into_iter().map(|o| {
-            let (obj, clock) = T::from_history(o.history())?;
-            Ok((*o.id(), obj, clock))
-        }))
+    /// Return the objects matching the given filter.
+    pub fn filter(
+        &self,
+        filter: Filter<T>,
+    ) -> Result<impl Iterator<Item = Result<(ObjectId, T, Lamport), Error>>, Error> {
+        let objects = self
+            .raw
+            .types(T::type_name())
+            .map_err(|err| cob::error::Retrieve::Refs { err: Box::new(err) })?;
+        let mut result = Vec::new();
+
+        for (id, refs) in objects {
+            match self.load_from(&id, &refs) {
+                Ok(Some(object)) if filter.matches(&object) => {
+                    result.push(Ok((id, object.state, object.clock)));
+                }
+                Ok(_) => {}
+                Err(err) => result.push(Err(err)),
+            }
+        }
+        Ok(result.into_iter())
+    }
+
+    /// Return the objects referencing the given object, eg. the patches referencing an
+    /// issue. See [`FromHistory::references`].
+    pub fn referencing(
+        &self,
+        typename: &TypeName,
+        id: &ObjectId,
+    ) -> Result<impl Iterator<Item = Result<(ObjectId, T, Lamport), Error>>, Error> {
+        self.filter(Filter::default().references(Reference::new(typename.clone(), *id)))
+    }
+
+    /// Squash the history of an object into a single snapshot change, signed by `signer`.
+    /// See [`Store::squash_with`].
+    pub fn squash<G: Signer>(
+        &self,
+        id: &ObjectId,
+        message: &str,
+        signer: &G,
+    ) -> Result<(T, Lamport), Error>
+    where
+        T: PartialEq,
+    {
+        self.squash_with(id, None, message, signer)
+    }
+
+    /// Squash the history of an object up to the given tips, or up to its current tips
+    /// if none are given, into a single snapshot change, signed by `signer`.
+    ///
+    /// The snapshot is verified to replay to the same state as the squashed history
+    /// before it is stored. Histories with operations by other authors than `signer`
+    /// can only be squashed by a delegate or one of their devices. See [`cob::squash`] for
+    /// caveats.
+    pub fn squash_with<G: Signer>(
+        &self,
+        id: &ObjectId,
+        tips: Option<BTreeSet<git::Oid>>,
+        message: &str,
+        signer: &G,
+    ) -> Result<(T, Lamport), Error>
+    where
+        T: PartialEq,
+    {
+        let cob = cob::get(self.raw, T::type_name(), id)?
+            .ok_or_else(|| Error::NotFound(T::type_name().clone(), *id))?;
+        if cob.manifest().history_type != HISTORY_TYPE {
+            return Err(Error::HistoryType(cob.manifest().history_type.clone()));
+        }
+        let mut history = cob.history().clone();
+        let tips = match tips {
+            Some(tips) => {
+                history.truncate(&tips);
+                tips
+            }
+            None => history.tips(),
+        };
+        let mut ops = Vec::new();
+        let mut foreign = false;
+        let expected: T = replay(&history, Some(&self.identity.doc), |batch| {
+            foreign |= batch.iter().any(|op| op.author != *signer.public_key());
+            ops.extend(batch.iter().map(encoding::encode));
+        });
+        if foreign && !self.identity.doc.is_authorized(signer.public_key()) {
+            return Err(Error::SnapshotUnauthorized(*id));
+        }
+        let ops = ops.into_iter().collect::<Result<Vec<_>, _>>()?;
+        let contents = NonEmpty::from_vec(ops).ok_or(Error::SnapshotMismatch(*id))?;
+
+        // Make sure the snapshot replays to the same state as the history it replaces.
+        let replayed = contents
+            .iter()
+            .map(|op| serde_json::from_slice::<Op<T::Action>>(op))
+            .collect::<Result<Vec<_>, _>>()?;
+        if T::from_ops(replayed).ok().as_ref() != Some(&expected) {
+            return Err(Error::SnapshotMismatch(*id));
+        }
+
+        let cob = cob::squash(
+            self.raw,
+            signer,
+            &self.identity,
+            signer.public_key(),
+            Squash {
+                object_id: *id,
+                history_type: HISTORY_TYPE.to_owned(),
+                version: T::Action::VERSION,
+                encoding: Encoding::Json,
+                typename: T::type_name().clone(),
+                message: message.to_owned(),
+                snapshot: contents,
+                tips,
+            },
+        )?;
+
+        Self::materialize(&cob, &self.identity.doc)
+    }
+
+    /// Load an object, see [`Store::load_from`].
+    fn load(&self, id: &ObjectId) -> Result<Option<Cached<T>>, Error> {
+        let refs = self
+            .raw
+            .objects(T::type_name(), id)
+            .map_err(|err| cob::error::Retrieve::Refs { err: Box::new(err) })?;
+
+        self.load_from(id, &refs)
+    }
+
+    /// Load an object from the references to its tips. The object is read from the
+    /// cache if none of its tips, the identity document or the revocations changed since
+    /// it was cached, and is otherwise materialized and cached. See [`cache`].
+    fn load_from(
+        &self,
+        id: &ObjectId,
+        refs: &radicle_cob::object::Objects,
+    ) -> Result<Option<Cached<T>>, Error> {
+        let tips = refs
+            .iter()
+            .map(|r| r.target.id)
+            .collect::<BTreeSet<git::Oid>>();
+        if tips.is_empty() {
+            return Ok(None);
+        }
+        let revocations = radicle_cob::Store::revocations(self.raw);
+        let key = cache::key(&tips, &self.identity.current, &revocations);
+
+        if let Some(object) = self.cache.get(T::type_name(), id, &key) {
+            return Ok(Some(object));
+        }
+        let Some(cob) = cob::get(self.raw, T::type_name(), id)? else {
+            return Ok(None);
+        };
+        let Some(root) = cob.history().root() else {
+            return Ok(None);
+        };
+        let (state, clock) = Self::materialize(&cob, &self.identity.doc)?;
+        let object = Cached {
+            key,
+            author: *root.actor(),
+            created: Timestamp::from(root.timestamp()),
+            tips: cob.history().tips(),
+            state,
+            clock,
+        };
+        self.cache.insert(T::type_name(), id, &object);
+
+        Ok(Some(object))
+    }
+
+    /// Materialize an object from its history, according to its history type.
+    /// Operations are authorized against the identity document of the repository.
+    fn materialize(cob: &CollaborativeObject, doc: &Doc<Verified>) -> Result<(T, Lamport), Error> {
+        if cob.manifest().encryption.is_some() {
+            return Err(Error::Encrypted(*cob.id()));
+        }
+        if cob.manifest().history_type != HISTORY_TYPE {
+            return Err(Error::HistoryType(cob.manifest().history_type.clone()));
+        }
+        let obj = replay(cob.history(), Some(doc), |_| {});
+
+        Ok((obj, cob.history().clock().into()))
+    }
+
+    /// Get the contents of a file attached to a change, see [`Transaction::attach`].
+    ///
+    /// Nb. Any blob of the repository can be read this way. Objects should check that
+    /// the blob is one of their attachments, eg. with [`cob::thread::Thread::attachment`].
+    pub fn attachment(&self, oid: &git::Oid) -> Result<Vec<u8>, Error> {
+        let blob = self.raw.backend.find_blob((*oid).into())?;
+
+        Ok(blob.content().to_owned())
     }
 
     /// Return objects count.
@@ -216,7 +675,10 @@ where
 
     /// Remove an object.
     pub fn remove(&self, id: &ObjectId) -> Result<(), Error> {
-        cob::remove(self.raw, &self.whoami, T::type_name(), id).map_err(Error::from)
+        cob::remove(self.raw, &self.whoami, T::type_name(), id)?;
+        self.cache.remove(T::type_name(), id);
+
+        Ok(())
     }
 }
 
@@ -226,19 +688,24 @@ pub struct Transaction<T: FromHistory> {
     actor: ActorId,
     start: Lamport,
     clock: Lamport,
+    tips: BTreeSet<git::Oid>,
     actions: Vec<T::Action>,
+    attachments: BTreeMap<String, Vec<u8>>,
 }
 
 impl<T: FromHistory> Transaction<T> {
-    /// Create a new transaction.
-    pub fn new(actor: ActorId, clock: Lamport) -> Self {
+    /// Create a new transaction, on top of an object read at the given clock and tips.
+    /// The transaction can only be committed if the object's tips haven't changed since.
+    pub fn new(actor: ActorId, clock: Lamport, tips: BTreeSet<git::Oid>) -> Self {
         let start = clock;
 
         Self {
             actor,
             start,
             clock,
+            tips,
             actions: Vec::new(),
+            attachments: BTreeMap::new(),
         }
     }
 
@@ -259,13 +726,15 @@ impl<T: FromHistory> Transaction<T> {
             actor,
             start: Lamport::initial(),
             clock: Lamport::initial(),
+            tips: BTreeSet::new(),
             actions: Vec::new(),
+            attachments: BTreeMap::new(),
         };
         operations(&mut tx);
 
         let actions = NonEmpty::from_vec(tx.actions)
             .expect("Transaction::initial: transaction must contain at least one operation");
-        let (id, cob, clock) = store.create(message, actions, signer)?;
+        let (id, cob, clock) = store.create_with(message, actions, tx.attachments, signer)?;
 
         // The history clock should be in sync with the tx clock.
         assert_eq!(clock, tx.clock);
@@ -279,22 +748,45 @@ impl<T: FromHistory> Transaction<T> {
         OpId::new(self.clock.tick(), self.actor)
     }
 
+    /// Attach a file to the change this transaction is committed as. Returns the
+    /// blob the file will be stored as, for operations to refer to.
+    ///
+    /// Files attached under the same name replace each other. Attachments are limited
+    /// in size, see [`cob::attachment::MAX_SIZE`].
+    pub fn attach(&mut self, name: impl ToString, content: Vec<u8>) -> git::Oid {
+        let oid = git::raw::Oid::hash_object(git::raw::ObjectType::Blob, &content)
+            .expect("Transaction::attach: blob hashing doesn't fail");
+
+        self.attachments.insert(name.to_string(), content);
+        oid.into()
+    }
+
     /// Commit transaction.
     ///
-    /// Returns a list of operations that can be applied onto an in-memory CRDT.
+    /// Returns a list of operations that can be applied onto an in-memory CRDT, along
+    /// with the new clock and tips of the object. Fails with [`Error::Conflict`] if the
+    /// object was modified since the transaction was created, in which case the
+    /// object should be read again and the transaction retried.
     pub fn commit<G: Signer>(
         self,
         msg: &str,
         id: ObjectId,
         store: &mut Store<T>,
         signer: &G,
-    ) -> Result<(Vec<cob::Op<T::Action>>, Lamport), Error>
+    ) -> Result<(Vec<cob::Op<T::Action>>, Lamport, BTreeSet<git::Oid>), Error>
     where
         T::Action: Serialize + Clone,
     {
         let actions = NonEmpty::from_vec(self.actions)
             .expect("Transaction::commit: transaction must not be empty");
-        let cob = store.update(id, msg, actions.clone(), signer)?;
+        let cob = store.update_with(
+            id,
+            Some(self.tips),
+            msg,
+            actions.clone(),
+            self.attachments,
+            signer,
+        )?;
         let author = self.actor;
         let timestamp = cob.history().timestamp().into();
 
@@ -313,14 +805,15 @@ impl<T: FromHistory> Transaction<T> {
             })
             .collect();
 
-        Ok((ops, clock))
+        Ok((ops, clock, cob.history().tips()))
     }
 }
 
 pub mod encoding {
     use serde::Serialize;
 
-    /// Serialize the change into a byte string.
+    /// Serialize the change into a byte string, as canonical JSON. Changes are written
+    /// with [`super::Encoding::Json`].
     pub fn encode<T: Serialize>(obj: &T) -> Result<Vec<u8>, serde_json::Error> {
         let mut buf = Vec::new();
         let mut serializer =
@@ -8,12 +8,13 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::cob;
-use crate::cob::common::{Reaction, Timestamp};
-use crate::cob::{ActorId, Op, OpId};
+use crate::cob::common::{Mention, Reaction, Reference, Timestamp};
+use crate::cob::{ActorId, ObjectId, Op, OpId, TypeName};
 use crate::crypto::Signer;
+use crate::git;
 
 use crdt::clock::Lamport;
-use crdt::{GMap, LWWSet, Max, Redactable, Semilattice};
+use crdt::{GMap, GSet, LWWSet, Max, Redactable, Semilattice, VecMap};
 
 /// Type name of a thread, as well as the domain for all thread operations.
 /// Note that threads are not usually used standalone. They are embeded into other COBs.
@@ -38,7 +39,7 @@ pub enum OpError {
 pub type CommentId = OpId;
 
 /// A comment edit is just some text and an edit time.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Edit {
     /// When the edit was made.
     pub timestamp: Timestamp,
@@ -47,7 +48,7 @@ pub struct Edit {
 }
 
 /// A comment on a discussion thread.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Comment {
     /// Comment author.
     author: ActorId,
@@ -97,6 +98,11 @@ impl Comment {
             .timestamp
     }
 
+    /// Return the users mentioned in the comment body.
+    pub fn mentions(&self) -> Vec<Mention> {
+        Mention::parse(self.body())
+    }
+
     /// Return the comment author.
     pub fn author(&self) -> ActorId {
         self.author
@@ -112,6 +118,11 @@ impl Comment {
         self.edits.values().map(Max::get)
     }
 
+    /// Whether the comment was edited since it was created.
+    pub fn is_edited(&self) -> bool {
+        self.edits.len() > 1
+    }
+
     /// Add an edit.
     pub fn edit(&mut self, clock: Lamport, body: String, timestamp: Timestamp) {
         self.edits.insert(clock, Edit { body, timestamp }.into())
@@ -128,6 +139,17 @@ impl PartialOrd for Comment {
     }
 }
 
+/// A file attached to a comment. The file contents are stored as a blob alongside the
+/// change that attached it, see [`cob::store::Transaction::attach`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    /// File name.
+    pub name: String,
+    /// Blob holding the file contents.
+    pub oid: git::Oid,
+}
+
 /// An action that can be carried out in a change.
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
@@ -151,8 +173,20 @@ pub enum Action {
         reaction: Reaction,
         active: bool,
     },
+    /// Reference another collaborative object from the thread.
+    Reference {
+        typename: TypeName,
+        object_id: ObjectId,
+    },
+    /// Attach files to a comment.
+    Attach {
+        to: CommentId,
+        attachments: Vec<Attachment>,
+    },
 }
 
+impl cob::op::Migrate for Action {}
+
 impl From<Action> for nonempty::NonEmpty<Action> {
     fn from(action: Action) -> Self {
         Self::new(action)
@@ -160,18 +194,24 @@ impl From<Action> for nonempty::NonEmpty<Action> {
 }
 
 /// A discussion thread.
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Thread {
     /// The comments under the thread.
     comments: GMap<CommentId, Redactable<Comment>>,
     /// Reactions to changes.
-    reactions: GMap<CommentId, LWWSet<(ActorId, Reaction), Lamport>>,
+    reactions: VecMap<CommentId, LWWSet<(ActorId, Reaction), Lamport>>,
+    /// Other objects referenced from the thread.
+    references: GSet<Reference>,
+    /// Files attached to comments.
+    attachments: GMap<CommentId, GSet<Attachment>>,
 }
 
 impl Semilattice for Thread {
     fn merge(&mut self, other: Self) {
         self.comments.merge(other.comments);
         self.reactions.merge(other.reactions);
+        self.references.merge(other.references);
+        self.attachments.merge(other.attachments);
     }
 }
 
@@ -179,7 +219,9 @@ impl Thread {
     pub fn new(id: CommentId, comment: Comment) -> Self {
         Self {
             comments: GMap::singleton(id, Redactable::Present(comment)),
-            reactions: GMap::default(),
+            reactions: VecMap::default(),
+            references: GSet::default(),
+            attachments: GMap::default(),
         }
     }
 
@@ -235,6 +277,28 @@ impl Thread {
             .map(|(a, r)| (a, r))
     }
 
+    /// Other objects referenced from the thread.
+    pub fn references(&self) -> impl Iterator<Item = &Reference> {
+        self.references.iter()
+    }
+
+    /// Files attached to a comment.
+    pub fn attachments<'a>(&'a self, to: &'a CommentId) -> impl Iterator<Item = &Attachment> {
+        self.attachments
+            .get(to)
+            .into_iter()
+            .flat_map(|attachments| attachments.iter())
+    }
+
+    /// Find an attachment of any comment in the thread.
+    pub fn attachment(&self, oid: &git::Oid) -> Option<&Attachment> {
+        self.attachments
+            .iter()
+            .filter(|(id, _)| self.comment(id).is_some())
+            .flat_map(|(_, attachments)| attachments.iter())
+            .find(|a| a.oid == *oid)
+    }
+
     pub fn comments(&self) -> impl Iterator<Item = (&CommentId, &Comment)> + '_ {
         self.comments.iter().filter_map(|(id, comment)| {
             if let Redactable::Present(c) = comment {
@@ -249,11 +313,18 @@ impl Thread {
 impl cob::store::FromHistory for Thread {
     type Action = Action;
     type Error = OpError;
+    type State = ();
 
     fn type_name() -> &'static radicle_cob::TypeName {
         &*TYPENAME
     }
 
+    fn state(&self) {}
+
+    fn references(&self) -> Vec<Reference> {
+        self.references.iter().cloned().collect()
+    }
+
     fn apply(&mut self, ops: impl IntoIterator<Item = Op<Action>>) -> Result<(), OpError> {
         for op in ops.into_iter() {
             let id = op.id();
@@ -267,16 +338,27 @@ impl cob::store::FromHistory for Thread {
                         Redactable::Present(Comment::new(author, body, reply_to, timestamp)),
                     );
                 }
-                Action::Edit { id, body } => {
-                    if let Some(Redactable::Present(comment)) = self.comments.get_mut(&id) {
-                        comment.edit(op.clock, body, timestamp);
-                    } else {
-                        return Err(OpError::Missing(id));
+                // Comments can only be edited and redacted by their author. Edits and
+                // redactions of redacted comments are ignored, and of unknown comments
+                // are errors, since the comment would otherwise never be shown.
+                Action::Edit { id, body } => match self.comments.get_mut(&id) {
+                    Some(Redactable::Present(comment)) => {
+                        if comment.author == author {
+                            comment.edit(op.clock, body, timestamp);
+                        }
                     }
-                }
-                Action::Redact { id } => {
-                    self.comments.insert(id, Redactable::Redacted);
-                }
+                    Some(Redactable::Redacted) => {}
+                    None => return Err(OpError::Missing(id)),
+                },
+                Action::Redact { id } => match self.comments.get(&id) {
+                    Some(Redactable::Present(comment)) => {
+                        if comment.author == author {
+                            self.comments.insert(id, Redactable::Redacted);
+                        }
+                    }
+                    Some(Redactable::Redacted) => {}
+                    None => return Err(OpError::Missing(id)),
+                },
                 Action::React {
                     to,
                     reaction,
@@ -292,6 +374,22 @@ impl cob::store::FromHistory for Thread {
                     };
                     self.reactions.insert(to, reactions);
                 }
+                Action::Reference {
+                    typename,
+                    object_id,
+                } => {
+                    self.references.insert(Reference::new(typename, object_id));
+                }
+                // Files can only be attached to a comment by its author.
+                Action::Attach { to, attachments } => match self.comments.get(&to) {
+                    Some(Redactable::Present(comment)) => {
+                        if comment.author == author {
+                            self.attachments.insert(to, GSet::from_iter(attachments));
+                        }
+                    }
+                    Some(Redactable::Redacted) => {}
+                    None => return Err(OpError::Missing(to)),
+                },
             }
         }
         Ok(())
@@ -361,120 +459,16 @@ impl<G> DerefMut for Actor<G> {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::BTreeSet;
-    use std::{array, iter};
-
     use nonempty::NonEmpty;
     use pretty_assertions::assert_eq;
-    use qcheck::{Arbitrary, TestResult};
-
-    use crdt::test::{assert_laws, WeightedGenerator};
+    use qcheck::TestResult;
 
     use super::*;
     use crate as radicle;
     use crate::cob::store::FromHistory;
     use crate::cob::test;
     use crate::crypto::test::signer::MockSigner;
-
-    #[derive(Clone)]
-    struct Changes<const N: usize> {
-        permutations: [Vec<Op<Action>>; N],
-    }
-
-    impl<const N: usize> std::fmt::Debug for Changes<N> {
-        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            for (i, p) in self.permutations.iter().enumerate() {
-                writeln!(
-                    f,
-                    "{i}: {:#?}",
-                    p.iter().map(|c| &c.action).collect::<Vec<_>>()
-                )?;
-            }
-            Ok(())
-        }
-    }
-
-    impl<const N: usize> Arbitrary for Changes<N> {
-        fn arbitrary(g: &mut qcheck::Gen) -> Self {
-            let author = ActorId::from([0; 32]);
-            let rng = fastrand::Rng::with_seed(u64::arbitrary(g));
-            let root = OpId::initial(author);
-            let gen =
-                WeightedGenerator::<(Lamport, Action), (Lamport, BTreeSet<OpId>)>::new(rng.clone())
-                    .variant(3, |(clock, comments), rng| {
-                        comments.insert(OpId::new(clock.tick(), author));
-
-                        Some((
-                            *clock,
-                            Action::Comment {
-                                body: iter::repeat_with(|| rng.alphabetic()).take(16).collect(),
-                                reply_to: Some(root),
-                            },
-                        ))
-                    })
-                    .variant(2, |(clock, comments), rng| {
-                        if comments.is_empty() {
-                            return None;
-                        }
-                        let id = *comments.iter().nth(rng.usize(..comments.len())).unwrap();
-
-                        Some((
-                            *clock,
-                            Action::Edit {
-                                id,
-                                body: iter::repeat_with(|| rng.alphabetic()).take(16).collect(),
-                            },
-                        ))
-                    })
-                    .variant(2, |(clock, comments), rng| {
-                        if comments.is_empty() {
-                            return None;
-                        }
-                        let to = *comments.iter().nth(rng.usize(..comments.len())).unwrap();
-
-                        Some((
-                            clock.tick(),
-                            Action::React {
-                                to,
-                                reaction: Reaction::new('✨').unwrap(),
-                                active: rng.bool(),
-                            },
-                        ))
-                    })
-                    .variant(2, |(clock, comments), rng| {
-                        if comments.is_empty() {
-                            return None;
-                        }
-                        let id = *comments.iter().nth(rng.usize(..comments.len())).unwrap();
-                        comments.remove(&id);
-
-                        Some((clock.tick(), Action::Redact { id }))
-                    });
-
-            let mut ops = vec![Op::new(
-                Action::Comment {
-                    body: String::default(),
-                    reply_to: None,
-                },
-                author,
-                Timestamp::now(),
-                Lamport::initial(),
-            )];
-            let mut permutations: [Vec<Op<Action>>; N] = array::from_fn(|_| Vec::new());
-
-            for (clock, action) in gen.take(g.size()) {
-                let timestamp = Timestamp::now() + rng.u64(..60);
-                ops.push(Op::new(action, author, timestamp, clock));
-            }
-
-            for p in &mut permutations {
-                *p = ops.clone();
-                rng.shuffle(&mut ops);
-            }
-
-            Changes { permutations }
-        }
-    }
+    use crate::test::arbitrary::cob::{converges, Changes};
 
     mod setup {
         use super::*;
@@ -515,6 +509,25 @@ mod tests {
         assert_eq!(comment1.body(), "Third comment"); // Second comment was redacted.
     }
 
+    #[test]
+    fn test_redact_missing_comment() {
+        let mut alice = Actor::<MockSigner>::default();
+        let mut thread = Thread::default();
+
+        let a0 = alice.comment("First comment", None);
+        let a1 = alice.redact(a0.id());
+
+        // The redaction is applied before the comment it redacts.
+        assert!(matches!(
+            thread.apply([a1]),
+            Err(OpError::Missing(id)) if id == a0.id()
+        ));
+        thread.apply([a0.clone()]).unwrap();
+
+        assert_eq!(thread.comments().count(), 1);
+        assert_eq!(thread.comment(&a0.id()).unwrap().body(), "First comment");
+    }
+
     #[test]
     fn test_edit_comment() {
         let mut alice = Actor::<MockSigner>::default();
@@ -533,6 +546,7 @@ mod tests {
         assert_eq!(edits[1].body.as_str(), "Goodbye world.");
         assert_eq!(edits[2].body.as_str(), "Goodbye world!");
         assert_eq!(t1.comment(&c0.id()).unwrap().body(), "Goodbye world!");
+        assert!(t1.comment(&c0.id()).unwrap().is_edited());
 
         let mut t2 = Thread::default();
         t2.apply([c0, c2, c1]).unwrap(); // Apply in different order.
@@ -540,6 +554,23 @@ mod tests {
         assert_eq!(t1, t2);
     }
 
+    #[test]
+    fn test_edit_and_redact_by_other() {
+        let mut alice = Actor::<MockSigner>::default();
+        let mut bob = Actor::<MockSigner>::default();
+
+        let a0 = alice.comment("Hello world!", None);
+        let b0 = bob.edit(a0.id(), "Goodbye world!");
+        let b1 = bob.redact(a0.id());
+
+        let mut thread = Thread::default();
+        thread.apply([a0.clone(), b0, b1]).unwrap();
+
+        let comment = thread.comment(&a0.id()).unwrap();
+        assert_eq!(comment.body(), "Hello world!");
+        assert!(!comment.is_edited());
+    }
+
     #[test]
     fn test_storage() {
         let tmp = tempfile::tempdir().unwrap();
@@ -671,35 +702,10 @@ mod tests {
 
     #[test]
     fn prop_invariants() {
-        fn property(log: Changes<3>) -> TestResult {
-            let t = Thread::default();
-            let [p1, p2, p3] = log.permutations;
-
-            let mut t1 = t.clone();
-            if t1.apply(p1).is_err() {
-                return TestResult::discard();
-            }
-
-            let mut t2 = t.clone();
-            if t2.apply(p2).is_err() {
-                return TestResult::discard();
-            }
-
-            let mut t3 = t;
-            if t3.apply(p3).is_err() {
-                return TestResult::discard();
-            }
-
-            assert_eq!(t1, t2);
-            assert_eq!(t2, t3);
-            assert_laws(&t1, &t2, &t3);
-
-            TestResult::passed()
-        }
         qcheck::QuickCheck::new()
             .min_tests_passed(100)
             .max_tests(10000)
             .gen(qcheck::Gen::new(7))
-            .quickcheck(property as fn(Changes<3>) -> TestResult);
+            .quickcheck(converges::<Thread, 3> as fn(Changes<Action, 3>) -> TestResult);
     }
 }
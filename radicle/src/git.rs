@@ -56,6 +56,8 @@ pub enum ListRefsError {
     InvalidRef(#[from] RefError),
 }
 
+pub mod hooks;
+
 pub mod refs {
     use super::*;
 
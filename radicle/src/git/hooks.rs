@@ -0,0 +1,172 @@
+//! Git hooks installed in working copies of radicle projects.
+//!
+//! These are regular git hooks, written to the `hooks` directory of a working copy, which
+//! keep the working copy and the network in sync with each other.
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::rad::REMOTE_NAME;
+
+/// A working copy hook.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Hook {
+    /// Refuses pushes to the radicle remote which would update references managed by
+    /// radicle, eg. the signed refs or identity branch, since these are only ever updated
+    /// by radicle itself.
+    PrePush,
+    /// Runs `rad sync` in the background once the remote tracking branches of the radicle
+    /// remote are updated, ie. after a push or fetch. This also keeps the patch branches
+    /// under `refs/remotes/rad/patches` up to date.
+    Sync,
+}
+
+impl Hook {
+    /// All hooks.
+    pub const ALL: [Hook; 2] = [Hook::PrePush, Hook::Sync];
+
+    /// Name of the hook file, as expected by git.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PrePush => "pre-push",
+            Self::Sync => "reference-transaction",
+        }
+    }
+
+    /// Contents of the hook script.
+    pub fn script(&self) -> String {
+        let remote = REMOTE_NAME.as_str();
+
+        match self {
+            Self::PrePush => format!(
+                r#"#!/bin/sh
+# Installed by radicle: refuse pushes that would break signed refs.
+url="$2"
+
+case "$url" in
+  rad://*) ;;
+  *) exit 0 ;;
+esac
+
+while read -r local_ref local_oid remote_ref remote_oid; do
+  case "$remote_ref" in
+    refs/rad/*|refs/namespaces/*)
+      echo "error: refusing to push to '$remote_ref' on remote '{remote}': ref is managed by radicle" >&2
+      exit 1
+      ;;
+  esac
+done
+
+exit 0
+"#
+            ),
+            Self::Sync => format!(
+                r#"#!/bin/sh
+# Installed by radicle: sync with the network after pushing or fetching.
+[ "$1" = "committed" ] || exit 0
+
+while read -r old new ref; do
+  case "$ref" in
+    refs/remotes/{remote}/patches/*)
+      ;;
+    refs/remotes/{remote}/*)
+      (rad sync >/dev/null 2>&1 &)
+      exit 0
+      ;;
+  esac
+done
+
+exit 0
+"#
+            ),
+        }
+    }
+}
+
+/// Install the given hooks in a working copy. Existing hooks are left untouched, unless
+/// `force` is set. Returns the paths of the hooks that were installed.
+pub fn install(
+    repo: &git2::Repository,
+    hooks: &[Hook],
+    force: bool,
+) -> Result<Vec<PathBuf>, io::Error> {
+    let dir = repo.path().join("hooks");
+    let mut installed = Vec::new();
+
+    fs::create_dir_all(&dir)?;
+
+    for hook in hooks {
+        let path = dir.join(hook.name());
+        if path.exists() && !force {
+            continue;
+        }
+        fs::write(&path, hook.script())?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        }
+        installed.push(path);
+    }
+    Ok(installed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(tmp.path()).unwrap();
+        let installed = install(&repo, &Hook::ALL, false).unwrap();
+
+        assert_eq!(installed.len(), 2);
+        for (hook, path) in Hook::ALL.iter().zip(&installed) {
+            assert_eq!(fs::read_to_string(path).unwrap(), hook.script());
+        }
+
+        // Existing hooks are not overwritten.
+        fs::write(&installed[0], "#!/bin/sh\n").unwrap();
+        assert!(install(&repo, &Hook::ALL, false).unwrap().is_empty());
+        assert_eq!(fs::read_to_string(&installed[0]).unwrap(), "#!/bin/sh\n");
+        assert_eq!(install(&repo, &[Hook::PrePush], true).unwrap().len(), 1);
+        assert_eq!(
+            fs::read_to_string(&installed[0]).unwrap(),
+            Hook::PrePush.script()
+        );
+    }
+
+    #[test]
+    fn test_pre_push() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(tmp.path()).unwrap();
+        let path = install(&repo, &[Hook::PrePush], false).unwrap().remove(0);
+        let run = |url: &str, line: &str| {
+            use std::io::Write;
+
+            let mut child = std::process::Command::new(&path)
+                .args([REMOTE_NAME.as_str(), url])
+                .stdin(std::process::Stdio::piped())
+                .spawn()
+                .unwrap();
+            writeln!(child.stdin.take().unwrap(), "{line}").unwrap();
+            child.wait().unwrap().success()
+        };
+        let oid = "0".repeat(40);
+
+        assert!(run(
+            "rad://z3gqcJUoA1n9HaHKufZs5FCSGazv5",
+            &format!("refs/heads/master {oid} refs/heads/master {oid}")
+        ));
+        assert!(!run(
+            "rad://z3gqcJUoA1n9HaHKufZs5FCSGazv5",
+            &format!("refs/heads/master {oid} refs/rad/sigrefs {oid}")
+        ));
+        assert!(run(
+            "https://example.com/repo.git",
+            &format!("refs/heads/master {oid} refs/rad/sigrefs {oid}")
+        ));
+    }
+}
@@ -94,10 +94,10 @@ impl Identity<Untrusted> {
         repo: &R,
     ) -> Result<Identity<Oid>, IdentityError> {
         let head = Doc::<Untrusted>::head(remote, repo)?;
-        let mut history = repo.revwalk(head)?.collect::<Vec<_>>();
+        let mut history = repo.history(head)?;
 
         // Retrieve root document.
-        let root_oid = history.pop().ok_or(IdentityError::MissingRoot)??.into();
+        let root_oid = history.pop().ok_or(IdentityError::MissingRoot)?;
         let root = Doc::<Verified>::load_at(root_oid, repo)?;
         let revision = history.len() as u32;
 
@@ -114,8 +114,7 @@ impl Identity<Untrusted> {
 
         // Traverse the history chronologically.
         for oid in history.into_iter().rev() {
-            let oid = oid?;
-            let untrusted = Doc::<Verified>::load_at(oid.into(), repo)?;
+            let untrusted = Doc::<Verified>::load_at(oid, repo)?;
 
             // Check that enough delegates signed this next version.
             let quorum = untrusted
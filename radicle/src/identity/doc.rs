@@ -14,11 +14,12 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::crypto;
+use crate::crypto::revocation::{Revocations, SignedRevocation};
 use crate::crypto::{Signature, Unverified, Verified};
 use crate::git;
 use crate::identity::{project::Project, Did};
 use crate::storage::git::trailers;
-use crate::storage::{ReadRepository, RemoteId};
+use crate::storage::{Blob, ReadRepository, RemoteId};
 
 pub use crypto::PublicKey;
 pub use id::*;
@@ -50,6 +51,10 @@ pub enum DocError {
     GitExt(#[from] git::Error),
     #[error("git: {0}")]
     Git(#[from] git2::Error),
+    #[error("invalid revocation: {0}")]
+    Revocation(&'static str),
+    #[error(transparent)]
+    Payload(#[from] PayloadError),
 }
 
 impl DocError {
@@ -81,6 +86,16 @@ impl PayloadId {
     pub fn project() -> Self {
         Self(String::from("xyz.radicle.project"))
     }
+
+    /// Device set payload type.
+    pub fn devices() -> Self {
+        Self(String::from("xyz.radicle.devices"))
+    }
+
+    /// Key revocations payload type.
+    pub fn revocations() -> Self {
+        Self(String::from("xyz.radicle.revocations"))
+    }
 }
 
 #[derive(Debug, Error)]
@@ -112,6 +127,42 @@ impl Deref for Payload {
     }
 }
 
+/// Device keys allowed to act on behalf of the identity, mapped to their device names.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Devices(BTreeMap<PublicKey, String>);
+
+impl Devices {
+    /// Check whether the given key is an enrolled device key.
+    pub fn contains(&self, key: &PublicKey) -> bool {
+        self.0.contains_key(key)
+    }
+
+    /// Iterate over the device keys and their names.
+    pub fn iter(&self) -> impl Iterator<Item = (&PublicKey, &str)> {
+        self.0.iter().map(|(k, n)| (k, n.as_str()))
+    }
+
+    /// Number of enrolled devices.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether there are no enrolled devices.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<Devices> for Payload {
+    fn from(devices: Devices) -> Self {
+        let value = serde_json::to_value(devices)
+            .expect("Payload::from: could not convert devices into value");
+
+        Self::from(value)
+    }
+}
+
 /// A verified identity document at a specific commit.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DocAt {
@@ -125,6 +176,42 @@ pub struct DocAt {
     pub sigs: HashMap<PublicKey, Signature>,
 }
 
+/// A change to a payload of an identity document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum PayloadChange {
+    /// The payload was added.
+    Added { new: Payload },
+    /// The payload was removed.
+    Removed { old: Payload },
+    /// The payload was modified.
+    Modified { old: Payload, new: Payload },
+}
+
+/// Structured difference between two identity documents.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocDiff {
+    /// Delegates only found in the new document.
+    pub delegates_added: Vec<Did>,
+    /// Delegates only found in the old document.
+    pub delegates_removed: Vec<Did>,
+    /// Old and new threshold, if the threshold changed.
+    pub threshold: Option<(usize, usize)>,
+    /// Changed payloads.
+    pub payload: BTreeMap<PayloadId, PayloadChange>,
+}
+
+impl DocDiff {
+    /// Whether the documents are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.delegates_added.is_empty()
+            && self.delegates_removed.is_empty()
+            && self.threshold.is_none()
+            && self.payload.is_empty()
+    }
+}
+
 /// An identity document.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -146,7 +233,7 @@ impl<V> Doc<V> {
             .map_err(DocError::from)
     }
 
-    pub fn blob_at<R: ReadRepository>(commit: Oid, repo: &R) -> Result<git2::Blob, DocError> {
+    pub fn blob_at<R: ReadRepository>(commit: Oid, repo: &R) -> Result<Blob, DocError> {
         repo.blob_at(commit, Path::new(&*PATH))
             .map_err(DocError::from)
     }
@@ -154,6 +241,84 @@ impl<V> Doc<V> {
     pub fn is_delegate(&self, key: &crypto::PublicKey) -> bool {
         self.delegates.contains(&key.into())
     }
+
+    /// Whether the given key may act on behalf of the identity, ie. it is either a
+    /// delegate, or an enrolled device key. Unlike delegates, device keys don't count
+    /// towards the threshold, or towards votes and reviews.
+    pub fn is_authorized(&self, key: &crypto::PublicKey) -> bool {
+        self.is_delegate(key) || self.devices().map_or(false, |d| d.contains(key))
+    }
+
+    /// Compute the changes going from this document to the `other` document.
+    pub fn diff<W>(&self, other: &Doc<W>) -> DocDiff {
+        let delegates_added = other
+            .delegates
+            .iter()
+            .filter(|d| !self.delegates.contains(d))
+            .cloned()
+            .collect();
+        let delegates_removed = self
+            .delegates
+            .iter()
+            .filter(|d| !other.delegates.contains(d))
+            .cloned()
+            .collect();
+        let threshold =
+            (self.threshold != other.threshold).then_some((self.threshold, other.threshold));
+
+        let mut payload = BTreeMap::new();
+        for (id, old) in &self.payload {
+            match other.payload.get(id) {
+                Some(new) if new == old => {}
+                Some(new) => {
+                    payload.insert(
+                        id.clone(),
+                        PayloadChange::Modified {
+                            old: old.clone(),
+                            new: new.clone(),
+                        },
+                    );
+                }
+                None => {
+                    payload.insert(id.clone(), PayloadChange::Removed { old: old.clone() });
+                }
+            }
+        }
+        for (id, new) in &other.payload {
+            if !self.payload.contains_key(id) {
+                payload.insert(id.clone(), PayloadChange::Added { new: new.clone() });
+            }
+        }
+
+        DocDiff {
+            delegates_added,
+            delegates_removed,
+            threshold,
+            payload,
+        }
+    }
+
+    /// Get the key revocations of this document. Only revocations that are validly signed,
+    /// either by the revoked key itself or by a delegate, are returned.
+    pub fn revocations(&self) -> Result<Revocations, PayloadError> {
+        let Some(value) = self.payload.get(&PayloadId::revocations()) else {
+            return Ok(Revocations::default());
+        };
+        let stored: Revocations = serde_json::from_value((**value).clone())?;
+        let mut revocations = Revocations::default();
+
+        for r in stored.iter().filter(|r| self.may_revoke(r)) {
+            if let Err(err) = revocations.insert(*r) {
+                log::warn!("Ignoring revocation of {}: {err}", r.revocation.key);
+            }
+        }
+        Ok(revocations)
+    }
+
+    /// Whether the revoker of the given revocation is allowed to revoke the key.
+    pub fn may_revoke(&self, revocation: &SignedRevocation) -> bool {
+        revocation.is_self_revocation() || self.is_delegate(&revocation.revoker)
+    }
 }
 
 impl Doc<Verified> {
@@ -178,6 +343,26 @@ impl Doc<Verified> {
         false
     }
 
+    /// Replace the `old` delegate key with the `new` one, keeping the threshold as is.
+    pub fn rotate(
+        &mut self,
+        old: &crypto::PublicKey,
+        new: &crypto::PublicKey,
+    ) -> Result<(), DocError> {
+        let (old, new) = (Did::from(old), Did::from(new));
+        if self.delegates.contains(&new) {
+            return Err(DocError::Delegates("the new key is already a delegate"));
+        }
+        let delegate = self
+            .delegates
+            .iter_mut()
+            .find(|d| **d == old)
+            .ok_or(DocError::Delegates("the key to rotate is not a delegate"))?;
+        *delegate = new;
+
+        Ok(())
+    }
+
     pub fn rescind(&mut self, key: &crypto::PublicKey) -> Result<Option<Did>, DocError> {
         let delegate = Did::from(key);
         let (matches, delegates) = self.delegates.iter().partition(|d| **d == delegate);
@@ -207,6 +392,79 @@ impl Doc<Verified> {
         Ok(proj)
     }
 
+    /// Get the device set of this document. Returns an empty set if there is no
+    /// device payload.
+    pub fn devices(&self) -> Result<Devices, PayloadError> {
+        let Some(value) = self.payload.get(&PayloadId::devices()) else {
+            return Ok(Devices::default());
+        };
+        let devices: Devices = serde_json::from_value((**value).clone())?;
+
+        Ok(devices)
+    }
+
+    /// Enroll a device key under the given name. Returns `false` if the key was
+    /// already enrolled under that name.
+    pub fn enroll_device(
+        &mut self,
+        key: crypto::PublicKey,
+        name: impl ToString,
+    ) -> Result<bool, PayloadError> {
+        let mut devices = self.devices()?;
+        let name = name.to_string();
+
+        if devices.0.get(&key) == Some(&name) {
+            return Ok(false);
+        }
+        devices.0.insert(key, name);
+        self.payload.insert(PayloadId::devices(), devices.into());
+
+        Ok(true)
+    }
+
+    /// Revoke a device key. Returns the name it was enrolled under, if any.
+    pub fn revoke_device(
+        &mut self,
+        key: &crypto::PublicKey,
+    ) -> Result<Option<String>, PayloadError> {
+        let mut devices = self.devices()?;
+        let name = devices.0.remove(key);
+
+        if name.is_some() {
+            if devices.is_empty() {
+                self.payload.remove(&PayloadId::devices());
+            } else {
+                self.payload.insert(PayloadId::devices(), devices.into());
+            }
+        }
+        Ok(name)
+    }
+
+    /// Record a key revocation in this document. The key is also removed from the device
+    /// set, if present. Returns `false` if the key was already revoked at or before the
+    /// given revocation's time.
+    pub fn revoke(&mut self, revocation: SignedRevocation) -> Result<bool, DocError> {
+        if !self.may_revoke(&revocation) {
+            return Err(DocError::Revocation(
+                "revoker is neither the revoked key nor a delegate",
+            ));
+        }
+        let mut revocations = self.revocations()?;
+        if !revocations
+            .insert(revocation)
+            .map_err(|_| DocError::Revocation("invalid signature"))?
+        {
+            return Ok(false);
+        }
+        self.revoke_device(&revocation.revocation.key)?;
+        self.payload.insert(
+            PayloadId::revocations(),
+            Payload::from(serde_json::to_value(revocations)?),
+        );
+
+        Ok(true)
+    }
+
     pub fn sign<G: crypto::Signer>(&self, signer: &G) -> Result<(git::Oid, Signature), DocError> {
         let (oid, bytes) = self.encode()?;
         let sig = signer.sign(&bytes);
@@ -217,10 +475,9 @@ impl Doc<Verified> {
     pub fn load_at<R: ReadRepository>(oid: Oid, repo: &R) -> Result<DocAt, DocError> {
         let blob = Self::blob_at(oid, repo)?;
         let doc = Doc::from_json(blob.content())?.verified()?;
-        let commit = repo.commit(oid)?;
-        let msg = commit
-            .message_raw()
-            .ok_or(DocError::Commit("commit message is not UTF-8"))?;
+        let msg = repo.commit_message(oid)?;
+        let msg = std::str::from_utf8(&msg)
+            .map_err(|_| DocError::Commit("commit message is not UTF-8"))?;
         let sigs = trailers::parse_signatures(msg)?;
 
         for (pk, sig) in &sigs {
@@ -231,7 +488,7 @@ impl Doc<Verified> {
         Ok(DocAt {
             commit: oid,
             doc,
-            blob: blob.id().into(),
+            blob: blob.id(),
             sigs,
         })
     }
@@ -342,7 +599,7 @@ impl Doc<Unverified> {
         let blob = Self::blob_at(commit, repo)?;
         let doc = Doc::from_json(blob.content())?;
 
-        Ok((doc, blob.id().into()))
+        Ok((doc, blob.id()))
     }
 
     pub fn load<R: ReadRepository>(remote: &RemoteId, repo: &R) -> Result<(Self, Oid), DocError> {
@@ -354,6 +611,7 @@ impl Doc<Unverified> {
 
 #[cfg(test)]
 mod test {
+    use radicle_crypto::revocation::Revocation;
     use radicle_crypto::test::signer::MockSigner;
     use radicle_crypto::Signer as _;
 
@@ -416,6 +674,160 @@ mod test {
         assert!(err.is_not_found());
     }
 
+    #[test]
+    fn test_devices() {
+        let mut doc = arbitrary::gen::<Doc<Verified>>(1);
+        let laptop = MockSigner::from_seed([0x01; 32]);
+        let phone = MockSigner::from_seed([0x02; 32]);
+
+        doc.payload.remove(&PayloadId::devices());
+        assert!(doc.devices().unwrap().is_empty());
+        assert!(doc.enroll_device(*laptop.public_key(), "laptop").unwrap());
+        assert!(!doc.enroll_device(*laptop.public_key(), "laptop").unwrap());
+        assert!(doc.enroll_device(*phone.public_key(), "phone").unwrap());
+
+        let devices = doc.devices().unwrap();
+        assert_eq!(devices.len(), 2);
+        assert!(devices.contains(laptop.public_key()));
+        assert!(doc.is_authorized(laptop.public_key()));
+        assert!(!doc.is_delegate(laptop.public_key()));
+
+        let (_, bytes) = doc.encode().unwrap();
+        let decoded = Doc::from_json(&bytes).unwrap().verified().unwrap();
+        assert_eq!(decoded.devices().unwrap(), devices);
+
+        assert_eq!(
+            doc.revoke_device(phone.public_key()).unwrap(),
+            Some(String::from("phone"))
+        );
+        assert_eq!(doc.revoke_device(phone.public_key()).unwrap(), None);
+        assert!(doc.revoke_device(laptop.public_key()).unwrap().is_some());
+        assert!(!doc.payload.contains_key(&PayloadId::devices()));
+        assert!(!doc.is_authorized(laptop.public_key()));
+    }
+
+    #[test]
+    fn test_revocations() {
+        let delegate = MockSigner::from_seed([0xff; 32]);
+        let device = MockSigner::from_seed([0x01; 32]);
+        let other = MockSigner::from_seed([0x02; 32]);
+        let mut doc = Doc::new(
+            arbitrary::gen::<Project>(1),
+            NonEmpty::new(Did::from(*delegate.public_key())),
+            1,
+        )
+        .verified()
+        .unwrap();
+
+        doc.enroll_device(*device.public_key(), "laptop").unwrap();
+
+        let revocation = Revocation::new(*device.public_key(), 100).sign(&delegate);
+        assert!(doc.revoke(revocation).unwrap());
+        assert!(!doc.revoke(revocation).unwrap());
+        assert!(doc.devices().unwrap().is_empty());
+
+        let revocations = doc.revocations().unwrap();
+        assert!(revocations.is_valid_at(device.public_key(), 99));
+        assert!(!revocations.is_valid_at(device.public_key(), 100));
+
+        // Only the key itself or a delegate may revoke a key.
+        let revocation = Revocation::new(*device.public_key(), 50).sign(&other);
+        assert!(matches!(
+            doc.revoke(revocation),
+            Err(DocError::Revocation(_))
+        ));
+
+        let (_, bytes) = doc.encode().unwrap();
+        let decoded = Doc::from_json(&bytes).unwrap().verified().unwrap();
+        assert_eq!(decoded.revocations().unwrap(), revocations);
+    }
+
+    #[test]
+    fn test_diff() {
+        let alice = MockSigner::from_seed([0xff; 32]);
+        let bob = MockSigner::from_seed([0x01; 32]);
+        let old = Doc::new(
+            arbitrary::gen::<Project>(1),
+            NonEmpty::new(Did::from(*alice.public_key())),
+            1,
+        )
+        .verified()
+        .unwrap();
+        let mut new = old.clone();
+
+        assert!(old.diff(&new).is_empty());
+
+        new.delegate(bob.public_key());
+        new.rescind(alice.public_key()).unwrap();
+        new.enroll_device(*alice.public_key(), "laptop").unwrap();
+
+        let project = Payload::from(serde_json::json!({
+            "name": "renamed",
+            "description": "",
+            "defaultBranch": "master",
+        }));
+        let old_project = old.payload[&PayloadId::project()].clone();
+        new.payload.insert(PayloadId::project(), project.clone());
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.delegates_added, vec![Did::from(*bob.public_key())]);
+        assert_eq!(diff.delegates_removed, vec![Did::from(*alice.public_key())]);
+        assert_eq!(diff.threshold, None);
+        assert_eq!(
+            diff.payload[&PayloadId::project()],
+            PayloadChange::Modified {
+                old: old_project,
+                new: project
+            }
+        );
+        assert!(matches!(
+            diff.payload[&PayloadId::devices()],
+            PayloadChange::Added { .. }
+        ));
+        assert!(matches!(
+            new.diff(&old).payload[&PayloadId::devices()],
+            PayloadChange::Removed { .. }
+        ));
+    }
+
+    #[test]
+    fn test_rotate() {
+        let alice = MockSigner::from_seed([0xff; 32]);
+        let bob = MockSigner::from_seed([0x01; 32]);
+        let carol = MockSigner::from_seed([0x02; 32]);
+        let mut doc = Doc::new(
+            arbitrary::gen::<Project>(1),
+            NonEmpty::from_vec(vec![
+                Did::from(*alice.public_key()),
+                Did::from(*bob.public_key()),
+            ])
+            .unwrap(),
+            2,
+        )
+        .verified()
+        .unwrap();
+
+        doc.rotate(alice.public_key(), carol.public_key()).unwrap();
+        assert_eq!(
+            doc.delegates,
+            NonEmpty::from_vec(vec![
+                Did::from(*carol.public_key()),
+                Did::from(*bob.public_key()),
+            ])
+            .unwrap()
+        );
+        assert_eq!(doc.threshold, 2);
+
+        assert!(matches!(
+            doc.rotate(alice.public_key(), carol.public_key()),
+            Err(DocError::Delegates(_))
+        ));
+        assert!(matches!(
+            doc.rotate(carol.public_key(), bob.public_key()),
+            Err(DocError::Delegates(_))
+        ));
+    }
+
     #[quickcheck]
     fn prop_encode_decode(doc: Doc<Verified>) {
         let (_, bytes) = doc.encode().unwrap();
@@ -1,3 +1,4 @@
+pub mod events;
 mod features;
 
 use amplify::WrapperMut;
@@ -7,11 +8,13 @@ use std::path::Path;
 use std::{io, net};
 
 use cyphernet::addr::{HostName, NetAddr};
+use serde::{Deserialize, Serialize};
 
 use crate::crypto::PublicKey;
 use crate::identity::Id;
 use crossbeam_channel as chan;
 
+pub use events::Event;
 pub use features::Features;
 
 /// Default name for control socket file.
@@ -60,6 +63,22 @@ pub enum Error {
     EmptyResponse { cmd: &'static str },
 }
 
+/// Node status, as reported by a running node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Info {
+    /// Node identifier.
+    pub id: NodeId,
+    /// Node software agent, eg. `radicle-node/0.2.0`.
+    pub agent: String,
+    /// Number of connected peers.
+    pub peers: usize,
+    /// Number of tracked repositories.
+    pub repos: usize,
+    /// Time since the node was started, in seconds.
+    pub uptime: u64,
+}
+
 /// A handle to send commands to the node or request information.
 pub trait Handle {
     /// The result of a fetch request.
@@ -92,6 +111,8 @@ pub trait Handle {
     fn sessions(&self) -> Result<Self::Sessions, Self::Error>;
     /// Query the inventory.
     fn inventory(&self) -> Result<chan::Receiver<Id>, Self::Error>;
+    /// Query the node status.
+    fn info(&self) -> Result<Info, Self::Error>;
 }
 
 /// Public node & device identifier.
@@ -122,7 +143,11 @@ impl Node {
             .map(ToString::to_string)
             .collect::<Vec<_>>()
             .join(" ");
-        writeln!(&self.stream, "{cmd} {args}")?;
+        if args.is_empty() {
+            writeln!(&self.stream, "{cmd}")?;
+        } else {
+            writeln!(&self.stream, "{cmd} {args}")?;
+        }
 
         Ok(BufReader::new(&self.stream).lines())
     }
@@ -242,6 +267,18 @@ impl Handle for Node {
         todo!();
     }
 
+    fn info(&self) -> Result<Info, Error> {
+        let mut line = self.call::<&str>("info", &[])?;
+        let line = line.next().ok_or(Error::EmptyResponse { cmd: "info" })??;
+
+        log::debug!("node: {}", line);
+
+        serde_json::from_str(&line).map_err(|_| Error::InvalidResponse {
+            cmd: "info",
+            response: line,
+        })
+    }
+
     fn shutdown(self) -> Result<(), Error> {
         todo!();
     }
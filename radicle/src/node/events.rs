@@ -0,0 +1,72 @@
+//! Events emitted by a running node.
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::revocation::SignedRevocation;
+use crate::identity::Id;
+use crate::storage::RefUpdate;
+
+use super::NodeId;
+
+/// A node event.
+///
+/// Events are emitted by the node as it interacts with the network, and can be consumed
+/// by applications embedding the node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Event {
+    /// A connection with a peer was established.
+    #[serde(rename_all = "camelCase")]
+    PeerConnected { nid: NodeId },
+    /// A connection with a peer was closed.
+    #[serde(rename_all = "camelCase")]
+    PeerDisconnected { nid: NodeId, reason: String },
+    /// The references of a project were announced to the network.
+    #[serde(rename_all = "camelCase")]
+    RefsAnnounced { project: Id },
+    /// The references of a project were fetched from a peer.
+    #[serde(rename_all = "camelCase")]
+    RefsFetched {
+        from: NodeId,
+        project: Id,
+        updated: Vec<RefUpdate>,
+    },
+    /// Fetching a project from a peer failed.
+    #[serde(rename_all = "camelCase")]
+    FetchFailed {
+        from: NodeId,
+        project: Id,
+        reason: String,
+    },
+    /// The tracking policy of a project changed.
+    #[serde(rename_all = "camelCase")]
+    RepoTrackingChanged { project: Id, tracked: bool },
+    /// The tracking policy of a node changed.
+    #[serde(rename_all = "camelCase")]
+    NodeTrackingChanged { nid: NodeId, tracked: bool },
+    /// A key revocation was received and applied.
+    #[serde(rename_all = "camelCase")]
+    KeyRevoked { revocation: SignedRevocation },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::arbitrary;
+
+    #[test]
+    fn test_serde() {
+        let event = Event::RefsFetched {
+            from: arbitrary::gen(1),
+            project: arbitrary::gen(1),
+            updated: vec![RefUpdate::Created {
+                name: crate::git::refname!("refs/heads/master"),
+                oid: arbitrary::oid(),
+            }],
+        };
+        let json = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(json["type"], "refsFetched");
+        assert_eq!(json["updated"][0]["type"], "created");
+        assert_eq!(serde_json::from_value::<Event>(json).unwrap(), event);
+    }
+}
@@ -7,20 +7,39 @@
 //!     keys/
 //!       radicle                                # Secret key (PKCS 8)
 //!       radicle.pub                            # Public key (PKCS 8)
+//!       devices/                               # Device keys
+//!         laptop                               # Device secret key
+//!         laptop.pub                           # Device public key
+//!     config.json                              # Profile configuration
+//!     inbox.db                                 # Notification inbox
 //!     node/
 //!       radicle.sock                           # Node control socket
+//!     profiles/                                # Named profiles
+//!       work/                                  # Named profile home, with the same layout
+//!       ...                                    # More named profiles...
 //!
+pub mod config;
+
 use std::path::{Path, PathBuf};
 use std::{fs, io};
 
 use thiserror::Error;
 
-use crate::crypto::ssh::agent::Agent;
-use crate::crypto::ssh::{keystore, Keystore, Passphrase};
-use crate::crypto::{PublicKey, Signer};
+#[cfg(all(unix, feature = "gpg"))]
+use crate::crypto::gpg;
+use crate::crypto::mnemonic::Mnemonic;
+use crate::crypto::shamir;
+use crate::crypto::ssh;
+use crate::crypto::ssh::keystore::KdfParams;
+use crate::crypto::ssh::{keystore, Keystore, Passphrase, SshSigner};
+use crate::crypto::{keypair, KeyPair, PublicKey, Signer};
 use crate::node;
+use crate::storage::git::migrate;
 use crate::storage::git::transport;
 use crate::storage::git::Storage;
+use crate::storage::hooks::Hooks;
+
+pub use config::Config;
 
 /// Environment variables used by radicle.
 pub mod env {
@@ -32,6 +51,13 @@ pub mod env {
     pub const RAD_SOCKET: &str = "RAD_SOCKET";
     /// Passphrase for the encrypted radicle secret key.
     pub const RAD_PASSPHRASE: &str = "RAD_PASSPHRASE";
+    /// Number of KDF rounds used to encrypt secret keys. Takes precedence over the
+    /// profile configuration.
+    pub const RAD_KDF_ROUNDS: &str = "RAD_KDF_ROUNDS";
+    /// Keygrip of an Ed25519 key held by `gpg-agent`, to be used for signing.
+    pub const RAD_GPG_KEYGRIP: &str = "RAD_GPG_KEYGRIP";
+    /// Name of the device key to sign with, instead of the profile key.
+    pub const RAD_DEVICE: &str = "RAD_DEVICE";
 
     pub fn read_passphrase() -> Option<super::Passphrase> {
         let Ok(passphrase) = std::env::var(RAD_PASSPHRASE) else {
@@ -39,6 +65,15 @@ pub mod env {
         };
         Some(super::Passphrase::from(passphrase))
     }
+
+    /// Read the keystore KDF parameters from the environment, if set.
+    pub fn kdf_params() -> Option<super::KdfParams> {
+        std::env::var(RAD_KDF_ROUNDS)
+            .ok()
+            .and_then(|rounds| rounds.parse().ok())
+            .filter(|rounds| *rounds > 0)
+            .map(|rounds| super::KdfParams { rounds })
+    }
 }
 
 #[derive(Debug, Error)]
@@ -51,10 +86,27 @@ pub enum Error {
     MemorySigner(#[from] keystore::MemorySignerError),
     #[error("no profile found at the filepath '{0}'")]
     NotFound(PathBuf),
-    #[error("error connecting to ssh-agent: {0}")]
-    Agent(#[from] crate::crypto::ssh::agent::Error),
+    #[error(transparent)]
+    Signer(#[from] ssh::signer::Error),
     #[error("profile key `{0}` is not registered with ssh-agent")]
     KeyNotRegistered(PublicKey),
+    #[cfg(all(unix, feature = "gpg"))]
+    #[error("error using gpg-agent: {0}")]
+    Gpg(#[from] crate::crypto::gpg::Error),
+    #[cfg(all(unix, feature = "gpg"))]
+    #[error("gpg key `{0}` does not match profile key `{1}`")]
+    KeyMismatch(PublicKey, PublicKey),
+    #[error("secret sharing error: {0}")]
+    Shamir(#[from] shamir::Error),
+    #[error("storage migration error: {0}")]
+    Migrate(#[from] migrate::Error),
+    #[error("configuration error: {0}")]
+    Config(#[from] config::Error),
+    #[error("invalid profile name '{0}'")]
+    InvalidName(String),
+    #[cfg(feature = "sql")]
+    #[error("refs index error: {0}")]
+    Index(#[from] crate::storage::index::Error),
 }
 
 #[derive(Debug, Clone)]
@@ -63,14 +115,47 @@ pub struct Profile {
     pub storage: Storage,
     pub keystore: Keystore,
     pub public_key: PublicKey,
+    pub config: Config,
 }
 
 impl Profile {
     pub fn init(home: Home, passphrase: impl Into<Passphrase>) -> Result<Self, Error> {
+        Self::create(home, keypair::generate(), passphrase)
+    }
+
+    /// Re-create a profile from a mnemonic backup of its secret key.
+    /// See [`Profile::mnemonic`].
+    pub fn recover(
+        home: Home,
+        mnemonic: &Mnemonic,
+        passphrase: impl Into<Passphrase>,
+    ) -> Result<Self, Error> {
+        Self::create(home, mnemonic.keypair(), passphrase)
+    }
+
+    /// Re-create a profile from shares of its secret key.
+    /// See [`Profile::split`].
+    pub fn combine(
+        home: Home,
+        shares: &[shamir::Share],
+        passphrase: impl Into<Passphrase>,
+    ) -> Result<Self, Error> {
+        let seed = shamir::combine_seed(shares)?;
+
+        Self::create(home, KeyPair::from_seed(seed), passphrase)
+    }
+
+    fn create(
+        home: Home,
+        keypair: KeyPair,
+        passphrase: impl Into<Passphrase>,
+    ) -> Result<Self, Error> {
         let home = home.init()?;
         let storage = Storage::open(home.storage())?;
-        let keystore = Keystore::new(&home.keys());
-        let public_key = keystore.init("radicle", passphrase)?;
+        let config = Config::load(&home.config())?;
+        let keystore =
+            Keystore::new(&home.keys()).with_kdf(kdf_params(&config).unwrap_or_default());
+        let public_key = keystore.store(keypair, "radicle", passphrase)?;
 
         transport::local::register(storage.clone());
 
@@ -79,13 +164,29 @@ impl Profile {
             storage,
             keystore,
             public_key,
+            config,
         })
     }
 
     pub fn load() -> Result<Self, Error> {
-        let home = self::home()?;
-        let storage = Storage::open(home.storage())?;
-        let keystore = Keystore::new(&home.keys());
+        Self::open(self::home()?)
+    }
+
+    /// Load the profile at the given home. Use this with [`Home::named`] to load a named
+    /// profile.
+    pub fn open(home: Home) -> Result<Self, Error> {
+        let storage = Storage::open(home.storage())?.with_hooks(Hooks::load(home.hooks())?);
+        storage.migrate()?;
+
+        // Keep the node's refs index in sync with changes made outside of the node, eg.
+        // by pushing or signing refs.
+        #[cfg(feature = "sql")]
+        if let Some(index) = crate::storage::index::Index::open_existing(home.index())? {
+            storage.hooks().register(index);
+        }
+        let config = Config::load(&home.config())?;
+        let keystore =
+            Keystore::new(&home.keys()).with_kdf(kdf_params(&config).unwrap_or_default());
         let public_key = keystore
             .public_key()?
             .ok_or_else(|| Error::NotFound(home.path().to_path_buf()))?;
@@ -97,6 +198,7 @@ impl Profile {
             storage,
             keystore,
             public_key,
+            config,
         })
     }
 
@@ -105,24 +207,97 @@ impl Profile {
     }
 
     pub fn signer(&self) -> Result<Box<dyn Signer>, Error> {
+        if let Ok(device) = env::var(env::RAD_DEVICE) {
+            let passphrase = env::read_passphrase().unwrap_or_default();
+            return self.device_signer(&device, passphrase).map(|s| s.boxed());
+        }
+
         if let Some(passphrase) = env::read_passphrase() {
             let signer = keystore::MemorySigner::load(&self.keystore, passphrase)?;
             return Ok(signer.boxed());
         }
 
-        match Agent::connect() {
-            Ok(agent) => {
-                let signer = agent.signer(self.public_key);
-                if signer.is_ready()? {
-                    Ok(signer.boxed())
-                } else {
-                    Err(Error::KeyNotRegistered(self.public_key))
-                }
+        #[cfg(all(unix, feature = "gpg"))]
+        if let Ok(keygrip) = env::var(env::RAD_GPG_KEYGRIP) {
+            let signer = gpg::Agent::connect()?.signer(keygrip.parse()?)?;
+            if signer.public_key() != &self.public_key {
+                return Err(Error::KeyMismatch(*signer.public_key(), self.public_key));
             }
+            return Ok(signer.boxed());
+        }
+
+        match SshSigner::load(&self.keystore, || None) {
+            Ok(signer) => Ok(signer.boxed()),
+            Err(ssh::signer::Error::NoSigner(key)) => Err(Error::KeyNotRegistered(key)),
             Err(err) => Err(err.into()),
         }
     }
 
+    /// Export the secret key as a mnemonic phrase, for backup purposes.
+    pub fn mnemonic(&self, passphrase: Passphrase) -> Result<Mnemonic, Error> {
+        let secret = self
+            .keystore
+            .secret_key(passphrase)?
+            .ok_or_else(|| Error::NotFound(self.keys()))?;
+
+        Ok(Mnemonic::from_secret_key(&secret))
+    }
+
+    /// Split the secret key into `count` shares, any `threshold` of which can be used to
+    /// recover it, for backup purposes.
+    pub fn split(
+        &self,
+        passphrase: Passphrase,
+        threshold: u8,
+        count: u8,
+    ) -> Result<Vec<shamir::Share>, Error> {
+        let secret = self
+            .keystore
+            .secret_key(passphrase)?
+            .ok_or_else(|| Error::NotFound(self.keys()))?;
+
+        shamir::split_seed(&secret.seed(), threshold, count).map_err(Error::from)
+    }
+
+    /// Generate a new device key, stored under the given name.
+    ///
+    /// The device still has to be enrolled in the identity documents it should act on,
+    /// see [`crate::identity::Doc::enroll_device`].
+    pub fn enroll_device(
+        &self,
+        name: &str,
+        passphrase: impl Into<Passphrase>,
+    ) -> Result<PublicKey, Error> {
+        self.keystore
+            .init_device(name, passphrase)
+            .map_err(Error::from)
+    }
+
+    /// Remove the device key stored under the given name. Returns the device key,
+    /// if it was found.
+    pub fn revoke_device(&self, name: &str) -> Result<Option<PublicKey>, Error> {
+        let Some(key) = self.keystore.device_public_key(name)? else {
+            return Ok(None);
+        };
+        self.keystore.remove_device(name)?;
+
+        Ok(Some(key))
+    }
+
+    /// List the device keys of this profile, by name.
+    pub fn devices(&self) -> Result<Vec<(String, PublicKey)>, Error> {
+        self.keystore.devices().map_err(Error::from)
+    }
+
+    /// Get a signer for the device key stored under the given name.
+    pub fn device_signer(
+        &self,
+        name: &str,
+        passphrase: Passphrase,
+    ) -> Result<keystore::MemorySigner, Error> {
+        keystore::MemorySigner::load_device(&self.keystore, name, passphrase).map_err(Error::from)
+    }
+
     /// Return the path to the keys folder.
     pub fn keys(&self) -> PathBuf {
         self.home.keys()
@@ -138,6 +313,11 @@ impl Profile {
         self.home.socket()
     }
 
+    /// Get the path to the storage hooks folder.
+    pub fn hooks(&self) -> PathBuf {
+        self.home.hooks()
+    }
+
     /// Get `Paths` of profile
     pub fn paths(&self) -> &Home {
         &self.home
@@ -158,6 +338,12 @@ pub fn home() -> Result<Home, io::Error> {
     }
 }
 
+/// Get the configured keystore KDF parameters, if any. The `RAD_KDF_ROUNDS` environment
+/// variable takes precedence over the profile configuration.
+pub fn kdf_params(config: &Config) -> Option<KdfParams> {
+    env::kdf_params().or_else(|| config.kdf_params())
+}
+
 /// Radicle home.
 #[derive(Debug, Clone)]
 pub struct Home {
@@ -197,9 +383,128 @@ impl Home {
         self.path.join("node")
     }
 
+    pub fn hooks(&self) -> PathBuf {
+        self.path.join("hooks")
+    }
+
+    /// Path to the profile configuration file. See [`Config`].
+    pub fn config(&self) -> PathBuf {
+        self.path.join("config.json")
+    }
+
+    /// Path to the notification inbox database. See [`crate::cob::inbox::Inbox`].
+    pub fn inbox(&self) -> PathBuf {
+        self.path.join("inbox.db")
+    }
+
+    /// Path to the refs index database. The index is created by the node, and kept up
+    /// to date by profiles that are loaded while it exists.
+    pub fn index(&self) -> PathBuf {
+        self.node().join("refs.db")
+    }
+
+    /// Path to the folder holding the named profiles of this home.
+    pub fn profiles(&self) -> PathBuf {
+        self.path.join("profiles")
+    }
+
+    /// Get the home of a named profile. Each named profile has its own keystore, storage
+    /// and node, and can be initialized and loaded like any other profile, via
+    /// [`Profile::init`] and [`Profile::open`].
+    pub fn named(&self, name: &str) -> Result<Home, Error> {
+        if name.is_empty()
+            || name.starts_with('.')
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            return Err(Error::InvalidName(name.to_owned()));
+        }
+        Ok(Home::new(self.profiles().join(name)))
+    }
+
+    /// List the names of the named profiles of this home that have a key.
+    pub fn names(&self) -> Result<Vec<String>, Error> {
+        let mut names = Vec::new();
+        let entries = match fs::read_dir(self.profiles()) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(names),
+            Err(e) => return Err(e.into()),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let Some(name) = entry.file_name().to_str().map(ToOwned::to_owned) else {
+                continue;
+            };
+            let Ok(home) = self.named(&name) else {
+                continue;
+            };
+            if Keystore::new(&home.keys()).public_key()?.is_some() {
+                names.push(name);
+            }
+        }
+        names.sort();
+
+        Ok(names)
+    }
+
     pub fn socket(&self) -> PathBuf {
         env::var_os(env::RAD_SOCKET)
             .map(PathBuf::from)
             .unwrap_or_else(|| self.node().join(node::DEFAULT_SOCKET_NAME))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_matches;
+
+    #[test]
+    fn test_named_profiles() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = Home::new(tmp.path());
+
+        assert!(home.names().unwrap().is_empty());
+        assert_matches!(home.named("../alice"), Err(Error::InvalidName(_)));
+        assert_matches!(home.named(""), Err(Error::InvalidName(_)));
+
+        let alice = Profile::init(home.named("alice").unwrap(), "alice".to_owned()).unwrap();
+        let bob = Profile::init(home.named("bob").unwrap(), "bob".to_owned()).unwrap();
+
+        assert_eq!(home.names().unwrap(), vec!["alice", "bob"]);
+        assert_ne!(alice.id(), bob.id());
+        assert_ne!(alice.storage.path(), bob.storage.path());
+
+        let loaded = Profile::open(home.named("alice").unwrap()).unwrap();
+        assert_eq!(loaded.id(), alice.id());
+        assert_matches!(
+            Profile::open(home.named("eve").unwrap()),
+            Err(Error::NotFound(_))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sql")]
+    fn test_index_hook() {
+        use crate::storage::index::Index;
+        use crate::test::fixtures;
+        use crypto::test::signer::MockSigner;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let home = Home::new(tmp.path().join("home"));
+        Profile::init(home.clone(), "alice".to_owned()).unwrap();
+
+        fs::create_dir_all(home.node()).unwrap();
+        let index = Index::open(home.index()).unwrap();
+        let profile = Profile::open(home).unwrap();
+        let (id, _, _, _) = fixtures::project(
+            tmp.path().join("acme"),
+            &profile.storage,
+            &MockSigner::default(),
+        )
+        .unwrap();
+
+        assert_eq!(index.projects().unwrap(), vec![id]);
+    }
+}
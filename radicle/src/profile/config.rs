@@ -0,0 +1,341 @@
+//! Profile configuration.
+//!
+//! Stored as JSON under `$RAD_HOME/config.json`. Every field is optional, and falls back to
+//! its default when missing, eg.
+//!
+//! ```json
+//! {
+//!   "node": {
+//!     "listen": ["0.0.0.0:8776"],
+//!     "connect": ["z6MkrLMMsiPWUcNPHcRajuMi9mDfYckSoJyPwwnknocNYPm7@seed.radicle.xyz:8776"]
+//!   },
+//!   "seeds": ["z6MkrLMMsiPWUcNPHcRajuMi9mDfYckSoJyPwwnknocNYPm7@seed.radicle.xyz:8776"],
+//!   "httpd": { "listen": "127.0.0.1:8080" },
+//!   "cli": { "color": "auto", "editor": "vim" },
+//!   "keys": { "kdfRounds": 128 },
+//!   "alias": "alice"
+//! }
+//! ```
+use std::collections::HashSet;
+use std::path::Path;
+use std::str::FromStr;
+use std::{fmt, fs, io, net};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::crypto::ssh::keystore::KdfParams;
+use crate::node::{Address, NodeId};
+use crate::serde_ext;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("i/o: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid configuration file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid configuration: {0}")]
+    Invalid(String),
+}
+
+/// Profile configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Config {
+    /// Node configuration.
+    pub node: Node,
+    /// Preferred seeds, used to fetch projects from and publish projects to.
+    pub seeds: Vec<PeerAddress>,
+    /// HTTP daemon configuration.
+    pub httpd: Httpd,
+    /// Command-line configuration.
+    pub cli: Cli,
+    /// Keystore configuration.
+    pub keys: Keys,
+    /// Alias by which this profile can be mentioned in comments, eg. `@alice`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+}
+
+impl Config {
+    /// Load the configuration from the given file. Returns the default configuration if the
+    /// file doesn't exist.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let config: Self = match fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Write the configuration to the given file.
+    pub fn write(&self, path: &Path) -> Result<(), Error> {
+        self.validate()?;
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    /// Check that the configuration is valid.
+    pub fn validate(&self) -> Result<(), Error> {
+        let mut listen = HashSet::new();
+        for addr in &self.node.listen {
+            if !listen.insert(addr) {
+                return Err(Error::Invalid(format!(
+                    "duplicate node listen address `{addr}`"
+                )));
+            }
+        }
+        let mut peers = HashSet::new();
+        for peer in self.node.connect.iter() {
+            if !peers.insert(peer.id) {
+                return Err(Error::Invalid(format!(
+                    "duplicate node connect address for `{}`",
+                    peer.id
+                )));
+            }
+        }
+        let mut seeds = HashSet::new();
+        for seed in self.seeds.iter() {
+            if !seeds.insert(seed.id) {
+                return Err(Error::Invalid(format!("duplicate seed `{}`", seed.id)));
+            }
+        }
+        if matches!(&self.cli.editor, Some(editor) if editor.trim().is_empty()) {
+            return Err(Error::Invalid(String::from("editor must not be empty")));
+        }
+        if self.keys.kdf_rounds == Some(0) {
+            return Err(Error::Invalid(String::from(
+                "KDF rounds must be greater than zero",
+            )));
+        }
+        Ok(())
+    }
+
+    /// Addresses the node should listen on.
+    pub fn listen(&self) -> &[net::SocketAddr] {
+        &self.node.listen
+    }
+
+    /// Peers the node should connect to, including the preferred seeds.
+    pub fn connect(&self) -> impl Iterator<Item = &PeerAddress> {
+        let mut seen = HashSet::new();
+
+        self.node
+            .connect
+            .iter()
+            .chain(self.seeds.iter())
+            .filter(move |p| seen.insert(p.id))
+    }
+
+    /// Preferred seeds.
+    pub fn seeds(&self) -> &[PeerAddress] {
+        &self.seeds
+    }
+
+    /// Address the HTTP daemon should bind to.
+    pub fn httpd_listen(&self) -> net::SocketAddr {
+        self.httpd.listen
+    }
+
+    /// Command-line color preference.
+    pub fn color(&self) -> Color {
+        self.cli.color
+    }
+
+    /// Command-line editor preference.
+    pub fn editor(&self) -> Option<&str> {
+        self.cli.editor.as_deref()
+    }
+
+    /// KDF parameters used to encrypt secret keys, if configured.
+    pub fn kdf_params(&self) -> Option<KdfParams> {
+        self.keys.kdf_rounds.map(|rounds| KdfParams { rounds })
+    }
+}
+
+/// Node configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Node {
+    /// Addresses to listen on for inbound connections.
+    pub listen: Vec<net::SocketAddr>,
+    /// Peers to connect to on startup.
+    pub connect: Vec<PeerAddress>,
+}
+
+/// HTTP daemon configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Httpd {
+    /// Address to bind to.
+    pub listen: net::SocketAddr,
+}
+
+impl Default for Httpd {
+    fn default() -> Self {
+        Self {
+            listen: ([0, 0, 0, 0], 8080).into(),
+        }
+    }
+}
+
+/// Command-line configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Cli {
+    /// Whether to use colors in the terminal.
+    pub color: Color,
+    /// Editor to use for editing text, eg. comments. Takes precedence over `$VISUAL` and
+    /// `$EDITOR`.
+    pub editor: Option<String>,
+}
+
+/// Keystore configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Keys {
+    /// Number of `bcrypt-pbkdf` rounds used to encrypt secret keys. Higher values make
+    /// keys more expensive to brute-force, and slower to unseal. Keys encrypted with
+    /// other parameters can still be read. Defaults to
+    /// [`KdfParams::DEFAULT_ROUNDS`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kdf_rounds: Option<u32>,
+}
+
+/// Color preference.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Color {
+    /// Use colors when writing to a terminal.
+    #[default]
+    Auto,
+    /// Always use colors.
+    Always,
+    /// Never use colors.
+    Never,
+}
+
+/// A peer address, in the form `<node-id>@<address>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerAddress {
+    pub id: NodeId,
+    pub addr: Address,
+}
+
+impl FromStr for PeerAddress {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (id, addr) = s
+            .split_once('@')
+            .ok_or_else(|| Error::Invalid(format!("expected `<node-id>@<address>`, got `{s}`")))?;
+        let id = NodeId::from_str(id)
+            .map_err(|e| Error::Invalid(format!("invalid node id `{id}`: {e}")))?;
+        let addr = Address::from_str(addr)
+            .map_err(|e| Error::Invalid(format!("invalid address `{addr}`: {e}")))?;
+
+        Ok(Self { id, addr })
+    }
+}
+
+impl fmt::Display for PeerAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.id, self.addr)
+    }
+}
+
+impl Serialize for PeerAddress {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serde_ext::string::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PeerAddress {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        serde_ext::string::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_matches;
+
+    const SEED: &str = "z6MkrLMMsiPWUcNPHcRajuMi9mDfYckSoJyPwwnknocNYPm7@seed.radicle.xyz:8776";
+
+    #[test]
+    fn test_load_default() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = Config::load(&tmp.path().join("config.json")).unwrap();
+
+        assert_eq!(config, Config::default());
+        assert_eq!(config.httpd_listen(), ([0, 0, 0, 0], 8080).into());
+        assert_eq!(config.color(), Color::Auto);
+    }
+
+    #[test]
+    fn test_load_keys() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.json");
+
+        fs::write(&path, r#"{ "keys": { "kdfRounds": 128 } }"#).unwrap();
+
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.kdf_params(), Some(KdfParams::hardened()));
+
+        config.write(&path).unwrap();
+        assert_eq!(Config::load(&path).unwrap(), config);
+    }
+
+    #[test]
+    fn test_load_partial() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.json");
+
+        fs::write(
+            &path,
+            format!(r#"{{ "seeds": ["{SEED}"], "cli": {{ "color": "never" }} }}"#),
+        )
+        .unwrap();
+
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.seeds().len(), 1);
+        assert_eq!(config.seeds()[0].to_string(), SEED);
+        assert_eq!(config.connect().count(), 1);
+        assert_eq!(config.color(), Color::Never);
+        assert_eq!(config.editor(), None);
+        assert_eq!(config.httpd, Httpd::default());
+        assert_eq!(config.kdf_params(), None);
+
+        config.write(&path).unwrap();
+        assert_eq!(Config::load(&path).unwrap(), config);
+    }
+
+    #[test]
+    fn test_validate() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.json");
+
+        fs::write(&path, format!(r#"{{ "seeds": ["{SEED}", "{SEED}"] }}"#)).unwrap();
+        assert_matches!(Config::load(&path), Err(Error::Invalid(_)));
+
+        fs::write(&path, r#"{ "seeds": ["seed.radicle.xyz:8776"] }"#).unwrap();
+        assert_matches!(Config::load(&path), Err(Error::Json(_)));
+
+        fs::write(&path, r#"{ "cli": { "editor": " " } }"#).unwrap();
+        assert_matches!(Config::load(&path), Err(Error::Invalid(_)));
+
+        fs::write(&path, r#"{ "keys": { "kdfRounds": 0 } }"#).unwrap();
+        assert_matches!(Config::load(&path), Err(Error::Invalid(_)));
+    }
+}
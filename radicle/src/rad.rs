@@ -1,12 +1,14 @@
 #![allow(clippy::let_unit_value)]
-use std::io;
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::str::FromStr;
+use std::{fs, io};
 
 use once_cell::sync::Lazy;
 use thiserror::Error;
 
-use crate::crypto::{Signer, Verified};
+use crate::cob::patch::Patches;
+use crate::crypto::{PublicKey, Signer, Verified};
 use crate::git;
 use crate::identity::doc;
 use crate::identity::doc::{DocError, Id};
@@ -16,8 +18,8 @@ use crate::node::NodeId;
 use crate::storage::git::transport::{self, remote};
 use crate::storage::git::{ProjectError, Repository, Storage};
 use crate::storage::refs::SignedRefs;
-use crate::storage::{BranchName, ReadRepository as _, RemoteId, WriteRepository as _};
-use crate::{identity, storage};
+use crate::storage::{BranchName, ReadRepository as _, RefUpdate, RemoteId, WriteRepository as _};
+use crate::{cob, identity, storage};
 
 /// Name of the radicle storage remote.
 pub static REMOTE_NAME: Lazy<git::RefString> = Lazy::new(|| git::refname!("rad"));
@@ -116,12 +118,14 @@ pub enum ForkError {
 }
 
 /// Create a local tree for an existing project, from an existing remote.
-pub fn fork_remote<G: Signer, S: storage::WriteStorage>(
+///
+/// See [`fork`] for details.
+pub fn fork_remote<G: Signer, S: storage::WriteStorage<Repository = Repository>>(
     proj: Id,
     remote: &RemoteId,
     signer: &G,
     storage: S,
-) -> Result<(), ForkError> {
+) -> Result<SignedRefs<Verified>, ForkError> {
     // TODO: Copy tags over?
 
     // Creates or copies the following references:
@@ -131,7 +135,6 @@ pub fn fork_remote<G: Signer, S: storage::WriteStorage>(
     // refs/namespaces/<pk>/refs/rad/sigrefs
     // refs/namespaces/<pk>/refs/tags/*
 
-    let me = signer.public_key();
     let doc = storage
         .get(remote, proj)?
         .ok_or(ForkError::NotFound(proj))?;
@@ -139,57 +142,155 @@ pub fn fork_remote<G: Signer, S: storage::WriteStorage>(
     let repository = storage.repository(proj)?;
 
     let raw = repository.raw();
-    let remote_head = raw.refname_to_id(&git::refs::storage::branch(
+    let branch = git::Qualified::from(git::lit::refs_heads(project.default_branch()));
+    let head = raw.refname_to_id(&git::refs::storage::branch(
         remote,
         project.default_branch(),
     ))?;
-    raw.reference(
-        &git::refs::storage::branch(me, project.default_branch()),
-        remote_head,
-        false,
-        &format!("creating default branch for {me}"),
-    )?;
-
-    let remote_id = raw.refname_to_id(&git::refs::storage::id(remote))?;
-    raw.reference(
-        &git::refs::storage::id(me),
-        remote_id,
-        false,
-        &format!("creating identity branch for {me}"),
-    )?;
-
-    repository.sign_refs(signer)?;
+    let id = raw.refname_to_id(&git::refs::storage::id(remote))?;
 
-    Ok(())
+    fork_from(&repository, &branch, head.into(), id.into(), signer)
 }
 
-pub fn fork<G: Signer, S: storage::WriteStorage>(
+/// Create the signer's namespace for an existing project, ie. a local tree of the project
+/// that the signer can push to.
+///
+/// The signer's default branch is created from the canonical head of the project, ie. the
+/// head agreed upon by the project delegates, and its identity branch from the current
+/// project identity. The signer's refs are then signed, and returned.
+///
+/// Forking a project that was already forked by the signer leaves its existing refs as they
+/// are, and only re-signs them.
+pub fn fork<G: Signer, S: storage::WriteStorage<Repository = Repository>>(
     proj: Id,
     signer: &G,
     storage: &S,
-) -> Result<(), ForkError> {
-    let me = signer.public_key();
+) -> Result<SignedRefs<Verified>, ForkError> {
+    if !storage.contains(&proj)? {
+        return Err(ForkError::NotFound(proj));
+    }
     let repository = storage.repository(proj)?;
     // TODO: We should get the id branch pointer from a stored canonical reference.
     let (canonical_id, _) = repository.project_identity()?;
     let (canonical_branch, canonical_head) = repository.head()?;
+
+    fork_from(
+        &repository,
+        &canonical_branch,
+        canonical_head,
+        canonical_id,
+        signer,
+    )
+}
+
+/// Create the signer's default and identity branches, if they don't exist, and sign the
+/// signer's refs.
+fn fork_from<G: Signer>(
+    repository: &Repository,
+    branch: &git::Qualified,
+    head: git::Oid,
+    id: git::Oid,
+    signer: &G,
+) -> Result<SignedRefs<Verified>, ForkError> {
+    let me = signer.public_key();
     let raw = repository.raw();
 
-    raw.reference(
-        &canonical_branch.with_namespace(me.into()),
-        *canonical_head,
-        false,
-        &format!("creating default branch for {me}"),
-    )?;
-    raw.reference(
-        &git::refs::storage::id(me),
-        canonical_id.into(),
-        false,
-        &format!("creating identity branch for {me}"),
+    for (name, oid, what) in [
+        (branch.with_namespace(me.into()), head, "default branch"),
+        (git::refs::storage::id(me), id, "identity branch"),
+    ] {
+        match raw.find_reference(&name) {
+            Ok(_) => continue,
+            Err(e) if git::is_not_found_err(&e) => {
+                raw.reference(&name, *oid, false, &format!("creating {what} for {me}"))?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    repository.sign_refs(signer).map_err(ForkError::from)
+}
+
+#[derive(Error, Debug)]
+pub enum RotateError {
+    #[error("git: {0}")]
+    Git(#[from] git2::Error),
+    #[error("invalid reference: {0}")]
+    Ref(#[from] git::RefError),
+    #[error("storage: {0}")]
+    Storage(#[from] storage::Error),
+    #[error("project `{0}` was not found in storage")]
+    NotFound(Id),
+    #[error("project identity error: {0}")]
+    InvalidIdentity(#[from] storage::git::ProjectError),
+    #[error("project identity document error: {0}")]
+    Doc(#[from] DocError),
+    #[error("a threshold of {0} can't be reached by a single delegate")]
+    Threshold(usize),
+}
+
+/// Replace the `old` delegate key of a project with the `new` one.
+///
+/// The identity document is updated in the old key's namespace, with signatures from both
+/// keys: the new key's signature proves possession of it. The refs of the old key are then
+/// copied over to the new key's namespace, and both keys' refs are re-signed. The new key's
+/// signed refs are returned.
+///
+/// Since the old key is the only signer of the update, the project threshold must be `1`.
+pub fn rotate<G: Signer, H: Signer, S: storage::WriteStorage<Repository = Repository>>(
+    proj: Id,
+    old: &G,
+    new: &H,
+    storage: &S,
+) -> Result<SignedRefs<Verified>, RotateError> {
+    let (from, to) = (old.public_key(), new.public_key());
+    let mut doc = storage
+        .get(from, proj)?
+        .ok_or(RotateError::NotFound(proj))?;
+
+    if doc.threshold > 1 {
+        return Err(RotateError::Threshold(doc.threshold));
+    }
+    doc.rotate(from, to)?;
+
+    let repository = storage.repository(proj)?;
+    let raw = repository.raw();
+    let (_, old_sig) = doc.sign(old)?;
+    let (_, new_sig) = doc.sign(new)?;
+    let id = doc.update(
+        from,
+        &format!("Rotate delegate key {from} to {to}"),
+        &[(from, old_sig), (to, new_sig)],
+        raw,
     )?;
-    repository.sign_refs(signer)?;
 
-    Ok(())
+    for r in raw.references_glob(&format!("refs/namespaces/{from}/*"))? {
+        let r = r?;
+        let (Some(name), Some(oid)) = (r.name(), r.target()) else {
+            continue;
+        };
+        let (_, refname) = git::parse_ref_namespaced::<PublicKey>(name)?;
+        if refname == *git::refs::storage::SIGREFS_BRANCH {
+            continue;
+        }
+        let target = refname.with_namespace(to.into());
+        let msg = format!("rotating key {from} to {to}");
+
+        if refname == *git::refs::storage::IDENTITY_BRANCH {
+            // The new key's identity branch always points to the rotated document.
+            raw.reference(&target, *id, true, &msg)?;
+            continue;
+        }
+        match raw.find_reference(&target) {
+            Ok(_) => continue,
+            Err(e) if git::is_not_found_err(&e) => {
+                raw.reference(&target, oid, false, &msg)?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    repository.sign_refs(old)?;
+
+    repository.sign_refs(new).map_err(RotateError::from)
 }
 
 #[derive(Error, Debug)]
@@ -204,7 +305,12 @@ pub enum CloneError {
     Doc(#[from] DocError),
 }
 
-pub fn clone<P: AsRef<Path>, G: Signer, S: storage::WriteStorage, H: node::Handle>(
+pub fn clone<
+    P: AsRef<Path>,
+    G: Signer,
+    S: storage::WriteStorage<Repository = Repository>,
+    H: node::Handle,
+>(
     proj: Id,
     path: P,
     signer: &G,
@@ -236,7 +342,7 @@ pub enum CloneUrlError {
     Checkout(#[from] CheckoutError),
 }
 
-pub fn clone_url<P: AsRef<Path>, G: Signer, S: storage::WriteStorage>(
+pub fn clone_url<P: AsRef<Path>, G: Signer, S: storage::WriteStorage<Repository = Repository>>(
     url: &remote::Url,
     path: P,
     signer: &G,
@@ -265,18 +371,48 @@ pub enum CheckoutError {
     NotFound(Id),
     #[error("project error: {0}")]
     Project(#[from] ProjectError),
+    #[error("i/o error: {0}")]
+    Io(#[from] io::Error),
 }
 
 /// Checkout a project from storage as a working copy.
 /// This effectively does a `git-clone` from storage.
-pub fn checkout<P: AsRef<Path>, S: storage::ReadStorage>(
+///
+/// The default branch is set to the project's canonical head, as agreed upon by a quorum
+/// of delegates, if it is reachable from the given remote. Otherwise, it is set to the
+/// remote's default branch.
+pub fn checkout<P: AsRef<Path>, S: storage::WriteStorage<Repository = Repository>>(
     proj: Id,
     remote: &RemoteId,
     path: P,
     storage: &S,
+) -> Result<git2::Repository, CheckoutError> {
+    checkout_from(proj, remote, path, storage, false)
+}
+
+/// Checkout a project from storage as a lightweight working copy.
+///
+/// Unlike [`checkout`], objects are not copied into the working copy: instead, the working
+/// copy borrows the object database of the storage repository via git's "alternates"
+/// mechanism. This saves disk space and speeds up checkouts of large repositories, but
+/// the working copy is only usable as long as the project remains in storage.
+pub fn checkout_worktree<P: AsRef<Path>, S: storage::WriteStorage<Repository = Repository>>(
+    proj: Id,
+    remote: &RemoteId,
+    path: P,
+    storage: &S,
+) -> Result<git2::Repository, CheckoutError> {
+    checkout_from(proj, remote, path, storage, true)
+}
+
+fn checkout_from<P: AsRef<Path>, S: storage::WriteStorage<Repository = Repository>>(
+    proj: Id,
+    remote: &RemoteId,
+    path: P,
+    storage: &S,
+    shared: bool,
 ) -> Result<git2::Repository, CheckoutError> {
     // TODO: Decide on whether we can use `clone_local`
-    // TODO: Look into sharing object databases.
     let doc = storage
         .get(remote, proj)?
         .ok_or(CheckoutError::NotFound(proj))?;
@@ -288,6 +424,20 @@ pub fn checkout<P: AsRef<Path>, S: storage::ReadStorage>(
     let repo = git2::Repository::init_opts(path.as_ref().join(project.name()), &opts)?;
     let url = git::Url::from(proj).with_namespace(*remote);
 
+    if shared {
+        // Objects already in storage are found through the alternates file, so the fetch
+        // below only has to update references.
+        let objects = storage
+            .repository(proj)?
+            .path()
+            .join("objects")
+            .canonicalize()?;
+        let info = repo.path().join("objects").join("info");
+
+        fs::create_dir_all(&info)?;
+        fs::write(info.join("alternates"), format!("{}\n", objects.display()))?;
+    }
+
     // Configure and fetch all refs from remote.
     git::configure_remote(&repo, &REMOTE_NAME, &url)?;
     git::fetch(&repo, &REMOTE_NAME).map_err(CheckoutError::Fetch)?;
@@ -298,7 +448,13 @@ pub fn checkout<P: AsRef<Path>, S: storage::ReadStorage>(
             git::refs::workdir::remote_branch(&REMOTE_NAME, project.default_branch());
 
         let remote_head_commit = repo.find_reference(&remote_head_ref)?.peel_to_commit()?;
-        let _ = repo.branch(project.default_branch(), &remote_head_commit, true)?;
+        let canonical_head_commit = storage
+            .repository(proj)?
+            .canonical_head()
+            .ok()
+            .and_then(|(_, oid)| repo.find_commit(*oid).ok());
+        let head_commit = canonical_head_commit.unwrap_or(remote_head_commit);
+        let _ = repo.branch(project.default_branch(), &head_commit, true)?;
 
         // Setup remote tracking for default branch.
         git::set_upstream(
@@ -355,18 +511,110 @@ pub fn repo(path: impl AsRef<Path>) -> Result<(git2::Repository, Id), RemoteErro
     Ok((repo, id))
 }
 
+#[derive(Error, Debug)]
+pub enum PatchBranchesError {
+    #[error("git: {0}")]
+    Git(#[from] git2::Error),
+    #[error("store: {0}")]
+    Store(#[from] cob::store::Error),
+}
+
+/// Update the remote tracking branches of patches in a working copy.
+///
+/// Every patch that isn't archived gets a `refs/remotes/rad/patches/<id>` branch pointing
+/// to the head of its latest revision. If the revision isn't in the working copy yet, it
+/// is fetched from the revision author's namespace in storage. Branches of archived or
+/// removed patches are deleted.
+///
+/// Returns the branches that were updated.
+pub fn update_patch_branches(
+    working: &git2::Repository,
+    stored: &Repository,
+    whoami: PublicKey,
+) -> Result<Vec<RefUpdate>, PatchBranchesError> {
+    let prefix = git::refs::workdir::remote_branch(&REMOTE_NAME, &git::refname!("patches"));
+    let patches = Patches::open(whoami, stored)?;
+    let mut heads = BTreeMap::new();
+
+    for result in patches.all()? {
+        let (id, patch, _) = result?;
+        if patch.is_archived() {
+            continue;
+        }
+        if let Some((_, revision)) = patch.latest() {
+            let name = format!("{prefix}/{id}");
+            heads.insert(name, (*revision.author.id(), revision.oid));
+        }
+    }
+
+    let mut updates = Vec::new();
+    let existing = working
+        .references_glob(&format!("{prefix}/*"))?
+        .filter_map(|r| r.ok())
+        .filter_map(|r| Some((r.name()?.to_owned(), r.target()?)))
+        .collect::<Vec<_>>();
+
+    for (name, oid) in existing {
+        if heads.contains_key(&name) {
+            continue;
+        }
+        working.find_reference(&name)?.delete()?;
+
+        if let Ok(name) = git::RefString::try_from(name) {
+            updates.push(RefUpdate::Deleted {
+                name,
+                oid: oid.into(),
+            });
+        }
+    }
+
+    for (name, (author, oid)) in heads {
+        let old = working.refname_to_id(&name).ok();
+        if old == Some(*oid) {
+            continue;
+        }
+        if working.find_commit(*oid).is_err() {
+            let url = git::Url::from(stored.id).with_namespace(author);
+
+            working
+                .remote_anonymous(&url.to_string())?
+                .fetch(&["refs/heads/*"], None, None)?;
+
+            if working.find_commit(*oid).is_err() {
+                log::warn!("Patch revision {oid} could not be fetched from {author}");
+                continue;
+            }
+        }
+        working.reference(&name, *oid, true, "update patch branch (radicle)")?;
+
+        let Ok(name) = git::RefString::try_from(name) else {
+            continue;
+        };
+        updates.push(match old {
+            Some(old) => RefUpdate::Updated {
+                name,
+                old: old.into(),
+                new: oid,
+            },
+            None => RefUpdate::Created { name, oid },
+        });
+    }
+    Ok(updates)
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
     use radicle_crypto::test::signer::MockSigner;
 
+    use crate::assert_matches;
     use crate::git::{name::component, qualified};
-    use crate::identity::Did;
+    use crate::identity::{Did, Identity, Untrusted};
     use crate::storage::git::transport;
     use crate::storage::git::Storage;
     use crate::storage::{ReadStorage, WriteStorage};
-    use crate::test::fixtures;
+    use crate::test::{arbitrary, fixtures};
 
     use super::*;
 
@@ -445,9 +693,16 @@ mod tests {
         .unwrap();
 
         // Bob forks it and creates a checkout.
-        fork(id, &bob, &storage).unwrap();
+        let bob_refs = fork(id, &bob, &storage).unwrap();
         checkout(id, bob_id, tempdir.path().join("copy"), &storage).unwrap();
 
+        // Forking again leaves Bob's refs as they are.
+        assert_eq!(*fork(id, &bob, &storage).unwrap(), *bob_refs);
+        assert_matches!(
+            fork(arbitrary::gen::<Id>(1), &bob, &storage),
+            Err(ForkError::NotFound(_))
+        );
+
         let bob_remote = storage.repository(id).unwrap().remote(bob_id).unwrap();
 
         assert_eq!(
@@ -459,6 +714,51 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rotate() {
+        let mut rng = fastrand::Rng::new();
+        let tempdir = tempfile::tempdir().unwrap();
+        let old = MockSigner::new(&mut rng);
+        let new = MockSigner::new(&mut rng);
+        let storage = Storage::open(tempdir.path().join("storage")).unwrap();
+
+        transport::local::register(storage.clone());
+
+        let (original, _) = fixtures::repository(tempdir.path().join("original"));
+        let (id, _, old_refs) = init(
+            &original,
+            "acme",
+            "Acme's repo",
+            git::refname!("master"),
+            &old,
+            &storage,
+        )
+        .unwrap();
+
+        let new_refs = rotate(id, &old, &new, &storage).unwrap();
+        let doc = storage.get(new.public_key(), id).unwrap().unwrap();
+
+        assert_eq!(doc.delegates.first(), &Did::from(*new.public_key()));
+        assert_eq!(doc.delegates.len(), 1);
+        assert_eq!(
+            new_refs.get(&qualified!("refs/heads/master")),
+            old_refs.get(&qualified!("refs/heads/master"))
+        );
+
+        // The rotated identity is valid, and signed by both keys.
+        let repo = storage.repository(id).unwrap();
+        let identity = Identity::<Untrusted>::load(new.public_key(), &repo).unwrap();
+        assert_eq!(identity.doc, doc);
+        assert!(identity.signatures.contains_key(old.public_key()));
+        assert!(identity.signatures.contains_key(new.public_key()));
+
+        // The old key is no longer a delegate.
+        assert_matches!(
+            rotate(id, &old, &new, &storage),
+            Err(RotateError::Doc(DocError::Delegates(_)))
+        );
+    }
+
     #[test]
     fn test_checkout() {
         let tempdir = tempfile::tempdir().unwrap();
@@ -511,4 +811,118 @@ mod tests {
                 .collect::<Vec<_>>(),
         );
     }
+
+    #[test]
+    fn test_checkout_worktree() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let signer = MockSigner::default();
+        let remote_id = signer.public_key();
+        let storage = Storage::open(tempdir.path().join("storage")).unwrap();
+
+        transport::local::register(storage.clone());
+
+        let (original, _) = fixtures::repository(tempdir.path().join("original"));
+        let (id, _, _) = init(
+            &original,
+            "acme",
+            "Acme's repo",
+            git::refname!("master"),
+            &signer,
+            &storage,
+        )
+        .unwrap();
+
+        let copy = checkout_worktree(id, remote_id, tempdir.path().join("copy"), &storage).unwrap();
+        let alternates =
+            std::fs::read_to_string(copy.path().join("objects/info/alternates")).unwrap();
+
+        assert_eq!(
+            Path::new(alternates.trim()),
+            storage
+                .repository(id)
+                .unwrap()
+                .path()
+                .join("objects")
+                .canonicalize()
+                .unwrap()
+        );
+        assert_eq!(
+            copy.head().unwrap().target(),
+            original.head().unwrap().target()
+        );
+        assert_eq!(
+            copy.branch_upstream_name("refs/heads/master")
+                .unwrap()
+                .as_str(),
+            Some("refs/remotes/rad/master")
+        );
+        // No objects were copied into the working copy.
+        assert!(std::fs::read_dir(copy.path().join("objects/pack"))
+            .map(|mut d| d.next().is_none())
+            .unwrap_or(true));
+    }
+
+    #[test]
+    fn test_update_patch_branches() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let signer = MockSigner::default();
+        let remote_id = signer.public_key();
+        let storage = Storage::open(tempdir.path().join("storage")).unwrap();
+
+        transport::local::register(storage.clone());
+
+        let (original, base) = fixtures::repository(tempdir.path().join("original"));
+        let (id, _, _) = init(
+            &original,
+            "acme",
+            "Acme's repo",
+            git::refname!("master"),
+            &signer,
+            &storage,
+        )
+        .unwrap();
+        let copy = checkout(id, remote_id, tempdir.path().join("copy"), &storage).unwrap();
+
+        // Push a feature branch and propose it as a patch.
+        let feature = qualified!("refs/heads/feature");
+        let head = git::commit(
+            &original,
+            &original.find_commit(base).unwrap(),
+            &feature,
+            "Add feature",
+            &git2::Signature::now("anonymous", "anonymous@radicle.xyz").unwrap(),
+        )
+        .unwrap()
+        .id();
+        git::push(&original, &REMOTE_NAME, [(&feature, &feature)]).unwrap();
+
+        let stored = storage.repository(id).unwrap();
+        let mut patches = Patches::open(*remote_id, &stored).unwrap();
+        let patch = patches
+            .create(
+                "Feature",
+                "Adds a feature",
+                cob::patch::MergeTarget::Delegates,
+                base,
+                head,
+                &[],
+                &signer,
+            )
+            .unwrap();
+        let name = format!("refs/remotes/rad/patches/{}", patch.id);
+
+        assert!(copy.find_commit(head).is_err());
+
+        let updates = update_patch_branches(&copy, &stored, *remote_id).unwrap();
+        assert_matches!(
+            updates.as_slice(),
+            [RefUpdate::Created { name: n, oid }] if n.as_str() == name && *oid == git::Oid::from(head)
+        );
+        assert_eq!(copy.refname_to_id(&name).unwrap(), head);
+
+        // Nothing changed, so nothing is updated.
+        assert!(update_patch_branches(&copy, &stored, *remote_id)
+            .unwrap()
+            .is_empty());
+    }
 }
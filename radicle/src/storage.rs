@@ -1,12 +1,15 @@
 pub mod git;
+pub mod hooks;
+#[cfg(feature = "sql")]
+pub mod index;
 pub mod refs;
 
 use std::collections::hash_map;
-use std::ops::Deref;
+use std::ops::{AddAssign, Deref};
 use std::path::Path;
 use std::{fmt, io};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crypto::{PublicKey, Signer, Unverified, Verified};
@@ -91,8 +94,59 @@ pub enum FetchError {
 
 pub type RemoteId = PublicKey;
 
-/// An update to a reference.
+/// On-disk size of a repository, or of all repositories in storage, in bytes.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct Size {
+    /// Size of the object database, loose objects and packs.
+    pub objects: u64,
+    /// Size of references, loose and packed.
+    pub refs: u64,
+    /// Uncompressed size of the objects reachable from collaborative object references.
+    /// These objects are also accounted for in `objects`.
+    pub cobs: u64,
+}
+
+impl Size {
+    /// Total size on disk.
+    pub fn total(&self) -> u64 {
+        self.objects + self.refs
+    }
+}
+
+impl AddAssign for Size {
+    fn add_assign(&mut self, other: Self) {
+        self.objects += other.objects;
+        self.refs += other.refs;
+        self.cobs += other.cobs;
+    }
+}
+
+/// A blob read from a repository.
 #[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blob {
+    id: Oid,
+    content: Vec<u8>,
+}
+
+impl Blob {
+    pub fn new(id: Oid, content: Vec<u8>) -> Self {
+        Self { id, content }
+    }
+
+    /// The object id of the blob.
+    pub fn id(&self) -> Oid {
+        self.id
+    }
+
+    /// The content of the blob.
+    pub fn content(&self) -> &[u8] {
+        &self.content
+    }
+}
+
+/// An update to a reference.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
 pub enum RefUpdate {
     Updated { name: RefString, old: Oid, new: Oid },
     Created { name: RefString, oid: Oid },
@@ -243,30 +297,64 @@ impl Remote<Verified> {
     }
 }
 
+/// Read access to a storage backend.
+///
+/// Storage traits make no assumption about how repositories are laid out: eg. a backend
+/// may keep one git directory per repository, like [`git::Storage`], share a single object
+/// store between repositories, or be backed by a remote service.
 pub trait ReadStorage {
-    fn path(&self) -> &Path;
+    /// Get the identity document of a repository, as published by the given remote.
     fn get(
         &self,
         remote: &RemoteId,
         proj: Id,
     ) -> Result<Option<identity::Doc<Verified>>, ProjectError>;
+    /// List the repositories in storage.
     fn inventory(&self) -> Result<Inventory, Error>;
+    /// Whether the storage contains the given repository.
+    fn contains(&self, proj: &Id) -> Result<bool, Error> {
+        Ok(self.inventory()?.contains(proj))
+    }
 }
 
+/// Write access to a storage backend.
 pub trait WriteStorage: ReadStorage {
     type Repository: WriteRepository;
 
+    /// Open a repository, creating it if it doesn't exist.
     fn repository(&self, proj: Id) -> Result<Self::Repository, Error>;
+    /// Remove a repository from storage.
+    fn remove(&self, proj: Id) -> Result<(), Error>;
+
+    /// Compute the on-disk size of every repository in storage.
+    fn sizes(&self) -> Result<Vec<(Id, Size)>, Error> {
+        self.inventory()?
+            .into_iter()
+            .map(|id| Ok((id, self.repository(id)?.size()?)))
+            .collect()
+    }
+
+    /// Compute the total on-disk size of storage.
+    fn size(&self) -> Result<Size, Error> {
+        let mut total = Size::default();
+        for (_, size) in self.sizes()? {
+            total += size;
+        }
+        Ok(total)
+    }
 }
 
+/// Read access to a repository. Like the storage traits, this doesn't expose the
+/// underlying git library, so that it can be implemented by any backend.
 pub trait ReadRepository {
-    /// Returns `true` if there are no references in the repository.
-    fn is_empty(&self) -> Result<bool, git2::Error>;
+    /// The repository identifier.
+    fn id(&self) -> Id;
 
-    /// The [`Path`] to the git repository.
-    fn path(&self) -> &Path;
+    /// Returns `true` if there are no references in the repository.
+    fn is_empty(&self) -> Result<bool, Error>;
 
-    fn blob_at<'a>(&'a self, oid: Oid, path: &'a Path) -> Result<git2::Blob<'a>, git_ext::Error>;
+    /// Get the blob at the given path, in the tree of the given commit.
+    fn blob_at(&self, commit: Oid, path: &Path) -> Result<Blob, git_ext::Error>;
 
     /// Get the head of this repository.
     ///
@@ -283,21 +371,12 @@ pub trait ReadRepository {
     /// Returns the [`Oid`] as well as the qualified reference name.
     fn canonical_head(&self) -> Result<(Qualified, Oid), ProjectError>;
 
-    /// Get the `reference` for the given `remote`.
-    ///
-    /// Returns `None` is the reference did not exist.
-    fn reference(
-        &self,
-        remote: &RemoteId,
-        reference: &Qualified,
-    ) -> Result<git2::Reference, git_ext::Error>;
+    /// Get the raw message of the commit with the given `oid`.
+    fn commit_message(&self, oid: Oid) -> Result<Vec<u8>, git_ext::Error>;
 
-    /// Get the [`git2::Commit`] found using its `oid`.
-    ///
-    /// Returns `None` if the commit did not exist.
-    fn commit(&self, oid: Oid) -> Result<git2::Commit, git_ext::Error>;
+    /// Get the history of the given commit, ie. the commit and its ancestors, latest first.
+    fn history(&self, head: Oid) -> Result<Vec<Oid>, git_ext::Error>;
 
-    fn revwalk(&self, head: Oid) -> Result<git2::Revwalk, git2::Error>;
     fn reference_oid(
         &self,
         remote: &RemoteId,
@@ -307,8 +386,16 @@ pub trait ReadRepository {
     fn remote(&self, remote: &RemoteId) -> Result<Remote<Verified>, refs::Error>;
     fn remotes(&self) -> Result<Remotes<Verified>, refs::Error>;
     /// Return the project associated with this repository.
-    fn project(&self) -> Result<identity::Doc<Verified>, Error>;
+    fn project(&self) -> Result<identity::Doc<Verified>, ProjectError>;
     fn project_identity(&self) -> Result<(Oid, identity::Doc<Unverified>), ProjectError>;
+    /// Compute the on-disk size of the repository.
+    fn size(&self) -> Result<Size, Error>;
+    /// Compute the total on-disk size of the repository, ie. [`Size::total`]. Unlike
+    /// [`ReadRepository::size`], collaborative objects are not accounted for separately,
+    /// which would require walking their history.
+    fn disk_size(&self) -> Result<u64, Error> {
+        self.size().map(|s| s.total())
+    }
 }
 
 pub trait WriteRepository: ReadRepository {
@@ -319,7 +406,6 @@ pub trait WriteRepository: ReadRepository {
     ) -> Result<Vec<RefUpdate>, FetchError>;
     fn set_head(&self) -> Result<Oid, ProjectError>;
     fn sign_refs<G: Signer>(&self, signer: &G) -> Result<SignedRefs<Verified>, Error>;
-    fn raw(&self) -> &git2::Repository;
 }
 
 impl<T, S> ReadStorage for T
@@ -327,14 +413,14 @@ where
     T: Deref<Target = S>,
     S: ReadStorage + 'static,
 {
-    fn path(&self) -> &Path {
-        self.deref().path()
-    }
-
     fn inventory(&self) -> Result<Inventory, Error> {
         self.deref().inventory()
     }
 
+    fn contains(&self, proj: &Id) -> Result<bool, Error> {
+        self.deref().contains(proj)
+    }
+
     fn get(
         &self,
         remote: &RemoteId,
@@ -354,10 +440,85 @@ where
     fn repository(&self, proj: Id) -> Result<Self::Repository, Error> {
         self.deref().repository(proj)
     }
+
+    fn remove(&self, proj: Id) -> Result<(), Error> {
+        self.deref().remove(proj)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crypto::test::signer::MockSigner;
+
+    use super::*;
+    use crate::identity::doc::{Doc, PATH};
+    use crate::test::arbitrary;
+    use crate::test::fixtures;
+    use crate::test::storage::MockStorage;
+
     #[test]
-    fn test_storage() {}
+    fn test_storage() {
+        let tmp = tempfile::tempdir().unwrap();
+        let signer = MockSigner::default();
+        let storage = fixtures::storage(tmp.path(), &signer).unwrap();
+        let id = storage.inventory().unwrap()[0];
+        let repo = storage.repository(id).unwrap();
+        let remote = signer.public_key();
+
+        assert!(storage.contains(&id).unwrap());
+        assert!(!repo.is_empty().unwrap());
+
+        let doc = repo.project().unwrap();
+        let head = Doc::<Verified>::head(remote, &repo).unwrap();
+        let history = repo.history(head).unwrap();
+        let revwalk = repo
+            .revwalk(head)
+            .unwrap()
+            .map(|oid| Oid::from(oid.unwrap()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(history.first(), Some(&head));
+        assert_eq!(history, revwalk);
+        assert!(!repo.commit_message(head).unwrap().is_empty());
+
+        let blob = repo.blob_at(head, *PATH).unwrap();
+        assert_eq!(
+            Doc::from_json(blob.content()).unwrap().verified().unwrap(),
+            doc
+        );
+        assert_eq!(repo.project_identity().unwrap().0, head);
+    }
+
+    #[test]
+    fn test_mock_storage() {
+        let tmp = tempfile::tempdir().unwrap();
+        let signer = MockSigner::default();
+        let storage = fixtures::storage(tmp.path(), &signer).unwrap();
+        let id = storage.inventory().unwrap()[0];
+        let doc = storage.repository(id).unwrap().project().unwrap();
+        let mock = MockStorage::new(vec![(id, doc.clone())]);
+        let repo = mock.repository(id).unwrap();
+
+        assert!(mock.contains(&id).unwrap());
+        assert_eq!(repo.id(), id);
+        assert!(!repo.is_empty().unwrap());
+        assert_eq!(repo.project().unwrap(), doc);
+        assert_eq!(repo.project_identity().unwrap().1.verified().unwrap(), doc);
+        assert!(repo.remotes().unwrap().is_empty());
+        assert!(repo.remote(signer.public_key()).unwrap_err().is_not_found());
+        assert!(repo.canonical_head().is_err());
+        assert!(repo
+            .sign_refs(&signer)
+            .unwrap()
+            .unverified()
+            .verify(signer.public_key())
+            .is_ok());
+
+        let unknown = arbitrary::gen::<Id>(1);
+        let repo = mock.repository(unknown).unwrap();
+
+        assert!(!mock.contains(&unknown).unwrap());
+        assert!(repo.is_empty().unwrap());
+        assert!(repo.project().is_err());
+    }
 }
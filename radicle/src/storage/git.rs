@@ -1,9 +1,12 @@
+pub mod bundle;
 pub mod cob;
+pub mod migrate;
+pub mod mirror;
 pub mod transport;
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::{fs, io};
+use std::{fs, io, time};
 
 use crypto::{Signer, Unverified, Verified};
 use git_ref_format::refspec;
@@ -13,11 +16,13 @@ use crate::git;
 use crate::identity;
 use crate::identity::{doc, Doc, Id};
 use crate::identity::{Identity, IdentityError, Project};
+use crate::storage::hooks;
+use crate::storage::hooks::Hooks;
 use crate::storage::refs;
 use crate::storage::refs::{Refs, SignedRefs};
 use crate::storage::{
-    Error, FetchError, Inventory, ReadRepository, ReadStorage, Remote, Remotes, WriteRepository,
-    WriteStorage,
+    Blob, Error, FetchError, Inventory, ReadRepository, ReadStorage, Remote, Remotes, Size,
+    WriteRepository, WriteStorage,
 };
 
 pub use crate::git::*;
@@ -25,6 +30,11 @@ pub use crate::git::*;
 use super::{Namespaces, RefUpdate, RemoteId};
 use transport::remote;
 
+/// Prefix under which quarantined remotes are kept.
+pub const QUARANTINE_PREFIX: &str = "refs/quarantine";
+/// Repository configuration key set when the repository needs pruning.
+pub const PRUNE_PENDING_KEY: &str = "radicle.prunePending";
+
 pub static NAMESPACES_GLOB: Lazy<refspec::PatternString> =
     Lazy::new(|| refspec::pattern!("refs/namespaces/*"));
 pub static SIGREFS_GLOB: Lazy<refspec::PatternString> =
@@ -49,6 +59,8 @@ pub enum ProjectError {
     GitExt(#[from] git::Error),
     #[error("refs: {0}")]
     Refs(#[from] refs::Error),
+    #[error("quorum: {0}")]
+    Quorum(#[from] QuorumError),
 }
 
 impl ProjectError {
@@ -64,13 +76,10 @@ impl ProjectError {
 #[derive(Debug, Clone)]
 pub struct Storage {
     path: PathBuf,
+    hooks: Hooks,
 }
 
 impl ReadStorage for Storage {
-    fn path(&self) -> &Path {
-        self.path.as_path()
-    }
-
     fn get(&self, remote: &RemoteId, proj: Id) -> Result<Option<Doc<Verified>>, ProjectError> {
         // TODO: Don't create a repo here if it doesn't exist?
         // Perhaps for checking we could have a `contains` method?
@@ -85,13 +94,30 @@ impl ReadStorage for Storage {
     fn inventory(&self) -> Result<Inventory, Error> {
         self.projects()
     }
+
+    fn contains(&self, proj: &Id) -> Result<bool, Error> {
+        Ok(paths::repository(self, proj).exists())
+    }
 }
 
 impl WriteStorage for Storage {
     type Repository = Repository;
 
     fn repository(&self, proj: Id) -> Result<Self::Repository, Error> {
-        Repository::open(paths::repository(self, &proj), proj)
+        let mut repo = Repository::open(paths::repository(self, &proj), proj)?;
+        repo.hooks = self.hooks.clone();
+
+        Ok(repo)
+    }
+
+    fn remove(&self, proj: Id) -> Result<(), Error> {
+        match fs::remove_dir_all(paths::repository(self, &proj)) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            result => result?,
+        }
+        self.hooks.removed(proj);
+
+        Ok(())
     }
 }
 
@@ -99,14 +125,33 @@ impl Storage {
     // TODO: Return a better error when not found.
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
         let path = path.as_ref().to_path_buf();
+        let exists = path.exists();
 
         match fs::create_dir_all(&path) {
             Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
             Err(err) => return Err(err),
             Ok(()) => {}
         }
+        // New storage doesn't need migrating.
+        if !exists {
+            migrate::write_version(&path, migrate::current())?;
+        }
+
+        Ok(Self {
+            path,
+            hooks: Hooks::default(),
+        })
+    }
+
+    /// Use the given hooks for reference updates in this storage.
+    pub fn with_hooks(mut self, hooks: Hooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
 
-        Ok(Self { path })
+    /// Hooks run on reference updates.
+    pub fn hooks(&self) -> &Hooks {
+        &self.hooks
     }
 
     pub fn path(&self) -> &Path {
@@ -118,6 +163,10 @@ impl Storage {
 
         for result in fs::read_dir(&self.path)? {
             let path = result?;
+            // Skip storage metadata, eg. the version file.
+            if path.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
             let id = Id::try_from(path.file_name())?;
 
             projects.push(id);
@@ -144,6 +193,7 @@ impl Storage {
 pub struct Repository {
     pub id: Id,
     pub(crate) backend: git2::Repository,
+    hooks: Hooks,
 }
 
 #[derive(Debug, Error)]
@@ -160,8 +210,26 @@ pub enum VerifyError {
     UnknownRef(RemoteId, git::RefString),
     #[error("missing reference `{1}` in remote `{0}`")]
     MissingRef(RemoteId, git::RefString),
+    #[error("refs of remote `{0}` were signed after its key was revoked")]
+    Revoked(RemoteId),
     #[error("git: {0}")]
     Git(#[from] git2::Error),
+    #[error("git: {0}")]
+    GitExt(#[from] git::Error),
+}
+
+impl VerifyError {
+    /// The remote that failed verification, if the error is specific to a remote.
+    pub fn remote(&self) -> Option<&RemoteId> {
+        match self {
+            Self::InvalidRemote(remote)
+            | Self::InvalidRefTarget(remote, _, _)
+            | Self::UnknownRef(remote, _)
+            | Self::MissingRef(remote, _)
+            | Self::Revoked(remote) => Some(remote),
+            _ => None,
+        }
+    }
 }
 
 impl Repository {
@@ -187,7 +255,46 @@ impl Repository {
             Err(e) => Err(e),
         }?;
 
-        Ok(Self { id, backend })
+        Ok(Self {
+            id,
+            backend,
+            hooks: Hooks::default(),
+        })
+    }
+
+    /// The path to the git directory of the repository.
+    pub fn path(&self) -> &Path {
+        self.backend.path()
+    }
+
+    /// The underlying git repository.
+    pub fn raw(&self) -> &git2::Repository {
+        &self.backend
+    }
+
+    /// Get the `reference` for the given `remote`.
+    pub fn reference(
+        &self,
+        remote: &RemoteId,
+        name: &git::Qualified,
+    ) -> Result<git2::Reference, git::Error> {
+        let name = name.with_namespace(remote.into());
+        self.backend.find_reference(&name).map_err(git::Error::from)
+    }
+
+    /// Get the commit with the given `oid`.
+    pub fn commit(&self, oid: Oid) -> Result<git2::Commit, git::Error> {
+        self.backend
+            .find_commit(oid.into())
+            .map_err(git::Error::from)
+    }
+
+    /// Walk the history of the given commit.
+    pub fn revwalk(&self, head: Oid) -> Result<git2::Revwalk, git2::Error> {
+        let mut revwalk = self.backend.revwalk()?;
+        revwalk.push(head.into())?;
+
+        Ok(revwalk)
     }
 
     /// Create the repository's identity branch.
@@ -212,8 +319,16 @@ impl Repository {
 
     /// Verify all references in the repository, checking that they are signed
     /// as part of 'sigrefs'. Also verify that no signed reference is missing
-    /// from the repository.
+    /// from the repository, and that no refs were signed with a revoked key.
     pub fn verify(&self) -> Result<(), VerifyError> {
+        // Revocations are taken from the canonical identity document. If it can't be
+        // loaded, the identity checks below will fail.
+        let revocations = self
+            .identity_doc()
+            .ok()
+            .and_then(|(_, doc)| doc.revocations().ok())
+            .unwrap_or_default();
+
         let mut remotes: HashMap<RemoteId, Refs> = self
             .remotes()?
             .map(|remote| {
@@ -245,11 +360,177 @@ impl Repository {
             }
             // Verify identity history of remote.
             self.identity(&remote)?.verified(self.id)?;
+
+            // Refs signed by a revoked key are only accepted if they were signed before
+            // the revocation took effect. Since the signing time is chosen by the signer,
+            // this is only a fallback for remotes we have no prior state of: see
+            // `Repository::verify_frozen`.
+            if revocations.is_revoked(&remote) {
+                let sigrefs = self.commit(self.reference_oid(&remote, &refs::SIGREFS_BRANCH)?)?;
+                let timestamp = sigrefs.time().seconds().max(0) as u64;
+
+                if !revocations.is_valid_at(&remote, timestamp) {
+                    return Err(VerifyError::Revoked(remote));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that the refs of remotes whose keys are revoked in this repository's identity
+    /// document are unchanged in `other`.
+    ///
+    /// Once we know of a revocation, the refs of the revoked remote are frozen at the state
+    /// we have, regardless of when they claim to have been signed, so that a compromised
+    /// key can't be used to publish backdated refs.
+    pub fn verify_frozen(&self, other: &Repository) -> Result<(), VerifyError> {
+        let revocations = self
+            .identity_doc()
+            .ok()
+            .and_then(|(_, doc)| doc.revocations().ok())
+            .unwrap_or_default();
+
+        for r in revocations.iter() {
+            let remote = r.revocation.key;
+            let Ok(ours) = self.reference_oid(&remote, &refs::SIGREFS_BRANCH) else {
+                continue;
+            };
+            match other.reference_oid(&remote, &refs::SIGREFS_BRANCH) {
+                Ok(theirs) if theirs != ours => return Err(VerifyError::Revoked(remote)),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Count the loose objects in the repository, and their total size in bytes.
+    pub fn loose_objects(&self) -> Result<(usize, u64), io::Error> {
+        let mut count = 0;
+        let mut size = 0;
+
+        for entry in fs::read_dir(self.backend.path().join("objects"))? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+
+            // Loose objects are stored in directories named after the first two
+            // hex digits of their hash.
+            if name.len() != 2 || !name.chars().all(|c| c.is_ascii_hexdigit()) {
+                continue;
+            }
+            for object in fs::read_dir(entry.path())? {
+                count += 1;
+                size += object?.metadata()?.len();
+            }
+        }
+        Ok((count, size))
+    }
+
+    /// Pack loose objects and consolidate packs, using `git gc`.
+    ///
+    /// Unreachable objects are only pruned once they are older than git's prune
+    /// expiry, so objects written by a concurrent fetch are not removed.
+    pub fn gc(&self) -> Result<(), io::Error> {
+        git::run(
+            self.path(),
+            ["gc", "--quiet"],
+            std::iter::empty::<(&str, &str)>(),
+        )
+        .map(|_| ())
+    }
+
+    /// Remove a remote's namespace from the repository. The remote's objects are not
+    /// removed from the object database, but the repository is marked for pruning,
+    /// see [`Repository::prune`].
+    pub fn remove_remote(&self, remote: &RemoteId) -> Result<(), Error> {
+        let mut updates = Vec::new();
+
+        for r in self
+            .backend
+            .references_glob(&format!("refs/namespaces/{remote}/*"))?
+        {
+            let mut r = r?;
+            let name = r.name().ok_or(Error::InvalidRef)?;
+            let (_, name) = git::parse_ref_namespaced::<RemoteId>(name)?;
+            let name = name.to_ref_string();
+
+            if let Some(oid) = r.target() {
+                updates.push(RefUpdate::Deleted {
+                    name,
+                    oid: oid.into(),
+                });
+            }
+            r.delete()?;
         }
+        self.backend
+            .config()?
+            .open_level(git2::ConfigLevel::Local)?
+            .set_bool(PRUNE_PENDING_KEY, true)?;
+        self.hooks.run(self.id, remote, &updates);
 
         Ok(())
     }
 
+    /// Move a remote's namespace out of the way, so that its references are no longer
+    /// served or verified, but are kept for inspection. The references of remote `R` are
+    /// moved from `refs/namespaces/R` to `refs/quarantine/R`.
+    /// Returns the number of references moved.
+    pub fn quarantine_remote(&self, remote: &RemoteId) -> Result<usize, Error> {
+        let prefix = format!("refs/namespaces/{remote}/");
+        let mut count = 0;
+
+        for r in self.backend.references_glob(&format!("{prefix}*"))? {
+            let mut r = r?;
+            let name = r.name().ok_or(Error::InvalidRef)?;
+            let name = name.strip_prefix(&prefix).ok_or(Error::InvalidRef)?;
+            let target = format!("{QUARANTINE_PREFIX}/{remote}/{name}");
+
+            r.rename(&target, true, "quarantine")?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Whether objects were possibly left unreachable by the removal of a remote, and the
+    /// repository should be pruned.
+    pub fn is_prune_pending(&self) -> Result<bool, git2::Error> {
+        match self.backend.config()?.get_bool(PRUNE_PENDING_KEY) {
+            Ok(pending) => Ok(pending),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Remove objects that aren't reachable from any reference, using `git repack`
+    /// and `git prune`.
+    ///
+    /// Unreachable objects are only removed once they are older than `expiry`, so that
+    /// objects written by a concurrent process, eg. a fetch, or a `git push` to the
+    /// working copy's remote, are not removed before they are referenced. Unreachable
+    /// objects that are younger are left behind, to be removed by [`Repository::gc`].
+    pub fn prune(&self, expiry: time::Duration) -> Result<(), io::Error> {
+        let env = std::iter::empty::<(&str, &str)>;
+        let expire = format!("--expire={}.seconds.ago", expiry.as_secs());
+
+        // Unreachable objects are unpacked rather than dropped, so that they can be
+        // expired by `git prune`, based on their age.
+        git::run(self.path(), ["repack", "-A", "-d", "-q"], env())?;
+        git::run(self.path(), ["prune", expire.as_str()], env())?;
+
+        match self
+            .backend
+            .config()
+            .and_then(|c| c.open_level(git2::ConfigLevel::Local))
+            .and_then(|mut c| c.remove(PRUNE_PENDING_KEY))
+        {
+            Err(e) if e.code() != git2::ErrorCode::NotFound => {
+                Err(io::Error::new(io::ErrorKind::Other, e))
+            }
+            _ => Ok(()),
+        }
+    }
+
     pub fn inspect(&self) -> Result<(), Error> {
         for r in self.backend.references()? {
             let r = r?;
@@ -365,6 +646,36 @@ impl Repository {
         Ok(remotes)
     }
 
+    /// Uncompressed size of all objects reachable from collaborative object references.
+    fn cobs_size(&self) -> Result<u64, git2::Error> {
+        let odb = self.backend.odb()?;
+        let mut walk = self.backend.revwalk()?;
+        let mut seen = HashSet::new();
+        let mut size = 0;
+
+        walk.push_glob("refs/namespaces/*/refs/cobs/*")?;
+
+        for oid in walk {
+            let commit = self.backend.find_commit(oid?)?;
+            let tree = commit.tree()?;
+
+            for oid in [commit.id(), tree.id()] {
+                if seen.insert(oid) {
+                    size += odb.read_header(oid)?.0 as u64;
+                }
+            }
+            tree.walk(git2::TreeWalkMode::PreOrder, |_, entry| {
+                if seen.insert(entry.id()) {
+                    if let Ok((len, _)) = odb.read_header(entry.id()) {
+                        size += len as u64;
+                    }
+                }
+                git2::TreeWalkResult::Ok
+            })?;
+        }
+        Ok(size)
+    }
+
     /// Return all references that are namespaced, ie. that are signed by a node and verified.
     fn namespaced_references(
         &self,
@@ -394,29 +705,22 @@ impl Repository {
 }
 
 impl ReadRepository for Repository {
-    fn is_empty(&self) -> Result<bool, git2::Error> {
-        Ok(self.remotes()?.next().is_none())
+    fn id(&self) -> Id {
+        self.id
     }
 
-    fn path(&self) -> &Path {
-        self.backend.path()
+    fn is_empty(&self) -> Result<bool, Error> {
+        Ok(self.remotes()?.next().is_none())
     }
 
-    fn blob_at<'a>(&'a self, oid: Oid, path: &'a Path) -> Result<git2::Blob<'a>, git::Error> {
-        git::ext::Blob::At {
-            object: oid.into(),
+    fn blob_at(&self, commit: Oid, path: &Path) -> Result<Blob, git::Error> {
+        let blob = git::ext::Blob::At {
+            object: commit.into(),
             path,
         }
-        .get(&self.backend)
-    }
+        .get(&self.backend)?;
 
-    fn reference(
-        &self,
-        remote: &RemoteId,
-        name: &git::Qualified,
-    ) -> Result<git2::Reference, git::Error> {
-        let name = name.with_namespace(remote.into());
-        self.backend.find_reference(&name).map_err(git::Error::from)
+        Ok(Blob::new(blob.id().into(), blob.content().to_vec()))
     }
 
     fn reference_oid(
@@ -430,17 +734,16 @@ impl ReadRepository for Repository {
         Ok(oid.into())
     }
 
-    fn commit(&self, oid: Oid) -> Result<git2::Commit, git::Error> {
-        self.backend
-            .find_commit(oid.into())
-            .map_err(git::Error::from)
-    }
+    fn commit_message(&self, oid: Oid) -> Result<Vec<u8>, git::Error> {
+        let commit = self.commit(oid)?;
 
-    fn revwalk(&self, head: Oid) -> Result<git2::Revwalk, git2::Error> {
-        let mut revwalk = self.backend.revwalk()?;
-        revwalk.push(head.into())?;
+        Ok(commit.message_bytes().to_vec())
+    }
 
-        Ok(revwalk)
+    fn history(&self, head: Oid) -> Result<Vec<Oid>, git::Error> {
+        self.revwalk(head)?
+            .map(|oid| oid.map(Oid::from).map_err(git::Error::from))
+            .collect()
     }
 
     fn remote(&self, remote: &RemoteId) -> Result<Remote<Verified>, refs::Error> {
@@ -474,14 +777,37 @@ impl ReadRepository for Repository {
         Ok(Remotes::from_iter(remotes))
     }
 
-    fn project(&self) -> Result<Doc<Verified>, Error> {
-        todo!()
+    fn project(&self) -> Result<Doc<Verified>, ProjectError> {
+        let (_, doc) = self.identity_doc()?;
+
+        Ok(doc.verified()?)
     }
 
     fn project_identity(&self) -> Result<(Oid, identity::Doc<Unverified>), ProjectError> {
         Repository::identity_doc(self)
     }
 
+    fn size(&self) -> Result<Size, Error> {
+        let path = self.backend.path();
+        let objects = paths::size(&path.join("objects"))?;
+        let refs = paths::size(&path.join("refs"))? + paths::size(&path.join("packed-refs"))?;
+        let cobs = self.cobs_size()?;
+
+        Ok(Size {
+            objects,
+            refs,
+            cobs,
+        })
+    }
+
+    fn disk_size(&self) -> Result<u64, Error> {
+        let path = self.backend.path();
+        let objects = paths::size(&path.join("objects"))?;
+        let refs = paths::size(&path.join("refs"))? + paths::size(&path.join("packed-refs"))?;
+
+        Ok(objects + refs)
+    }
+
     fn head(&self) -> Result<(Qualified, Oid), ProjectError> {
         // If `HEAD` is already set locally, just return that.
         if let Ok(head) = self.backend.head() {
@@ -503,16 +829,14 @@ impl ReadRepository for Repository {
 
         let mut heads = Vec::new();
         for delegate in doc.delegates.iter() {
-            let r = self.reference_oid(delegate, &branch_ref)?.into();
-
-            heads.push(r);
+            // Delegates without a default branch don't count towards the quorum.
+            match self.reference_oid(delegate, &branch_ref) {
+                Ok(r) => heads.push(*r),
+                Err(git::Error::Git(e)) if git::is_not_found_err(&e) => continue,
+                Err(e) => return Err(e.into()),
+            }
         }
-
-        let oid = match heads.as_slice() {
-            [head] => Ok(*head),
-            // FIXME: This branch is not tested.
-            heads => raw.merge_base_many(heads),
-        }?;
+        let oid = quorum(&heads, doc.threshold, raw)?;
 
         Ok((branch_ref, oid.into()))
     }
@@ -601,11 +925,13 @@ impl WriteRepository for Repository {
                 .fetch(&["refs/*:refs/*"], Some(&mut opts), None)?;
 
             // Verify the staging copy as if it was the canonical copy.
-            Repository {
+            let staging = Repository {
                 id: self.id,
                 backend: staging_repo,
-            }
-            .verify()?;
+                hooks: Hooks::default(),
+            };
+            staging.verify()?;
+            self.verify_frozen(&staging)?;
 
             path
         };
@@ -643,6 +969,7 @@ impl WriteRepository for Repository {
         }
         // Set repository HEAD for git cloning support.
         self.set_head()?;
+        self.hooks.run_namespaced(self.id, &updates);
 
         Ok(updates)
     }
@@ -665,16 +992,105 @@ impl WriteRepository for Repository {
     fn sign_refs<G: Signer>(&self, signer: &G) -> Result<SignedRefs<Verified>, Error> {
         let remote = signer.public_key();
         let refs = self.references(remote)?;
+        let old = self
+            .remote(remote)
+            .map(|r| Refs::from(r.refs))
+            .unwrap_or_default();
         let signed = refs.signed(signer)?;
 
         signed.save(remote, self)?;
+        self.hooks.run(self.id, remote, &hooks::diff(&old, &signed));
 
         Ok(signed)
     }
+}
 
-    fn raw(&self) -> &git2::Repository {
-        &self.backend
+#[derive(Error, Debug)]
+pub enum QuorumError {
+    #[error("no commit is agreed upon by {threshold} of {heads} delegate head(s)")]
+    NoQuorum { threshold: usize, heads: usize },
+    #[error("delegate heads have no common ancestor")]
+    Diverged,
+    #[error("git: {0}")]
+    Git(#[from] git2::Error),
+}
+
+/// Compute the quorum commit of a set of delegate heads, given a signature threshold.
+///
+/// The quorum commit is the latest commit that at least `threshold` heads either point to,
+/// or descend from. Candidates are the heads themselves and their merge bases. If more than
+/// one candidate reaches the threshold without one descending from the others, ie. there
+/// are competing quorums on diverging branches, their merge base is returned.
+pub fn quorum(
+    heads: &[git2::Oid],
+    threshold: usize,
+    repo: &git2::Repository,
+) -> Result<git2::Oid, QuorumError> {
+    let no_quorum = || QuorumError::NoQuorum {
+        threshold,
+        heads: heads.len(),
+    };
+    if threshold == 0 || heads.len() < threshold {
+        return Err(no_quorum());
+    }
+    let merge_base = |a: git2::Oid, b: git2::Oid| match repo.merge_base(a, b) {
+        Ok(base) => Ok(Some(base)),
+        Err(e) if git::is_not_found_err(&e) => Ok(None),
+        Err(e) => Err(e),
+    };
+
+    // Close the set of candidates under pairwise merge bases.
+    let mut candidates = heads.iter().copied().collect::<BTreeSet<_>>();
+    loop {
+        let mut bases = BTreeSet::new();
+        for (i, a) in candidates.iter().enumerate() {
+            for b in candidates.iter().skip(i + 1) {
+                if let Some(base) = merge_base(*a, *b)? {
+                    if !candidates.contains(&base) {
+                        bases.insert(base);
+                    }
+                }
+            }
+        }
+        if bases.is_empty() {
+            break;
+        }
+        candidates.extend(bases);
+    }
+
+    // Keep the candidates that have enough votes.
+    let mut quorum = Vec::new();
+    for c in candidates {
+        let mut votes = 0;
+        for h in heads {
+            if *h == c || repo.graph_descendant_of(*h, c)? {
+                votes += 1;
+            }
+        }
+        if votes >= threshold {
+            quorum.push(c);
+        }
     }
+
+    // Keep the latest candidates, ie. the ones that have no descendants in the quorum.
+    let mut latest = Vec::new();
+    for c in &quorum {
+        let mut is_latest = true;
+        for other in &quorum {
+            if other != c && repo.graph_descendant_of(*other, *c)? {
+                is_latest = false;
+                break;
+            }
+        }
+        if is_latest {
+            latest.push(*c);
+        }
+    }
+
+    let mut latest = latest.into_iter();
+    let first = latest.next().ok_or_else(no_quorum)?;
+
+    latest.try_fold(first, |a, b| merge_base(a, b)?.ok_or(QuorumError::Diverged))
 }
 
 pub mod trailers {
@@ -683,10 +1099,13 @@ pub mod trailers {
     use thiserror::Error;
 
     use super::*;
+    use crate::cob::ObjectId;
     use crypto::{PublicKey, PublicKeyError};
     use crypto::{Signature, SignatureError};
 
     pub const SIGNATURE_TRAILER: &str = "Rad-Signature";
+    /// Trailers referencing issues to close once a patch is merged.
+    pub const CLOSING_TRAILERS: [&str; 2] = ["Fixes", "Closes"];
 
     #[derive(Error, Debug)]
     pub enum Error {
@@ -717,17 +1136,49 @@ pub mod trailers {
         }
         Ok(signatures)
     }
+
+    /// Parse the issues closed by a commit, from its `Fixes:` and `Closes:` trailers.
+    /// Trailer values that aren't object ids are ignored.
+    pub fn parse_closes(msg: &str) -> Vec<ObjectId> {
+        let Ok(trailers) = git2::message_trailers_strs(msg) else {
+            return Vec::new();
+        };
+        trailers
+            .iter()
+            .filter(|(key, _)| CLOSING_TRAILERS.iter().any(|t| t.eq_ignore_ascii_case(key)))
+            .filter_map(|(_, val)| ObjectId::from_str(val.trim()).ok())
+            .collect()
+    }
 }
 
 pub mod paths {
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
+    use std::{fs, io};
 
     use super::Id;
-    use super::ReadStorage;
+    use super::Storage;
 
-    pub fn repository<S: ReadStorage>(storage: &S, proj: &Id) -> PathBuf {
+    pub fn repository(storage: &Storage, proj: &Id) -> PathBuf {
         storage.path().join(proj.to_string())
     }
+
+    /// Size of a file, or of a directory and its contents, in bytes.
+    /// Returns zero if the path doesn't exist.
+    pub fn size(path: &Path) -> io::Result<u64> {
+        let meta = match fs::symlink_metadata(path) {
+            Ok(meta) => meta,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(err),
+        };
+        if !meta.is_dir() {
+            return Ok(meta.len());
+        }
+        let mut size = 0;
+        for entry in fs::read_dir(path)? {
+            size += self::size(&entry?.path())?;
+        }
+        Ok(size)
+    }
 }
 
 #[cfg(test)]
@@ -772,6 +1223,72 @@ mod tests {
         assert_eq!(refs, remotes);
     }
 
+    #[test]
+    fn test_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let signer = MockSigner::default();
+        let storage = fixtures::storage(dir.path(), &signer).unwrap();
+        let sizes = storage.sizes().unwrap();
+        let (proj, size) = *sizes.first().unwrap();
+
+        assert!(size.objects > 0);
+        assert!(size.refs > 0);
+        assert_eq!(
+            storage.size().unwrap().total(),
+            sizes.iter().map(|(_, s)| s.total()).sum::<u64>()
+        );
+
+        storage.remove(proj).unwrap();
+        assert!(!storage.inventory().unwrap().contains(&proj));
+    }
+
+    #[test]
+    fn test_gc() {
+        let dir = tempfile::tempdir().unwrap();
+        let signer = MockSigner::default();
+        let storage = fixtures::storage(dir.path(), &signer).unwrap();
+        let proj = *storage.inventory().unwrap().first().unwrap();
+        let repo = storage.repository(proj).unwrap();
+        let (count, size) = repo.loose_objects().unwrap();
+
+        assert!(count > 0);
+        assert!(size > 0);
+
+        repo.gc().unwrap();
+        assert!(repo.loose_objects().unwrap().0 < count);
+        assert!(repo.verify().is_ok());
+    }
+
+    #[test]
+    fn test_quarantine_remote() {
+        let dir = tempfile::tempdir().unwrap();
+        let signer = MockSigner::default();
+        let storage = fixtures::storage(dir.path(), &signer).unwrap();
+        let proj = *storage.inventory().unwrap().first().unwrap();
+        let repo = storage.repository(proj).unwrap();
+        let remote = *signer.public_key();
+        let refs = repo.references(&remote).unwrap();
+
+        // Corrupt the remote's refs.
+        repo.raw()
+            .reference(
+                &format!("refs/namespaces/{remote}/refs/heads/unsigned"),
+                *refs.head(git::refname!("master")).unwrap(),
+                false,
+                "test",
+            )
+            .unwrap();
+        let err = repo.verify().unwrap_err();
+        assert_eq!(err.remote(), Some(&remote));
+
+        assert_eq!(repo.quarantine_remote(&remote).unwrap(), refs.len() + 1);
+        assert_eq!(repo.remote_ids().unwrap().count(), 0);
+        assert!(repo
+            .raw()
+            .find_reference(&format!("{QUARANTINE_PREFIX}/{remote}/refs/heads/unsigned"))
+            .is_ok());
+    }
+
     #[test]
     fn test_fetch() {
         let tmp = tempfile::tempdir().unwrap();
@@ -1045,4 +1562,78 @@ mod tests {
         assert_eq!(remote.refs, signed);
         assert_eq!(*remote.refs, unsigned);
     }
+
+    #[test]
+    fn test_quorum() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init_bare(tmp.path()).unwrap();
+        let sig = git2::Signature::now("anonymous", "anonymous@radicle.xyz").unwrap();
+        let c0 = git::initial_commit(&repo, &sig).unwrap();
+        let c1 = git::commit(&repo, &c0, &git::refname!("refs/heads/c1"), "c1", &sig).unwrap();
+        let c2 = git::commit(&repo, &c1, &git::refname!("refs/heads/c2"), "c2", &sig).unwrap();
+        let d2 = git::commit(&repo, &c1, &git::refname!("refs/heads/d2"), "d2", &sig).unwrap();
+        let (c0, c1, c2, d2) = (c0.id(), c1.id(), c2.id(), d2.id());
+
+        assert_eq!(quorum(&[c2], 1, &repo).unwrap(), c2);
+        assert_eq!(quorum(&[c2, c1], 1, &repo).unwrap(), c2);
+        assert_eq!(quorum(&[c2, c1], 2, &repo).unwrap(), c1);
+        assert_eq!(quorum(&[c2, d2, c0], 2, &repo).unwrap(), c1);
+        assert_eq!(quorum(&[c2, c2, d2], 2, &repo).unwrap(), c2);
+        // Competing quorums resolve to their merge base.
+        assert_eq!(quorum(&[c2, d2], 1, &repo).unwrap(), c1);
+        assert_matches!(
+            quorum(&[c2, d2], 3, &repo),
+            Err(QuorumError::NoQuorum { .. })
+        );
+        assert_matches!(quorum(&[], 1, &repo), Err(QuorumError::NoQuorum { .. }));
+    }
+
+    #[test]
+    fn test_sign_refs_hooks() {
+        let tmp = tempfile::tempdir().unwrap();
+        let signer = MockSigner::default();
+        let alice = *signer.public_key();
+        let hooks = Hooks::default();
+        let storage = Storage::open(tmp.path()).unwrap().with_hooks(hooks.clone());
+        let proj_id = arbitrary::gen::<Id>(1);
+        let project = storage.repository(proj_id).unwrap();
+        let backend = &project.backend;
+        let sig = git2::Signature::now(&alice.to_string(), "anonymous@radicle.xyz").unwrap();
+        let head = git::initial_commit(backend, &sig).unwrap();
+        let updated = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        hooks.register({
+            let updated = updated.clone();
+            move |proj: Id, remote: &RemoteId, updates: &[RefUpdate]| {
+                assert_eq!(proj, proj_id);
+                assert_eq!(remote, &alice);
+
+                updated.lock().unwrap().extend(updates.iter().cloned());
+            }
+        });
+
+        let master = git::qualified!("refs/heads/master");
+        let oid = git::commit(
+            backend,
+            &head,
+            &git::refs::storage::branch(&alice, &git::refname!("master")).to_ref_string(),
+            "Second commit",
+            &sig,
+        )
+        .unwrap()
+        .id();
+
+        project.sign_refs(&signer).unwrap();
+        assert_eq!(
+            updated.lock().unwrap().drain(..).collect::<Vec<_>>(),
+            vec![RefUpdate::Created {
+                name: master.to_ref_string(),
+                oid: oid.into()
+            }]
+        );
+
+        // Signing again without changes doesn't run the hooks.
+        project.sign_refs(&signer).unwrap();
+        assert!(updated.lock().unwrap().is_empty());
+    }
 }
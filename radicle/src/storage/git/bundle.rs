@@ -0,0 +1,173 @@
+//! Export and import of stored projects as git bundles.
+//!
+//! A bundle is a single file containing every reference of a stored project, along with
+//! the objects they point to. Since a project's identity branches, signed refs and
+//! collaborative objects are all stored as references, a bundle holds a complete copy of
+//! the project, which can be transferred offline or kept as a backup.
+use std::ffi::OsStr;
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::git;
+use crate::identity::{Id, IdentityError};
+use crate::storage::refs;
+use crate::storage::{ReadStorage, RefUpdate};
+
+use super::{paths, Repository, Storage, VerifyError};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("project {0} was not found in storage")]
+    NotFound(Id),
+    #[error("project {0} already exists in storage")]
+    AlreadyExists(Id),
+    #[error("bundle doesn't contain a project identity")]
+    MissingIdentity,
+    #[error("identity: {0}")]
+    Identity(#[from] IdentityError),
+    #[error("verify: {0}")]
+    Verify(#[from] VerifyError),
+    #[error("storage: {0}")]
+    Storage(#[from] crate::storage::Error),
+    #[error("refs: {0}")]
+    Refs(#[from] refs::Error),
+    #[error("git: {0}")]
+    Git(#[from] git2::Error),
+    #[error("i/o: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl Storage {
+    /// Export a project to a bundle file at the given path.
+    pub fn export(&self, id: Id, path: &Path) -> Result<(), Error> {
+        if !self.contains(&id)? {
+            return Err(Error::NotFound(id));
+        }
+        let repo = paths::repository(self, &id);
+        let path = std::env::current_dir()?.join(path);
+
+        git::run::<_, _, &str, &str>(
+            &repo,
+            [
+                OsStr::new("bundle"),
+                OsStr::new("create"),
+                path.as_os_str(),
+                OsStr::new("--all"),
+            ],
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    /// Import a project from a bundle file. The bundle is verified before being added to
+    /// storage. Returns the imported project's identifier.
+    pub fn import(&self, path: &Path) -> Result<Id, Error> {
+        let path = std::env::current_dir()?.join(path);
+        // Fetch the bundle into a temporary repository inside storage, so that it can be
+        // moved into place once verified.
+        let tmp = tempfile::Builder::new()
+            .prefix(".import-")
+            .tempdir_in(self.path())?;
+        let id = {
+            // The project identifier isn't known until the bundle is fetched, so the
+            // repository is first opened with a placeholder.
+            let repo = Repository::open(tmp.path(), Id::from(git2::Oid::zero()))?;
+
+            git::run::<_, _, &str, &str>(
+                tmp.path(),
+                [
+                    OsStr::new("fetch"),
+                    OsStr::new("--quiet"),
+                    path.as_os_str(),
+                    OsStr::new("refs/*:refs/*"),
+                ],
+                [],
+            )?;
+            let remote = repo.remote_ids()?.next().ok_or(Error::MissingIdentity)??;
+            let id = Id::from(repo.identity(&remote)?.root);
+
+            // Verification checks every identity against the project identifier, so it
+            // must be done with the identifier read from the bundle.
+            Repository::open(tmp.path(), id)?.verify()?;
+
+            id
+        };
+        if self.contains(&id)? {
+            return Err(Error::AlreadyExists(id));
+        }
+        std::fs::rename(tmp.path(), paths::repository(self, &id))?;
+
+        let repo = Repository::open(paths::repository(self, &id), id)?;
+        let mut updates = Vec::new();
+
+        for r in repo.raw().references_glob("refs/namespaces/*")? {
+            let r = r?;
+            let (Some(name), Some(oid)) = (r.name(), r.target()) else {
+                continue;
+            };
+            let Ok(name) = git::RefString::try_from(name) else {
+                continue;
+            };
+            updates.push(RefUpdate::Created {
+                name,
+                oid: oid.into(),
+            });
+        }
+        self.hooks.run_namespaced(id, &updates);
+
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto::test::signer::MockSigner;
+
+    use super::*;
+    use crate::assert_matches;
+    use crate::storage::hooks::Hooks;
+    use crate::storage::{RemoteId, WriteStorage};
+    use crate::test::fixtures;
+
+    #[test]
+    fn test_export_import() {
+        let tmp = tempfile::tempdir().unwrap();
+        let signer = MockSigner::default();
+        let storage = fixtures::storage(tmp.path().join("alice"), &signer).unwrap();
+        let id = *storage.inventory().unwrap().first().unwrap();
+        let bundle = tmp.path().join("project.bundle");
+
+        storage.export(id, &bundle).unwrap();
+        assert!(bundle.exists());
+
+        let hooks = Hooks::default();
+        let imported = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        hooks.register({
+            let imported = imported.clone();
+            move |proj: Id, _: &RemoteId, updates: &[RefUpdate]| {
+                imported.lock().unwrap().push((proj, updates.len()));
+            }
+        });
+        let other = Storage::open(tmp.path().join("bob"))
+            .unwrap()
+            .with_hooks(hooks);
+
+        assert_eq!(other.import(&bundle).unwrap(), id);
+        assert_matches!(other.import(&bundle), Err(Error::AlreadyExists(_)));
+        assert_eq!(other.inventory().unwrap(), vec![id]);
+        assert!(!imported.lock().unwrap().is_empty());
+
+        let (a, b) = (
+            storage.repository(id).unwrap(),
+            other.repository(id).unwrap(),
+        );
+        a.verify().unwrap();
+        b.verify().unwrap();
+
+        assert_eq!(a.remotes().unwrap().count(), b.remotes().unwrap().count());
+        assert_eq!(a.identity_doc().unwrap().0, b.identity_doc().unwrap().0);
+        assert_eq!(a.canonical_head().unwrap().1, b.canonical_head().unwrap().1);
+    }
+}
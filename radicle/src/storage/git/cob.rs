@@ -1,8 +1,9 @@
 //! COB storage Git backend.
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use radicle_cob as cob;
 use radicle_cob::change;
+use radicle_cob::object::verify::Invalid;
 
 use crate::git;
 use crate::storage::Error;
@@ -12,6 +13,10 @@ pub use cob::*;
 
 use super::{RemoteId, Repository};
 
+/// Directory, under a repository, where materialized COBs are cached.
+/// See [`crate::cob::cache`].
+pub const COB_CACHE_DIR: &str = "cobs";
+
 #[derive(Error, Debug)]
 pub enum ObjectsError {
     #[error(transparent)]
@@ -34,7 +39,116 @@ pub enum TypesError {
     RefFormat(#[from] git_ref_format::Error),
 }
 
-impl cob::Store for Repository {}
+/// A collaborative object change that failed verification.
+#[derive(Debug, Clone)]
+pub struct InvalidChange {
+    /// The remote that published the change, if it is known. See
+    /// [`Repository::verify_cobs`].
+    pub remote: Option<RemoteId>,
+    /// The change commit.
+    pub oid: git::Oid,
+    /// Why the change is invalid.
+    pub reason: String,
+}
+
+impl Repository {
+    /// Verify the integrity of all collaborative objects in the repository, see
+    /// [`cob::verify`]. Returns the changes that failed verification, each of them once.
+    ///
+    /// Only changes that can't be loaded or whose signatures are invalid are attributed
+    /// to a remote, since other failures, eg. a change of the wrong type, can be caused
+    /// by any remote building on a valid change. A change with invalid signatures is
+    /// attributed to its claimed signer, if the change is reachable from the signer's
+    /// own references. A change that can't be loaded is attributed to the remote whose
+    /// reference points to it, if any.
+    pub fn verify_cobs(
+        &self,
+    ) -> Result<Vec<InvalidChange>, cob::object::collaboration::error::Retrieve> {
+        let mut objects = HashMap::<_, Vec<(RemoteId, git2::Oid)>>::new();
+        let mut visited = HashSet::new();
+        let mut invalid = Vec::new();
+
+        for r in self
+            .backend
+            .references_glob("refs/namespaces/*/refs/cobs/*")?
+        {
+            let r = r?;
+            let (Some(name), Some(tip)) = (r.name(), r.target()) else {
+                continue;
+            };
+            let Ok((remote, refname)) = git::parse_ref_namespaced::<RemoteId>(name) else {
+                continue;
+            };
+            let Some(object) = cob::object::parse_refstr(&refname) else {
+                continue;
+            };
+            objects.entry(object).or_default().push((remote, tip));
+        }
+
+        for ((typename, id), tips) in objects {
+            for failure in cob::verify(self, &typename, &id)?.failures {
+                if !visited.insert(failure.change) {
+                    continue;
+                }
+                let oid = git2::Oid::from(failure.change);
+                let remote = match (&failure.reason, failure.signer) {
+                    (Invalid::Signatures, Some(signer)) => tips.iter().find(|(remote, tip)| {
+                        *remote == signer
+                            && (*tip == oid
+                                || self.backend.graph_descendant_of(*tip, oid).unwrap_or(false))
+                    }),
+                    (Invalid::Load(_), _) => tips.iter().find(|(_, tip)| *tip == oid),
+                    _ => None,
+                };
+                invalid.push(InvalidChange {
+                    remote: remote.map(|(remote, _)| *remote),
+                    oid: failure.change,
+                    reason: failure.reason.to_string(),
+                });
+            }
+        }
+        Ok(invalid)
+    }
+}
+
+impl Repository {
+    /// The notes references holding the attestations of changes by each remote, see
+    /// [`cob::change::Attestations`].
+    fn attestation_refs(&self) -> Result<Vec<String>, git2::Error> {
+        let glob = format!("refs/namespaces/*/{}", cob::git::change::ATTESTATIONS_REF);
+        let mut refs = Vec::new();
+
+        for r in self.backend.references_glob(&glob)? {
+            if let Some(name) = r?.name() {
+                refs.push(name.to_owned());
+            }
+        }
+        Ok(refs)
+    }
+}
+
+/// The notes reference holding the attestations of changes by the given remote.
+fn attestations_ref(remote: &RemoteId) -> String {
+    format!(
+        "refs/namespaces/{remote}/{}",
+        cob::git::change::ATTESTATIONS_REF
+    )
+}
+
+impl cob::Store for Repository {
+    fn revocations(&self) -> crypto::revocation::Revocations {
+        self.identity_doc()
+            .ok()
+            .and_then(|(_, doc)| doc.revocations().ok())
+            .unwrap_or_default()
+    }
+
+    fn owner(&self, reference: &cob::object::Reference) -> Option<crypto::PublicKey> {
+        git::parse_ref_namespaced::<RemoteId>(reference.name.as_str())
+            .ok()
+            .map(|(remote, _)| remote)
+    }
+}
 
 impl change::Storage for Repository {
     type StoreError = <git2::Repository as change::Storage>::StoreError;
@@ -57,7 +171,47 @@ impl change::Storage for Repository {
     }
 
     fn load(&self, id: Self::ObjectId) -> Result<cob::Change, Self::LoadError> {
-        self.backend.load(id)
+        let mut change = self.backend.load(id)?;
+
+        for notes in self.attestation_refs()? {
+            change.attest(cob::git::change::attestations(&self.backend, &notes, id));
+        }
+        Ok(change)
+    }
+
+    fn attachment(&self, id: Self::ObjectId) -> Result<Vec<u8>, Self::LoadError> {
+        self.backend.attachment(id)
+    }
+
+    fn cosign<Signer>(
+        &self,
+        id: Self::ObjectId,
+        signer: &Signer,
+    ) -> Result<cob::Change, Self::StoreError>
+    where
+        Signer: crypto::Signer,
+    {
+        let mut change = self.load(id)?;
+        let notes = attestations_ref(signer.public_key());
+        cob::git::change::cosign(&self.backend, &notes, &mut change, signer)?;
+
+        Ok(change)
+    }
+
+    fn notarize<Signer>(
+        &self,
+        id: Self::ObjectId,
+        signer: &Signer,
+        timestamp: cob::history::Timestamp,
+    ) -> Result<cob::Change, Self::StoreError>
+    where
+        Signer: crypto::Signer,
+    {
+        let mut change = self.load(id)?;
+        let notes = attestations_ref(signer.public_key());
+        cob::git::change::notarize(&self.backend, &notes, &mut change, signer, timestamp)?;
+
+        Ok(change)
     }
 }
 
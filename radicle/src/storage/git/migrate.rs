@@ -0,0 +1,301 @@
+//! Versioned migrations of the storage layout.
+//!
+//! The storage version is kept in a file at the root of storage. When the layout of
+//! storage changes, eg. how references are named, a [`Migration`] is added to
+//! [`MIGRATIONS`], bumping the storage version. Pending migrations are run in order on
+//! first access, while holding a lock file, so that concurrent processes don't migrate
+//! storage at the same time. Lock files left behind by processes that are no longer
+//! running are reclaimed.
+//!
+//! Storage created by this version of the software is initialized with the latest version
+//! and doesn't need migrating. Storage without a version file predates versioning and is
+//! at version `0`.
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{fs, io, process};
+
+use thiserror::Error;
+
+use super::Storage;
+
+/// Storage version.
+pub type Version = u32;
+
+/// Name of the file holding the storage version, relative to the storage root.
+pub const VERSION_FILE: &str = ".version";
+/// Name of the lock file held while migrating, relative to the storage root.
+pub const LOCK_FILE: &str = ".migrate.lock";
+
+/// Storage migrations, ordered by version.
+pub static MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "Record the storage version",
+    run: |_| Ok(()),
+}];
+
+/// The current storage version, ie. the version of the latest migration.
+pub fn current() -> Version {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or_default()
+}
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("i/o: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid storage version `{0}`")]
+    InvalidVersion(String),
+    #[error("storage version {version} is newer than the latest supported version {latest}")]
+    Unsupported { version: Version, latest: Version },
+    #[error("storage is being migrated by another process (lock file at {0})")]
+    Locked(PathBuf),
+    #[error("migration to version {version} failed: {reason}")]
+    Failed { version: Version, reason: String },
+}
+
+/// A storage migration.
+pub struct Migration {
+    /// Version that storage is at after running the migration.
+    pub version: Version,
+    /// Short description of the migration.
+    pub description: &'static str,
+    /// Run the migration.
+    pub run: fn(&Storage) -> Result<(), Box<dyn std::error::Error + Send + Sync>>,
+}
+
+impl std::fmt::Debug for Migration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Migration")
+            .field("version", &self.version)
+            .field("description", &self.description)
+            .finish()
+    }
+}
+
+/// Lock held while migrating. The lock file records the id of the process holding it,
+/// and is removed when dropped.
+///
+/// If the process holding the lock exits without removing the lock file, eg. because it
+/// was killed, the lock is considered stale and is reclaimed by the next process trying
+/// to acquire it.
+#[derive(Debug)]
+struct Lock {
+    path: PathBuf,
+}
+
+impl Lock {
+    /// How long a lock file that doesn't hold a process id yet is considered held,
+    /// since it may have just been created by another process.
+    const GRACE: Duration = Duration::from_secs(60);
+
+    fn acquire(path: PathBuf) -> Result<Self, Error> {
+        match Self::create(&path) {
+            Err(Error::Locked(path)) if Self::is_stale(&path)? => {
+                log::warn!("Reclaiming stale migration lock {}..", path.display());
+
+                match fs::remove_file(&path) {
+                    Ok(()) => {}
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                    Err(err) => return Err(err.into()),
+                }
+                // If another process reclaimed the lock first, this fails.
+                Self::create(&path)
+            }
+            result => result,
+        }
+    }
+
+    fn create(path: &Path) -> Result<Self, Error> {
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+        {
+            Ok(mut file) => {
+                writeln!(file, "{}", std::process::id())?;
+                Ok(Self {
+                    path: path.to_path_buf(),
+                })
+            }
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                Err(Error::Locked(path.to_path_buf()))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Check whether the lock file at the given path was left behind by a process that
+    /// is no longer running.
+    fn is_stale(path: &Path) -> Result<bool, Error> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            // The lock was released in the meantime.
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(true),
+            Err(err) => return Err(err.into()),
+        };
+        match contents.trim().parse::<u32>() {
+            Ok(pid) => Ok(pid != std::process::id() && !is_running(pid)),
+            Err(_) => {
+                let age = fs::metadata(path)?
+                    .modified()?
+                    .elapsed()
+                    .unwrap_or_default();
+                Ok(age > Self::GRACE)
+            }
+        }
+    }
+}
+
+/// Check whether a process with the given id is running. Where this can't be
+/// determined, the process is assumed to be running.
+fn is_running(pid: u32) -> bool {
+    if cfg!(target_os = "linux") {
+        Path::new("/proc").join(pid.to_string()).exists()
+    } else if cfg!(unix) {
+        process::Command::new("kill")
+            .arg("-0")
+            .arg(pid.to_string())
+            .stdout(process::Stdio::null())
+            .stderr(process::Stdio::null())
+            .status()
+            .map_or(true, |status| status.success())
+    } else {
+        true
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_file(&self.path) {
+            log::error!(
+                "Error removing migration lock {}: {err}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+impl Storage {
+    /// Get the storage version.
+    pub fn version(&self) -> Result<Version, Error> {
+        read_version(&self.path)
+    }
+
+    /// Get the migrations that haven't been run on this storage yet.
+    pub fn pending_migrations(&self) -> Result<Vec<&'static Migration>, Error> {
+        let version = self.version()?;
+        let latest = current();
+
+        if version > latest {
+            return Err(Error::Unsupported { version, latest });
+        }
+        Ok(MIGRATIONS.iter().filter(|m| m.version > version).collect())
+    }
+
+    /// Run all pending migrations, in order. Returns the migrations that were run.
+    /// The storage version is updated after each successful migration, so that a failed
+    /// migration can be retried without running earlier ones again.
+    pub fn migrate(&self) -> Result<Vec<&'static Migration>, Error> {
+        if self.pending_migrations()?.is_empty() {
+            return Ok(vec![]);
+        }
+        let _lock = Lock::acquire(self.path.join(LOCK_FILE))?;
+        // Storage may have been migrated by another process before we acquired the lock.
+        let pending = self.pending_migrations()?;
+
+        for migration in &pending {
+            log::info!(
+                "Migrating storage to version {} ({})..",
+                migration.version,
+                migration.description
+            );
+            (migration.run)(self).map_err(|e| Error::Failed {
+                version: migration.version,
+                reason: e.to_string(),
+            })?;
+            write_version(&self.path, migration.version)?;
+        }
+        Ok(pending)
+    }
+}
+
+/// Read the storage version at the given storage root.
+pub fn read_version(root: &Path) -> Result<Version, Error> {
+    match fs::read_to_string(root.join(VERSION_FILE)) {
+        Ok(s) => s
+            .trim()
+            .parse()
+            .map_err(|_| Error::InvalidVersion(s.trim().to_owned())),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(0),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Write the storage version at the given storage root. The version file is replaced
+/// atomically.
+pub fn write_version(root: &Path, version: Version) -> io::Result<()> {
+    let tmp = root.join(format!("{VERSION_FILE}.tmp"));
+
+    fs::write(&tmp, format!("{version}\n"))?;
+    fs::rename(tmp, root.join(VERSION_FILE))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_matches;
+
+    #[test]
+    fn test_new_storage_is_current() {
+        let tmp = tempfile::tempdir().unwrap();
+        let storage = Storage::open(tmp.path().join("storage")).unwrap();
+
+        assert_eq!(storage.version().unwrap(), current());
+        assert!(storage.pending_migrations().unwrap().is_empty());
+        assert!(storage.migrate().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_migrate() {
+        let tmp = tempfile::tempdir().unwrap();
+        let storage = Storage::open(tmp.path()).unwrap();
+
+        // Storage that predates versioning.
+        assert_eq!(storage.version().unwrap(), 0);
+        assert_eq!(
+            storage.pending_migrations().unwrap().len(),
+            MIGRATIONS.len()
+        );
+        assert!(storage.projects().unwrap().is_empty());
+
+        // Storage is locked by another process.
+        fs::write(tmp.path().join(LOCK_FILE), "").unwrap();
+        assert_matches!(storage.migrate(), Err(Error::Locked(_)));
+        assert_eq!(storage.version().unwrap(), 0);
+        fs::remove_file(tmp.path().join(LOCK_FILE)).unwrap();
+
+        // Storage is locked by a running process.
+        let mut child = process::Command::new("sleep").arg("60").spawn().unwrap();
+        fs::write(tmp.path().join(LOCK_FILE), format!("{}\n", child.id())).unwrap();
+        assert_matches!(storage.migrate(), Err(Error::Locked(_)));
+        assert_eq!(storage.version().unwrap(), 0);
+
+        // The process exited without removing the lock.
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert_eq!(storage.migrate().unwrap().len(), MIGRATIONS.len());
+        assert_eq!(storage.version().unwrap(), current());
+        assert!(!tmp.path().join(LOCK_FILE).exists());
+        assert!(storage.projects().unwrap().is_empty());
+
+        // Storage from the future.
+        write_version(tmp.path(), current() + 1).unwrap();
+        assert_matches!(
+            storage.migrate(),
+            Err(Error::Unsupported { version, .. }) if version == current() + 1
+        );
+    }
+}
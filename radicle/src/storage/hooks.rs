@@ -0,0 +1,310 @@
+//! Hooks invoked when storage references are updated.
+//!
+//! Hooks are the storage equivalent of git's `post-receive` hook: they are invoked after
+//! the references of a remote have been updated, either by a fetch, or by the local user
+//! signing their refs. Hooks are either registered callbacks, or executables, which are
+//! invoked with the project and remote as arguments, and the list of updated references on
+//! standard input, in the same format as `post-receive`, ie. `<old> <new> <ref>` per line.
+use std::collections::BTreeMap;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::{fmt, fs, io, process};
+
+use crate::git;
+use crate::identity::Id;
+use crate::storage::refs::{Refs, SIGREFS_BRANCH};
+
+use super::{Oid, RefUpdate, RemoteId};
+
+/// A hook invoked on reference updates.
+pub trait Hook: Send + Sync {
+    /// Called after the references of `remote` in project `proj` were updated.
+    /// The updated reference names are not namespaced.
+    fn updated(&self, proj: Id, remote: &RemoteId, updates: &[RefUpdate]);
+
+    /// Called after project `proj` was removed from storage.
+    fn removed(&self, _proj: Id) {}
+}
+
+impl<F> Hook for F
+where
+    F: Fn(Id, &RemoteId, &[RefUpdate]) + Send + Sync,
+{
+    fn updated(&self, proj: Id, remote: &RemoteId, updates: &[RefUpdate]) {
+        self(proj, remote, updates)
+    }
+}
+
+/// A hook that runs an executable.
+#[derive(Debug, Clone)]
+pub struct Executable {
+    path: PathBuf,
+}
+
+impl Executable {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Run the executable, waiting for it to exit.
+    pub fn run(&self, proj: Id, remote: &RemoteId, updates: &[RefUpdate]) -> io::Result<()> {
+        let mut child = process::Command::new(&self.path)
+            .arg(proj.to_string())
+            .arg(remote.to_string())
+            .stdin(process::Stdio::piped())
+            .stdout(process::Stdio::null())
+            .spawn()?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            for update in updates {
+                let zero = Oid::from(git2::Oid::zero());
+                let (old, new, name) = match update {
+                    RefUpdate::Updated { name, old, new } => (old, new, name),
+                    RefUpdate::Created { name, oid } => (&zero, oid, name),
+                    RefUpdate::Deleted { name, oid } => (oid, &zero, name),
+                    RefUpdate::Skipped { .. } => continue,
+                };
+                writeln!(stdin, "{old} {new} {name}")?;
+            }
+        }
+        let status = child.wait()?;
+
+        if !status.success() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("hook `{}` exited with {status}", self.path.display()),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Hook for Executable {
+    fn updated(&self, proj: Id, remote: &RemoteId, updates: &[RefUpdate]) {
+        if let Err(err) = self.run(proj, remote, updates) {
+            log::warn!("Error running hook for {proj}: {err}");
+        }
+    }
+}
+
+/// A set of hooks, shared between clones.
+#[derive(Clone, Default)]
+pub struct Hooks(Arc<RwLock<Vec<Arc<dyn Hook>>>>);
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Hooks").field(&self.len()).finish()
+    }
+}
+
+impl Hooks {
+    /// Load executable hooks from a directory. Every executable file in the directory
+    /// is registered as a hook. If the directory doesn't exist, no hooks are loaded.
+    pub fn load(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let hooks = Self::default();
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(hooks),
+            Err(err) => return Err(err),
+        };
+        let mut paths = Vec::new();
+
+        for entry in entries {
+            let path = entry?.path();
+            if is_executable(&path)? {
+                paths.push(path);
+            }
+        }
+        // Run hooks in a predictable order.
+        paths.sort();
+
+        for path in paths {
+            hooks.register(Executable::new(path));
+        }
+        Ok(hooks)
+    }
+
+    /// Register a new hook.
+    pub fn register(&self, hook: impl Hook + 'static) {
+        self.0
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Arc::new(hook));
+    }
+
+    /// Number of registered hooks.
+    pub fn len(&self) -> usize {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Whether there are no registered hooks.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Run all hooks for the given updates. Hooks are not run if nothing was updated.
+    pub fn run(&self, proj: Id, remote: &RemoteId, updates: &[RefUpdate]) {
+        if updates
+            .iter()
+            .all(|u| matches!(u, RefUpdate::Skipped { .. }))
+        {
+            return;
+        }
+        // Clone the hooks, so that hooks can register other hooks.
+        let hooks = self.0.read().unwrap_or_else(|e| e.into_inner()).clone();
+
+        for hook in hooks {
+            hook.updated(proj, remote, updates);
+        }
+    }
+
+    /// Run all hooks for a removed project.
+    pub fn removed(&self, proj: Id) {
+        let hooks = self.0.read().unwrap_or_else(|e| e.into_inner()).clone();
+
+        for hook in hooks {
+            hook.removed(proj);
+        }
+    }
+
+    /// Run all hooks for namespaced reference updates, eg. as returned by a fetch.
+    /// Hooks are run once per remote. Updates to references outside of a remote namespace
+    /// are ignored.
+    pub fn run_namespaced(&self, proj: Id, updates: &[RefUpdate]) {
+        let mut remotes: BTreeMap<RemoteId, Vec<RefUpdate>> = BTreeMap::new();
+
+        for update in updates {
+            let (name, update) = match update {
+                RefUpdate::Updated { name, old, new } => (name, (*old, *new)),
+                RefUpdate::Created { name, oid } => (name, (Oid::from(git2::Oid::zero()), *oid)),
+                RefUpdate::Deleted { name, oid } => (name, (*oid, Oid::from(git2::Oid::zero()))),
+                RefUpdate::Skipped { .. } => continue,
+            };
+            let Ok((remote, name)) = git::parse_ref_namespaced::<RemoteId>(name.as_str()) else {
+                continue;
+            };
+            remotes.entry(remote).or_default().push(RefUpdate::from(
+                name.to_ref_string(),
+                update.0,
+                update.1,
+            ));
+        }
+        for (remote, updates) in remotes {
+            self.run(proj, &remote, &updates);
+        }
+    }
+}
+
+/// Compute the reference updates between two sets of refs.
+/// The signed refs branch is not included.
+pub fn diff(old: &Refs, new: &Refs) -> Vec<RefUpdate> {
+    let zero = Oid::from(git2::Oid::zero());
+    let (old, new): (&BTreeMap<_, _>, &BTreeMap<_, _>) = (old, new);
+    let sigrefs = SIGREFS_BRANCH.to_ref_string();
+    let mut updates = Vec::new();
+
+    for (name, oid) in new.iter().filter(|(name, _)| **name != sigrefs) {
+        let old = old.get(name).copied().unwrap_or(zero);
+        if old != *oid {
+            updates.push(RefUpdate::from(name.clone(), old, *oid));
+        }
+    }
+    for (name, oid) in old.iter().filter(|(name, _)| **name != sigrefs) {
+        if !new.contains_key(name) {
+            updates.push(RefUpdate::from(name.clone(), *oid, zero));
+        }
+    }
+    updates
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> io::Result<bool> {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let meta = fs::metadata(path)?;
+    Ok(meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> io::Result<bool> {
+    Ok(fs::metadata(path)?.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::test::arbitrary;
+
+    #[test]
+    fn test_hooks_run_namespaced() {
+        let hooks = Hooks::default();
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let proj = arbitrary::gen::<Id>(1);
+        let alice = arbitrary::gen::<RemoteId>(1);
+        let bob = arbitrary::gen::<RemoteId>(1);
+        let oid = arbitrary::oid();
+
+        hooks.register({
+            let calls = calls.clone();
+            move |proj: Id, remote: &RemoteId, updates: &[RefUpdate]| {
+                calls
+                    .lock()
+                    .unwrap()
+                    .push((proj, *remote, updates.to_vec()));
+            }
+        });
+        hooks.run_namespaced(
+            proj,
+            &[
+                RefUpdate::from(
+                    git::refs::storage::branch(&alice, &git::refname!("master")).to_ref_string(),
+                    git2::Oid::zero(),
+                    oid,
+                ),
+                RefUpdate::from(
+                    git::refs::storage::branch(&bob, &git::refname!("master")).to_ref_string(),
+                    oid,
+                    oid,
+                ),
+            ],
+        );
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(
+            *calls,
+            vec![(
+                proj,
+                alice,
+                vec![RefUpdate::Created {
+                    name: git::refname!("refs/heads/master"),
+                    oid
+                }]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let a = arbitrary::oid();
+        let b = arbitrary::oid();
+        let master = git::refname!("refs/heads/master");
+        let dev = git::refname!("refs/heads/dev");
+        let old = Refs::from(BTreeMap::from([(master.clone(), a), (dev.clone(), a)]));
+        let new = Refs::from(BTreeMap::from([(master.clone(), b)]));
+
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                RefUpdate::Updated {
+                    name: master,
+                    old: a,
+                    new: b
+                },
+                RefUpdate::Deleted { name: dev, oid: a },
+            ]
+        );
+    }
+}
@@ -0,0 +1,347 @@
+//! SQLite index of stored references.
+//!
+//! Listing projects, remotes and references by reading the git refs of every repository
+//! in storage is slow when there are many repositories. The index keeps a table of
+//! `(project, remote, refname, oid)` entries which is kept in sync with storage by
+//! registering it as a storage [`Hook`]. This is done by the node, and by
+//! [`Profile::open`](crate::Profile::open) for other processes writing to storage.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use sqlite as sql;
+use thiserror::Error;
+
+use crate::git;
+use crate::identity::Id;
+use crate::storage::git::{Repository, Storage};
+use crate::storage::hooks::Hook;
+use crate::storage::refs::{Refs, SIGREFS_BRANCH};
+use crate::storage::{Oid, ReadRepository, ReadStorage, RefUpdate, RemoteId, WriteStorage};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("internal error: {0}")]
+    Internal(#[from] sql::Error),
+    #[error("storage: {0}")]
+    Storage(#[from] crate::storage::Error),
+    #[error("refs: {0}")]
+    Refs(#[from] crate::storage::refs::Error),
+    #[error("git: {0}")]
+    Git(#[from] git2::Error),
+    #[error("invalid entry in index: {0}")]
+    InvalidEntry(String),
+}
+
+/// Refs index, backed by SQLite. Clones share the same database connection.
+#[derive(Clone)]
+pub struct Index {
+    db: Arc<Mutex<sql::Connection>>,
+}
+
+impl fmt::Debug for Index {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Index(..)")
+    }
+}
+
+impl Index {
+    const SCHEMA: &str = include_str!("index/schema.sql");
+    /// How long to wait for other processes writing to the index, in milliseconds.
+    const BUSY_TIMEOUT: usize = 3000;
+
+    /// Open an index at the given path. Creates a new empty index if an existing
+    /// index isn't found.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let mut db = sql::Connection::open(path)?;
+        // The index is shared by the node and any process that loads the profile.
+        db.set_busy_timeout(Self::BUSY_TIMEOUT)?;
+        db.execute(Self::SCHEMA)?;
+
+        Ok(Self {
+            db: Arc::new(Mutex::new(db)),
+        })
+    }
+
+    /// Open an index at the given path, only if it exists.
+    pub fn open_existing<P: AsRef<Path>>(path: P) -> Result<Option<Self>, Error> {
+        if !path.as_ref().exists() {
+            return Ok(None);
+        }
+        Self::open(path).map(Some)
+    }
+
+    /// Create a new in-memory index.
+    pub fn memory() -> Result<Self, Error> {
+        Self::open(":memory:")
+    }
+
+    /// Rebuild the index of all projects in storage.
+    pub fn reindex(&self, storage: &Storage) -> Result<(), Error> {
+        let db = self.db();
+
+        db.execute("BEGIN")?;
+        let result = storage.inventory().map_err(Error::from).and_then(|inv| {
+            db.execute("DELETE FROM refs")?;
+
+            for id in inv {
+                Self::index_project(&db, &storage.repository(id)?)?;
+            }
+            Ok(())
+        });
+        db.execute(if result.is_ok() { "COMMIT" } else { "ROLLBACK" })?;
+
+        result
+    }
+
+    /// Rebuild the index of a single project.
+    pub fn reindex_project(&self, repo: &Repository) -> Result<(), Error> {
+        Self::index_project(&self.db(), repo)
+    }
+
+    /// Apply reference updates to the index.
+    pub fn update(&self, proj: &Id, remote: &RemoteId, updates: &[RefUpdate]) -> Result<(), Error> {
+        let db = self.db();
+
+        for update in updates {
+            let (name, oid) = match update {
+                RefUpdate::Created { name, oid } => (name, Some(oid)),
+                RefUpdate::Updated { name, new, .. } => (name, Some(new)),
+                RefUpdate::Deleted { name, .. } => (name, None),
+                RefUpdate::Skipped { .. } => continue,
+            };
+            if is_sigrefs(name) {
+                continue;
+            }
+            let mut stmt = if let Some(oid) = oid {
+                let mut stmt = db.prepare(
+                    "INSERT INTO refs (project, remote, refname, oid)
+                     VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT DO UPDATE SET oid = ?4",
+                )?;
+                stmt.bind((4, oid.to_string().as_str()))?;
+                stmt
+            } else {
+                db.prepare("DELETE FROM refs WHERE project = ?1 AND remote = ?2 AND refname = ?3")?
+            };
+            stmt.bind((1, proj))?;
+            stmt.bind((2, remote))?;
+            stmt.bind((3, name.as_str()))?;
+            stmt.next()?;
+        }
+        Ok(())
+    }
+
+    /// Remove a project from the index.
+    pub fn remove(&self, proj: &Id) -> Result<(), Error> {
+        let db = self.db();
+        let mut stmt = db.prepare("DELETE FROM refs WHERE project = ?")?;
+
+        stmt.bind((1, proj))?;
+        stmt.next()?;
+
+        Ok(())
+    }
+
+    /// Get all indexed projects.
+    pub fn projects(&self) -> Result<Vec<Id>, Error> {
+        let db = self.db();
+        let stmt = db.prepare("SELECT DISTINCT project FROM refs ORDER BY project")?;
+        let mut projects = Vec::new();
+
+        for row in stmt.into_iter() {
+            projects.push(row?.try_read::<Id, _>("project")?);
+        }
+        Ok(projects)
+    }
+
+    /// Get the remotes of a project.
+    pub fn remotes(&self, proj: &Id) -> Result<Vec<RemoteId>, Error> {
+        let db = self.db();
+        let mut stmt =
+            db.prepare("SELECT DISTINCT remote FROM refs WHERE project = ? ORDER BY remote")?;
+        stmt.bind((1, proj))?;
+
+        let mut remotes = Vec::new();
+        for row in stmt.into_iter() {
+            remotes.push(row?.try_read::<RemoteId, _>("remote")?);
+        }
+        Ok(remotes)
+    }
+
+    /// Get the references of a project remote.
+    pub fn refs(&self, proj: &Id, remote: &RemoteId) -> Result<Refs, Error> {
+        let db = self.db();
+        let mut stmt =
+            db.prepare("SELECT refname, oid FROM refs WHERE project = ? AND remote = ?")?;
+        stmt.bind((1, proj))?;
+        stmt.bind((2, remote))?;
+
+        let mut refs = Vec::new();
+        for row in stmt.into_iter() {
+            let row = row?;
+            let name = row.try_read::<&str, _>("refname")?;
+            let oid = row.try_read::<&str, _>("oid")?;
+
+            refs.push((parse_refname(name)?, parse_oid(oid)?));
+        }
+        Ok(Refs::from(refs.into_iter().collect::<BTreeMap<_, _>>()))
+    }
+
+    /// Get a reference of a project remote.
+    pub fn get(
+        &self,
+        proj: &Id,
+        remote: &RemoteId,
+        refname: &git::Qualified,
+    ) -> Result<Option<Oid>, Error> {
+        let db = self.db();
+        let mut stmt =
+            db.prepare("SELECT oid FROM refs WHERE project = ? AND remote = ? AND refname = ?")?;
+        stmt.bind((1, proj))?;
+        stmt.bind((2, remote))?;
+        stmt.bind((3, refname.as_str()))?;
+
+        if let Some(row) = stmt.into_iter().next() {
+            let oid = row?.try_read::<&str, _>("oid")?.to_owned();
+            return parse_oid(&oid).map(Some);
+        }
+        Ok(None)
+    }
+
+    /// Whether the index is empty.
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        let db = self.db();
+        let stmt = db.prepare("SELECT 1 FROM refs LIMIT 1")?;
+        let is_empty = stmt.into_iter().next().is_none();
+
+        Ok(is_empty)
+    }
+
+    fn index_project(db: &sql::Connection, repo: &Repository) -> Result<(), Error> {
+        let mut stmt = db.prepare("DELETE FROM refs WHERE project = ?")?;
+        stmt.bind((1, &repo.id))?;
+        stmt.next()?;
+
+        let remotes = repo.remote_ids()?.collect::<Result<Vec<_>, _>>()?;
+        for remote in remotes {
+            for (name, oid) in repo.references(&remote)?.iter() {
+                if is_sigrefs(name) {
+                    continue;
+                }
+                let mut stmt = db.prepare(
+                    "INSERT INTO refs (project, remote, refname, oid)
+                     VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT DO UPDATE SET oid = ?4",
+                )?;
+                stmt.bind((1, &repo.id))?;
+                stmt.bind((2, &remote))?;
+                stmt.bind((3, name.as_str()))?;
+                stmt.bind((4, oid.to_string().as_str()))?;
+                stmt.next()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn db(&self) -> MutexGuard<sql::Connection> {
+        self.db.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Hook for Index {
+    fn updated(&self, proj: Id, remote: &RemoteId, updates: &[RefUpdate]) {
+        if let Err(err) = self.update(&proj, remote, updates) {
+            log::error!("Error updating refs index for {proj}: {err}");
+        }
+    }
+
+    fn removed(&self, proj: Id) {
+        if let Err(err) = self.remove(&proj) {
+            log::error!("Error removing {proj} from refs index: {err}");
+        }
+    }
+}
+
+fn is_sigrefs(name: &git::RefString) -> bool {
+    name.as_refstr() == SIGREFS_BRANCH.as_ref()
+}
+
+fn parse_refname(name: &str) -> Result<git::RefString, Error> {
+    git::RefString::try_from(name).map_err(|e| Error::InvalidEntry(e.to_string()))
+}
+
+fn parse_oid(oid: &str) -> Result<Oid, Error> {
+    git2::Oid::from_str(oid)
+        .map(Oid::from)
+        .map_err(|e| Error::InvalidEntry(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto::test::signer::MockSigner;
+    use crypto::Signer;
+
+    use super::*;
+    use crate::storage::hooks::Hooks;
+    use crate::storage::WriteRepository;
+    use crate::test::fixtures;
+
+    #[test]
+    fn test_reindex() {
+        let tmp = tempfile::tempdir().unwrap();
+        let signer = MockSigner::default();
+        let storage = fixtures::storage(tmp.path(), &signer).unwrap();
+        let index = Index::memory().unwrap();
+
+        assert!(index.is_empty().unwrap());
+        index.reindex(&storage).unwrap();
+
+        let mut inventory = storage.inventory().unwrap();
+        inventory.sort();
+        assert_eq!(index.projects().unwrap(), inventory);
+
+        for id in inventory {
+            let repo = storage.repository(id).unwrap();
+            let mut refs = repo.references(signer.public_key()).unwrap();
+            refs.remove(&SIGREFS_BRANCH.to_ref_string());
+
+            assert_eq!(index.remotes(&id).unwrap(), vec![*signer.public_key()]);
+            assert_eq!(index.refs(&id, signer.public_key()).unwrap(), refs);
+        }
+    }
+
+    #[test]
+    fn test_hook() {
+        let tmp = tempfile::tempdir().unwrap();
+        let signer = MockSigner::default();
+        let storage = fixtures::storage(tmp.path(), &signer).unwrap();
+        let index = Index::memory().unwrap();
+        let hooks = Hooks::default();
+        let storage = storage.with_hooks(hooks.clone());
+        let id = *storage.inventory().unwrap().first().unwrap();
+        let remote = *signer.public_key();
+        let master = git::qualified!("refs/heads/master");
+
+        index.reindex(&storage).unwrap();
+        hooks.register(index.clone());
+
+        // Create a new branch and sign it.
+        let repo = storage.repository(id).unwrap();
+        let head = index.get(&id, &remote, &master).unwrap().unwrap();
+        let dev = git::refs::storage::branch(&remote, &git::refname!("dev"));
+        repo.raw().reference(&dev, *head, false, "test").unwrap();
+        repo.sign_refs(&signer).unwrap();
+
+        assert_eq!(
+            index
+                .get(&id, &remote, &git::qualified!("refs/heads/dev"))
+                .unwrap(),
+            Some(head)
+        );
+
+        storage.remove(id).unwrap();
+        assert!(!index.projects().unwrap().contains(&id));
+    }
+}
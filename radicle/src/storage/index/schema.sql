@@ -0,0 +1,15 @@
+--
+-- Storage refs index SQL schema.
+--
+create table if not exists "refs" (
+  -- Project the reference belongs to.
+  "project"      text      not null,
+  -- Remote (namespace) the reference belongs to.
+  "remote"       text      not null,
+  -- Reference name, without the namespace, eg. `refs/heads/master`.
+  "refname"      text      not null,
+  -- Object the reference points to.
+  "oid"          text      not null,
+
+  primary key ("project", "remote", "refname")
+);
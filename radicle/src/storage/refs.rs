@@ -15,7 +15,7 @@ use crate::git;
 use crate::git::ext as git_ext;
 use crate::git::Oid;
 use crate::storage;
-use crate::storage::{ReadRepository, RemoteId, WriteRepository};
+use crate::storage::{ReadRepository, RemoteId};
 
 pub use crate::git::refs::storage::*;
 
@@ -271,11 +271,11 @@ impl SignedRefs<Verified> {
 
     /// Save the signed refs to disk.
     /// This creates a new commit on the signed refs branch, and updates the branch pointer.
-    pub fn save<S: WriteRepository>(
+    pub fn save(
         &self,
         // TODO: This should be part of the signed refs.
         remote: &RemoteId,
-        repo: &S,
+        repo: &storage::git::Repository,
     ) -> Result<Updated, Error> {
         let sigref = &SIGREFS_BRANCH;
         let parent = match repo.reference(remote, sigref) {
@@ -1,3 +1,5 @@
+pub mod cob;
+
 use std::collections::{BTreeMap, HashSet};
 use std::hash::Hash;
 use std::ops::RangeBounds;
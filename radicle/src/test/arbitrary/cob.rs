@@ -0,0 +1,459 @@
+//! Generators for collaborative object operations.
+//!
+//! Besides [`Arbitrary`] instances for individual actions, this module provides causally
+//! valid operation sequences, see [`Actions`], delivered in random orders, see [`Changes`].
+//! These can be used to check that applying operations converges to the same state no
+//! matter the delivery order, see [`converges`].
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+use std::{array, iter};
+
+use qcheck::{Arbitrary, TestResult};
+use radicle_crdt::clock::Lamport;
+use radicle_crdt::test::{assert_laws, WeightedGenerator};
+use radicle_crdt::Semilattice;
+
+use crate::cob::common::{Reaction, Tag, Timestamp};
+use crate::cob::issue::{self, CloseReason};
+use crate::cob::patch::{self, CodeLocation, MergeTarget, Verdict};
+use crate::cob::store::FromHistory;
+use crate::cob::thread;
+use crate::cob::{ActorId, Op, OpId};
+use crate::git;
+
+/// Actions for which causally valid operation sequences can be generated.
+pub trait Actions: Sized {
+    /// Generate a sequence of `size` operations by the given author. Operations only ever
+    /// refer to operations that precede them in the sequence.
+    fn ops(rng: fastrand::Rng, author: ActorId, size: usize) -> Vec<Op<Self>>;
+}
+
+/// A causally valid sequence of operations, delivered in `N` different orders.
+#[derive(Clone)]
+pub struct Changes<A, const N: usize> {
+    pub permutations: [Vec<Op<A>>; N],
+}
+
+impl<A: Debug, const N: usize> Debug for Changes<A, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, p) in self.permutations.iter().enumerate() {
+            writeln!(
+                f,
+                "{i}: {:#?}",
+                p.iter().map(|c| &c.action).collect::<Vec<_>>()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<A: Actions + Clone + 'static, const N: usize> Arbitrary for Changes<A, N> {
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        let author = ActorId::from([0; 32]);
+        let rng = fastrand::Rng::with_seed(u64::arbitrary(g));
+        let mut ops = A::ops(rng.clone(), author, g.size());
+        let mut permutations: [Vec<Op<A>>; N] = array::from_fn(|_| Vec::new());
+
+        for p in &mut permutations {
+            *p = ops.clone();
+            rng.shuffle(&mut ops);
+        }
+        Changes { permutations }
+    }
+}
+
+/// Check that applying every permutation of the given changes yields the same state, and
+/// that the resulting states satisfy the semilattice laws. Permutations that fail to apply
+/// are discarded.
+pub fn converges<T, const N: usize>(changes: Changes<T::Action, N>) -> TestResult
+where
+    T: FromHistory + Semilattice + Clone + PartialEq + Debug,
+{
+    let mut states = Vec::with_capacity(N);
+
+    for ops in changes.permutations {
+        let mut state = T::default();
+        if state.apply(ops).is_err() {
+            return TestResult::discard();
+        }
+        states.push(state);
+    }
+    for pair in states.windows(2) {
+        assert_eq!(pair[0], pair[1]);
+    }
+    for triple in states.windows(3) {
+        assert_laws(&triple[0], &triple[1], &triple[2]);
+    }
+    TestResult::passed()
+}
+
+fn string(rng: &fastrand::Rng, len: usize) -> String {
+    iter::repeat_with(|| rng.alphabetic()).take(len).collect()
+}
+
+fn oid(rng: &fastrand::Rng) -> git::Oid {
+    let bytes = iter::repeat_with(|| rng.u8(..))
+        .take(20)
+        .collect::<Vec<_>>();
+
+    git::Oid::try_from(bytes.as_slice()).unwrap()
+}
+
+fn close_reason(rng: &fastrand::Rng) -> CloseReason {
+    match rng.u8(..4) {
+        0 => CloseReason::Solved,
+        1 => CloseReason::WontFix,
+        2 => CloseReason::Duplicate {
+            of: oid(rng).into(),
+        },
+        _ => CloseReason::Other,
+    }
+}
+
+fn tag(rng: &fastrand::Rng) -> Tag {
+    Tag::new(rng.alphabetic()).unwrap()
+}
+
+fn op_id(g: &mut qcheck::Gen) -> OpId {
+    OpId::new(Lamport::from(u64::arbitrary(g) % 64), ActorId::arbitrary(g))
+}
+
+fn rng(g: &mut qcheck::Gen) -> fastrand::Rng {
+    fastrand::Rng::with_seed(u64::arbitrary(g))
+}
+
+impl Arbitrary for thread::Action {
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        let rng = rng(g);
+
+        match rng.u8(..6) {
+            0 => Self::Comment {
+                body: string(&rng, 16),
+                reply_to: bool::arbitrary(g).then(|| op_id(g)),
+            },
+            1 => Self::Edit {
+                id: op_id(g),
+                body: string(&rng, 16),
+            },
+            2 => Self::Redact { id: op_id(g) },
+            3 => Self::Reference {
+                typename: issue::TYPENAME.clone(),
+                object_id: oid(&rng).into(),
+            },
+            4 => Self::Attach {
+                to: op_id(g),
+                attachments: vec![thread::Attachment {
+                    name: string(&rng, 8),
+                    oid: oid(&rng),
+                }],
+            },
+            _ => Self::React {
+                to: op_id(g),
+                reaction: Reaction::new('✨').unwrap(),
+                active: bool::arbitrary(g),
+            },
+        }
+    }
+}
+
+impl Arbitrary for issue::Action {
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        let rng = rng(g);
+
+        match rng.u8(..5) {
+            0 => Self::Assign {
+                add: Vec::arbitrary(g),
+                remove: Vec::arbitrary(g),
+            },
+            1 => Self::Edit {
+                title: string(&rng, 8),
+            },
+            2 => Self::Lifecycle {
+                state: match rng.u8(..2) {
+                    0 => issue::State::Open,
+                    _ => issue::State::Closed {
+                        reason: close_reason(&rng),
+                    },
+                },
+            },
+            3 => Self::Tag {
+                add: iter::repeat_with(|| tag(&rng))
+                    .take(rng.usize(0..=3))
+                    .collect(),
+                remove: iter::repeat_with(|| tag(&rng))
+                    .take(rng.usize(0..=3))
+                    .collect(),
+            },
+            _ => Self::Thread {
+                action: thread::Action::arbitrary(g),
+            },
+        }
+    }
+}
+
+impl Arbitrary for patch::Action {
+    fn arbitrary(g: &mut qcheck::Gen) -> Self {
+        let rng = rng(g);
+
+        match rng.u8(..8) {
+            0 => Self::Edit {
+                title: string(&rng, 8),
+                description: string(&rng, 16),
+                target: MergeTarget::Delegates,
+            },
+            1 => Self::Tag {
+                add: iter::repeat_with(|| tag(&rng))
+                    .take(rng.usize(0..=3))
+                    .collect(),
+                remove: iter::repeat_with(|| tag(&rng))
+                    .take(rng.usize(0..=3))
+                    .collect(),
+            },
+            2 => Self::Revision {
+                base: oid(&rng),
+                oid: oid(&rng),
+            },
+            3 => Self::Redact { revision: op_id(g) },
+            4 => Self::Review {
+                revision: op_id(g),
+                comment: bool::arbitrary(g).then(|| string(&rng, 16)),
+                verdict: match rng.u8(..3) {
+                    0 => Some(Verdict::Accept),
+                    1 => Some(Verdict::Reject),
+                    _ => None,
+                },
+                inline: vec![],
+            },
+            5 => Self::Merge {
+                revision: op_id(g),
+                commit: oid(&rng),
+            },
+            6 => {
+                let start = rng.usize(1..100);
+
+                Self::CodeComment {
+                    revision: op_id(g),
+                    location: CodeLocation {
+                        path: string(&rng, 8).into(),
+                        blob: oid(&rng),
+                        commit: oid(&rng),
+                        lines: start..start + rng.usize(..10),
+                    },
+                    body: string(&rng, 16),
+                }
+            }
+            _ => Self::Thread {
+                revision: op_id(g),
+                action: thread::Action::arbitrary(g),
+            },
+        }
+    }
+}
+
+impl Actions for thread::Action {
+    fn ops(rng: fastrand::Rng, author: ActorId, size: usize) -> Vec<Op<Self>> {
+        let root = OpId::initial(author);
+        let gen = WeightedGenerator::<(Lamport, Self), (Lamport, BTreeSet<OpId>)>::new(rng.clone())
+            .variant(3, |(clock, comments), rng| {
+                comments.insert(OpId::new(clock.tick(), author));
+
+                Some((
+                    *clock,
+                    Self::Comment {
+                        body: string(&rng, 16),
+                        reply_to: Some(root),
+                    },
+                ))
+            })
+            .variant(2, |(clock, comments), rng| {
+                if comments.is_empty() {
+                    return None;
+                }
+                let id = *comments.iter().nth(rng.usize(..comments.len())).unwrap();
+
+                Some((
+                    *clock,
+                    Self::Edit {
+                        id,
+                        body: string(&rng, 16),
+                    },
+                ))
+            })
+            .variant(2, |(clock, comments), rng| {
+                if comments.is_empty() {
+                    return None;
+                }
+                let to = *comments.iter().nth(rng.usize(..comments.len())).unwrap();
+
+                Some((
+                    clock.tick(),
+                    Self::React {
+                        to,
+                        reaction: Reaction::new('✨').unwrap(),
+                        active: rng.bool(),
+                    },
+                ))
+            })
+            .variant(2, |(clock, comments), rng| {
+                if comments.is_empty() {
+                    return None;
+                }
+                let id = *comments.iter().nth(rng.usize(..comments.len())).unwrap();
+                comments.remove(&id);
+
+                Some((clock.tick(), Self::Redact { id }))
+            });
+
+        let mut ops = vec![Op::new(
+            Self::Comment {
+                body: String::default(),
+                reply_to: None,
+            },
+            author,
+            Timestamp::now(),
+            Lamport::initial(),
+        )];
+        for (clock, action) in gen.take(size) {
+            let timestamp = Timestamp::now() + rng.u64(..60);
+            ops.push(Op::new(action, author, timestamp, clock));
+        }
+        ops
+    }
+}
+
+impl Actions for issue::Action {
+    fn ops(rng: fastrand::Rng, author: ActorId, size: usize) -> Vec<Op<Self>> {
+        type State = (Lamport, Vec<ActorId>, Vec<Tag>);
+
+        let assignees = iter::repeat_with(|| ActorId::from([rng.u8(..); 32]))
+            .take(4)
+            .collect::<Vec<_>>();
+        let gen = WeightedGenerator::<(Lamport, Self), State>::new(rng.clone())
+            .variant(1, |(clock, _, _), rng| {
+                Some((
+                    clock.tick(),
+                    Self::Edit {
+                        title: string(&rng, 8),
+                    },
+                ))
+            })
+            .variant(1, |(clock, _, _), rng| {
+                let state = match rng.u8(..2) {
+                    0 => issue::State::Open,
+                    _ => issue::State::Closed {
+                        reason: close_reason(&rng),
+                    },
+                };
+                Some((clock.tick(), Self::Lifecycle { state }))
+            })
+            .variant(1, |(clock, assigned, _), rng| {
+                let add = assignees
+                    .iter()
+                    .filter(|_| rng.bool())
+                    .copied()
+                    .collect::<Vec<_>>();
+                let remove = assigned
+                    .iter()
+                    .take(rng.usize(0..=assigned.len()))
+                    .copied()
+                    .collect();
+                assigned.extend(add.iter().copied());
+
+                Some((clock.tick(), Self::Assign { add, remove }))
+            })
+            .variant(1, |(clock, _, tags), rng| {
+                let add = iter::repeat_with(|| tag(&rng))
+                    .take(rng.usize(0..=3))
+                    .collect::<Vec<_>>();
+                let remove = tags
+                    .iter()
+                    .take(rng.usize(0..=tags.len()))
+                    .cloned()
+                    .collect();
+                tags.extend(add.iter().cloned());
+
+                Some((clock.tick(), Self::Tag { add, remove }))
+            });
+
+        let mut ops = vec![Op::new(
+            Self::Thread {
+                action: thread::Action::Comment {
+                    body: String::default(),
+                    reply_to: None,
+                },
+            },
+            author,
+            Timestamp::now(),
+            Lamport::initial(),
+        )];
+        for (clock, action) in gen.take(size) {
+            let timestamp = Timestamp::now() + rng.u64(..60);
+            ops.push(Op::new(action, author, timestamp, clock));
+        }
+        ops
+    }
+}
+
+impl Actions for patch::Action {
+    fn ops(rng: fastrand::Rng, author: ActorId, size: usize) -> Vec<Op<Self>> {
+        type State = (Lamport, Vec<OpId>, Vec<Tag>);
+
+        let oids = iter::repeat_with(|| oid(&rng)).take(16).collect::<Vec<_>>();
+        let gen = WeightedGenerator::<(Lamport, Self), State>::new(rng.clone())
+            .variant(1, |(clock, _, _), rng| {
+                Some((
+                    clock.tick(),
+                    Self::Edit {
+                        title: string(&rng, 8),
+                        description: string(&rng, 16),
+                        target: MergeTarget::Delegates,
+                    },
+                ))
+            })
+            .variant(1, |(clock, revisions, _), rng| {
+                if revisions.is_empty() {
+                    return None;
+                }
+                let revision = revisions[rng.usize(..revisions.len())];
+                let commit = oids[rng.usize(..oids.len())];
+
+                Some((clock.tick(), Self::Merge { revision, commit }))
+            })
+            .variant(1, |(clock, revisions, _), rng| {
+                if revisions.is_empty() {
+                    return None;
+                }
+                let revision = revisions[rng.usize(..revisions.len())];
+
+                Some((clock.tick(), Self::Redact { revision }))
+            })
+            .variant(1, |(clock, _, tags), rng| {
+                let add = iter::repeat_with(|| tag(&rng))
+                    .take(rng.usize(0..=3))
+                    .collect::<Vec<_>>();
+                let remove = tags
+                    .iter()
+                    .take(rng.usize(0..=tags.len()))
+                    .cloned()
+                    .collect();
+                tags.extend(add.iter().cloned());
+
+                Some((clock.tick(), Self::Tag { add, remove }))
+            })
+            .variant(1, |(clock, revisions, _), rng| {
+                let oid = oids[rng.usize(..oids.len())];
+                let base = oids[rng.usize(..oids.len())];
+
+                if rng.bool() {
+                    revisions.push(OpId::new(clock.tick(), author));
+                }
+                Some((*clock, Self::Revision { base, oid }))
+            });
+
+        let timestamp = Timestamp::now() + rng.u64(..60);
+
+        gen.take(size)
+            .map(|(clock, action)| Op::new(action, author, timestamp, clock))
+            .collect()
+    }
+}
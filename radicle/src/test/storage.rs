@@ -1,41 +1,35 @@
 use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
 use git_ref_format as fmt;
 use radicle_git_ext as git_ext;
 
-use crate::crypto::{Signer, Verified};
+use crate::crypto::{Signer, Unverified, Verified};
 use crate::identity::doc::{Doc, Id};
 
 pub use crate::storage::*;
 
+/// In-memory storage backend, holding only identity documents.
 #[derive(Clone, Debug)]
 pub struct MockStorage {
-    pub path: PathBuf,
     pub inventory: HashMap<Id, Doc<Verified>>,
 }
 
 impl MockStorage {
     pub fn new(inventory: Vec<(Id, Doc<Verified>)>) -> Self {
         Self {
-            path: PathBuf::default(),
             inventory: inventory.into_iter().collect(),
         }
     }
 
     pub fn empty() -> Self {
         Self {
-            path: PathBuf::default(),
             inventory: HashMap::new(),
         }
     }
 }
 
 impl ReadStorage for MockStorage {
-    fn path(&self) -> &Path {
-        self.path.as_path()
-    }
-
     fn get(
         &self,
         _remote: &RemoteId,
@@ -47,87 +41,106 @@ impl ReadStorage for MockStorage {
     fn inventory(&self) -> Result<Inventory, Error> {
         Ok(self.inventory.keys().cloned().collect::<Vec<_>>())
     }
+
+    fn contains(&self, proj: &Id) -> Result<bool, Error> {
+        Ok(self.inventory.contains_key(proj))
+    }
 }
 
 impl WriteStorage for MockStorage {
     type Repository = MockRepository;
 
-    fn repository(&self, _proj: Id) -> Result<Self::Repository, Error> {
-        Ok(MockRepository {})
+    fn repository(&self, proj: Id) -> Result<Self::Repository, Error> {
+        Ok(MockRepository {
+            id: proj,
+            doc: self.inventory.get(&proj).cloned(),
+        })
     }
-}
 
-pub struct MockRepository {}
-
-impl ReadRepository for MockRepository {
-    fn is_empty(&self) -> Result<bool, git2::Error> {
-        Ok(true)
+    fn remove(&self, _proj: Id) -> Result<(), Error> {
+        Ok(())
     }
+}
 
-    fn head(&self) -> Result<(fmt::Qualified, Oid), ProjectError> {
-        todo!()
-    }
+/// Repository of a [`MockStorage`]. Only the identity document is available: the
+/// repository has no refs or objects.
+pub struct MockRepository {
+    id: Id,
+    doc: Option<Doc<Verified>>,
+}
 
-    fn canonical_head(&self) -> Result<(fmt::Qualified, Oid), ProjectError> {
-        todo!()
+impl MockRepository {
+    fn not_found() -> git_ext::Error {
+        git_ext::Error::Git(git2::Error::new(
+            git2::ErrorCode::NotFound,
+            git2::ErrorClass::Reference,
+            "mock repositories have no refs or objects",
+        ))
     }
+}
 
-    fn path(&self) -> &std::path::Path {
-        todo!()
+impl ReadRepository for MockRepository {
+    fn id(&self) -> Id {
+        self.id
     }
 
-    fn remote(&self, _remote: &RemoteId) -> Result<Remote<Verified>, refs::Error> {
-        todo!()
+    fn is_empty(&self) -> Result<bool, Error> {
+        Ok(self.doc.is_none())
     }
 
-    fn remotes(&self) -> Result<Remotes<Verified>, refs::Error> {
-        todo!()
+    fn blob_at(&self, _commit: Oid, _path: &Path) -> Result<Blob, git_ext::Error> {
+        Err(Self::not_found())
     }
 
-    fn commit(&self, _oid: Oid) -> Result<git2::Commit, git_ext::Error> {
-        todo!()
+    fn head(&self) -> Result<(fmt::Qualified, Oid), ProjectError> {
+        Err(Self::not_found().into())
     }
 
-    fn revwalk(&self, _head: Oid) -> Result<git2::Revwalk, git2::Error> {
-        todo!()
+    fn canonical_head(&self) -> Result<(fmt::Qualified, Oid), ProjectError> {
+        Err(Self::not_found().into())
     }
 
-    fn blob_at<'a>(
-        &'a self,
-        _oid: git_ext::Oid,
-        _path: &'a std::path::Path,
-    ) -> Result<git2::Blob<'a>, git_ext::Error> {
-        todo!()
+    fn commit_message(&self, _oid: Oid) -> Result<Vec<u8>, git_ext::Error> {
+        Err(Self::not_found())
     }
 
-    fn reference(
-        &self,
-        _remote: &RemoteId,
-        _reference: &git::Qualified,
-    ) -> Result<git2::Reference, git_ext::Error> {
-        todo!()
+    fn history(&self, _head: Oid) -> Result<Vec<Oid>, git_ext::Error> {
+        Err(Self::not_found())
     }
 
     fn reference_oid(
         &self,
         _remote: &RemoteId,
         _reference: &git::Qualified,
-    ) -> Result<git_ext::Oid, git_ext::Error> {
-        todo!()
+    ) -> Result<Oid, git_ext::Error> {
+        Err(Self::not_found())
     }
 
-    fn references(&self, _remote: &RemoteId) -> Result<crate::storage::refs::Refs, Error> {
-        todo!()
+    fn references(&self, _remote: &RemoteId) -> Result<refs::Refs, Error> {
+        Ok(refs::Refs::default())
     }
 
-    fn project(&self) -> Result<Doc<Verified>, Error> {
-        todo!()
+    fn remote(&self, _remote: &RemoteId) -> Result<Remote<Verified>, refs::Error> {
+        Err(Self::not_found().into())
     }
 
-    fn project_identity(
-        &self,
-    ) -> Result<(Oid, crate::identity::Doc<crate::crypto::Unverified>), git::ProjectError> {
-        todo!()
+    fn remotes(&self) -> Result<Remotes<Verified>, refs::Error> {
+        Ok(Remotes::default())
+    }
+
+    fn project(&self) -> Result<Doc<Verified>, ProjectError> {
+        self.doc.clone().ok_or_else(|| Self::not_found().into())
+    }
+
+    fn project_identity(&self) -> Result<(Oid, Doc<Unverified>), ProjectError> {
+        let (oid, bytes) = self.project()?.encode()?;
+        let doc = Doc::from_json(&bytes)?;
+
+        Ok((oid, doc))
+    }
+
+    fn size(&self) -> Result<Size, Error> {
+        Ok(Size::default())
     }
 }
 
@@ -140,18 +153,11 @@ impl WriteRepository for MockRepository {
         Ok(vec![])
     }
 
-    fn raw(&self) -> &git2::Repository {
-        todo!()
-    }
-
     fn set_head(&self) -> Result<Oid, ProjectError> {
-        todo!()
+        self.canonical_head().map(|(_, oid)| oid)
     }
 
-    fn sign_refs<G: Signer>(
-        &self,
-        _signer: &G,
-    ) -> Result<crate::storage::refs::SignedRefs<Verified>, Error> {
-        todo!()
+    fn sign_refs<G: Signer>(&self, signer: &G) -> Result<refs::SignedRefs<Verified>, Error> {
+        refs::Refs::default().signed(signer).map_err(Error::from)
     }
 }